@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// A paused clock makes `WAIT` and the other timed commands return at once
+fuzz_target!(|data: &[u8]| {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()
+        .unwrap()
+        .block_on(tronmcp::fuzzing::tcp_session(data));
+});
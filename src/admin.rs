@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Failed token checks allowed from one address per window before it is locked out
+const MAX_FAILURES: u32 = 5;
+const FAILURE_WINDOW: Duration = Duration::from_secs(60);
+
+/// What an admin token is allowed to do. Roles are ordered: each one can do
+/// everything the roles below it can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only access to admin views
+    Viewer,
+    /// May kick players and force-finish games
+    Operator,
+    /// May delete persisted data
+    Owner,
+}
+
+impl Role {
+    pub fn parse(s: &str) -> Option<Role> {
+        match s.to_lowercase().as_str() {
+            "viewer" => Some(Role::Viewer),
+            "operator" => Some(Role::Operator),
+            "owner" => Some(Role::Owner),
+            _ => None,
+        }
+    }
+}
+
+/// Why a request was not authorized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    Missing,
+    Invalid,
+    RateLimited,
+    Forbidden,
+}
+
+/// Admin tokens and their roles, plus brute-force protection
+#[derive(Debug, Default)]
+pub struct AdminAuth {
+    tokens: Vec<(String, Role)>,
+    failures: Mutex<HashMap<IpAddr, (u32, Instant)>>,
+    failed_attempts: AtomicU64,
+}
+
+impl AdminAuth {
+    pub fn new(tokens: Vec<(String, Role)>) -> Self {
+        AdminAuth {
+            tokens,
+            ..Default::default()
+        }
+    }
+
+    /// Read a tokens file: one `<role> <token>` pair per line, `#` starts a comment
+    pub fn load_tokens_file(path: &Path) -> Result<Vec<(String, Role)>, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut tokens = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (role, token) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("{}:{}: expected '<role> <token>'", path.display(), i + 1))?;
+            let role = Role::parse(role).ok_or_else(|| {
+                format!(
+                    "{}:{}: unknown role '{}' (viewer, operator or owner)",
+                    path.display(),
+                    i + 1,
+                    role
+                )
+            })?;
+            crate::validation::token("token", token.trim())
+                .map_err(|e| format!("{}:{}: {}", path.display(), i + 1, e))?;
+            tokens.push((token.trim().to_string(), role));
+        }
+        Ok(tokens)
+    }
+
+    /// Whether any token is configured; without one the admin routes are off
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// Role granted by a token, if any. Every configured token is compared so
+    /// timing does not reveal which one matched.
+    pub fn resolve(&self, token: &str) -> Option<Role> {
+        let mut role = None;
+        for (candidate, candidate_role) in &self.tokens {
+            if constant_time_eq(candidate.as_bytes(), token.as_bytes()) {
+                role = role.max(Some(*candidate_role));
+            }
+        }
+        role
+    }
+
+    /// Check a request's token against the minimum role a route needs
+    pub fn authorize(&self, ip: IpAddr, token: Option<&str>, min: Role) -> Result<Role, AuthError> {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        failures.retain(|_, (_, since)| now.duration_since(*since) < FAILURE_WINDOW);
+        if failures.get(&ip).is_some_and(|(count, _)| *count >= MAX_FAILURES) {
+            return Err(AuthError::RateLimited);
+        }
+
+        let token = token.ok_or(AuthError::Missing)?;
+        let Some(role) = self.resolve(token) else {
+            self.failed_attempts.fetch_add(1, Ordering::Relaxed);
+            crate::metrics::METRICS.admin_auth_failures.inc();
+            failures.entry(ip).or_insert((0, now)).0 += 1;
+            return Err(AuthError::Invalid);
+        };
+        if role < min {
+            return Err(AuthError::Forbidden);
+        }
+        Ok(role)
+    }
+
+    /// Total rejected tokens since startup
+    pub fn failed_attempts(&self) -> u64 {
+        self.failed_attempts.load(Ordering::Relaxed)
+    }
+}
+
+pub type SharedAdminAuth = Arc<AdminAuth>;
+
+/// Compare secrets in time that depends only on their length
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,782 @@
+use crate::game::{Cell, CrashCause, Direction, Game, GameEvent, GameEventKind, GameStatus, SteerAction};
+use crate::render;
+use crate::course::Course;
+use crate::rules::{Ruleset, WinCondition};
+
+/// Widest/tallest board rendered in a recap or for spectators before it is downsampled
+const MAX_BOARD_SIZE: usize = 40;
+/// Narrative lines kept in a recap
+const MAX_STORY_LINES: usize = 8;
+/// Recent events shown by `look` and `game_status`
+pub const FEED_LEN: usize = 5;
+/// Upper bound on the recap text, in characters
+pub const MAX_RECAP_LEN: usize = 4000;
+/// Largest open area `analyze` counts for a move
+pub const TERRITORY_CAP: usize = 500;
+/// Widest/tallest `minimap` before the grid is downsampled
+const MINIMAP_SIZE: usize = 40;
+
+/// Render the whole board under `title`, shrinking it so neither side exceeds
+/// `MAX_BOARD_SIZE`. Each output cell shows the most notable grid cell it covers.
+pub fn render_board(game: &Game, title: &str) -> Vec<String> {
+    let (scale, rows) = game.downsample(MAX_BOARD_SIZE, |x, y| render::ranked_glyph(game, x, y, None));
+    let mut lines = Vec::new();
+    if scale == 1 {
+        lines.push(format!("{} ({}x{}):", title, game.width, game.height));
+    } else {
+        lines.push(format!(
+            "{} ({}x{}, shown at 1/{} scale):",
+            title, game.width, game.height, scale
+        ));
+    }
+
+    lines.extend(rows);
+    lines.push(render::BOARD_LEGEND.to_string());
+    lines
+}
+
+/// What a spectator sees of a game: a summary line, the whole board and
+/// every player with their score so far
+pub fn spectator_view(game: &Game) -> Vec<String> {
+    let status = match game.status {
+        GameStatus::WaitingForPlayers => "waiting to start",
+        GameStatus::Running => "running",
+        GameStatus::Finished => "finished",
+    };
+    let mut lines = vec![format!(
+        "Spectating {} (Level {}), game {}: {}{}, tick {}, {}/{} alive.",
+        game.course_name,
+        game.course_level,
+        game.id,
+        status,
+        if game.realtime { " in real time" } else { "" },
+        game.tick,
+        game.players.iter().filter(|p| p.alive).count(),
+        game.players.len()
+    )];
+    lines.extend(render_board(game, "Board"));
+    lines.push("Players:".to_string());
+    for (idx, p) in game.players.iter().enumerate() {
+        lines.push(format!(
+            "  {} {}{}: {} at ({}, {}) heading {}, traveled {}, score so far {}",
+            render::head_symbol(idx),
+            p.name,
+            if p.bot.is_some() { " (bot)" } else { "" },
+            game.status_of(idx),
+            p.x,
+            p.y,
+            p.direction.name(),
+            p.distance_traveled,
+            p.distance_traveled + game.survival_ticks(idx)
+        ));
+    }
+    lines
+}
+
+/// One event as a sentence, or None when another event already tells it
+/// (the second half of a head-on collision, a checkpoint win's finish)
+pub fn describe_event(game: &Game, event: &GameEvent) -> Option<String> {
+    let name = |idx: usize| game.players[idx].name.as_str();
+    let line = match event.kind {
+        GameEventKind::Started => {
+            let names: Vec<&str> = game.players.iter().map(|p| p.name.as_str()).collect();
+            format!(
+                "{} launched on {} (Level {})",
+                join_names(&names),
+                game.course_name,
+                game.course_level
+            )
+        }
+        GameEventKind::Crashed { player, cause } => match cause {
+            CrashCause::Boundary => format!("{} ran off the edge of the grid", name(player)),
+            CrashCause::Wall => format!("{} crashed into a wall", name(player)),
+            CrashCause::Obstruction => format!("{} crashed into an obstruction", name(player)),
+            CrashCause::OwnTrail => format!("{} crashed into their own trail", name(player)),
+            CrashCause::Trail { owner, cut_off: true } => format!(
+                "{} cut off {}, who crashed into {}'s trail",
+                name(owner),
+                name(player),
+                name(owner)
+            ),
+            CrashCause::Trail { owner, cut_off: false } => {
+                format!("{} crashed into {}'s trail", name(player), name(owner))
+            }
+            CrashCause::HeadOn { other } if other < player => return None,
+            CrashCause::HeadOn { other } => {
+                format!("{} and {} collided head-on", name(player), name(other))
+            }
+            CrashCause::Timeout => format!("{} stopped steering and timed out", name(player)),
+            CrashCause::Kicked => format!("{} was removed by an operator", name(player)),
+            CrashCause::Forfeit => format!("{} left the game", name(player)),
+            CrashCause::ArenaShrink => format!("{} was caught by the closing arena wall", name(player)),
+        },
+        GameEventKind::SuddenDeath => "sudden death began; trails stopped fading".to_string(),
+        GameEventKind::PowerUp { player, kind } => format!("{} picked up a {}", name(player), kind.name()),
+        GameEventKind::Ghosted { player, owner } if owner == player => {
+            format!("{} ghosted through their own trail", name(player))
+        }
+        GameEventKind::Ghosted { player, owner } => {
+            format!("{} ghosted through {}'s trail", name(player), name(owner))
+        }
+        GameEventKind::ReachedCheckpoint { player } => {
+            format!("{} reached the checkpoint", name(player))
+        }
+        GameEventKind::Respawned { player, lives } => format!(
+            "{} respawned with {} li{} left",
+            name(player),
+            lives,
+            if lives == 1 { "fe" } else { "ves" }
+        ),
+        GameEventKind::TimeLimit => format!(
+            "the {}-tick time limit ran out",
+            game.rules.max_ticks.unwrap_or(event.tick)
+        ),
+        GameEventKind::ArenaShrank { width, height } => {
+            format!("the arena closed in to {}x{}", width, height)
+        }
+        GameEventKind::Finished { winner: Some(w) } if game.rules.win_condition == WinCondition::Territory => {
+            let claimed = game.cells_claimed();
+            match (game.team_of(w), game.tie_break) {
+                (Some(team), _) => {
+                    let held = (0..claimed.len()).filter(|&i| game.team_of(i) == Some(team)).map(|i| claimed[i]).sum();
+                    format!("{} held the most territory with {}", team_label(game, team), cells(held))
+                }
+                (None, Some(reason)) => format!(
+                    "{} held the most territory, {}, and won the tie on {}",
+                    name(w),
+                    cells(claimed[w]),
+                    reason.name()
+                ),
+                (None, None) => format!("{} held the most territory with {}", name(w), cells(claimed[w])),
+            }
+        }
+        GameEventKind::Finished { winner: Some(w) } if game.hit_time_limit() => {
+            let who = match game.winning_team {
+                Some(team) => format!("{} for Team {}", name(w), team),
+                None => name(w).to_string(),
+            };
+            match game.tie_break {
+                Some(reason) => format!("time ran out; {} won the tie-break ({})", who, reason.name()),
+                None => format!("time ran out; {} had traveled furthest and won", who),
+            }
+        }
+        GameEventKind::Finished { winner: Some(w) } => {
+            if game.events.iter().any(|e| {
+                matches!(e.kind, GameEventKind::ReachedCheckpoint { player } if player == w)
+            }) {
+                return None;
+            }
+            match (game.tie_break, game.winning_team) {
+                (Some(reason), Some(team)) => format!(
+                    "the last cycles crashed together; {} won the tie-break ({}) for Team {}",
+                    name(w),
+                    reason.name(),
+                    team
+                ),
+                (Some(reason), None) => format!(
+                    "the last cycles crashed together; {} won the tie-break ({})",
+                    name(w),
+                    reason.name()
+                ),
+                (None, Some(team)) => format!("Team {} was the last team standing", team),
+                (None, None) => format!("{} was the last cycle standing", name(w)),
+            }
+        }
+        GameEventKind::Finished { winner: None } if game.hit_time_limit() => {
+            "time ran out with the leaders tied; the game ended in a draw".to_string()
+        }
+        GameEventKind::Finished { winner: None } if game.aborted && game.finish_reason().is_some() => {
+            "the game was voided, since nobody had moved yet".to_string()
+        }
+        GameEventKind::Finished { winner: None } if game.aborted => {
+            "the players agreed to abort the game".to_string()
+        }
+        GameEventKind::Finished { winner: None } if game.territory_decided() => {
+            "the territory was tied; the game ended in a draw".to_string()
+        }
+        GameEventKind::Finished { winner: None } if game.players.iter().any(|p| p.alive) => {
+            "the game was ended early with no winner".to_string()
+        }
+        GameEventKind::Finished { winner: None } => {
+            "no cycle survived; the game ended in a draw".to_string()
+        }
+    };
+    Some(line)
+}
+
+/// The last `n` events as `tick N: ...` lines, oldest first
+pub fn recent_events(game: &Game, n: usize) -> Vec<String> {
+    let mut lines: Vec<String> = game
+        .events
+        .iter()
+        .rev()
+        .filter_map(|e| describe_event(game, e).map(|line| format!("tick {}: {}.", e.tick, line)))
+        .take(n)
+        .collect();
+    lines.reverse();
+    lines
+}
+
+/// How a player's run ended, if they crashed
+pub fn crash_description(game: &Game, idx: usize) -> Option<String> {
+    game.events
+        .iter()
+        .filter(|e| match e.kind {
+            // A head-on collision is told once, from either side
+            GameEventKind::Crashed { player, cause: CrashCause::HeadOn { other } } => player == idx || other == idx,
+            GameEventKind::Crashed { player, .. } => player == idx,
+            _ => false,
+        })
+        .find_map(|e| describe_event(game, e).map(|line| format!("{} at tick {}", line, e.tick)))
+}
+
+/// Chronological story of the game built from its event log
+pub fn narrative(game: &Game) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for event in &game.events {
+        if let Some(line) = describe_event(game, event) {
+            lines.push(format!("tick {}: {}.", event.tick, line));
+        }
+    }
+
+    // Keep the opening and the ending when the log runs long
+    if lines.len() > MAX_STORY_LINES {
+        let skipped = lines.len() - (MAX_STORY_LINES - 1);
+        let tail = lines.split_off(lines.len() - (MAX_STORY_LINES - 2));
+        lines.truncate(1);
+        lines.push(format!("... {} more events ...", skipped));
+        lines.extend(tail);
+    }
+
+    if let Some(longest) = game.players.iter().max_by_key(|p| p.distance_traveled)
+        && longest.distance_traveled > 0
+    {
+        lines.push(format!(
+            "Longest ride: {} with {} cells over {} ticks.",
+            longest.name, longest.distance_traveled, game.tick
+        ));
+    }
+    lines
+}
+
+/// Player indices from first to last place: the winner, then survivors, then
+/// crashed players from the last to crash to the first. Ties go to distance.
+pub fn placements(game: &Game) -> Vec<usize> {
+    let crash_tick = |idx: usize| {
+        game.events
+            .iter()
+            .find(|e| matches!(e.kind, GameEventKind::Crashed { player, .. } if player == idx))
+            .map(|e| e.tick)
+    };
+    let mut order: Vec<usize> = (0..game.players.len()).collect();
+    order.sort_by_key(|&i| {
+        let p = &game.players[i];
+        (
+            !game.won(i),
+            !p.alive,
+            std::cmp::Reverse(crash_tick(i)),
+            std::cmp::Reverse(p.distance_traveled),
+        )
+    });
+    order
+}
+
+/// Full game-over recap for the players: final board, story, placements and
+/// what the result means for their campaign. Capped at `MAX_RECAP_LEN`.
+pub fn recap(game: &Game, campaign: &[String]) -> String {
+    let mut lines = vec![format!(
+        "=== GAME OVER: {} (Level {}) ===",
+        game.course_name, game.course_level
+    )];
+    if !game.tags.is_empty() {
+        lines.push(format!("Tags: {}", game.tags.join(", ")));
+    }
+    let winner = |w: usize| match game.winning_team {
+        Some(team) => team_label(game, team),
+        None => game.players[w].name.clone(),
+    };
+    match game.winner {
+        Some(w) => match game.tie_break {
+            Some(reason) => lines.push(format!("Winner: {} (tie-break: {})", winner(w), reason.name())),
+            None => lines.push(format!("Winner: {}", winner(w))),
+        },
+        None if game.aborted && game.finish_reason().is_some() => lines.push("Result: ABORTED".to_string()),
+        None if game.aborted => lines.push("Result: ABORTED by agreement".to_string()),
+        None if game.hit_time_limit() => lines.push("Result: DRAW (time limit, leaders tied)".to_string()),
+        None if game.territory_decided() => lines.push("Result: DRAW (territory tied)".to_string()),
+        None if game.players.iter().any(|p| p.alive) => {
+            lines.push("Result: ended early, no winner".to_string())
+        }
+        None => lines.push("Result: DRAW (everyone crashed)".to_string()),
+    }
+    if let Some(reason) = game.finish_reason() {
+        lines.push(format!("Finish reason: {} (tick {})", reason, game.tick));
+    }
+
+    lines.push(String::new());
+    lines.extend(render_board(game, "Final board"));
+
+    lines.push(String::new());
+    lines.push("Story:".to_string());
+    lines.extend(narrative(game).into_iter().map(|l| format!("  {}", l)));
+
+    lines.push(String::new());
+    lines.push("Placements:".to_string());
+    for (place, idx) in placements(game).into_iter().enumerate() {
+        let p = &game.players[idx];
+        lines.push(format!(
+            "  {}. {} ({}{}) - score {}, distance {}{}",
+            place + 1,
+            p.name,
+            game.team_of(idx).map(|team| format!("Team {}, ", team)).unwrap_or_default(),
+            if p.alive { "survived" } else { "crashed" },
+            p.score,
+            p.distance_traveled,
+            match p.kills {
+                0 => String::new(),
+                1 => ", 1 kill".to_string(),
+                n => format!(", {} kills", n),
+            }
+        ));
+    }
+    let team_scores = game.team_scores();
+    if !team_scores.is_empty() {
+        let totals: Vec<String> = team_scores
+            .iter()
+            .enumerate()
+            .map(|(i, score)| format!("Team {} {}", i + 1, score))
+            .collect();
+        lines.push(format!("  Team scores: {}", totals.join(", ")));
+    }
+
+    if !campaign.is_empty() {
+        lines.push(String::new());
+        lines.push("Campaign:".to_string());
+        lines.extend(campaign.iter().map(|l| format!("  {}", l)));
+    }
+
+    let mut text = lines.join("\n");
+    if text.chars().count() > MAX_RECAP_LEN {
+        text = text.chars().take(MAX_RECAP_LEN - 16).collect();
+        text.push_str("\n...(truncated)");
+    }
+    text
+}
+
+/// What each steer runs into from a player's cycle, one line per action:
+/// the next cell, and how many free cells lie ahead before the first obstacle
+pub fn move_hints(game: &Game, idx: usize) -> Vec<String> {
+    let player = &game.players[idx];
+    [SteerAction::Left, SteerAction::Straight, SteerAction::Right]
+        .into_iter()
+        .map(|action| {
+            let heading = action.turn(player.direction);
+            let (free, obstacle) = ray(game, idx, heading);
+            let outcome = match (free, obstacle) {
+                (0, Some(obstacle)) => format!("CRASH into {}", obstacle),
+                (1, Some(obstacle)) => format!("empty, then {}", obstacle),
+                (free, Some(obstacle)) => format!("empty, {} free cells before {}", free, obstacle),
+                (free, None) => format!("empty, {} free cells and no obstacle in line", free),
+            };
+            format!("{} ({}): {}", action.name().to_lowercase(), heading.name(), outcome)
+        })
+        .collect()
+}
+
+/// What a player needs to know about a course before driving it: `heading`
+/// says how they relate to it, then its size, trails, blocked cells, mode
+/// and tick limit under `rules`, and its preview. `example` marks a
+/// generated layout that the game will make afresh, so the preview only
+/// shows what such a layout looks like.
+pub fn course_info(course: &Course, rules: &Ruleset, realtime: bool, heading: &str, example: bool) -> String {
+    let cells = course.width * course.height;
+    let blocked = course.walls.len() + course.obstructions.len();
+    let mut lines = vec![
+        heading.to_string(),
+        format!(
+            "Size: {}x{} cells, {}",
+            course.width,
+            course.height,
+            if rules.wrap_edges { "edges wrap around" } else { "walled in around the edge" }
+        ),
+        format!("Seats: up to {}", course.max_players),
+        match rules.max_trail_length {
+            0 => "Max trail length: none, trails never fade".to_string(),
+            len => format!("Max trail length: {} cells", len),
+        },
+        format!(
+            "Blocked: {}% of the grid ({} wall cells, {} obstructions)",
+            (blocked * 100).div_ceil(cells.max(1)),
+            course.walls.len(),
+            course.obstructions.len()
+        ),
+        format!(
+            "Mode: {}, win condition {}",
+            if realtime { "real-time" } else { "turn-based" },
+            rules.win_condition.name()
+        ),
+        match rules.max_ticks {
+            Some(max) => format!("Max ticks: {}", max),
+            None => "Max ticks: none".to_string(),
+        },
+    ];
+    let tags = course.tags_with(rules);
+    if !tags.is_empty() {
+        lines.push(format!("Tags: {}", tags.join(", ")));
+    }
+    if example {
+        lines.push("Layout: generated afresh for each game; the preview is one example".to_string());
+    } else if let Some(seed) = course.seed {
+        lines.push(format!("Layout seed: {}", seed));
+    }
+    let (scale, map) = course.render_ascii_at(crate::course::PREVIEW_SIZE);
+    lines.push(if scale == 1 {
+        "Preview, north up:".to_string()
+    } else {
+        format!("Preview, north up, at 1/{} scale:", scale)
+    });
+    lines.push(map);
+    lines.push("Legend: # = wall, X = obstruction, F = checkpoint, P = power-up spawn, 1-8 = where each seat starts, . = empty".to_string());
+    lines.join("\n")
+}
+
+/// The whole grid from a player's point of view, downsampled to at most
+/// `MINIMAP_SIZE` characters a side. Heads and the checkpoint stay visible
+/// over walls, walls over trails, trails over obstructions.
+pub fn minimap(game: &Game, idx: usize) -> String {
+    let player = &game.players[idx];
+    let (scale, rows) = game.downsample(MINIMAP_SIZE, |x, y| render::ranked_glyph(game, x, y, Some(idx)));
+    let mut lines = vec![if scale == 1 {
+        format!("Minimap of {} ({}x{}), north up:", game.course_name, game.width, game.height)
+    } else {
+        format!(
+            "Minimap of {} ({}x{}), north up, at 1/{} scale: each character covers up to {}x{} cells. Approximate; use look for exact cells.",
+            game.course_name, game.width, game.height, scale, scale, scale
+        )
+    }];
+    lines.extend(rows);
+    lines.push(format!(
+        "You (@) are at ({}, {}) heading {}.",
+        player.x,
+        player.y,
+        player.direction.name()
+    ));
+    lines.push(format!(
+        "Legend: @ = you, | = your trail, A-I = other players' heads, 1-9 = their trails, a-i = trails of crashed players, {}F = checkpoint, {}# = wall, X = obstruction, . = empty",
+        if game.teams.is_some() { "T = a teammate's head, + = teammates' trails, " } else { "" },
+        render::powerup_legend(game)
+    ));
+    lines.join("\n")
+}
+
+/// How much open area each steer keeps a player connected to, by flood
+/// fill from the cell it moves into. A move whose area is enclosed and under
+/// half the best move's is flagged as a dead pocket.
+pub fn territory(game: &Game, idx: usize) -> String {
+    let player = &game.players[idx];
+    if !player.alive {
+        return "You have crashed; there are no moves left to analyze.".to_string();
+    }
+    let moves: Vec<(SteerAction, Direction, Result<usize, String>)> =
+        [SteerAction::Left, SteerAction::Straight, SteerAction::Right]
+            .into_iter()
+            .map(|action| {
+                let heading = action.turn(player.direction);
+                let area = match ray(game, idx, heading) {
+                    (0, Some(obstacle)) => Err(obstacle),
+                    _ => {
+                        let (dx, dy) = heading.delta();
+                        Ok(game.open_area(player.x + dx, player.y + dy, TERRITORY_CAP))
+                    }
+                };
+                (action, heading, area)
+            })
+            .collect();
+    let best = moves.iter().filter_map(|(_, _, area)| area.as_ref().ok()).copied().max();
+
+    let mut lines = vec![format!(
+        "Open area after each move from ({}, {}) heading {} (empty cells still reachable, counted up to {}):",
+        player.x,
+        player.y,
+        player.direction.name(),
+        TERRITORY_CAP
+    )];
+    for (action, heading, area) in moves {
+        let outcome = match area {
+            Err(obstacle) => format!("CRASH into {}", obstacle),
+            Ok(area) if area >= TERRITORY_CAP => format!("{}+ cells", TERRITORY_CAP),
+            Ok(area) if best.is_some_and(|best| area * 2 < best) => {
+                format!("{}, DEAD POCKET: cut off from the bigger area", cells(area))
+            }
+            Ok(area) => format!("{}, enclosed", cells(area)),
+        };
+        lines.push(format!("  {} ({}): {}", action.name().to_lowercase(), heading.name(), outcome));
+    }
+    lines.join("\n")
+}
+
+/// Walk from a player's cycle in a straight line: the number of empty cells
+/// passed and what stops the walk. Wrapping edges are followed, for at most
+/// one lap.
+fn ray(game: &Game, idx: usize, heading: Direction) -> (usize, Option<String>) {
+    let player = &game.players[idx];
+    let (dx, dy) = heading.delta();
+    let (mut x, mut y) = (player.x, player.y);
+    let lap = game.width.max(game.height);
+    for free in 0..lap {
+        (x, y) = (x + dx, y + dy);
+        if game.rules.wrap_edges {
+            x = x.rem_euclid(game.width as i32);
+            y = y.rem_euclid(game.height as i32);
+        }
+        if x < 0 || y < 0 || x >= game.width as i32 || y >= game.height as i32 {
+            return (free, Some("the boundary wall".to_string()));
+        }
+        if let Some(head) = game
+            .players
+            .iter()
+            .enumerate()
+            .find(|&(i, p)| p.alive && (p.x, p.y) == (x, y) && !game.harmless_trail(idx, i))
+        {
+            return (free, Some(format!("{}'s head", head.1.name)));
+        }
+        let obstacle = match game.grid[y as usize][x as usize] {
+            Cell::Empty | Cell::PowerUp(_) => continue,
+            Cell::Trail(owner) if game.harmless_trail(idx, owner) => continue,
+            Cell::Wall => "a wall".to_string(),
+            Cell::Obstruction => "an obstruction".to_string(),
+            Cell::Trail(owner) if owner == idx => "your own trail".to_string(),
+            Cell::Trail(owner) => format!("{}'s trail", game.players[owner].name),
+        };
+        return (free, Some(obstacle));
+    }
+    (lap, None)
+}
+
+/// What the player's cycle would run into with its next step straight
+/// ahead, if anything
+pub fn blocked_ahead(game: &Game, idx: usize) -> Option<String> {
+    match ray(game, idx, game.players[idx].direction) {
+        (0, obstacle) => obstacle,
+        _ => None,
+    }
+}
+
+/// `Team 1 (Ann, Bo)`: a team and who rides for it
+pub fn team_label(game: &Game, team: usize) -> String {
+    let names: Vec<&str> = (0..game.players.len())
+        .filter(|&i| game.team_of(i) == Some(team))
+        .map(|i| game.players[i].name.as_str())
+        .collect();
+    format!("Team {} ({})", team, names.join(", "))
+}
+
+fn cells(n: usize) -> String {
+    if n == 1 { "1 cell".to_string() } else { format!("{} cells", n) }
+}
+
+fn join_names(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [one] => one.to_string(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    const ARENA: &str = "
+        ############
+        #1........2#
+        #..........#
+        #..........#
+        #..........#
+        #..........#
+        #..........#
+        ############";
+
+    /// Claude drives east and turns south across Gemini's path; Gemini,
+    /// coming west, rides into the fresh trail
+    fn scripted_game() -> Game {
+        let mut game = testutil::game(ARENA, &["Claude", "Gemini"]);
+        use SteerAction::{Right, Straight};
+        for (player, action) in [
+            (0, Straight),
+            (1, Straight),
+            (0, Straight),
+            (1, Straight),
+            (0, Right),
+            (1, Straight),
+            (0, Straight),
+            (1, Straight),
+            (1, Straight),
+            (1, Straight),
+            (1, Straight),
+        ] {
+            game.move_player(player, action);
+        }
+        game
+    }
+
+    const RAYS: &str = "
+        ##########
+        #........#
+        #.1...X..#
+        #........#
+        #........#
+        #........#
+        #2.......#
+        ##########";
+
+    #[test]
+    fn move_hints_walk_the_three_rays() {
+        let mut game = testutil::game(RAYS, &["Claude", "Gemini"]);
+        game.players[0].direction = Direction::Right;
+        game.players[1].direction = Direction::Right;
+        assert_eq!(
+            move_hints(&game, 0),
+            [
+                "left (NORTH): empty, then a wall",
+                "straight (EAST): empty, 3 free cells before an obstruction",
+                "right (SOUTH): empty, 4 free cells before a wall",
+            ]
+        );
+        assert_eq!(
+            move_hints(&game, 1),
+            [
+                "left (NORTH): empty, 5 free cells before a wall",
+                "straight (EAST): empty, 7 free cells before a wall",
+                "right (SOUTH): CRASH into a wall",
+            ]
+        );
+
+        game.move_player(0, SteerAction::Right);
+        game.move_player(1, SteerAction::Straight);
+        assert_eq!(
+            move_hints(&game, 0),
+            [
+                "left (EAST): empty, 6 free cells before a wall",
+                "straight (SOUTH): empty, 2 free cells before Gemini's head",
+                "right (WEST): empty, then a wall",
+            ]
+        );
+
+        game.move_player(0, SteerAction::Left);
+        game.move_player(1, SteerAction::Left);
+        assert_eq!(
+            move_hints(&game, 1),
+            [
+                "left (WEST): empty, then a wall",
+                "straight (NORTH): empty, then Claude's trail",
+                "right (EAST): empty, 6 free cells before a wall",
+            ]
+        );
+    }
+
+    /// Claude stands in the mouth of a four-cell pocket to the west
+    const POCKET: &str = "
+        ##########
+        #..#.....#
+        #..1.....#
+        ####.....#
+        #........#
+        #........#
+        #2.......#
+        ##########";
+
+    #[test]
+    fn territory_flags_the_dead_pocket() {
+        let mut game = testutil::game(POCKET, &["Claude", "Gemini"]);
+        game.players[0].direction = Direction::Up;
+        assert_eq!(game.open_area(2, 2, TERRITORY_CAP), 3);
+        assert_eq!(game.open_area(4, 2, TERRITORY_CAP), 37);
+        assert_eq!(game.open_area(4, 2, 10), 10);
+        assert_eq!(
+            territory(&game, 0),
+            "Open area after each move from (3, 2) heading NORTH (empty cells still reachable, counted up to 500):
+  left (WEST): 3 cells, DEAD POCKET: cut off from the bigger area
+  straight (NORTH): CRASH into a wall
+  right (EAST): 37 cells, enclosed"
+        );
+    }
+
+    #[test]
+    fn narrative_tells_the_scripted_game() {
+        let game = scripted_game();
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert_eq!(
+            narrative(&game),
+            vec![
+                "tick 0: Claude and Gemini launched on Test Course (Level 1).",
+                "tick 10: Claude cut off Gemini, who crashed into Claude's trail.",
+                "tick 10: Claude was the last cycle standing.",
+                "Longest ride: Gemini with 6 cells over 10 ticks.",
+            ]
+        );
+    }
+
+    #[test]
+    fn recap_of_the_scripted_game() {
+        let game = scripted_game();
+        let recap = recap(&game, &["Claude advances to Level 2.".to_string()]);
+        let expected = format!(
+            "\
+=== GAME OVER: Test Course (Level 1) ===
+Tags: small
+Winner: Claude
+
+Final board (12x8):
+############
+#111bbbbbbb#
+#..1.......#
+#..A.......#
+#..........#
+#..........#
+#..........#
+############
+{}
+
+Story:
+  tick 0: Claude and Gemini launched on Test Course (Level 1).
+  tick 10: Claude cut off Gemini, who crashed into Claude's trail.
+  tick 10: Claude was the last cycle standing.
+  Longest ride: Gemini with 6 cells over 10 ticks.
+
+Placements:
+  1. Claude (survived) - score {}, distance 4, 1 kill
+  2. Gemini (crashed) - score {}, distance 6
+
+Campaign:
+  Claude advances to Level 2.",
+            render::BOARD_LEGEND,
+            game.players()[0].score,
+            game.players()[1].score,
+        );
+        assert_eq!(recap, expected);
+    }
+
+    #[test]
+    fn long_games_keep_the_opening_and_the_ending() {
+        let mut game = testutil::game(ARENA, &["Claude", "Gemini"]);
+        for _ in 0..12 {
+            game.events.push(GameEvent { tick: 1, kind: GameEventKind::SuddenDeath });
+        }
+        let story = narrative(&game);
+        assert_eq!(story.len(), MAX_STORY_LINES);
+        assert!(story[0].contains("launched"));
+        assert_eq!(story[1], "... 6 more events ...");
+    }
+
+    #[test]
+    fn recap_is_capped() {
+        let game = scripted_game();
+        let campaign = vec!["x".repeat(200); 40];
+        let recap = recap(&game, &campaign);
+        assert!(recap.chars().count() <= MAX_RECAP_LEN);
+        assert!(recap.ends_with("...(truncated)"));
+    }
+}
@@ -0,0 +1,336 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::time::Duration;
+
+use crate::bot::Policy;
+use crate::course::Course;
+use crate::error::{ErrorCode, TronError};
+use crate::game::{Cell, Game, LookView, MIN_GRID_SIZE, Steer};
+use crate::protocol::{Player, Request};
+use crate::tcp::TcpClient;
+use crate::validation;
+
+/// How long `tronmcp bot` keeps dialing a server that went away
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+const RECONNECT_FIRST_DELAY: Duration = Duration::from_millis(250);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(2);
+/// Pause before joining again after a retryable refusal (name in use, cooldown)
+const JOIN_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Longest `WAIT` while queued
+const WAIT_SECS: u64 = 30;
+
+/// Who `tronmcp bot` plays as, and how
+pub struct Autoplay {
+    /// Game server address, `host:port`
+    pub server: String,
+    pub name: String,
+    pub policy: Policy,
+    /// Pause between moves
+    pub delay: Duration,
+    /// Join again after each game instead of stopping
+    pub rejoin: bool,
+}
+
+/// `tronmcp bot`: join a game over TCP and play it with a built-in policy,
+/// like any other network player
+pub async fn run_bot(bot: Autoplay) -> Result<(), Box<dyn std::error::Error>> {
+    let mut link = Link {
+        client: connect(&bot.server).await?,
+        server: bot.server.clone(),
+        name: bot.name.clone(),
+        session: None,
+        resume: None,
+    };
+    let mut rng = StdRng::from_entropy();
+    println!("{} connected to {}, playing {}", bot.name, bot.server, bot.policy.name());
+    loop {
+        link.join().await?;
+        play(&mut link, &bot, &mut rng).await?;
+        if !bot.rejoin {
+            return Ok(());
+        }
+    }
+}
+
+/// The connection and who it plays for, kept across reconnects
+struct Link {
+    client: TcpClient,
+    server: String,
+    name: String,
+    /// Session token from the join reply; stands for the player in `LOOK`
+    /// and `STEER`
+    session: Option<String>,
+    /// Token from `TOKEN` that takes the player back after a reconnect
+    resume: Option<String>,
+}
+
+impl Link {
+    /// Send a command, dialing again and taking the player back first when
+    /// the connection is lost. `request` is built again after a reconnect,
+    /// since the session token changes.
+    async fn command(&mut self, request: impl Fn(Player) -> Request) -> Result<Result<String, TronError>, String> {
+        loop {
+            let player = Player {
+                token: self.session.clone(),
+                name: if self.session.is_some() { None } else { Some(self.name.clone()) },
+            };
+            let line = request(player).to_text().map_err(|e| e.message)?;
+            match self.client.send(&line).await {
+                Ok(reply) => return Ok(reply.map_err(|e| {
+                    TronError::from_reply(&e).unwrap_or_else(|| TronError::new(ErrorCode::Internal, e))
+                })),
+                Err(e) => {
+                    println!("{}; reconnecting", e);
+                    self.reconnect().await?;
+                }
+            }
+        }
+    }
+
+    /// Dial again and `RESUME` the player; when the server no longer knows
+    /// them, the next command finds out and the game counts as over
+    async fn reconnect(&mut self) -> Result<(), String> {
+        self.client = connect(&self.server).await?;
+        let Some(token) = self.resume.clone() else {
+            return Ok(());
+        };
+        let resume = Request::Resume {
+            name: self.name.clone(),
+            token,
+        };
+        let resumed = self
+            .client
+            .send(&resume.to_text().map_err(|e| e.message)?)
+            .await?
+            .ok()
+            .and_then(|reply| Some(reply.rsplit_once("\nSESSION ")?.1.trim().to_string()));
+        if resumed.is_none() {
+            self.resume = None;
+        }
+        self.session = resumed.or(self.session.take());
+        Ok(())
+    }
+
+    /// `JOIN` the public queue, waiting out a name still in use or a crash
+    /// cooldown
+    async fn join(&mut self) -> Result<(), String> {
+        self.session = None;
+        self.resume = None;
+        let name = self.name.clone();
+        let reply = loop {
+            match self.command(|_| Request::Join { name: name.clone() }).await? {
+                Ok(reply) => break reply,
+                Err(e) if e.code.retryable() => {
+                    println!("{}; trying again in {}s", e.message, JOIN_RETRY_DELAY.as_secs());
+                    tokio::time::sleep(JOIN_RETRY_DELAY).await;
+                }
+                Err(e) => return Err(format!("{} could not join: {}", name, e)),
+            }
+        };
+        let (reply, session) = match reply.rsplit_once("\nSESSION ") {
+            Some((reply, token)) => (reply.to_string(), Some(token.trim().to_string())),
+            None => (reply, None),
+        };
+        println!("{}", reply);
+        self.session = session;
+        self.resume = self
+            .command(|_| Request::Token)
+            .await?
+            .ok()
+            .and_then(|reply| reply.strip_prefix("TOKEN ").map(str::to_string));
+        Ok(())
+    }
+}
+
+/// Dial `server`, backing off while it is down, for up to `RECONNECT_TIMEOUT`
+async fn connect(server: &str) -> Result<TcpClient, String> {
+    let deadline = tokio::time::Instant::now() + RECONNECT_TIMEOUT;
+    let mut delay = RECONNECT_FIRST_DELAY;
+    loop {
+        match TcpClient::connect(server).await {
+            Ok(client) => return Ok(client),
+            Err(e) if tokio::time::Instant::now() + delay >= deadline => return Err(e),
+            Err(_) => {
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+            }
+        }
+    }
+}
+
+/// Play the joined game to its end: wait to be matched, then look and steer
+/// every `delay` while alive, and print the result
+async fn play(link: &mut Link, bot: &Autoplay, rng: &mut StdRng) -> Result<(), String> {
+    loop {
+        let look = link
+            .command(|player| Request::Look {
+                player,
+                format: Some("json".to_string()),
+                orientation: None,
+                hints: None,
+            })
+            .await?;
+        match look {
+            Ok(json) => {
+                let view: LookView =
+                    serde_json::from_str(&json).map_err(|e| format!("the server's look is not JSON: {}", e))?;
+                if view.alive {
+                    let direction = bot.policy.choose(&window(&view)?, 0, rng).name().to_lowercase();
+                    // A steer can lose the race with the game ending; the
+                    // next look notices
+                    let _ = link
+                        .command(|player| Request::Steer {
+                            player,
+                            direction: direction.clone(),
+                            count: None,
+                        })
+                        .await?;
+                }
+                tokio::time::sleep(bot.delay).await;
+            }
+            Err(e) if e.code == ErrorCode::NotInGame => {
+                let _ = link
+                    .command(|player| Request::Wait {
+                        player,
+                        timeout_secs: WAIT_SECS,
+                    })
+                    .await?;
+            }
+            Err(e) if matches!(e.code, ErrorCode::RateLimited | ErrorCode::Internal) => {
+                tokio::time::sleep(bot.delay).await;
+            }
+            Err(e) if matches!(e.code, ErrorCode::GameNotFound | ErrorCode::GameNotRunning) => break,
+            Err(e) if matches!(e.code, ErrorCode::NotJoined | ErrorCode::NotQueued) => {
+                println!("Lost track of the game: {}", e.message);
+                return Ok(());
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    match link.command(|player| Request::Status { player }).await? {
+        Ok(status) => println!("{}", status),
+        Err(e) => println!("The game is over, but its result is unavailable: {}", e.message),
+    }
+    Ok(())
+}
+
+/// A small game holding just what `view` shows, ringed by walls, with the
+/// viewer (player 0) and the living cycles in view seated where they are.
+/// Trails all belong to the viewer, as policies only ask whether a cell is
+/// free.
+fn window(view: &LookView) -> Result<Game, String> {
+    let r = view.view_radius as i32;
+    let size = (view.view_radius * 2 + 3).max(MIN_GRID_SIZE);
+    let heading = |name: &str| match validation::direction("heading", name) {
+        Ok(Steer::Toward(dir)) => Ok(dir),
+        _ => Err(format!("the look has an unknown heading '{}'", name)),
+    };
+    let mut seats = vec![(view.x, view.y, heading(&view.heading)?)];
+    for p in view.players.iter().filter(|p| p.alive && p.in_view) {
+        let (dx, dy) = (p.x - view.x, p.y - view.y);
+        // Offsets across a wrapped edge do not fit the window
+        if dx.abs() <= r && dy.abs() <= r {
+            seats.push((p.x, p.y, heading(&p.heading)?));
+        }
+    }
+    let mut course = Course {
+        name: view.name.clone(),
+        level: 0,
+        width: size,
+        height: size,
+        max_trail_length: 0,
+        max_players: seats.len(),
+        obstructions: Vec::new(),
+        walls: Vec::new(),
+        checkpoint: None,
+        race: false,
+        powerups: Vec::new(),
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: None,
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: Vec::new(),
+        spawns: seats
+            .iter()
+            .map(|&(x, y, dir)| ((x - view.x + r + 1) as usize, (y - view.y + r + 1) as usize, dir))
+            .collect(),
+        seed: None,
+    };
+    let mut trails = Vec::new();
+    for (row, cells) in view.cells.iter().enumerate() {
+        for (col, glyph) in cells.iter().enumerate() {
+            let at = (col + 1, row + 1);
+            match glyph.as_str() {
+                "." | "F" | "*" | "~" | "o" => {}
+                "#" => course.walls.push(at),
+                "X" => course.obstructions.push(at),
+                _ => trails.push(at),
+            }
+        }
+    }
+    let mut game = Game::new(&course, course.default_rules())?;
+    for (i, &(x, y, direction)) in course.spawns.iter().enumerate() {
+        let idx = game
+            .add_player(format!("{}{}", view.name, i))
+            .ok_or("the view window has no seat for a cycle in view")?;
+        let player = &mut game.players[idx];
+        (player.x, player.y, player.direction) = (x as i32, y as i32, direction);
+    }
+    for (x, y) in trails {
+        game.grid[y][x] = Cell::Trail(0);
+    }
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::LeaderboardSort;
+    use crate::tcp::{CommandListener, TcpIdentity, TcpSettings};
+    use crate::testutil;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn two_bots_play_a_game_to_a_winner() {
+        let (rooms, _dir) = testutil::rooms();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+        let settings = TcpSettings {
+            identity: TcpIdentity::Tokens,
+            rate_limit: crate::ratelimit::RateLimit::Off,
+        };
+        let ct = CancellationToken::new();
+        let serving = crate::tcp::run_tcp_server(CommandListener::Tcp(listener), rooms.clone(), settings, ct.clone());
+
+        let bot = |name: &str| {
+            run_bot(Autoplay {
+                server: server.clone(),
+                name: name.to_string(),
+                policy: Policy::Greedy,
+                delay: Duration::ZERO,
+                rejoin: false,
+            })
+        };
+        let playing = async {
+            let played = tokio::join!(bot("Rinzler"), bot("Tron"));
+            ct.cancel();
+            played
+        };
+        let (served, (rinzler, tron)) = tokio::join!(serving, playing);
+        served.unwrap();
+        rinzler.unwrap();
+        tron.unwrap();
+
+        let mgr = rooms.default_room().lock().await;
+        let finished = mgr.get_finished_games();
+        assert_eq!(finished.len(), 1);
+        assert!(finished[0].winner.is_some());
+        let leaderboard = mgr.get_leaderboard(LeaderboardSort::Points, 0);
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!(leaderboard.iter().map(|e| e.wins).sum::<u32>(), 1);
+        assert!(leaderboard.iter().all(|e| e.games_played == 1));
+    }
+}
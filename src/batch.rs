@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bot::Difficulty;
+use crate::error::{ErrorCode, TronError};
+use crate::rules::RulesOverride;
+use crate::validation;
+
+/// Most steps accepted in one batch
+pub const MAX_STEPS: usize = 50;
+
+/// `POST /api/admin/batch` body
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    pub steps: Vec<BatchOp>,
+}
+
+/// One admin operation in a batch. Steps that act on a lobby name it by code
+/// or by the `alias` an earlier `create_lobby` step gave it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BatchOp {
+    CreateLobby {
+        alias: Option<String>,
+        level: Option<u32>,
+        rules: Option<RulesOverride>,
+        #[serde(default)]
+        public: bool,
+        prefer_tags: Option<Vec<String>>,
+        avoid_tags: Option<Vec<String>>,
+    },
+    /// Seat a server bot in the lobby's next game
+    AddBot { lobby: String, difficulty: Difficulty },
+    /// Overlay more rule changes on the lobby
+    SetRules { lobby: String, rules: RulesOverride },
+    /// Make the lobby's games the ones the web UI shows first
+    Feature { lobby: String },
+    /// Seconds each player gets per move in the lobby's games
+    SetSpeed { lobby: String, move_timeout_secs: u64 },
+}
+
+impl BatchOp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatchOp::CreateLobby { .. } => "create_lobby",
+            BatchOp::AddBot { .. } => "add_bot",
+            BatchOp::SetRules { .. } => "set_rules",
+            BatchOp::Feature { .. } => "feature",
+            BatchOp::SetSpeed { .. } => "set_speed",
+        }
+    }
+
+    /// Check the step's own fields, before anything in the batch is applied
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            BatchOp::CreateLobby {
+                alias,
+                rules,
+                prefer_tags,
+                avoid_tags,
+                ..
+            } => {
+                if let Some(alias) = alias {
+                    validation::name("alias", alias)?;
+                }
+                if let Some(rules) = rules {
+                    rules.validate()?;
+                }
+                validation::tag_preferences(prefer_tags.as_deref(), avoid_tags.as_deref())?;
+                Ok(())
+            }
+            BatchOp::AddBot { .. } | BatchOp::Feature { .. } => Ok(()),
+            BatchOp::SetRules { rules, .. } => rules.validate(),
+            BatchOp::SetSpeed {
+                move_timeout_secs, ..
+            } => speed_rules(*move_timeout_secs).validate(),
+        }
+    }
+}
+
+/// The rule change a `set_speed` step stands for
+pub fn speed_rules(move_timeout_secs: u64) -> RulesOverride {
+    RulesOverride {
+        move_timeout_secs: Some(move_timeout_secs),
+        ..Default::default()
+    }
+}
+
+/// Lobby code a step refers to: an alias from an earlier step, or a code
+pub fn resolve_lobby(lobby: &str, aliases: &HashMap<String, String>) -> Result<String, String> {
+    match aliases.get(lobby.trim()) {
+        Some(code) => Ok(code.clone()),
+        None => validation::lobby_code("lobby", lobby),
+    }
+}
+
+/// Results of an applied batch, one line per step
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    pub results: Vec<String>,
+    /// Lobby codes created by the batch, by alias
+    pub lobbies: HashMap<String, String>,
+}
+
+/// Why a batch was rejected; none of its steps took effect
+#[derive(Debug, Serialize)]
+pub struct BatchFailure {
+    /// Index of the failing step in `steps`
+    pub step: usize,
+    pub op: &'static str,
+    pub code: ErrorCode,
+    pub error: String,
+}
+
+impl BatchFailure {
+    pub fn new(step: usize, op: &'static str, error: impl Into<TronError>) -> Self {
+        let error = error.into();
+        BatchFailure {
+            step,
+            op,
+            code: error.code,
+            error: error.message,
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::game::{self, Direction, Game, GameStatus, Player, SteerAction};
+
+/// Largest area counted by the bot's flood fill
+const AREA_CAP: usize = 300;
+/// Practice results the adaptive controller looks back over
+const ADAPT_WINDOW: usize = 10;
+/// How far one game can move the adaptive skill
+const ADAPT_STEP: f64 = 0.1;
+
+const ACTIONS: [SteerAction; 3] = [SteerAction::Straight, SteerAction::Left, SteerAction::Right];
+
+/// Practice opponent strength
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    /// Tracks the player's practice results, aiming for a 50% win rate
+    Adaptive,
+}
+
+impl Difficulty {
+    pub fn parse(s: &str) -> Option<Difficulty> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            "adaptive" => Some(Difficulty::Adaptive),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Adaptive => "adaptive",
+        }
+    }
+}
+
+/// A way of playing for `tronmcp simulate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Any move that does not crash on the spot, picked at random
+    Random,
+    /// The move that keeps the most open area, by flood fill
+    Greedy,
+    /// The practice opponent of a fixed difficulty
+    Practice(Difficulty),
+}
+
+impl Policy {
+    pub fn parse(s: &str) -> Result<Policy, String> {
+        match s.trim().to_lowercase().as_str() {
+            "random" => Ok(Policy::Random),
+            "greedy" => Ok(Policy::Greedy),
+            other => match Difficulty::parse(other) {
+                Some(Difficulty::Adaptive) | None => {
+                    Err("expected 'random', 'greedy', 'easy', 'medium' or 'hard'".to_string())
+                }
+                Some(difficulty) => Ok(Policy::Practice(difficulty)),
+            },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Policy::Random => "random",
+            Policy::Greedy => "greedy",
+            Policy::Practice(difficulty) => difficulty.name(),
+        }
+    }
+
+    /// Pick a steering action for the player at `idx`
+    pub fn choose(self, game: &Game, idx: usize, rng: &mut impl Rng) -> SteerAction {
+        let board = Board::from_game(game);
+        let me = cycle(&game.players[idx]);
+        match self {
+            Policy::Random => {
+                let safe: Vec<SteerAction> = ACTIONS
+                    .into_iter()
+                    .filter(|a| board.step(me, *a).is_some())
+                    .collect();
+                if safe.is_empty() {
+                    SteerAction::Straight
+                } else {
+                    safe[rng.gen_range(0..safe.len())]
+                }
+            }
+            Policy::Greedy => {
+                let mut board = board;
+                let mut best = (-1, SteerAction::Straight);
+                for action in ACTIONS {
+                    let Some((next, i)) = board.step(me, action) else {
+                        continue;
+                    };
+                    board.blocked[i] = true;
+                    let area = board.area(next);
+                    board.blocked[i] = false;
+                    if area > best.0 {
+                        best = (area, action);
+                    }
+                }
+                best.1
+            }
+            Policy::Practice(difficulty) => {
+                choose_action_with(game, idx, BotParams::for_difficulty(difficulty, 0.5), rng)
+            }
+        }
+    }
+}
+
+/// How a bot plays: search depth and how often it picks a random move instead
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BotParams {
+    pub difficulty: Difficulty,
+    pub depth: u32,
+    pub mistake_rate: f64,
+}
+
+impl BotParams {
+    /// Map a skill in 0.0..=1.0 onto search depth 1-4 and a 25%-0% mistake rate
+    pub fn from_skill(difficulty: Difficulty, skill: f64) -> Self {
+        let skill = skill.clamp(0.0, 1.0);
+        BotParams {
+            difficulty,
+            depth: 1 + (skill * 3.0).round() as u32,
+            mistake_rate: 0.25 * (1.0 - skill),
+        }
+    }
+
+    /// Parameters for a fixed tier; adaptive bots use the player's own skill
+    pub fn for_difficulty(difficulty: Difficulty, adaptive_skill: f64) -> Self {
+        let skill = match difficulty {
+            Difficulty::Easy => 0.0,
+            Difficulty::Medium => 0.5,
+            Difficulty::Hard => 1.0,
+            Difficulty::Adaptive => adaptive_skill,
+        };
+        BotParams::from_skill(difficulty, skill)
+    }
+}
+
+/// Feedback loop over a player's recent results against adaptive bots:
+/// winning a lot raises the bot's skill, losing a lot lowers it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveDifficulty {
+    pub skill: f64,
+    /// Most recent last; true when the player won
+    pub recent: VecDeque<bool>,
+}
+
+impl Default for AdaptiveDifficulty {
+    fn default() -> Self {
+        AdaptiveDifficulty {
+            skill: 0.5,
+            recent: VecDeque::new(),
+        }
+    }
+}
+
+impl AdaptiveDifficulty {
+    /// Record a practice result and nudge the skill toward a 50% player win rate
+    pub fn record(&mut self, player_won: bool) {
+        self.recent.push_back(player_won);
+        while self.recent.len() > ADAPT_WINDOW {
+            self.recent.pop_front();
+        }
+        let wins = self.recent.iter().filter(|w| **w).count() as f64;
+        let rate = wins / self.recent.len() as f64;
+        self.skill = (self.skill + ADAPT_STEP * (2.0 * rate - 1.0)).clamp(0.0, 1.0);
+    }
+}
+
+/// Move every living bot in the game one step
+pub fn play_turn(game: &mut Game) {
+    for idx in 0..game.players.len() {
+        if game.status != GameStatus::Running {
+            return;
+        }
+        let Some(params) = game.players[idx].bot else {
+            continue;
+        };
+        if !game.players[idx].alive {
+            continue;
+        }
+        let action = choose_action(game, idx, params);
+        game.move_player(idx, action);
+    }
+}
+
+/// Real-time games: choose every living bot's action for the next tick
+pub fn queue_turn(game: &mut Game) {
+    for idx in 0..game.players.len() {
+        let Some(params) = game.players[idx].bot else {
+            continue;
+        };
+        if game.players[idx].alive {
+            game.players[idx].pending_action = Some(choose_action(game, idx, params));
+        }
+    }
+}
+
+/// Pick a steering action for the bot at `idx`
+pub fn choose_action(game: &Game, idx: usize, params: BotParams) -> SteerAction {
+    choose_action_with(game, idx, params, &mut rand::thread_rng())
+}
+
+/// `choose_action`, with its mistakes drawn from `rng`
+fn choose_action_with(game: &Game, idx: usize, params: BotParams, rng: &mut impl Rng) -> SteerAction {
+    let mut board = Board::from_game(game);
+    let me = cycle(&game.players[idx]);
+
+    // A "mistake" is a random move, but never into an occupied cell while an
+    // empty one is available
+    if rng.gen_bool(params.mistake_rate.clamp(0.0, 1.0)) {
+        let safe: Vec<SteerAction> = ACTIONS
+            .into_iter()
+            .filter(|a| board.step(me, *a).is_some())
+            .collect();
+        if !safe.is_empty() {
+            return safe[rng.gen_range(0..safe.len())];
+        }
+    }
+
+    let opponent = game
+        .players
+        .iter()
+        .enumerate()
+        .filter(|(i, p)| *i != idx && p.alive)
+        .min_by_key(|(_, p)| (p.x - me.x).abs() + (p.y - me.y).abs())
+        .map(|(_, p)| cycle(p));
+
+    let mut best = (i32::MIN, SteerAction::Straight);
+    for action in ACTIONS {
+        let score = board.search_move(me, action, opponent, params.depth.max(1));
+        if score > best.0 {
+            best = (score, action);
+        }
+    }
+    best.1
+}
+
+#[derive(Clone, Copy)]
+struct Cycle {
+    x: i32,
+    y: i32,
+    dir: Direction,
+}
+
+fn cycle(player: &Player) -> Cycle {
+    Cycle {
+        x: player.x,
+        y: player.y,
+        dir: player.direction,
+    }
+}
+
+/// Occupancy snapshot of a game grid for lookahead
+struct Board {
+    width: i32,
+    height: i32,
+    wrap: bool,
+    blocked: Vec<bool>,
+}
+
+const WIN: i32 = 10_000;
+
+impl Board {
+    fn from_game(game: &Game) -> Self {
+        Board {
+            width: game.width as i32,
+            height: game.height as i32,
+            wrap: game.rules.wrap_edges,
+            blocked: game
+                .grid
+                .iter()
+                .flatten()
+                .map(|c| !c.is_open())
+                .collect(),
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (x, y) = if self.wrap {
+            (x.rem_euclid(self.width), y.rem_euclid(self.height))
+        } else {
+            (x, y)
+        };
+        (x >= 0 && y >= 0 && x < self.width && y < self.height)
+            .then(|| (y * self.width + x) as usize)
+    }
+
+    /// Where a cycle ends up after an action, or None if it crashes
+    fn step(&self, cycle: Cycle, action: SteerAction) -> Option<(Cycle, usize)> {
+        let dir = action.turn(cycle.dir);
+        let (dx, dy) = dir.delta();
+        let i = self.index(cycle.x + dx, cycle.y + dy)?;
+        if self.blocked[i] {
+            return None;
+        }
+        let x = (i as i32) % self.width;
+        let y = (i as i32) / self.width;
+        Some((Cycle { x, y, dir }, i))
+    }
+
+    /// Empty cells reachable from a cycle's head, up to `AREA_CAP`
+    fn area(&self, cycle: Cycle) -> i32 {
+        game::flood_fill(
+            self.width as usize,
+            self.height as usize,
+            self.wrap,
+            &self.blocked,
+            (cycle.x, cycle.y),
+            AREA_CAP,
+        ) as i32
+    }
+
+    /// Minimax value of `me` taking `action`, with the opponent replying to minimize it
+    fn search_move(&mut self, me: Cycle, action: SteerAction, opponent: Option<Cycle>, depth: u32) -> i32 {
+        let Some((me_next, mi)) = self.step(me, action) else {
+            return -WIN;
+        };
+        let Some(opp) = opponent else {
+            // Nobody to play against: just keep as much room as possible
+            self.blocked[mi] = true;
+            let score = if depth <= 1 {
+                self.area(me_next)
+            } else {
+                ACTIONS
+                    .iter()
+                    .map(|a| self.search_move(me_next, *a, None, depth - 1))
+                    .max()
+                    .unwrap_or(-WIN)
+            };
+            self.blocked[mi] = false;
+            return score;
+        };
+
+        let mut worst = i32::MAX;
+        for reply in ACTIONS {
+            let score = match self.step(opp, reply) {
+                None => WIN,
+                Some((_, oi)) if oi == mi => -WIN / 2,
+                Some((opp_next, oi)) => {
+                    self.blocked[mi] = true;
+                    self.blocked[oi] = true;
+                    let score = if depth <= 1 {
+                        self.area(me_next) - self.area(opp_next)
+                    } else {
+                        ACTIONS
+                            .iter()
+                            .map(|a| self.search_move(me_next, *a, Some(opp_next), depth - 1))
+                            .max()
+                            .unwrap_or(-WIN)
+                    };
+                    self.blocked[mi] = false;
+                    self.blocked[oi] = false;
+                    score
+                }
+            };
+            worst = worst.min(score);
+        }
+        worst
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The adaptive skill after each of `games` practice games against a
+    /// scripted opponent who beats any bot below `beats_below` and loses to
+    /// the rest
+    fn practice(beats_below: f64, games: usize) -> Vec<f64> {
+        let mut state = AdaptiveDifficulty::default();
+        (0..games)
+            .map(|_| {
+                state.record(state.skill < beats_below);
+                state.skill
+            })
+            .collect()
+    }
+
+    #[test]
+    fn adaptive_skill_heads_toward_the_opponents_level() {
+        let strong = practice(0.8, 60);
+        let weak = practice(0.2, 60);
+        let mean = |skills: &[f64]| skills.iter().sum::<f64>() / skills.len() as f64;
+
+        // Both leave the middle right away, in opposite directions, and stay
+        // on their side while the window of results swings them about
+        assert!(strong.iter().all(|&s| (0.55..=1.0).contains(&s)), "{:?}", strong);
+        assert!(weak.iter().all(|&s| (0.0..=0.45).contains(&s)), "{:?}", weak);
+        assert!(mean(&strong[30..]) > 0.7, "{:?}", strong);
+        assert!(mean(&weak[30..]) < 0.3, "{:?}", weak);
+
+        let params = |skill| BotParams::for_difficulty(Difficulty::Adaptive, skill);
+        for (&s, &w) in strong.iter().zip(&weak) {
+            assert!(params(s).depth > params(w).depth, "skills {} and {}", s, w);
+            assert!(params(s).mistake_rate < params(w).mistake_rate, "skills {} and {}", s, w);
+        }
+    }
+
+    #[test]
+    fn adaptive_skill_only_remembers_the_window() {
+        let mut state = AdaptiveDifficulty::default();
+        for _ in 0..ADAPT_WINDOW * 3 {
+            state.record(false);
+        }
+        assert_eq!(state.skill, 0.0);
+        assert_eq!(state.recent.len(), ADAPT_WINDOW);
+
+        // The losses age out as wins come in; the skill climbs once wins
+        // are the majority, by 0.02 + 0.04 + ... + 0.1
+        for _ in 0..ADAPT_WINDOW {
+            state.record(true);
+        }
+        assert!(state.recent.iter().all(|&won| won));
+        assert!((state.skill - 0.3).abs() < 1e-9, "{}", state.skill);
+    }
+}
@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{WebChatMessage, WebGameState};
+
+/// Where a player who left was
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeftFrom {
+    Queue,
+    Game,
+}
+
+/// Everything a room announces on its broadcast channel. Serialized with a
+/// `type` field at the edge (the web event stream, webhooks), so the wire
+/// format is `{"type": "game_update", "game": {...}}` and so on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BroadcastEvent {
+    GameStarted {
+        game_id: String,
+        game: Box<WebGameState>,
+    },
+    GameUpdate {
+        game: Box<WebGameState>,
+    },
+    GameFinished {
+        game: Box<WebGameState>,
+        /// Leaderboard after the result counted; absent for exhibitions
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        leaderboard_checksum: Option<String>,
+    },
+    PlayerLeft {
+        player: String,
+        from: LeftFrom,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        game_id: Option<String>,
+    },
+    /// A queued player went quiet for too long and lost their slot
+    QueueExpired {
+        player: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lobby: Option<String>,
+    },
+    AbortRequested {
+        game_id: String,
+        player: String,
+    },
+    /// A player said something in their game (`say`)
+    Chat {
+        game_id: String,
+        message: WebChatMessage,
+    },
+    /// A player asked to play the finished game `game_id` again with the
+    /// same opponents; `needed` requests start it
+    RematchRequested {
+        game_id: String,
+        player: String,
+        requested: Vec<String>,
+        needed: usize,
+    },
+    /// Enough players asked: `new_game_id` replays `game_id` on the next level
+    RematchStarted {
+        game_id: String,
+        new_game_id: String,
+        players: Vec<String>,
+    },
+    /// Queued players are counting down to a public game on `level`, which
+    /// starts sooner if more join and fill it
+    MatchCountdown {
+        level: u32,
+        players: Vec<String>,
+        starts_in_secs: u64,
+    },
+    /// A real-time tick moved these players, every cycle still riding, so
+    /// their window to queue the next turn is open until tick `tick + 1`
+    MoveWindow {
+        game_id: String,
+        tick: u32,
+        players: Vec<String>,
+    },
+    /// An admin acted on the room: `action` is `kick`, `abort`,
+    /// `clear_queue` or `remove_entry`, and `target` the player, game id or
+    /// queue it acted on
+    AdminAction {
+        action: String,
+        target: String,
+    },
+    ServerDegraded {
+        stalled_secs: u64,
+    },
+    ServerRecovered,
+    /// The server is going down; running games are kept for a restart
+    ServerShutdown,
+    /// First message of a web event stream: the games running when it opened.
+    /// Never sent on the channel itself.
+    Snapshot {
+        games: Vec<WebGameState>,
+    },
+}
+
+impl BroadcastEvent {
+    /// Game the event is about, if any
+    pub fn game_id(&self) -> Option<&str> {
+        match self {
+            BroadcastEvent::GameStarted { game_id, .. }
+            | BroadcastEvent::AbortRequested { game_id, .. }
+            | BroadcastEvent::Chat { game_id, .. }
+            | BroadcastEvent::RematchRequested { game_id, .. }
+            | BroadcastEvent::RematchStarted { game_id, .. }
+            | BroadcastEvent::MoveWindow { game_id, .. } => Some(game_id),
+            BroadcastEvent::GameUpdate { game } | BroadcastEvent::GameFinished { game, .. } => Some(&game.id),
+            BroadcastEvent::PlayerLeft { game_id, .. } => game_id.as_deref(),
+            BroadcastEvent::MatchCountdown { .. }
+            | BroadcastEvent::QueueExpired { .. }
+            | BroadcastEvent::AdminAction { .. }
+            | BroadcastEvent::ServerDegraded { .. }
+            | BroadcastEvent::ServerRecovered
+            | BroadcastEvent::ServerShutdown
+            | BroadcastEvent::Snapshot { .. } => None,
+        }
+    }
+
+    /// About the whole server rather than one room's games
+    pub fn is_server_wide(&self) -> bool {
+        matches!(
+            self,
+            BroadcastEvent::ServerDegraded { .. } | BroadcastEvent::ServerRecovered | BroadcastEvent::ServerShutdown
+        )
+    }
+
+    /// JSON text as sent to web clients
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("broadcast events always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    /// Sent as `json`, and read back to the same event
+    fn check(event: BroadcastEvent, json: &str) {
+        assert_eq!(event.to_json(), json);
+        let read: BroadcastEvent = serde_json::from_str(json).unwrap();
+        assert_eq!(read.to_json(), json);
+    }
+
+    #[test]
+    fn events_keep_their_wire_format() {
+        let names = || vec!["Claude".to_string(), "Gemini".to_string()];
+        check(
+            BroadcastEvent::PlayerLeft {
+                player: "Claude".to_string(),
+                from: LeftFrom::Game,
+                game_id: Some("g1".to_string()),
+            },
+            r#"{"type":"player_left","player":"Claude","from":"game","game_id":"g1"}"#,
+        );
+        check(
+            BroadcastEvent::PlayerLeft {
+                player: "Claude".to_string(),
+                from: LeftFrom::Queue,
+                game_id: None,
+            },
+            r#"{"type":"player_left","player":"Claude","from":"queue"}"#,
+        );
+        check(
+            BroadcastEvent::QueueExpired {
+                player: "Claude".to_string(),
+                lobby: Some("ABCDEF".to_string()),
+            },
+            r#"{"type":"queue_expired","player":"Claude","lobby":"ABCDEF"}"#,
+        );
+        check(
+            BroadcastEvent::AbortRequested {
+                game_id: "g1".to_string(),
+                player: "Claude".to_string(),
+            },
+            r#"{"type":"abort_requested","game_id":"g1","player":"Claude"}"#,
+        );
+        check(
+            BroadcastEvent::Chat {
+                game_id: "g1".to_string(),
+                message: WebChatMessage {
+                    player: "Claude".to_string(),
+                    tick: 3,
+                    text: "gg &amp; good luck".to_string(),
+                },
+            },
+            r#"{"type":"chat","game_id":"g1","message":{"player":"Claude","tick":3,"text":"gg &amp; good luck"}}"#,
+        );
+        check(
+            BroadcastEvent::RematchRequested {
+                game_id: "g1".to_string(),
+                player: "Claude".to_string(),
+                requested: vec!["Claude".to_string()],
+                needed: 2,
+            },
+            r#"{"type":"rematch_requested","game_id":"g1","player":"Claude","requested":["Claude"],"needed":2}"#,
+        );
+        check(
+            BroadcastEvent::RematchStarted {
+                game_id: "g1".to_string(),
+                new_game_id: "g2".to_string(),
+                players: names(),
+            },
+            r#"{"type":"rematch_started","game_id":"g1","new_game_id":"g2","players":["Claude","Gemini"]}"#,
+        );
+        check(
+            BroadcastEvent::MatchCountdown {
+                level: 2,
+                players: names(),
+                starts_in_secs: 10,
+            },
+            r#"{"type":"match_countdown","level":2,"players":["Claude","Gemini"],"starts_in_secs":10}"#,
+        );
+        check(
+            BroadcastEvent::MoveWindow {
+                game_id: "g1".to_string(),
+                tick: 8,
+                players: names(),
+            },
+            r#"{"type":"move_window","game_id":"g1","tick":8,"players":["Claude","Gemini"]}"#,
+        );
+        check(
+            BroadcastEvent::AdminAction {
+                action: "kick".to_string(),
+                target: "Claude".to_string(),
+            },
+            r#"{"type":"admin_action","action":"kick","target":"Claude"}"#,
+        );
+        check(
+            BroadcastEvent::ServerDegraded { stalled_secs: 7 },
+            r#"{"type":"server_degraded","stalled_secs":7}"#,
+        );
+        check(BroadcastEvent::ServerRecovered, r#"{"type":"server_recovered"}"#);
+        check(BroadcastEvent::ServerShutdown, r#"{"type":"server_shutdown"}"#);
+    }
+
+    #[test]
+    fn game_events_carry_the_game_state() {
+        let map = "
+            ##########
+            #1......2#
+            #........#
+            #........#
+            #........#
+            #........#
+            #........#
+            ##########";
+        let game = testutil::game(map, &["Claude", "Gemini"]).to_web_state();
+        let id = game.id.clone();
+        let state = serde_json::to_value(&game).unwrap();
+        let events = [
+            (
+                BroadcastEvent::GameStarted {
+                    game_id: id.clone(),
+                    game: Box::new(game.clone()),
+                },
+                serde_json::json!({"type": "game_started", "game_id": id, "game": state}),
+            ),
+            (
+                BroadcastEvent::GameUpdate {
+                    game: Box::new(game.clone()),
+                },
+                serde_json::json!({"type": "game_update", "game": state}),
+            ),
+            (
+                BroadcastEvent::GameFinished {
+                    game: Box::new(game.clone()),
+                    leaderboard_checksum: Some("sha256:00".to_string()),
+                },
+                serde_json::json!({"type": "game_finished", "game": state, "leaderboard_checksum": "sha256:00"}),
+            ),
+            (
+                BroadcastEvent::GameFinished {
+                    game: Box::new(game.clone()),
+                    leaderboard_checksum: None,
+                },
+                serde_json::json!({"type": "game_finished", "game": state}),
+            ),
+            (
+                BroadcastEvent::Snapshot { games: vec![game] },
+                serde_json::json!({"type": "snapshot", "games": [state]}),
+            ),
+        ];
+        for (event, expected) in events {
+            let sent: serde_json::Value = serde_json::from_str(&event.to_json()).unwrap();
+            assert_eq!(sent, expected);
+            let read: BroadcastEvent = serde_json::from_value(sent).unwrap();
+            assert_eq!(serde_json::to_value(&read).unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,548 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+use crate::rooms::{Rooms, SharedRooms};
+use crate::{
+    admin, autoplay, bot, course, demo, fairness, game, manager, mcp, ratelimit, rating, rematch, replay, simulate,
+    snapshot, storage, tcp, validation, webhook,
+};
+
+#[derive(Parser)]
+#[command(name = "tronmcp", about = "Tron Light-Cycle MCP Game for LLMs")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// HTTP port for the web UI
+    #[arg(long, default_value = "3000")]
+    port: u16,
+    /// TCP port for MCP player connections
+    #[arg(long, default_value = "9999")]
+    tcp_port: u16,
+    /// Data directory for persistent storage
+    #[arg(long, default_value = "data")]
+    data_dir: String,
+    /// Where leaderboards, finished games and active state are kept: `json`
+    /// files in the data directory, or `sqlite` (needs the `sqlite` feature)
+    #[arg(long, default_value = "json", value_parser = storage::StorageKind::parse)]
+    storage: storage::StorageKind,
+    /// SQLite database file for `--storage sqlite` [default: <data-dir>/tron.db]
+    #[arg(long, value_name = "FILE")]
+    db_path: Option<std::path::PathBuf>,
+    /// Owner-level admin token (also keeps custom-rule lobbies ranked)
+    #[arg(long)]
+    admin_token: Option<String>,
+    /// File of `<role> <token>` lines granting viewer, operator or owner access
+    #[arg(long)]
+    admin_tokens_file: Option<String>,
+    /// Comma-separated arenas to host; the first is the default room
+    #[arg(long, value_delimiter = ',', default_value = "default")]
+    rooms: Vec<String>,
+    /// Take player connections on this Unix domain socket instead of the TCP
+    /// port, so only local agents can reach the game
+    #[arg(long, value_name = "PATH")]
+    tcp_socket: Option<std::path::PathBuf>,
+    /// Permissions of the `--tcp-socket` file, in octal
+    #[arg(long, value_name = "MODE", default_value = "600", value_parser = tcp::CommandListener::parse_mode)]
+    tcp_socket_mode: u32,
+    /// Let TCP commands act for any player name, not just the one the
+    /// connection joined as (debugging only)
+    #[arg(long)]
+    tcp_legacy_names: bool,
+    /// Let TCP STEER, LOOK and STATUS name the player instead of passing the
+    /// session token from the join reply, as older clients do
+    #[arg(long)]
+    tcp_name_commands: bool,
+    /// Commands a second each TCP connection may send: `<rate>`,
+    /// `<rate>/<burst>` or `off` (default 20/40)
+    #[arg(long, value_parser = ratelimit::RateLimit::parse)]
+    tcp_rate_limit: Option<ratelimit::RateLimit>,
+    /// Seconds a lone player waits in the public queue before a bot fills the game
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..=86_400))]
+    bot_fill: Option<u64>,
+    /// Count bot-filled games for the leaderboard and level progression
+    #[arg(long)]
+    count_bots: bool,
+    /// Minutes without players before a featured bot-vs-bot game is shown
+    #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..=1_440))]
+    exhibition: Option<u64>,
+    /// Hold back steers from fast connections: `auto` pads everyone to the
+    /// slowest measured round trip in their game, `<n>` pads to n milliseconds
+    #[arg(long, value_parser = fairness::FairnessDelay::parse)]
+    fairness_delay_ms: Option<fairness::FairnessDelay>,
+    /// Seconds a player may go without steering before the idle action applies
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..=86_400))]
+    idle_timeout: Option<u64>,
+    /// Opponent moves a player may sit through without steering before the
+    /// idle action applies
+    #[arg(long)]
+    idle_moves: Option<u32>,
+    /// What happens to an idle player: `straight` moves them one step, `crash` ends their run
+    #[arg(long, default_value = "straight", value_parser = game::IdleAction::parse)]
+    idle_action: game::IdleAction,
+    /// `turnbased`: each steer moves the player one step. `realtime`: every
+    /// cycle advances once per second and a steer sets the next turn
+    #[arg(long, default_value = "turnbased", value_parser = game::GameMode::parse)]
+    mode: game::GameMode,
+    /// How a `steer` with a count or a `plan_moves` reaches the web view:
+    /// an update after each move (`per-step`), or one at the end (`combined`)
+    #[arg(long, default_value = "per-step", value_parser = manager::PlanUpdates::parse)]
+    plan_updates: manager::PlanUpdates,
+    /// Load extra courses from the `*.json` and `*.toml` files in this
+    /// directory; a course replaces the built-in one with the same level
+    #[arg(long, value_name = "DIR")]
+    courses_dir: Option<std::path::PathBuf>,
+    /// Generate random course layouts (Chaos) from this seed instead of a
+    /// fresh one per game, so every game gets the same layout
+    #[arg(long, value_name = "SEED")]
+    course_seed: Option<u64>,
+    /// Seconds a player who crashed out of a game waits before joining again;
+    /// tripled when they crashed within the first ticks
+    #[arg(long, value_name = "SECS", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..=3_600))]
+    crash_cooldown: u64,
+    /// Let crashed players rejoin straight away
+    #[arg(long)]
+    no_crash_cooldown: bool,
+    /// Match queued players regardless of level, on the lowest level among
+    /// them, instead of only with players on their own level
+    #[arg(long)]
+    mixed_levels: bool,
+    /// Levels apart two queued players may be and still be matched
+    #[arg(long, value_name = "LEVELS", default_value_t = 0, conflicts_with = "mixed_levels", value_parser = clap::value_parser!(u32).range(0..=4))]
+    level_tolerance: u32,
+    /// Queued players a public game waits for before it starts
+    #[arg(long, value_name = "N", default_value_t = 2, value_parser = clap::value_parser!(u64).range(2..=16))]
+    min_players: u64,
+    /// Most players a public game seats, when fewer than the course allows
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(2..=16))]
+    max_players: Option<u64>,
+    /// Seconds a public game counts down once enough players are queued,
+    /// so late joiners make it in; it starts early when the course fills
+    #[arg(long, value_name = "SECS", default_value_t = 0, value_parser = clap::value_parser!(u64).range(0..=300))]
+    start_delay: u64,
+    /// Seconds a queued player may go without joining again, waiting or
+    /// checking their status before their slot expires
+    #[arg(long, value_name = "SECS", default_value_t = manager::QUEUE_TTL_SECS, value_parser = clap::value_parser!(u64).range(10..=86_400))]
+    queue_ttl: u64,
+    /// Never expire queued players
+    #[arg(long)]
+    no_queue_ttl: bool,
+    /// What a dropped TCP or MCP connection does to the player's running
+    /// game: `keep` it for the idle policy, forfeit after a `grace` period
+    /// unless they resume, or `forfeit` right away
+    #[arg(long, default_value = "keep", value_parser = manager::DisconnectPolicy::parse)]
+    disconnect_policy: manager::DisconnectPolicy,
+    /// Seconds a dropped player has to resume under `--disconnect-policy grace`
+    #[arg(long, value_name = "SECS", default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..=3_600))]
+    disconnect_grace: u64,
+    /// Players of a finished game who must ask before its rematch starts:
+    /// `all` of those still around, or a `majority`
+    #[arg(long, default_value = "all", value_parser = rematch::RematchQuorum::parse)]
+    rematch_quorum: rematch::RematchQuorum,
+    /// Seconds a rematch waits for the others to ask before those who asked
+    /// join the public queue
+    #[arg(long, value_name = "SECS", default_value_t = 30, value_parser = clap::value_parser!(u64).range(5..=600))]
+    rematch_timeout: u64,
+    /// Largest rating change one ranked game can bring
+    #[arg(long, value_name = "K", default_value = "32", value_parser = rating::parse_k_factor)]
+    elo_k_factor: f64,
+    /// When more players are queued than a game seats, seat those rated
+    /// within this many points of the longest waiter first
+    #[arg(long, value_name = "POINTS", value_parser = clap::value_parser!(u32).range(1..=2_000))]
+    elo_window: Option<u32>,
+    /// POST every game start and finish to this URL (plain `http://`);
+    /// repeat for more
+    #[arg(long, value_name = "URL")]
+    webhook_url: Vec<String>,
+    /// Let players register webhooks on loopback and private addresses, for
+    /// orchestrators on the server's own network
+    #[arg(long)]
+    allow_private_webhooks: bool,
+    /// Body of `--webhook-url` posts: the `json` broadcast event, or a
+    /// `discord` message
+    #[arg(long, default_value = "json", value_parser = webhook::WebhookFormat::parse)]
+    webhook_format: webhook::WebhookFormat,
+    /// Adopt state exported by another server (`/api/admin/state-export`)
+    /// before listening; repeat once per room
+    #[arg(long)]
+    import_state: Vec<std::path::PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Start the game server with web UI
+    Serve(Box<ServeArgs>),
+    /// Connect as an MCP player (stdio mode for LLM agents)
+    Play {
+        /// Game server address: `host:port`, or `unix:<path>` for a server
+        /// listening on `--tcp-socket`
+        #[arg(long, default_value = "127.0.0.1:9999")]
+        server: String,
+        /// Seconds a command may wait for the game server's reply before the
+        /// tool call fails with "not responding"
+        #[arg(long, value_name = "SECS", default_value_t = mcp::DEFAULT_COMMAND_TIMEOUT_SECS, value_parser = clap::value_parser!(u64).range(1..=600))]
+        command_timeout: u64,
+    },
+    /// Run a local server where two built-in bots play each other
+    Demo {
+        /// Web UI port on localhost; 0 picks a free one
+        #[arg(long, default_value_t = 0)]
+        port: u16,
+        /// Games to play before shutting down
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=100))]
+        games: u32,
+        /// Open the web UI in a browser
+        #[arg(long)]
+        open: bool,
+    },
+    /// Play a finished game back in the terminal
+    Replay {
+        /// The game's id, or enough of its start to pick it out, or a JSON
+        /// file holding the game (e.g. saved from `/api/games/<id>`)
+        game: String,
+        /// Data directory the server archived the game in; games from a room
+        /// other than the first are under `<data-dir>/rooms/<room>`
+        #[arg(long, default_value = "data")]
+        data_dir: String,
+        /// Milliseconds between frames
+        #[arg(long, value_name = "MS", default_value_t = 200, value_parser = clap::value_parser!(u64).range(0..=10_000))]
+        delay_ms: u64,
+        /// Print only frame N: 0 is the start, and each move adds one
+        #[arg(long, value_name = "N", conflicts_with = "summary")]
+        frame: Option<usize>,
+        /// Print only the crashes and final scores
+        #[arg(long)]
+        summary: bool,
+    },
+    /// Connect a built-in bot to a game server as a network player
+    Bot {
+        /// Game server TCP address
+        #[arg(long, default_value = "127.0.0.1:9999")]
+        server: String,
+        /// Name to play as
+        #[arg(long)]
+        name: String,
+        /// How to play: random, greedy, easy, medium or hard
+        #[arg(long, default_value = "greedy", value_parser = bot::Policy::parse)]
+        policy: bot::Policy,
+        /// Milliseconds between moves
+        #[arg(long, value_name = "MS", default_value_t = 300, value_parser = clap::value_parser!(u64).range(0..=10_000))]
+        delay_ms: u64,
+        /// Join again after each game instead of stopping
+        #[arg(long)]
+        rejoin: bool,
+    },
+    /// Play built-in bots against each other in-process and report how each did
+    Simulate {
+        /// Course level to play on
+        #[arg(long, value_name = "LEVEL", default_value_t = 1)]
+        course: u32,
+        /// Bot for each seat, comma-separated: random, greedy, easy, medium or hard
+        #[arg(long, value_delimiter = ',', default_value = "greedy,random", value_parser = bot::Policy::parse)]
+        bots: Vec<bot::Policy>,
+        /// Games to play
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..=100_000))]
+        games: u32,
+        /// Seed for the course layout and every bot move; the same seed plays
+        /// the same games [default: random, and printed]
+        #[arg(long)]
+        seed: Option<u64>,
+        /// End a game still running after this many ticks as a draw
+        #[arg(long, value_name = "TICKS", default_value_t = 5_000, value_parser = clap::value_parser!(u32).range(1..))]
+        max_ticks: u32,
+    },
+    /// List the courses, or show one course's layout
+    Courses {
+        /// Level or name of the course to show [default: list them all]
+        course: Option<String>,
+        /// Widest/tallest the layout is drawn before it is downsampled
+        #[arg(long, value_name = "CHARS", default_value_t = course::PREVIEW_SIZE as u64, value_parser = clap::value_parser!(u64).range(8..=200))]
+        max_size: u64,
+        /// Include the courses in this directory, as `serve --courses-dir` would
+        #[arg(long, value_name = "DIR")]
+        courses_dir: Option<std::path::PathBuf>,
+    },
+}
+
+/// Parse the command line and run the command it names
+pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    // A replay prints its frames on stdout; keep log lines out of them
+    if let Commands::Replay { .. } = cli.command {
+        tracing_subscriber::fmt().with_writer(std::io::stderr).with_max_level(tracing::Level::WARN).init();
+    } else {
+        tracing_subscriber::fmt::init();
+    }
+
+    match cli.command {
+        Commands::Serve(args) => {
+            run_server(*args).await?;
+        }
+        Commands::Play { server, command_timeout } => {
+            mcp::run_mcp_server(server, std::time::Duration::from_secs(command_timeout)).await?;
+        }
+        Commands::Demo { port, games, open } => {
+            demo::run_demo(port, games, open).await?;
+        }
+        Commands::Replay {
+            game,
+            data_dir,
+            delay_ms,
+            frame,
+            summary,
+        } => {
+            let show = match (frame, summary) {
+                (Some(n), _) => replay::Show::Frame(n),
+                (None, true) => replay::Show::Summary,
+                (None, false) => replay::Show::Frames(Duration::from_millis(delay_ms)),
+            };
+            replay::run_replay(&game, Path::new(&data_dir), show).await?;
+        }
+        Commands::Bot {
+            server,
+            name,
+            policy,
+            delay_ms,
+            rejoin,
+        } => {
+            autoplay::run_bot(autoplay::Autoplay {
+                server,
+                name,
+                policy,
+                delay: Duration::from_millis(delay_ms),
+                rejoin,
+            })
+            .await?;
+        }
+        Commands::Simulate {
+            course,
+            bots,
+            games,
+            seed,
+            max_ticks,
+        } => {
+            simulate::run_simulate(simulate::Simulation {
+                level: course,
+                bots,
+                games,
+                seed,
+                max_ticks,
+            })?;
+        }
+        Commands::Courses {
+            course,
+            max_size,
+            courses_dir,
+        } => {
+            show_courses(course.as_deref(), max_size as usize, courses_dir.as_deref())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print every course's summary line, or one course's line and layout
+fn show_courses(
+    wanted: Option<&str>,
+    max_size: usize,
+    courses_dir: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = courses_dir {
+        course::install_courses(course::load_courses_dir(dir)?)?;
+    }
+    let registry = course::CourseRegistry::current();
+    let Some(wanted) = wanted else {
+        for (c, file) in registry.list() {
+            println!("{}", c.summary_line(file.as_deref()));
+        }
+        return Ok(());
+    };
+    let found = match wanted.parse::<u32>() {
+        Ok(level) => registry.get_by_level(level),
+        Err(_) => registry.get_by_name(wanted),
+    };
+    let Some(c) = found else {
+        return Err(format!("no course '{}'; run `tronmcp courses` to list them", wanted).into());
+    };
+    let file = registry.list().iter().find(|(other, _)| other.level == c.level).and_then(|(_, file)| file.as_deref());
+    let (scale, map) = c.render_ascii_at(max_size);
+    println!("{}", c.summary_line(file));
+    if scale > 1 {
+        println!("At 1/{} scale:", scale);
+    }
+    println!("{}", map);
+    Ok(())
+}
+
+async fn run_server(args: ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tokens = match &args.admin_tokens_file {
+        Some(path) => admin::AdminAuth::load_tokens_file(Path::new(path))?,
+        None => Vec::new(),
+    };
+    if let Some(token) = args.admin_token {
+        validation::token("admin-token", &token)?;
+        tokens.push((token, admin::Role::Owner));
+    }
+    let admin = Arc::new(admin::AdminAuth::new(tokens));
+    let server_webhooks = Arc::new(webhook::ServerWebhooks::new(args.webhook_url.clone(), args.webhook_format)?);
+
+    if let Some(dir) = &args.courses_dir {
+        let courses = course::load_courses_dir(dir)?;
+        let count = courses.len();
+        course::install_courses(courses)?;
+        tracing::info!("Loaded {} courses from {}", count, dir.display());
+    }
+
+    if let Some(max) = args.max_players
+        && max < args.min_players
+    {
+        return Err(format!("'max-players' must be at least 'min-players' ({})", args.min_players).into());
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for name in &args.rooms {
+        let name = validation::room("rooms", name)?;
+        if names.contains(&name) {
+            return Err(format!("Room '{}' is listed twice", name).into());
+        }
+        names.push(name);
+    }
+    let open_storage = storage_opener(args.storage, args.db_path.clone(), Path::new(&args.data_dir))?;
+    let rooms: SharedRooms = Arc::new(Rooms::new(&names, Path::new(&args.data_dir), open_storage, |manager| {
+        manager.admin = admin.clone();
+        manager.server_webhooks = server_webhooks.clone();
+        manager.allow_private_webhooks = args.allow_private_webhooks;
+        manager.bot_fill_secs = args.bot_fill;
+        manager.count_bots = args.count_bots;
+        manager.exhibition_mins = args.exhibition;
+        manager.fairness_delay = args.fairness_delay_ms.unwrap_or_default();
+        manager.idle = game::IdlePolicy {
+            after_secs: args.idle_timeout,
+            after_moves: args.idle_moves,
+            action: args.idle_action,
+        };
+        manager.mode = args.mode;
+        manager.plan_updates = args.plan_updates;
+        manager.crash_cooldown_secs = (!args.no_crash_cooldown).then_some(args.crash_cooldown);
+        manager.mixed_levels = args.mixed_levels;
+        manager.level_tolerance = args.level_tolerance;
+        manager.min_players = args.min_players as usize;
+        manager.max_players_override = args.max_players.map(|n| n as usize);
+        manager.start_delay_secs = args.start_delay;
+        manager.rematch_quorum = args.rematch_quorum;
+        manager.queue_ttl_secs = (!args.no_queue_ttl).then_some(args.queue_ttl);
+        manager.disconnect_policy = args.disconnect_policy;
+        manager.disconnect_grace_secs = args.disconnect_grace;
+        manager.rematch_timeout_secs = args.rematch_timeout;
+        manager.elo_k_factor = args.elo_k_factor;
+        manager.elo_window = args.elo_window;
+        manager.course_seed = args.course_seed;
+    })?);
+
+    // Adopt a previous process's state before anyone can connect
+    for path in &args.import_state {
+        let snapshot = snapshot::StateSnapshot::load(path)?;
+        let room = snapshot.room.clone();
+        let manager = rooms
+            .get(&room)
+            .ok_or_else(|| format!("{} is for room '{}', which this server does not host", path.display(), room))?;
+        let games = snapshot.active_games.len();
+        if let Err(problems) = manager.lock().await.import_state(snapshot) {
+            return Err(format!("{} failed the state audit: {}", path.display(), problems.join("; ")).into());
+        }
+        tracing::info!("Imported room {} from {} ({} active games)", room, path.display(), games);
+    }
+
+    let http = TcpListener::bind(format!("0.0.0.0:{}", args.port)).await?;
+    let tcp = match &args.tcp_socket {
+        #[cfg(unix)]
+        Some(path) => tcp::CommandListener::bind_unix(path, args.tcp_socket_mode)
+            .map_err(|e| format!("cannot listen on {}: {}", path.display(), e))?,
+        #[cfg(not(unix))]
+        Some(_) => return Err("--tcp-socket needs a Unix system".into()),
+        None => tcp::CommandListener::Tcp(TcpListener::bind(format!("0.0.0.0:{}", args.tcp_port)).await?),
+    };
+    let ct = CancellationToken::new();
+    tokio::spawn(cancel_on_signal(ct.clone()));
+    let identity = if args.tcp_legacy_names {
+        tcp::TcpIdentity::Unchecked
+    } else if args.tcp_name_commands {
+        tcp::TcpIdentity::Names
+    } else {
+        tcp::TcpIdentity::Tokens
+    };
+    let settings = tcp::TcpSettings {
+        identity,
+        rate_limit: args.tcp_rate_limit.unwrap_or_default(),
+    };
+    crate::serve(rooms, admin, http, tcp, settings, ct).await
+}
+
+/// What `Rooms::new` opens each room's storage with, per `--storage`
+fn storage_opener(
+    kind: storage::StorageKind,
+    db_path: Option<std::path::PathBuf>,
+    data_dir: &Path,
+) -> Result<impl Fn(&str, &Path) -> Result<storage::SharedStorage, String> + use<>, String> {
+    if db_path.is_some() && kind != storage::StorageKind::Sqlite {
+        return Err("--db-path only applies to --storage sqlite".to_string());
+    }
+    #[cfg(feature = "sqlite")]
+    let db = match kind {
+        storage::StorageKind::Sqlite => {
+            let path = db_path.unwrap_or_else(|| data_dir.join("tron.db"));
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            let db = storage::sqlite::open(&path)?;
+            tracing::info!("Storing rooms in {}", path.display());
+            Some(db)
+        }
+        storage::StorageKind::Json => None,
+    };
+    #[cfg(not(feature = "sqlite"))]
+    if kind == storage::StorageKind::Sqlite {
+        let _ = data_dir;
+        return Err("--storage sqlite needs a build with the `sqlite` feature (cargo build --features sqlite)".to_string());
+    }
+    Ok(move |room: &str, dir: &Path| -> Result<storage::SharedStorage, String> {
+        #[cfg(feature = "sqlite")]
+        if let Some(db) = &db {
+            return Ok(Arc::new(storage::sqlite::SqliteStorage::new(db.clone(), room, dir)?));
+        }
+        #[cfg(not(feature = "sqlite"))]
+        let _ = room;
+        Ok(Arc::new(storage::JsonStorage::new(dir)))
+    })
+}
+
+/// Cancel `ct` on Ctrl-C or, on Unix, SIGTERM
+async fn cancel_on_signal(ct: CancellationToken) {
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Cannot listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutting down");
+    ct.cancel();
+}
@@ -0,0 +1,32 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Where a room reads the time for its deadlines (match countdowns, queue
+/// expiry, abort votes, rematches, exhibitions, the chat cooldown): the
+/// system clock, or a manual clock that stands still until it is advanced,
+/// so tests can step past a deadline without sleeping. Clones share the
+/// same manual time.
+#[derive(Debug, Clone, Default)]
+pub struct Clock(Option<Arc<Mutex<DateTime<Utc>>>>);
+
+impl Clock {
+    /// A clock stopped at the current time
+    pub fn manual() -> Clock {
+        Clock(Some(Arc::new(Mutex::new(Utc::now()))))
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match &self.0 {
+            Some(time) => *time.lock().unwrap_or_else(|e| e.into_inner()),
+            None => Utc::now(),
+        }
+    }
+
+    /// Move a manual clock forward; the system clock is left alone
+    pub fn advance(&self, by: Duration) {
+        if let Some(time) = &self.0 {
+            *time.lock().unwrap_or_else(|e| e.into_inner()) += by;
+        }
+    }
+}
@@ -1,6 +1,77 @@
-use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-/// A course / level definition
+use serde::{Deserialize, Serialize};
+
+use crate::game::{self, Direction, MIN_GRID_SIZE, spawn_positions};
+use crate::{maze, scatter};
+use crate::rules::{DeadTrailPolicy, Ruleset, WinCondition};
+
+/// Every tag a course can carry, hand-written or derived
+pub const KNOWN_TAGS: &[&str] = &[
+    "open", "maze", "corridors", "obstacles", "random", "walls", "small", "large", "checkpoint",
+    "wrap", "powerups", "timed", "sudden-death", "teams", "territory", "shrinking", "lives",
+    "classic",
+];
+/// Courses with at most this many cells are tagged "small"
+const SMALL_CELLS: usize = 1000;
+/// Courses with at least this many cells are tagged "large"
+const LARGE_CELLS: usize = 2400;
+/// Largest width or height a course file may ask for
+pub const MAX_COURSE_SIZE: usize = 200;
+const MAX_COURSE_NAME_LEN: usize = 64;
+/// Largest `powerup_rate` a course may set
+const MAX_POWERUP_RATE: u32 = 10_000;
+
+/// Widest/tallest course preview before the layout is downsampled
+pub const PREVIEW_SIZE: usize = 40;
+
+/// Courses loaded with `--courses-dir`, with the file each came from
+static CUSTOM_COURSES: OnceLock<Vec<(Course, String)>> = OnceLock::new();
+
+/// How the walls of a scattered layout repeat around the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Symmetry {
+    /// No symmetry: walls anywhere
+    None,
+    /// Mirrored across both the vertical and the horizontal middle line
+    Mirror,
+    /// Turned a quarter at a time about the middle, or half turns on grids
+    /// that aren't square
+    Rotational,
+}
+
+/// What a generated course's layout is made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Generator {
+    /// A maze with corridors this many cells wide (`maze::generate`)
+    Maze { corridor_width: usize },
+    /// Short random wall segments under the given symmetry
+    /// (`scatter::generate`)
+    Scatter { symmetry: Symmetry },
+}
+
+/// How a generated course is laid out
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GeneratorSpec {
+    pub generator: Generator,
+    /// Seed pinned by the course file; without one each game gets a fresh
+    /// layout
+    pub seed: Option<u64>,
+}
+
+/// The walls and seats a generator made
+pub struct GeneratedLayout {
+    pub walls: Vec<(usize, usize)>,
+    pub spawns: Vec<(usize, usize, Direction)>,
+}
+
+/// A course / level definition: the grid size, walls, obstructions and
+/// spawn points a `Game` is built on, and the default rules that go with it
+/// (`default_rules`). `get_course` and `all_courses` give the built-in ones.
 #[derive(Debug, Clone, Serialize)]
 pub struct Course {
     pub name: String,
@@ -11,24 +82,859 @@ pub struct Course {
     pub max_players: usize,
     pub obstructions: Vec<(usize, usize)>,
     pub walls: Vec<(usize, usize)>,
+    /// Target cell for checkpoint games
+    pub checkpoint: Option<(usize, usize)>,
+    /// Race course: games on it default to the checkpoint win condition
+    pub race: bool,
+    /// Power-up spawn points, filled while the `boosts` rule is on
+    pub powerups: Vec<(usize, usize)>,
+    /// With `boosts` on, a power-up appears on a random empty cell every
+    /// this many ticks
+    pub powerup_rate: Option<u32>,
+    /// Default for the `shrink_every` rule: the arena closes in a ring every
+    /// this many ticks
+    pub shrink_every: Option<u32>,
+    /// Default for the `lives` rule: a crash with lives left respawns the
+    /// cycle
+    pub lives: Option<u32>,
+    /// Default for the `max_ticks` rule: the game ends at this tick and the
+    /// longest distance traveled wins
+    pub max_ticks: Option<u32>,
+    /// Default for the `win_condition` rule; without it races play to the
+    /// checkpoint and other courses to the last cycle standing
+    pub win_condition: Option<WinCondition>,
+    /// Default for the `dead_trail_policy` rule
+    pub dead_trail_policy: Option<DeadTrailPolicy>,
+    /// Set on generated courses: the walls and spawns are made again for
+    /// every game unless the seed is pinned
+    pub generator: Option<GeneratorSpec>,
+    /// Hand-written tags describing the layout; see `tags_with` for the full set
+    pub tags: Vec<String>,
+    /// Spawn point and heading of each seat, from a map; empty for the usual
+    /// corners and midpoints
+    pub spawns: Vec<(usize, usize, Direction)>,
+    /// Seed a random layout was generated from; `None` for fixed layouts
+    pub seed: Option<u64>,
+}
+
+impl Course {
+    /// Build a course from a map drawn one row per line: `#` is a wall, `X`
+    /// an obstruction, `.` empty, `F` the checkpoint, `P` a power-up spawn
+    /// point, and `1`-`8` the spawn point of that seat.
+    /// Blanks around each line are ignored. Without digits the usual spawn
+    /// points are used; each digit's seat faces its longest open run. The
+    /// course gets one seat per spawn point (4 without), 50-cell trails and
+    /// no tags; adjust the fields afterwards as needed.
+    pub fn from_ascii(name: &str, level: u32, text: &str) -> Result<Course, String> {
+        let rows: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err("map is empty".to_string());
+        }
+        if let Some(y) = rows.iter().position(|row| row.chars().count() != width) {
+            return Err(format!(
+                "map is not rectangular: row {} has {} cells, row 1 has {}",
+                y + 1,
+                rows[y].chars().count(),
+                width
+            ));
+        }
+
+        let mut course = Course {
+            name: name.to_string(),
+            level,
+            width,
+            height: rows.len(),
+            max_trail_length: Ruleset::default().max_trail_length,
+            max_players: 4,
+            obstructions: vec![],
+            walls: vec![],
+            checkpoint: None,
+            race: false,
+            powerups: vec![],
+            powerup_rate: None,
+            shrink_every: None,
+            lives: None,
+            max_ticks: None,
+            win_condition: None,
+            dead_trail_policy: None,
+            generator: None,
+            tags: vec![],
+            spawns: vec![],
+            seed: None,
+        };
+        let mut seats: Vec<(u32, usize, usize)> = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                match cell {
+                    '#' => course.walls.push((x, y)),
+                    'X' => course.obstructions.push((x, y)),
+                    '.' => {}
+                    'F' if course.checkpoint.is_some() => {
+                        return Err("map has more than one checkpoint 'F'".to_string());
+                    }
+                    'F' => course.checkpoint = Some((x, y)),
+                    'P' => course.powerups.push((x, y)),
+                    '1'..='8' => {
+                        let seat = cell as u32 - '0' as u32;
+                        if seats.iter().any(|&(s, _, _)| s == seat) {
+                            return Err(format!("map has spawn point {} twice", seat));
+                        }
+                        seats.push((seat, x, y));
+                    }
+                    other => {
+                        return Err(format!(
+                            "map has '{}' at ({}, {}); cells are '#', 'X', '.', 'F', 'P' or 1-8",
+                            other, x, y
+                        ));
+                    }
+                }
+            }
+        }
+
+        if !seats.is_empty() {
+            seats.sort();
+            if seats.len() < 2 {
+                return Err("map needs at least 2 spawn points, or none for the usual ones".to_string());
+            }
+            if let Some((missing, _)) = (1..).zip(&seats).find(|(n, (seat, _, _))| seat != n) {
+                return Err(format!(
+                    "spawn points must be numbered from 1 without gaps, but {} is missing",
+                    missing
+                ));
+            }
+            course.max_players = seats.len();
+            course.spawns = seats
+                .iter()
+                .map(|&(_, x, y)| (x, y, course.open_direction(x, y)))
+                .collect();
+        }
+        Ok(course)
+    }
+
+    /// Direction with the longest run of open cells from (x, y), stopping at
+    /// walls, obstructions and the border
+    fn open_direction(&self, x: usize, y: usize) -> Direction {
+        let blocked: HashSet<(usize, usize)> = self.walls.iter().chain(&self.obstructions).copied().collect();
+        let (w, h) = (self.width as i32, self.height as i32);
+        let run = |dir: Direction| {
+            let (dx, dy) = dir.delta();
+            let (mut cx, mut cy) = (x as i32 + dx, y as i32 + dy);
+            let mut len = 0;
+            while cx > 0 && cy > 0 && cx < w - 1 && cy < h - 1 && !blocked.contains(&(cx as usize, cy as usize)) {
+                len += 1;
+                cx += dx;
+                cy += dy;
+            }
+            len
+        };
+        // max_by_key keeps the last maximum, so ties go to Right, then Down
+        [Direction::Up, Direction::Left, Direction::Down, Direction::Right]
+            .into_iter()
+            .max_by_key(|&dir| run(dir))
+            .unwrap_or(Direction::Right)
+    }
+
+    /// Generate this course's layout from `seed`, replacing its walls and
+    /// spawns. Does nothing to courses without a generator.
+    pub fn generate_layout(&mut self, seed: u64) -> Result<(), String> {
+        let Some(spec) = self.generator else {
+            return Ok(());
+        };
+        // The generators size their work by the grid
+        self.check_size()?;
+        let seed = spec.seed.unwrap_or(seed);
+        let layout = match spec.generator {
+            Generator::Maze { corridor_width } => maze::generate(self.width, self.height, corridor_width, seed)?,
+            Generator::Scatter { symmetry } => scatter::generate(self.width, self.height, symmetry, seed),
+        };
+        self.walls = layout.walls;
+        self.spawns = layout.spawns;
+        self.seed = Some(seed);
+        Ok(())
+    }
+
+    /// Where each seat starts and which way it faces, in seat order
+    pub fn spawn_points(&self) -> Vec<(i32, i32, Direction)> {
+        if self.spawns.is_empty() {
+            return spawn_positions(self.width, self.height);
+        }
+        self.spawns
+            .iter()
+            .map(|&(x, y, dir)| (x as i32, y as i32, dir))
+            .collect()
+    }
+
+    /// The layout as a map at most `PREVIEW_SIZE` characters across, in the
+    /// glyphs `from_ascii` reads; see `render_ascii_at`
+    pub fn render_ascii(&self) -> String {
+        self.render_ascii_at(PREVIEW_SIZE).1
+    }
+
+    /// The layout drawn inside its border wall, shrunk by a whole factor so
+    /// neither side exceeds `max_size` characters: `#` walls, `X`
+    /// obstructions, `F` the checkpoint, `P` power-up spawn points and each
+    /// seat's number where it starts (`a` onwards past seat 9). A block of
+    /// cells shows its most telling glyph, seats first and empty cells last.
+    /// Returns the factor and the map, one row per line.
+    pub fn render_ascii_at(&self, max_size: usize) -> (usize, String) {
+        let walls: HashSet<&(usize, usize)> = self.walls.iter().collect();
+        let obstructions: HashSet<&(usize, usize)> = self.obstructions.iter().collect();
+        let powerups: HashSet<&(usize, usize)> = self.powerups.iter().collect();
+        let seats: HashMap<(usize, usize), usize> = self
+            .spawn_points()
+            .into_iter()
+            .take(self.max_players)
+            .enumerate()
+            .map(|(seat, (x, y, _))| ((x as usize, y as usize), seat))
+            .collect();
+        let (width, height) = (self.width + 2, self.height + 2);
+        let (scale, rows) = game::downsample(width, height, max_size, |x, y| {
+            if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                return ('#', 3);
+            }
+            let cell = (x - 1, y - 1);
+            if let Some(&seat) = seats.get(&cell) {
+                return (char::from_digit(seat as u32 + 1, 36).unwrap_or('?'), 5);
+            }
+            if self.checkpoint == Some(cell) {
+                ('F', 4)
+            } else if powerups.contains(&cell) {
+                ('P', 4)
+            } else if walls.contains(&cell) {
+                ('#', 3)
+            } else if obstructions.contains(&cell) {
+                ('X', 1)
+            } else {
+                ('.', 0)
+            }
+        });
+        (scale, rows.join("\n"))
+    }
+
+    /// One line naming the course, its size, seats and tags, and where it
+    /// came from (`source` is its file, or `None` for a built-in)
+    pub fn summary_line(&self, source: Option<&str>) -> String {
+        format!(
+            "Level {}: {} '{}' ({}x{}, up to {} players) [{}] from {}",
+            self.level,
+            self.name,
+            slug(&self.name),
+            self.width,
+            self.height,
+            self.max_players,
+            self.tags_with(&self.default_rules()).join(", "),
+            source.unwrap_or("built-in")
+        )
+    }
+
+    /// Whether power-ups can appear on this course
+    pub fn has_powerups(&self) -> bool {
+        !self.powerups.is_empty() || self.powerup_rate.is_some()
+    }
+
+    /// The rules a game on this course uses when nothing is overridden
+    pub fn default_rules(&self) -> Ruleset {
+        Ruleset {
+            max_trail_length: self.max_trail_length,
+            win_condition: self.win_condition.unwrap_or(if self.race {
+                WinCondition::Checkpoint
+            } else {
+                WinCondition::LastStanding
+            }),
+            boosts: self.has_powerups(),
+            shrink_every: self.shrink_every,
+            lives: self.lives,
+            max_ticks: self.max_ticks,
+            dead_trail_policy: self.dead_trail_policy.unwrap_or_default(),
+            ..Ruleset::default()
+        }
+    }
+
+    /// The course's own tags plus those derived from its size and the rules
+    /// it is played under
+    pub fn tags_with(&self, rules: &Ruleset) -> Vec<String> {
+        let mut tags = self.tags.clone();
+        let cells = self.width * self.height;
+        if cells <= SMALL_CELLS {
+            tags.push("small".to_string());
+        } else if cells >= LARGE_CELLS {
+            tags.push("large".to_string());
+        }
+        match rules.win_condition {
+            WinCondition::Checkpoint => tags.push("checkpoint".to_string()),
+            WinCondition::Territory => tags.push("territory".to_string()),
+            WinCondition::LastStanding => {}
+        }
+        if rules.wrap_edges {
+            tags.push("wrap".to_string());
+        }
+        if rules.boosts {
+            tags.push("powerups".to_string());
+        }
+        if rules.move_timeout_secs.is_some() {
+            tags.push("timed".to_string());
+        }
+        if rules.sudden_death_tick.is_some() {
+            tags.push("sudden-death".to_string());
+        }
+        if rules.team_size.is_some() {
+            tags.push("teams".to_string());
+        }
+        if rules.shrink_every.is_some() {
+            tags.push("shrinking".to_string());
+        }
+        if rules.lives.is_some() {
+            tags.push("lives".to_string());
+        }
+        tags
+    }
+
+    /// The checks a game needs to build its grid: the size is in range,
+    /// every wall and obstruction lies on it, and the declared spawn points
+    /// lie on it too, one seat per cell
+    pub fn check_grid(&self) -> Result<(), String> {
+        self.check_size()?;
+        let cells = [("wall", &self.walls), ("obstruction", &self.obstructions)];
+        for (kind, cells) in cells {
+            if let Some(&(x, y)) = cells.iter().find(|&&(x, y)| x >= self.width || y >= self.height) {
+                return Err(format!(
+                    "{} ({}, {}) is outside the {}x{} grid",
+                    kind, x, y, self.width, self.height
+                ));
+            }
+        }
+        for (seat, &(x, y, _)) in self.spawns.iter().enumerate() {
+            if x >= self.width || y >= self.height {
+                return Err(format!(
+                    "spawn point of seat {} ({}, {}) is outside the {}x{} grid",
+                    seat + 1,
+                    x,
+                    y,
+                    self.width,
+                    self.height
+                ));
+            }
+            if let Some(other) = self.spawns[..seat].iter().position(|&(ox, oy, _)| (ox, oy) == (x, y)) {
+                return Err(format!("seats {} and {} share the spawn point ({}, {})", other + 1, seat + 1, x, y));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the grid is neither too small to play on nor too big to
+    /// build
+    fn check_size(&self) -> Result<(), String> {
+        for (field, size) in [("width", self.width), ("height", self.height)] {
+            if !(MIN_GRID_SIZE..=MAX_COURSE_SIZE).contains(&size) {
+                return Err(format!(
+                    "{} must be between {} and {}",
+                    field, MIN_GRID_SIZE, MAX_COURSE_SIZE
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a course read from a file: its size and seats, that walls and
+    /// obstructions lie on the grid, and that every seat's spawn cell and
+    /// first step are free and its own
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_COURSE_NAME_LEN {
+            return Err(format!("name must be 1 to {} bytes", MAX_COURSE_NAME_LEN));
+        }
+        if self.level == 0 {
+            return Err("level must be at least 1".to_string());
+        }
+        self.check_grid()?;
+        let seats = self.spawn_points();
+        if !self.spawns.is_empty() && self.max_players > seats.len() {
+            return Err(format!(
+                "max_players is {} but the course has only {} spawn points",
+                self.max_players,
+                seats.len()
+            ));
+        }
+        if !(2..=seats.len()).contains(&self.max_players) {
+            return Err(format!("max_players must be between 2 and {}", seats.len()));
+        }
+        self.default_rules().validate(self)?;
+        if let Some(tag) = self.tags.iter().find(|t| !KNOWN_TAGS.contains(&t.as_str())) {
+            return Err(format!("unknown tag '{}'; known tags: {}", tag, KNOWN_TAGS.join(", ")));
+        }
+
+        let blocked: HashSet<(usize, usize)> = self.walls.iter().chain(&self.obstructions).copied().collect();
+        if let Some((x, y)) = self.checkpoint
+            && (x >= self.width || y >= self.height || blocked.contains(&(x, y)))
+        {
+            return Err(format!("checkpoint ({}, {}) is off the grid or blocked", x, y));
+        }
+
+        // The border is walled in every game that doesn't wrap
+        let (w, h) = (self.width as i32, self.height as i32);
+        let on_border = |(x, y): (i32, i32)| x <= 0 || y <= 0 || x >= w - 1 || y >= h - 1;
+        if let Some((x, y)) = self.checkpoint
+            && on_border((x as i32, y as i32))
+        {
+            return Err(format!("checkpoint ({}, {}) is on the border wall", x, y));
+        }
+        if let Some(&(x, y)) = self.powerups.iter().find(|&&(x, y)| {
+            x >= self.width || y >= self.height || blocked.contains(&(x, y)) || on_border((x as i32, y as i32))
+        }) {
+            return Err(format!("power-up spawn point ({}, {}) is off the grid, blocked or on the border", x, y));
+        }
+        if let Some(rate) = self.powerup_rate
+            && !(1..=MAX_POWERUP_RATE).contains(&rate)
+        {
+            return Err(format!("powerup_rate must be between 1 and {}", MAX_POWERUP_RATE));
+        }
+        let mut claimed: HashMap<(usize, usize), usize> = HashMap::new();
+        for (seat, &(x, y, dir)) in seats.iter().take(self.max_players).enumerate() {
+            let (dx, dy) = dir.delta();
+            for cell in [(x, y), (x + dx, y + dy)] {
+                if on_border(cell) {
+                    return Err(format!(
+                        "spawn area of seat {} reaches the border at ({}, {})",
+                        seat + 1,
+                        cell.0,
+                        cell.1
+                    ));
+                }
+                let cell = (cell.0 as usize, cell.1 as usize);
+                if blocked.contains(&cell) {
+                    return Err(format!(
+                        "spawn area of seat {} is blocked at ({}, {})",
+                        seat + 1,
+                        cell.0,
+                        cell.1
+                    ));
+                }
+                if let Some(other) = claimed.insert(cell, seat)
+                    && other != seat
+                {
+                    return Err(format!(
+                        "spawn areas of seats {} and {} overlap at ({}, {})",
+                        other + 1,
+                        seat + 1,
+                        cell.0,
+                        cell.1
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Course tags a player wants to play, or not to play
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagPreferences {
+    pub prefer: Vec<String>,
+    pub avoid: Vec<String>,
+}
+
+impl TagPreferences {
+    pub fn is_empty(&self) -> bool {
+        self.prefer.is_empty() && self.avoid.is_empty()
+    }
+
+    /// Combine several players' preferences: every preferred tag must be
+    /// present and every avoided tag absent
+    pub fn combine<'a>(all: impl IntoIterator<Item = &'a TagPreferences>) -> TagPreferences {
+        let mut combined = TagPreferences::default();
+        for prefs in all {
+            for tag in &prefs.prefer {
+                if !combined.prefer.contains(tag) {
+                    combined.prefer.push(tag.clone());
+                }
+            }
+            for tag in &prefs.avoid {
+                if !combined.avoid.contains(tag) {
+                    combined.avoid.push(tag.clone());
+                }
+            }
+        }
+        combined
+    }
+
+    pub fn matches(&self, tags: &[String]) -> bool {
+        self.prefer.iter().all(|t| tags.contains(t)) && !self.avoid.iter().any(|t| tags.contains(t))
+    }
+
+    /// Human-readable form, e.g. "prefer maze; avoid large"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.prefer.is_empty() {
+            parts.push(format!("prefer {}", self.prefer.join(", ")));
+        }
+        if !self.avoid.is_empty() {
+            parts.push(format!("avoid {}", self.avoid.join(", ")));
+        }
+        parts.join("; ")
+    }
+}
+
+/// Choose the course for a forming game. Without preferences this is the
+/// course for `max_level`; otherwise the highest course up to `max_level`
+/// whose tags satisfy everyone, falling back to the level-based course when
+/// none does. Returns the course and a note explaining the choice.
+pub fn select_course(max_level: u32, prefs: &TagPreferences) -> (Course, Option<String>) {
+    let level_based = get_course(max_level);
+    let level = level_based.level;
+    if prefs.is_empty() {
+        return (level_based, None);
+    }
+
+    let conflicts: Vec<&str> = prefs
+        .prefer
+        .iter()
+        .filter(|t| prefs.avoid.contains(t))
+        .map(String::as_str)
+        .collect();
+    if !conflicts.is_empty() {
+        return (
+            level_based,
+            Some(format!(
+                "Players' tag preferences conflict ({} both preferred and avoided), so the course was picked by level.",
+                conflicts.join(", ")
+            )),
+        );
+    }
+
+    let chosen = all_courses()
+        .into_iter()
+        .filter(|c| c.level <= level)
+        .filter(|c| prefs.matches(&c.tags_with(&c.default_rules())))
+        .max_by_key(|c| c.level);
+    match chosen {
+        Some(course) => {
+            let note = format!("{} was chosen for the players' tag preferences ({}).", course.name, prefs.describe());
+            (course, Some(note))
+        }
+        None => (
+            level_based,
+            Some(format!(
+                "No course up to level {} matches the players' tag preferences ({}), so the course was picked by level.",
+                level,
+                prefs.describe()
+            )),
+        ),
+    }
+}
+
+/// A course as written in a `--courses-dir` file: the grid is either given
+/// by `width`, `height`, `walls` and `obstructions`, or drawn as a `map`
+/// (see `Course::from_ascii`)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CourseFile {
+    name: String,
+    level: u32,
+    max_trail_length: usize,
+    /// Defaults to the number of spawn points
+    max_players: Option<usize>,
+    map: Option<String>,
+    width: Option<usize>,
+    height: Option<usize>,
+    #[serde(default)]
+    walls: Vec<(usize, usize)>,
+    #[serde(default)]
+    obstructions: Vec<(usize, usize)>,
+    /// Also accepted as `finish`; or draw it as `F` in the map
+    #[serde(alias = "finish")]
+    checkpoint: Option<(usize, usize)>,
+    #[serde(default)]
+    race: bool,
+    /// Added to any `P` cells in the map
+    #[serde(default)]
+    powerups: Vec<(usize, usize)>,
+    powerup_rate: Option<u32>,
+    shrink_every: Option<u32>,
+    lives: Option<u32>,
+    max_ticks: Option<u32>,
+    /// "last_standing", "checkpoint" or "territory"; overrides `race`
+    win_condition: Option<WinCondition>,
+    dead_trail_policy: Option<DeadTrailPolicy>,
+    /// `[x, y, heading]` per seat, for courses without a map; or draw
+    /// them as digits in the map
+    #[serde(default)]
+    spawns: Vec<(usize, usize, Direction)>,
+    /// "maze" or "scatter" to generate the walls and spawns instead of
+    /// listing them
+    generator: Option<String>,
+    /// Corridor width of a generated maze (1-5, default 2)
+    corridor_width: Option<usize>,
+    /// Symmetry of a scattered layout (default rotational)
+    symmetry: Option<Symmetry>,
+    /// Pins a generated layout; without it every game gets a new one
+    seed: Option<u64>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl CourseFile {
+    fn into_course(self) -> Result<Course, String> {
+        let generator = match self.generator.as_deref() {
+            None if self.corridor_width.is_some() || self.symmetry.is_some() || self.seed.is_some() => {
+                return Err("corridor_width, symmetry and seed only apply to a course with a `generator`".to_string());
+            }
+            None => None,
+            Some(kind @ ("maze" | "scatter")) => {
+                if self.map.is_some() || !self.walls.is_empty() || !self.obstructions.is_empty() || !self.spawns.is_empty() {
+                    return Err("a generated course cannot also set a map, walls, obstructions or spawns".to_string());
+                }
+                let generator = if kind == "maze" {
+                    if self.symmetry.is_some() {
+                        return Err("symmetry only applies to `generator = \"scatter\"`".to_string());
+                    }
+                    Generator::Maze {
+                        corridor_width: self.corridor_width.unwrap_or(maze::DEFAULT_CORRIDOR_WIDTH),
+                    }
+                } else {
+                    if self.corridor_width.is_some() {
+                        return Err("corridor_width only applies to `generator = \"maze\"`".to_string());
+                    }
+                    Generator::Scatter {
+                        symmetry: self.symmetry.unwrap_or(Symmetry::Rotational),
+                    }
+                };
+                Some(GeneratorSpec { generator, seed: self.seed })
+            }
+            Some(other) => return Err(format!("unknown generator '{}'; use 'maze' or 'scatter'", other)),
+        };
+        let mut course = match &self.map {
+            Some(map) => {
+                if self.width.is_some()
+                    || self.height.is_some()
+                    || !self.walls.is_empty()
+                    || !self.obstructions.is_empty()
+                    || !self.spawns.is_empty()
+                {
+                    return Err("a course with a map cannot also set width, height, walls, obstructions or spawns".to_string());
+                }
+                Course::from_ascii(&self.name, self.level, map)?
+            }
+            None => Course {
+                name: self.name,
+                level: self.level,
+                width: self.width.ok_or("missing field `width` (or draw a `map`)")?,
+                height: self.height.ok_or("missing field `height` (or draw a `map`)")?,
+                max_trail_length: self.max_trail_length,
+                max_players: match self.max_players {
+                    Some(max) => max,
+                    None if !self.spawns.is_empty() => self.spawns.len(),
+                    None if generator.is_some() => 4,
+                    None => return Err("missing field `max_players` (or list `spawns`)".to_string()),
+                },
+                obstructions: self.obstructions,
+                walls: self.walls,
+                checkpoint: None,
+                race: false,
+                powerups: Vec::new(),
+                powerup_rate: None,
+                shrink_every: None,
+                lives: None,
+                max_ticks: None,
+                win_condition: None,
+                dead_trail_policy: None,
+                generator,
+                tags: Vec::new(),
+                spawns: self.spawns,
+                seed: None,
+            },
+        };
+        course.max_trail_length = self.max_trail_length;
+        course.max_players = self.max_players.unwrap_or(course.max_players);
+        if self.checkpoint.is_some() {
+            if course.checkpoint.is_some() {
+                return Err("a course with an 'F' in its map cannot also set checkpoint".to_string());
+            }
+            course.checkpoint = self.checkpoint;
+        }
+        course.race = self.race;
+        course.powerups.extend(self.powerups);
+        course.powerup_rate = self.powerup_rate;
+        course.shrink_every = self.shrink_every;
+        course.lives = self.lives;
+        course.max_ticks = self.max_ticks;
+        course.win_condition = self.win_condition;
+        course.dead_trail_policy = self.dead_trail_policy;
+        course.tags = self.tags;
+        // Unpinned layouts are made again for every game; this one is only
+        // for checking the course, once its size is known to be sane
+        course.check_grid()?;
+        course.generate_layout(0)?;
+        Ok(course)
+    }
+}
+
+/// Parse and validate the contents of one course file: TOML when `file`
+/// ends in `.toml`, JSON otherwise
+pub fn parse_course_file(file: &str, data: &str) -> Result<Course, String> {
+    let parsed = if file.ends_with(".toml") {
+        toml::from_str::<CourseFile>(data).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str::<CourseFile>(data).map_err(|e| e.to_string())
+    };
+    let course = parsed.and_then(CourseFile::into_course)?;
+    course.validate()?;
+    Ok(course)
+}
+
+/// Read and validate every `*.json` and `*.toml` course in `dir`, in file
+/// name order. Returns each course with its file name.
+pub fn load_courses_dir(dir: &Path) -> Result<Vec<(Course, String)>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("cannot read {}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("json" | "toml")))
+        .collect();
+    paths.sort();
+
+    let mut courses: Vec<(Course, String)> = Vec::new();
+    for path in paths {
+        let file = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let data = std::fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let course = parse_course_file(&file, &data).map_err(|e| format!("{}: {}", file, e))?;
+        if let Some((_, other)) = courses.iter().find(|(c, _)| c.level == course.level) {
+            return Err(format!("{} and {} both define level {}", other, file, course.level));
+        }
+        courses.push((course, file));
+    }
+    Ok(courses)
+}
+
+/// Make loaded courses part of `all_courses()`: each replaces the built-in
+/// course of its level or adds a level after them. Levels must still run
+/// from 1 without gaps. Only done once, at startup.
+pub fn install_courses(custom: Vec<(Course, String)>) -> Result<(), String> {
+    let merged = merge_courses(&custom, 0);
+    if let Some((missing, _)) = (1..).zip(&merged).find(|(level, (c, _))| c.level != *level) {
+        return Err(format!("course levels must run from 1 without gaps, but level {} is missing", missing));
+    }
+    for (i, (course, file)) in merged.iter().enumerate() {
+        if let Some((other, _)) = merged[..i].iter().find(|(c, _)| slug(&c.name) == slug(&course.name)) {
+            return Err(format!(
+                "{}: course name '{}' is already taken by level {} ({})",
+                file.as_deref().unwrap_or("built-in"),
+                course.name,
+                other.level,
+                other.name
+            ));
+        }
+    }
+    CUSTOM_COURSES
+        .set(custom)
+        .map_err(|_| "courses were already loaded".to_string())
+}
+
+/// Built-in courses with the custom ones laid over them, ordered by level
+fn merge_courses(custom: &[(Course, String)], seed: u64) -> Vec<(Course, Option<String>)> {
+    let mut courses: Vec<(Course, Option<String>)> = builtin_courses(seed).into_iter().map(|c| (c, None)).collect();
+    for (course, file) in custom {
+        let mut course = course.clone();
+        // Loading generated it once, and the size is all that can fail
+        course.generate_layout(seed).expect("generated course was checked when loaded");
+        let level = course.level;
+        let entry = (course, Some(file.clone()));
+        match courses.iter_mut().find(|(c, _)| c.level == level) {
+            Some(slot) => *slot = entry,
+            None => courses.push(entry),
+        }
+    }
+    courses.sort_by_key(|(c, _)| c.level);
+    courses
+}
+
+/// Lowercase a course name and join its words with dashes, so "The
+/// Gauntlet" is `the-gauntlet`
+pub fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Every playable course, built-in and loaded, each with the file it came
+/// from (`None` for built-in ones). Levels and name slugs are unique, so a
+/// course can be looked up by either.
+pub struct CourseRegistry {
+    courses: Vec<(Course, Option<String>)>,
+}
+
+impl CourseRegistry {
+    /// The current courses, with fresh random layouts
+    pub fn current() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// The current courses, with random layouts generated from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        CourseRegistry {
+            courses: merge_courses(CUSTOM_COURSES.get().map_or(&[], Vec::as_slice), seed),
+        }
+    }
+
+    pub fn get_by_level(&self, level: u32) -> Option<&Course> {
+        self.courses.iter().map(|(c, _)| c).find(|c| c.level == level)
+    }
+
+    /// The course called `name`, compared by slug, so "the gauntlet" and
+    /// `the-gauntlet` both find The Gauntlet
+    pub fn get_by_name(&self, name: &str) -> Option<&Course> {
+        let wanted = slug(name);
+        self.courses.iter().map(|(c, _)| c).find(|c| slug(&c.name) == wanted)
+    }
+
+    /// Every course with its source file, ordered by level
+    pub fn list(&self) -> &[(Course, Option<String>)] {
+        &self.courses
+    }
+
+    /// The highest level; levels run from 1 without gaps
+    pub fn max_level(&self) -> u32 {
+        self.courses.len() as u32
+    }
 }
 
 /// Get all available courses, ordered by difficulty
 pub fn all_courses() -> Vec<Course> {
+    CourseRegistry::current().courses.into_iter().map(|(course, _)| course).collect()
+}
+
+/// Built-in courses, with random layouts generated from `seed`
+fn builtin_courses(seed: u64) -> Vec<Course> {
     vec![
         course_open_arena(),
         course_the_maze(),
         course_narrow_corridors(),
         course_the_gauntlet(),
-        course_chaos(),
+        course_chaos(seed),
+        course_the_sprint(),
+        course_open_arena_classic(),
+        course_the_labyrinth(seed),
+        course_spiral(),
+        course_rings(),
+        course_crossroads(),
     ]
 }
 
-/// Get a course by level number (1-indexed)
+/// Get a course by level number (1-indexed), with a random layout if it has one
 pub fn get_course(level: u32) -> Course {
-    let courses = all_courses();
-    let idx = ((level as usize).saturating_sub(1)).min(courses.len() - 1);
-    courses[idx].clone()
+    get_course_with_seed(level, rand::random())
+}
+
+/// Get a course by level number (1-indexed). Random layouts come from
+/// `seed`, so the same seed always gives the same course.
+pub fn get_course_with_seed(level: u32, seed: u64) -> Course {
+    let registry = CourseRegistry::with_seed(seed);
+    let level = level.clamp(1, registry.max_level());
+    registry.get_by_level(level).expect("levels run from 1 without gaps").clone()
+}
+
+fn tags(tags: &[&str]) -> Vec<String> {
+    tags.iter().map(|t| t.to_string()).collect()
 }
 
 fn course_open_arena() -> Course {
@@ -41,36 +947,78 @@ fn course_open_arena() -> Course {
         max_players: 4,
         obstructions: vec![],
         walls: vec![],
+        checkpoint: None,
+        race: false,
+        powerups: vec![],
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(2000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: tags(&["open"]),
+        spawns: vec![
+            (3, 3, Direction::Right),
+            (26, 26, Direction::Left),
+            (26, 3, Direction::Down),
+            (3, 26, Direction::Up),
+        ],
+        seed: None,
     }
 }
 
 fn course_the_maze() -> Course {
-    let mut walls = Vec::new();
-    // Horizontal walls
-    for x in 8..22 {
-        walls.push((x, 10));
-        walls.push((x, 25));
-    }
-    // Vertical walls
-    for y in 10..20 {
-        walls.push((15, y));
-    }
-    for y in 5..15 {
-        walls.push((25, y));
-    }
-    for y in 20..30 {
-        walls.push((8, y));
-    }
-
+    // The border wall comes with every game, so the map leaves it out
+    let map = r"
+        ........................................
+        ........................................
+        ........................................
+        ........................................
+        ........................................
+        .........................#..............
+        .........................#..............
+        .........................#..............
+        .........................#..............
+        .........................#..............
+        ........##############...#..............
+        ...............#.........#..............
+        ...............#.........#..............
+        ...............#.........#..............
+        ...............#.........#..............
+        ...............#........................
+        ...............#........................
+        ...............#........................
+        ...............#........................
+        ...............#........................
+        ........#...............................
+        ........#...............................
+        ........#...............................
+        ........#...............................
+        ........#...............................
+        ........##############..................
+        ........#...............................
+        ........#...............................
+        ........#...............................
+        ........#...............................
+        ........................................
+        ........................................
+        ........................................
+        ........................................
+        ........................................
+    ";
     Course {
-        name: "The Maze".to_string(),
-        level: 2,
-        width: 40,
-        height: 35,
         max_trail_length: 80,
-        max_players: 4,
-        obstructions: vec![],
-        walls,
+        max_ticks: Some(3000),
+        tags: tags(&["maze", "walls"]),
+        // In the open border lanes, clear of every maze wall
+        spawns: vec![
+            (3, 3, Direction::Right),
+            (36, 31, Direction::Left),
+            (36, 3, Direction::Down),
+            (3, 31, Direction::Up),
+        ],
+        ..Course::from_ascii("The Maze", 2, map).expect("built-in map is valid")
     }
 }
 
@@ -78,10 +1026,10 @@ fn course_narrow_corridors() -> Course {
     let mut walls = Vec::new();
     // Create horizontal corridor dividers
     for x in 0..50 {
-        if x < 10 || x > 15 {
+        if !(10..=15).contains(&x) {
             walls.push((x, 7));
         }
-        if x < 30 || x > 40 {
+        if !(30..=40).contains(&x) {
             walls.push((x, 14));
         }
     }
@@ -95,6 +1043,26 @@ fn course_narrow_corridors() -> Course {
         max_players: 4,
         obstructions: vec![],
         walls,
+        checkpoint: None,
+        race: false,
+        powerups: vec![],
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(3000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: tags(&["corridors", "walls"]),
+        // Two seats in each outer corridor, on separate rows heading for
+        // each other's end
+        spawns: vec![
+            (3, 2, Direction::Right),
+            (46, 19, Direction::Left),
+            (46, 5, Direction::Left),
+            (3, 16, Direction::Right),
+        ],
+        seed: None,
     }
 }
 
@@ -119,41 +1087,574 @@ fn course_the_gauntlet() -> Course {
         max_players: 6,
         obstructions,
         walls: vec![],
+        checkpoint: None,
+        race: false,
+        // In the open lanes between the obstruction blocks
+        powerups: vec![(14, 14), (44, 14), (30, 20), (14, 26), (44, 26)],
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(4000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: tags(&["obstacles"]),
+        // Corners and the middle of the long sides, each in an open lane
+        spawns: vec![
+            (3, 3, Direction::Right),
+            (56, 36, Direction::Left),
+            (56, 3, Direction::Down),
+            (3, 36, Direction::Up),
+            (27, 3, Direction::Down),
+            (32, 36, Direction::Up),
+        ],
+        seed: None,
+    }
+}
+
+fn course_chaos(seed: u64) -> Course {
+    // Random walls, but turned a quarter at a time about the middle, so no
+    // seat gets a harder corner than the others
+    let mut course = Course {
+        name: "Chaos".to_string(),
+        level: 5,
+        width: 80,
+        height: 80,
+        max_trail_length: 300,
+        max_players: 8,
+        obstructions: vec![],
+        walls: vec![],
+        checkpoint: None,
+        race: false,
+        powerups: vec![],
+        powerup_rate: Some(30),
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(6000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: Some(GeneratorSpec {
+            generator: Generator::Scatter {
+                symmetry: Symmetry::Rotational,
+            },
+            seed: None,
+        }),
+        tags: tags(&["random", "walls"]),
+        spawns: vec![],
+        seed: None,
+    };
+    course.generate_layout(seed).expect("scattered layouts always generate");
+    course
+}
+
+fn course_the_sprint() -> Course {
+    // Every seat is the same distance from the checkpoint in the middle; the
+    // gaps in the outer ring sit mid-side and those in the inner ring at the
+    // corners, so nobody gets a straight run at it
+    let map = r"
+        ...............................
+        ...............................
+        ..1.........................3..
+        ...............................
+        ...............................
+        ...............................
+        ......########...########......
+        ......#.................#......
+        ......#.................#......
+        ......#.................#......
+        ......#.................#......
+        ......#......#####......#......
+        ......#.................#......
+        ......#....#.......#....#......
+        ...........#.......#...........
+        ...........#...F...#...........
+        ...........#.......#...........
+        ......#....#.......#....#......
+        ......#.................#......
+        ......#......#####......#......
+        ......#.................#......
+        ......#.................#......
+        ......#.................#......
+        ......#.................#......
+        ......########...########......
+        ...............................
+        ...............................
+        ...............................
+        ..4.........................2..
+        ...............................
+        ...............................
+    ";
+    Course {
+        max_trail_length: 60,
+        race: true,
+        max_ticks: Some(1500),
+        tags: tags(&["walls"]),
+        ..Course::from_ascii("The Sprint", 6, map).expect("built-in map is valid")
+    }
+}
+
+fn course_open_arena_classic() -> Course {
+    // Classic Tron: trails never fade, so the arena only ever fills up
+    Course {
+        name: "Open Arena Classic".to_string(),
+        level: 7,
+        max_trail_length: 0,
+        tags: tags(&["open", "classic"]),
+        ..course_open_arena()
     }
 }
 
-fn course_chaos() -> Course {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
+fn course_the_labyrinth(seed: u64) -> Course {
+    // A fresh maze every game, so there is no layout to learn by heart
+    let mut course = Course {
+        name: "The Labyrinth".to_string(),
+        level: 8,
+        width: 40,
+        height: 40,
+        max_trail_length: 120,
+        max_players: 4,
+        obstructions: vec![],
+        walls: vec![],
+        checkpoint: None,
+        race: false,
+        powerups: vec![],
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(4000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: Some(GeneratorSpec {
+            generator: Generator::Maze {
+                corridor_width: maze::DEFAULT_CORRIDOR_WIDTH,
+            },
+            seed: None,
+        }),
+        tags: tags(&["maze", "walls", "random"]),
+        spawns: vec![],
+        seed: None,
+    };
+    course.generate_layout(seed).expect("built-in maze is valid");
+    course
+}
+
+fn course_spiral() -> Course {
+    // One wall winds in from the top-left corner, leaving a 3-wide corridor
+    // that spirals to a dead end in the middle: whoever rides in is
+    // committed for a long way
+    let (size, inset, pitch): (usize, usize, usize) = (33, 4, 4);
+    let span = size - 1 - 2 * inset;
     let mut walls = Vec::new();
+    let (mut x, mut y) = (inset as i32, inset as i32);
+    for (side, (dx, dy)) in [(1, 0), (0, 1), (-1, 0), (0, -1)].into_iter().cycle().enumerate() {
+        // Each side is as long as the one across from it, less a pitch for
+        // every lap wound in so far
+        let laps = if side % 2 == 0 { side.saturating_sub(1) / 2 } else { side / 2 };
+        let Some(len) = span.checked_sub(pitch * laps).filter(|&len| len >= pitch) else {
+            break;
+        };
+        for _ in 0..len {
+            walls.push((x as usize, y as usize));
+            x += dx;
+            y += dy;
+        }
+    }
+    walls.push((x as usize, y as usize));
 
-    // Random wall segments
-    for _ in 0..30 {
-        let sx = rng.gen_range(5..70);
-        let sy = rng.gen_range(5..70);
-        let horizontal = rng.gen_bool(0.5);
-        let length = rng.gen_range(3..10);
+    Course {
+        name: "Spiral".to_string(),
+        level: 9,
+        width: size,
+        height: size,
+        max_trail_length: 90,
+        max_players: 4,
+        obstructions: vec![],
+        walls,
+        checkpoint: None,
+        race: false,
+        powerups: vec![],
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(3000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: tags(&["corridors", "walls"]),
+        spawns: vec![
+            (2, 2, Direction::Right),
+            (30, 30, Direction::Left),
+            (30, 2, Direction::Down),
+            (2, 30, Direction::Up),
+        ],
+        seed: None,
+    }
+}
 
-        for i in 0..length {
-            let (wx, wy) = if horizontal {
-                (sx + i, sy)
-            } else {
-                (sx, sy + i)
-            };
-            if wx < 79 && wy < 79 {
-                walls.push((wx, wy));
+fn course_rings() -> Course {
+    // Three walled squares one inside the other, each with two 3-cell gaps;
+    // the gaps alternate between top/bottom and left/right, so reaching the
+    // middle means riding half way round every ring
+    let size: usize = 41;
+    let mid = size / 2;
+    let mut walls = Vec::new();
+    for (ring, inset) in [5, 10, 15].into_iter().enumerate() {
+        let far = size - 1 - inset;
+        for k in inset..=far {
+            for (x, y) in [(k, inset), (k, far), (inset, k), (far, k)] {
+                let gap = if ring % 2 == 0 {
+                    (y == inset || y == far) && x.abs_diff(mid) <= 1
+                } else {
+                    (x == inset || x == far) && y.abs_diff(mid) <= 1
+                };
+                if !gap && !walls.contains(&(x, y)) {
+                    walls.push((x, y));
+                }
             }
         }
     }
 
     Course {
-        name: "Chaos".to_string(),
-        level: 5,
-        width: 80,
-        height: 80,
-        max_trail_length: 300,
+        name: "Rings".to_string(),
+        level: 10,
+        width: size,
+        height: size,
+        max_trail_length: 120,
         max_players: 8,
         obstructions: vec![],
         walls,
+        checkpoint: None,
+        race: false,
+        powerups: vec![],
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(4000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: tags(&["walls"]),
+        spawns: vec![
+            (3, 3, Direction::Right),
+            (37, 37, Direction::Left),
+            (37, 3, Direction::Down),
+            (3, 37, Direction::Up),
+            (7, 7, Direction::Down),
+            (33, 33, Direction::Up),
+            (33, 7, Direction::Left),
+            (7, 33, Direction::Right),
+        ],
+        seed: None,
+    }
+}
+
+fn course_crossroads() -> Course {
+    // Four solid blocks leave a lane round the edge and two 5-wide roads
+    // that cross in an open square; every seat starts at a road's end,
+    // pointed at the middle
+    let (size, lane) = (41, 4);
+    let road = 18..=22;
+    let square = 14..=26;
+    let mut walls = Vec::new();
+    for y in lane..size - lane {
+        for x in lane..size - lane {
+            let open = road.contains(&x) || road.contains(&y) || (square.contains(&x) && square.contains(&y));
+            if !open {
+                walls.push((x, y));
+            }
+        }
+    }
+
+    Course {
+        name: "Crossroads".to_string(),
+        level: 11,
+        width: size,
+        height: size,
+        max_trail_length: 100,
+        max_players: 4,
+        obstructions: vec![],
+        walls,
+        checkpoint: None,
+        race: false,
+        powerups: vec![],
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: Some(3000),
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: tags(&["corridors", "walls"]),
+        spawns: vec![
+            (20, 2, Direction::Down),
+            (20, 38, Direction::Up),
+            (38, 20, Direction::Left),
+            (2, 20, Direction::Right),
+        ],
+        seed: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every seat spawns on its own empty cell with room to ride forward,
+    /// and every seat can reach every other
+    fn check_spawns(course: &Course) {
+        let blocked: HashSet<(usize, usize)> = course.walls.iter().chain(&course.obstructions).copied().collect();
+        let open = |(x, y): (i32, i32)| {
+            x >= 0
+                && y >= 0
+                && (x as usize) < course.width
+                && (y as usize) < course.height
+                && !blocked.contains(&(x as usize, y as usize))
+        };
+        let seats: Vec<(i32, i32, Direction)> = course.spawn_points().into_iter().take(course.max_players).collect();
+        assert!(seats.len() >= 2, "{} has {} seats", course.name, seats.len());
+
+        for (seat, &(x, y, direction)) in seats.iter().enumerate() {
+            assert!(open((x, y)), "{}: seat {} spawns on ({}, {})", course.name, seat + 1, x, y);
+            let (dx, dy) = direction.delta();
+            assert!(open((x + dx, y + dy)), "{}: seat {} faces a wall", course.name, seat + 1);
+            assert!(
+                !seats[..seat].iter().any(|&(ox, oy, _)| (ox, oy) == (x, y)),
+                "{}: seat {} shares its spawn",
+                course.name,
+                seat + 1
+            );
+        }
+
+        let mut reached = HashSet::from([(seats[0].0, seats[0].1)]);
+        let mut frontier = vec![(seats[0].0, seats[0].1)];
+        while let Some((x, y)) = frontier.pop() {
+            for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+                let next = (x + dx, y + dy);
+                if open(next) && reached.insert(next) {
+                    frontier.push(next);
+                }
+            }
+        }
+        for (seat, &(x, y, _)) in seats.iter().enumerate() {
+            assert!(reached.contains(&(x, y)), "{}: seat {} is walled off from seat 1", course.name, seat + 1);
+        }
+    }
+
+    #[test]
+    fn builtin_spawns_are_reachable_empty_cells() {
+        for seed in 0..20 {
+            for course in builtin_courses(seed) {
+                check_spawns(&course);
+            }
+        }
+    }
+
+    #[test]
+    fn generated_maze_spawns_are_reachable_empty_cells() {
+        for corridor_width in 1..=maze::MAX_CORRIDOR_WIDTH {
+            let smallest = (MIN_GRID_SIZE..)
+                .find(|&size| maze::generate(size, size, corridor_width, 0).is_ok())
+                .unwrap();
+            let sizes = [(smallest, smallest), (smallest + 3, smallest + 7), (smallest * 2 + 1, smallest), (60, 40)];
+            for (width, height) in sizes {
+                for seed in 0..10 {
+                    let mut course = course_the_labyrinth(seed);
+                    course.width = width;
+                    course.height = height;
+                    course.generator = Some(GeneratorSpec {
+                        generator: Generator::Maze { corridor_width },
+                        seed: None,
+                    });
+                    course.generate_layout(seed).unwrap();
+                    course.check_grid().unwrap();
+                    course.name = format!("{}x{} maze, corridors {} wide, seed {}", width, height, corridor_width, seed);
+                    check_spawns(&course);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_walls() {
+        let random_levels: Vec<u32> = builtin_courses(0)
+            .iter()
+            .filter(|c| c.seed.is_some() || c.generator.is_some())
+            .map(|c| c.level)
+            .collect();
+        assert!(random_levels.contains(&5), "{:?}", random_levels);
+        for level in random_levels {
+            let course = get_course_with_seed(level, 42);
+            assert_eq!(course.seed, Some(42), "{}", course.name);
+            assert_eq!(course.walls, get_course_with_seed(level, 42).walls, "{}", course.name);
+            assert_eq!(course.spawns, get_course_with_seed(level, 42).spawns, "{}", course.name);
+            assert_ne!(course.walls, get_course_with_seed(level, 43).walls, "{}", course.name);
+        }
+    }
+
+    #[test]
+    fn chaos_is_the_same_from_every_corner() {
+        for seed in 0..10 {
+            let course = course_chaos(seed);
+            assert_eq!(course.width, course.height);
+            let right = course.width - 1;
+            let walls: HashSet<(usize, usize)> = course.walls.iter().copied().collect();
+            let turned: HashSet<(usize, usize)> = walls.iter().map(|&(x, y)| (right - y, x)).collect();
+            assert_eq!(turned, walls, "seed {}", seed);
+            check_spawns(&course);
+        }
+    }
+
+    #[test]
+    fn from_ascii_refuses_bad_maps() {
+        let err = |map: &str| Course::from_ascii("Bad", 12, map).err().unwrap();
+        assert_eq!(err("\n  \n"), "map is empty");
+        assert_eq!(err("....\n...\n...."), "map is not rectangular: row 2 has 3 cells, row 1 has 4");
+        assert_eq!(err("1...\n...."), "map needs at least 2 spawn points, or none for the usual ones");
+        assert_eq!(err("1..3\n...."), "spawn points must be numbered from 1 without gaps, but 2 is missing");
+        assert_eq!(err("1..1\n.2.."), "map has spawn point 1 twice");
+        assert_eq!(err("F..F\n...."), "map has more than one checkpoint 'F'");
+        assert_eq!(err("..a.\n...."), "map has 'a' at (2, 0); cells are '#', 'X', '.', 'F', 'P' or 1-8");
+    }
+
+    #[test]
+    fn map_course_files_need_a_spawn_point_per_seat() {
+        let map = r#"{"name": "Pair", "level": 12, "max_trail_length": 40, "max_players": 4,
+            "map": "..........\n.1......2.\n..........\n..........\n..........\n..........\n..........\n.........."}"#;
+        let err = parse_course_file("pair.json", map).unwrap_err();
+        assert_eq!(err, "max_players is 4 but the course has only 2 spawn points");
+        let course = parse_course_file("pair.json", &map.replace(r#""max_players": 4,"#, "")).unwrap();
+        assert_eq!(course.max_players, 2);
+        assert_eq!(course.spawns, [(1, 1, Direction::Right), (8, 1, Direction::Left)]);
+    }
+
+    /// A small open course file, as written to a courses directory
+    fn course_toml(name: &str, level: u32) -> String {
+        format!("name = \"{}\"\nlevel = {}\nwidth = 20\nheight = 20\nmax_trail_length = 40\nmax_players = 4\n", name, level)
+    }
+
+    #[test]
+    fn example_course_files_load() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/courses");
+        let courses = load_courses_dir(&dir).unwrap();
+        let files: Vec<&str> = courses.iter().map(|(_, file)| file.as_str()).collect();
+        assert_eq!(files, ["box.json", "ring.toml"]);
+
+        let (ring, _) = &courses[1];
+        assert_eq!((ring.name.as_str(), ring.level, ring.width, ring.height), ("Ring", 12, 24, 24));
+        assert_eq!(ring.walls, [(10, 10), (11, 10), (12, 10), (13, 10)]);
+        assert_eq!(ring.tags, ["walls"]);
+        let (map, _) = &courses[0];
+        assert_eq!((map.level, map.width, map.height, map.max_players), (13, 12, 8, 4));
+        assert_eq!(map.obstructions.len(), 4);
+        check_spawns(ring);
+        check_spawns(map);
+    }
+
+    #[test]
+    fn course_files_with_overlapping_spawns_are_refused() {
+        let tight = |spawns: &str| {
+            let file = format!(
+                "name = \"Tight\"\nlevel = 12\nwidth = 20\nheight = 20\nmax_trail_length = 40\nspawns = {}\n",
+                spawns
+            );
+            parse_course_file("tight.toml", &file).unwrap_err()
+        };
+        assert_eq!(tight(r#"[[3, 3, "Right"], [4, 3, "Left"]]"#), "spawn areas of seats 1 and 2 overlap at (4, 3)");
+        assert_eq!(tight(r#"[[3, 3, "Right"], [3, 3, "Left"]]"#), "seats 1 and 2 share the spawn point (3, 3)");
+    }
+
+    #[test]
+    fn course_files_with_cells_off_the_grid_are_refused() {
+        let err = parse_course_file("far.toml", &format!("{}walls = [[20, 5]]\n", course_toml("Far", 12))).unwrap_err();
+        assert_eq!(err, "wall (20, 5) is outside the 20x20 grid");
+        let err =
+            parse_course_file("far.toml", &format!("{}obstructions = [[5, 31]]\n", course_toml("Far", 12))).unwrap_err();
+        assert_eq!(err, "obstruction (5, 31) is outside the 20x20 grid");
+    }
+
+    #[test]
+    fn loaded_courses_cannot_share_a_level() {
+        let dir = crate::testutil::ScratchDir::new();
+        std::fs::write(dir.path().join("a.toml"), course_toml("Ring", 12)).unwrap();
+        std::fs::write(dir.path().join("b.toml"), course_toml("Box", 12)).unwrap();
+        let err = load_courses_dir(dir.path()).err().unwrap();
+        assert_eq!(err, "a.toml and b.toml both define level 12");
+    }
+
+    #[test]
+    fn registry_refuses_level_gaps_and_taken_names() {
+        let parse = |name: &str, level: u32| {
+            let course = parse_course_file("c.toml", &course_toml(name, level)).unwrap();
+            vec![(course, "c.toml".to_string())]
+        };
+        let max = builtin_courses(0).len() as u32;
+
+        let err = install_courses(parse("Ring", max + 2)).unwrap_err();
+        assert!(err.contains(&format!("level {} is missing", max + 1)), "{}", err);
+        let err = install_courses(parse("the GAUNTLET", max + 1)).unwrap_err();
+        assert!(err.starts_with("c.toml: course name 'the GAUNTLET' is already taken by level 4 (The Gauntlet)"), "{}", err);
+        assert!(CUSTOM_COURSES.get().is_none());
+    }
+
+    #[test]
+    fn registry_finds_courses_by_level_and_name() {
+        let registry = CourseRegistry::with_seed(0);
+        assert_eq!(registry.max_level() as usize, registry.list().len());
+        assert_eq!(registry.get_by_level(4).unwrap().name, "The Gauntlet");
+        assert_eq!(registry.get_by_name("the gauntlet").unwrap().level, 4);
+        assert_eq!(registry.get_by_name("The-Gauntlet").unwrap().level, 4);
+        assert!(registry.get_by_level(0).is_none());
+        assert!(registry.get_by_name("nowhere").is_none());
+    }
+
+    #[test]
+    fn new_courses_play_a_bot_game_to_the_end() {
+        use crate::bot::{self, BotParams, Difficulty};
+        use crate::game::{Game, GameStatus};
+
+        for course in [course_spiral(), course_rings(), course_crossroads()] {
+            course.validate().unwrap();
+            let mut game = Game::new(&course, course.default_rules()).unwrap();
+            for seat in 0..course.max_players {
+                let idx = game.add_player(format!("Bot {}", seat + 1)).unwrap();
+                game.players[idx].bot = Some(BotParams::for_difficulty(Difficulty::Easy, 0.5));
+            }
+            game.start();
+            for _ in 0..course.max_ticks.unwrap_or(5000) {
+                if game.status() != GameStatus::Running {
+                    break;
+                }
+                bot::play_turn(&mut game);
+            }
+            assert_eq!(game.status(), GameStatus::Finished, "{} did not finish", course.name);
+        }
+    }
+
+    /// The seed random built-in layouts are pinned at
+    const SNAPSHOT_SEED: u64 = 1;
+
+    /// Each built-in course's preview is pinned in `src/snapshots/courses`,
+    /// so an edit to a course shows up here. Run with `UPDATE_SNAPSHOTS=1`
+    /// to rewrite them after a deliberate change.
+    #[test]
+    fn builtin_previews_match_their_snapshots() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/snapshots/courses");
+        let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+        let mut changed = Vec::new();
+        for course in builtin_courses(SNAPSHOT_SEED) {
+            let path = dir.join(format!("{}.txt", slug(&course.name)));
+            let preview = format!("{}\n", course.render_ascii());
+            if update {
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(&path, &preview).unwrap();
+            } else if std::fs::read_to_string(&path).ok().as_deref() != Some(preview.as_str()) {
+                changed.push(format!("{} ({}):\n{}", course.name, path.display(), preview));
+            }
+        }
+        assert!(
+            changed.is_empty(),
+            "course previews differ from their snapshots; rerun with UPDATE_SNAPSHOTS=1 if that is intended\n{}",
+            changed.join("\n")
+        );
     }
 }
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+use crate::admin::AdminAuth;
+use crate::bot::{self, AdaptiveDifficulty, BotParams, Difficulty};
+use crate::course::{TagPreferences, all_courses};
+use crate::game::{GameStatus, Steer};
+use crate::manager::SharedGameManager;
+use crate::rooms::Rooms;
+use crate::rules::RulesOverride;
+use crate::storage::{JsonStorage, SharedStorage};
+use crate::tcp::TcpClient;
+
+/// Plays over the TCP protocol, like a `tronmcp play` client
+const TCP_PLAYER: &str = "tcp-hard";
+/// Plays by calling the game manager directly, like the MCP HTTP endpoint
+const LOCAL_PLAYER: &str = "local-medium";
+/// Pause between a demo player's steers, so games can be followed in the browser
+const STEP_DELAY: Duration = Duration::from_millis(40);
+/// The demo's TCP player steers faster than the default rate limit allows
+const TCP_SETTINGS: crate::tcp::TcpSettings = crate::tcp::TcpSettings {
+    identity: crate::tcp::TcpIdentity::Tokens,
+    rate_limit: crate::ratelimit::RateLimit::Off,
+};
+/// Longest one demo game may run before the demo gives up
+const GAME_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// `tronmcp demo`: serve a throwaway room on localhost and let two bots play
+/// `games` games in it, printing each result
+pub async fn run_demo(port: u16, games: u32, open: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let data_dir = std::env::temp_dir().join(format!("tronmcp-demo-{}", std::process::id()));
+    let json = |_: &str, dir: &Path| Ok(Arc::new(JsonStorage::new(dir)) as SharedStorage);
+    let rooms = Arc::new(Rooms::new(&["default".to_string()], &data_dir, json, |_| {})?);
+    let http = TcpListener::bind(("127.0.0.1", port)).await?;
+    let tcp = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let (http_addr, tcp_addr) = (http.local_addr()?, tcp.local_addr()?);
+
+    let url = format!("http://{}", http_addr);
+    println!("Tron demo: watch at {}", url);
+    println!("  {} plays over the TCP protocol", TCP_PLAYER);
+    println!("  {} plays in-process", LOCAL_PLAYER);
+    if open {
+        open_browser(&url);
+    }
+
+    let ct = CancellationToken::new();
+    let tcp = crate::tcp::CommandListener::Tcp(tcp);
+    let serving = crate::serve(rooms.clone(), Arc::new(AdminAuth::default()), http, tcp, TCP_SETTINGS, ct.clone());
+    let playing = async {
+        let played = play_games(rooms.default_room().clone(), tcp_addr, games).await;
+        // Nothing here is worth keeping for a restart
+        rooms.default_room().lock().await.lobbies.clear();
+        ct.cancel();
+        played
+    };
+    let (served, played) = tokio::join!(serving, playing);
+    let _ = std::fs::remove_dir_all(&data_dir);
+    served?;
+    Ok(played?)
+}
+
+/// Best effort: the URL is printed either way
+fn open_browser(url: &str) {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    if let Err(e) = std::process::Command::new(opener).arg(url).spawn() {
+        println!("Could not open a browser ({}); open {} yourself", e, url);
+    }
+}
+
+async fn play_games(manager: SharedGameManager, tcp_addr: SocketAddr, games: u32) -> Result<(), String> {
+    let mut client = TcpClient::connect(tcp_addr).await?;
+    let skill = AdaptiveDifficulty::default().skill;
+    let tcp_params = BotParams::for_difficulty(Difficulty::Hard, skill);
+    let local_params = BotParams::for_difficulty(Difficulty::Medium, skill);
+    // Trails that fade let good bots ride forever, so demo games keep every
+    // trail and skip the large courses, which take long to fill up
+    let rules = RulesOverride {
+        sudden_death_tick: Some(1),
+        ..Default::default()
+    };
+    let levels: Vec<u32> = all_courses()
+        .iter()
+        .filter(|c| !c.tags_with(&c.default_rules()).iter().any(|t| t == "large"))
+        .map(|c| c.level)
+        .collect();
+    let mut wins: HashMap<String, u32> = HashMap::new();
+    let mut draws = 0;
+
+    for round in 1..=games {
+        let level = levels[(round as usize - 1) % levels.len()];
+        let (code, _) = manager
+            .lock()
+            .await
+            .open_lobby(Some(level), Some(rules.clone()), false, false, &TagPreferences::default())
+            .map_err(|e| format!("cannot open a demo lobby: {}", e))?;
+        let joined = client
+            .send(&format!("LOBBY JOIN {} {}", code, TCP_PLAYER))
+            .await?
+            .map_err(|e| format!("{} could not join: {}", TCP_PLAYER, e))?;
+        let token = joined
+            .lines()
+            .find_map(|line| line.strip_prefix("SESSION "))
+            .ok_or("the join reply had no session token")?
+            .to_string();
+        let game_id = {
+            let mut mgr = manager.lock().await;
+            mgr.join_lobby(&code, LOCAL_PLAYER.to_string(), None)
+                .map_err(|e| format!("{} could not join: {}", LOCAL_PLAYER, e))?;
+            mgr.player_sessions
+                .get(LOCAL_PLAYER)
+                .and_then(|s| s.game_id)
+                .ok_or("the demo game did not start")?
+        };
+
+        let both = async {
+            tokio::try_join!(
+                drive(&manager, TCP_PLAYER, tcp_params, Some((&mut client, &token))),
+                drive(&manager, LOCAL_PLAYER, local_params, None),
+            )
+        };
+        tokio::time::timeout(GAME_TIMEOUT, both)
+            .await
+            .map_err(|_| format!("game {} did not finish within {:?}", round, GAME_TIMEOUT))??;
+
+        let mgr = manager.lock().await;
+        let game = mgr
+            .finished_games
+            .iter()
+            .find(|g| g.id == game_id.to_string())
+            .ok_or_else(|| format!("game {} was not archived", round))?;
+        let scores: Vec<String> = game.players.iter().map(|p| format!("{} {}", p.name, p.score)).collect();
+        let result = match game.winner.and_then(|w| game.players.get(w)) {
+            Some(winner) => {
+                *wins.entry(winner.name.clone()).or_default() += 1;
+                format!("{} wins", winner.name)
+            }
+            None => {
+                draws += 1;
+                "draw".to_string()
+            }
+        };
+        println!(
+            "Game {}/{} on {} (Level {}): {} at tick {}. Scores: {}",
+            round,
+            games,
+            game.course_name,
+            game.course_level,
+            result,
+            game.tick,
+            scores.join(", ")
+        );
+    }
+
+    println!(
+        "Demo over: {} won {}, {} won {}, {} draws",
+        TCP_PLAYER,
+        wins.get(TCP_PLAYER).copied().unwrap_or(0),
+        LOCAL_PLAYER,
+        wins.get(LOCAL_PLAYER).copied().unwrap_or(0),
+        draws
+    );
+    Ok(())
+}
+
+/// Steer `name` until their game is over. Moves are picked from the shared
+/// game state; with a `tcp` client and session token they are sent as
+/// `STEER` commands, otherwise straight to the manager. Replies are not checked: a steer can
+/// lose the race with the game ending, and the next look at the game notices.
+async fn drive(
+    manager: &SharedGameManager,
+    name: &str,
+    params: BotParams,
+    mut tcp: Option<(&mut TcpClient, &str)>,
+) -> Result<(), String> {
+    loop {
+        let action = {
+            let mgr = manager.lock().await;
+            let seat = mgr.player_sessions.get(name).and_then(|s| s.game_id.zip(s.player_index));
+            let Some((game, idx)) = seat.and_then(|(id, idx)| Some((mgr.active_games.get(&id)?, idx))) else {
+                return Ok(());
+            };
+            if game.status != GameStatus::Running || !game.players.get(idx).is_some_and(|p| p.alive) {
+                return Ok(());
+            }
+            bot::choose_action(game, idx, params)
+        };
+        match tcp.as_mut() {
+            Some((client, token)) => {
+                let _ = client
+                    .send(&format!("STEER {} {}", token, action.name().to_lowercase()))
+                    .await?;
+            }
+            None => {
+                let _ = manager.lock().await.move_player(name, Steer::Turn(action));
+            }
+        }
+        tokio::time::sleep(STEP_DELAY).await;
+    }
+}
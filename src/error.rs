@@ -0,0 +1,292 @@
+use serde::Serialize;
+
+/// Stable, machine-readable kind of every error a player or client can see.
+/// Codes never change meaning; new ones are only ever added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidParams,
+    UnknownCommand,
+    NotJoined,
+    NotAuthorized,
+    NameInUse,
+    NotInGame,
+    NotQueued,
+    PlayerNotFound,
+    GameNotFound,
+    GameNotRunning,
+    PlayerCrashed,
+    LobbyNotFound,
+    LobbyFull,
+    NoMatchingCourse,
+    RoomNotFound,
+    Unauthorized,
+    Forbidden,
+    RateLimited,
+    Internal,
+    Cooldown,
+    NoRematch,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 21] = [
+        ErrorCode::InvalidParams,
+        ErrorCode::UnknownCommand,
+        ErrorCode::NotJoined,
+        ErrorCode::NotAuthorized,
+        ErrorCode::NameInUse,
+        ErrorCode::NotInGame,
+        ErrorCode::NotQueued,
+        ErrorCode::PlayerNotFound,
+        ErrorCode::GameNotFound,
+        ErrorCode::GameNotRunning,
+        ErrorCode::PlayerCrashed,
+        ErrorCode::LobbyNotFound,
+        ErrorCode::LobbyFull,
+        ErrorCode::NoMatchingCourse,
+        ErrorCode::RoomNotFound,
+        ErrorCode::Unauthorized,
+        ErrorCode::Forbidden,
+        ErrorCode::RateLimited,
+        ErrorCode::Internal,
+        ErrorCode::Cooldown,
+        ErrorCode::NoRematch,
+    ];
+
+    pub fn parse(s: &str) -> Option<ErrorCode> {
+        ErrorCode::ALL.into_iter().find(|code| code.as_str() == s)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidParams => "INVALID_PARAMS",
+            ErrorCode::UnknownCommand => "UNKNOWN_COMMAND",
+            ErrorCode::NotJoined => "NOT_JOINED",
+            ErrorCode::NotAuthorized => "NOT_AUTHORIZED",
+            ErrorCode::NameInUse => "NAME_IN_USE",
+            ErrorCode::NotInGame => "NOT_IN_GAME",
+            ErrorCode::NotQueued => "NOT_QUEUED",
+            ErrorCode::PlayerNotFound => "PLAYER_NOT_FOUND",
+            ErrorCode::GameNotFound => "GAME_NOT_FOUND",
+            ErrorCode::GameNotRunning => "GAME_NOT_RUNNING",
+            ErrorCode::PlayerCrashed => "PLAYER_CRASHED",
+            ErrorCode::LobbyNotFound => "LOBBY_NOT_FOUND",
+            ErrorCode::LobbyFull => "LOBBY_FULL",
+            ErrorCode::NoMatchingCourse => "NO_MATCHING_COURSE",
+            ErrorCode::RoomNotFound => "ROOM_NOT_FOUND",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::Internal => "INTERNAL",
+            ErrorCode::Cooldown => "COOLDOWN",
+            ErrorCode::NoRematch => "NO_REMATCH",
+        }
+    }
+
+    /// Whether the same request may succeed later without changes
+    pub fn retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::NameInUse
+                | ErrorCode::NotInGame
+                | ErrorCode::RateLimited
+                | ErrorCode::Internal
+                | ErrorCode::Cooldown
+        )
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidParams => "An argument is missing, malformed or out of range",
+            ErrorCode::UnknownCommand => "The TCP command word is not recognized",
+            ErrorCode::NotJoined => "Call join_game (TCP: JOIN) before other player commands",
+            ErrorCode::NotAuthorized => "This connection plays as a different player",
+            ErrorCode::NameInUse => "The name is already playing or queued; retry after that game, or pick another name",
+            ErrorCode::NotInGame => "Still waiting for opponents; retry once the game starts",
+            ErrorCode::NotQueued => "The player is neither queued nor in a game",
+            ErrorCode::PlayerNotFound => "No player with this name is known",
+            ErrorCode::GameNotFound => "The game no longer exists",
+            ErrorCode::GameNotRunning => "The game has not started or has already ended",
+            ErrorCode::PlayerCrashed => "Crashed players cannot do this",
+            ErrorCode::LobbyNotFound => "No lobby has this code",
+            ErrorCode::LobbyFull => "The lobby has no free seat for this",
+            ErrorCode::NoMatchingCourse => "No course satisfies the requested level and tags",
+            ErrorCode::RoomNotFound => "No room has this name",
+            ErrorCode::Unauthorized => "The admin token is missing or invalid",
+            ErrorCode::Forbidden => "The admin token's role is too low for this route",
+            ErrorCode::RateLimited => "Too many failed admin attempts or TCP commands; wait before retrying",
+            ErrorCode::Internal => "The server hit an unexpected problem; retrying may help",
+            ErrorCode::Cooldown => "The player crashed out of their last game; retry once the cooldown in the message is over",
+            ErrorCode::NoRematch => "The last game cannot be replayed: it had bots, is no longer kept, or its other players have moved on",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A player-facing error: a stable code plus a human-readable message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TronError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl TronError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        TronError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Parse a TCP `ERROR [CODE]: message` reply; `None` for anything else,
+    /// including errors from servers that predate the codes
+    pub fn from_reply(reply: &str) -> Option<TronError> {
+        let (code, message) = reply.strip_prefix("ERROR [")?.split_once("]: ")?;
+        Some(TronError::new(ErrorCode::parse(code)?, message))
+    }
+
+    /// Structured form used by the MCP tools and the HTTP API
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "code": self.code,
+            "message": self.message,
+            "retryable": self.code.retryable(),
+        })
+    }
+}
+
+/// Validation helpers report plain strings; those are always bad arguments
+impl From<String> for TronError {
+    fn from(message: String) -> Self {
+        TronError::new(ErrorCode::InvalidParams, message)
+    }
+}
+
+impl std::fmt::Display for TronError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+/// Every code with its retryability and meaning, served at `/api/errors`
+pub fn catalog() -> serde_json::Value {
+    let codes: serde_json::Map<String, serde_json::Value> = ErrorCode::ALL
+        .iter()
+        .map(|code| {
+            (
+                code.as_str().to_string(),
+                serde_json::json!({
+                    "retryable": code.retryable(),
+                    "description": code.description(),
+                }),
+            )
+        })
+        .collect();
+    serde_json::Value::Object(codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The wire codes in `ALL` order; clients branch on these, so a rename
+    /// here is a breaking change
+    const PINNED: [&str; 21] = [
+        "INVALID_PARAMS",
+        "UNKNOWN_COMMAND",
+        "NOT_JOINED",
+        "NOT_AUTHORIZED",
+        "NAME_IN_USE",
+        "NOT_IN_GAME",
+        "NOT_QUEUED",
+        "PLAYER_NOT_FOUND",
+        "GAME_NOT_FOUND",
+        "GAME_NOT_RUNNING",
+        "PLAYER_CRASHED",
+        "LOBBY_NOT_FOUND",
+        "LOBBY_FULL",
+        "NO_MATCHING_COURSE",
+        "ROOM_NOT_FOUND",
+        "UNAUTHORIZED",
+        "FORBIDDEN",
+        "RATE_LIMITED",
+        "INTERNAL",
+        "COOLDOWN",
+        "NO_REMATCH",
+    ];
+
+    #[test]
+    fn every_code_is_listed_once_and_keeps_its_name() {
+        for code in ErrorCode::ALL {
+            // No wildcard: a new variant fails to build here until it is
+            // listed in ALL and pinned above
+            match code {
+                ErrorCode::InvalidParams
+                | ErrorCode::UnknownCommand
+                | ErrorCode::NotJoined
+                | ErrorCode::NotAuthorized
+                | ErrorCode::NameInUse
+                | ErrorCode::NotInGame
+                | ErrorCode::NotQueued
+                | ErrorCode::PlayerNotFound
+                | ErrorCode::GameNotFound
+                | ErrorCode::GameNotRunning
+                | ErrorCode::PlayerCrashed
+                | ErrorCode::LobbyNotFound
+                | ErrorCode::LobbyFull
+                | ErrorCode::NoMatchingCourse
+                | ErrorCode::RoomNotFound
+                | ErrorCode::Unauthorized
+                | ErrorCode::Forbidden
+                | ErrorCode::RateLimited
+                | ErrorCode::Internal
+                | ErrorCode::Cooldown
+                | ErrorCode::NoRematch => {}
+            }
+            assert_eq!(ErrorCode::ALL.iter().filter(|c| **c == code).count(), 1, "{code:?}");
+        }
+        assert_eq!(ErrorCode::ALL.map(ErrorCode::as_str), PINNED);
+
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::parse(code.as_str()), Some(code));
+            assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+            assert_eq!(code.to_string(), code.as_str());
+            assert!(!code.description().is_empty(), "{code} has no description");
+        }
+        assert_eq!(ErrorCode::parse("not_in_game"), None);
+    }
+
+    #[test]
+    fn catalog_documents_every_code() {
+        let catalog = catalog();
+        let entries = catalog.as_object().unwrap();
+        assert_eq!(entries.len(), ErrorCode::ALL.len());
+        for code in ErrorCode::ALL {
+            let entry = &entries[code.as_str()];
+            assert_eq!(entry["retryable"], code.retryable(), "{code}");
+            assert_eq!(entry["description"], code.description(), "{code}");
+        }
+        let retryable: Vec<&str> = ErrorCode::ALL
+            .into_iter()
+            .filter(|code| code.retryable())
+            .map(ErrorCode::as_str)
+            .collect();
+        assert_eq!(retryable, ["NAME_IN_USE", "NOT_IN_GAME", "RATE_LIMITED", "INTERNAL", "COOLDOWN"]);
+    }
+
+    #[test]
+    fn replies_without_a_known_code_are_not_parsed() {
+        assert_eq!(
+            TronError::from_reply("ERROR [LOBBY_FULL]: No seat: try later"),
+            Some(TronError::new(ErrorCode::LobbyFull, "No seat: try later"))
+        );
+        assert_eq!(TronError::from_reply("ERROR: Not in game"), None);
+        assert_eq!(TronError::from_reply("ERROR [NO_SUCH_CODE]: x"), None);
+        assert_eq!(TronError::from_reply("OK joined"), None);
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+/// Longest a single steer is ever held back
+pub const MAX_DELAY_MS: u64 = 2000;
+
+/// Artificial steer delay that evens out reaction time between players on
+/// fast and slow links (`--fairness-delay-ms`). A player's reaction time
+/// includes a full round trip (the view travels out, the steer travels back),
+/// so round trips are what get equalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FairnessDelay {
+    #[default]
+    Off,
+    /// Pad every player up to this round trip, in milliseconds
+    Fixed(u64),
+    /// Pad every player up to the slowest measured round trip in their game
+    Auto,
+}
+
+impl FairnessDelay {
+    pub fn parse(s: &str) -> Result<FairnessDelay, String> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(FairnessDelay::Auto),
+            "off" | "0" => Ok(FairnessDelay::Off),
+            ms => match ms.parse::<u64>() {
+                Ok(ms) if ms <= MAX_DELAY_MS => Ok(FairnessDelay::Fixed(ms)),
+                _ => Err(format!(
+                    "expected 'auto' or a delay in milliseconds (0-{})",
+                    MAX_DELAY_MS
+                )),
+            },
+        }
+    }
+
+    /// Delay for a player whose round trip is `own_ms`, given the round trips
+    /// of everyone in the game (including their own)
+    pub fn delay_for(self, own_ms: u64, game_ms: impl Iterator<Item = u64>) -> Duration {
+        let target = match self {
+            FairnessDelay::Off => return Duration::ZERO,
+            FairnessDelay::Fixed(ms) => ms,
+            FairnessDelay::Auto => game_ms.max().unwrap_or(own_ms),
+        };
+        Duration::from_millis(target.saturating_sub(own_ms).min(MAX_DELAY_MS))
+    }
+}
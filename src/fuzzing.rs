@@ -0,0 +1,205 @@
+//! Entry points for the cargo-fuzz targets in `fuzz/`. Each takes the raw
+//! bytes of one fuzz input and feeds them to one place hostile input gets
+//! in: the TCP command protocol, course files, and the game engine. Any
+//! panic is a bug. Not part of the supported API.
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::course::{self, Course};
+use crate::game::{Game, GameStatus, LookOptions, Orientation, SteerAction};
+use crate::ratelimit::RateLimit;
+use crate::rooms::Rooms;
+use crate::rules::{DeadTrailPolicy, RulesOverride, TieBreak, WinCondition};
+use crate::storage::{JsonStorage, SharedStorage};
+use crate::tcp::{self, TcpIdentity, TcpSettings};
+
+/// Play one TCP connection on two fresh rooms: the first byte picks how
+/// commands name their player, the rest is sent as-is and the replies are
+/// read until the server hangs up. `WAIT` sleeps for real, so run this on a
+/// runtime with a paused clock.
+pub async fn tcp_session(input: &[u8]) {
+    let Some((&first, input)) = input.split_first() else {
+        return;
+    };
+    let identity = match first % 3 {
+        0 => TcpIdentity::Tokens,
+        1 => TcpIdentity::Names,
+        _ => TcpIdentity::Unchecked,
+    };
+    let dir = std::env::temp_dir().join(format!("tronmcp-fuzz-{}", uuid::Uuid::new_v4()));
+    let rooms = Rooms::new(
+        &["default".to_string(), "other".to_string()],
+        &dir,
+        |_, dir| Ok(Arc::new(JsonStorage::new(dir)) as SharedStorage),
+        |_| {},
+    )
+    .expect("JSON rooms always open");
+    let settings = TcpSettings {
+        identity,
+        rate_limit: RateLimit::Off,
+    };
+
+    let (client, server) = tokio::io::duplex(64 * 1024);
+    let (mut replies, mut commands) = tokio::io::split(client);
+    let serving = tcp::handle_connection(Box::new(server), "fuzz".to_string(), Arc::new(rooms), settings);
+    let sending = async {
+        // The server may hang up first (`QUIT`), which fails the write
+        let _ = commands.write_all(input).await;
+        let _ = commands.shutdown().await;
+    };
+    let reading = async {
+        let mut out = Vec::new();
+        let _ = replies.read_to_end(&mut out).await;
+    };
+    tokio::join!(serving, sending, reading);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Load one course file, as TOML or JSON, and play a short game on
+/// whatever loads
+pub fn course_file(input: &[u8]) {
+    let Some((&first, input)) = input.split_first() else {
+        return;
+    };
+    let Ok(data) = std::str::from_utf8(input) else {
+        return;
+    };
+    let file = if first % 2 == 0 { "fuzz.toml" } else { "fuzz.json" };
+    let Ok(course) = course::parse_course_file(file, data) else {
+        return;
+    };
+    let _ = course.render_ascii();
+    let _ = course.summary_line(Some(file));
+    let Ok(mut game) = Game::new(&course, course.default_rules()) else {
+        return;
+    };
+    for i in 0..course.max_players {
+        game.add_player(format!("p{}", i));
+    }
+    game.start();
+    let actions = [SteerAction::Straight, SteerAction::Left, SteerAction::Right];
+    for (i, &b) in data.as_bytes().iter().take(200).enumerate() {
+        let players = game.players().len();
+        if game.status() != GameStatus::Running || players == 0 {
+            break;
+        }
+        game.move_player(i % players, actions[b as usize % 3]);
+    }
+}
+
+/// Start a game from the first bytes (course, rule tweaks, player count)
+/// and run every following byte as an action by one of the players, checking
+/// that ticks and the game status only move forward
+pub fn game_actions(input: &[u8]) {
+    let [level, flags, extra, seats, actions @ ..] = input else {
+        return;
+    };
+    let courses = course::all_courses();
+    let course: &Course = &courses[*level as usize % courses.len()];
+    let overrides = rules_override(*flags, *extra);
+    if overrides.validate().is_err() {
+        return;
+    }
+    let rules = course.default_rules().overlay(&overrides);
+    if rules.validate(course).is_err() {
+        return;
+    }
+    let Ok(mut game) = Game::new(course, rules) else {
+        return;
+    };
+    for i in 0..2 + *seats as usize % course.max_players.max(2) {
+        game.add_player(format!("p{}", i));
+    }
+    let picks: Vec<Option<usize>> = (0..game.players().len()).map(|i| Some(i % 2)).collect();
+    game.assign_teams(&picks);
+    game.shuffle_spawns();
+    game.start();
+
+    let mut actions = actions.iter();
+    let (mut tick, mut finished) = (game.tick(), false);
+    while let Some(&b) = actions.next() {
+        let player = (b >> 4) as usize % game.players().len();
+        let steer = [SteerAction::Straight, SteerAction::Left, SteerAction::Right][b as usize % 3];
+        match b & 0x0f {
+            0..=2 => {
+                game.move_player(player, steer);
+            }
+            3..=5 => {
+                game.queue_steer(player, steer);
+            }
+            6 => game.advance(),
+            7 | 8 => {
+                let radius = match actions.next().copied().unwrap_or(0) % 4 {
+                    0 => 0,
+                    1 => 1,
+                    2 => 7,
+                    _ => usize::MAX,
+                };
+                let options = LookOptions {
+                    hints: b & 0x10 != 0,
+                    orientation: if b & 0x20 != 0 { Orientation::Ego } else { Orientation::World },
+                };
+                if b & 0x0f == 7 {
+                    let _ = game.look(player, radius, options);
+                } else {
+                    let _ = game.look_compact(player, radius, options);
+                    let _ = game.look_view(player, radius);
+                }
+            }
+            9 => {
+                game.forfeit(player);
+            }
+            10 => {
+                let _ = game.request_abort(player);
+            }
+            11 => game.kick(player),
+            12 => {
+                let _ = game.say(player, String::from_utf8_lossy(actions.as_slice()).into_owned());
+            }
+            13 => {
+                let _ = game.to_web_state();
+                let _ = game.status_of(player);
+                let _ = game.territory_line(Some(player));
+            }
+            14 => {
+                let _ = game.enforce_move_timeout();
+            }
+            _ => game.end_now(),
+        }
+        assert!(game.tick() >= tick, "tick went backwards");
+        assert!(!finished || game.status() == GameStatus::Finished, "a finished game restarted");
+        tick = game.tick();
+        finished = game.status() == GameStatus::Finished;
+    }
+}
+
+/// Rule tweaks picked by two bytes: each bit of `flags` turns one on, and
+/// `extra` sizes the numeric ones
+fn rules_override(flags: u8, extra: u8) -> RulesOverride {
+    let bit = |n: u8| flags & (1 << n) != 0;
+    let small = u32::from(extra % 8) + 1;
+    RulesOverride {
+        max_trail_length: bit(0).then_some(extra as usize % 4),
+        wrap_edges: bit(1).then_some(true),
+        boosts: bit(2).then_some(true),
+        win_condition: bit(3).then_some(if extra & 0x80 != 0 {
+            WinCondition::Territory
+        } else {
+            WinCondition::Checkpoint
+        }),
+        tie_break: bit(4).then_some(TieBreak::Draw),
+        dead_trail_policy: bit(4).then_some(if extra & 0x40 != 0 {
+            DeadTrailPolicy::Decay(small)
+        } else {
+            DeadTrailPolicy::ClearImmediately
+        }),
+        shrink_every: bit(5).then_some(small),
+        lives: bit(6).then_some(small.min(9)),
+        spawn_grace: Some(u32::from(extra % 4)),
+        team_size: bit(7).then_some(2),
+        max_ticks: (extra & 0x20 != 0).then_some(small * 10),
+        ..RulesOverride::default()
+    }
+}
@@ -1,8 +1,17 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use uuid::Uuid;
 
+use crate::analysis;
+use crate::clock::Clock;
+use crate::bot::BotParams;
 use crate::course::Course;
+use crate::render;
+use crate::rules::{DeadTrailPolicy, Ruleset, TieBreak, WinCondition};
+use crate::web;
 
 /// Cell types on the game grid
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,6 +20,84 @@ pub enum Cell {
     Wall,
     Obstruction,
     Trail(usize), // player index
+    PowerUp(PowerUpKind),
+}
+
+impl Cell {
+    /// Whether a cycle can ride into this cell without crashing
+    pub fn is_open(self) -> bool {
+        matches!(self, Cell::Empty | Cell::PowerUp(_))
+    }
+}
+
+/// Pickups that appear on the grid when the `boosts` rule is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUpKind {
+    /// Your next steer moves you two cells
+    SpeedBoost,
+    /// Clears the oldest `ERASER_CELLS` cells of your trail
+    TrailEraser,
+    /// Ride through one trail cell within the next `GHOST_MOVES` moves
+    Ghost,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 3] = [PowerUpKind::SpeedBoost, PowerUpKind::TrailEraser, PowerUpKind::Ghost];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUpKind::SpeedBoost => "speed boost",
+            PowerUpKind::TrailEraser => "trail eraser",
+            PowerUpKind::Ghost => "ghost",
+        }
+    }
+
+    /// How `look` and the other text views draw it
+    pub fn glyph(self) -> char {
+        match self {
+            PowerUpKind::SpeedBoost => '*',
+            PowerUpKind::TrailEraser => '~',
+            PowerUpKind::Ghost => 'o',
+        }
+    }
+
+    fn index(self) -> usize {
+        PowerUpKind::ALL.iter().position(|&k| k == self).unwrap_or(0)
+    }
+}
+
+/// A power-up a player is holding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActiveEffect {
+    /// The next steer moves two cells
+    SpeedBoost,
+    /// The next trail cell ridden into is passed through, if that happens
+    /// within `moves_left` moves
+    Ghost { moves_left: u32 },
+}
+
+impl ActiveEffect {
+    pub fn describe(self) -> String {
+        match self {
+            ActiveEffect::SpeedBoost => "speed boost (your next steer moves 2 cells)".to_string(),
+            ActiveEffect::Ghost { moves_left } => format!(
+                "ghost (ride through one trail cell, not a head, within {} more move{})",
+                moves_left,
+                if moves_left == 1 { "" } else { "s" }
+            ),
+        }
+    }
+}
+
+/// A course's power-up spawn point and when it next fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerUpSpawn {
+    pub at: (usize, usize),
+    /// Tick from which a new power-up appears once the cell is empty; None
+    /// while one is waiting there
+    pub respawn_at: Option<u32>,
 }
 
 /// Movement direction
@@ -41,6 +128,23 @@ impl Direction {
         }
     }
 
+    /// The turn that heads a cycle facing this way toward `target`; `None`
+    /// when `target` is straight behind, since cycles cannot reverse
+    pub fn turn_toward(self, target: Direction) -> Option<SteerAction> {
+        [SteerAction::Straight, SteerAction::Left, SteerAction::Right]
+            .into_iter()
+            .find(|action| action.turn(self) == target)
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
     pub fn delta(self) -> (i32, i32) {
         match self {
             Direction::Up => (0, -1),
@@ -69,6 +173,117 @@ pub enum SteerAction {
     Straight,
 }
 
+impl SteerAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            SteerAction::Left => "LEFT",
+            SteerAction::Right => "RIGHT",
+            SteerAction::Straight => "STRAIGHT",
+        }
+    }
+
+    /// The heading a cycle moves in after this action
+    pub fn turn(self, heading: Direction) -> Direction {
+        match self {
+            SteerAction::Left => heading.turn_left(),
+            SteerAction::Right => heading.turn_right(),
+            SteerAction::Straight => heading,
+        }
+    }
+}
+
+/// What a player asked `steer` for: a turn relative to their heading, or
+/// the compass heading to move in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Steer {
+    Turn(SteerAction),
+    Toward(Direction),
+}
+
+impl Steer {
+    /// The turn this asks for of a cycle heading `heading`; a compass
+    /// heading straight behind it is refused with the legal choices
+    pub fn resolve(self, heading: Direction) -> Result<SteerAction, String> {
+        match self {
+            Steer::Turn(action) => Ok(action),
+            Steer::Toward(target) => heading.turn_toward(target).ok_or_else(|| {
+                let legal: Vec<String> = [SteerAction::Straight, SteerAction::Left, SteerAction::Right]
+                    .iter()
+                    .map(|a| format!("{} ({})", a.turn(heading).name().to_lowercase(), a.name().to_lowercase()))
+                    .collect();
+                format!(
+                    "You are heading {} and cannot reverse to {}. Legal moves: {}.",
+                    heading.name(),
+                    target.name(),
+                    legal.join(", ")
+                )
+            }),
+        }
+    }
+}
+
+/// How steering drives a game (`--mode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    /// Each steer moves the player one step, and the bots answer
+    #[default]
+    TurnBased,
+    /// Every cycle advances once per server tick; a steer only picks the
+    /// turn taken on the next one
+    Realtime,
+}
+
+impl GameMode {
+    pub fn parse(s: &str) -> Result<GameMode, String> {
+        match s.trim().to_lowercase().as_str() {
+            "turnbased" | "turn-based" => Ok(GameMode::TurnBased),
+            "realtime" | "real-time" => Ok(GameMode::Realtime),
+            _ => Err("expected 'realtime' or 'turnbased'".to_string()),
+        }
+    }
+}
+
+/// What the idle policy does to a player who stops steering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleAction {
+    /// Move them one step straight ahead
+    Straight,
+    /// Crash them out of the game
+    Crash,
+}
+
+impl IdleAction {
+    pub fn parse(s: &str) -> Result<IdleAction, String> {
+        match s.trim().to_lowercase().as_str() {
+            "straight" => Ok(IdleAction::Straight),
+            "crash" => Ok(IdleAction::Crash),
+            _ => Err("expected 'straight' or 'crash'".to_string()),
+        }
+    }
+}
+
+/// Server-wide rule for players who stop steering (`--idle-timeout`,
+/// `--idle-moves`, `--idle-action`). Without it an idle player freezes their
+/// own cycle while opponents keep moving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdlePolicy {
+    /// Seconds without steering before a player counts as idle
+    pub after_secs: Option<u64>,
+    /// Opponent moves without steering before a player counts as idle
+    pub after_moves: Option<u32>,
+    pub action: IdleAction,
+}
+
+impl Default for IdlePolicy {
+    fn default() -> Self {
+        IdlePolicy {
+            after_secs: None,
+            after_moves: None,
+            action: IdleAction::Straight,
+        }
+    }
+}
+
 /// A player in the game
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
@@ -80,6 +295,319 @@ pub struct Player {
     pub trail: VecDeque<(i32, i32)>,
     pub distance_traveled: u32,
     pub score: u32,
+    pub last_move_at: chrono::DateTime<chrono::Utc>,
+    /// Set for server-side bot opponents
+    #[serde(default)]
+    pub bot: Option<BotParams>,
+    /// Round trip last measured on the player's connection, in milliseconds
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    /// Fairness delay applied to the player's last steer, in milliseconds
+    #[serde(default)]
+    pub fairness_delay_ms: u64,
+    /// Moves other players made since this one last moved
+    #[serde(default)]
+    pub moves_waited: u32,
+    /// Steps the idle policy moved this player forward
+    #[serde(default)]
+    pub idle_moves: u32,
+    /// Real-time games: the turn taken on the next tick
+    #[serde(default)]
+    pub pending_action: Option<SteerAction>,
+    /// Power-ups picked up and not used yet
+    #[serde(default)]
+    pub active_effects: Vec<ActiveEffect>,
+    /// Until this tick, other players' trails can't crash this one
+    #[serde(default)]
+    pub spawn_protection_until: u32,
+    /// In games with lives, how many are left, counting the current cycle
+    #[serde(default)]
+    pub lives: u32,
+    /// Tick at which a crashed player with lives left rides again
+    #[serde(default)]
+    pub respawn_at_tick: Option<u32>,
+    /// Opponents who crashed into this player's trail or head
+    #[serde(default)]
+    pub kills: u32,
+}
+
+impl Player {
+    /// Riding, or crashed with a respawn coming
+    pub fn in_play(&self) -> bool {
+        self.alive || self.respawn_at_tick.is_some()
+    }
+
+    /// Moves left on a held ghost
+    pub fn ghost_moves(&self) -> Option<u32> {
+        self.active_effects.iter().find_map(|e| match *e {
+            ActiveEffect::Ghost { moves_left } => Some(moves_left),
+            ActiveEffect::SpeedBoost => None,
+        })
+    }
+}
+
+/// What ended a cycle's run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrashCause {
+    Boundary,
+    Wall,
+    Obstruction,
+    OwnTrail,
+    /// Another player's trail; `cut_off` when it was laid in the last couple of steps
+    Trail { owner: usize, cut_off: bool },
+    /// Ran into another cycle heading straight at it; both crash
+    HeadOn { other: usize },
+    Timeout,
+    /// Removed from the game by an operator
+    Kicked,
+    /// Left the game on their own; counts as a loss
+    Forfeit,
+    /// Caught on the outer ring when the arena closed in
+    ArenaShrink,
+}
+
+impl CrashCause {
+    /// The player credited with `victim`'s crash: the owner of the trail they
+    /// hit, or the other cycle in a head-on. Nobody for their own trail.
+    pub fn killer(self, victim: usize) -> Option<usize> {
+        match self {
+            CrashCause::Trail { owner, .. } if owner != victim => Some(owner),
+            CrashCause::HeadOn { other } => Some(other),
+            _ => None,
+        }
+    }
+}
+
+/// Notable things that happen during a game, in the order they happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEventKind {
+    Started,
+    Crashed { player: usize, cause: CrashCause },
+    SuddenDeath,
+    ReachedCheckpoint { player: usize },
+    PowerUp { player: usize, kind: PowerUpKind },
+    /// A ghosting cycle rode through `owner`'s trail
+    Ghosted { player: usize, owner: usize },
+    /// The arena closed in a ring, leaving this much room
+    ArenaShrank { width: usize, height: usize },
+    /// A crashed player rode again with this many lives left
+    Respawned { player: usize, lives: u32 },
+    /// The game reached the `max_ticks` rule's tick
+    TimeLimit,
+    Finished { winner: Option<usize> },
+}
+
+/// How a tie-break picked the winner of a game whose last cycles crashed
+/// on the same tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreakReason {
+    /// Traveled further than the others
+    Distance,
+    /// Equal distances; a coin flip seeded from the game id decided
+    CoinFlip,
+    /// Territory games: equal cells, and this cycle lasted longest
+    Survival,
+    /// Time limit: equal distances, and this cycle had the most kills
+    Kills,
+}
+
+impl TieBreakReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            TieBreakReason::Distance => "distance",
+            TieBreakReason::CoinFlip => "coin flip",
+            TieBreakReason::Survival => "survival",
+            TieBreakReason::Kills => "kills",
+        }
+    }
+}
+
+/// An entry in a game's event log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameEvent {
+    pub tick: u32,
+    pub kind: GameEventKind,
+}
+
+/// Votes for an action that needs every required player to agree within a
+/// time window (e.g. aborting a game)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Consensus {
+    votes: Vec<(usize, chrono::DateTime<chrono::Utc>)>,
+}
+
+impl Consensus {
+    /// Record `player`'s vote at `now`. Returns true once every player in
+    /// `required` has voted within `window`; expired votes are dropped.
+    pub fn vote(
+        &mut self,
+        player: usize,
+        required: &[usize],
+        window: chrono::Duration,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        self.votes.retain(|(p, at)| *p != player && now - *at < window);
+        self.votes.push((player, now));
+        required.iter().all(|r| self.votes.iter().any(|(p, _)| p == r))
+    }
+
+    /// Players with a vote that has not expired by `now`
+    pub fn voters(&self, window: chrono::Duration, now: chrono::DateTime<chrono::Utc>) -> Vec<usize> {
+        self.votes
+            .iter()
+            .filter(|(_, at)| now - *at < window)
+            .map(|(p, _)| *p)
+            .collect()
+    }
+}
+
+/// Points the winner gets on top of their distance and survival
+pub const WIN_BONUS: u32 = 100;
+
+/// Points a player gets for each opponent that crashed into them
+pub const KILL_BONUS: u32 = 20;
+
+/// Points a crash costs in games with lives
+pub const DEATH_PENALTY: u32 = 25;
+
+/// Rounds a crashed player with lives left waits before respawning
+pub const RESPAWN_ROUNDS: u32 = 3;
+
+/// How long an abort request waits for the other players to agree
+pub const ABORT_WINDOW_SECS: i64 = 60;
+
+/// Chat messages a game keeps; older ones are dropped
+pub const CHAT_HISTORY: usize = 20;
+/// Opponents' chat messages shown in `look` and `game_status`
+pub const CHAT_SHOWN: usize = 3;
+/// Seconds a player waits between chat messages
+pub const CHAT_COOLDOWN_SECS: i64 = 5;
+
+/// Something a player said during a game (`say`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub player: usize,
+    pub tick: u32,
+    pub text: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One thing that moved the game on, as `Game::replay_step` applies it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStep {
+    Move { player: usize, action: SteerAction },
+    /// Players knocked out together between moves: timeouts, kicks, forfeits
+    Out { players: Vec<usize>, cause: CrashCause },
+    /// Ended early, without a winner (aborted or finished by an operator)
+    End,
+}
+
+/// Enough to play a game back from its start (`tronmcp replay`): where
+/// each cycle started and every step after, in order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub starts: Vec<(i32, i32, Direction)>,
+    pub checkpoint: Option<(usize, usize)>,
+    #[serde(default)]
+    pub powerups: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub powerup_rate: Option<u32>,
+    pub steps: Vec<ReplayStep>,
+}
+
+/// Smallest width and height a course may have, so every spawn point and
+/// the border fit
+pub const MIN_GRID_SIZE: usize = 8;
+
+/// Web grid code of player 0's trail after they crashed; living trails are
+/// `3 + index`
+pub const DEAD_TRAIL_CODE: u8 = 100;
+
+/// Web grid code of an empty checkpoint cell
+pub const CHECKPOINT_CODE: u8 = 99;
+
+/// Web grid code of a cell walled off by the arena closing in
+pub const CLOSED_CODE: u8 = 95;
+
+/// Smallest playable area, in cells across, the arena shrinks down to
+pub const MIN_ARENA: usize = 3;
+
+/// Web grid code of the first power-up kind; the others follow in
+/// `PowerUpKind::ALL` order
+pub const POWERUP_CODE: u8 = 96;
+
+/// Ticks before a consumed power-up's spawn point fills again
+pub const POWERUP_RESPAWN_TICKS: u32 = 40;
+
+/// Most power-ups a course's spawn rate keeps on the grid at once
+pub const MAX_RANDOM_POWERUPS: usize = 4;
+
+/// Trail cells a trail eraser clears
+pub const ERASER_CELLS: usize = 10;
+
+/// Moves a ghost lasts
+pub const GHOST_MOVES: u32 = 3;
+
+/// Largest view radius `look` renders
+pub const MAX_VIEW_RADIUS: usize = 32;
+
+/// Spawn positions for players on a grid of this size, in seat order
+/// (corners, then midpoints)
+pub fn spawn_positions(width: usize, height: usize) -> Vec<(i32, i32, Direction)> {
+    let w = width as i32;
+    let h = height as i32;
+    vec![
+        (3, 3, Direction::Right),
+        (w - 4, h - 4, Direction::Left),
+        (w - 4, 3, Direction::Down),
+        (3, h - 4, Direction::Up),
+        (w / 2, 3, Direction::Down),
+        (3, h / 2, Direction::Right),
+        (w - 4, h / 2, Direction::Left),
+        (w / 2, h - 4, Direction::Up),
+    ]
+}
+
+/// A `width` x `height` grid of symbols shrunk by a whole factor so neither
+/// side exceeds `max_size` characters; see `Game::downsample`
+pub fn downsample(
+    width: usize,
+    height: usize,
+    max_size: usize,
+    symbol: impl Fn(usize, usize) -> (char, u8),
+) -> (usize, Vec<String>) {
+    let scale = width.max(height).div_ceil(max_size.max(1)).max(1);
+    let rows = (0..height)
+        .step_by(scale)
+        .map(|by| {
+            (0..width)
+                .step_by(scale)
+                .map(|bx| {
+                    let mut best = symbol(bx, by);
+                    for y in by..(by + scale).min(height) {
+                        for x in bx..(bx + scale).min(width) {
+                            let candidate = symbol(x, y);
+                            if candidate.1 > best.1 {
+                                best = candidate;
+                            }
+                        }
+                    }
+                    best.0
+                })
+                .collect()
+        })
+        .collect();
+    (scale, rows)
+}
+
+/// Result of a player asking to abort
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbortOutcome {
+    /// Still waiting for these players to agree
+    Pending(Vec<String>),
+    Aborted,
 }
 
 /// Game status
@@ -90,95 +618,276 @@ pub enum GameStatus {
     Finished,
 }
 
-/// A game instance
-#[derive(Debug, Clone, Serialize)]
+/// One game on one course. Build it with `new`, seat players with
+/// `add_player`, then `start` it. In turn-based games each `move_player`
+/// moves one cycle a step; in real-time games `queue_steer` sets each
+/// cycle's next turn and `advance` moves them all. The state that these
+/// keep consistent (grid, players, status, winner) is read through
+/// accessors; the course and rule details are plain fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub id: Uuid,
-    pub grid: Vec<Vec<Cell>>,
+    pub(crate) grid: Vec<Vec<Cell>>,
+    /// Tick each trail cell was laid on, by grid cell; meaningless where
+    /// the grid holds no trail, and empty in games saved before
+    #[serde(default)]
+    pub(crate) laid_at: Vec<Vec<u32>>,
     pub width: usize,
     pub height: usize,
-    pub players: Vec<Player>,
-    pub status: GameStatus,
-    pub tick: u32,
-    pub max_trail_length: usize,
+    pub(crate) players: Vec<Player>,
+    pub(crate) status: GameStatus,
+    pub(crate) tick: u32,
+    pub rules: Ruleset,
+    pub rules_diff: Vec<String>,
+    pub unranked: bool,
+    pub lobby: Option<String>,
+    /// Started from a featured lobby; listed first in the web UI
+    pub featured: bool,
+    /// Bot-vs-bot showcase run while the server is empty
+    #[serde(default)]
+    pub exhibition: bool,
+    /// Cycles advance on the server tick instead of on each steer
+    #[serde(default)]
+    pub realtime: bool,
+    /// Real-time rounds played, each moving every living cycle once
+    #[serde(default)]
+    pub(crate) rounds: u32,
+    pub checkpoint: Option<(usize, usize)>,
+    /// The course's power-up spawn points, when the `boosts` rule is on
+    #[serde(default)]
+    pub(crate) powerups: Vec<PowerUpSpawn>,
+    /// Every this many ticks a power-up appears on a random empty cell,
+    /// when the `boosts` rule is on
+    #[serde(default)]
+    pub(crate) powerup_rate: Option<u32>,
+    /// Rings the arena has closed in by, under the `shrink_every` rule
+    #[serde(default)]
+    pub(crate) shrinks: usize,
+    /// Cells the closing arena turned into wall
+    #[serde(default)]
+    pub(crate) closed: Vec<(usize, usize)>,
+    /// Start cell and heading of each seat; empty in games saved before
+    /// courses could place them
+    #[serde(default)]
+    pub(crate) spawns: Vec<(i32, i32, Direction)>,
     pub course_name: String,
     pub course_level: u32,
-    pub winner: Option<usize>,
+    /// Seed the course layout was generated from, if it is random
+    #[serde(default)]
+    pub course_seed: Option<u64>,
+    /// Course tags under this game's rules
+    pub tags: Vec<String>,
+    /// Why matchmaking picked this course, when tag preferences were involved
+    pub course_note: Option<String>,
+    pub(crate) winner: Option<usize>,
+    /// Each player's team (1 or 2) by player index, in team games
+    #[serde(default)]
+    pub(crate) teams: Option<Vec<usize>>,
+    /// The team of the winner, in team games
+    #[serde(default)]
+    pub(crate) winning_team: Option<usize>,
+    /// Set when the winner was decided by the tie-break rule
+    #[serde(default)]
+    pub(crate) tie_break: Option<TieBreakReason>,
+    pub(crate) events: Vec<GameEvent>,
+    /// The last `CHAT_HISTORY` chat messages, oldest first
+    #[serde(default)]
+    pub(crate) chat: VecDeque<ChatMessage>,
+    /// Everything that happened since the start, archived for replays
+    #[serde(default)]
+    pub(crate) replay: Replay,
+    pub(crate) abort_votes: Consensus,
+    /// Time source for abort votes; the room's clock once the game is
+    /// launched by a manager
+    #[serde(skip)]
+    pub(crate) clock: Clock,
+    /// Voided by agreement of the players, or by a forfeit before the first
+    /// move; excluded from stats
+    pub(crate) aborted: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
-    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub(crate) finished_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl Game {
-    /// Create a new game from a course definition
-    pub fn new(course: &Course) -> Self {
-        let mut grid = vec![vec![Cell::Empty; course.width]; course.height];
+    /// Create a new game from a course definition and the rules to play it
+    /// under. Fails if the course is too small or has cells off its grid.
+    pub fn new(course: &Course, rules: Ruleset) -> Result<Self, String> {
+        course
+            .check_grid()
+            .map_err(|e| format!("course '{}': {}", course.name, e))?;
+        let (width, height) = (course.width, course.height);
+        let mut grid = vec![vec![Cell::Empty; width]; height];
 
-        // Place walls around the border
-        for x in 0..course.width {
-            grid[0][x] = Cell::Wall;
-            grid[course.height - 1][x] = Cell::Wall;
-        }
-        for y in 0..course.height {
-            grid[y][0] = Cell::Wall;
-            grid[y][course.width - 1] = Cell::Wall;
+        // Place walls around the border (open edges when wrapping)
+        if !rules.wrap_edges {
+            grid[0].fill(Cell::Wall);
+            grid[height - 1].fill(Cell::Wall);
+            for row in grid.iter_mut() {
+                row[0] = Cell::Wall;
+                row[width - 1] = Cell::Wall;
+            }
         }
 
         // Place course obstructions
         for &(x, y) in &course.obstructions {
-            if x < course.width && y < course.height {
-                grid[y][x] = Cell::Obstruction;
-            }
+            grid[y][x] = Cell::Obstruction;
         }
 
         // Place course walls
         for &(x, y) in &course.walls {
-            if x < course.width && y < course.height {
-                grid[y][x] = Cell::Wall;
-            }
+            grid[y][x] = Cell::Wall;
         }
 
-        Game {
+        let mut game = Game {
             id: Uuid::new_v4(),
-            width: course.width,
-            height: course.height,
+            width,
+            height,
             grid,
+            laid_at: vec![vec![0; width]; height],
             players: Vec::new(),
             status: GameStatus::WaitingForPlayers,
             tick: 0,
-            max_trail_length: course.max_trail_length,
+            rules_diff: rules.diff(&course.default_rules()),
+            tags: course.tags_with(&rules),
+            course_note: None,
+            rules,
+            unranked: false,
+            lobby: None,
+            featured: false,
+            exhibition: false,
+            realtime: false,
+            rounds: 0,
+            checkpoint: course.checkpoint,
+            powerups: Vec::new(),
+            powerup_rate: None,
+            shrinks: 0,
+            closed: Vec::new(),
+            spawns: course.spawn_points(),
             course_name: course.name.clone(),
             course_level: course.level,
+            course_seed: course.seed,
             winner: None,
+            teams: None,
+            winning_team: None,
+            tie_break: None,
+            events: Vec::new(),
+            chat: VecDeque::new(),
+            replay: Replay::default(),
+            abort_votes: Consensus::default(),
+            clock: Clock::default(),
+            aborted: false,
             created_at: chrono::Utc::now(),
             finished_at: None,
+        };
+        if game.rules.boosts {
+            game.set_powerups(&course.powerups, course.powerup_rate);
         }
+        Ok(game)
+    }
+
+    /// Where power-ups appear: fixed spawn points, refilled
+    /// `POWERUP_RESPAWN_TICKS` after each pickup, and optionally one on a
+    /// random empty cell every `rate` ticks. They first appear on `start`.
+    pub fn set_powerups(&mut self, spawns: &[(usize, usize)], rate: Option<u32>) {
+        self.powerups = spawns.iter().map(|&at| PowerUpSpawn { at, respawn_at: Some(0) }).collect();
+        self.powerup_rate = rate;
+    }
+
+    /// Cells by row, top to bottom; `grid()[y][x]`
+    pub fn grid(&self) -> &[Vec<Cell>] {
+        &self.grid
+    }
+
+    /// The seated players, in seat order; indexes elsewhere point into this
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Moves made so far. A real-time round moves every living cycle, so it
+    /// adds one per cycle.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// The winner's index into `players()`; `None` while running and after a draw
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// Everything that happened, oldest first
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// A player's team, 1 or 2, in team games
+    pub fn team_of(&self, idx: usize) -> Option<usize> {
+        self.teams.as_ref()?.get(idx).copied()
+    }
+
+    /// The winner's team in team games; `None` while running, after a draw
+    /// and outside team games
+    pub fn winning_team(&self) -> Option<usize> {
+        self.winning_team
+    }
+
+    /// Whether two different players ride for the same team
+    pub fn teammates(&self, a: usize, b: usize) -> bool {
+        a != b && self.team_of(a).is_some_and(|team| self.team_of(b) == Some(team))
+    }
+
+    /// Whether `rider` goes through `owner`'s trail unharmed: teammates'
+    /// trails are, unless the rules turn on friendly fire
+    pub fn harmless_trail(&self, rider: usize, owner: usize) -> bool {
+        !self.rules.friendly_fire && self.teammates(rider, owner)
+    }
+
+    /// Whether a player is credited with the win: the winner, or anyone on
+    /// the winning team
+    pub fn won(&self, idx: usize) -> bool {
+        self.winner == Some(idx) || self.winning_team.is_some_and(|team| self.team_of(idx) == Some(team))
     }
 
-    /// Spawn positions for players (corners and midpoints)
-    fn spawn_positions(&self) -> Vec<(i32, i32, Direction)> {
-        let w = self.width as i32;
-        let h = self.height as i32;
-        vec![
-            (3, 3, Direction::Right),
-            (w - 4, h - 4, Direction::Left),
-            (w - 4, 3, Direction::Down),
-            (3, h - 4, Direction::Up),
-            (w / 2, 3, Direction::Down),
-            (3, h / 2, Direction::Right),
-            (w - 4, h / 2, Direction::Left),
-            (w / 2, h - 4, Direction::Up),
-        ]
+    /// Split the seated players into two teams of `rules.team_size`. Each
+    /// player gets the team `picks` asks for (by player index) while it has
+    /// room; the rest fill the smaller team. Does nothing outside team games.
+    pub fn assign_teams(&mut self, picks: &[Option<usize>]) {
+        let Some(size) = self.rules.team_size else {
+            return;
+        };
+        let mut teams = vec![0; self.players.len()];
+        let mut counts = [0usize; 2];
+        for (team, pick) in teams.iter_mut().zip(picks) {
+            if let Some(pick @ 1..=2) = *pick
+                && counts[pick - 1] < size
+            {
+                *team = pick;
+                counts[pick - 1] += 1;
+            }
+        }
+        for team in teams.iter_mut().filter(|team| **team == 0) {
+            *team = if counts[1] < counts[0] { 2 } else { 1 };
+            counts[*team - 1] += 1;
+        }
+        self.teams = Some(teams);
     }
 
     /// Add a player to the game. Returns player index or None if full.
     pub fn add_player(&mut self, name: String) -> Option<usize> {
-        let spawns = self.spawn_positions();
+        let spawns = if self.spawns.is_empty() {
+            spawn_positions(self.width, self.height)
+        } else {
+            self.spawns.clone()
+        };
         let idx = self.players.len();
         if idx >= spawns.len() {
             return None;
         }
 
-        let (x, y, dir) = spawns[idx];
+        let (x, y, dir) = self.free_spawn(spawns[idx]);
         self.players.push(Player {
             name,
             x,
@@ -188,22 +897,96 @@ impl Game {
             trail: VecDeque::new(),
             distance_traveled: 0,
             score: 0,
+            last_move_at: chrono::Utc::now(),
+            bot: None,
+            latency_ms: None,
+            fairness_delay_ms: 0,
+            moves_waited: 0,
+            idle_moves: 0,
+            pending_action: None,
+            active_effects: Vec::new(),
+            spawn_protection_until: 0,
+            lives: 0,
+            respawn_at_tick: None,
+            kills: 0,
         });
 
         Some(idx)
     }
 
+    /// `spawn` if its cell and the one ahead are free, otherwise the nearest
+    /// spot that has both, searching outward ring by ring. Earlier players'
+    /// start cells and first steps count as taken.
+    fn free_spawn(&self, spawn: (i32, i32, Direction)) -> (i32, i32, Direction) {
+        let free = |x: i32, y: i32| {
+            x >= 0
+                && y >= 0
+                && (x as usize) < self.width
+                && (y as usize) < self.height
+                && self.grid[y as usize][x as usize] == Cell::Empty
+                && !self.players.iter().filter(|p| p.alive).any(|p| {
+                    let (dx, dy) = p.direction.delta();
+                    (p.x, p.y) == (x, y) || (p.x + dx, p.y + dy) == (x, y)
+                })
+        };
+        let (sx, sy, dir) = spawn;
+        let reach = self.width.max(self.height) as i32;
+        for r in 0..reach {
+            for y in sy - r..=sy + r {
+                for x in sx - r..=sx + r {
+                    // Only the ring at distance r; the inside was searched already
+                    if (x - sx).abs() != r && (y - sy).abs() != r {
+                        continue;
+                    }
+                    for d in [dir, dir.turn_left(), dir.turn_right(), dir.opposite()] {
+                        let (dx, dy) = d.delta();
+                        if free(x, y) && free(x + dx, y + dy) {
+                            return (x, y, d);
+                        }
+                    }
+                }
+            }
+        }
+        spawn
+    }
+
+    /// Deal the seated players' spawns out again in an order seeded from
+    /// the game id, so joining first doesn't pick the seat
+    pub fn shuffle_spawns(&mut self) {
+        let mut seats: Vec<(i32, i32, Direction)> = self.players.iter().map(|p| (p.x, p.y, p.direction)).collect();
+        let mut rng = StdRng::seed_from_u64(self.id.as_u128() as u64);
+        seats.shuffle(&mut rng);
+        for (player, (x, y, direction)) in self.players.iter_mut().zip(seats) {
+            (player.x, player.y, player.direction) = (x, y, direction);
+        }
+    }
+
     /// Start the game
     pub fn start(&mut self) {
         self.status = GameStatus::Running;
+        self.log(GameEventKind::Started);
+        // Every player moves once per round, so a round is a tick per player
+        let protected_until = self.rules.spawn_grace_moves() * self.players.len() as u32;
+        let now = chrono::Utc::now();
+        self.replay = Replay {
+            starts: self.players.iter().map(|p| (p.x, p.y, p.direction)).collect(),
+            checkpoint: self.checkpoint,
+            powerups: self.powerups.iter().map(|spawn| spawn.at).collect(),
+            powerup_rate: self.powerup_rate,
+            steps: Vec::new(),
+        };
         // Place initial player positions on the grid
-        for (idx, player) in self.players.iter().enumerate() {
+        for (idx, player) in self.players.iter_mut().enumerate() {
+            player.last_move_at = now;
+            player.spawn_protection_until = protected_until;
+            player.lives = self.rules.lives.unwrap_or(0);
             let x = player.x as usize;
             let y = player.y as usize;
             if y < self.height && x < self.width {
                 self.grid[y][x] = Cell::Trail(idx);
             }
         }
+        self.spawn_powerups();
     }
 
     /// Move a single player one step: apply steering then advance forward.
@@ -213,54 +996,122 @@ impl Game {
             return "Game is not running.".to_string();
         }
 
-        let player = &mut self.players[player_idx];
-        if !player.alive {
-            return "You have crashed! Game over.".to_string();
+        if !self.players[player_idx].alive {
+            return self.out_message(player_idx);
+        }
+        self.replay.steps.push(ReplayStep::Move {
+            player: player_idx,
+            action,
+        });
+        // Bots answer every human move, so only human moves count as waiting
+        let human_move = self.players[player_idx].bot.is_none();
+        for (i, p) in self.players.iter_mut().enumerate() {
+            if i == player_idx {
+                p.moves_waited = 0;
+            } else if human_move {
+                p.moves_waited += 1;
+            }
         }
+        let player = &mut self.players[player_idx];
 
         // Apply steering
-        match action {
-            SteerAction::Left => player.direction = player.direction.turn_left(),
-            SteerAction::Right => player.direction = player.direction.turn_right(),
-            SteerAction::Straight => {}
+        player.direction = action.turn(player.direction);
+        player.last_move_at = chrono::Utc::now();
+        let boosted = player.active_effects.contains(&ActiveEffect::SpeedBoost);
+        player.active_effects.retain(|e| *e != ActiveEffect::SpeedBoost);
+        let ghosting = player.ghost_moves().is_some();
+
+        let mut outcome = self.step(player_idx);
+        if boosted && self.status == GameStatus::Running && self.players[player_idx].alive {
+            outcome = format!("{} Speed boost: {}", outcome, self.step(player_idx));
+        }
+        // A ghost picked up on this move lasts from the next one
+        if ghosting {
+            let player = &mut self.players[player_idx];
+            for effect in player.active_effects.iter_mut() {
+                if let ActiveEffect::Ghost { moves_left } = effect {
+                    *moves_left = moves_left.saturating_sub(1);
+                }
+            }
+            player.active_effects.retain(|e| *e != ActiveEffect::Ghost { moves_left: 0 });
         }
+        outcome
+    }
 
-        // Calculate new position
+    /// Advance a player one cell along their heading: collisions, pickups,
+    /// trail and the win condition. Returns what happened.
+    fn step(&mut self, player_idx: usize) -> String {
+        let player = &self.players[player_idx];
         let (dx, dy) = player.direction.delta();
-        let nx = player.x + dx;
-        let ny = player.y + dy;
+        let (mut nx, mut ny) = (player.x + dx, player.y + dy);
+        if self.rules.wrap_edges {
+            nx = nx.rem_euclid(self.width as i32);
+            ny = ny.rem_euclid(self.height as i32);
+        }
 
         // Check out of bounds
         if nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32 {
-            self.players[player_idx].alive = false;
-            self.check_win_condition();
+            self.crash(player_idx, CrashCause::Boundary);
             return "CRASHED into the boundary wall!".to_string();
         }
 
         let ux = nx as usize;
         let uy = ny as usize;
+        // Reaching the checkpoint wins even if the cell is contested: a trail
+        // or a head arriving there on the same tick doesn't stop the finish
+        let finishing = self.rules.win_condition == WinCondition::Checkpoint
+            && self.checkpoint == Some((ux, uy));
+        let mut picked = None;
 
         // Check grid collision
         match self.grid[uy][ux] {
             Cell::Wall => {
-                self.players[player_idx].alive = false;
-                self.check_win_condition();
+                self.crash(player_idx, CrashCause::Wall);
                 return "CRASHED into a wall!".to_string();
             }
             Cell::Obstruction => {
-                self.players[player_idx].alive = false;
-                self.check_win_condition();
+                self.crash(player_idx, CrashCause::Obstruction);
                 return "CRASHED into an obstruction!".to_string();
             }
+            Cell::PowerUp(kind) => picked = Some(kind),
+            Cell::Trail(_) if finishing => {}
+            Cell::Trail(other_idx) if self.harmless_trail(player_idx, other_idx) => {}
+            Cell::Trail(other_idx)
+                if other_idx != player_idx
+                    && self.protects_from(player_idx, (ux, uy))
+                    && !self.head_at((nx, ny)) => {}
+            Cell::Trail(other_idx) if self.can_ghost(player_idx, (nx, ny)) => {
+                self.ghost_through(player_idx, other_idx, (nx, ny));
+            }
             Cell::Trail(other_idx) => {
-                self.players[player_idx].alive = false;
-                let whose = if other_idx == player_idx {
-                    "your own".to_string()
-                } else {
-                    format!("{}'s", self.players[other_idx].name)
-                };
-                self.check_win_condition();
-                return format!("CRASHED into {} trail!", whose);
+                if other_idx == player_idx {
+                    self.crash(player_idx, CrashCause::OwnTrail);
+                    return "CRASHED into your own trail!".to_string();
+                }
+                let owner = &self.players[other_idx];
+                // Two cycles driving into each other: neither gets through
+                if owner.alive
+                    && (owner.x, owner.y) == (nx, ny)
+                    && owner.direction == self.players[player_idx].direction.opposite()
+                {
+                    let other_name = owner.name.clone();
+                    self.head_on(player_idx, other_idx);
+                    return format!(
+                        "CRASHED head-on into {}! Both cycles are destroyed.",
+                        other_name
+                    );
+                }
+                let cut_off = (owner.x, owner.y) == (nx, ny)
+                    || owner.trail.iter().rev().take(2).any(|&c| c == (nx, ny));
+                let whose = owner.name.clone();
+                self.crash(
+                    player_idx,
+                    CrashCause::Trail {
+                        owner: other_idx,
+                        cut_off,
+                    },
+                );
+                return format!("CRASHED into {}'s trail!", whose);
             }
             Cell::Empty => {}
         }
@@ -270,17 +1121,16 @@ impl Game {
         let old_y = self.players[player_idx].y;
         self.players[player_idx].trail.push_back((old_x, old_y));
 
-        // Trim trail if too long
-        let max_trail = self.max_trail_length;
-        while self.players[player_idx].trail.len() > max_trail {
-            if let Some((tx, ty)) = self.players[player_idx].trail.pop_front() {
-                let tux = tx as usize;
-                let tuy = ty as usize;
-                if tuy < self.height && tux < self.width {
-                    if self.grid[tuy][tux] == Cell::Trail(player_idx) {
-                        self.grid[tuy][tux] = Cell::Empty;
-                    }
-                }
+        // Trim trail if too long (trails become permanent in sudden death,
+        // and under classic rules never fade at all)
+        let sudden_death = self
+            .rules
+            .sudden_death_tick
+            .is_some_and(|t| self.tick >= t);
+        let max_trail = self.rules.trail_limit().unwrap_or(usize::MAX);
+        while !sudden_death && self.players[player_idx].trail.len() > max_trail {
+            if let Some(cell) = self.players[player_idx].trail.pop_front() {
+                self.clear_trail_cell(player_idx, cell);
             }
         }
 
@@ -289,220 +1139,3025 @@ impl Game {
         self.players[player_idx].y = ny;
         self.players[player_idx].distance_traveled += 1;
         self.tick += 1;
+        if self.rules.sudden_death_tick == Some(self.tick) {
+            self.log(GameEventKind::SuddenDeath);
+        }
+        self.decay_dead_trails();
+
+        // Place trail on grid; riding through a teammate's trail takes the cell over
+        self.lay(player_idx, (ux, uy));
+        let pickup = picked.map(|kind| self.pick_up(player_idx, kind, (ux, uy)));
+        self.spawn_powerups();
 
-        // Place trail on grid
-        self.grid[uy][ux] = Cell::Trail(player_idx);
+        if finishing {
+            self.log(GameEventKind::ReachedCheckpoint { player: player_idx });
+            self.finish(Some(player_idx));
+            return format!(
+                "Reached the checkpoint at ({}, {})! You win!",
+                nx, ny
+            );
+        }
 
+        if self.rules.shrink_every.is_some_and(|every| self.tick.is_multiple_of(every)) {
+            self.shrink_arena();
+        }
         self.check_win_condition();
+        if !self.players[player_idx].alive {
+            return format!("Moved to ({}, {}), but the arena closed in on you: CRASHED!", nx, ny);
+        }
 
-        format!(
+        let moved = format!(
             "Moved {} to ({}, {}). Distance: {}.",
             self.players[player_idx].direction.name(),
             nx,
             ny,
             self.players[player_idx].distance_traveled
+        );
+        match pickup {
+            Some(note) => format!("{} {}", moved, note),
+            None => moved,
+        }
+    }
+
+    /// Playable cells as `(min_x, min_y, max_x, max_y)`, inclusive: inside
+    /// the border wall (when there is one) and any rings closed so far
+    pub fn arena_bounds(&self) -> (usize, usize, usize, usize) {
+        let inset = self.shrinks + usize::from(!self.rules.wrap_edges);
+        (
+            inset,
+            inset,
+            self.width.saturating_sub(inset + 1),
+            self.height.saturating_sub(inset + 1),
         )
     }
 
-    /// Check if only one (or zero) players are alive and finish the game
-    fn check_win_condition(&mut self) {
-        let alive_players: Vec<usize> = self
-            .players
-            .iter()
-            .enumerate()
-            .filter(|(_, p)| p.alive)
-            .map(|(i, _)| i)
+    /// Turn the outermost ring of playable cells into wall, crashing anyone
+    /// on it and dropping the trail cells it covers. Stops once the arena is
+    /// down to `MIN_ARENA` cells across.
+    fn shrink_arena(&mut self) {
+        let (min_x, min_y, max_x, max_y) = self.arena_bounds();
+        if max_x < min_x + MIN_ARENA || max_y < min_y + MIN_ARENA {
+            return;
+        }
+        let on_ring = |(x, y): (i32, i32)| {
+            (x == min_x as i32 || x == max_x as i32 || y == min_y as i32 || y == max_y as i32)
+                && (min_x as i32..=max_x as i32).contains(&x)
+                && (min_y as i32..=max_y as i32).contains(&y)
+        };
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                if on_ring((x as i32, y as i32))
+                    && !matches!(self.grid[y][x], Cell::Wall | Cell::Obstruction)
+                {
+                    self.grid[y][x] = Cell::Wall;
+                    self.closed.push((x, y));
+                }
+            }
+        }
+        for player in self.players.iter_mut() {
+            player.trail.retain(|&cell| !on_ring(cell));
+        }
+        self.powerups.retain(|spawn| !on_ring((spawn.at.0 as i32, spawn.at.1 as i32)));
+        self.shrinks += 1;
+        let (width, height) = (max_x - min_x - 1, max_y - min_y - 1);
+        self.log(GameEventKind::ArenaShrank { width, height });
+        let caught: Vec<usize> = (0..self.players.len())
+            .filter(|&i| self.players[i].alive && on_ring((self.players[i].x, self.players[i].y)))
             .collect();
+        for idx in caught {
+            self.knock_out(idx, CrashCause::ArenaShrink);
+        }
+    }
 
-        if alive_players.len() <= 1 && self.players.len() > 1 {
-            self.status = GameStatus::Finished;
-            self.finished_at = Some(chrono::Utc::now());
+    /// Ticks until the arena next closes in, while it still can
+    pub fn shrink_in(&self) -> Option<u32> {
+        let every = self.rules.shrink_every?;
+        let (min_x, min_y, max_x, max_y) = self.arena_bounds();
+        if max_x < min_x + MIN_ARENA || max_y < min_y + MIN_ARENA {
+            return None;
+        }
+        Some(every - self.tick % every)
+    }
 
-            if alive_players.len() == 1 {
-                let winner_idx = alive_players[0];
-                self.winner = Some(winner_idx);
+    /// Warning about the arena closing in, for `look` and `game_status`
+    pub fn shrink_line(&self) -> Option<String> {
+        let ticks = self.shrink_in()?;
+        let (min_x, min_y, max_x, max_y) = self.arena_bounds();
+        Some(format!(
+            "Arena shrinks in {} tick{}: cells at x={} or {}, or y={} or {}, become wall, and anyone on them crashes.",
+            ticks,
+            if ticks == 1 { "" } else { "s" },
+            min_x,
+            max_x,
+            min_y,
+            max_y
+        ))
+    }
 
-                let speed_bonus = if self.tick > 0 {
-                    (1000 / self.tick).min(200)
-                } else {
-                    0
-                };
-                self.players[winner_idx].score =
-                    100 + self.players[winner_idx].distance_traveled + speed_bonus;
-            }
-        }
+    /// Whether a ghosting player may ride through the trail cell at `at`:
+    /// a ghost passes trails, never a living cycle's head
+    fn can_ghost(&self, player_idx: usize, at: (i32, i32)) -> bool {
+        self.players[player_idx].ghost_moves().is_some() && !self.head_at(at)
     }
 
-    /// Get the visible area around a player for the `look` tool
-    pub fn look(&self, player_idx: usize, view_radius: usize) -> String {
-        let player = &self.players[player_idx];
-        let mut lines = Vec::new();
+    /// Whether a living cycle's head is at `at`
+    fn head_at(&self, at: (i32, i32)) -> bool {
+        self.players.iter().any(|p| p.alive && (p.x, p.y) == at)
+    }
 
-        lines.push(format!(
-            "Your light-cycle '{}' is at ({}, {}) heading {}.",
-            player.name, player.x, player.y, player.direction.name()
-        ));
+    /// Whether `player_idx`'s spawn protection covers the trail cell at
+    /// `at`: it has to be laid since they (re)spawned, so older trail kills
+    fn protects_from(&self, player_idx: usize, at: (usize, usize)) -> bool {
+        if self.protected_for(player_idx).is_none() {
+            return false;
+        }
+        let window = self.rules.spawn_grace_moves() * self.players.len() as u32;
+        let since = self.players[player_idx].spawn_protection_until.saturating_sub(window);
+        let laid = self.laid_at.get(at.1).and_then(|row| row.get(at.0)).copied().unwrap_or(0);
+        laid >= since
+    }
 
-        if !player.alive {
-            lines.push("YOU HAVE CRASHED! Game over for you.".to_string());
-            return lines.join("\n");
+    /// Put `player_idx`'s trail on the cell at `at`, laid this tick
+    fn lay(&mut self, player_idx: usize, (x, y): (usize, usize)) {
+        self.grid[y][x] = Cell::Trail(player_idx);
+        if let Some(laid) = self.laid_at.get_mut(y).and_then(|row| row.get_mut(x)) {
+            *laid = self.tick;
         }
+    }
 
-        lines.push(format!(
-            "Distance traveled: {}. Tick: {}.",
-            player.distance_traveled, self.tick
-        ));
+    /// Ticks of spawn protection `player_idx` has left, while it lasts
+    pub fn protected_for(&self, player_idx: usize) -> Option<u32> {
+        let until = self.players[player_idx].spawn_protection_until;
+        (self.tick < until).then(|| until - self.tick)
+    }
 
-        let alive_count = self.players.iter().filter(|p| p.alive).count();
-        let total_count = self.players.len();
-        lines.push(format!(
-            "Players alive: {}/{}",
-            alive_count, total_count
-        ));
+    /// Spend a ghost riding through `owner`'s trail cell at `at`; the cell
+    /// becomes the rider's
+    fn ghost_through(&mut self, player_idx: usize, owner: usize, at: (i32, i32)) {
+        let player = &mut self.players[player_idx];
+        player.active_effects.retain(|e| !matches!(e, ActiveEffect::Ghost { .. }));
+        if owner == player_idx {
+            // The cell moves to the front of the trail; keep it there only
+            player.trail.retain(|&cell| cell != at);
+        }
+        self.log(GameEventKind::Ghosted { player: player_idx, owner });
+    }
 
-        // Render grid view
-        let r = view_radius as i32;
-        lines.push(format!(
-            "Grid ({}x{} view centered on you):",
-            view_radius * 2 + 1,
-            view_radius * 2 + 1
-        ));
+    /// Collect the power-up at `at` and apply it. Returns a note for the
+    /// move's reply.
+    fn pick_up(&mut self, player_idx: usize, kind: PowerUpKind, at: (usize, usize)) -> String {
+        self.log(GameEventKind::PowerUp { player: player_idx, kind });
+        let tick = self.tick;
+        if let Some(spawn) = self.powerups.iter_mut().find(|spawn| spawn.at == at) {
+            spawn.respawn_at = Some(tick + POWERUP_RESPAWN_TICKS);
+        }
+        let player = &mut self.players[player_idx];
+        match kind {
+            PowerUpKind::SpeedBoost => {
+                if !player.active_effects.contains(&ActiveEffect::SpeedBoost) {
+                    player.active_effects.push(ActiveEffect::SpeedBoost);
+                }
+                "Picked up a speed boost: your next steer moves 2 cells.".to_string()
+            }
+            PowerUpKind::TrailEraser => {
+                let erased: Vec<(i32, i32)> = (0..ERASER_CELLS).map_while(|_| player.trail.pop_front()).collect();
+                for &cell in &erased {
+                    self.clear_trail_cell(player_idx, cell);
+                }
+                format!("Picked up a trail eraser: the oldest {} cells of your trail are gone.", erased.len())
+            }
+            PowerUpKind::Ghost => {
+                player.active_effects.retain(|e| !matches!(e, ActiveEffect::Ghost { .. }));
+                player.active_effects.push(ActiveEffect::Ghost { moves_left: GHOST_MOVES });
+                format!(
+                    "Picked up a ghost: you ride through the next trail cell you hit (not a head) within {} moves.",
+                    GHOST_MOVES
+                )
+            }
+        }
+    }
 
-        for dy in -r..=r {
-            let mut row = String::new();
-            for dx in -r..=r {
-                let gx = player.x + dx;
-                let gy = player.y + dy;
+    /// Fill spawn points whose cooldown is over and, on the spawn rate's
+    /// ticks, drop a power-up on a random empty cell. Deterministic given
+    /// the game id and tick, so replays see the same power-ups.
+    fn spawn_powerups(&mut self) {
+        for i in 0..self.powerups.len() {
+            let PowerUpSpawn { at: (x, y), respawn_at } = self.powerups[i];
+            if respawn_at.is_some_and(|t| self.tick >= t) && self.grid[y][x] == Cell::Empty {
+                self.grid[y][x] = Cell::PowerUp(PowerUpKind::ALL[(self.tick as usize + i) % PowerUpKind::ALL.len()]);
+                self.powerups[i].respawn_at = None;
+            }
+        }
+        let Some(rate) = self.powerup_rate else {
+            return;
+        };
+        let live = self.grid.iter().flatten().filter(|c| matches!(c, Cell::PowerUp(_))).count();
+        if !self.tick.is_multiple_of(rate) || live >= self.powerups.len() + MAX_RANDOM_POWERUPS {
+            return;
+        }
+        let mut rng = StdRng::seed_from_u64(self.id.as_u128() as u64 ^ u64::from(self.tick));
+        for _ in 0..20 {
+            let (x, y) = (rng.gen_range(0..self.width), rng.gen_range(0..self.height));
+            if self.grid[y][x] == Cell::Empty && self.checkpoint != Some((x, y)) {
+                self.grid[y][x] = Cell::PowerUp(PowerUpKind::ALL[rng.gen_range(0..PowerUpKind::ALL.len())]);
+                break;
+            }
+        }
+    }
 
-                if !row.is_empty() {
-                    row.push(' ');
-                }
+    /// Real-time games: remember a steer for the player's next step
+    pub fn queue_steer(&mut self, player_idx: usize, action: SteerAction) -> String {
+        if self.status != GameStatus::Running {
+            return "Game is not running.".to_string();
+        }
+        if !self.players[player_idx].alive {
+            return self.out_message(player_idx);
+        }
+        let player = &mut self.players[player_idx];
+        player.pending_action = Some(action);
+        format!(
+            "Steering {} on the next tick. Your cycle advances every tick whether you steer or not.",
+            action.name()
+        )
+    }
 
-                if gx == player.x && gy == player.y {
-                    row.push('@');
-                } else if gx < 0
-                    || gy < 0
-                    || gx >= self.width as i32
-                    || gy >= self.height as i32
-                {
-                    row.push('#');
-                } else {
-                    let cell = self.grid[gy as usize][gx as usize];
-                    match cell {
-                        Cell::Empty => row.push('.'),
-                        Cell::Wall => row.push('#'),
-                        Cell::Obstruction => row.push('X'),
-                        Cell::Trail(idx) => {
-                            if idx == player_idx {
-                                row.push('|');
-                            } else {
-                                // Use digits 1-9 for other players
-                                let digit = ((idx % 9) + 1).to_string();
-                                row.push_str(&digit);
-                            }
-                        }
-                    }
-                }
+    /// Real-time games: move every living cycle one step with its pending
+    /// action, or straight ahead without one. The first seat to move rotates
+    /// so nobody always gets first claim on a contested cell.
+    pub fn advance(&mut self) {
+        let count = self.players.len();
+        if count == 0 {
+            return;
+        }
+        let first = self.rounds as usize % count;
+        self.rounds += 1;
+        for idx in (0..count).map(|i| (first + i) % count) {
+            if self.status != GameStatus::Running {
+                return;
             }
-            lines.push(row);
+            if !self.players[idx].alive {
+                continue;
+            }
+            let action = self.players[idx].pending_action.take().unwrap_or(SteerAction::Straight);
+            self.move_player(idx, action);
         }
+    }
 
-        lines.push(String::new());
-        lines.push(
-            "Legend: @ = you, | = your trail, 1-9 = other players/trails, # = wall, X = obstruction, . = empty"
-                .to_string(),
-        );
+    fn log(&mut self, kind: GameEventKind) {
+        self.events.push(GameEvent {
+            tick: self.tick,
+            kind,
+        });
+    }
 
-        // Show other players info
-        for (i, p) in self.players.iter().enumerate() {
-            if i == player_idx {
+    /// Knock a player out of the game
+    fn crash(&mut self, player_idx: usize, cause: CrashCause) {
+        self.knock_out(player_idx, cause);
+        self.check_win_condition();
+    }
+
+    /// Mark a player crashed and apply the dead-trail policy, without
+    /// deciding the game; callers crashing several players check the win
+    /// condition once afterwards
+    fn knock_out(&mut self, player_idx: usize, cause: CrashCause) {
+        self.players[player_idx].alive = false;
+        self.log(GameEventKind::Crashed {
+            player: player_idx,
+            cause,
+        });
+        // Taking out a teammate earns nothing
+        if let Some(killer) = cause.killer(player_idx)
+            && !self.teammates(player_idx, killer)
+        {
+            self.players[killer].kills += 1;
+        }
+        let rounds = self.players.len() as u32;
+        let tick = self.tick;
+        let player = &mut self.players[player_idx];
+        player.respawn_at_tick = None;
+        player.lives = match cause {
+            CrashCause::Kicked | CrashCause::Forfeit => 0,
+            _ => player.lives.saturating_sub(1),
+        };
+        if player.lives > 0 {
+            player.respawn_at_tick = Some(tick + RESPAWN_ROUNDS * rounds);
+        }
+        // A cycle that will ride again leaves no wreck behind
+        if self.rules.dead_trail_policy == DeadTrailPolicy::ClearImmediately || self.players[player_idx].lives > 0 {
+            for cell in std::mem::take(&mut self.players[player_idx].trail) {
+                self.clear_trail_cell(player_idx, cell);
+            }
+            let head = (self.players[player_idx].x, self.players[player_idx].y);
+            self.clear_trail_cell(player_idx, head);
+        }
+    }
+
+    /// Under `Decay(n)`, remove the oldest cell of every crashed cycle's
+    /// trail once n ticks have passed since the crash; the head goes last
+    fn decay_dead_trails(&mut self) {
+        let DeadTrailPolicy::Decay(delay) = self.rules.dead_trail_policy else {
+            return;
+        };
+        for idx in 0..self.players.len() {
+            if self.players[idx].alive || self.tick < self.survival_ticks(idx).saturating_add(delay) {
                 continue;
             }
-            let status = if p.alive { "ALIVE" } else { "CRASHED" };
-            let distance = ((p.x - player.x).abs() + (p.y - player.y).abs()) as u32;
-            lines.push(format!(
-                "Player '{}': {} (manhattan distance: {})",
-                p.name, status, distance
+            let player = &mut self.players[idx];
+            let cell = player.trail.pop_front().unwrap_or((player.x, player.y));
+            self.clear_trail_cell(idx, cell);
+        }
+    }
+
+    /// Empty a grid cell if it still holds this player's trail
+    fn clear_trail_cell(&mut self, player_idx: usize, (x, y): (i32, i32)) {
+        if x >= 0
+            && y >= 0
+            && (y as usize) < self.height
+            && (x as usize) < self.width
+            && self.grid[y as usize][x as usize] == Cell::Trail(player_idx)
+        {
+            self.grid[y as usize][x as usize] = Cell::Empty;
+        }
+    }
+
+    /// Bring back every crashed player whose respawn is due, or all of them
+    /// when nobody is left riding. Each comes back at the nearest free spot
+    /// to where they started, with spawn protection.
+    fn respawn_due(&mut self) {
+        let nobody_riding = !self.players.iter().any(|p| p.alive);
+        for idx in 0..self.players.len() {
+            let due = self.players[idx].respawn_at_tick.is_some_and(|at| nobody_riding || at <= self.tick);
+            if !due {
+                continue;
+            }
+            let start = self.replay.starts.get(idx).copied().unwrap_or((
+                self.players[idx].x,
+                self.players[idx].y,
+                self.players[idx].direction,
             ));
+            let (x, y, direction) = self.free_spawn(start);
+            let protected_until = self.tick + self.rules.spawn_grace_moves() * self.players.len() as u32;
+            let player = &mut self.players[idx];
+            (player.x, player.y, player.direction) = (x, y, direction);
+            player.alive = true;
+            player.respawn_at_tick = None;
+            player.spawn_protection_until = protected_until;
+            player.active_effects.clear();
+            player.last_move_at = chrono::Utc::now();
+            let lives = player.lives;
+            self.lay(idx, (x as usize, y as usize));
+            self.log(GameEventKind::Respawned { player: idx, lives });
+        }
+    }
+
+    /// What a crashed player is told when they try to move
+    fn out_message(&self, player_idx: usize) -> String {
+        let player = &self.players[player_idx];
+        match player.respawn_at_tick {
+            Some(at) => format!(
+                "You crashed and respawn at tick {} (now {}) with {} li{} left. Wait for it, then look() again.",
+                at,
+                self.tick,
+                player.lives,
+                if player.lives == 1 { "fe" } else { "ves" }
+            ),
+            None => "You have crashed! Game over.".to_string(),
         }
+    }
 
-        lines.join("\n")
+    /// In games with lives, how many `player_idx` has and what a crash costs
+    fn lives_line(&self, player_idx: usize) -> Option<String> {
+        let lives = self.lives_left(player_idx)?;
+        Some(format!(
+            "Lives: {} left, counting this one. A crash with lives left costs {} points and respawns you near your start after {} rounds.",
+            lives, DEATH_PENALTY, RESPAWN_ROUNDS
+        ))
     }
 
-    /// Serialize game state for the web UI
-    pub fn to_web_state(&self) -> WebGameState {
-        let grid_data: Vec<Vec<u8>> = self
-            .grid
+    /// Lives `player_idx` has left, in games with lives
+    pub fn lives_left(&self, player_idx: usize) -> Option<u32> {
+        self.rules.lives.map(|_| self.players[player_idx].lives)
+    }
+
+    /// ALIVE, RESPAWNING or CRASHED, with lives left in games that have them
+    pub fn status_of(&self, player_idx: usize) -> String {
+        let player = &self.players[player_idx];
+        let status = match player.respawn_at_tick {
+            _ if player.alive => "ALIVE".to_string(),
+            Some(at) => format!("RESPAWNING at tick {}", at),
+            None => "CRASHED".to_string(),
+        };
+        match self.lives_left(player_idx) {
+            Some(lives) => format!("{} ({} li{} left)", status, lives, if lives == 1 { "fe" } else { "ves" }),
+            None => status,
+        }
+    }
+
+    /// Crash a player out on an operator's request
+    pub fn kick(&mut self, player_idx: usize) {
+        if self.status == GameStatus::Running && self.players[player_idx].in_play() {
+            self.knock_out_between_moves(&[player_idx], CrashCause::Kicked);
+        }
+    }
+
+    /// A player leaves a running game: their cycle crashes and the others
+    /// play on. Returns false if they were already out.
+    pub fn forfeit(&mut self, player_idx: usize) -> bool {
+        if self.status != GameStatus::Running || !self.players[player_idx].in_play() {
+            return false;
+        }
+        self.knock_out_between_moves(&[player_idx], CrashCause::Forfeit);
+        true
+    }
+
+    /// Ask to abort the game. It is voided once every living human player has
+    /// asked within `ABORT_WINDOW_SECS`; bots always agree.
+    pub fn request_abort(&mut self, player_idx: usize) -> AbortOutcome {
+        let required: Vec<usize> = self
+            .players
             .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|cell| match cell {
-                        Cell::Empty => 0,
-                        Cell::Wall => 1,
-                        Cell::Obstruction => 2,
-                        Cell::Trail(idx) => (3 + *idx) as u8,
-                    })
-                    .collect()
-            })
+            .enumerate()
+            .filter(|(_, p)| p.alive && p.bot.is_none())
+            .map(|(i, _)| i)
             .collect();
+        let window = chrono::Duration::seconds(ABORT_WINDOW_SECS);
+        let now = self.clock.now();
+        if self.abort_votes.vote(player_idx, &required, window, now) {
+            self.aborted = true;
+            self.replay.steps.push(ReplayStep::End);
+            self.finish(None);
+            return AbortOutcome::Aborted;
+        }
+        let voters = self.abort_votes.voters(window, now);
+        AbortOutcome::Pending(
+            required
+                .into_iter()
+                .filter(|i| !voters.contains(i))
+                .map(|i| self.players[i].name.clone())
+                .collect(),
+        )
+    }
 
-        let players: Vec<WebPlayer> = self
+    /// Tell a player that others have asked to abort and are waiting on them
+    pub fn abort_notice(&self, player_idx: usize) -> Option<String> {
+        if self.status != GameStatus::Running {
+            return None;
+        }
+        let voters = self
+            .abort_votes
+            .voters(chrono::Duration::seconds(ABORT_WINDOW_SECS), self.clock.now());
+        if voters.is_empty() || voters.contains(&player_idx) {
+            return None;
+        }
+        let names: Vec<&str> = voters.iter().map(|&i| self.players[i].name.as_str()).collect();
+        Some(format!(
+            "{} asked to abort this game. Call abort() within {}s to agree and void it (no stats), or keep playing.",
+            names.join(", "),
+            ABORT_WINDOW_SECS
+        ))
+    }
+
+    /// Record what a player said. Fails with the seconds left to wait
+    /// while their last message is under `CHAT_COOLDOWN_SECS` old.
+    pub fn say(&mut self, player_idx: usize, text: String) -> Result<&ChatMessage, i64> {
+        let now = self.clock.now();
+        if let Some(last) = self.chat.iter().rev().find(|m| m.player == player_idx) {
+            let waited = (now - last.at).num_seconds();
+            if waited < CHAT_COOLDOWN_SECS {
+                return Err(CHAT_COOLDOWN_SECS - waited);
+            }
+        }
+        if self.chat.len() == CHAT_HISTORY {
+            self.chat.pop_front();
+        }
+        self.chat.push_back(ChatMessage {
+            player: player_idx,
+            tick: self.tick,
+            text,
+            at: now,
+        });
+        Ok(self.chat.back().expect("just pushed"))
+    }
+
+    /// The last `CHAT_SHOWN` messages the other players said, oldest first,
+    /// as `name (tick n): "text"`
+    pub fn chat_for(&self, player_idx: usize) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .chat
+            .iter()
+            .rev()
+            .filter(|m| m.player != player_idx)
+            .take(CHAT_SHOWN)
+            .map(|m| self.chat_line(m))
+            .collect();
+        lines.reverse();
+        lines
+    }
+
+    pub fn chat_line(&self, message: &ChatMessage) -> String {
+        format!("{} (tick {}): \"{}\"", self.players[message.player].name, message.tick, message.text)
+    }
+
+    /// End the game now without a winner
+    pub fn end_now(&mut self) {
+        if self.status != GameStatus::Finished {
+            self.replay.steps.push(ReplayStep::End);
+            self.finish(None);
+        }
+    }
+
+    /// Knock players out together, outside of anyone's move, and decide the
+    /// game once they are all out
+    fn knock_out_between_moves(&mut self, players: &[usize], cause: CrashCause) {
+        self.replay.steps.push(ReplayStep::Out {
+            players: players.to_vec(),
+            cause,
+        });
+        for &idx in players {
+            self.knock_out(idx, cause);
+        }
+        // Leaving before anyone moved would hand the others a free win;
+        // the game is voided like an abort instead
+        if cause == CrashCause::Forfeit && self.tick == 0 && self.players.len() > 1 && self.sides_alive() <= 1 {
+            self.aborted = true;
+            self.finish(None);
+            return;
+        }
+        self.check_win_condition();
+    }
+
+    /// Apply a step recorded in another game's `replay`. Steps that no
+    /// longer apply (a crashed player moving, the game already over) do nothing.
+    pub fn replay_step(&mut self, step: &ReplayStep) {
+        if self.status != GameStatus::Running {
+            return;
+        }
+        match step {
+            ReplayStep::Move { player, action } => {
+                self.move_player(*player, *action);
+            }
+            ReplayStep::Out { players, cause } => {
+                let alive: Vec<usize> = players.iter().copied().filter(|&i| self.players[i].in_play()).collect();
+                self.knock_out_between_moves(&alive, *cause);
+            }
+            ReplayStep::End => self.end_now(),
+        }
+    }
+
+    /// Knock out two cycles that collided head-on
+    fn head_on(&mut self, a: usize, b: usize) {
+        for (player, other) in [(a, b), (b, a)] {
+            self.knock_out(player, CrashCause::HeadOn { other });
+        }
+        self.check_win_condition();
+    }
+
+    /// Check if only one (or zero) players are alive, or in team games one
+    /// team, or a territory game's time is up, and finish the game
+    fn check_win_condition(&mut self) {
+        // Players waiting to respawn are still in the game
+        let alive_players: Vec<usize> = self
             .players
             .iter()
             .enumerate()
-            .map(|(i, p)| WebPlayer {
-                index: i,
-                name: p.name.clone(),
-                x: p.x,
-                y: p.y,
-                alive: p.alive,
-                direction: p.direction,
-                distance: p.distance_traveled,
-                score: p.score,
+            .filter(|(_, p)| p.in_play())
+            .map(|(i, _)| i)
+            .collect();
+
+        let territory = self.rules.win_condition == WinCondition::Territory;
+        let time_up = territory && self.tick >= self.rules.territory_end();
+        if !territory && self.rules.max_ticks.is_some_and(|max| self.tick >= max) && alive_players.len() > 1 {
+            self.log(GameEventKind::TimeLimit);
+            let winner = self.time_limit_leader(&alive_players);
+            self.finish(winner);
+            return;
+        }
+        if (self.sides_alive() <= 1 && self.players.len() > 1) || time_up {
+            let winner = match alive_players.first() {
+                _ if territory => self.territory_leader(),
+                Some(&idx) => Some(idx),
+                None => self.break_tie(),
+            };
+            self.finish(winner);
+        } else {
+            self.respawn_due();
+        }
+    }
+
+    /// Players (or teams, in team games) with a cycle still alive or about
+    /// to respawn
+    fn sides_alive(&self) -> usize {
+        let alive = self.players.iter().enumerate().filter(|(_, p)| p.in_play()).map(|(i, _)| i);
+        match &self.teams {
+            Some(_) => {
+                let mut teams: Vec<usize> = alive.filter_map(|i| self.team_of(i)).collect();
+                teams.sort_unstable();
+                teams.dedup();
+                teams.len()
+            }
+            None => alive.count(),
+        }
+    }
+
+    /// Trail cells on the grid for each player, by player index
+    pub fn cells_claimed(&self) -> Vec<usize> {
+        let mut cells = vec![0; self.players.len()];
+        for cell in self.grid.iter().flatten() {
+            if let Cell::Trail(idx) = *cell
+                && let Some(count) = cells.get_mut(idx)
+            {
+                *count += 1;
+            }
+        }
+        cells
+    }
+
+    /// `cells_claimed`, but only in territory games
+    fn territory_cells(&self) -> Option<Vec<usize>> {
+        (self.rules.win_condition == WinCondition::Territory).then(|| self.cells_claimed())
+    }
+
+    /// Whether a territory game has run its course: time is up or at most
+    /// one side is left
+    pub fn territory_decided(&self) -> bool {
+        self.rules.win_condition == WinCondition::Territory
+            && (self.tick >= self.rules.territory_end() || self.sides_alive() <= 1)
+    }
+
+    /// Winner of a territory game: the most trail cells, then whoever lasted
+    /// longest. None when that still leaves a tie.
+    fn territory_leader(&mut self) -> Option<usize> {
+        let cells = self.cells_claimed();
+        if self.teams.is_some() {
+            // Teams pool their cells; the team's biggest holder stands in
+            // for it so `finish` credits the whole team
+            let totals = self.team_cells(&cells);
+            return match totals[..] {
+                [one, two] if one != two => {
+                    let team = if one > two { 1 } else { 2 };
+                    (0..cells.len()).filter(|&i| self.team_of(i) == Some(team)).max_by_key(|&i| cells[i])
+                }
+                _ => None,
+            };
+        }
+        let most = cells.iter().copied().max()?;
+        let leaders: Vec<usize> = (0..cells.len()).filter(|&i| cells[i] == most).collect();
+        if let [only] = leaders[..] {
+            return Some(only);
+        }
+        // A cycle still riding outlasts one that crashed on the final tick
+        let lasted = |i: usize| (self.players[i].alive, self.survival_ticks(i));
+        let longest = leaders.iter().map(|&i| lasted(i)).max()?;
+        match leaders.iter().filter(|&&i| lasted(i) == longest).collect::<Vec<_>>()[..] {
+            [&only] => {
+                self.tie_break = Some(TieBreakReason::Survival);
+                Some(only)
+            }
+            _ => None,
+        }
+    }
+
+    /// Cells held by each team, team 1 first
+    fn team_cells(&self, cells: &[usize]) -> Vec<usize> {
+        let mut totals = vec![0; 2];
+        for (i, &count) in cells.iter().enumerate() {
+            if let Some(team) = self.team_of(i) {
+                totals[team - 1] += count;
+            }
+        }
+        totals
+    }
+
+    /// For territory games: when the game ends and who holds how many
+    /// cells, `viewer` first as "you"
+    pub fn territory_line(&self, viewer: Option<usize>) -> Option<String> {
+        if self.rules.win_condition != WinCondition::Territory {
+            return None;
+        }
+        let cells = self.cells_claimed();
+        let mut order: Vec<usize> = (0..self.players.len()).collect();
+        order.sort_by_key(|&i| (Some(i) != viewer, std::cmp::Reverse(cells[i])));
+        let counts: Vec<String> = order
+            .iter()
+            .map(|&i| match viewer {
+                Some(v) if v == i => format!("you {}", cells[i]),
+                _ => format!("{} {}", self.players[i].name, cells[i]),
             })
             .collect();
+        let end = self.rules.territory_end();
+        let teams = match self.teams {
+            Some(_) => {
+                let totals = self.team_cells(&cells);
+                format!(" Team cells: Team 1 {}, Team 2 {}.", totals[0], totals[1])
+            }
+            None => String::new(),
+        };
+        Some(format!(
+            "Territory game: the most trail cells on the grid wins at tick {} ({} ticks left) or when one side is left. Cells: {}.{}",
+            end,
+            end.saturating_sub(self.tick),
+            counts.join(", "),
+            teams
+        ))
+    }
 
-        WebGameState {
-            id: self.id.to_string(),
-            width: self.width,
-            height: self.height,
-            grid: grid_data,
-            players,
-            status: self.status,
-            tick: self.tick,
-            course_name: self.course_name.clone(),
-            course_level: self.course_level,
-            winner: self.winner,
-            created_at: self.created_at.to_rfc3339(),
-            finished_at: self.finished_at.map(|t| t.to_rfc3339()),
+    /// Winner among `riders` when the time limit runs out: the longest
+    /// distance traveled, then the most kills, then (unless the `tie_break`
+    /// rule says draw) a coin flip seeded from the game id
+    fn time_limit_leader(&mut self, riders: &[usize]) -> Option<usize> {
+        let distance = |i: usize| self.players[i].distance_traveled;
+        let furthest = riders.iter().map(|&i| distance(i)).max()?;
+        let leaders: Vec<usize> = riders.iter().copied().filter(|&i| distance(i) == furthest).collect();
+        if let [only] = leaders[..] {
+            return Some(only);
+        }
+        let most = leaders.iter().map(|&i| self.players[i].kills).max()?;
+        let leaders: Vec<usize> = leaders.into_iter().filter(|&i| self.players[i].kills == most).collect();
+        if let [only] = leaders[..] {
+            self.tie_break = Some(TieBreakReason::Kills);
+            return Some(only);
+        }
+        if self.rules.tie_break == TieBreak::Draw {
+            return None;
         }
+        let mut rng = StdRng::seed_from_u64(self.id.as_u128() as u64);
+        self.tie_break = Some(TieBreakReason::CoinFlip);
+        Some(leaders[rng.gen_range(0..leaders.len())])
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WebGameState {
-    pub id: String,
-    pub width: usize,
-    pub height: usize,
-    pub grid: Vec<Vec<u8>>,
-    pub players: Vec<WebPlayer>,
-    pub status: GameStatus,
-    pub tick: u32,
-    pub course_name: String,
-    pub course_level: u32,
-    pub winner: Option<usize>,
-    pub created_at: String,
-    pub finished_at: Option<String>,
-}
+    /// How long trails get before they fade, for `look` and `game_status`
+    pub fn trail_line(&self) -> String {
+        let sudden_death = self.rules.sudden_death_tick;
+        match self.rules.trail_limit() {
+            None => "Trails are permanent: nothing fades (classic rules).".to_string(),
+            Some(_) if sudden_death.is_some_and(|t| self.tick >= t) => {
+                "Trails are permanent now: sudden death has begun.".to_string()
+            }
+            Some(len) => match sudden_death {
+                Some(tick) => format!("Trails fade after {} cells until sudden death at tick {}.", len, tick),
+                None => format!("Trails fade after {} cells.", len),
+            },
+        }
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WebPlayer {
-    pub index: usize,
-    pub name: String,
-    pub x: i32,
-    pub y: i32,
-    pub alive: bool,
-    pub direction: Direction,
-    pub distance: u32,
-    pub score: u32,
+    /// Whether the game ended on the `max_ticks` time limit
+    pub fn hit_time_limit(&self) -> bool {
+        self.events.iter().any(|e| e.kind == GameEventKind::TimeLimit)
+    }
+
+    /// Why a finished game ended, when it wasn't down to the last cycle
+    /// standing or the checkpoint
+    pub fn finish_reason(&self) -> Option<&'static str> {
+        if self.voided_by_forfeit() {
+            return Some("forfeit before the first move");
+        }
+        self.hit_time_limit().then_some("time limit")
+    }
+
+    /// Whether the game was voided because players left before anyone moved
+    fn voided_by_forfeit(&self) -> bool {
+        self.aborted
+            && self.tick == 0
+            && self.events.iter().any(|e| matches!(e.kind, GameEventKind::Crashed { cause: CrashCause::Forfeit, .. }))
+    }
+
+    /// For games with a time limit: when it runs out and who leads on
+    /// distance
+    pub fn time_limit_line(&self) -> Option<String> {
+        let max = self.rules.max_ticks?;
+        if self.rules.win_condition == WinCondition::Territory {
+            return None;
+        }
+        Some(format!(
+            "Time limit: the game ends at tick {} ({} ticks left); then the longest distance traveled wins, then the most kills.",
+            max,
+            max.saturating_sub(self.tick)
+        ))
+    }
+
+    /// Winner among the cycles that crashed on the final tick, under the
+    /// `tie_break` rule: the longest distance traveled, then a coin flip
+    /// seeded from the game id. None means a draw.
+    fn break_tie(&mut self) -> Option<usize> {
+        if self.rules.tie_break == TieBreak::Draw {
+            return None;
+        }
+        let last_tick = (0..self.players.len()).map(|i| self.survival_ticks(i)).max()?;
+        let last_out: Vec<usize> = (0..self.players.len())
+            .filter(|&i| self.survival_ticks(i) == last_tick)
+            .collect();
+        let furthest = last_out.iter().map(|&i| self.players[i].distance_traveled).max()?;
+        let leaders: Vec<usize> = last_out
+            .into_iter()
+            .filter(|&i| self.players[i].distance_traveled == furthest)
+            .collect();
+
+        let (winner, reason) = if let [only] = leaders[..] {
+            (only, TieBreakReason::Distance)
+        } else {
+            let mut rng = StdRng::seed_from_u64(self.id.as_u128() as u64);
+            (leaders[rng.gen_range(0..leaders.len())], TieBreakReason::CoinFlip)
+        };
+        self.tie_break = Some(reason);
+        Some(winner)
+    }
+
+    /// Finish the game and score everyone: distance plus ticks survived plus
+    /// a bonus per kill (plus trail cells held, in territory games), with a
+    /// win bonus (and a bonus for winning fast) on top for the winner, or in
+    /// team games for everyone on the winner's team
+    fn finish(&mut self, winner: Option<usize>) {
+        self.status = GameStatus::Finished;
+        self.finished_at = Some(chrono::Utc::now());
+        self.log(GameEventKind::Finished { winner });
+
+        for idx in 0..self.players.len() {
+            let player = &self.players[idx];
+            self.players[idx].score = player.distance_traveled + self.survival_ticks(idx) + player.kills * KILL_BONUS;
+        }
+        if self.rules.win_condition == WinCondition::Territory {
+            for (idx, cells) in self.cells_claimed().into_iter().enumerate() {
+                self.players[idx].score += cells as u32;
+            }
+        }
+        if self.rules.lives.is_some() {
+            for idx in 0..self.players.len() {
+                let deaths = self.deaths(idx);
+                self.players[idx].score = self.players[idx].score.saturating_sub(deaths * DEATH_PENALTY);
+            }
+        }
+        if let Some(winner_idx) = winner {
+            self.winner = Some(winner_idx);
+            self.winning_team = self.team_of(winner_idx);
+
+            let speed_bonus = 1000u32.checked_div(self.tick).unwrap_or(0).min(200);
+            for idx in 0..self.players.len() {
+                if self.won(idx) {
+                    self.players[idx].score += WIN_BONUS + speed_bonus;
+                }
+            }
+        }
+    }
+
+    /// Ticks a player lasted: until their last crash, or the whole game
+    pub fn survival_ticks(&self, idx: usize) -> u32 {
+        if self.players[idx].in_play() {
+            return self.tick;
+        }
+        self.events
+            .iter()
+            .rev()
+            .find(|e| matches!(e.kind, GameEventKind::Crashed { player, .. } if player == idx))
+            .map_or(self.tick, |e| e.tick)
+    }
+
+    /// Times `idx` crashed
+    pub fn deaths(&self, idx: usize) -> u32 {
+        self.events
+            .iter()
+            .filter(|e| matches!(e.kind, GameEventKind::Crashed { player, .. } if player == idx))
+            .count() as u32
+    }
+
+    /// Crash every living player who has not steered within the move timeout.
+    /// Returns the names of the players that timed out.
+    pub fn enforce_move_timeout(&mut self) -> Vec<String> {
+        let Some(secs) = self.rules.move_timeout_secs else {
+            return Vec::new();
+        };
+        if self.status != GameStatus::Running {
+            return Vec::new();
+        }
+
+        let deadline = chrono::Utc::now() - chrono::Duration::seconds(secs as i64);
+        let late: Vec<usize> = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.alive && p.last_move_at < deadline)
+            .map(|(i, _)| i)
+            .collect();
+        if late.is_empty() {
+            return Vec::new();
+        }
+        self.knock_out_between_moves(&late, CrashCause::Timeout);
+        late.iter().map(|&idx| self.players[idx].name.clone()).collect()
+    }
+
+    /// Apply the server's idle policy to every living human player who has
+    /// not steered for too long, or while opponents moved too often. Returns
+    /// the names of the players it acted on.
+    pub fn enforce_idle(&mut self, policy: IdlePolicy) -> Vec<String> {
+        if self.status != GameStatus::Running {
+            return Vec::new();
+        }
+        let deadline = policy
+            .after_secs
+            .map(|secs| chrono::Utc::now() - chrono::Duration::seconds(secs as i64));
+        let idle: Vec<usize> = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| {
+                p.alive
+                    && p.bot.is_none()
+                    && (deadline.is_some_and(|d| p.last_move_at < d)
+                        || policy.after_moves.is_some_and(|n| p.moves_waited >= n))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        if policy.action == IdleAction::Crash {
+            // Crash everyone idle together, so none of them wins by order
+            if !idle.is_empty() {
+                self.knock_out_between_moves(&idle, CrashCause::Timeout);
+            }
+            return idle.iter().map(|&idx| self.players[idx].name.clone()).collect();
+        }
+        let mut acted = Vec::new();
+        for &idx in &idle {
+            if self.status != GameStatus::Running || !self.players[idx].alive {
+                continue;
+            }
+            self.move_player(idx, SteerAction::Straight);
+            self.players[idx].idle_moves += 1;
+            acted.push(self.players[idx].name.clone());
+        }
+        acted
+    }
+
+    /// Get the visible area around a player for the `look` tool
+    pub fn look(&self, player_idx: usize, view_radius: usize, options: LookOptions) -> String {
+        let view_radius = view_radius.min(MAX_VIEW_RADIUS);
+        let player = &self.players[player_idx];
+        let mut lines = Vec::new();
+
+        lines.push(format!(
+            "Your light-cycle '{}' is at ({}, {}) heading {}.",
+            player.name, player.x, player.y, player.direction.name()
+        ));
+
+        if player.respawn_at_tick.is_some() {
+            lines.push(self.out_message(player_idx));
+            return lines.join("\n");
+        }
+        if !player.alive {
+            match analysis::crash_description(self, player_idx) {
+                Some(how) => lines.push(format!("YOU HAVE CRASHED! {}. Game over for you.", how)),
+                None => lines.push("YOU HAVE CRASHED! Game over for you.".to_string()),
+            }
+            return lines.join("\n");
+        }
+
+        lines.push(format!(
+            "Distance traveled: {}. Tick: {}.",
+            player.distance_traveled, self.tick
+        ));
+
+        let alive_count = self.players.iter().filter(|p| p.alive).count();
+        let total_count = self.players.len();
+        lines.push(format!(
+            "Players alive: {}/{}",
+            alive_count, total_count
+        ));
+        if let Some(lives) = self.lives_line(player_idx) {
+            lines.push(lives);
+        }
+        if let Some(team) = self.team_of(player_idx) {
+            lines.push(self.team_line(player_idx, team));
+        }
+        lines.push(self.trail_line());
+        if let Some(territory) = self.territory_line(Some(player_idx)) {
+            lines.push(territory);
+        }
+
+        // Render grid view
+        let cells = self.view_cells(player_idx, view_radius);
+        let cells = match options.orientation {
+            Orientation::World => {
+                lines.push(format!(
+                    "Grid ({}x{} view centered on you):",
+                    view_radius * 2 + 1,
+                    view_radius * 2 + 1
+                ));
+                cells
+            }
+            Orientation::Ego => {
+                lines.push(format!(
+                    "Grid ({}x{} view centered on you, turned so you face up: up = straight, left column = your left, right column = your right):",
+                    view_radius * 2 + 1,
+                    view_radius * 2 + 1
+                ));
+                render::rotate_to_heading(&cells, player.direction)
+            }
+        };
+        lines.extend(cells.iter().map(|row| render::spaced_row(row)));
+        if options.hints {
+            lines.push("Moves:".to_string());
+            lines.extend(analysis::move_hints(self, player_idx).into_iter().map(|h| format!("  {}", h)));
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "Legend: @ = you, | = your trail, A-I = other players' heads, 1-9 = their trails, a-i = trails of crashed players, {}{}# = wall, X = obstruction, . = empty",
+            if self.teams.is_some() { "T = a teammate's head, + = teammates' trails, " } else { "" },
+            render::powerup_legend(self)
+        ));
+        if let Some(checkpoint) = self.checkpoint_line(player_idx) {
+            lines.push(checkpoint);
+        }
+        if let Some(effects) = self.effects_line(player_idx) {
+            lines.push(effects);
+        }
+        if let Some(protection) = self.protection_line(player_idx) {
+            lines.push(protection);
+        }
+        if let Some(shrink) = self.shrink_line() {
+            lines.push(shrink);
+        }
+        if let Some(limit) = self.time_limit_line() {
+            lines.push(limit);
+        }
+        let recent = analysis::recent_events(self, analysis::FEED_LEN);
+        if !recent.is_empty() {
+            lines.push("Recent events:".to_string());
+            lines.extend(recent.into_iter().map(|e| format!("  {}", e)));
+        }
+        if self.rules.wrap_edges {
+            lines.push("Edges wrap: leaving the grid re-enters from the opposite side.".to_string());
+        }
+        if self.realtime {
+            let queued = player
+                .pending_action
+                .map(|a| format!(" Queued: {}.", a.name()))
+                .unwrap_or_default();
+            lines.push(format!(
+                "Real-time game: every cycle advances one step per tick; steer sets your next turn.{}",
+                queued
+            ));
+        }
+        for rule in &self.rules_diff {
+            lines.push(format!("Custom rule: {}", rule));
+        }
+        if let Some(note) = &self.course_note {
+            lines.push(note.clone());
+        }
+        if let Some(notice) = self.abort_notice(player_idx) {
+            lines.push(notice);
+        }
+        let chat = self.chat_for(player_idx);
+        if !chat.is_empty() {
+            lines.push("Chat:".to_string());
+            lines.extend(chat.into_iter().map(|c| format!("  {}", c)));
+        }
+
+        // Show other players info
+        for (i, p) in self.players.iter().enumerate() {
+            if i == player_idx {
+                continue;
+            }
+            let (dx, dy) = self.offset(player, p);
+            let marks = if self.teammates(player_idx, i) {
+                format!("teammate, head {}, trail {}", render::TEAMMATE_HEAD, render::TEAMMATE_TRAIL)
+            } else if p.alive {
+                format!("head {}, trail {}", render::head_symbol(i), render::trail_symbol(i, true))
+            } else {
+                format!("trail {}", render::trail_symbol(i, false))
+            };
+            let status = self.status_of(i);
+            let seen = if dx.abs() <= view_radius as i32 && dy.abs() <= view_radius as i32 {
+                "in view"
+            } else {
+                "out of view"
+            };
+            lines.push(format!(
+                "Player '{}' ({}): {} at ({}, {}) heading {}, offset ({:+}, {:+}) from you (manhattan distance: {}), traveled {}, {}",
+                p.name,
+                marks,
+                status,
+                p.x,
+                p.y,
+                p.direction.name(),
+                dx,
+                dy,
+                dx.abs() + dy.abs(),
+                p.distance_traveled,
+                seen
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    /// The look view in few tokens: a summary line, the grid rows run-length
+    /// encoded with `render::rle_row`, a line for the move hints when asked for and
+    /// one for the other players; no legend
+    pub fn look_compact(&self, player_idx: usize, view_radius: usize, options: LookOptions) -> String {
+        let view_radius = view_radius.min(MAX_VIEW_RADIUS);
+        let player = &self.players[player_idx];
+        if !player.alive {
+            return self.look(player_idx, view_radius, options);
+        }
+        let side = view_radius * 2 + 1;
+        let cells = self.view_cells(player_idx, view_radius);
+        let (cells, orientation) = match options.orientation {
+            Orientation::World => (cells, "north up"),
+            Orientation::Ego => (render::rotate_to_heading(&cells, player.direction), "heading up"),
+        };
+        let mut lines = vec![format!(
+            "{} at ({}, {}) heading {}, tick {}, traveled {}, {}/{} alive; {}x{} view {}:",
+            player.name,
+            player.x,
+            player.y,
+            player.direction.name(),
+            self.tick,
+            player.distance_traveled,
+            self.players.iter().filter(|p| p.alive).count(),
+            self.players.len(),
+            side,
+            side,
+            orientation
+        )];
+        lines.extend(cells.iter().map(|row| render::rle_row(row)));
+        if options.hints {
+            lines.push(format!("Moves: {}", analysis::move_hints(self, player_idx).join("; ")));
+        }
+        let others: Vec<String> = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != player_idx)
+            .map(|(i, p)| {
+                let (dx, dy) = self.offset(player, p);
+                let teammate = self.teammates(player_idx, i);
+                if let Some(at) = p.respawn_at_tick {
+                    return format!("{} respawning at tick {}", p.name, at);
+                }
+                if !p.alive {
+                    let trail = if teammate { render::TEAMMATE_TRAIL } else { render::trail_symbol(i, false) };
+                    return format!("{} {} crashed", trail, p.name);
+                }
+                format!(
+                    "{} {} at ({}, {}) heading {}, offset ({:+}, {:+})",
+                    if teammate { render::TEAMMATE_HEAD } else { render::head_symbol(i) },
+                    p.name,
+                    p.x,
+                    p.y,
+                    p.direction.name(),
+                    dx,
+                    dy
+                )
+            })
+            .collect();
+        if !others.is_empty() {
+            lines.push(format!("Others: {}", others.join("; ")));
+        }
+        if let Some(lives) = self.lives_line(player_idx) {
+            lines.push(lives);
+        }
+        if let Some(team) = self.team_of(player_idx) {
+            lines.push(self.team_line(player_idx, team));
+        }
+        lines.push(self.trail_line());
+        if let Some(territory) = self.territory_line(Some(player_idx)) {
+            lines.push(territory);
+        }
+        if let Some(checkpoint) = self.checkpoint_line(player_idx) {
+            lines.push(checkpoint);
+        }
+        if let Some(effects) = self.effects_line(player_idx) {
+            lines.push(effects);
+        }
+        if let Some(protection) = self.protection_line(player_idx) {
+            lines.push(protection);
+        }
+        if let Some(shrink) = self.shrink_line() {
+            lines.push(shrink);
+        }
+        if let Some(limit) = self.time_limit_line() {
+            lines.push(limit);
+        }
+        let chat = self.chat_for(player_idx);
+        if !chat.is_empty() {
+            lines.push(format!("Chat: {}", chat.join("; ")));
+        }
+        lines.join("\n")
+    }
+
+    /// Where the checkpoint is and how far, in checkpoint games; shown
+    /// whether or not it is inside the view window
+    fn checkpoint_line(&self, player_idx: usize) -> Option<String> {
+        let ((cx, cy), distance) = self.checkpoint_distance(player_idx)?;
+        Some(format!(
+            "Checkpoint (F) at ({}, {}), manhattan distance {}. First to reach it wins!",
+            cx, cy, distance
+        ))
+    }
+
+    /// How much longer `player_idx` is spawn protected, while they are
+    fn protection_line(&self, player_idx: usize) -> Option<String> {
+        let ticks = self.protected_for(player_idx)?;
+        Some(format!(
+            "Spawn protection: {} more tick{}. Other players' trails can't crash you yet; walls, your own trail and cycle heads still can.",
+            ticks,
+            if ticks == 1 { "" } else { "s" }
+        ))
+    }
+
+    /// The power-ups `player_idx` is holding, if any
+    fn effects_line(&self, player_idx: usize) -> Option<String> {
+        let effects = &self.players[player_idx].active_effects;
+        if effects.is_empty() {
+            return None;
+        }
+        let described: Vec<String> = effects.iter().map(|e| e.describe()).collect();
+        Some(format!("Active effects: {}.", described.join(", ")))
+    }
+
+    /// The checkpoint and its manhattan distance from `player_idx`, in
+    /// checkpoint games
+    fn checkpoint_distance(&self, player_idx: usize) -> Option<((usize, usize), u32)> {
+        if self.rules.win_condition != WinCondition::Checkpoint {
+            return None;
+        }
+        let (cx, cy) = self.checkpoint?;
+        let player = &self.players[player_idx];
+        Some(((cx, cy), ((cx as i32 - player.x).abs() + (cy as i32 - player.y).abs()) as u32))
+    }
+
+    /// Who `player_idx` rides with in a team game, and whether their trails
+    /// are safe to cross
+    fn team_line(&self, player_idx: usize, team: usize) -> String {
+        let mates: Vec<&str> = (0..self.players.len())
+            .filter(|&i| self.teammates(player_idx, i))
+            .map(|i| self.players[i].name.as_str())
+            .collect();
+        format!(
+            "Team game: you ride for Team {} with {}. {}",
+            team,
+            mates.join(", "),
+            if self.rules.friendly_fire {
+                "Friendly fire is on: teammates' trails crash you too."
+            } else {
+                "You ride through teammates' trails unharmed; the last team with a cycle alive wins."
+            }
+        )
+    }
+
+    /// Where `other` is as seen from `from`, in cells east and south; the
+    /// shorter way round when edges wrap
+    fn offset(&self, from: &Player, other: &Player) -> (i32, i32) {
+        let (mut dx, mut dy) = (other.x - from.x, other.y - from.y);
+        if self.rules.wrap_edges {
+            let (w, h) = (self.width as i32, self.height as i32);
+            dx = (dx + w / 2).rem_euclid(w) - w / 2;
+            dy = (dy + h / 2).rem_euclid(h) - h / 2;
+        }
+        (dx, dy)
+    }
+
+    /// `render::glyph` codes in a square around a player, rows top to bottom
+    fn view_cells(&self, player_idx: usize, view_radius: usize) -> Vec<Vec<char>> {
+        let player = &self.players[player_idx];
+        let r = view_radius as i32;
+        (-r..=r)
+            .map(|dy| {
+                (-r..=r)
+                    .map(|dx| {
+                        let (gx, gy) = (player.x + dx, player.y + dy);
+                        let (gx, gy) = if self.rules.wrap_edges {
+                            (gx.rem_euclid(self.width as i32), gy.rem_euclid(self.height as i32))
+                        } else {
+                            (gx, gy)
+                        };
+                        render::glyph(self, gx, gy, Some(player_idx))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Structured version of `look` for clients that parse the view
+    pub fn look_view(&self, player_idx: usize, view_radius: usize) -> LookView {
+        let view_radius = view_radius.min(MAX_VIEW_RADIUS);
+        let player = &self.players[player_idx];
+        let claimed = self.territory_cells();
+        let others = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != player_idx)
+            .map(|(i, p)| {
+                let (dx, dy) = self.offset(player, p);
+                LookPlayer {
+                    index: i,
+                    name: p.name.clone(),
+                    x: p.x,
+                    y: p.y,
+                    heading: p.direction.name().to_string(),
+                    alive: p.alive,
+                    distance: (dx.abs() + dy.abs()) as u32,
+                    distance_traveled: p.distance_traveled,
+                    in_view: dx.abs() <= view_radius as i32 && dy.abs() <= view_radius as i32,
+                    team: self.team_of(i),
+                    claimed: claimed.as_ref().map(|c| c[i]),
+                    lives: self.lives_left(i),
+                }
+            })
+            .collect();
+        LookView {
+            name: player.name.clone(),
+            x: player.x,
+            y: player.y,
+            heading: player.direction.name().to_string(),
+            alive: player.alive,
+            tick: self.tick,
+            distance_traveled: player.distance_traveled,
+            team: self.team_of(player_idx),
+            claimed: claimed.as_ref().map(|c| c[player_idx]),
+            territory_ends_at: claimed.as_ref().map(|_| self.rules.territory_end()),
+            view_radius,
+            cells: self
+                .view_cells(player_idx, view_radius)
+                .into_iter()
+                .map(|row| row.into_iter().map(String::from).collect())
+                .collect(),
+            players: others,
+            checkpoint: self.checkpoint,
+            checkpoint_distance: self.checkpoint_distance(player_idx).map(|(_, d)| d),
+            active_effects: player.active_effects.clone(),
+            spawn_protection: self.protected_for(player_idx),
+            lives: self.lives_left(player_idx),
+            ticks_left: self
+                .rules
+                .max_ticks
+                .filter(|_| self.rules.win_condition != WinCondition::Territory)
+                .map(|max| max.saturating_sub(self.tick)),
+            wrap_edges: self.rules.wrap_edges,
+            events: analysis::recent_events(self, analysis::FEED_LEN),
+            chat: self.chat_for(player_idx),
+            crash: analysis::crash_description(self, player_idx),
+        }
+    }
+
+    /// The whole grid shrunk by a whole factor so neither side exceeds
+    /// `max_size` characters. Each block of cells becomes the symbol that
+    /// `symbol` ranks highest among them, the first one on a tie. Returns
+    /// the factor and the rows, top to bottom.
+    pub fn downsample(&self, max_size: usize, symbol: impl Fn(usize, usize) -> (char, u8)) -> (usize, Vec<String>) {
+        downsample(self.width, self.height, max_size, symbol)
+    }
+
+    /// Empty cells a cycle entering `(x, y)` could still reach, up to `cap`
+    pub fn open_area(&self, x: i32, y: i32, cap: usize) -> usize {
+        let blocked: Vec<bool> = self.grid.iter().flatten().map(|c| !c.is_open()).collect();
+        flood_fill(self.width, self.height, self.rules.wrap_edges, &blocked, (x, y), cap)
+    }
+
+    /// Summed score of each team, team 1 first; empty outside team games
+    pub fn team_scores(&self) -> Vec<u32> {
+        if self.teams.is_none() {
+            return Vec::new();
+        }
+        (1..=2)
+            .map(|team| {
+                (0..self.players.len())
+                    .filter(|&i| self.team_of(i) == Some(team))
+                    .map(|i| self.players[i].score)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Serialize game state for the web UI
+    pub fn to_web_state(&self) -> WebGameState {
+        let mut grid_data: Vec<Vec<u8>> = self
+            .grid
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, cell)| match cell {
+                        Cell::Empty if self.checkpoint == Some((x, y)) => CHECKPOINT_CODE,
+                        Cell::Empty => 0,
+                        Cell::Wall => 1,
+                        Cell::Obstruction => 2,
+                        Cell::Trail(idx) if self.players.get(*idx).is_some_and(|p| !p.alive) => {
+                            DEAD_TRAIL_CODE + *idx as u8
+                        }
+                        Cell::Trail(idx) => (3 + *idx) as u8,
+                        Cell::PowerUp(kind) => POWERUP_CODE + kind.index() as u8,
+                    })
+                    .collect()
+            })
+            .collect();
+        for &(x, y) in &self.closed {
+            grid_data[y][x] = CLOSED_CODE;
+        }
+
+        let claimed = self.territory_cells();
+        let players: Vec<WebPlayer> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| WebPlayer {
+                index: i,
+                name: p.name.clone(),
+                x: p.x,
+                y: p.y,
+                alive: p.alive,
+                direction: p.direction,
+                distance: p.distance_traveled,
+                score: p.score,
+                bot: p.bot.is_some(),
+                team: self.team_of(i),
+                claimed: claimed.as_ref().map(|c| c[i]),
+                lives: self.lives_left(i),
+                respawn_at_tick: p.respawn_at_tick,
+                kills: p.kills,
+            })
+            .collect();
+
+        WebGameState {
+            id: self.id.to_string(),
+            width: self.width,
+            height: self.height,
+            grid: grid_data,
+            players,
+            status: self.status,
+            tick: self.tick,
+            course_name: self.course_name.clone(),
+            course_level: self.course_level,
+            course_seed: self.course_seed,
+            winner: self.winner,
+            winning_team: self.winning_team,
+            team_scores: self.team_scores(),
+            arena: self.rules.shrink_every.map(|_| {
+                let (min_x, min_y, max_x, max_y) = self.arena_bounds();
+                [min_x, min_y, max_x, max_y]
+            }),
+            shrink_in: self.shrink_in(),
+            tie_break: self.tie_break,
+            finish_reason: self.finish_reason().map(str::to_string),
+            created_at: self.created_at.to_rfc3339(),
+            finished_at: self.finished_at.map(|t| t.to_rfc3339()),
+            rules: self.rules.clone(),
+            rules_diff: self.rules_diff.clone(),
+            unranked: self.unranked,
+            lobby: self.lobby.clone(),
+            featured: self.featured,
+            exhibition: self.exhibition,
+            recap: None,
+            aborted: self.aborted,
+            tags: self.tags.clone(),
+            events: self
+                .events
+                .iter()
+                .map(|e| WebGameEvent {
+                    tick: e.tick,
+                    kind: e.kind,
+                    text: analysis::describe_event(self, e),
+                })
+                .collect(),
+            chat: self.chat.iter().map(|m| self.web_chat(m)).collect(),
+            replay: None,
+        }
+    }
+
+    pub fn web_chat(&self, message: &ChatMessage) -> WebChatMessage {
+        WebChatMessage {
+            player: self.players[message.player].name.clone(),
+            tick: message.tick,
+            text: web::escape_html(&message.text),
+        }
+    }
+}
+
+/// Cells reachable from `start` through cells that are not `blocked`, not
+/// counting `start` itself; counting stops at `cap`. `blocked` holds one
+/// entry per grid cell, row by row.
+pub fn flood_fill(width: usize, height: usize, wrap: bool, blocked: &[bool], start: (i32, i32), cap: usize) -> usize {
+    let (w, h) = (width as i32, height as i32);
+    let index = |x: i32, y: i32| {
+        let (x, y) = if wrap { (x.rem_euclid(w), y.rem_euclid(h)) } else { (x, y) };
+        (x >= 0 && y >= 0 && x < w && y < h).then(|| (y * w + x) as usize)
+    };
+    let mut seen = vec![false; blocked.len()];
+    if let Some(i) = index(start.0, start.1) {
+        seen[i] = true;
+    }
+    let mut queue = VecDeque::from([start]);
+    let mut count = 0;
+    while let Some((x, y)) = queue.pop_front() {
+        for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+            let Some(i) = index(x + dx, y + dy) else {
+                continue;
+            };
+            if blocked[i] || seen[i] {
+                continue;
+            }
+            seen[i] = true;
+            count += 1;
+            if count >= cap {
+                return count;
+            }
+            queue.push_back((i as i32 % w, i as i32 / w));
+        }
+    }
+    count
+}
+
+/// Output format of the look tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookFormat {
+    Text,
+    Json,
+    /// Run-length encoded rows without the legend, for fewer tokens
+    Compact,
+}
+
+impl LookFormat {
+    pub fn parse(s: &str) -> Option<LookFormat> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Some(LookFormat::Text),
+            "json" => Some(LookFormat::Json),
+            "compact" => Some(LookFormat::Compact),
+            _ => None,
+        }
+    }
+}
+
+/// Which way up the text look draws the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// North up
+    #[default]
+    World,
+    /// Turned so the player's heading points up
+    Ego,
+}
+
+impl Orientation {
+    pub fn parse(s: &str) -> Option<Orientation> {
+        match s.trim().to_lowercase().as_str() {
+            "world" => Some(Orientation::World),
+            "ego" => Some(Orientation::Ego),
+            _ => None,
+        }
+    }
+}
+
+/// How the text look is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LookOptions {
+    /// Follow the grid with what each steer runs into
+    pub hints: bool,
+    pub orientation: Orientation,
+}
+
+impl Default for LookOptions {
+    fn default() -> Self {
+        LookOptions {
+            hints: true,
+            orientation: Orientation::World,
+        }
+    }
+}
+
+/// What a player sees from their cycle, in structured form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookView {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub heading: String,
+    pub alive: bool,
+    pub tick: u32,
+    pub distance_traveled: u32,
+    /// Your team, in team games; teammates show as `T` with `+` trails
+    #[serde(default)]
+    pub team: Option<usize>,
+    /// Trail cells you hold, in territory games
+    #[serde(default)]
+    pub claimed: Option<usize>,
+    /// Tick at which a territory game is scored
+    #[serde(default)]
+    pub territory_ends_at: Option<u32>,
+    pub view_radius: usize,
+    /// `cells[row][col]`, centered on you: row 0 is `view_radius` cells north
+    /// of you and column 0 is `view_radius` cells west
+    pub cells: Vec<Vec<String>>,
+    pub players: Vec<LookPlayer>,
+    pub checkpoint: Option<(usize, usize)>,
+    /// Manhattan distance to the checkpoint, in checkpoint games
+    #[serde(default)]
+    pub checkpoint_distance: Option<u32>,
+    /// Power-ups you are holding
+    #[serde(default)]
+    pub active_effects: Vec<ActiveEffect>,
+    /// Ticks left during which other players' trails can't crash you
+    #[serde(default)]
+    pub spawn_protection: Option<u32>,
+    /// Lives left, counting this one, in games with lives
+    #[serde(default)]
+    pub lives: Option<u32>,
+    /// Ticks until the time limit ends the game
+    #[serde(default)]
+    pub ticks_left: Option<u32>,
+    pub wrap_edges: bool,
+    /// The last few game events, oldest first
+    pub events: Vec<String>,
+    /// What the other players said lately, oldest first
+    #[serde(default)]
+    pub chat: Vec<String>,
+    /// How you crashed, once you have
+    pub crash: Option<String>,
+}
+
+/// Another cycle as seen from `LookView`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookPlayer {
+    pub index: usize,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub heading: String,
+    pub alive: bool,
+    /// Manhattan distance from you
+    pub distance: u32,
+    pub distance_traveled: u32,
+    /// Whether their head is inside your view window
+    pub in_view: bool,
+    #[serde(default)]
+    pub team: Option<usize>,
+    #[serde(default)]
+    pub claimed: Option<usize>,
+    #[serde(default)]
+    pub lives: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebGameState {
+    pub id: String,
+    pub width: usize,
+    pub height: usize,
+    /// 0 empty, 1 wall, 2 obstruction, `3 + i` trail of player i,
+    /// `DEAD_TRAIL_CODE + i` once player i has crashed, `CHECKPOINT_CODE`,
+    /// `POWERUP_CODE` plus the power-up's place in `PowerUpKind::ALL`, or
+    /// `CLOSED_CODE` where the arena closed in
+    pub grid: Vec<Vec<u8>>,
+    pub players: Vec<WebPlayer>,
+    pub status: GameStatus,
+    pub tick: u32,
+    pub course_name: String,
+    pub course_level: u32,
+    /// Regenerates a random course layout with `get_course_with_seed`
+    #[serde(default)]
+    pub course_seed: Option<u64>,
+    pub winner: Option<usize>,
+    /// In team games, the winner's team; everyone on it shares the win
+    #[serde(default)]
+    pub winning_team: Option<usize>,
+    /// In team games, the summed score of each team, team 1 first
+    #[serde(default)]
+    pub team_scores: Vec<u32>,
+    /// In shrinking games, the playable cells as `[min_x, min_y, max_x,
+    /// max_y]`, inclusive
+    #[serde(default)]
+    pub arena: Option<[usize; 4]>,
+    /// Ticks until the arena next closes in
+    #[serde(default)]
+    pub shrink_in: Option<u32>,
+    #[serde(default)]
+    pub tie_break: Option<TieBreakReason>,
+    /// "time limit" when the game ended on the `max_ticks` rule
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    #[serde(default)]
+    pub rules: Ruleset,
+    #[serde(default)]
+    pub rules_diff: Vec<String>,
+    #[serde(default)]
+    pub unranked: bool,
+    #[serde(default)]
+    pub lobby: Option<String>,
+    #[serde(default)]
+    pub featured: bool,
+    /// Bot-vs-bot showcase; never counted or archived
+    #[serde(default)]
+    pub exhibition: bool,
+    /// Game-over recap, filled in when the game is archived
+    #[serde(default)]
+    pub recap: Option<String>,
+    /// Voided by agreement of the players, or by a forfeit before the first
+    /// move (`finish_reason` says which)
+    #[serde(default)]
+    pub aborted: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The game's event log, for kill feeds
+    #[serde(default)]
+    pub events: Vec<WebGameEvent>,
+    /// The game's recent chat, oldest first
+    #[serde(default)]
+    pub chat: Vec<WebChatMessage>,
+    /// Every move, filled in when the game is archived; missing for games
+    /// archived before replays were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<Replay>,
+}
+
+/// A chat message as sent to the web UI, its text HTML-escaped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebChatMessage {
+    pub player: String,
+    pub tick: u32,
+    pub text: String,
+}
+
+/// A game event with its sentence, as sent to the web UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebGameEvent {
+    pub tick: u32,
+    pub kind: GameEventKind,
+    /// None for events another entry already tells
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebPlayer {
+    pub index: usize,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub alive: bool,
+    pub direction: Direction,
+    pub distance: u32,
+    pub score: u32,
+    #[serde(default)]
+    pub bot: bool,
+    /// Team 1 or 2, in team games
+    #[serde(default)]
+    pub team: Option<usize>,
+    /// Trail cells held, in territory games
+    #[serde(default)]
+    pub claimed: Option<usize>,
+    /// Lives left, in games with lives
+    #[serde(default)]
+    pub lives: Option<u32>,
+    /// Tick a crashed player with lives left rides again
+    #[serde(default)]
+    pub respawn_at_tick: Option<u32>,
+    /// Opponents who crashed into this player's trail or head
+    #[serde(default)]
+    pub kills: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    /// A 10x8 arena; tests seat the cycles where they need them
+    const ARENA: &str = "
+        ##########
+        #........#
+        #........#
+        #........#
+        #........#
+        #........#
+        #........#
+        ##########";
+
+    #[test]
+    fn spawns_blocked_by_the_course_move_to_empty_cells() {
+        // Walls through every usual spawn and obstructions on the step ahead
+        let mut course = testutil::course(&"....................\n".repeat(20));
+        course.max_players = 8;
+        for (x, y, dir) in spawn_positions(20, 20) {
+            let (dx, dy) = dir.delta();
+            course.walls.push((x as usize, y as usize));
+            course.obstructions.push(((x + dx) as usize, (y + dy) as usize));
+        }
+        let mut game = Game::new(&course, Ruleset::default()).unwrap();
+        for seat in 0..8 {
+            game.add_player(format!("P{}", seat + 1)).unwrap();
+        }
+
+        let mut taken = std::collections::HashSet::new();
+        for p in &game.players {
+            let (dx, dy) = p.direction.delta();
+            for (x, y) in [(p.x, p.y), (p.x + dx, p.y + dy)] {
+                assert_eq!(game.grid[y as usize][x as usize], Cell::Empty, "{} starts blocked at ({}, {})", p.name, x, y);
+                assert!(taken.insert((x, y)), "{} shares ({}, {})", p.name, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn courses_off_their_grid_are_refused() {
+        let mut course = testutil::course(ARENA);
+        course.walls.push((10, 3));
+        let err = Game::new(&course, Ruleset::default()).err().unwrap();
+        assert_eq!(err, "course 'Test Course': wall (10, 3) is outside the 10x8 grid");
+        let small = testutil::course("#######\n#.....#\n#######");
+        let err = Game::new(&small, Ruleset::default()).err().unwrap();
+        assert_eq!(err, "course 'Test Course': width must be between 8 and 200");
+    }
+
+    #[test]
+    fn ego_view_turns_the_heading_up() {
+        // A wall two cells north of Claude and an obstruction two cells east
+        let mut game = testutil::game(
+            "
+            ############
+            #..........#
+            #....#.....#
+            #..........#
+            #....1.X...#
+            #..........#
+            #..........#
+            #..........#
+            #.........2#
+            ############",
+            &["Claude", "Gemini"],
+        );
+        let ego = LookOptions { hints: false, orientation: Orientation::Ego };
+        for (heading, expected) in [
+            (Direction::Up, [". . # . .", ". . . . .", ". . @ . X", ". . . . .", ". . . . ."]),
+            (Direction::Right, [". . X . .", ". . . . .", "# . @ . .", ". . . . .", ". . . . ."]),
+            (Direction::Down, [". . . . .", ". . . . .", "X . @ . .", ". . . . .", ". . # . ."]),
+            (Direction::Left, [". . . . .", ". . . . .", ". . @ . #", ". . . . .", ". . X . ."]),
+        ] {
+            game.players[0].direction = heading;
+            let look = game.look(0, 2, ego);
+            let grid: Vec<&str> = look.lines().skip_while(|l| !l.starts_with("Grid (")).skip(1).take(5).collect();
+            assert_eq!(grid, expected, "heading {}", heading.name());
+        }
+    }
+
+    #[test]
+    fn look_places_other_players_and_marks_their_heads() {
+        let mut game = testutil::game(
+            "
+            ############
+            #1.........#
+            #..........#
+            #..........#
+            #.........2#
+            #..........#
+            #..........#
+            ############",
+            &["Claude", "Gemini"],
+        );
+        game.move_player(0, SteerAction::Straight);
+        game.move_player(1, SteerAction::Straight);
+
+        let look = game.look(0, 8, LookOptions::default());
+        assert!(
+            look.lines().any(|l| l
+                == "Player 'Gemini' (head B, trail 2): ALIVE at (9, 4) heading WEST, offset (+7, +3) from you (manhattan distance: 10), traveled 1, in view"),
+            "{}",
+            look
+        );
+        // Row y=4 of the view, which ends at x=10: Gemini's head, then the trail behind it
+        let grid: Vec<&str> = look.lines().skip_while(|l| !l.starts_with("Grid (")).skip(1).collect();
+        assert!(grid[11].ends_with(". B 2"), "{}", grid[11]);
+        assert!(look.contains("A-I = other players' heads, 1-9 = their trails"));
+
+        let look = game.look(0, 2, LookOptions::default());
+        assert!(look.contains("offset (+7, +3) from you (manhattan distance: 10), traveled 1, out of view"), "{}", look);
+    }
+
+    #[test]
+    fn downsample_keeps_the_highest_ranked_cell_of_each_block() {
+        let grid = [".......", ".1...X#", "..X....", "#......", ".2..o.1"];
+        let rank = |c: char| match c {
+            '#' => 3,
+            '1' | '2' => 2,
+            'X' | 'o' => 1,
+            _ => 0,
+        };
+        let symbol = |x: usize, y: usize| {
+            let c = grid[y].as_bytes()[x] as char;
+            (c, rank(c))
+        };
+
+        // 7x5 into at most 3 a side: 3x3 blocks, the last column and row cut short
+        let (scale, rows) = downsample(7, 5, 3, symbol);
+        assert_eq!(scale, 3);
+        assert_eq!(rows, ["1X#", "#o1"]);
+        let (scale, rows) = downsample(7, 5, 4, symbol);
+        assert_eq!(scale, 2);
+        assert_eq!(rows, ["1.X#", "#X..", "2.o1"]);
+        // Ties go to the first cell of the block, row by row
+        let (_, rows) = downsample(2, 2, 1, |x, y| (grid[y + 3].as_bytes()[x] as char, rank('1')));
+        assert_eq!(rows, ["#"]);
+        let (scale, rows) = downsample(7, 5, 7, symbol);
+        assert_eq!(scale, 1);
+        assert_eq!(rows, grid);
+    }
+
+    /// The JSON look view is pinned in `src/snapshots/look_view.json`, so a
+    /// change to its schema shows up here. Run with `UPDATE_SNAPSHOTS=1` to
+    /// rewrite it after a deliberate change.
+    #[test]
+    fn look_view_matches_its_snapshot() {
+        let mut game = testutil::game(
+            "
+            ##########
+            #1.......#
+            #....X...#
+            #........#
+            #.......2#
+            #........#
+            #........#
+            ##########",
+            &["Claude", "Gemini"],
+        );
+        game.move_player(0, SteerAction::Straight);
+        game.move_player(1, SteerAction::Right);
+        let view = format!("{}\n", serde_json::to_string_pretty(&game.look_view(0, 3)).unwrap());
+
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/snapshots/look_view.json");
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, &view).unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap_or_default(),
+            view,
+            "the look view differs from {}; rerun with UPDATE_SNAPSHOTS=1 if that is intended",
+            path.display()
+        );
+    }
+
+    /// Every crash logged so far, in order
+    fn crashes(game: &Game) -> Vec<(usize, CrashCause)> {
+        game.events
+            .iter()
+            .filter_map(|e| match e.kind {
+                GameEventKind::Crashed { player, cause } => Some((player, cause)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// A running game on `ARENA` with one cycle per seat, placed and facing
+    /// as given
+    fn seated(seats: &[(i32, i32, Direction)]) -> Game {
+        seated_on(ARENA, Ruleset::default(), seats)
+    }
+
+    /// Like `seated`, on `map` under `rules` (spawn protection off)
+    fn seated_on(map: &str, rules: Ruleset, seats: &[(i32, i32, Direction)]) -> Game {
+        let names = ["Claude", "Gemini", "Grok", "Llama"];
+        let rules = Ruleset {
+            spawn_grace: Some(0),
+            ..rules
+        };
+        let mut game = testutil::game_with(map, &names[..seats.len()], rules);
+        for (idx, &(x, y, direction)) in seats.iter().enumerate() {
+            let player = &game.players[idx];
+            game.grid[player.y as usize][player.x as usize] = Cell::Empty;
+            let player = &mut game.players[idx];
+            (player.x, player.y, player.direction) = (x, y, direction);
+        }
+        for (idx, &(x, y, _)) in seats.iter().enumerate() {
+            game.grid[y as usize][x as usize] = Cell::Trail(idx);
+        }
+        game
+    }
+
+    #[test]
+    fn adjacent_cycles_swapping_cells_both_crash() {
+        let mut game = seated(&[(3, 2, Direction::Right), (4, 2, Direction::Left)]);
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+
+        assert_eq!(outcome, "CRASHED head-on into Gemini! Both cycles are destroyed.");
+        assert_eq!(
+            crashes(&game),
+            [(0, CrashCause::HeadOn { other: 1 }), (1, CrashCause::HeadOn { other: 0 })]
+        );
+        assert_eq!(game.status(), GameStatus::Finished);
+        // Each takes the other out, so both are credited
+        assert_eq!(game.players[0].kills, 1);
+        assert_eq!(game.players[1].kills, 1);
+    }
+
+    /// Claude and Gemini meeting head-on, having traveled `distances`
+    /// beforehand
+    fn head_on(tie_break: TieBreak, distances: [u32; 2]) -> Game {
+        let rules = Ruleset {
+            tie_break,
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(ARENA, rules, &[(3, 2, Direction::Right), (4, 2, Direction::Left)]);
+        for (player, distance) in game.players.iter_mut().zip(distances) {
+            player.distance_traveled = distance;
+        }
+        game.move_player(0, SteerAction::Straight);
+        assert_eq!(game.status(), GameStatus::Finished);
+        game
+    }
+
+    #[test]
+    fn last_two_crashing_together_at_equal_distance_is_a_draw() {
+        let game = head_on(TieBreak::Draw, [4, 4]);
+        assert_eq!(game.winner(), None);
+        assert_eq!(game.tie_break, None);
+
+        // Unless the rules call for a coin flip
+        let game = head_on(TieBreak::Distance, [4, 4]);
+        assert!(game.winner().is_some());
+        assert_eq!(game.tie_break, Some(TieBreakReason::CoinFlip));
+    }
+
+    #[test]
+    fn last_two_crashing_together_goes_to_the_longer_ride() {
+        let game = head_on(TieBreak::Distance, [12, 4]);
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(game.tie_break, Some(TieBreakReason::Distance));
+    }
+
+    #[test]
+    fn chain_crashes_only_the_pair_meeting_head_on() {
+        let mut game = seated(&[
+            (3, 2, Direction::Right),
+            (4, 2, Direction::Left),
+            (5, 2, Direction::Left),
+        ]);
+
+        // Claude and Gemini meet head-on; Grok, right behind Gemini, is not
+        // part of it and is the last cycle standing
+        game.advance();
+
+        assert_eq!(
+            crashes(&game),
+            [(0, CrashCause::HeadOn { other: 1 }), (1, CrashCause::HeadOn { other: 0 })]
+        );
+        assert!(game.players[2].alive);
+        assert_eq!(game.winner(), Some(2));
+    }
+
+    #[test]
+    fn chain_following_from_behind_is_not_head_on() {
+        let mut game = seated(&[
+            (3, 2, Direction::Right),
+            (4, 2, Direction::Right),
+            (5, 2, Direction::Right),
+        ]);
+
+        // Seats move in order, so each follower reaches the cell ahead before
+        // its owner has left it. Nobody is heading at anyone: the followers
+        // crash into the trail ahead and the leader rides on.
+        game.advance();
+
+        assert_eq!(
+            crashes(&game),
+            [
+                (0, CrashCause::Trail { owner: 1, cut_off: true }),
+                (1, CrashCause::Trail { owner: 2, cut_off: true }),
+            ]
+        );
+        assert_eq!(game.winner(), Some(2));
+        assert_eq!(game.players[2].kills, 1);
+    }
+
+    #[test]
+    fn side_by_side_cycles_pass_each_other() {
+        // One row apart and heading at each other: they ride past on
+        // parallel lines
+        let mut game = seated(&[(2, 2, Direction::Right), (5, 3, Direction::Left)]);
+        for _ in 0..3 {
+            game.move_player(0, SteerAction::Straight);
+            game.move_player(1, SteerAction::Straight);
+        }
+
+        assert!(crashes(&game).is_empty());
+        assert_eq!(game.status(), GameStatus::Running);
+        assert_eq!((game.players[0].x, game.players[0].y), (5, 2));
+        assert_eq!((game.players[1].x, game.players[1].y), (2, 3));
+    }
+
+    #[test]
+    fn huge_view_radius_is_clamped() {
+        let game = seated(&[(3, 2, Direction::Right), (6, 5, Direction::Left)]);
+        let options = LookOptions::default();
+
+        assert_eq!(game.look(0, usize::MAX, options), game.look(0, MAX_VIEW_RADIUS, options));
+        assert_eq!(
+            game.look_compact(0, usize::MAX, options),
+            game.look_compact(0, MAX_VIEW_RADIUS, options)
+        );
+        assert_eq!(game.look_view(0, usize::MAX).view_radius, MAX_VIEW_RADIUS);
+    }
+
+    #[test]
+    fn undersized_courses_are_refused_not_indexed() {
+        for (width, height) in [(0, 10), (1, 10), (10, 0), (10, 1), (MIN_GRID_SIZE - 1, MIN_GRID_SIZE)] {
+            let mut course = testutil::course(ARENA);
+            course.width = width;
+            course.height = height;
+            course.spawns.clear();
+            course.walls.clear();
+            for wrap_edges in [false, true] {
+                let rules = Ruleset {
+                    wrap_edges,
+                    ..Ruleset::default()
+                };
+                let err = Game::new(&course, rules).unwrap_err();
+                assert!(err.contains("must be between"), "{}x{}: {}", width, height, err);
+            }
+        }
+    }
+
+    #[test]
+    fn first_claim_rotates_every_round() {
+        let mut game = seated(&[
+            (2, 2, Direction::Right),
+            (2, 4, Direction::Right),
+            (2, 6, Direction::Right),
+        ]);
+        for _ in 0..4 {
+            game.advance();
+        }
+
+        let order: Vec<usize> = game
+            .replay
+            .steps
+            .iter()
+            .map(|step| match step {
+                ReplayStep::Move { player, .. } => *player,
+                other => panic!("unexpected step {:?}", other),
+            })
+            .collect();
+        assert_eq!(order, [0, 1, 2, 1, 2, 0, 2, 0, 1, 0, 1, 2]);
+        assert_eq!(game.tick(), 12);
+    }
+
+    #[test]
+    fn compass_headings_turn_the_right_way() {
+        use Direction::{Down, Left, Right, Up};
+        use SteerAction as Turn;
+        let table = [
+            (Up, Up, Some(Turn::Straight)),
+            (Up, Left, Some(Turn::Left)),
+            (Up, Right, Some(Turn::Right)),
+            (Up, Down, None),
+            (Down, Down, Some(Turn::Straight)),
+            (Down, Right, Some(Turn::Left)),
+            (Down, Left, Some(Turn::Right)),
+            (Down, Up, None),
+            (Left, Left, Some(Turn::Straight)),
+            (Left, Down, Some(Turn::Left)),
+            (Left, Up, Some(Turn::Right)),
+            (Left, Right, None),
+            (Right, Right, Some(Turn::Straight)),
+            (Right, Up, Some(Turn::Left)),
+            (Right, Down, Some(Turn::Right)),
+            (Right, Left, None),
+        ];
+        for (heading, target, expected) in table {
+            assert_eq!(heading.turn_toward(target), expected, "heading {:?} toward {:?}", heading, target);
+            if let Some(turn) = expected {
+                assert_eq!(Steer::Toward(target).resolve(heading), Ok(turn));
+            }
+        }
+
+        assert_eq!(
+            Steer::Toward(Down).resolve(Up).unwrap_err(),
+            "You are heading NORTH and cannot reverse to SOUTH. Legal moves: north (straight), west (left), east (right)."
+        );
+    }
+
+    /// Lay `cells` down as `idx`'s trail, oldest first
+    fn lay_trail(game: &mut Game, idx: usize, cells: &[(i32, i32)]) {
+        for &(x, y) in cells {
+            game.players[idx].trail.push_back((x, y));
+            game.grid[y as usize][x as usize] = Cell::Trail(idx);
+        }
+    }
+
+    fn picked_up(game: &Game) -> Vec<(usize, PowerUpKind)> {
+        game.events
+            .iter()
+            .filter_map(|e| match e.kind {
+                GameEventKind::PowerUp { player, kind } => Some((player, kind)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn speed_boost_moves_two_cells_on_the_next_steer_only() {
+        let mut game = seated(&[(2, 2, Direction::Right), (2, 5, Direction::Right)]);
+        game.grid[2][3] = Cell::PowerUp(PowerUpKind::SpeedBoost);
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+        assert!(outcome.ends_with("Picked up a speed boost: your next steer moves 2 cells."), "{}", outcome);
+        assert_eq!(picked_up(&game), [(0, PowerUpKind::SpeedBoost)]);
+        assert_eq!(game.players[0].active_effects, [ActiveEffect::SpeedBoost]);
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+        assert!(outcome.contains("Speed boost: Moved EAST to (5, 2)"), "{}", outcome);
+        assert_eq!((game.players[0].x, game.players[0].y), (5, 2));
+        assert_eq!(game.grid[2][4], Cell::Trail(0));
+        assert!(game.players[0].active_effects.is_empty());
+
+        game.move_player(0, SteerAction::Straight);
+        assert_eq!((game.players[0].x, game.players[0].y), (6, 2));
+    }
+
+    #[test]
+    fn trail_eraser_clears_the_oldest_cells() {
+        let mut game = seated(&[(4, 2, Direction::Left), (2, 5, Direction::Right)]);
+        let old: Vec<(i32, i32)> = (1..=8).map(|x| (x, 1)).chain((5..=8).rev().map(|x| (x, 2))).collect();
+        lay_trail(&mut game, 0, &old);
+        game.grid[2][3] = Cell::PowerUp(PowerUpKind::TrailEraser);
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+
+        assert!(outcome.ends_with("the oldest 10 cells of your trail are gone."), "{}", outcome);
+        assert_eq!(picked_up(&game), [(0, PowerUpKind::TrailEraser)]);
+        assert_eq!(Vec::from(game.players[0].trail.clone()), [(6, 2), (5, 2), (4, 2)]);
+        for &(x, y) in &old[..10] {
+            assert_eq!(game.grid[y as usize][x as usize], Cell::Empty, "({}, {})", x, y);
+        }
+        for (x, y) in [(6, 2), (5, 2), (4, 2), (3, 2)] {
+            assert_eq!(game.grid[y][x], Cell::Trail(0), "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn ghost_rides_through_one_trail_cell() {
+        let mut game = seated(&[(2, 2, Direction::Right), (6, 5, Direction::Up)]);
+        lay_trail(&mut game, 1, &[(4, 2), (5, 2)]);
+        game.grid[2][3] = Cell::PowerUp(PowerUpKind::Ghost);
+
+        game.move_player(0, SteerAction::Straight);
+        assert_eq!(game.players[0].ghost_moves(), Some(GHOST_MOVES));
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+        assert_eq!(outcome, "Moved EAST to (4, 2). Distance: 2.");
+        assert!(game.events.iter().any(|e| e.kind == GameEventKind::Ghosted { player: 0, owner: 1 }));
+        assert_eq!(game.grid[2][4], Cell::Trail(0));
+        assert_eq!(game.players[0].ghost_moves(), None);
+
+        // Spent: the next trail cell stops the cycle
+        game.move_player(0, SteerAction::Straight);
+        assert!(matches!(crashes(&game)[..], [(0, CrashCause::Trail { owner: 1, .. })]));
+    }
+
+    #[test]
+    fn ghost_wears_off_after_its_moves() {
+        let mut game = seated(&[(1, 3, Direction::Right), (6, 5, Direction::Up)]);
+        lay_trail(&mut game, 1, &[(6, 3)]);
+        game.grid[3][2] = Cell::PowerUp(PowerUpKind::Ghost);
+
+        game.move_player(0, SteerAction::Straight);
+        for left in (0..GHOST_MOVES).rev() {
+            game.move_player(0, SteerAction::Straight);
+            assert_eq!(game.players[0].ghost_moves(), (left > 0).then_some(left));
+        }
+        assert_eq!((game.players[0].x, game.players[0].y), (5, 3));
+
+        game.move_player(0, SteerAction::Straight);
+        assert!(matches!(crashes(&game)[..], [(0, CrashCause::Trail { owner: 1, .. })]));
+    }
+
+    #[test]
+    fn ghosts_still_crash_head_on() {
+        let mut game = seated(&[(3, 2, Direction::Right), (4, 2, Direction::Left)]);
+        for player in game.players.iter_mut() {
+            player.active_effects.push(ActiveEffect::Ghost { moves_left: GHOST_MOVES });
+        }
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+
+        assert_eq!(outcome, "CRASHED head-on into Gemini! Both cycles are destroyed.");
+        assert_eq!(
+            crashes(&game),
+            [(0, CrashCause::HeadOn { other: 1 }), (1, CrashCause::HeadOn { other: 0 })]
+        );
+        assert!(!game.events.iter().any(|e| matches!(e.kind, GameEventKind::Ghosted { .. })));
+    }
+
+    #[test]
+    fn ghosts_do_not_pass_a_cycle_from_the_side() {
+        let mut game = seated(&[(3, 2, Direction::Right), (4, 2, Direction::Down), (6, 5, Direction::Up)]);
+        game.players[0].active_effects.push(ActiveEffect::Ghost { moves_left: GHOST_MOVES });
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+
+        assert_eq!(outcome, "CRASHED into Gemini's trail!");
+        assert_eq!(crashes(&game), [(0, CrashCause::Trail { owner: 1, cut_off: true })]);
+        assert!(game.players[1].alive);
+        assert!(!game.events.iter().any(|e| matches!(e.kind, GameEventKind::Ghosted { .. })));
+    }
+
+    /// `seated`, in an arena that closes in every `every` moves
+    fn shrinking(every: u32, seats: &[(i32, i32, Direction)]) -> Game {
+        let mut game = seated(seats);
+        game.rules.shrink_every = Some(every);
+        game
+    }
+
+    #[test]
+    fn cycle_on_the_closing_ring_crashes() {
+        let mut game = shrinking(2, &[(1, 3, Direction::Up), (4, 3, Direction::Right)]);
+
+        game.move_player(0, SteerAction::Straight);
+        assert_eq!(game.shrink_in(), Some(1));
+        assert_eq!(game.to_web_state().arena, Some([1, 1, 8, 6]));
+        // The second move of the game closes the ring Claude is riding on
+        let outcome = game.move_player(1, SteerAction::Straight);
+
+        assert_eq!(outcome, "Moved EAST to (5, 3). Distance: 1.");
+        assert_eq!(crashes(&game), [(0, CrashCause::ArenaShrink)]);
+        assert!(game.events.iter().any(|e| e.kind == GameEventKind::ArenaShrank { width: 6, height: 4 }));
+        assert_eq!(game.winner(), Some(1));
+        assert_eq!(game.arena_bounds(), (2, 2, 7, 5));
+        assert_eq!(game.to_web_state().arena, Some([2, 2, 7, 5]));
+        // Claude's trail on the ring went under the new wall
+        assert!(game.players[0].trail.is_empty());
+        for (x, y) in [(1, 2), (1, 3), (1, 1), (8, 6), (5, 1), (8, 4)] {
+            assert_eq!(game.grid[y][x], Cell::Wall, "({}, {})", x, y);
+        }
+        assert_eq!(game.grid[3][5], Cell::Trail(1));
+    }
+
+    #[test]
+    fn riding_onto_the_ring_as_it_closes_crashes_the_rider() {
+        let mut game = shrinking(2, &[(2, 3, Direction::Left), (4, 3, Direction::Right), (5, 5, Direction::Up)]);
+
+        game.move_player(1, SteerAction::Straight);
+        let outcome = game.move_player(0, SteerAction::Straight);
+
+        assert_eq!(outcome, "Moved to (1, 3), but the arena closed in on you: CRASHED!");
+        assert_eq!(crashes(&game), [(0, CrashCause::ArenaShrink)]);
+        assert!(game.players[1].alive && game.players[2].alive);
+        assert_eq!(game.status(), GameStatus::Running);
+        assert_eq!(game.shrink_in(), Some(2));
+    }
+
+    #[test]
+    fn springing_a_trap_credits_the_trail_owner() {
+        let mut game = seated(&[(1, 3, Direction::Right), (5, 1, Direction::Down)]);
+
+        // Claude rides across Gemini's path and Gemini runs into the cycle
+        for _ in 0..3 {
+            game.move_player(0, SteerAction::Straight);
+        }
+        game.move_player(1, SteerAction::Straight);
+        game.move_player(0, SteerAction::Straight);
+        let outcome = game.move_player(1, SteerAction::Straight);
+
+        assert_eq!(outcome, "CRASHED into Claude's trail!");
+        assert_eq!(crashes(&game), [(1, CrashCause::Trail { owner: 0, cut_off: true })]);
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!((game.players[0].kills, game.players[1].kills), (1, 0));
+        let web = game.to_web_state();
+        assert_eq!((web.players[0].kills, web.players[1].kills), (1, 0));
+        // The victim's score has no kill bonus; the trapper's does
+        assert_eq!(game.players[1].score, 1 + game.survival_ticks(1));
+        assert!(game.players[0].score >= 4 + game.survival_ticks(0) + KILL_BONUS);
+    }
+
+    #[test]
+    fn head_on_crashes_credit_both_cycles() {
+        let mut game = seated(&[(3, 2, Direction::Right), (4, 2, Direction::Left), (6, 5, Direction::Up)]);
+
+        game.move_player(1, SteerAction::Straight);
+
+        assert_eq!(
+            crashes(&game),
+            [(1, CrashCause::HeadOn { other: 0 }), (0, CrashCause::HeadOn { other: 1 })]
+        );
+        assert_eq!((game.players[0].kills, game.players[1].kills, game.players[2].kills), (1, 1, 0));
+
+        // Grok is left alone; the pair each score their kill with no win bonus
+        assert_eq!(game.winner(), Some(2));
+        for idx in [0, 1] {
+            assert_eq!(game.players[idx].score, game.survival_ticks(idx) + KILL_BONUS);
+        }
+    }
+
+    #[test]
+    fn a_draw_still_scores_everyone_for_their_ride() {
+        let rules = Ruleset {
+            tie_break: TieBreak::Draw,
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(
+            ARENA,
+            rules,
+            &[(1, 2, Direction::Right), (8, 2, Direction::Left), (5, 5, Direction::Down)],
+        );
+        // Grok rides into the wall early; the other two meet head-on later
+        game.move_player(2, SteerAction::Straight);
+        game.move_player(2, SteerAction::Straight);
+        for _ in 0..3 {
+            game.move_player(0, SteerAction::Straight);
+            game.move_player(1, SteerAction::Straight);
+        }
+        game.move_player(0, SteerAction::Straight);
+
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert_eq!(game.winner(), None);
+        assert_eq!((game.survival_ticks(0), game.survival_ticks(2)), (7, 1));
+        for player in &game.players {
+            assert!(player.score > 0, "{} scored nothing", player.name);
+        }
+        assert_eq!(game.players[0].score, 3 + 7 + KILL_BONUS);
+        assert_eq!(game.players[1].score, game.players[0].score);
+        assert_eq!(game.players[2].score, 1 + 1);
+    }
+
+    #[test]
+    fn crash_causes_reach_the_event_feed() {
+        // (seats for Claude and Gemini, Gemini's trail, the crash, the feed line)
+        let cases = [
+            (
+                [(1, 1, Direction::Up), (5, 5, Direction::Right)],
+                vec![],
+                CrashCause::Wall,
+                "Claude crashed into a wall",
+            ),
+            (
+                [(3, 2, Direction::Down), (7, 5, Direction::Up)],
+                vec![(3, 3), (4, 3), (5, 3), (6, 3)],
+                CrashCause::Trail { owner: 1, cut_off: false },
+                "Claude crashed into Gemini's trail",
+            ),
+            (
+                [(3, 2, Direction::Right), (4, 2, Direction::Left)],
+                vec![],
+                CrashCause::HeadOn { other: 1 },
+                "Claude and Gemini collided head-on",
+            ),
+        ];
+        for ([claude, gemini], trail, cause, line) in cases {
+            // Grok looks on
+            let mut game = seated(&[claude, gemini, (8, 6, Direction::Left)]);
+            lay_trail(&mut game, 1, &trail);
+
+            game.move_player(0, SteerAction::Straight);
+
+            assert_eq!(crashes(&game)[0], (0, cause));
+            let feed: Vec<String> = game.to_web_state().events.into_iter().filter_map(|e| e.text).collect();
+            assert_eq!(feed.iter().filter(|text| text.contains(line)).count(), 1, "{:?}", feed);
+            let look = game.look(2, MAX_VIEW_RADIUS, LookOptions::default());
+            assert!(look.contains(&format!("tick 0: {}.", line)), "{}", look);
+        }
+    }
+
+    #[test]
+    fn walls_and_own_trails_credit_nobody() {
+        let mut game = seated(&[(3, 3, Direction::Right), (5, 1, Direction::Up), (6, 5, Direction::Up)]);
+        lay_trail(&mut game, 0, &[(4, 3)]);
+
+        game.move_player(0, SteerAction::Straight);
+        game.move_player(1, SteerAction::Straight);
+
+        assert_eq!(crashes(&game), [(0, CrashCause::OwnTrail), (1, CrashCause::Wall)]);
+        assert!(game.players.iter().all(|p| p.kills == 0));
+    }
+
+    /// Like `seated`, as two teams of two: Claude and Grok against Gemini
+    /// and Llama
+    fn teamed(seats: &[(i32, i32, Direction)]) -> Game {
+        let rules = Ruleset {
+            team_size: Some(2),
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(ARENA, rules, seats);
+        game.assign_teams(&[Some(1), Some(2), Some(1), Some(2)]);
+        game
+    }
+
+    #[test]
+    fn teammates_ride_through_each_others_trails() {
+        let mut game = teamed(&[
+            (2, 2, Direction::Right),
+            (2, 6, Direction::Right),
+            (6, 4, Direction::Up),
+            (7, 6, Direction::Up),
+        ]);
+        lay_trail(&mut game, 2, &[(3, 2), (4, 2)]);
+        lay_trail(&mut game, 1, &[(5, 2)]);
+
+        game.move_player(0, SteerAction::Straight);
+        game.move_player(0, SteerAction::Straight);
+        assert!(game.players[0].alive);
+        assert_eq!((game.players[0].x, game.players[0].y), (4, 2));
+
+        // An opponent's trail still crashes
+        game.move_player(0, SteerAction::Straight);
+        assert_eq!(crashes(&game), [(0, CrashCause::Trail { owner: 1, cut_off: true })]);
+    }
+
+    #[test]
+    fn team_wins_with_a_member_dead() {
+        let mut game = teamed(&[
+            (1, 1, Direction::Up),
+            (8, 1, Direction::Up),
+            (4, 4, Direction::Right),
+            (8, 6, Direction::Down),
+        ]);
+        game.move_player(0, SteerAction::Straight);
+        game.move_player(1, SteerAction::Straight);
+        game.move_player(2, SteerAction::Straight);
+        assert_eq!(game.status(), GameStatus::Running);
+        game.move_player(3, SteerAction::Straight);
+
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert_eq!(game.winning_team(), Some(1));
+        assert!(!game.players[0].alive);
+        assert!(game.won(0) && game.won(2));
+        assert!(!game.won(1) && !game.won(3));
+    }
+
+    #[test]
+    fn territory_game_ends_on_time_with_the_most_cells_winning() {
+        let rules = Ruleset {
+            win_condition: WinCondition::Territory,
+            territory_ticks: Some(8),
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(ARENA, rules, &[(1, 2, Direction::Right), (1, 4, Direction::Right)]);
+        // Claude starts out holding two cells more
+        lay_trail(&mut game, 0, &[(1, 6), (2, 6)]);
+
+        for _ in 0..3 {
+            game.advance();
+        }
+        assert_eq!(game.cells_claimed(), [6, 4]);
+        assert_eq!(game.status(), GameStatus::Running);
+        game.advance();
+
+        assert_eq!(game.tick(), 8);
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert!(game.players.iter().all(|p| p.alive));
+        assert_eq!(game.winner(), Some(0));
+    }
+
+    /// `ARENA` with a checkpoint
+    const RACE: &str = "
+        ##########
+        #........#
+        #.....F..#
+        #........#
+        #........#
+        #........#
+        #........#
+        ##########";
+
+    #[test]
+    fn reaching_the_checkpoint_wins_with_others_still_riding() {
+        let rules = Ruleset {
+            win_condition: WinCondition::Checkpoint,
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(RACE, rules, &[(2, 4, Direction::Right), (2, 6, Direction::Right)]);
+        ride(&mut game, 0, &[(3, 4), (4, 4), (5, 4), (6, 4), (6, 3)]);
+        assert_eq!(game.status(), GameStatus::Running);
+
+        let outcome = game.move_player(0, SteerAction::Straight);
+
+        assert_eq!(outcome, "Reached the checkpoint at (6, 2)! You win!");
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert_eq!(game.winner(), Some(0));
+        assert!(game.players[1].alive);
+        assert!(game.events.iter().any(|e| matches!(e.kind, GameEventKind::ReachedCheckpoint { player: 0 })));
+    }
+
+    #[test]
+    fn reaching_the_checkpoint_through_a_trail_still_wins() {
+        let rules = Ruleset {
+            win_condition: WinCondition::Checkpoint,
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(RACE, rules, &[(5, 2, Direction::Right), (6, 4, Direction::Up)]);
+        lay_trail(&mut game, 1, &[(6, 3), (6, 2)]);
+
+        game.move_player(0, SteerAction::Straight);
+
+        assert_eq!(game.winner(), Some(0));
+        assert!(crashes(&game).is_empty());
+    }
+
+    /// Ride `idx` along the cells of `path` in order, one move each
+    fn ride(game: &mut Game, idx: usize, path: &[(i32, i32)]) {
+        for &(x, y) in path {
+            let player = &game.players[idx];
+            let toward = [Direction::Up, Direction::Down, Direction::Left, Direction::Right]
+                .into_iter()
+                .find(|d| (player.x + d.delta().0, player.y + d.delta().1) == (x, y))
+                .unwrap();
+            let action = player.direction.turn_toward(toward).unwrap();
+            game.move_player(idx, action);
+        }
+    }
+
+    /// Back and forth along rows `rows` of a course `width` cells wide inside
+    /// its border, starting at the left end of the first row
+    fn serpentine(width: i32, rows: std::ops::RangeInclusive<i32>) -> Vec<(i32, i32)> {
+        rows.enumerate()
+            .flat_map(|(i, y)| {
+                let xs: Vec<i32> = if i % 2 == 0 { (1..=width).collect() } else { (1..=width).rev().collect() };
+                xs.into_iter().map(move |x| (x, y))
+            })
+            .collect()
+    }
+
+    /// Like `seated`, with `lives` each
+    fn with_lives(lives: u32, seats: &[(i32, i32, Direction)]) -> Game {
+        let rules = Ruleset {
+            lives: Some(lives),
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(ARENA, rules, seats);
+        for player in &mut game.players {
+            player.lives = lives;
+        }
+        game
+    }
+
+    #[test]
+    fn respawn_skips_an_occupied_start() {
+        let mut game = with_lives(2, &[(1, 1, Direction::Up), (5, 4, Direction::Right)]);
+        game.replay.starts[0] = (3, 3, Direction::Right);
+        lay_trail(&mut game, 1, &[(3, 3)]);
+
+        game.move_player(0, SteerAction::Straight);
+        assert_eq!(game.players[0].respawn_at_tick, Some(RESPAWN_ROUNDS * 2));
+        ride(&mut game, 1, &[(6, 4), (7, 4), (8, 4), (8, 5), (8, 6), (7, 6)]);
+
+        let claude = &game.players[0];
+        assert!(claude.alive);
+        assert_eq!(claude.lives, 1);
+        // The nearest ring around the start, with room ahead
+        assert_ne!((claude.x, claude.y), (3, 3));
+        assert_eq!((claude.x - 3).abs().max((claude.y - 3).abs()), 1);
+        assert_eq!(game.grid[claude.y as usize][claude.x as usize], Cell::Trail(0));
+        let (dx, dy) = claude.direction.delta();
+        assert_eq!(game.grid[(claude.y + dy) as usize][(claude.x + dx) as usize], Cell::Empty);
+        assert_eq!(game.grid[3][3], Cell::Trail(1));
+    }
+
+    #[test]
+    fn game_ends_when_the_last_opponent_runs_out_of_lives() {
+        let mut game = with_lives(2, &[(1, 1, Direction::Up), (1, 3, Direction::Right)]);
+        let mut path = serpentine(8, 3..=6).into_iter().skip(1);
+
+        game.move_player(0, SteerAction::Straight);
+        while !game.players[0].alive {
+            ride(&mut game, 1, &[path.next().unwrap()]);
+        }
+        assert_eq!(game.status(), GameStatus::Running);
+        for _ in 0..10 {
+            if !game.players[0].alive {
+                break;
+            }
+            game.move_player(0, SteerAction::Straight);
+        }
+
+        assert_eq!(game.players[0].lives, 0);
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert_eq!(game.winner(), Some(1));
+    }
+
+    /// Two cycles riding side by side into a two-tick time limit, having
+    /// traveled `distances` and taken `kills` beforehand
+    fn out_of_time(tie_break: TieBreak, distances: [u32; 2], kills: [u32; 2]) -> Game {
+        let rules = Ruleset {
+            max_ticks: Some(2),
+            tie_break,
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(ARENA, rules, &[(2, 2, Direction::Right), (2, 5, Direction::Right)]);
+        for (player, (distance, kills)) in game.players.iter_mut().zip(distances.into_iter().zip(kills)) {
+            (player.distance_traveled, player.kills) = (distance, kills);
+        }
+        game.advance();
+        assert!(game.hit_time_limit());
+        game
+    }
+
+    #[test]
+    fn time_limit_goes_to_the_longest_distance() {
+        let game = out_of_time(TieBreak::Distance, [3, 5], [2, 0]);
+        assert_eq!(game.winner(), Some(1));
+        assert_eq!(game.tie_break, None);
+    }
+
+    #[test]
+    fn time_limit_at_equal_distance_goes_to_the_most_kills() {
+        let game = out_of_time(TieBreak::Distance, [5, 5], [2, 1]);
+        assert_eq!(game.winner(), Some(0));
+        assert_eq!(game.tie_break, Some(TieBreakReason::Kills));
+    }
+
+    #[test]
+    fn time_limit_at_equal_distance_and_kills_is_a_coin_flip_or_a_draw() {
+        let game = out_of_time(TieBreak::Distance, [5, 5], [1, 1]);
+        assert_eq!(game.tie_break, Some(TieBreakReason::CoinFlip));
+        // Seeded from the game id, so the same game always lands the same way
+        let mut again = out_of_time(TieBreak::Distance, [5, 5], [1, 1]);
+        again.id = game.id;
+        assert_eq!(again.time_limit_leader(&[0, 1]), game.winner());
+        assert!(game.winner().is_some());
+
+        let game = out_of_time(TieBreak::Draw, [5, 5], [1, 1]);
+        assert_eq!(game.winner(), None);
+        assert_eq!(game.tie_break, None);
+    }
+
+    #[test]
+    fn classic_trails_never_fade() {
+        // 20x12 inside the border: room for two cycles to ride 100 cells each
+        let wall = "#".repeat(22);
+        let row = format!("#{}#\n", ".".repeat(20));
+        let open = format!("{}\n{}{}", wall, row.repeat(12), wall);
+        let classic = Ruleset {
+            max_trail_length: 0,
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(&open, classic, &[(1, 1, Direction::Right), (1, 7, Direction::Right)]);
+        let paths = [serpentine(20, 1..=6), serpentine(20, 7..=12)];
+
+        for step in 1..=100 {
+            for (idx, path) in paths.iter().enumerate() {
+                ride(&mut game, idx, &path[step..=step]);
+                assert_eq!(game.players[idx].trail.len(), step);
+                for &(x, y) in &path[..=step] {
+                    assert_eq!(game.grid[y as usize][x as usize], Cell::Trail(idx), "({}, {})", x, y);
+                }
+            }
+        }
+        assert_eq!(game.tick(), 200);
+        assert_eq!(game.status(), GameStatus::Running);
+        assert!(crashes(&game).is_empty());
+
+        // The same ride under the default rules leaves only the newest cells
+        let mut game = seated_on(&open, Ruleset::default(), &[(1, 1, Direction::Right), (1, 7, Direction::Right)]);
+        ride(&mut game, 0, &paths[0][1..=100]);
+        let limit = Ruleset::default().max_trail_length;
+        assert_eq!(game.players[0].trail.len(), limit);
+        assert_eq!(game.grid[1][1], Cell::Empty);
+    }
+
+    /// Like `seated`, with `grace` moves of spawn protection each, as
+    /// `start` hands it out
+    fn protected(grace: u32, seats: &[(i32, i32, Direction)]) -> Game {
+        let mut game = seated(seats);
+        game.rules.spawn_grace = Some(grace);
+        for player in game.players.iter_mut() {
+            player.spawn_protection_until = grace * seats.len() as u32;
+        }
+        game
+    }
+
+    #[test]
+    fn spawn_seats_are_dealt_the_same_for_the_same_game_id() {
+        let deal = |id: u128| {
+            let mut game = Game::new(&testutil::course(ARENA), Ruleset::default()).unwrap();
+            game.id = Uuid::from_u128(id);
+            for name in ["Claude", "Gemini", "Grok", "Llama"] {
+                game.add_player(name.to_string()).unwrap();
+            }
+            let joined: Vec<(i32, i32)> = game.players.iter().map(|p| (p.x, p.y)).collect();
+            game.shuffle_spawns();
+            let dealt: Vec<(i32, i32, Direction)> = game.players.iter().map(|p| (p.x, p.y, p.direction)).collect();
+            (joined, dealt)
+        };
+
+        for id in [1, 7, 12345] {
+            assert_eq!(deal(id), deal(id));
+        }
+        // Some ids deal the seats in another order than joining did
+        assert!((0..20).any(|id| {
+            let (joined, dealt) = deal(id);
+            dealt.iter().map(|&(x, y, _)| (x, y)).ne(joined)
+        }));
+    }
+
+    #[test]
+    fn spawn_protection_spares_fresh_trail_until_it_runs_out() {
+        // Two players with 2 moves of grace each: protected through tick 3.
+        // Gemini rides down across Claude's path, then Claude rides into
+        // the trail Gemini left at (3, 3).
+        let seats = [(2, 3, Direction::Right), (3, 1, Direction::Down)];
+        let mut game = protected(2, &seats);
+        ride(&mut game, 1, &[(3, 2), (3, 3), (3, 4)]);
+        assert_eq!(game.protected_for(0), Some(1));
+        assert_eq!(game.move_player(0, SteerAction::Straight), "Moved EAST to (3, 3). Distance: 1.");
+        assert!(game.players[0].alive);
+
+        // One more move by Gemini and the protection is gone by Claude's turn
+        let mut game = protected(2, &seats);
+        ride(&mut game, 1, &[(3, 2), (3, 3), (3, 4), (3, 5)]);
+        assert_eq!(game.protected_for(0), None);
+        assert_eq!(game.move_player(0, SteerAction::Straight), "CRASHED into Gemini's trail!");
+        assert!(!game.players[0].alive);
+    }
+
+    #[test]
+    fn spawn_protection_does_not_cover_trail_laid_before_it() {
+        // Gemini's trail at (3, 3) is laid on tick 2; Claude is protected
+        // from tick 3 on, as after a respawn, so it still crashes there
+        let mut game = protected(2, &[(2, 3, Direction::Right), (3, 1, Direction::Down)]);
+        ride(&mut game, 1, &[(3, 2), (3, 3), (3, 4)]);
+        game.players[0].spawn_protection_until = game.tick() + 4;
+        assert_eq!(game.protected_for(0), Some(4));
+
+        assert_eq!(game.move_player(0, SteerAction::Straight), "CRASHED into Gemini's trail!");
+        assert!(!game.players[0].alive);
+    }
+
+    /// Three humans on `ARENA` whose abort votes are timed by a manual clock
+    fn voting() -> Game {
+        let mut game = testutil::game(ARENA, &["Claude", "Gemini", "Grok"]);
+        game.clock = Clock::manual();
+        game
+    }
+
+    #[test]
+    fn game_is_aborted_once_every_player_agrees() {
+        let mut game = voting();
+        assert_eq!(game.request_abort(0), AbortOutcome::Pending(vec!["Gemini".to_string(), "Grok".to_string()]));
+        assert_eq!(game.request_abort(2), AbortOutcome::Pending(vec!["Gemini".to_string()]));
+        assert_eq!(game.request_abort(1), AbortOutcome::Aborted);
+
+        assert!(game.aborted);
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert_eq!(game.winner(), None);
+    }
+
+    #[test]
+    fn partial_abort_votes_only_notify_the_others() {
+        let mut game = voting();
+        game.request_abort(0);
+        assert_eq!(game.request_abort(1), AbortOutcome::Pending(vec!["Grok".to_string()]));
+
+        assert_eq!(game.status(), GameStatus::Running);
+        assert!(!game.aborted);
+        let notice = game.abort_notice(2).unwrap();
+        assert!(notice.starts_with("Claude, Gemini asked to abort this game."), "{}", notice);
+        assert_eq!(game.abort_notice(0), None);
+        // Asking again does not count twice
+        assert_eq!(game.request_abort(1), AbortOutcome::Pending(vec!["Grok".to_string()]));
+    }
+
+    #[test]
+    fn abort_votes_expire_after_the_window() {
+        let mut game = voting();
+        game.request_abort(0);
+        game.clock.advance(chrono::Duration::seconds(ABORT_WINDOW_SECS - 1));
+        game.request_abort(1);
+        game.clock.advance(chrono::Duration::seconds(1));
+
+        // Claude's vote is a full window old by now; Gemini's still stands
+        assert_eq!(game.request_abort(2), AbortOutcome::Pending(vec!["Claude".to_string()]));
+        assert!(game.abort_notice(0).unwrap().starts_with("Gemini, Grok asked"));
+        assert_eq!(game.request_abort(0), AbortOutcome::Aborted);
+    }
+
+    /// Claude keeps steering on row 2 while Gemini never does
+    fn one_silent(policy: IdlePolicy, active_moves: usize) -> (Game, Vec<String>) {
+        let mut game = seated(&[(1, 2, Direction::Right), (6, 5, Direction::Left)]);
+        let mut acted = Vec::new();
+        for _ in 0..active_moves {
+            game.move_player(0, SteerAction::Straight);
+            acted.extend(game.enforce_idle(policy));
+        }
+        (game, acted)
+    }
+
+    #[test]
+    fn silent_player_is_moved_straight_after_enough_opponent_moves() {
+        let policy = IdlePolicy {
+            after_moves: Some(3),
+            ..IdlePolicy::default()
+        };
+        let (game, acted) = one_silent(policy, 2);
+        assert!(acted.is_empty());
+        assert_eq!((game.players[1].x, game.players[1].idle_moves), (6, 0));
+
+        let (game, acted) = one_silent(policy, 6);
+        assert_eq!(acted, ["Gemini", "Gemini"]);
+        let gemini = &game.players[1];
+        assert_eq!((gemini.x, gemini.y, gemini.idle_moves), (4, 5, 2));
+        assert!(gemini.alive);
+        assert_eq!(game.players[0].idle_moves, 0);
+        assert_eq!(game.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn silent_player_is_crashed_and_the_active_one_wins() {
+        let policy = IdlePolicy {
+            after_moves: Some(3),
+            action: IdleAction::Crash,
+            ..IdlePolicy::default()
+        };
+        let (game, acted) = one_silent(policy, 3);
+        assert_eq!(acted, ["Gemini"]);
+        assert_eq!(crashes(&game), [(1, CrashCause::Timeout)]);
+        assert!(game.players[0].alive);
+        assert_eq!(game.status(), GameStatus::Finished);
+        assert_eq!(game.winner(), Some(0));
+    }
+
+    #[test]
+    fn idle_time_only_counts_for_players_who_stopped_steering() {
+        let policy = IdlePolicy {
+            after_secs: Some(30),
+            action: IdleAction::Crash,
+            ..IdlePolicy::default()
+        };
+        let (mut game, acted) = one_silent(policy, 1);
+        assert!(acted.is_empty());
+
+        game.players[1].last_move_at -= chrono::Duration::seconds(31);
+        assert_eq!(game.enforce_idle(policy), ["Gemini"]);
+        assert!(game.players[0].alive);
+        assert_eq!(game.winner(), Some(0));
+
+        // Without a limit nobody is ever idle
+        let (game, acted) = one_silent(IdlePolicy::default(), 6);
+        assert!(acted.is_empty());
+        assert!(game.players[1].alive);
+    }
+
+    /// Grok rides a four-cell trail up column 5 into the wall while Claude
+    /// and Gemini ride on
+    fn wrecked(policy: DeadTrailPolicy) -> Game {
+        let rules = Ruleset {
+            dead_trail_policy: policy,
+            ..Ruleset::default()
+        };
+        let mut game = seated_on(
+            ARENA,
+            rules,
+            &[(1, 2, Direction::Right), (8, 6, Direction::Left), (5, 2, Direction::Up)],
+        );
+        lay_trail(&mut game, 2, &[(5, 4), (5, 3)]);
+        game.move_player(2, SteerAction::Straight);
+        game.move_player(2, SteerAction::Straight);
+        assert_eq!(crashes(&game), [(2, CrashCause::Wall)]);
+        game
+    }
+
+    /// Grok's trail cells still on the grid, top to bottom
+    fn wreck(game: &Game) -> Vec<(i32, i32)> {
+        (1..7).map(|y| (5, y)).filter(|&(x, y)| game.grid[y as usize][x as usize] == Cell::Trail(2)).collect()
+    }
+
+    #[test]
+    fn kept_dead_trails_stay_and_are_drawn_apart() {
+        let mut game = wrecked(DeadTrailPolicy::Keep);
+        for _ in 0..5 {
+            game.move_player(1, SteerAction::Straight);
+        }
+        assert_eq!(wreck(&game), [(5, 1), (5, 2), (5, 3), (5, 4)]);
+
+        // Lowercase in `look`, their own code on the web grid
+        assert_eq!(render::glyph(&game, 5, 3, Some(0)), 'c');
+        assert_eq!(render::glyph(&game, 4, 6, Some(0)), '2');
+        let web = game.to_web_state();
+        assert_eq!(web.grid[3][5], DEAD_TRAIL_CODE + 2);
+        assert_eq!(web.grid[6][4], 3 + 1);
+    }
+
+    #[test]
+    fn cleared_dead_trails_vanish_with_the_crash() {
+        let game = wrecked(DeadTrailPolicy::ClearImmediately);
+        assert!(wreck(&game).is_empty());
+        assert_eq!(game.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn decaying_dead_trails_fade_from_the_tail() {
+        let mut game = wrecked(DeadTrailPolicy::Decay(2));
+        let mut left = vec![wreck(&game)];
+        for _ in 0..5 {
+            game.move_player(1, SteerAction::Straight);
+            left.push(wreck(&game));
+        }
+        assert_eq!(
+            left,
+            [
+                vec![(5, 1), (5, 2), (5, 3), (5, 4)],
+                vec![(5, 1), (5, 2), (5, 3), (5, 4)],
+                vec![(5, 1), (5, 2), (5, 3)],
+                vec![(5, 1), (5, 2)],
+                vec![(5, 1)],
+                vec![],
+            ]
+        );
+    }
 }
@@ -0,0 +1,213 @@
+//! Tron light-cycle games for LLM agents, played over MCP, TCP or a web UI.
+//!
+//! The `tronmcp` binary only calls [`cli::run`]. The rest of the public
+//! surface is for embedding the game elsewhere: [`game::Game`] is one game on
+//! a [`course::Course`], stepped by hand; [`manager::GameManager`] runs a room
+//! of them, with the queue, lobbies and leaderboard; [`web::create_router`]
+//! serves the web UI and the MCP HTTP endpoint for a set of [`rooms::Rooms`];
+//! and [`mcp::TronMcpServer`] is the stdio MCP player that talks to a running
+//! server over TCP.
+
+pub mod admin;
+mod analysis;
+mod autoplay;
+mod batch;
+pub mod bot;
+pub mod broadcast;
+pub mod cli;
+pub mod clock;
+pub mod course;
+mod demo;
+pub mod error;
+pub mod fairness;
+#[doc(hidden)]
+pub mod fuzzing;
+pub mod game;
+pub mod lobby;
+pub mod manager;
+mod maze;
+pub mod mcp;
+mod metrics;
+mod persist;
+mod protocol;
+mod push;
+mod ratelimit;
+pub mod rating;
+pub mod rematch;
+mod render;
+mod replay;
+pub mod rooms;
+pub mod rules;
+mod scatter;
+mod simulate;
+mod snapshot;
+pub mod storage;
+mod tcp;
+#[cfg(test)]
+mod testutil;
+mod validation;
+pub mod watchdog;
+pub mod web;
+mod webhook;
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+use crate::broadcast::BroadcastEvent;
+use crate::rooms::SharedRooms;
+
+/// Time open web connections get to close once shutdown starts; browsers
+/// keep the event stream open, so the server doesn't wait on them forever
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// Run the rooms' background tasks and serve TCP players and the web UI on
+/// the given listeners until `ct` is cancelled. Then stop taking
+/// connections, tell every room's watchers, and save each room's state.
+async fn serve(
+    rooms: SharedRooms,
+    admin: Arc<admin::AdminAuth>,
+    http: TcpListener,
+    tcp: tcp::CommandListener,
+    tcp_settings: tcp::TcpSettings,
+    ct: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Housekeeping tick loop (bot fill) and the watchdog that reports it stalling
+    let heartbeat = watchdog::SharedHeartbeat::default();
+    let mut senders = Vec::new();
+    for (_, manager) in rooms.iter() {
+        senders.push(manager.lock().await.broadcast_tx.clone());
+    }
+    let tick_loop = tokio::spawn(watchdog::run_tick_loop(rooms.clone(), heartbeat.clone(), ct.clone()));
+    for (_, manager) in rooms.iter() {
+        tokio::spawn(webhook::run_sender(manager.clone()));
+    }
+    let watchdog_heartbeat = heartbeat.clone();
+    std::thread::Builder::new()
+        .name("watchdog".to_string())
+        .spawn(move || watchdog::run_watchdog(watchdog_heartbeat, senders))?;
+
+    // Spawn TCP command server for MCP players
+    let (http_port, tcp_at) = (http.local_addr()?.port(), tcp.describe());
+    let tcp_rooms = rooms.clone();
+    let tcp_ct = ct.clone();
+    let tcp_server = tokio::spawn(async move {
+        if let Err(e) = tcp::run_tcp_server(tcp, tcp_rooms, tcp_settings, tcp_ct).await {
+            tracing::error!("TCP server error: {}", e);
+        }
+    });
+
+    // Start HTTP web UI + MCP HTTP endpoint
+    let app = web::create_router(rooms.clone(), admin, heartbeat, ct.clone());
+    tracing::info!("Tron MCP server starting!");
+    tracing::info!("Web UI: http://localhost:{}", http_port);
+    tracing::info!("MCP HTTP: http://localhost:{}/mcp", http_port);
+    tracing::info!("TCP command server: {}", tcp_at);
+    tracing::info!("Rooms: {} (default: {})", rooms.names().join(", "), rooms.default_name());
+
+    let (announce_rooms, announce_ct) = (rooms.clone(), ct.clone());
+    let served = axum::serve(
+        http,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        announce_ct.cancelled().await;
+        for (_, manager) in announce_rooms.iter() {
+            let _ = manager.lock().await.broadcast_tx.send(BroadcastEvent::ServerShutdown);
+        }
+    });
+    let grace = async {
+        ct.cancelled().await;
+        tokio::time::sleep(SHUTDOWN_GRACE).await;
+    };
+    let result = tokio::select! {
+        result = served => result,
+        _ = grace => Ok(()),
+    };
+    let _ = tcp_server.await;
+    let _ = tick_loop.await;
+
+    for (_, manager) in rooms.iter() {
+        manager.lock().await.shutdown();
+    }
+    result?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Steer, SteerAction};
+
+    #[tokio::test]
+    async fn cancelling_the_token_closes_the_listeners_and_writes_the_files() {
+        let (rooms, dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let (finished, running, mut events) = {
+            let mut mgr = manager.lock().await;
+            for name in ["Claude", "Gemini"] {
+                mgr.join(name.to_string(), Default::default()).unwrap();
+            }
+            let finished = mgr.player_sessions["Claude"].game_id.unwrap();
+            mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+            mgr.leave("Gemini").unwrap();
+            for name in ["Grok", "Llama"] {
+                mgr.join(name.to_string(), Default::default()).unwrap();
+            }
+            let running = mgr.player_sessions["Grok"].game_id.unwrap();
+            mgr.move_player("Grok", Steer::Turn(SteerAction::Straight)).unwrap();
+            mgr.move_player("Grok", Steer::Turn(SteerAction::Straight)).unwrap();
+            (finished, running, mgr.subscribe())
+        };
+
+        let http = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let tcp = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let (http_addr, tcp_addr) = (http.local_addr().unwrap(), tcp.local_addr().unwrap());
+        let settings = tcp::TcpSettings {
+            identity: tcp::TcpIdentity::Tokens,
+            rate_limit: ratelimit::RateLimit::Off,
+        };
+        let ct = CancellationToken::new();
+        let serving = serve(
+            rooms.clone(),
+            Arc::new(admin::AdminAuth::default()),
+            http,
+            tcp::CommandListener::Tcp(tcp),
+            settings,
+            ct.clone(),
+        );
+        let stopping = async {
+            // A player still connected doesn't hold the shutdown up
+            let player = tokio::net::TcpStream::connect(tcp_addr).await.unwrap();
+            ct.cancel();
+            player
+        };
+        let (served, _player) = tokio::time::timeout(SHUTDOWN_GRACE * 3, async { tokio::join!(serving, stopping) })
+            .await
+            .expect("serve did not return");
+        served.unwrap();
+
+        loop {
+            match events.try_recv() {
+                Ok(BroadcastEvent::ServerShutdown) => break,
+                Ok(_) => continue,
+                Err(e) => panic!("no server_shutdown event: {}", e),
+            }
+        }
+        assert!(tokio::net::TcpStream::connect(http_addr).await.is_err());
+        assert!(tokio::net::TcpStream::connect(tcp_addr).await.is_err());
+
+        let read = |file: &str| -> serde_json::Value {
+            let text = std::fs::read_to_string(dir.path().join(file)).unwrap_or_else(|e| panic!("{}: {}", file, e));
+            serde_json::from_str(&text).unwrap()
+        };
+        assert!(read("leaderboard.json").to_string().contains("\"Claude\""));
+        assert!(read("finished_games.json").to_string().contains(&finished.to_string()));
+        let active: storage::ActiveState = serde_json::from_value(read("active_state.json")).unwrap();
+        assert_eq!(active.games.len(), 1);
+        assert_eq!(active.games[0].id, running);
+        assert_eq!(active.games[0].players[0].distance_traveled, 2);
+        assert_eq!(active.player_sessions["Llama"].game_id, Some(running));
+    }
+}
@@ -0,0 +1,93 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::bot::BotParams;
+use crate::rules::Ruleset;
+
+/// Characters used in lobby codes (no 0/O or 1/I to keep codes easy to read aloud)
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+
+/// A private matchmaking queue with its own course and rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lobby {
+    pub code: String,
+    pub course_name: String,
+    pub course_level: u32,
+    pub tags: Vec<String>,
+    pub rules: Ruleset,
+    pub rules_diff: Vec<String>,
+    pub unranked: bool,
+    /// Public lobbies are listed at /api/lobbies; private ones are reachable by code only
+    pub public: bool,
+    pub waiting_players: Vec<String>,
+    /// The team each waiting player asked for, in team games
+    #[serde(default)]
+    pub team_picks: HashMap<String, usize>,
+    /// Server bots seated in every game the lobby starts
+    pub bots: Vec<(String, BotParams)>,
+    /// Games from this lobby are listed first in the web UI
+    pub featured: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Lobby {
+    /// Seats in each game the lobby starts: both full teams in team games,
+    /// otherwise up to the course's `course_seats`
+    pub fn seats(&self, course_seats: usize) -> usize {
+        self.rules.team_size.map_or(course_seats, |size| size * 2)
+    }
+
+    /// Serialize lobby state for the web UI and join links
+    pub fn to_web_state(&self, room: &str) -> WebLobbyState {
+        WebLobbyState {
+            code: self.code.clone(),
+            room: room.to_string(),
+            course_name: self.course_name.clone(),
+            course_level: self.course_level,
+            tags: self.tags.clone(),
+            rules: self.rules.clone(),
+            rules_diff: self.rules_diff.clone(),
+            unranked: self.unranked,
+            public: self.public,
+            waiting_players: self.waiting_players.clone(),
+            bots: self.bots.iter().map(|(name, _)| name.clone()).collect(),
+            featured: self.featured,
+            created_at: self.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebLobbyState {
+    pub code: String,
+    pub room: String,
+    pub course_name: String,
+    pub course_level: u32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub rules: Ruleset,
+    pub rules_diff: Vec<String>,
+    pub unranked: bool,
+    pub public: bool,
+    pub waiting_players: Vec<String>,
+    #[serde(default)]
+    pub bots: Vec<String>,
+    #[serde(default)]
+    pub featured: bool,
+    pub created_at: String,
+}
+
+/// Generate a lobby code that `taken` does not already use
+pub fn generate_code(taken: impl Fn(&str) -> bool) -> String {
+    let mut rng = rand::thread_rng();
+    loop {
+        let code: String = (0..CODE_LENGTH)
+            .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+            .collect();
+        if !taken(&code) {
+            return code;
+        }
+    }
+}
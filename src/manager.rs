@@ -1,46 +1,594 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{broadcast, Mutex};
 use uuid::Uuid;
 
-use crate::course::{all_courses, get_course};
-use crate::game::{Game, GameStatus, SteerAction, WebGameState};
+use crate::admin::{AdminAuth, Role, SharedAdminAuth, constant_time_eq};
+use crate::analysis;
+use crate::batch::{self, BatchFailure, BatchOp, BatchReport, MAX_STEPS};
+use crate::bot::{self, AdaptiveDifficulty, BotParams, Difficulty};
+use crate::broadcast::{BroadcastEvent, LeftFrom};
+use crate::clock::Clock;
+use crate::course::{self, all_courses, get_course, get_course_with_seed, Course, CourseRegistry, TagPreferences};
+use crate::error::{ErrorCode, TronError};
+use crate::fairness::FairnessDelay;
+use crate::game::{
+    AbortOutcome, Game, GameMode, GameStatus, IdlePolicy, LookFormat, LookOptions, Steer, SteerAction, WebGameState, CHAT_SHOWN,
+    MIN_GRID_SIZE,
+};
+use crate::lobby::{self, Lobby, WebLobbyState};
+use crate::metrics::{METRICS, RoomSample};
+use crate::persist::{self, DebouncedFile};
+use crate::rating::{self, RatingHistory, RatingPoint, RatingSummary};
+use crate::rematch::{Rematch, RematchQuorum};
+use crate::rules::{Ruleset, RulesOverride, WinCondition, MAX_MOVE_TIMEOUT_SECS};
+use crate::snapshot::{StateSnapshot, SCHEMA_VERSION};
+use crate::storage::{ActiveState, GameFilter, SharedStorage};
+use crate::validation;
+use crate::webhook::{MAX_WEBHOOKS, ServerWebhooks, Webhook, WebhookRequest};
 
 /// Leaderboard entry
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderboardEntry {
     pub name: String,
     pub wins: u32,
     pub total_points: u32,
     pub games_played: u32,
     pub highest_level: u32,
+    #[serde(default = "initial_rating")]
+    pub rating: i32,
+    /// Rating change over the last 7 days, filled in when the leaderboard is served
+    #[serde(default)]
+    pub rating_change_7d: i32,
+    /// Ranked games someone else won
+    #[serde(default)]
+    pub losses: u32,
+    /// Ranked games that ended with no winner
+    #[serde(default)]
+    pub draws: u32,
+    /// Wins in a row up to the last game; a loss or a draw resets it
+    #[serde(default)]
+    pub current_streak: u32,
+    #[serde(default)]
+    pub best_streak: u32,
+    /// When the player's last ranked game finished (RFC 3339)
+    #[serde(default)]
+    pub last_played: Option<String>,
+    /// Share of games won, 0 to 1, filled in when the leaderboard is served
+    #[serde(default)]
+    pub win_rate: f64,
+    /// Opponents who crashed into the player's trail or head, over all
+    /// ranked games
+    #[serde(default)]
+    pub kills: u32,
+    /// The same record kept for each level played, so strengths on one
+    /// kind of course don't hide behind the totals
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub levels: BTreeMap<u32, LevelStats>,
+}
+
+/// A player's ranked games on one level
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LevelStats {
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub total_points: u32,
+    /// Ticks the games lasted, summed
+    pub total_ticks: u64,
+}
+
+/// One player's line of a level's leaderboard
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelStanding {
+    pub name: String,
+    pub level: u32,
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub total_points: u32,
+    /// Share of the level's games won, 0 to 1
+    pub win_rate: f64,
+    /// Average length of the player's games on the level, in ticks
+    pub avg_ticks: f64,
+    /// The player's overall rating; ratings aren't kept per level
+    pub rating: i32,
+}
+
+fn initial_rating() -> i32 {
+    rating::INITIAL_RATING
+}
+
+/// Crashing within this many ticks of the start counts as an early crash
+pub const EARLY_CRASH_TICKS: u32 = 10;
+/// How much longer an early crasher waits before rejoining
+const EARLY_CRASH_FACTOR: u64 = 3;
+
+/// Seconds a player who crashed at `crash_tick` waits before they may join
+/// again: `base_secs`, or three times that for an early crash
+pub fn crash_cooldown_secs(base_secs: u64, crash_tick: u32) -> u64 {
+    if crash_tick < EARLY_CRASH_TICKS {
+        base_secs * EARLY_CRASH_FACTOR
+    } else {
+        base_secs
+    }
+}
+
+/// Minimum games for a place in the win-rate standings, unless asked otherwise
+const WIN_RATE_MIN_GAMES: u32 = 5;
+
+/// What happens to a player in a running game whose connection drops
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisconnectPolicy {
+    /// Nothing; the idle policy deals with a cycle nobody steers
+    #[default]
+    Keep,
+    /// Forfeit unless the player resumes within the grace period
+    Grace,
+    /// Forfeit right away
+    Forfeit,
+}
+
+impl DisconnectPolicy {
+    pub fn parse(s: &str) -> Result<DisconnectPolicy, String> {
+        match s.trim().to_lowercase().as_str() {
+            "keep" => Ok(DisconnectPolicy::Keep),
+            "grace" => Ok(DisconnectPolicy::Grace),
+            "forfeit" => Ok(DisconnectPolicy::Forfeit),
+            _ => Err("expected 'keep', 'grace' or 'forfeit'".to_string()),
+        }
+    }
+}
+
+/// How a multi-step move reaches the web stream (`--plan-updates`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlanUpdates {
+    /// An update after every step, like separate steers
+    #[default]
+    PerStep,
+    /// One update once all the steps are made
+    Combined,
+}
+
+impl PlanUpdates {
+    pub fn parse(s: &str) -> Result<PlanUpdates, String> {
+        match s.trim().to_lowercase().as_str() {
+            "per-step" | "perstep" => Ok(PlanUpdates::PerStep),
+            "combined" => Ok(PlanUpdates::Combined),
+            _ => Err("expected 'per-step' or 'combined'".to_string()),
+        }
+    }
+}
+
+/// Several moves made in one call (`steer` with a count, `plan_moves`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MovePlan {
+    /// Steer once, then go straight for the rest of the steps, stopping
+    /// short of anything in the way
+    Repeat(Steer, usize),
+    /// These steers in order, whatever they run into
+    List(Vec<Steer>),
+}
+
+/// What a forfeit did to the player's game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Forfeited {
+    /// The others play on
+    Continues,
+    /// It left one side standing, which won
+    Ended,
+    /// Nobody had moved yet, so the game was voided
+    Voided,
+}
+
+/// What a spectator has been shown of a game, so `watch` only reports what
+/// is new. Kept by the spectator's TCP connection or MCP session; spectators
+/// have no player session and are never queued or ranked.
+#[derive(Debug, Clone, Default)]
+pub struct SpectatorSession {
+    /// The game last watched
+    pub game_id: Option<Uuid>,
+    /// How many of its events have been reported
+    pub events_seen: usize,
+    /// When the newest chat message reported was said
+    pub chat_seen: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Order of the leaderboard standings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeaderboardSort {
+    #[default]
+    Points,
+    Rating,
+    WinRate,
+}
+
+impl LeaderboardSort {
+    pub fn parse(s: &str) -> Result<LeaderboardSort, String> {
+        match s.trim().to_lowercase().as_str() {
+            "points" => Ok(LeaderboardSort::Points),
+            "rating" => Ok(LeaderboardSort::Rating),
+            "win_rate" | "winrate" => Ok(LeaderboardSort::WinRate),
+            _ => Err("expected 'points', 'rating' or 'win_rate'".to_string()),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LeaderboardSort::Points => "points",
+            LeaderboardSort::Rating => "rating",
+            LeaderboardSort::WinRate => "win_rate",
+        }
+    }
+
+    /// Games a player needs to be listed when no minimum is given: a win
+    /// rate over one or two games says little
+    pub fn default_min_games(self) -> u32 {
+        match self {
+            LeaderboardSort::WinRate => WIN_RATE_MIN_GAMES,
+            LeaderboardSort::Points | LeaderboardSort::Rating => 0,
+        }
+    }
+}
+
+/// Checksum of a set of leaderboard entries: `sha256:` and the hex SHA-256
+/// of their canonical form, which is one line per entry sorted by name:
+///
+/// `<name> <wins> <total_points> <games_played> <highest_level> <rating> <losses> <draws> <current_streak> <best_streak> <kills> <last_played> <levels>\n`
+///
+/// `name` and `last_played` are JSON strings (`null` when never played) and
+/// `levels` is the per-level record as compact JSON, levels in ascending
+/// order and fields in declaration order. Every stored field is covered, so
+/// changing any one changes the checksum; the display fields filled in when
+/// the leaderboard is served (`rating_change_7d`, `win_rate`) are not. The
+/// value depends on the entries alone and never on HashMap order.
+pub fn leaderboard_checksum<'a>(entries: impl IntoIterator<Item = &'a LeaderboardEntry>) -> String {
+    let mut entries: Vec<&LeaderboardEntry> = entries.into_iter().collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut hasher = Sha256::new();
+    for e in entries {
+        let name = serde_json::Value::String(e.name.clone()).to_string();
+        let last_played = serde_json::json!(e.last_played).to_string();
+        let levels = serde_json::to_string(&e.levels).unwrap_or_default();
+        hasher.update(format!(
+            "{} {} {} {} {} {} {} {} {} {} {} {} {}\n",
+            name,
+            e.wins,
+            e.total_points,
+            e.games_played,
+            e.highest_level,
+            e.rating,
+            e.losses,
+            e.draws,
+            e.current_streak,
+            e.best_streak,
+            e.kills,
+            last_played,
+            levels
+        ));
+    }
+    let hex: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256:{}", hex)
+}
+
+impl Default for LeaderboardEntry {
+    fn default() -> Self {
+        LeaderboardEntry {
+            name: String::new(),
+            wins: 0,
+            total_points: 0,
+            games_played: 0,
+            highest_level: 0,
+            rating: rating::INITIAL_RATING,
+            rating_change_7d: 0,
+            losses: 0,
+            draws: 0,
+            current_streak: 0,
+            best_streak: 0,
+            last_played: None,
+            win_rate: 0.0,
+            kills: 0,
+            levels: BTreeMap::new(),
+        }
+    }
+}
+
+/// Finished games a player profile lists
+pub const PROFILE_GAMES: usize = 20;
+
+/// What a player is doing right now, for their profile
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum PlayerActivity {
+    Playing { game_id: String, course: String, level: u32 },
+    /// Waiting in the public queue (`lobby` is `None`) or in a lobby
+    Queued { lobby: Option<String>, level: u32 },
+    Idle,
+}
+
+/// One finished game from a player's point of view
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayedGame {
+    pub id: String,
+    pub course: String,
+    pub level: u32,
+    /// `win`, `loss`, `draw` or `aborted`
+    pub result: &'static str,
+    pub score: u32,
+    pub opponents: Vec<String>,
+    pub ranked: bool,
+    pub finished_at: Option<String>,
+}
+
+impl PlayedGame {
+    fn from_web(game: &WebGameState, name: &str) -> Option<PlayedGame> {
+        let me = game.players.iter().find(|p| p.name == name)?;
+        let won = game.winner == Some(me.index) || game.winning_team.is_some_and(|team| me.team == Some(team));
+        Some(PlayedGame {
+            id: game.id.clone(),
+            course: game.course_name.clone(),
+            level: game.course_level,
+            result: match (game.aborted, game.winner, won) {
+                (true, _, _) => "aborted",
+                (false, _, true) => "win",
+                (false, None, false) => "draw",
+                (false, Some(_), false) => "loss",
+            },
+            score: me.score,
+            opponents: game.players.iter().filter(|p| p.name != name).map(|p| p.name.clone()).collect(),
+            ranked: !game.unranked && !game.aborted,
+            finished_at: game.finished_at.clone(),
+        })
+    }
+}
+
+/// Everything known about a player: their leaderboard standing (`None`
+/// before their first ranked game), what they are doing now and their
+/// latest finished games, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerProfile {
+    pub name: String,
+    pub leaderboard: Option<LeaderboardEntry>,
+    pub activity: PlayerActivity,
+    pub recent_games: Vec<PlayedGame>,
+}
+
+impl PlayerProfile {
+    /// The profile as text, for `player_stats`
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("Player '{}'", self.name)];
+        match &self.leaderboard {
+            Some(e) => {
+                lines.push(format!(
+                    "Standing: {} pts, rating {}, {}W {}L {}D ({:.0}% wins), {} kills, streak {} (best {}), level {}",
+                    e.total_points,
+                    e.rating,
+                    e.wins,
+                    e.losses,
+                    e.draws,
+                    e.win_rate * 100.0,
+                    e.kills,
+                    e.current_streak,
+                    e.best_streak,
+                    e.highest_level
+                ));
+                if !e.levels.is_empty() {
+                    let levels: Vec<String> = e
+                        .levels
+                        .iter()
+                        .map(|(level, s)| format!("level {} {}W/{}", level, s.wins, s.games_played))
+                        .collect();
+                    lines.push(format!("By level (wins/games): {}", levels.join(", ")));
+                }
+            }
+            None => lines.push("Standing: no ranked games yet".to_string()),
+        }
+        lines.push(match &self.activity {
+            PlayerActivity::Playing { game_id, course, level } => {
+                format!("Now: playing {} (level {}) in game {}", course, level, game_id)
+            }
+            PlayerActivity::Queued { lobby: Some(code), level } => {
+                format!("Now: waiting in lobby {} (level {})", code, level)
+            }
+            PlayerActivity::Queued { lobby: None, level } => format!("Now: queued at level {}", level),
+            PlayerActivity::Idle => "Now: not playing or queued".to_string(),
+        });
+        if self.recent_games.is_empty() {
+            lines.push("No finished games on record.".to_string());
+        } else {
+            lines.push(format!("Last {} games, newest first:", self.recent_games.len()));
+            for g in &self.recent_games {
+                lines.push(format!(
+                    "- {} {} (level {}): {}, {} pts{}{}",
+                    &g.id[..8.min(g.id.len())],
+                    g.course,
+                    g.level,
+                    g.result,
+                    g.score,
+                    match g.opponents.len() {
+                        0 => String::new(),
+                        _ => format!(" vs {}", g.opponents.join(", ")),
+                    },
+                    if g.ranked { "" } else { " [unranked]" }
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// A player's rating series with summary stats, for graphs
+#[derive(Debug, Clone, Serialize)]
+pub struct RatingHistoryView {
+    pub name: String,
+    #[serde(flatten)]
+    pub summary: RatingSummary,
+    pub history: Vec<RatingPoint>,
 }
 
 /// Player session — tracks which game a connected player is in
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerSession {
     pub game_id: Option<Uuid>,
     pub player_index: Option<usize>,
     pub current_level: u32,
+    /// Lobby the player queued through, if any
+    pub lobby: Option<String>,
+    /// The recap of their last game has not been shown yet
+    pub recap_pending: bool,
+    /// When the player last joined a queue
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+    /// Course tags the player asked for when joining the public queue
+    pub tags: TagPreferences,
+    /// Lets a reconnecting TCP client take the player back (`RESUME`)
+    pub resume_token: Uuid,
+    /// Stands for the player in TCP `STEER`, `LOOK` and `STATUS`; handed to
+    /// the client with its join and kept from the agent
+    #[serde(default = "Uuid::new_v4")]
+    pub session_token: Uuid,
+    /// The player crashed out of their last game and may not join again before this
+    #[serde(default)]
+    pub cooldown_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// The public game the player is counting down to starts no later than this
+    #[serde(default)]
+    pub match_starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the player last showed they are still there while queued: joined,
+    /// joined again, waited or asked for their status
+    #[serde(default = "chrono::Utc::now")]
+    pub active_at: chrono::DateTime<chrono::Utc>,
+    /// The player was dropped from the queue for going quiet; their next
+    /// command says so
+    #[serde(default)]
+    pub queue_expired: bool,
+    /// The connection dropped mid-game; the player forfeits if they have not
+    /// resumed within the grace period from then
+    #[serde(default)]
+    pub disconnected_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-/// Central game manager
+/// Finished games kept in memory for the web UI and archived to JSON storage
+const MAX_FINISHED_GAMES: usize = 30;
+
+/// Longest an exhibition game runs before it is ended with no winner
+const EXHIBITION_MAX_TICKS: u32 = 300;
+
+/// Default seconds a queued player may go quiet before losing their slot
+pub const QUEUE_TTL_SECS: u64 = 300;
+
+/// How often the games, queues and lobbies in play are saved for a restart
+const ACTIVE_STATE_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// One room's games: the public queue and lobbies that start them, the
+/// games in play, the finished-game archive and the leaderboard, ratings
+/// and practice records. Players are addressed by name (`join`,
+/// `move_player`, `look`, `game_status`, ...), and every change is
+/// announced on the channel `subscribe` listens to. Something must call
+/// `tick` about once a second for timeouts, bot fill and real-time games.
+/// The settings fields are set right after `new`.
 pub struct GameManager {
-    pub active_games: HashMap<Uuid, Game>,
-    pub finished_games: Vec<WebGameState>,
-    pub leaderboard: HashMap<String, LeaderboardEntry>,
-    pub player_sessions: HashMap<String, PlayerSession>,
-    pub waiting_players: Vec<String>,
-    pub broadcast_tx: broadcast::Sender<String>,
+    pub(crate) active_games: HashMap<Uuid, Game>,
+    pub(crate) finished_games: Vec<WebGameState>,
+    pub(crate) leaderboard: HashMap<String, LeaderboardEntry>,
+    pub(crate) player_sessions: HashMap<String, PlayerSession>,
+    pub(crate) waiting_players: Vec<String>,
+    pub(crate) lobbies: HashMap<String, Lobby>,
+    /// Adaptive bot state per player, persisted across sessions
+    pub(crate) practice: HashMap<String, AdaptiveDifficulty>,
+    /// Rating history per player, persisted to ratings.json
+    pub(crate) ratings: HashMap<String, RatingHistory>,
+    /// Notification endpoints per player, persisted to webhooks.json
+    pub(crate) webhooks: HashMap<String, Webhook>,
+    /// Told of every game that starts or finishes (`--webhook-url`)
+    pub(crate) server_webhooks: Arc<ServerWebhooks>,
+    /// Player webhooks may point at private and loopback addresses
+    /// (`--allow-private-webhooks`)
+    pub allow_private_webhooks: bool,
+    pub(crate) broadcast_tx: broadcast::Sender<BroadcastEvent>,
     pub max_finished_games: usize,
     pub max_leaderboard_size: usize,
-    pub data_dir: PathBuf,
+    pub(crate) data_dir: PathBuf,
+    /// Admin tokens; operator and owner tokens keep custom-rule lobbies ranked
+    pub admin: SharedAdminAuth,
+    /// Name of the room this manager serves
+    pub room: String,
+    /// Seconds a lone player waits in the public queue before bots join (`--bot-fill`)
+    pub bot_fill_secs: Option<u64>,
+    /// Games filled with bots count for the leaderboard (`--count-bots`)
+    pub count_bots: bool,
+    /// Pending rematches of finished games, by the finished game's id
+    pub(crate) rematches: HashMap<Uuid, Rematch>,
+    /// How many of a game's players must ask before a rematch starts (`--rematch-quorum`)
+    pub rematch_quorum: RematchQuorum,
+    /// Seconds a rematch waits for the others to ask (`--rematch-timeout`)
+    pub rematch_timeout_secs: u64,
+    /// Seconds a queued player may go without a sign of life before their
+    /// slot expires (`--queue-ttl`)
+    pub queue_ttl_secs: Option<u64>,
+    /// What a dropped connection does to a player's running game (`--disconnect-policy`)
+    pub disconnect_policy: DisconnectPolicy,
+    /// Seconds a dropped player has to resume under `DisconnectPolicy::Grace`
+    /// (`--disconnect-grace`)
+    pub disconnect_grace_secs: u64,
+    /// Steer delay that evens out players' round trips (`--fairness-delay-ms`)
+    pub fairness_delay: FairnessDelay,
+    /// What happens to players who stop steering
+    pub idle: IdlePolicy,
+    /// Whether a multi-step move is broadcast step by step (`--plan-updates`)
+    pub plan_updates: PlanUpdates,
+    /// Whether new games advance on steers or on the server tick (`--mode`)
+    pub mode: GameMode,
+    /// Match queued players regardless of level, on the lowest level among
+    /// them (`--mixed-levels`)
+    pub mixed_levels: bool,
+    /// Levels apart two queued players may be and still be matched (`--level-tolerance`)
+    pub level_tolerance: u32,
+    /// Queued players a public game needs before it starts (`--min-players`)
+    pub min_players: usize,
+    /// Most players a public game seats, when fewer than the course's own
+    /// limit (`--max-players`)
+    pub max_players_override: Option<usize>,
+    /// Seconds a public game waits for more players once `min_players` are
+    /// queued (`--start-delay`); it starts right away when the course fills
+    pub start_delay_secs: u64,
+    /// Time source for countdowns and other deadlines; the system clock
+    /// unless a test swaps in a manual one
+    pub clock: Clock,
+    /// Largest rating change from one ranked game (`--elo-k-factor`)
+    pub elo_k_factor: f64,
+    /// When more players queue than a game seats, seat those rated within
+    /// this many points of the longest waiter first (`--elo-window`)
+    pub elo_window: Option<u32>,
+    /// Minutes without players before a bot exhibition starts (`--exhibition`)
+    pub exhibition_mins: Option<u64>,
+    /// Base seconds a crashed player waits before rejoining (`--crash-cooldown`)
+    pub crash_cooldown_secs: Option<u64>,
+    /// Seed every random course layout is generated from (`--course-seed`);
+    /// a fresh one per game when unset
+    pub course_seed: Option<u64>,
+    /// Since when no human has been queued or playing
+    quiet_since: chrono::DateTime<chrono::Utc>,
+    /// Index into `all_courses()` of the next exhibition course
+    exhibition_course: usize,
+    /// When the active state was last saved
+    active_saved_at: std::time::Instant,
+    /// Where the leaderboard, finished games and active state are kept
+    storage: SharedStorage,
+    /// `practice.json`, `ratings.json` and `webhooks.json`, written off the lock
+    practice_file: DebouncedFile,
+    ratings_file: DebouncedFile,
+    webhooks_file: DebouncedFile,
 }
 
 impl GameManager {
-    pub fn new(data_dir: impl Into<PathBuf>) -> (Self, broadcast::Receiver<String>) {
+    pub fn new(
+        data_dir: impl Into<PathBuf>,
+        storage: SharedStorage,
+    ) -> (Self, broadcast::Receiver<BroadcastEvent>) {
         let (tx, rx) = broadcast::channel(256);
         let data_dir = data_dir.into();
 
@@ -48,405 +596,4682 @@ impl GameManager {
         let _ = std::fs::create_dir_all(&data_dir);
 
         // Load persisted leaderboard
-        let leaderboard = Self::load_leaderboard(&data_dir);
-        let finished_games = Self::load_finished_games(&data_dir);
+        let leaderboard = storage.load_leaderboard();
+        let finished_games = storage.load_finished_games(MAX_FINISHED_GAMES);
+        let practice = Self::load_practice(&data_dir);
+        let ratings = Self::load_ratings(&data_dir);
+        let webhooks = Self::load_webhooks(&data_dir);
+        let practice_file = DebouncedFile::new(Self::practice_path(&data_dir));
+        let ratings_file = DebouncedFile::new(Self::ratings_path(&data_dir));
+        let webhooks_file = DebouncedFile::new(Self::webhooks_path(&data_dir));
 
-        let manager = GameManager {
+        let mut manager = GameManager {
             active_games: HashMap::new(),
             finished_games,
             leaderboard,
             player_sessions: HashMap::new(),
             waiting_players: Vec::new(),
+            lobbies: HashMap::new(),
+            practice,
+            ratings,
+            webhooks,
+            server_webhooks: Arc::new(ServerWebhooks::default()),
+            allow_private_webhooks: false,
             broadcast_tx: tx,
-            max_finished_games: 30,
+            max_finished_games: MAX_FINISHED_GAMES,
             max_leaderboard_size: 10,
             data_dir,
+            admin: Arc::new(AdminAuth::default()),
+            room: "default".to_string(),
+            bot_fill_secs: None,
+            count_bots: false,
+            rematches: HashMap::new(),
+            rematch_quorum: RematchQuorum::default(),
+            rematch_timeout_secs: 30,
+            queue_ttl_secs: Some(QUEUE_TTL_SECS),
+            disconnect_policy: DisconnectPolicy::default(),
+            disconnect_grace_secs: 30,
+            fairness_delay: FairnessDelay::Off,
+            idle: IdlePolicy::default(),
+            mode: GameMode::default(),
+            plan_updates: PlanUpdates::default(),
+            exhibition_mins: None,
+            crash_cooldown_secs: None,
+            mixed_levels: false,
+            level_tolerance: 0,
+            min_players: 2,
+            max_players_override: None,
+            start_delay_secs: 0,
+            clock: Clock::default(),
+            elo_k_factor: rating::DEFAULT_K_FACTOR,
+            elo_window: None,
+            course_seed: None,
+            quiet_since: chrono::Utc::now(),
+            exhibition_course: 0,
+            active_saved_at: std::time::Instant::now(),
+            storage,
+            practice_file,
+            ratings_file,
+            webhooks_file,
         };
+        manager.load_active_state();
         (manager, rx)
     }
 
-    fn finished_games_path(data_dir: &Path) -> PathBuf {
-        data_dir.join("finished_games.json")
-    }
+    /// Pick up the games, queues and lobbies a previous process left behind.
+    /// State that fails the audit is set aside rather than half-loaded.
+    fn load_active_state(&mut self) {
+        let Some(state) = self.storage.load_active_state() else {
+            return;
+        };
+        self.active_games = state.games.into_iter().map(|g| (g.id, g)).collect();
+        self.player_sessions = state.player_sessions;
+        self.waiting_players = state.waiting_players;
+        self.lobbies = state.lobbies.into_iter().map(|l| (l.code.clone(), l)).collect();
 
-    fn save_finished_games(&self) {
-        let path = Self::finished_games_path(&self.data_dir);
-        match serde_json::to_string_pretty(&self.finished_games) {
-            Ok(json) => {
-                if let Err(e) = std::fs::write(&path, json) {
-                    tracing::error!("Failed to save finished games: {}", e);
-                }
+        let problems = self.audit();
+        if !problems.is_empty() {
+            tracing::error!("Ignoring the saved active state of room {}: {}", self.room, problems.join("; "));
+            self.active_games.clear();
+            self.player_sessions.clear();
+            self.waiting_players.clear();
+            self.lobbies.clear();
+            return;
+        }
+        // The time the server was down does not count against move timeouts
+        let now = chrono::Utc::now();
+        for game in self.active_games.values_mut() {
+            for player in &mut game.players {
+                player.last_move_at = now;
             }
-            Err(e) => tracing::error!("Failed to serialize finished games: {}", e),
+        }
+        if !self.active_games.is_empty() {
+            tracing::info!("Resumed {} active games of room {}", self.active_games.len(), self.room);
         }
     }
 
-    fn load_finished_games(data_dir: &Path) -> Vec<WebGameState> {
-        let path = Self::finished_games_path(data_dir);
-        match std::fs::read_to_string(&path) {
-            Ok(json) => {
-                match serde_json::from_str::<Vec<WebGameState>>(&json) {
-                    Ok(entries) => {
-                        tracing::info!("Loaded {} finished games from {}", entries.len(), path.display());
-                        entries
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to parse finished games: {}", e);
-                        Vec::new()
-                    }
-                }
-            }
-            Err(_) => {
-                tracing::info!("No existing finished games at {}, starting fresh", path.display());
-                Vec::new()
-            }
-        }
+    /// Save what is in play, or clear the saved state when nothing is
+    fn save_active_state(&mut self) {
+        self.active_saved_at = std::time::Instant::now();
+        let idle = self.active_games.is_empty()
+            && self.player_sessions.is_empty()
+            && self.waiting_players.is_empty()
+            && self.lobbies.is_empty();
+        let state = (!idle).then(|| ActiveState {
+            games: self.active_games.values().cloned().collect(),
+            player_sessions: self.player_sessions.clone(),
+            waiting_players: self.waiting_players.clone(),
+            lobbies: self.lobbies.values().cloned().collect(),
+        });
+        self.storage.save_active_state(state.as_ref());
     }
 
-    fn leaderboard_path(data_dir: &Path) -> PathBuf {
-        data_dir.join("leaderboard.json")
+    fn practice_path(data_dir: &Path) -> PathBuf {
+        data_dir.join("practice.json")
     }
 
-    fn load_leaderboard(data_dir: &Path) -> HashMap<String, LeaderboardEntry> {
-        let path = Self::leaderboard_path(data_dir);
-        match std::fs::read_to_string(&path) {
-            Ok(json) => {
-                match serde_json::from_str::<Vec<LeaderboardEntry>>(&json) {
-                    Ok(entries) => {
-                        tracing::info!("Loaded {} leaderboard entries from {}", entries.len(), path.display());
-                        entries.into_iter().map(|e| (e.name.clone(), e)).collect()
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to parse leaderboard: {}", e);
-                        HashMap::new()
-                    }
-                }
-            }
-            Err(_) => {
-                tracing::info!("No existing leaderboard at {}, starting fresh", path.display());
-                HashMap::new()
-            }
+    fn load_practice(data_dir: &Path) -> HashMap<String, AdaptiveDifficulty> {
+        persist::read_json(&Self::practice_path(data_dir), "practice stats").unwrap_or_default()
+    }
+
+    fn save_practice(&self) {
+        match serde_json::to_string_pretty(&self.practice) {
+            Ok(json) => self.practice_file.save(json),
+            Err(e) => tracing::error!("Failed to serialize practice stats: {}", e),
         }
     }
 
-    fn save_leaderboard(&self) {
-        let entries = self.get_leaderboard();
-        let path = Self::leaderboard_path(&self.data_dir);
-        match serde_json::to_string_pretty(&entries) {
-            Ok(json) => {
-                if let Err(e) = std::fs::write(&path, json) {
-                    tracing::error!("Failed to save leaderboard: {}", e);
-                }
-            }
-            Err(e) => tracing::error!("Failed to serialize leaderboard: {}", e),
+    fn ratings_path(data_dir: &Path) -> PathBuf {
+        data_dir.join("ratings.json")
+    }
+
+    fn load_ratings(data_dir: &Path) -> HashMap<String, RatingHistory> {
+        persist::read_json(&Self::ratings_path(data_dir), "rating history").unwrap_or_default()
+    }
+
+    fn save_ratings(&self) {
+        match serde_json::to_string(&self.ratings) {
+            Ok(json) => self.ratings_file.save(json),
+            Err(e) => tracing::error!("Failed to serialize rating history: {}", e),
         }
     }
 
-    /// Register a player and add them to the waiting queue
-    pub fn join(&mut self, name: String) -> Result<String, String> {
-        if self.player_sessions.contains_key(&name) {
-            let session = self.player_sessions.get(&name).unwrap();
-            if let Some(game_id) = session.game_id {
-                if let Some(game) = self.active_games.get(&game_id) {
-                    if game.status != GameStatus::Finished {
-                        return Err(format!(
-                            "Player '{}' is already in an active game.",
-                            name
-                        ));
-                    }
-                }
-            }
+    fn webhooks_path(data_dir: &Path) -> PathBuf {
+        data_dir.join("webhooks.json")
+    }
+
+    fn load_webhooks(data_dir: &Path) -> HashMap<String, Webhook> {
+        persist::read_json(&Self::webhooks_path(data_dir), "webhooks").unwrap_or_default()
+    }
+
+    fn save_webhooks(&self) {
+        match serde_json::to_string(&self.webhooks) {
+            Ok(json) => self.webhooks_file.save(json),
+            Err(e) => tracing::error!("Failed to serialize webhooks: {}", e),
         }
+    }
 
-        if self.waiting_players.contains(&name) {
-            return Err(format!(
-                "The name '{}' is already waiting in the queue! If you are a new player, please pick a DIFFERENT and unique name.",
-                name
+    /// Register or replace a player's webhook. Only the player can: `token`
+    /// must be the resume or session token of their join. The URL has been
+    /// checked to point at a public address already (`webhook::resolve`).
+    pub(crate) fn set_webhook(&mut self, name: &str, req: WebhookRequest) -> Result<String, TronError> {
+        let name = validation::name("name", name)?;
+        req.validate()?;
+        let holds_name = self.player_sessions.get(&name).is_some_and(|s| {
+            constant_time_eq(s.resume_token.to_string().as_bytes(), req.token.trim().as_bytes())
+                || constant_time_eq(s.session_token.to_string().as_bytes(), req.token.trim().as_bytes())
+        });
+        if !holds_name {
+            return Err(TronError::new(
+                ErrorCode::NotAuthorized,
+                format!("'token' must be the resume or session token from a join as '{}'.", name),
             ));
         }
-
-        let level = self
-            .player_sessions
-            .get(&name)
-            .map(|s| s.current_level)
-            .unwrap_or(1);
-
-        self.player_sessions.insert(
+        if !self.webhooks.contains_key(&name) && self.webhooks.len() >= MAX_WEBHOOKS {
+            return Err(TronError::new(
+                ErrorCode::Forbidden,
+                format!("This room already has {} webhooks, the most it keeps.", MAX_WEBHOOKS),
+            ));
+        }
+        self.webhooks.insert(
             name.clone(),
-            PlayerSession {
-                game_id: None,
-                player_index: None,
-                current_level: level,
+            Webhook {
+                url: req.url,
+                secret: req.secret,
+                created_at: chrono::Utc::now(),
             },
         );
+        self.save_webhooks();
+        Ok(format!("Webhook set for '{}'.", name))
+    }
 
-        self.waiting_players.push(name.clone());
-
-        // Try to start a game if we have enough players
-        if self.waiting_players.len() >= 2 {
-            self.try_start_game();
+    /// Remove a player's webhook; takes the secret it was registered with
+    pub(crate) fn remove_webhook(&mut self, name: &str, secret: &str) -> Result<String, TronError> {
+        match self.webhooks.get(name) {
+            None => Err(TronError::new(
+                ErrorCode::PlayerNotFound,
+                format!("'{}' has no webhook.", name),
+            )),
+            Some(webhook) if !constant_time_eq(webhook.secret.as_bytes(), secret.as_bytes()) => Err(TronError::new(
+                ErrorCode::NotAuthorized,
+                "The secret does not match this webhook.",
+            )),
+            Some(_) => {
+                self.webhooks.remove(name);
+                self.save_webhooks();
+                Ok(format!("Webhook removed for '{}'.", name))
+            }
         }
+    }
 
-        let session = self.player_sessions.get(&name).unwrap();
-        if session.game_id.is_some() {
-            return Ok("Joined! The game has STARTED! Call look() immediately to see the grid and decide your first steer() direction.".to_string());
-        }
+    fn save_leaderboard(&self) {
+        self.storage.save_leaderboard(&self.leaderboard);
+    }
 
-        Ok(format!(
-            "Joined! Waiting for opponents... ({} players in queue)",
-            self.waiting_players.len()
-        ))
+    /// Register a player and add them to the waiting queue. Tag preferences
+    /// steer which course the game is played on.
+    pub fn join(&mut self, name: String, tags: TagPreferences) -> Result<String, TronError> {
+        self.enqueue(name, None, tags)
     }
 
-    /// Try to start a game with waiting players
-    fn try_start_game(&mut self) {
-        if self.waiting_players.len() < 2 {
-            return;
+    /// Register a player and add them to a lobby's queue. In team games
+    /// they may ask for a team; it is theirs if it still has room when the
+    /// game starts.
+    pub fn join_lobby(&mut self, code: &str, name: String, team: Option<usize>) -> Result<String, TronError> {
+        let code = code.to_string();
+        let Some(lobby) = self.lobbies.get(&code) else {
+            return Err(TronError::new(ErrorCode::LobbyNotFound, format!("Lobby '{}' not found.", code)));
+        };
+        let team_size = lobby.rules.team_size;
+        if team.is_some() && team_size.is_none() {
+            return Err(TronError::new(
+                ErrorCode::InvalidParams,
+                format!("Lobby {} is not a team game; join without 'team'.", code),
+            ));
         }
+        // Recorded first: the join may start the game right away
+        if let Some(lobby) = self.lobbies.get_mut(&code) {
+            lobby.team_picks.remove(&name);
+            if let Some(team) = team {
+                lobby.team_picks.insert(name.clone(), team);
+            }
+        }
+        let joined = self.enqueue(name.clone(), Some(code.clone()), TagPreferences::default());
+        if joined.is_err()
+            && let Some(lobby) = self.lobbies.get_mut(&code)
+        {
+            lobby.team_picks.remove(&name);
+        }
+        let msg = joined?;
+        match team_size {
+            Some(size) if self.player_sessions.get(&name).is_some_and(|s| s.game_id.is_none()) => Ok(format!(
+                "{}. Team game, {} a side: it starts once all {} seats are taken{}.",
+                msg,
+                size,
+                size * 2,
+                match team {
+                    Some(team) => format!("; you asked for Team {}", team),
+                    None => ", and you join whichever team is short".to_string(),
+                }
+            )),
+            _ => Ok(msg),
+        }
+    }
 
-        // Determine course level (use the minimum level among waiting players)
-        let min_level = self
-            .waiting_players
-            .iter()
-            .filter_map(|name| self.player_sessions.get(name))
-            .map(|s| s.current_level)
-            .min()
-            .unwrap_or(1);
-
-        let course = get_course(min_level);
-        let max = course.max_players.min(self.waiting_players.len());
-
-        let players_for_game: Vec<String> = self.waiting_players.drain(..max).collect();
-
-        let mut game = Game::new(&course);
+    /// Remove a player from the public queue and every lobby queue.
+    /// Returns true if they were queued anywhere.
+    fn remove_from_queues(&mut self, name: &str) -> bool {
+        let before = self.waiting_players.len();
+        self.waiting_players.retain(|n| n != name);
+        let mut removed = self.waiting_players.len() != before;
+        for lobby in self.lobbies.values_mut() {
+            let before = lobby.waiting_players.len();
+            lobby.waiting_players.retain(|n| n != name);
+            lobby.team_picks.remove(name);
+            removed |= lobby.waiting_players.len() != before;
+        }
+        removed
+    }
 
-        for name in &players_for_game {
-            if let Some(idx) = game.add_player(name.clone()) {
+    /// A player's connection went away. Free their queue slot and take back
+    /// their rematch request so the name can rejoin and nobody is matched
+    /// against a player who is gone. A cycle in a running game is dealt with
+    /// by the disconnect policy.
+    pub fn handle_disconnect(&mut self, name: &str) {
+        if self.remove_from_queues(name) {
+            tracing::info!("Removed disconnected player '{}' from the queue", name);
+        }
+        if self.withdraw_rematch(name) {
+            tracing::info!("Withdrew the rematch request of disconnected player '{}'", name);
+        }
+        let playing = self.seat(name).is_ok_and(|(game_id, idx)| {
+            self.active_games
+                .get(&game_id)
+                .is_some_and(|g| g.status == GameStatus::Running && g.players.get(idx).is_some_and(|p| p.in_play()))
+        });
+        if !playing {
+            return;
+        }
+        match self.disconnect_policy {
+            DisconnectPolicy::Keep => {}
+            DisconnectPolicy::Grace => {
                 if let Some(session) = self.player_sessions.get_mut(name) {
-                    session.game_id = Some(game.id);
-                    session.player_index = Some(idx);
+                    session.disconnected_at = Some(self.clock.now());
+                }
+                tracing::info!(
+                    "Player '{}' disconnected mid-game; forfeiting in {}s unless they resume",
+                    name,
+                    self.disconnect_grace_secs
+                );
+            }
+            DisconnectPolicy::Forfeit => {
+                if self.forfeit(name).is_ok() {
+                    tracing::info!("Player '{}' forfeited by disconnecting", name);
                 }
             }
         }
+    }
 
-        game.start();
-
-        let game_id = game.id;
-        self.active_games.insert(game_id, game);
-
-        let _ = self.broadcast_tx.send(serde_json::json!({
-            "type": "game_started",
-            "game_id": game_id.to_string(),
-        }).to_string());
+    /// A dropped player took their name back on a new connection
+    pub fn handle_reconnect(&mut self, name: &str) {
+        if let Some(session) = self.player_sessions.get_mut(name)
+            && session.disconnected_at.take().is_some()
+        {
+            tracing::info!("Player '{}' resumed within the disconnect grace period", name);
+        }
     }
 
-    /// Move a player: steer + advance one step. Returns result message.
-    pub fn move_player(&mut self, player_name: &str, action: SteerAction) -> Result<String, String> {
+    /// Take a player back after their client restarted, given the resume
+    /// token of their original join. Returns a summary of the running game or
+    /// queue slot they are back in, or `None` when their game is over and the
+    /// caller should queue them as a fresh join would.
+    pub fn resume(&mut self, name: &str, token: &str) -> Result<Option<String>, TronError> {
         let session = self
             .player_sessions
-            .get(player_name)
-            .ok_or_else(|| "Player not found. Use join_game first.".to_string())?;
-
-        let game_id = session
-            .game_id
-            .ok_or_else(|| "Not in a game yet. Waiting for opponents.".to_string())?;
-
-        let player_idx = session
-            .player_index
-            .ok_or_else(|| "Player index not set.".to_string())?;
-
-        let game = self
-            .active_games
-            .get_mut(&game_id)
-            .ok_or_else(|| "Game not found.".to_string())?;
-
-        let result = game.move_player(player_idx, action);
-
-        // Broadcast update
-        let _ = self.broadcast_tx.send(serde_json::json!({
-            "type": "game_update",
-            "game": game.to_web_state(),
-        }).to_string());
-
-        // Check if game just finished
-        if game.status == GameStatus::Finished {
-            self.finish_game(game_id);
+            .get_mut(name)
+            .filter(|s| constant_time_eq(s.resume_token.to_string().as_bytes(), token.trim().as_bytes()))
+            .ok_or_else(|| {
+                TronError::new(
+                    ErrorCode::NotAuthorized,
+                    format!("The resume token does not match '{}'. Join without one to start afresh.", name),
+                )
+            })?;
+        session.active_at = self.clock.now();
+        let seat = session.game_id.zip(session.player_index);
+        if let Some(msg) = self.refresh_queue(name) {
+            return Ok(Some(msg));
+        }
+        let Some((game, idx)) = seat
+            .and_then(|(id, idx)| Some((self.active_games.get(&id)?, idx)))
+            .filter(|(g, _)| g.status == GameStatus::Running)
+        else {
+            return Ok(None);
+        };
+        let Some(player) = game.players.get(idx) else {
+            return Ok(None);
+        };
+        let alive = game.players.iter().filter(|p| p.alive).count();
+        let mut summary = format!(
+            "Back in your game on {} (Level {}) at tick {}, {} of {} players still riding. ",
+            game.course_name,
+            game.course_level,
+            game.tick,
+            alive,
+            game.players.len()
+        );
+        if player.alive {
+            summary.push_str(&format!(
+                "You are ALIVE at ({}, {}) heading {}. Call look() now before you steer.",
+                player.x,
+                player.y,
+                player.direction.name()
+            ));
+        } else if let Some(at) = player.respawn_at_tick {
+            summary.push_str(&format!("You CRASHED and respawn at tick {}; call look() then.", at));
+        } else {
+            summary.push_str("You have already CRASHED; call game_status for the result once the game ends.");
         }
+        self.handle_reconnect(name);
+        Ok(Some(summary))
+    }
 
-        Ok(result)
+    /// The player a session token from a TCP join stands for
+    pub fn player_for_token(&self, token: &str) -> Result<String, TronError> {
+        let token = Uuid::parse_str(token.trim()).ok();
+        self.player_sessions
+            .iter()
+            .find(|(_, s)| Some(s.session_token) == token)
+            .map(|(name, _)| name.clone())
+            .ok_or_else(|| {
+                TronError::new(
+                    ErrorCode::NotAuthorized,
+                    "Unknown session token; use the one from your latest join reply.",
+                )
+            })
     }
 
-    /// Get the look view for a player
-    pub fn look(&self, player_name: &str) -> Result<String, String> {
-        let session = self
+    /// Forfeit the players whose disconnect grace period ran out
+    fn forfeit_disconnected(&mut self) {
+        let cutoff = self.clock.now() - chrono::Duration::seconds(self.disconnect_grace_secs as i64);
+        let gone: Vec<String> = self
             .player_sessions
-            .get(player_name)
-            .ok_or_else(|| "Player not found. Use join_game first.".to_string())?;
+            .iter()
+            .filter(|(_, s)| s.disconnected_at.is_some_and(|at| at <= cutoff))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in gone {
+            if let Some(session) = self.player_sessions.get_mut(&name) {
+                session.disconnected_at = None;
+            }
+            if self.forfeit(&name).is_ok() {
+                tracing::info!("Player '{}' did not resume in time and forfeited", name);
+            }
+        }
+    }
 
-        let game_id = session
-            .game_id
-            .ok_or_else(|| "Not in a game yet. Waiting for opponents.".to_string())?;
+    /// A player backs out: out of the queue if still waiting, out of a
+    /// rematch they asked for, or crashed out of their running game as a loss
+    /// while the others play on
+    pub fn leave(&mut self, name: &str) -> Result<String, TronError> {
+        if self.remove_from_queues(name) {
+            if let Some(session) = self.player_sessions.get_mut(name) {
+                session.lobby = None;
+                session.tags = TagPreferences::default();
+            }
+            let _ = self.broadcast_tx.send(BroadcastEvent::PlayerLeft {
+                player: name.to_string(),
+                from: LeftFrom::Queue,
+                game_id: None,
+            });
+            return Ok("Left the queue. Call join_game to queue again.".to_string());
+        }
+        if self.withdraw_rematch(name) {
+            return Ok("Withdrew your rematch request. Call join_game to queue instead.".to_string());
+        }
 
-        let player_idx = session
-            .player_index
-            .ok_or_else(|| "Player index not set.".to_string())?;
+        match self.forfeit(name)? {
+            Forfeited::Voided => {
+                return Ok("You left before anyone moved, so the game is void and counts for nobody. Call join_game to play again.".to_string());
+            }
+            Forfeited::Ended => {
+                return Ok("You forfeited; that ended the game. Call join_game to play again.".to_string());
+            }
+            Forfeited::Continues => {}
+        }
+        Ok("You forfeited; the game counts as a loss and continues for the others. Call join_game to play again.".to_string())
+    }
 
+    /// Crash a player out of their running game as a loss, or void the game
+    /// when nobody has moved yet and it would end
+    fn forfeit(&mut self, name: &str) -> Result<Forfeited, TronError> {
+        let session = self.player_sessions.get(name).ok_or_else(not_joined)?;
+        let (Some(game_id), Some(idx)) = (session.game_id, session.player_index) else {
+            return Err(TronError::new(ErrorCode::NotQueued, "You are not queued or in a game."));
+        };
         let game = self
             .active_games
-            .get(&game_id)
-            .ok_or_else(|| "Game not found.".to_string())?;
-
-        Ok(game.look(player_idx, 7))
+            .get_mut(&game_id)
+            .filter(|g| g.status == GameStatus::Running)
+            .ok_or_else(|| TronError::new(ErrorCode::NotQueued, "Your game is over; there is nothing to leave."))?;
+        if !game.forfeit(idx) {
+            return Err(TronError::new(
+                ErrorCode::PlayerCrashed,
+                "You have already crashed; call game_status for the result.",
+            ));
+        }
+        tracing::info!("Player '{}' forfeited game {}", name, game_id);
+        let _ = self.broadcast_tx.send(BroadcastEvent::PlayerLeft {
+            player: name.to_string(),
+            from: LeftFrom::Game,
+            game_id: Some(game_id.to_string()),
+        });
+        let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+            game: Box::new(game.to_web_state()),
+        });
+        let outcome = match (game.status, game.aborted) {
+            (GameStatus::Finished, true) => Forfeited::Voided,
+            (GameStatus::Finished, false) => Forfeited::Ended,
+            _ => Forfeited::Continues,
+        };
+        if outcome != Forfeited::Continues {
+            self.finish_game(game_id);
+        }
+        Ok(outcome)
     }
 
-    /// Get game status for a player
-    pub fn game_status(&self, player_name: &str) -> Result<String, String> {
-        let session = self
-            .player_sessions
-            .get(player_name)
-            .ok_or_else(|| "Player not found. Use join_game first.".to_string())?;
+    fn is_waiting(&self, name: &str) -> bool {
+        self.waiting_players.iter().any(|n| n == name)
+            || self
+                .lobbies
+                .values()
+                .any(|l| l.waiting_players.iter().any(|n| n == name))
+    }
 
-        if session.game_id.is_none() {
-            return Ok(format!(
-                "Status: WAITING for game to start. {} players in queue.",
-                self.waiting_players.len()
+    /// Reject a join from a player who is already playing or queued
+    fn check_can_join(&self, name: &str) -> Result<(), TronError> {
+        if let Some(session) = self.player_sessions.get(name)
+            && let Some(game_id) = session.game_id
+            && let Some(game) = self.active_games.get(&game_id)
+            && game.status != GameStatus::Finished
+        {
+            return Err(TronError::new(
+                ErrorCode::NameInUse,
+                format!(
+                    "Player '{}' is already in an active game. If that is you, pass the resume_token from your original join to take it back.",
+                    name
+                ),
             ));
         }
 
-        let game_id = session.game_id.unwrap();
-        let player_idx = session.player_index.unwrap_or(0);
+        if self.is_waiting(name) {
+            return Err(TronError::new(
+                ErrorCode::NameInUse,
+                format!(
+                    "The name '{}' is already waiting in the queue! If you are a new player, please pick a DIFFERENT and unique name.",
+                    name
+                ),
+            ));
+        }
 
-        // Check active games first
-        if let Some(game) = self.active_games.get(&game_id) {
-            return Ok(self.format_status(game, player_idx));
+        if let Some(secs) = self.cooldown_left(name) {
+            return Err(TronError::new(
+                ErrorCode::Cooldown,
+                format!("'{}' crashed out of their last game and can join again in {}s.", name, secs),
+            ));
         }
+        Ok(())
+    }
 
-        // Check finished games
-        if let Some(finished) = self
-            .finished_games
-            .iter()
-            .find(|g| g.id == game_id.to_string())
-        {
-            let mut lines = vec![format!("Status: FINISHED")];
-            if let Some(winner_idx) = finished.winner {
-                if let Some(wp) = finished.players.get(winner_idx) {
-                    lines.push(format!("Winner: {}", wp.name));
+    /// Seconds until a crashed player may join again, if they still have to wait
+    fn cooldown_left(&self, name: &str) -> Option<i64> {
+        let until = self.player_sessions.get(name)?.cooldown_until?;
+        // Round up so "0s" is never reported while the player is still held back
+        let left_ms = (until - chrono::Utc::now()).num_milliseconds();
+        (left_ms > 0).then(|| (left_ms + 999) / 1000)
+    }
+
+    fn player_level(&self, name: &str) -> u32 {
+        self.player_sessions
+            .get(name)
+            .map(|s| s.current_level)
+            .unwrap_or(1)
+    }
+
+    /// Start a practice game against a server-side bot right away.
+    /// Practice games are unranked.
+    pub fn join_vs_bot(&mut self, name: String, difficulty: Difficulty) -> Result<String, TronError> {
+        self.check_can_join(&name)?;
+        self.end_exhibitions();
+        METRICS.player_joined(&self.room);
+
+        let level = self.player_level(&name);
+        self.player_sessions.insert(
+            name.clone(),
+            PlayerSession {
+                game_id: None,
+                player_index: None,
+                current_level: level,
+                lobby: None,
+                recap_pending: false,
+                queued_at: self.clock.now(),
+                tags: TagPreferences::default(),
+                resume_token: Uuid::new_v4(),
+                session_token: Uuid::new_v4(),
+                cooldown_until: None,
+                match_starts_at: None,
+                active_at: self.clock.now(),
+                queue_expired: false,
+                disconnected_at: None,
+            },
+        );
+
+        let skill = self
+            .practice
+            .get(&name)
+            .map(|p| p.skill)
+            .unwrap_or_else(|| AdaptiveDifficulty::default().skill);
+        let params = BotParams::for_difficulty(difficulty, skill);
+        // Parentheses are not allowed in player names, so this never collides
+        let bot_name = format!("Bot ({})", difficulty.name());
+
+        let course = self.course_for_game(level);
+        let rules = course.default_rules();
+        self.launch_game(&course, rules, vec![name], vec![(bot_name.clone(), params)], true, None)?;
+
+        Ok(format!(
+            "Practice game STARTED against {} (search depth {}) on {} (Level {}). Unranked. The bot moves each time you steer. Call look() now.",
+            bot_name,
+            params.depth,
+            course.name,
+            level
+        ))
+    }
+
+    fn enqueue(
+        &mut self,
+        name: String,
+        lobby_code: Option<String>,
+        tags: TagPreferences,
+    ) -> Result<String, TronError> {
+        self.check_can_join(&name)?;
+        self.end_exhibitions();
+        METRICS.player_joined(&self.room);
+
+        let level = self.player_level(&name);
+
+        self.player_sessions.insert(
+            name.clone(),
+            PlayerSession {
+                game_id: None,
+                player_index: None,
+                current_level: level,
+                lobby: lobby_code.clone(),
+                recap_pending: false,
+                queued_at: self.clock.now(),
+                tags,
+                resume_token: Uuid::new_v4(),
+                session_token: Uuid::new_v4(),
+                cooldown_until: None,
+                match_starts_at: None,
+                active_at: self.clock.now(),
+                queue_expired: false,
+                disconnected_at: None,
+            },
+        );
+
+        // Try to start a game if we have enough players
+        let queued = match &lobby_code {
+            Some(code) => {
+                // join_lobby checked the code before getting here
+                if let Some(lobby) = self.lobbies.get_mut(code) {
+                    lobby.waiting_players.push(name.clone());
                 }
-            } else {
-                lines.push("Result: DRAW (everyone crashed)".to_string());
+                self.try_start_lobby_game(code);
+                self.lobbies.get(code).map_or(0, |l| l.waiting_players.len())
             }
-            if let Some(pp) = finished.players.get(player_idx) {
-                lines.push(format!("Your score: {}", pp.score));
+            None => {
+                self.waiting_players.push(name.clone());
+                self.try_start_game();
+                self.waiting_players.len()
             }
-            return Ok(lines.join("\n"));
+        };
+
+        if self.player_sessions.get(&name).is_some_and(|s| s.game_id.is_some()) {
+            return Ok("Joined! The game has STARTED! Call look() immediately to see the grid and decide your first steer() direction.".to_string());
         }
 
-        Ok("Game not found.".to_string())
+        match lobby_code {
+            Some(code) => Ok(format!(
+                "Joined lobby {}! Waiting for opponents... ({} players in lobby)",
+                code, queued
+            )),
+            None => {
+                let mut waiting = if self.mixed_levels {
+                    format!("Joined! Waiting for opponents... ({} players in queue)", queued)
+                } else {
+                    format!(
+                        "Joined! Waiting for opponents at {}... ({} players in queue, {} in your bracket)",
+                        self.bracket_label(level),
+                        queued,
+                        self.bracket_of(&name).len()
+                    )
+                };
+                if let Some(at) = self.player_sessions.get(&name).and_then(|s| s.match_starts_at) {
+                    let secs = (at - self.clock.now()).num_seconds().max(0);
+                    waiting.push_str(&format!(". The game starts in {}s unless it fills up sooner", secs));
+                }
+                match self.bot_fill_secs {
+                    Some(secs) => Ok(format!(
+                        "{}. If nobody else joins within {}s, a bot will fill the game.",
+                        waiting, secs
+                    )),
+                    None => Ok(waiting),
+                }
+            }
+        }
     }
 
-    fn format_status(&self, game: &Game, player_idx: usize) -> String {
-        let mut lines = Vec::new();
-        lines.push(format!("Status: {:?}", game.status));
-        lines.push(format!(
-            "Course: {} (Level {})",
-            game.course_name, game.course_level
-        ));
-        lines.push(format!("Tick: {}", game.tick));
+    /// Periodic housekeeping, run once a second by the server tick loop
+    pub fn tick(&mut self) {
+        self.tick_all();
+        if self.start_delay_secs > 0 {
+            self.try_start_game();
+        }
+        self.settle_rematches();
+        self.expire_queue();
+        self.forfeit_disconnected();
+        self.fill_with_bots();
+        self.enforce_idle();
+        self.run_exhibition();
+        if self.active_saved_at.elapsed() >= ACTIVE_STATE_SAVE_INTERVAL {
+            self.save_active_state();
+        }
+    }
 
-        let alive = game.players.iter().filter(|p| p.alive).count();
-        lines.push(format!("Players alive: {}/{}", alive, game.players.len()));
+    /// Advance every running real-time game one step and broadcast it.
+    /// Steers in these games only queue a turn; this is where cycles move.
+    fn tick_all(&mut self) {
+        let mut finished = Vec::new();
+        for (game_id, game) in &mut self.active_games {
+            // Exhibitions are stepped by run_exhibition
+            if !game.realtime || game.exhibition || game.status != GameStatus::Running {
+                continue;
+            }
+            bot::queue_turn(game);
+            game.advance();
+            let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+                game: Box::new(game.to_web_state()),
+            });
+            // Every cycle still riding gets the next window, steered or not
+            let players: Vec<String> = game
+                .players
+                .iter()
+                .filter(|p| game.status == GameStatus::Running && p.alive)
+                .map(|p| p.name.clone())
+                .collect();
+            if !players.is_empty() {
+                let _ = self.broadcast_tx.send(BroadcastEvent::MoveWindow {
+                    game_id: game_id.to_string(),
+                    tick: game.tick,
+                    players,
+                });
+            }
+            if game.status == GameStatus::Finished {
+                finished.push(*game_id);
+            }
+        }
+        for game_id in finished {
+            self.finish_game(game_id);
+        }
+    }
 
-        if let Some(p) = game.players.get(player_idx) {
+    /// While nobody is around for `exhibition_mins`, keep a featured
+    /// bot-vs-bot game running (one step per tick) on a rotating course
+    fn run_exhibition(&mut self) {
+        let Some(mins) = self.exhibition_mins else {
+            return;
+        };
+        let now = self.clock.now();
+        let busy = !self.waiting_players.is_empty()
+            || self.lobbies.values().any(|l| !l.waiting_players.is_empty())
+            || self.active_games.values().any(|g| !g.exhibition);
+        if busy {
+            self.quiet_since = now;
+            return;
+        }
+
+        let mut finished = Vec::new();
+        for (game_id, game) in &mut self.active_games {
+            bot::play_turn(game);
+            // Bots on courses with fading trails could circle forever
+            if game.tick >= EXHIBITION_MAX_TICKS {
+                game.end_now();
+            }
+            let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+                game: Box::new(game.to_web_state()),
+            });
+            if game.status == GameStatus::Finished {
+                finished.push(*game_id);
+            }
+        }
+        let running = self.active_games.len() > finished.len();
+        for game_id in finished {
+            self.finish_game(game_id);
+        }
+        if running || now - self.quiet_since < chrono::Duration::minutes(mins as i64) {
+            return;
+        }
+
+        let courses = all_courses();
+        let course = self.course_for_game(courses[self.exhibition_course % courses.len()].level);
+        self.exhibition_course += 1;
+        let bots = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+            .into_iter()
+            .take(course.max_players.clamp(2, 3))
+            .map(|d| (format!("Bot ({})", d.name()), BotParams::for_difficulty(d, 0.5)))
+            .collect();
+        let game_id = match self.launch_game(&course, course.default_rules(), Vec::new(), bots, true, None) {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("No exhibition game: {}", e.message);
+                return;
+            }
+        };
+        if let Some(game) = self.active_games.get_mut(&game_id) {
+            game.exhibition = true;
+            game.featured = true;
+        }
+        tracing::info!("Started exhibition game {} on {}", game_id, course.name);
+    }
+
+    /// A human wants to play: end exhibition games so they don't compete
+    /// for attention with the real match
+    fn end_exhibitions(&mut self) {
+        self.quiet_since = self.clock.now();
+        let ids: Vec<Uuid> = self
+            .active_games
+            .values()
+            .filter(|g| g.exhibition)
+            .map(|g| g.id)
+            .collect();
+        for game_id in ids {
+            if let Some(game) = self.active_games.get_mut(&game_id) {
+                game.aborted = true;
+                game.end_now();
+            }
+            tracing::info!("Ended exhibition game {} for a joining player", game_id);
+            self.finish_game(game_id);
+        }
+    }
+
+    /// Apply the idle policy to running games, letting bots answer any
+    /// forced moves
+    fn enforce_idle(&mut self) {
+        let policy = self.idle;
+        let mut finished = Vec::new();
+        for (game_id, game) in &mut self.active_games {
+            let acted = game.enforce_idle(policy);
+            if acted.is_empty() {
+                continue;
+            }
+            tracing::info!("Idle policy acted on {} in game {}", acted.join(", "), game_id);
+            bot::play_turn(game);
+            let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+                game: Box::new(game.to_web_state()),
+            });
+            if game.status == GameStatus::Finished {
+                finished.push(*game_id);
+            }
+        }
+        for game_id in finished {
+            self.finish_game(game_id);
+        }
+    }
+
+    /// Drop queued players who have shown no sign of life for `queue_ttl_secs`,
+    /// so the queue counts only players who will move when matched
+    fn expire_queue(&mut self) {
+        let Some(ttl) = self.queue_ttl_secs else {
+            return;
+        };
+        let now = self.clock.now();
+        let queued = self
+            .waiting_players
+            .iter()
+            .chain(self.lobbies.values().flat_map(|l| &l.waiting_players));
+        let expired: Vec<String> = queued
+            .filter(|name| {
+                self.player_sessions
+                    .get(*name)
+                    .is_some_and(|s| now - s.active_at >= chrono::Duration::seconds(ttl as i64))
+            })
+            .cloned()
+            .collect();
+        for name in expired {
+            self.remove_from_queues(&name);
+            let lobby = self.player_sessions.get_mut(&name).and_then(|session| {
+                session.queue_expired = true;
+                session.match_starts_at = None;
+                session.lobby.take()
+            });
+            tracing::info!("Queue slot of '{}' expired after {}s without activity", name, ttl);
+            let _ = self.broadcast_tx.send(BroadcastEvent::QueueExpired { player: name, lobby });
+        }
+    }
+
+    /// A queued player joined again under the same name: keep their place and
+    /// refresh their slot. None when they are not queued.
+    pub fn refresh_queue(&mut self, name: &str) -> Option<String> {
+        if !self.is_waiting(name) {
+            return None;
+        }
+        let session = self.player_sessions.get_mut(name)?;
+        session.active_at = self.clock.now();
+        Some(format!(
+            "Still queued; your slot was refreshed ({} players in queue). Call wait_for_game_start to wait for your game.",
+            self.queue_len(name)
+        ))
+    }
+
+    /// Start a game for players whose bracket of the public queue has been
+    /// short of `min_players` for longer than `bot_fill_secs`, filling the
+    /// empty seats with bots. Bot-filled games are unranked unless
+    /// `count_bots` is set.
+    fn fill_with_bots(&mut self) {
+        let Some(secs) = self.bot_fill_secs else {
+            return;
+        };
+        let now = self.clock.now();
+        let short = self.waiting_players.iter().find_map(|name| {
+            let bracket = self.bracket_of(name);
+            let waited = now - self.player_sessions.get(name)?.queued_at;
+            (bracket.len() < self.min_players && waited >= chrono::Duration::seconds(secs as i64))
+                .then_some((bracket, waited))
+        });
+        let Some((players, waited)) = short else {
+            return;
+        };
+
+        let (course, note) = self.bracket_course(&players);
+        let course = self.course_for_game(course.level);
+        let seats = self.seats(&course);
+        let Some(missing) = self.min_players.min(seats).checked_sub(players.len()).filter(|&n| n > 0) else {
+            return;
+        };
+        tracing::info!(
+            "Filling game for {} with {} bot(s) after {}s",
+            players.join(", "),
+            missing,
+            waited.num_seconds()
+        );
+
+        let rules = course.default_rules();
+        let params = BotParams::for_difficulty(Difficulty::Medium, 0.5);
+        let bots = (1..=missing)
+            .map(|n| match missing {
+                1 => (format!("Bot ({})", Difficulty::Medium.name()), params),
+                _ => (format!("Bot {} ({})", n, Difficulty::Medium.name()), params),
+            })
+            .collect();
+        let game_id = match self.launch_game(&course, rules, players.clone(), bots, !self.count_bots, None) {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("Could not fill a game: {}", e.message);
+                return;
+            }
+        };
+        self.waiting_players.retain(|n| !players.contains(n));
+        if let Some(game) = self.active_games.get_mut(&game_id) {
+            game.course_note = note;
+        }
+    }
+
+    /// Create a lobby on a course, optionally overriding its default rules.
+    /// Custom rules make the lobby unranked unless an operator token is supplied.
+    pub fn create_lobby(
+        &mut self,
+        level: Option<u32>,
+        overrides: Option<RulesOverride>,
+        admin_token: Option<&str>,
+        public: bool,
+        tags: &TagPreferences,
+    ) -> Result<String, TronError> {
+        let is_admin = admin_token
+            .and_then(|token| self.admin.resolve(token))
+            .is_some_and(|role| role >= Role::Operator);
+        self.open_lobby(level, overrides, is_admin, public, tags)
+            .map(|(_, msg)| msg)
+    }
+
+    /// Create a lobby and return its code with the creation message. Custom
+    /// rules keep it ranked only when `is_admin`.
+    pub fn open_lobby(
+        &mut self,
+        level: Option<u32>,
+        overrides: Option<RulesOverride>,
+        is_admin: bool,
+        public: bool,
+        tags: &TagPreferences,
+    ) -> Result<(String, String), TronError> {
+        let rules_on = |course: &Course| match &overrides {
+            Some(o) => course.default_rules().overlay(o),
+            None => course.default_rules(),
+        };
+        let registry = CourseRegistry::current();
+        let course = match level {
+            Some(level) => {
+                let course = registry.get_by_level(level).cloned().ok_or_else(|| {
+                    TronError::new(
+                        ErrorCode::InvalidParams,
+                        format!("There is no level {}; levels run from 1 to {}.", level, registry.max_level()),
+                    )
+                })?;
+                if !tags.matches(&course.tags_with(&rules_on(&course))) {
+                    return Err(TronError::new(
+                        ErrorCode::NoMatchingCourse,
+                        format!(
+                            "{} (level {}) does not match the requested tags ({}).",
+                            course.name,
+                            level,
+                            tags.describe()
+                        ),
+                    ));
+                }
+                course
+            }
+            None => registry
+                .list()
+                .iter()
+                .map(|(c, _)| c)
+                .find(|c| tags.matches(&c.tags_with(&rules_on(c))))
+                .cloned()
+                .ok_or_else(|| {
+                    TronError::new(
+                        ErrorCode::NoMatchingCourse,
+                        format!("No course matches the requested tags ({}).", tags.describe()),
+                    )
+                })?,
+        };
+        let level = course.level;
+        let defaults = course.default_rules();
+        let rules = rules_on(&course);
+        rules.validate(&course)?;
+        let rules_diff = rules.diff(&defaults);
+        let unranked = !rules_diff.is_empty() && !is_admin;
+
+        let code = lobby::generate_code(|c| self.lobbies.contains_key(c));
+        self.lobbies.insert(
+            code.clone(),
+            Lobby {
+                code: code.clone(),
+                course_name: course.name.clone(),
+                course_level: level,
+                tags: course.tags_with(&rules),
+                rules,
+                rules_diff: rules_diff.clone(),
+                unranked,
+                public,
+                waiting_players: Vec::new(),
+                team_picks: HashMap::new(),
+                bots: Vec::new(),
+                featured: false,
+                created_at: chrono::Utc::now(),
+            },
+        );
+
+        let mut lines = vec![format!(
+            "Lobby {} created on {} (Level {}).",
+            code, course.name, level
+        )];
+        lines.push(format!("Tags: {}.", self.lobbies[&code].tags.join(", ")));
+        if rules_diff.is_empty() {
+            lines.push("Rules: course defaults.".to_string());
+        } else {
+            lines.push("Rules changed from course defaults:".to_string());
+            lines.extend(rules_diff.iter().map(|d| format!("  {}", d)));
+        }
+        if unranked {
+            lines.push("Custom rules: games in this lobby are UNRANKED.".to_string());
+        }
+        if let Some(size) = self.lobbies[&code].rules.team_size {
             lines.push(format!(
-                "You: {} at ({}, {}) heading {} — {}",
-                p.name,
-                p.x,
-                p.y,
-                p.direction.name(),
-                if p.alive { "ALIVE" } else { "CRASHED" }
+                "Team game: two teams of {}. Players may pick one with team=1 or team=2; the rest are balanced.",
+                size
             ));
-            lines.push(format!("Distance: {}", p.distance_traveled));
         }
+        lines.push(format!(
+            "Players join with join_game(name, lobby=\"{}\", room=\"{}\") or via the link /rooms/{}/join/{}.",
+            code, self.room, self.room, code
+        ));
+        if public {
+            lines.push("This lobby is PUBLIC and listed at /api/lobbies.".to_string());
+        }
+        Ok((code, lines.join("\n")))
+    }
 
-        if game.status == GameStatus::Finished {
-            if let Some(winner_idx) = game.winner {
-                let winner = &game.players[winner_idx];
-                lines.push(format!("Winner: {} (score: {})", winner.name, winner.score));
-                if winner_idx == player_idx {
-                    lines.push("Congratulations! You won! Use join_game to play the next level.".to_string());
+    /// Seat a server bot in every game a lobby starts. At least one seat is
+    /// kept for a human, since bots only move when a player steers. Bots make
+    /// the lobby unranked unless `count_bots` is set.
+    pub fn add_lobby_bot(&mut self, code: &str, difficulty: Difficulty) -> Result<String, TronError> {
+        let count_bots = self.count_bots;
+        let lobby = self
+            .lobbies
+            .get_mut(code)
+            .ok_or_else(|| TronError::new(ErrorCode::LobbyNotFound, format!("Lobby '{}' not found.", code)))?;
+        let max = lobby.seats(get_course(lobby.course_level).max_players);
+        if lobby.bots.len() + 1 >= max {
+            return Err(TronError::new(
+                ErrorCode::LobbyFull,
+                format!(
+                    "Lobby {} has no seat left for a bot ({} players max, one kept for a human).",
+                    code, max
+                ),
+            ));
+        }
+        let name = format!("Bot {} ({})", lobby.bots.len() + 1, difficulty.name());
+        lobby.bots.push((name.clone(), BotParams::for_difficulty(difficulty, 0.5)));
+        if !count_bots {
+            lobby.unranked = true;
+        }
+        Ok(format!("{} added to lobby {}.", name, code))
+    }
+
+    /// Overlay more rule changes on a lobby. Only operators get here, so the
+    /// lobby's ranked status is left alone.
+    pub fn set_lobby_rules(&mut self, code: &str, overrides: &RulesOverride) -> Result<String, TronError> {
+        let lobby = self
+            .lobbies
+            .get_mut(code)
+            .ok_or_else(|| TronError::new(ErrorCode::LobbyNotFound, format!("Lobby '{}' not found.", code)))?;
+        let course = get_course(lobby.course_level);
+        let rules = lobby.rules.overlay(overrides);
+        rules.validate(&course)?;
+        lobby.rules_diff = rules.diff(&course.default_rules());
+        lobby.tags = course.tags_with(&rules);
+        lobby.rules = rules;
+        if lobby.rules_diff.is_empty() {
+            Ok(format!("Lobby {} rules: course defaults.", code))
+        } else {
+            Ok(format!("Lobby {} rules: {}.", code, lobby.rules_diff.join(", ")))
+        }
+    }
+
+    /// Feature a lobby: its games are listed first in the web UI. Only one
+    /// lobby is featured at a time.
+    pub fn feature_lobby(&mut self, code: &str) -> Result<String, TronError> {
+        if !self.lobbies.contains_key(code) {
+            return Err(TronError::new(ErrorCode::LobbyNotFound, format!("Lobby '{}' not found.", code)));
+        }
+        for lobby in self.lobbies.values_mut() {
+            lobby.featured = lobby.code == code;
+        }
+        Ok(format!("Lobby {} is featured.", code))
+    }
+
+    /// Apply admin operations all-or-nothing. Every step is checked first;
+    /// then they run in order against a snapshot of the lobbies, which is
+    /// restored if any step fails. Lobby games only start once the whole
+    /// batch has gone through.
+    pub(crate) fn run_batch(&mut self, steps: Vec<BatchOp>) -> Result<BatchReport, BatchFailure> {
+        if steps.is_empty() || steps.len() > MAX_STEPS {
+            return Err(BatchFailure::new(0, "batch", format!("A batch takes 1-{} steps.", MAX_STEPS)));
+        }
+        let mut aliases = std::collections::HashSet::new();
+        for (i, step) in steps.iter().enumerate() {
+            let fail = |error: String| BatchFailure::new(i, step.name(), error);
+            step.validate().map_err(fail)?;
+            if let BatchOp::CreateLobby { alias: Some(alias), .. } = step
+                && !aliases.insert(alias.trim())
+            {
+                return Err(fail(format!("Alias '{}' is used twice.", alias.trim())));
+            }
+        }
+
+        let snapshot = self.lobbies.clone();
+        let mut report = BatchReport {
+            results: Vec::new(),
+            lobbies: HashMap::new(),
+        };
+        let mut touched = Vec::new();
+        for (i, step) in steps.into_iter().enumerate() {
+            let op = step.name();
+            match self.apply_batch_step(step, &mut report.lobbies, &mut touched) {
+                Ok(msg) => report.results.push(msg),
+                Err(error) => {
+                    self.lobbies = snapshot;
+                    return Err(BatchFailure::new(i, op, error));
                 }
-            } else {
-                lines.push("Result: DRAW (everyone crashed)".to_string());
             }
         }
+        for code in touched {
+            self.try_start_lobby_game(&code);
+        }
+        Ok(report)
+    }
+
+    fn apply_batch_step(
+        &mut self,
+        step: BatchOp,
+        aliases: &mut HashMap<String, String>,
+        touched: &mut Vec<String>,
+    ) -> Result<String, TronError> {
+        if let BatchOp::CreateLobby {
+            alias,
+            level,
+            rules,
+            public,
+            prefer_tags,
+            avoid_tags,
+        } = step
+        {
+            let tags = validation::tag_preferences(prefer_tags.as_deref(), avoid_tags.as_deref())?;
+            let (code, msg) = self.open_lobby(level, rules, true, public, &tags)?;
+            if let Some(alias) = alias {
+                aliases.insert(alias.trim().to_string(), code.clone());
+            }
+            touched.push(code);
+            return Ok(msg);
+        }
 
-        lines.join("\n")
+        let (lobby, result) = match step {
+            BatchOp::CreateLobby { .. } => unreachable!("create_lobby is handled above"),
+            BatchOp::AddBot { lobby, difficulty } => {
+                let code = batch::resolve_lobby(&lobby, aliases)?;
+                (code.clone(), self.add_lobby_bot(&code, difficulty))
+            }
+            BatchOp::SetRules { lobby, rules } => {
+                let code = batch::resolve_lobby(&lobby, aliases)?;
+                (code.clone(), self.set_lobby_rules(&code, &rules))
+            }
+            BatchOp::Feature { lobby } => {
+                let code = batch::resolve_lobby(&lobby, aliases)?;
+                (code.clone(), self.feature_lobby(&code))
+            }
+            BatchOp::SetSpeed { lobby, move_timeout_secs } => {
+                let code = batch::resolve_lobby(&lobby, aliases)?;
+                (code.clone(), self.set_lobby_rules(&code, &batch::speed_rules(move_timeout_secs)))
+            }
+        };
+        if !touched.contains(&lobby) {
+            touched.push(lobby);
+        }
+        result
     }
 
-    /// Handle a game that just finished — update leaderboard, broadcast, archive
-    fn finish_game(&mut self, game_id: Uuid) {
-        if let Some(game) = self.active_games.remove(&game_id) {
-            // Update leaderboard
-            for (i, player) in game.players.iter().enumerate() {
-                let entry = self
-                    .leaderboard
-                    .entry(player.name.clone())
-                    .or_insert_with(|| LeaderboardEntry {
-                        name: player.name.clone(),
-                        ..Default::default()
-                    });
-                entry.games_played += 1;
+    /// Start public games while some bracket of queued players is ready for
+    /// one, then start or call off the countdowns of the rest. Brackets
+    /// anchored on the longest waiters are tried first.
+    fn try_start_game(&mut self) {
+        let now = self.clock.now();
+        while let Some(bracket) = self
+            .waiting_players
+            .iter()
+            .map(|name| self.bracket_of(name))
+            .find(|bracket| self.bracket_ready(bracket, now))
+        {
+            if !self.start_queued_game(&bracket) {
+                break;
+            }
+        }
+        self.update_countdowns(now);
+    }
 
-                if game.winner == Some(i) {
-                    entry.wins += 1;
-                    entry.total_points += player.score;
-                    if game.course_level >= entry.highest_level {
-                        entry.highest_level = game.course_level + 1;
-                    }
+    /// A bracket with `min_players` starts when the course is full, when its
+    /// countdown is over, or right away without a start delay
+    fn bracket_ready(&self, bracket: &[String], now: chrono::DateTime<chrono::Utc>) -> bool {
+        let seats = self.seats(&self.bracket_course(bracket).0);
+        if bracket.len() < self.min_players.min(seats) {
+            return false;
+        }
+        self.start_delay_secs == 0
+            || bracket.len() >= seats
+            || self.countdown_of(bracket).is_some_and(|at| at <= now)
+    }
 
-                    // Advance winner's level
-                    if let Some(session) = self.player_sessions.get_mut(&player.name) {
-                        let max_level = all_courses().len() as u32;
-                        if session.current_level < max_level {
-                            session.current_level += 1;
-                        }
-                    }
-                }
+    /// Give every queued player whose bracket has `min_players` the
+    /// bracket's countdown, starting one if it has none yet, and take it
+    /// away from those whose bracket fell short. Each countdown started or
+    /// joined is announced once.
+    fn update_countdowns(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        if self.start_delay_secs == 0 {
+            return;
+        }
+        let mut announced = Vec::new();
+        for name in self.waiting_players.clone() {
+            let bracket = self.bracket_of(&name);
+            let seats = self.seats(&self.bracket_course(&bracket).0);
+            let starts_at = (bracket.len() >= self.min_players.min(seats)).then(|| {
+                self.countdown_of(&bracket)
+                    .unwrap_or(now + chrono::Duration::seconds(self.start_delay_secs as i64))
+            });
+            let level = bracket.iter().map(|n| self.queued_level(n)).min().unwrap_or(1);
+            let Some(session) = self.player_sessions.get_mut(&name) else {
+                continue;
+            };
+            let joined = session.match_starts_at.is_none();
+            session.match_starts_at = starts_at;
+            if let Some(at) = starts_at.filter(|at| joined && !announced.contains(at)) {
+                announced.push(at);
+                let _ = self.broadcast_tx.send(BroadcastEvent::MatchCountdown {
+                    level,
+                    players: bracket,
+                    starts_in_secs: (at - now).num_seconds().max(0) as u64,
+                });
             }
+        }
+    }
 
-            let web_state = game.to_web_state();
-            let _ = self.broadcast_tx.send(serde_json::json!({
-                "type": "game_finished",
-                "game": &web_state,
-            }).to_string());
+    /// When the countdown of `bracket` runs out: the earliest of its players'
+    fn countdown_of(&self, bracket: &[String]) -> Option<chrono::DateTime<chrono::Utc>> {
+        bracket
+            .iter()
+            .filter_map(|name| self.player_sessions.get(name)?.match_starts_at)
+            .min()
+    }
 
-            self.finished_games.push(web_state);
-            if self.finished_games.len() > self.max_finished_games {
-                self.finished_games.remove(0);
+    /// The course `bracket` would play, on the lowest level among its
+    /// players and suiting their combined tags, before any random layout
+    fn bracket_course(&self, bracket: &[String]) -> (Course, Option<String>) {
+        let sessions: Vec<&PlayerSession> = bracket
+            .iter()
+            .filter_map(|name| self.player_sessions.get(name))
+            .collect();
+        let min_level = sessions.iter().map(|s| s.current_level).min().unwrap_or(1);
+        let prefs = TagPreferences::combine(sessions.iter().map(|s| &s.tags));
+        course::select_course(min_level, &prefs)
+    }
+
+    /// Seats a public game on `course` has, capped by `max_players_override`
+    fn seats(&self, course: &Course) -> usize {
+        self.max_players_override
+            .map_or(course.max_players, |max| max.min(course.max_players))
+    }
+
+    /// Start a game with players from `bracket`, on the lowest level among
+    /// them. False when the game could not be built; everyone stays queued.
+    fn start_queued_game(&mut self, bracket: &[String]) -> bool {
+        let (course, note) = self.bracket_course(bracket);
+        let course = self.course_for_game(course.level);
+
+        let max = self.seats(&course).min(bracket.len());
+
+        let players_for_game = self.pick_queued_players(bracket, max);
+        let rules = course.default_rules();
+        let game_id = match self.launch_game(&course, rules, players_for_game.clone(), Vec::new(), false, None) {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("Could not start a queued game: {}", e.message);
+                return false;
             }
+        };
+        self.waiting_players.retain(|name| !players_for_game.contains(name));
+        if let Some(game) = self.active_games.get_mut(&game_id) {
+            game.course_note = note;
+        }
+        true
+    }
 
-            self.save_leaderboard();
-            self.save_finished_games();
+    /// Queued players who may share a game with `name`, in queue order:
+    /// everyone with `--mixed-levels`, otherwise those whose level is within
+    /// `level_tolerance` of theirs
+    fn bracket_of(&self, name: &str) -> Vec<String> {
+        if self.mixed_levels {
+            return self.waiting_players.clone();
         }
+        let level = self.queued_level(name);
+        self.waiting_players
+            .iter()
+            .filter(|other| self.queued_level(other).abs_diff(level) <= self.level_tolerance)
+            .cloned()
+            .collect()
     }
 
-    /// Get leaderboard sorted by total points
-    pub fn get_leaderboard(&self) -> Vec<LeaderboardEntry> {
-        let mut entries: Vec<LeaderboardEntry> = self.leaderboard.values().cloned().collect();
-        entries.sort_by(|a, b| b.total_points.cmp(&a.total_points));
-        entries.truncate(self.max_leaderboard_size);
-        entries
+    fn queued_level(&self, name: &str) -> u32 {
+        self.player_sessions.get(name).map_or(1, |s| s.current_level)
     }
 
-    /// Get all active games as web states
-    pub fn get_active_games(&self) -> Vec<WebGameState> {
-        self.active_games.values().map(|g| g.to_web_state()).collect()
+    /// The levels a player on `level` is matched across, for the join reply
+    fn bracket_label(&self, level: u32) -> String {
+        let max_level = all_courses().len() as u32;
+        let low = level.saturating_sub(self.level_tolerance).max(1);
+        let high = (level + self.level_tolerance).min(max_level.max(level));
+        if low == high {
+            format!("level {}", level)
+        } else {
+            format!("levels {}-{}", low, high)
+        }
     }
 
-    /// Get finished games
-    pub fn get_finished_games(&self) -> &[WebGameState] {
-        &self.finished_games
+    /// The `seats` players from `bracket` the game starts with. Normally the
+    /// longest waiting; with an Elo window and more players than seats, the
+    /// longest waiter is joined by those rated close to them first, in queue
+    /// order, and the rest of the bracket only fills what is left.
+    fn pick_queued_players(&self, bracket: &[String], seats: usize) -> Vec<String> {
+        let Some(window) = self.elo_window.filter(|_| bracket.len() > seats) else {
+            return bracket[..seats].to_vec();
+        };
+        let first = &bracket[0];
+        let anchor = self.rating_of(first);
+        let (close, far): (Vec<&String>, Vec<&String>) = bracket[1..]
+            .iter()
+            .partition(|name| self.rating_of(name).abs_diff(anchor) <= window);
+        std::iter::once(first)
+            .chain(close)
+            .chain(far)
+            .take(seats)
+            .cloned()
+            .collect()
     }
-}
 
-pub type SharedGameManager = Arc<Mutex<GameManager>>;
+    /// The course a new game on `level` is played on: random layouts come
+    /// from the pinned seed, or a fresh one
+    fn course_for_game(&self, level: u32) -> Course {
+        // Fresh seeds stay below 2^53 so they survive JSON in a browser
+        let seed = self.course_seed.unwrap_or_else(|| rand::random::<u64>() >> 11);
+        get_course_with_seed(level, seed)
+    }
+
+    /// Try to start a game with the players waiting in a lobby
+    fn try_start_lobby_game(&mut self, code: &str) {
+        let Some(level) = self.lobbies.get(code).map(|l| l.course_level) else {
+            return;
+        };
+        let course = self.course_for_game(level);
+        let Some(lobby) = self.lobbies.get_mut(code) else {
+            return;
+        };
+        // Bots fill seats, but somebody has to steer for them to move. Team
+        // games wait for both teams to fill.
+        let seats = lobby.seats(course.max_players);
+        let needed = if lobby.rules.team_size.is_some() { seats } else { 2 };
+        if lobby.waiting_players.is_empty()
+            || lobby.waiting_players.len() + lobby.bots.len() < needed
+        {
+            return;
+        }
+
+        let max = seats
+            .saturating_sub(lobby.bots.len())
+            .min(lobby.waiting_players.len());
+        let players_for_game = lobby.waiting_players[..max].to_vec();
+        let rules = lobby.rules.clone();
+        let bots = lobby.bots.clone();
+        let (unranked, featured) = (lobby.unranked, lobby.featured);
+        let game_id = match self.launch_game(&course, rules, players_for_game, bots, unranked, Some(code.to_string())) {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("Could not start lobby {}'s game: {}", code, e.message);
+                return;
+            }
+        };
+        if let Some(lobby) = self.lobbies.get_mut(code) {
+            for name in lobby.waiting_players.drain(..max) {
+                lobby.team_picks.remove(&name);
+            }
+        }
+        if let Some(game) = self.active_games.get_mut(&game_id) {
+            game.featured = featured;
+        }
+    }
+
+    fn launch_game(
+        &mut self,
+        course: &Course,
+        rules: Ruleset,
+        players: Vec<String>,
+        bots: Vec<(String, BotParams)>,
+        unranked: bool,
+        lobby: Option<String>,
+    ) -> Result<Uuid, TronError> {
+        let mut game = Game::new(course, rules)
+            .map_err(|e| TronError::new(ErrorCode::Internal, format!("Cannot start the game: {}", e)))?;
+        game.unranked = unranked;
+        game.realtime = self.mode == GameMode::Realtime;
+        game.clock = self.clock.clone();
+        game.lobby = lobby;
+
+        for name in &players {
+            if let Some(idx) = game.add_player(name.clone())
+                && let Some(session) = self.player_sessions.get_mut(name)
+            {
+                session.game_id = Some(game.id);
+                session.player_index = Some(idx);
+                session.match_starts_at = None;
+            }
+        }
+        for (name, params) in bots {
+            if let Some(idx) = game.add_player(name) {
+                game.players[idx].bot = Some(params);
+            }
+        }
+        // Team games come from lobbies, where players may have picked a side
+        let picks: Vec<Option<usize>> = game
+            .players
+            .iter()
+            .map(|p| {
+                let lobby = self.lobbies.get(game.lobby.as_ref()?)?;
+                lobby.team_picks.get(&p.name).copied()
+            })
+            .collect();
+        game.assign_teams(&picks);
+
+        game.shuffle_spawns();
+        game.start();
+
+        let game_id = game.id;
+        METRICS.game_started(&self.room);
+        let _ = self.broadcast_tx.send(BroadcastEvent::GameStarted {
+            game_id: game_id.to_string(),
+            game: Box::new(game.to_web_state()),
+        });
+        self.active_games.insert(game_id, game);
+        self.save_active_state();
+        Ok(game_id)
+    }
+
+    /// Game and player index of a player who is in a game
+    fn seat(&self, player_name: &str) -> Result<(Uuid, usize), TronError> {
+        let session = self.player_sessions.get(player_name).ok_or_else(not_joined)?;
+        if session.queue_expired {
+            return Err(queue_expired());
+        }
+        let game_id = session.game_id.ok_or_else(|| {
+            TronError::new(ErrorCode::NotInGame, "Not in a game yet. Waiting for opponents.")
+        })?;
+        let player_idx = session
+            .player_index
+            .ok_or_else(|| TronError::new(ErrorCode::Internal, "Player index not set."))?;
+        Ok((game_id, player_idx))
+    }
+
+    /// Move a player: steer + advance one step. Returns result message.
+    /// A compass heading is turned into the matching turn first.
+    pub fn move_player(&mut self, player_name: &str, steer: Steer) -> Result<String, TronError> {
+        let (game_id, player_idx) = self.seat(player_name)?;
+        let game = self
+            .active_games
+            .get_mut(&game_id)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotFound, "Game not found."))?;
+        let action = steer.resolve(game.players[player_idx].direction)?;
+
+        METRICS.moved(&self.room);
+        if game.realtime {
+            return Ok(game.queue_steer(player_idx, action));
+        }
+        let result = Self::step(game, player_idx, action, self.idle);
+
+        // Broadcast update
+        let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+            game: Box::new(game.to_web_state()),
+        });
+
+        // Check if game just finished
+        if game.status == GameStatus::Finished {
+            self.finish_game(game_id);
+        }
+
+        Ok(result)
+    }
+
+    /// Make several moves for a player in one go, with a numbered line per
+    /// move made. The whole plan is checked for reversals before the first
+    /// move; it stops early when the player crashes or the game ends, and a
+    /// `Repeat` also stops before running into anything.
+    pub fn move_plan(&mut self, player_name: &str, plan: MovePlan) -> Result<String, TronError> {
+        let (game_id, player_idx) = self.seat(player_name)?;
+        let game = self
+            .active_games
+            .get_mut(&game_id)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotFound, "Game not found."))?;
+        if game.realtime {
+            return Err(TronError::new(
+                ErrorCode::InvalidParams,
+                "This is a real-time game: your cycle moves every tick on its own, so steer one turn at a time.",
+            ));
+        }
+
+        let (steers, stop_short) = match plan {
+            MovePlan::Repeat(steer, count) => {
+                let mut steers = vec![steer];
+                steers.resize(count, Steer::Turn(SteerAction::Straight));
+                (steers, true)
+            }
+            MovePlan::List(steers) => (steers, false),
+        };
+        let mut heading = game.players[player_idx].direction;
+        let mut actions = Vec::with_capacity(steers.len());
+        for (i, steer) in steers.iter().enumerate() {
+            let action = steer
+                .resolve(heading)
+                .map_err(|e| TronError::new(ErrorCode::InvalidParams, format!("Move {}: {}", i + 1, e)))?;
+            heading = action.turn(heading);
+            actions.push(action);
+        }
+
+        let combined = self.plan_updates == PlanUpdates::Combined;
+        let mut lines = Vec::new();
+        let mut stopped = None;
+        for (i, action) in actions.iter().enumerate() {
+            if i > 0 {
+                if !game.players[player_idx].alive {
+                    stopped = Some("you crashed".to_string());
+                } else if game.status != GameStatus::Running {
+                    stopped = Some("the game is over".to_string());
+                } else if stop_short && let Some(obstacle) = analysis::blocked_ahead(game, player_idx) {
+                    stopped = Some(format!("{} is right ahead", obstacle));
+                }
+                if stopped.is_some() {
+                    break;
+                }
+            }
+            METRICS.moved(&self.room);
+            lines.push(format!("{}. {}", i + 1, Self::step(game, player_idx, *action, self.idle)));
+            if !combined {
+                let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+                    game: Box::new(game.to_web_state()),
+                });
+            }
+        }
+        if combined {
+            let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+                game: Box::new(game.to_web_state()),
+            });
+        }
+        match stopped {
+            Some(reason) => lines.push(format!("Stopped after {} of {} moves: {}.", lines.len(), actions.len(), reason)),
+            None if actions.len() > 1 => lines.push(format!("Made all {} moves.", actions.len())),
+            None => {}
+        }
+
+        if game.status == GameStatus::Finished {
+            self.finish_game(game_id);
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    /// One turn-based move: the player's step, the bots' answer, then the
+    /// move timeout and idle policy
+    fn step(game: &mut Game, player_idx: usize, action: SteerAction, idle: IdlePolicy) -> String {
+        let result = game.move_player(player_idx, action);
+        bot::play_turn(game);
+        for name in game.enforce_move_timeout() {
+            tracing::info!("Player '{}' timed out in game {}", name, game.id);
+        }
+        let idle = game.enforce_idle(idle);
+        if !idle.is_empty() {
+            tracing::info!("Idle policy acted on {} in game {}", idle.join(", "), game.id);
+        }
+        result
+    }
+
+    /// Record the round trip measured on the player's connection and work out
+    /// how long to hold back their next steer. Players whose round trip was
+    /// never measured count as 0 ms; bots are left out.
+    pub fn fairness_delay(&mut self, player_name: &str, rtt_ms: Option<u64>) -> std::time::Duration {
+        let policy = self.fairness_delay;
+        if policy == FairnessDelay::Off {
+            return std::time::Duration::ZERO;
+        }
+        let Some((game_id, idx)) = self
+            .player_sessions
+            .get(player_name)
+            .and_then(|s| Some((s.game_id?, s.player_index?)))
+        else {
+            return std::time::Duration::ZERO;
+        };
+        let Some(game) = self.active_games.get_mut(&game_id) else {
+            return std::time::Duration::ZERO;
+        };
+        if rtt_ms.is_some() {
+            game.players[idx].latency_ms = rtt_ms;
+        }
+        let own = game.players[idx].latency_ms.unwrap_or(0);
+        let delay = policy.delay_for(
+            own,
+            game.players
+                .iter()
+                .filter(|p| p.bot.is_none())
+                .map(|p| p.latency_ms.unwrap_or(0)),
+        );
+        game.players[idx].fairness_delay_ms = delay.as_millis() as u64;
+        delay
+    }
+
+    /// Ask to abort the player's game. Once every living player has asked
+    /// within the window the game is voided; until then opponents are notified.
+    pub fn request_abort(&mut self, player_name: &str) -> Result<String, TronError> {
+        let (game_id, player_idx) = self.seat(player_name)?;
+        let game = self
+            .active_games
+            .get_mut(&game_id)
+            .filter(|g| g.status == GameStatus::Running)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotRunning, "Game is not running."))?;
+        if !game.players[player_idx].alive {
+            return Err(TronError::new(
+                ErrorCode::PlayerCrashed,
+                "You have crashed; only living players can abort.",
+            ));
+        }
+
+        match game.request_abort(player_idx) {
+            AbortOutcome::Aborted => {
+                self.finish_game(game_id);
+                Ok("Game ABORTED by agreement. It does not count for the leaderboard or levels. You can join_game again.".to_string())
+            }
+            AbortOutcome::Pending(waiting) => {
+                let _ = self.broadcast_tx.send(BroadcastEvent::AbortRequested {
+                    game_id: game_id.to_string(),
+                    player: player_name.to_string(),
+                });
+                Ok(format!(
+                    "Abort requested. The game is voided only if the others also call abort() within {}s (waiting on: {}); otherwise keep playing.",
+                    crate::game::ABORT_WINDOW_SECS,
+                    waiting.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Record a chat message in the player's running game and send it to
+    /// the web UI. The message is tidied and cut by `validation::chat_message`;
+    /// a player who chats again within `CHAT_COOLDOWN_SECS` is turned away.
+    pub fn say(&mut self, player_name: &str, text: &str) -> Result<String, TronError> {
+        let (text, cut) = validation::chat_message("message", text)?;
+        let (game_id, player_idx) = self.seat(player_name)?;
+        let game = self
+            .active_games
+            .get_mut(&game_id)
+            .filter(|g| g.status == GameStatus::Running)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotRunning, "Game is not running."))?;
+        let message = match game.say(player_idx, text) {
+            Ok(message) => message.clone(),
+            Err(wait) => {
+                return Err(TronError::new(
+                    ErrorCode::RateLimited,
+                    format!("You are chatting too fast; say something again in {}s.", wait),
+                ));
+            }
+        };
+        let _ = self.broadcast_tx.send(BroadcastEvent::Chat {
+            game_id: game_id.to_string(),
+            message: game.web_chat(&message),
+        });
+        if cut {
+            return Ok(format!(
+                "Said (cut to {} characters): \"{}\"",
+                validation::MAX_CHAT_LEN,
+                message.text
+            ));
+        }
+        Ok(format!("Said: \"{}\"", message.text))
+    }
+
+    /// A game for a spectator: the one asked for, or the newest running one
+    fn spectated(&self, game_id: Option<Uuid>) -> Result<&Game, TronError> {
+        match game_id {
+            Some(id) => self.active_games.get(&id).ok_or_else(|| {
+                TronError::new(ErrorCode::GameNotFound, format!("No game {} is running.", id))
+            }),
+            None => self
+                .active_games
+                .values()
+                .filter(|g| g.status == GameStatus::Running)
+                .max_by_key(|g| g.created_at)
+                .ok_or_else(|| TronError::new(ErrorCode::GameNotFound, "No game is running right now.")),
+        }
+    }
+
+    /// The recap of a game that has finished, for spectators who ask for it
+    fn finished_view(&self, id: Uuid) -> Option<String> {
+        let game = self.finished_games.iter().find(|g| g.id == id.to_string())?;
+        Some(
+            game.recap
+                .clone()
+                .unwrap_or_else(|| format!("Game {} has finished.", id)),
+        )
+    }
+
+    /// The whole board of a game with everyone's score so far, its recent
+    /// events and chat, for someone not playing in it
+    pub fn spectate(&self, game_id: Option<&str>) -> Result<String, TronError> {
+        let game_id = game_id.map(|id| validation::game_id("game_id", id)).transpose()?;
+        if let Some(id) = game_id
+            && !self.active_games.contains_key(&id)
+            && let Some(over) = self.finished_view(id)
+        {
+            return Ok(over);
+        }
+        let game = self.spectated(game_id)?;
+        let mut lines = analysis::spectator_view(game);
+        let events = analysis::recent_events(game, analysis::FEED_LEN);
+        if !events.is_empty() {
+            lines.push("Recent events:".to_string());
+            lines.extend(events.into_iter().map(|e| format!("  {}", e)));
+        }
+        if !game.chat.is_empty() {
+            let skip = game.chat.len().saturating_sub(CHAT_SHOWN);
+            lines.push("Chat:".to_string());
+            lines.extend(game.chat.iter().skip(skip).map(|m| format!("  {}", game.chat_line(m))));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Like `spectate`, but followed by the events and chat since this
+    /// spectator's last `watch` of the same game instead of the latest few.
+    /// Without a game id it keeps to the game watched last, showing its
+    /// recap once it finishes.
+    pub fn watch(&self, game_id: Option<&str>, seen: &mut SpectatorSession) -> Result<String, TronError> {
+        let asked = game_id.map(|id| validation::game_id("game_id", id)).transpose()?;
+        let mut game_id = asked.or(seen.game_id);
+        if let Some(id) = game_id
+            && !self.active_games.contains_key(&id)
+        {
+            if let Some(over) = self.finished_view(id) {
+                *seen = SpectatorSession::default();
+                return Ok(over);
+            }
+            if asked.is_none() {
+                game_id = None;
+            }
+        }
+        let game = self.spectated(game_id)?;
+        if seen.game_id != Some(game.id) {
+            *seen = SpectatorSession {
+                game_id: Some(game.id),
+                ..SpectatorSession::default()
+            };
+        }
+        let mut lines = analysis::spectator_view(game);
+        let mut news: Vec<String> = game.events[seen.events_seen.min(game.events.len())..]
+            .iter()
+            .filter_map(|e| analysis::describe_event(game, e).map(|line| format!("tick {}: {}.", e.tick, line)))
+            .collect();
+        news.extend(
+            game.chat
+                .iter()
+                .filter(|m| seen.chat_seen.is_none_or(|t| m.at > t))
+                .map(|m| format!("chat: {}", game.chat_line(m))),
+        );
+        seen.events_seen = game.events.len();
+        seen.chat_seen = game.chat.back().map(|m| m.at).or(seen.chat_seen);
+        if news.is_empty() {
+            lines.push("Nothing new since your last watch.".to_string());
+        } else {
+            lines.push("Since your last watch:".to_string());
+            lines.extend(news.into_iter().map(|n| format!("  {}", n)));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Ask to play the game the player just finished again with the same
+    /// opponents. Once the quorum of its players still around have asked,
+    /// the rematch starts on the next level without going through the
+    /// queue. Crash cooldowns do not hold it back: they stop players from
+    /// re-rolling their opponents, and a rematch keeps them.
+    pub fn request_rematch(&mut self, player_name: &str) -> Result<String, TronError> {
+        let session = self.player_sessions.get(player_name).ok_or_else(not_joined)?;
+        if self.is_waiting(player_name) {
+            return Err(TronError::new(
+                ErrorCode::NameInUse,
+                "You are queued for another game; call leave_game first to ask for a rematch.",
+            ));
+        }
+        let game_id = session
+            .game_id
+            .ok_or_else(|| TronError::new(ErrorCode::NoRematch, "You have not finished a game yet."))?;
+        if self.active_games.contains_key(&game_id) {
+            return Err(TronError::new(
+                ErrorCode::NameInUse,
+                "Your game is still running; ask for a rematch once it is over.",
+            ));
+        }
+
+        let mut rematch = match self.rematches.remove(&game_id) {
+            Some(rematch) => rematch,
+            None => self.new_rematch(game_id)?,
+        };
+        if !rematch.requested.iter().any(|n| n == player_name) {
+            rematch.requested.push(player_name.to_string());
+        }
+        let eligible = self.rematch_eligible(&rematch);
+        let requested: Vec<String> = rematch
+            .requested
+            .iter()
+            .filter(|n| eligible.contains(n))
+            .cloned()
+            .collect();
+        let needed = self.rematch_quorum.needed(eligible.len());
+        let (level, secs_left) = (rematch.course_level, rematch.secs_left(self.clock.now()));
+        self.rematches.insert(game_id, rematch);
+        let _ = self.broadcast_tx.send(BroadcastEvent::RematchRequested {
+            game_id: game_id.to_string(),
+            player: player_name.to_string(),
+            requested: requested.clone(),
+            needed,
+        });
+
+        if let Some(new_id) = self.settle_rematch(game_id)
+            && let Some(game) = self.active_games.get(&new_id)
+        {
+            let opponents: Vec<&str> = game
+                .players
+                .iter()
+                .map(|p| p.name.as_str())
+                .filter(|n| *n != player_name)
+                .collect();
+            return Ok(format!(
+                "Rematch STARTED on {} (Level {}) against {}. Call look() now.",
+                game.course_name,
+                game.course_level,
+                opponents.join(", ")
+            ));
+        }
+        if self.rematches.contains_key(&game_id) {
+            let waiting: Vec<&str> = eligible
+                .iter()
+                .filter(|n| !requested.contains(n))
+                .map(String::as_str)
+                .collect();
+            return Ok(format!(
+                "Rematch on Level {} requested ({} of {} needed: {}). Waiting up to {}s for {}; if the rematch does not happen by then you join the public queue.",
+                level,
+                requested.len(),
+                needed,
+                requested.join(", "),
+                secs_left,
+                waiting.join(", ")
+            ));
+        }
+        if self.is_waiting(player_name) {
+            return Ok("Your opponents have moved on, so you joined the public queue instead. Call game_status to see when your game starts.".to_string());
+        }
+        Err(TronError::new(
+            ErrorCode::NoRematch,
+            "Your opponents have moved on, so there is nobody to rematch. Call join_game to play.",
+        ))
+    }
+
+    /// A rematch of the finished game `game_id`, with nobody asked yet
+    fn new_rematch(&self, game_id: Uuid) -> Result<Rematch, TronError> {
+        let finished = self
+            .finished_games
+            .iter()
+            .find(|g| g.id == game_id.to_string())
+            .ok_or_else(|| {
+                TronError::new(
+                    ErrorCode::NoRematch,
+                    "Your last game is no longer kept, so it cannot be replayed. Call join_game to play.",
+                )
+            })?;
+        if finished.players.iter().any(|p| p.bot) {
+            return Err(TronError::new(
+                ErrorCode::NoRematch,
+                "Only games between players can be replayed; yours had bots. Call join_game or play_vs_bot to play again.",
+            ));
+        }
+        let max_level = all_courses().len() as u32;
+        Ok(Rematch {
+            game_id,
+            course_level: (finished.course_level + 1).min(max_level.max(finished.course_level)),
+            roster: finished.players.iter().map(|p| p.name.clone()).collect(),
+            requested: Vec::new(),
+            expires_at: self.clock.now() + chrono::Duration::seconds(self.rematch_timeout_secs as i64),
+        })
+    }
+
+    /// The rematch's players who can still play it: the finished game is
+    /// still their last one and they are not queued anywhere
+    fn rematch_eligible(&self, rematch: &Rematch) -> Vec<String> {
+        rematch
+            .roster
+            .iter()
+            .filter(|name| {
+                self.player_sessions
+                    .get(*name)
+                    .is_some_and(|s| s.game_id == Some(rematch.game_id))
+                    && !self.is_waiting(name)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Start the rematch of `game_id` once the quorum has asked. When it
+    /// lapses, or fewer than two of its players are left, it is called off
+    /// and those who asked join the public queue. Returns the new game's id
+    /// if it started.
+    fn settle_rematch(&mut self, game_id: Uuid) -> Option<Uuid> {
+        let rematch = self.rematches.get(&game_id)?;
+        let eligible = self.rematch_eligible(rematch);
+        let requested: Vec<String> = rematch
+            .requested
+            .iter()
+            .filter(|n| eligible.contains(n))
+            .cloned()
+            .collect();
+        let enough = eligible.len() >= 2;
+        if enough && requested.len() >= self.rematch_quorum.needed(eligible.len()) {
+            let level = rematch.course_level;
+            self.rematches.remove(&game_id);
+            return self.start_rematch(game_id, level, requested);
+        }
+        if enough && self.clock.now() < rematch.expires_at {
+            return None;
+        }
+
+        self.rematches.remove(&game_id);
+        tracing::info!("Rematch of game {} called off; queueing {}", game_id, requested.join(", "));
+        for name in requested {
+            if let Err(e) = self.enqueue(name.clone(), None, TagPreferences::default()) {
+                tracing::info!("Could not queue '{}' after their rematch fell through: {}", name, e.message);
+            }
+        }
+        None
+    }
+
+    fn start_rematch(&mut self, game_id: Uuid, level: u32, players: Vec<String>) -> Option<Uuid> {
+        let course = self.course_for_game(level);
+        for name in &players {
+            if let Some(session) = self.player_sessions.get_mut(name) {
+                session.lobby = None;
+                session.tags = TagPreferences::default();
+                session.queued_at = self.clock.now();
+                session.cooldown_until = None;
+            }
+        }
+        let rules = course.default_rules();
+        match self.launch_game(&course, rules, players.clone(), Vec::new(), false, None) {
+            Ok(new_id) => {
+                tracing::info!("Rematch of game {} started as {}", game_id, new_id);
+                let _ = self.broadcast_tx.send(BroadcastEvent::RematchStarted {
+                    game_id: game_id.to_string(),
+                    new_game_id: new_id.to_string(),
+                    players,
+                });
+                Some(new_id)
+            }
+            Err(e) => {
+                tracing::error!("Could not start a rematch: {}", e.message);
+                None
+            }
+        }
+    }
+
+    /// Start or call off every pending rematch whose time has come
+    fn settle_rematches(&mut self) {
+        let ids: Vec<Uuid> = self.rematches.keys().copied().collect();
+        for game_id in ids {
+            self.settle_rematch(game_id);
+        }
+    }
+
+    /// Take back a player's rematch requests; true if they had any
+    fn withdraw_rematch(&mut self, name: &str) -> bool {
+        let mut withdrew = false;
+        for rematch in self.rematches.values_mut() {
+            let before = rematch.requested.len();
+            rematch.requested.retain(|n| n != name);
+            withdrew |= rematch.requested.len() != before;
+        }
+        withdrew
+    }
+
+    /// Start message for a player whose game has begun, or None while they
+    /// are still queued
+    fn game_start_message(&self, player_name: &str) -> Result<Option<String>, TronError> {
+        let session = self
+            .player_sessions
+            .get(player_name)
+            .ok_or_else(not_joined)?;
+        if let Some(game_id) = session.game_id {
+            return Ok(Some(match self.active_games.get(&game_id) {
+                Some(game) => format!(
+                    "Your game has STARTED on {} (Level {}) with {} players! Call look() now.",
+                    game.course_name,
+                    game.course_level,
+                    game.players.len()
+                ),
+                None => "Your last game has finished. Call game_status() for the result or join_game to play again."
+                    .to_string(),
+            }));
+        }
+        if session.queue_expired {
+            return Err(queue_expired());
+        }
+        if !self.is_waiting(player_name) {
+            return Err(TronError::new(ErrorCode::NotQueued, "You are not queued. Use join_game first."));
+        }
+        Ok(None)
+    }
+
+    /// Players in the queue a player is waiting in
+    fn queue_len(&self, player_name: &str) -> usize {
+        let lobby = self.player_sessions.get(player_name).and_then(|s| s.lobby.as_ref());
+        match lobby.and_then(|code| self.lobbies.get(code)) {
+            Some(lobby) => lobby.waiting_players.len(),
+            None => self.waiting_players.len(),
+        }
+    }
+
+    /// Get the look view for a player, as text drawn per `options` or as a
+    /// JSON `LookView`
+    pub fn look(&self, player_name: &str, format: LookFormat, options: LookOptions) -> Result<String, TronError> {
+        let (game_id, player_idx) = self.seat(player_name)?;
+        let game = self
+            .active_games
+            .get(&game_id)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotFound, "Game not found."))?;
+
+        match format {
+            LookFormat::Text => Ok(game.look(player_idx, 7, options)),
+            LookFormat::Compact => Ok(game.look_compact(player_idx, 7, options)),
+            LookFormat::Json => serde_json::to_string(&game.look_view(player_idx, 7))
+                .map_err(|e| TronError::new(ErrorCode::Internal, format!("Failed to serialize view: {}", e))),
+        }
+    }
+
+    /// The whole grid downsampled around a player, for `minimap`
+    pub fn minimap(&self, player_name: &str) -> Result<String, TronError> {
+        let (game_id, player_idx) = self.seat(player_name)?;
+        let game = self
+            .active_games
+            .get(&game_id)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotFound, "Game not found."))?;
+        Ok(analysis::minimap(game, player_idx))
+    }
+
+    /// The course a player is driving, or else the one their next game is
+    /// most likely on: their lobby's, or the course matchmaking would pick
+    /// for their queue bracket (or for them alone) right now, for
+    /// `course_info`
+    pub fn course_info(&self, player_name: &str) -> Result<String, TronError> {
+        let session = self.player_sessions.get(player_name).ok_or_else(not_joined)?;
+        if let Some(game) = session.game_id.and_then(|id| self.active_games.get(&id)) {
+            let course = get_course_with_seed(game.course_level, game.course_seed.unwrap_or_default());
+            let heading = format!("You are playing on {} (level {}).", game.course_name, game.course_level);
+            return Ok(analysis::course_info(&course, &game.rules, game.realtime, &heading, false));
+        }
+
+        let realtime = self.mode == GameMode::Realtime;
+        let lobby = session
+            .lobby
+            .as_ref()
+            .and_then(|code| self.lobbies.get(code))
+            .filter(|lobby| lobby.waiting_players.iter().any(|n| n == player_name));
+        let (course, rules, heading) = match lobby {
+            Some(lobby) => {
+                let course = self.course_for_game(lobby.course_level);
+                let heading = format!(
+                    "You are waiting in lobby {} for {} (level {}).",
+                    lobby.code, course.name, course.level
+                );
+                (course, lobby.rules.clone(), heading)
+            }
+            None => {
+                let queued = self.waiting_players.iter().any(|n| n == player_name);
+                let bracket = if queued { self.bracket_of(player_name) } else { vec![player_name.to_string()] };
+                let course = self.course_for_game(self.bracket_course(&bracket).0.level);
+                let heading = format!(
+                    "You are {} at level {}; your next game will most likely be on {} (level {}).",
+                    if queued { "queued" } else { "not queued" },
+                    session.current_level,
+                    course.name,
+                    course.level
+                );
+                let rules = course.default_rules();
+                (course, rules, heading)
+            }
+        };
+        let example = course.generator.is_some_and(|spec| spec.seed.is_none()) && self.course_seed.is_none();
+        Ok(analysis::course_info(&course, &rules, realtime, &heading, example))
+    }
+
+    /// How much open area each of a player's moves keeps, for `analyze`
+    pub fn analyze(&self, player_name: &str) -> Result<String, TronError> {
+        let (game_id, player_idx) = self.seat(player_name)?;
+        let game = self
+            .active_games
+            .get(&game_id)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotFound, "Game not found."))?;
+        Ok(analysis::territory(game, player_idx))
+    }
+
+    /// Everything the server knows about a name in one place, for an agent
+    /// that restarted and lost track: queue position, current or last game,
+    /// and whether the caller's connection (`bound_to`) may act for it
+    pub fn whoami(&self, name: &str, bound_to: Option<&str>) -> String {
+        let session = self.player_sessions.get(name);
+        let entry = self.leaderboard.get(name);
+        let mut lines = vec![format!("Player '{}'", name)];
+
+        lines.push(match (session, entry) {
+            (None, None) => "Known: no, this name has not played in this room".to_string(),
+            (session, entry) => format!(
+                "Known: yes, level {}, {} games played, {} wins",
+                session.map_or(1, |s| s.current_level),
+                entry.map_or(0, |e| e.games_played),
+                entry.map_or(0, |e| e.wins)
+            ),
+        });
+
+        let game = session
+            .and_then(|s| s.game_id.zip(s.player_index))
+            .map(|(id, idx)| (id, idx, self.active_games.get(&id)));
+        let queue = self
+            .waiting_players
+            .iter()
+            .position(|n| n == name)
+            .map(|pos| ("the public queue".to_string(), pos, self.waiting_players.len()))
+            .or_else(|| {
+                self.lobbies.values().find_map(|lobby| {
+                    let pos = lobby.waiting_players.iter().position(|n| n == name)?;
+                    Some((format!("lobby {}", lobby.code), pos, lobby.waiting_players.len()))
+                })
+            });
+        let now = self.clock.now();
+        // A drop only counts against a game still being played
+        let in_game = matches!(game, Some((_, _, Some(_))));
+        lines.push(match (queue, game) {
+            (Some((queue, pos, len)), _) => format!(
+                "State: QUEUED in {}, position {} of {}, waiting {}s",
+                queue,
+                pos + 1,
+                len,
+                session.map_or(0, |s| (now - s.queued_at).num_seconds())
+            ),
+            (None, Some((id, idx, Some(game)))) => {
+                let mut line = format!(
+                    "State: PLAYING game {} on {} (Level {}), tick {}",
+                    &id.to_string()[..8],
+                    game.course_name,
+                    game.course_level,
+                    game.tick
+                );
+                match game.players.get(idx) {
+                    Some(p) if p.alive => line.push_str(&format!(
+                        ", ALIVE, last steered {}s ago. Cycles move independently; steer whenever you are ready.",
+                        // Steers are stamped by the game, on the wall clock
+                        (chrono::Utc::now() - p.last_move_at).num_seconds()
+                    )),
+                    _ => match analysis::crash_description(game, idx) {
+                        Some(how) => line.push_str(&format!(", CRASHED: {}", how)),
+                        None => line.push_str(", CRASHED"),
+                    },
+                }
+                line
+            }
+            (None, Some((id, idx, None))) => {
+                let finished = self.finished_games.iter().find(|g| g.id == id.to_string());
+                let result = match finished {
+                    Some(g) if g.aborted => match &g.finish_reason {
+                        Some(reason) => format!("voided ({})", reason),
+                        None => "aborted by agreement".to_string(),
+                    },
+                    Some(g) if g.winner == Some(idx) => "won".to_string(),
+                    Some(g) if g.winning_team.is_some_and(|t| g.players.get(idx).and_then(|p| p.team) == Some(t)) => {
+                        "won with your team".to_string()
+                    }
+                    Some(g) if let Some(team) = g.winning_team => format!("lost to Team {}", team),
+                    Some(g) => match g.winner.and_then(|w| g.players.get(w)) {
+                        Some(w) => format!("lost to {}", w.name),
+                        None => "draw".to_string(),
+                    },
+                    None => "result no longer kept".to_string(),
+                };
+                let score = finished
+                    .and_then(|g| g.players.get(idx))
+                    .map(|p| format!(", score {}", p.score))
+                    .unwrap_or_default();
+                format!("State: FINISHED game {}: {}{}", &id.to_string()[..8], result, score)
+            }
+            (None, None) if session.is_some_and(|s| s.queue_expired) => {
+                "State: IDLE, the queue slot expired for lack of activity. Call join_game to play.".to_string()
+            }
+            (None, None) => "State: IDLE, not queued or playing. Call join_game to play.".to_string(),
+        });
+
+        lines.push(match bound_to {
+            Some(bound) if bound == name => format!("Connection: holds '{}' and may act for it", name),
+            Some(bound) => format!("Connection: plays as '{}', not '{}'", bound, name),
+            None => "Connection: not joined as any player".to_string(),
+        });
+        if in_game && let Some(at) = session.and_then(|s| s.disconnected_at) {
+            let left = self.disconnect_grace_secs as i64 - (now - at).num_seconds();
+            lines.push(format!("Disconnected: forfeits in {}s unless resumed", left.max(0)));
+        }
+        if let Some(secs) = self.cooldown_left(name) {
+            lines.push(format!("Cooldown: crashed last game, can join again in {}s", secs));
+        }
+        lines.join("\n")
+    }
+
+    /// Get game status for a player. The first call after a game ends
+    /// returns the full recap.
+    pub fn game_status(&mut self, player_name: &str) -> Result<String, TronError> {
+        let queued = self.is_waiting(player_name);
+        let session = self
+            .player_sessions
+            .get_mut(player_name)
+            .ok_or_else(not_joined)?;
+        if session.queue_expired {
+            return Err(queue_expired());
+        }
+        session.active_at = self.clock.now();
+        let show_recap = std::mem::take(&mut session.recap_pending);
+        let session = &*session;
+
+        let Some(game_id) = session.game_id else {
+            if !queued {
+                // Dropped from the queue along with their connection
+                return Err(TronError::new(ErrorCode::NotQueued, "You are not queued. Use join_game first."));
+            }
+            if let Some(lobby) = session.lobby.as_ref().and_then(|c| self.lobbies.get(c)) {
+                return Ok(format!(
+                    "Status: WAITING for game to start in lobby {}. {} players in lobby.",
+                    lobby.code,
+                    lobby.waiting_players.len()
+                ));
+            }
+            return Ok(format!(
+                "Status: WAITING for game to start. {} players in queue.",
+                self.waiting_players.len()
+            ));
+        };
+        let player_idx = session.player_index.unwrap_or(0);
+
+        // Check active games first
+        if let Some(game) = self.active_games.get(&game_id) {
+            return Ok(self.format_status(game, player_idx));
+        }
+
+        // Check finished games
+        if let Some(finished) = self
+            .finished_games
+            .iter()
+            .find(|g| g.id == game_id.to_string())
+        {
+            if show_recap && let Some(recap) = &finished.recap {
+                return Ok(recap.clone());
+            }
+            let mut lines = vec![format!("Status: FINISHED")];
+            if let Some(team) = finished.winning_team {
+                let names: Vec<&str> = finished
+                    .players
+                    .iter()
+                    .filter(|p| p.team == Some(team))
+                    .map(|p| p.name.as_str())
+                    .collect();
+                match finished.tie_break {
+                    Some(reason) => lines.push(format!("Winner: Team {} ({}) (tie-break: {})", team, names.join(", "), reason.name())),
+                    None => lines.push(format!("Winner: Team {} ({})", team, names.join(", "))),
+                }
+            } else if let Some(winner_idx) = finished.winner {
+                if let Some(wp) = finished.players.get(winner_idx) {
+                    match finished.tie_break {
+                        Some(reason) => lines.push(format!("Winner: {} (tie-break: {})", wp.name, reason.name())),
+                        None => lines.push(format!("Winner: {}", wp.name)),
+                    }
+                }
+            } else if finished.aborted && finished.finish_reason.is_some() {
+                lines.push("Result: ABORTED (no leaderboard effect)".to_string());
+            } else if finished.aborted {
+                lines.push("Result: ABORTED by agreement".to_string());
+            } else if finished.finish_reason.is_some() {
+                lines.push("Result: DRAW (time limit, leaders tied)".to_string());
+            } else if finished.rules.win_condition == WinCondition::Territory {
+                lines.push("Result: DRAW (territory tied)".to_string());
+            } else {
+                lines.push("Result: DRAW (everyone crashed)".to_string());
+            }
+            if let Some(reason) = &finished.finish_reason {
+                lines.push(format!("Finish reason: {} (tick {})", reason, finished.tick));
+            }
+            if let Some(pp) = finished.players.get(player_idx) {
+                lines.push(format!("Your score: {}", pp.score));
+            }
+            if let Some(secs) = self.cooldown_left(player_name) {
+                lines.push(format!("Cooldown: you can join again in {}s", secs));
+            }
+            if let Some(rematch) = self.rematches.get(&game_id)
+                && rematch.requested.iter().any(|n| n == player_name)
+            {
+                lines.push(format!(
+                    "Rematch: requested, waiting up to {}s for the others",
+                    rematch.secs_left(self.clock.now())
+                ));
+            }
+            return Ok(lines.join("\n"));
+        }
+
+        Ok("Game not found.".to_string())
+    }
+
+    fn format_status(&self, game: &Game, player_idx: usize) -> String {
+        let mut lines = Vec::new();
+        lines.push(format!("Status: {:?}", game.status));
+        match game.course_seed {
+            Some(seed) => lines.push(format!(
+                "Course: {} (Level {}, layout seed {})",
+                game.course_name, game.course_level, seed
+            )),
+            None => lines.push(format!("Course: {} (Level {})", game.course_name, game.course_level)),
+        }
+        lines.push(format!("Tick: {}", game.tick));
+        if !game.tags.is_empty() {
+            lines.push(format!("Tags: {}", game.tags.join(", ")));
+        }
+        if let Some(note) = &game.course_note {
+            lines.push(note.clone());
+        }
+        if let Some(code) = &game.lobby {
+            lines.push(format!("Lobby: {}", code));
+        }
+        if game.unranked {
+            if game.players.iter().any(|p| p.bot.is_some()) {
+                lines.push("Unranked game (bot opponents).".to_string());
+            } else {
+                lines.push("Unranked game (custom rules).".to_string());
+            }
+        }
+        for rule in &game.rules_diff {
+            lines.push(format!("Custom rule: {}", rule));
+        }
+        if let Some(territory) = game.territory_line(Some(player_idx)) {
+            lines.push(territory);
+        }
+        if let Some(shrink) = game.shrink_line() {
+            lines.push(shrink);
+        }
+        if game.status == GameStatus::Running {
+            lines.push(game.trail_line());
+        }
+        if game.status == GameStatus::Running
+            && let Some(limit) = game.time_limit_line()
+        {
+            lines.push(limit);
+        }
+        if let Some(notice) = game.abort_notice(player_idx) {
+            lines.push(notice);
+        }
+        let chat = game.chat_for(player_idx);
+        if !chat.is_empty() {
+            lines.push("Chat:".to_string());
+            lines.extend(chat.into_iter().map(|c| format!("  {}", c)));
+        }
+        let idle: Vec<String> = game
+            .players
+            .iter()
+            .filter(|p| p.idle_moves > 0)
+            .map(|p| format!("{} x{}", p.name, p.idle_moves))
+            .collect();
+        if !idle.is_empty() {
+            lines.push(format!("Moved straight while idle: {}", idle.join(", ")));
+        }
+        if self.fairness_delay != FairnessDelay::Off {
+            let delays: Vec<String> = game
+                .players
+                .iter()
+                .filter(|p| p.bot.is_none())
+                .map(|p| match p.latency_ms {
+                    Some(rtt) => format!("{} +{}ms (rtt {}ms)", p.name, p.fairness_delay_ms, rtt),
+                    None => format!("{} +{}ms (rtt not measured)", p.name, p.fairness_delay_ms),
+                })
+                .collect();
+            lines.push(format!("Fairness delay per steer: {}", delays.join(", ")));
+        }
+
+        let alive = game.players.iter().filter(|p| p.alive).count();
+        lines.push(format!("Players alive: {}/{}", alive, game.players.len()));
+
+        if let Some(p) = game.players.get(player_idx) {
+            lines.push(format!(
+                "You: {} at ({}, {}) heading {} — {}",
+                p.name,
+                p.x,
+                p.y,
+                p.direction.name(),
+                game.status_of(player_idx)
+            ));
+            lines.push(format!("Distance: {}", p.distance_traveled));
+            if let Some(how) = analysis::crash_description(game, player_idx) {
+                lines.push(format!("Crash: {}", how));
+            }
+        }
+
+        let recent = analysis::recent_events(game, analysis::FEED_LEN);
+        if !recent.is_empty() {
+            lines.push("Recent events:".to_string());
+            lines.extend(recent.into_iter().map(|e| format!("  {}", e)));
+        }
+
+        if game.status == GameStatus::Finished {
+            if let Some(team) = game.winning_team() {
+                let score: u32 = game.team_scores().get(team - 1).copied().unwrap_or(0);
+                match game.tie_break {
+                    Some(reason) => lines.push(format!(
+                        "Winner: {} (tie-break: {}, team score: {})",
+                        analysis::team_label(game, team),
+                        reason.name(),
+                        score
+                    )),
+                    None => lines.push(format!("Winner: {} (team score: {})", analysis::team_label(game, team), score)),
+                }
+                if game.won(player_idx) {
+                    lines.push("Congratulations! Your team won! Use join_game to play the next level.".to_string());
+                }
+            } else if let Some(winner_idx) = game.winner {
+                let winner = &game.players[winner_idx];
+                match game.tie_break {
+                    Some(reason) => lines.push(format!(
+                        "Winner: {} (tie-break: {}, score: {})",
+                        winner.name,
+                        reason.name(),
+                        winner.score
+                    )),
+                    None => lines.push(format!("Winner: {} (score: {})", winner.name, winner.score)),
+                }
+                if winner_idx == player_idx {
+                    lines.push("Congratulations! You won! Use join_game to play the next level.".to_string());
+                }
+            } else if game.aborted && game.finish_reason().is_some() {
+                lines.push("Result: ABORTED (no leaderboard effect)".to_string());
+            } else if game.aborted {
+                lines.push("Result: ABORTED by agreement (no leaderboard effect)".to_string());
+            } else if game.hit_time_limit() {
+                lines.push("Result: DRAW (time limit, leaders tied)".to_string());
+            } else if game.territory_decided() {
+                lines.push("Result: DRAW (territory tied)".to_string());
+            } else {
+                lines.push("Result: DRAW (everyone crashed)".to_string());
+            }
+            if let Some(reason) = game.finish_reason() {
+                lines.push(format!("Finish reason: {} (tick {})", reason, game.tick));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Handle a game that just finished — update leaderboard, broadcast, archive
+    fn finish_game(&mut self, game_id: Uuid) {
+        if let Some(game) = self.active_games.remove(&game_id) {
+            METRICS.game_finished(&self.room, &game);
+            // Exhibitions are only for show: no stats, no archive
+            if game.exhibition {
+                let _ = self.broadcast_tx.send(BroadcastEvent::GameFinished {
+                    game: Box::new(game.to_web_state()),
+                    leaderboard_checksum: None,
+                });
+                return;
+            }
+
+            // Update leaderboard (unranked and aborted games only get archived)
+            let ranked_players = if game.unranked || game.aborted {
+                &[][..]
+            } else {
+                &game.players[..]
+            };
+            let finished_at = chrono::Utc::now().to_rfc3339();
+            for (i, player) in ranked_players.iter().enumerate() {
+                let entry = self
+                    .leaderboard
+                    .entry(player.name.clone())
+                    .or_insert_with(|| LeaderboardEntry {
+                        name: player.name.clone(),
+                        ..Default::default()
+                    });
+                entry.games_played += 1;
+                entry.total_points += player.score;
+                entry.kills += player.kills;
+                entry.last_played = Some(finished_at.clone());
+                let level = entry.levels.entry(game.course_level).or_default();
+                level.games_played += 1;
+                level.total_points += player.score;
+                level.total_ticks += game.tick as u64;
+                match game.winner {
+                    Some(_) if game.won(i) => {
+                        entry.current_streak += 1;
+                        entry.best_streak = entry.best_streak.max(entry.current_streak);
+                        level.wins += 1;
+                    }
+                    Some(_) => {
+                        entry.losses += 1;
+                        entry.current_streak = 0;
+                        level.losses += 1;
+                    }
+                    None => {
+                        entry.draws += 1;
+                        entry.current_streak = 0;
+                        level.draws += 1;
+                    }
+                }
+
+                if game.won(i) {
+                    entry.wins += 1;
+                    if game.course_level >= entry.highest_level {
+                        entry.highest_level = game.course_level + 1;
+                    }
+
+                    // Advance winner's level
+                    if let Some(session) = self.player_sessions.get_mut(&player.name) {
+                        let max_level = all_courses().len() as u32;
+                        if session.current_level < max_level {
+                            session.current_level += 1;
+                        }
+                    }
+                }
+            }
+
+            if !ranked_players.is_empty() {
+                self.update_ratings(&game);
+            }
+
+            let mut campaign = self.campaign_summary(&game);
+            campaign.extend(self.update_practice(&game));
+            campaign.extend(self.start_cooldowns(&game));
+            for player in &game.players {
+                if let Some(session) = self.player_sessions.get_mut(&player.name) {
+                    session.recap_pending = true;
+                }
+            }
+
+            let mut web_state = game.to_web_state();
+            web_state.recap = Some(analysis::recap(&game, &campaign));
+            let _ = self.broadcast_tx.send(BroadcastEvent::GameFinished {
+                game: Box::new(web_state.clone()),
+                leaderboard_checksum: Some(leaderboard_checksum(self.leaderboard.values())),
+            });
+
+            // Watchers were sent the game without it; the archive keeps it for `tronmcp replay`
+            web_state.replay = Some(game.replay.clone());
+            self.storage.add_finished_game(&web_state, self.max_finished_games);
+            self.finished_games.push(web_state);
+            if self.finished_games.len() > self.max_finished_games {
+                self.finished_games.remove(0);
+            }
+
+            self.save_leaderboard();
+            self.save_active_state();
+        }
+    }
+
+    /// Apply Elo changes for a ranked game to each player's rating history
+    /// and leaderboard entry
+    fn update_ratings(&mut self, game: &Game) {
+        let order = analysis::placements(game);
+        let current: Vec<i32> = order
+            .iter()
+            .map(|&i| self.rating_of(&game.players[i].name))
+            .collect();
+        let deltas = rating::elo_deltas(&current, game.winner.is_none(), self.elo_k_factor);
+
+        let now = chrono::Utc::now();
+        for ((&idx, before), delta) in order.iter().zip(current).zip(deltas) {
+            let name = &game.players[idx].name;
+            self.ratings.entry(name.clone()).or_default().record(RatingPoint {
+                at: now,
+                game_id: game.id.to_string(),
+                rating: before + delta,
+                delta,
+            });
+            if let Some(entry) = self.leaderboard.get_mut(name) {
+                entry.rating = before + delta;
+            }
+        }
+        self.save_ratings();
+    }
+
+    fn rating_of(&self, name: &str) -> i32 {
+        self.ratings
+            .get(name)
+            .map(|h| h.current())
+            .unwrap_or(rating::INITIAL_RATING)
+    }
+
+    /// A player's rating series and summary, or None if they have never played ranked
+    pub fn rating_history(&self, name: &str) -> Option<RatingHistoryView> {
+        let history = self.ratings.get(name)?;
+        Some(RatingHistoryView {
+            name: name.to_string(),
+            summary: history.summary(chrono::Utc::now()),
+            history: history.points.clone(),
+        })
+    }
+
+    /// Feed the result of a game against an adaptive bot into each human
+    /// player's difficulty controller. Returns lines for the recap.
+    fn update_practice(&mut self, game: &Game) -> Vec<String> {
+        let adaptive = game
+            .players
+            .iter()
+            .any(|p| p.bot.is_some_and(|b| b.difficulty == Difficulty::Adaptive));
+        if !adaptive {
+            return Vec::new();
+        }
+        let mut lines = Vec::new();
+        for (i, player) in game.players.iter().enumerate() {
+            if player.bot.is_some() {
+                continue;
+            }
+            let state = self.practice.entry(player.name.clone()).or_default();
+            let before = state.skill;
+            state.record(game.won(i));
+            lines.push(format!(
+                "Adaptive bot skill for {}: {:.2} -> {:.2}.",
+                player.name, before, state.skill
+            ));
+        }
+        self.save_practice();
+        lines
+    }
+
+    /// What a finished game means for each player's campaign progress.
+    /// Called after levels have been advanced.
+    fn campaign_summary(&self, game: &Game) -> Vec<String> {
+        if game.aborted && game.finish_reason().is_some() {
+            return vec!["Voided, as a player left before anyone moved: leaderboard and levels are unchanged.".to_string()];
+        }
+        if game.aborted {
+            return vec!["Aborted by agreement: leaderboard and levels are unchanged.".to_string()];
+        }
+        if game.unranked {
+            return vec!["Unranked game: leaderboard and levels are unchanged.".to_string()];
+        }
+        let max_level = all_courses().len() as u32;
+        game.players
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| {
+                let level = self.player_sessions.get(&p.name)?.current_level;
+                Some(if game.won(i) {
+                    if game.course_level >= max_level {
+                        format!("{} has cleared the final course.", p.name)
+                    } else {
+                        format!("{} advances to level {}.", p.name, level)
+                    }
+                } else {
+                    format!("{} stays on level {}.", p.name, level)
+                })
+            })
+            .collect()
+    }
+
+    /// Hold back every human who crashed out of a game someone else won.
+    /// Winners, draws and aborted games get no cooldown.
+    fn start_cooldowns(&mut self, game: &Game) -> Vec<String> {
+        let (Some(base), Some(_), Some(finished_at)) =
+            (self.crash_cooldown_secs, game.winner, game.finished_at)
+        else {
+            return Vec::new();
+        };
+        if game.aborted {
+            return Vec::new();
+        }
+        let mut lines = Vec::new();
+        for (i, player) in game.players.iter().enumerate() {
+            if game.won(i) || player.alive || player.bot.is_some() {
+                continue;
+            }
+            let Some(session) = self.player_sessions.get_mut(&player.name) else {
+                continue;
+            };
+            let crash_tick = game.survival_ticks(i);
+            let secs = crash_cooldown_secs(base, crash_tick);
+            session.cooldown_until = Some(finished_at + chrono::Duration::seconds(secs as i64));
+            lines.push(format!(
+                "{} crashed on tick {} and can join again in {}s.",
+                player.name, crash_tick, secs
+            ));
+        }
+        lines
+    }
+
+    /// Snapshot of queues, sessions and games for the admin API
+    pub fn admin_overview(&self) -> serde_json::Value {
+        let games: Vec<serde_json::Value> = self
+            .active_games
+            .values()
+            .map(|g| {
+                serde_json::json!({
+                    "id": g.id.to_string(),
+                    "course_level": g.course_level,
+                    "tick": g.tick,
+                    "lobby": g.lobby,
+                    "players": g.players.iter().map(|p| &p.name).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "room": self.room,
+            "active_games": games,
+            "waiting_players": self.waiting_players,
+            "lobbies": self.lobbies.len(),
+            "sessions": self.player_sessions.len(),
+            "finished_games": self.finished_games.len(),
+            "failed_admin_auth_attempts": self.admin.failed_attempts(),
+        })
+    }
+
+    /// Remove a player from the server: out of every queue, and crashed out
+    /// of any running game.
+    pub fn kick_player(&mut self, name: &str) -> Result<String, TronError> {
+        let session = self
+            .player_sessions
+            .remove(name)
+            .ok_or_else(|| TronError::new(ErrorCode::PlayerNotFound, format!("Player '{}' not found.", name)))?;
+        self.remove_from_queues(name);
+
+        if let (Some(game_id), Some(idx)) = (session.game_id, session.player_index)
+            && let Some(game) = self.active_games.get_mut(&game_id)
+        {
+            game.kick(idx);
+            let _ = self.broadcast_tx.send(BroadcastEvent::GameUpdate {
+                game: Box::new(game.to_web_state()),
+            });
+            if game.status == GameStatus::Finished {
+                self.finish_game(game_id);
+            }
+        }
+        self.announce_admin_action("kick", name);
+        Ok(format!("Player '{}' kicked.", name))
+    }
+
+    /// End a running game immediately with no winner
+    pub fn force_finish_game(&mut self, game_id: Uuid) -> Result<String, TronError> {
+        let game = self
+            .active_games
+            .get_mut(&game_id)
+            .ok_or_else(|| TronError::new(ErrorCode::GameNotFound, "Game not found."))?;
+        game.end_now();
+        self.finish_game(game_id);
+        self.announce_admin_action("abort", &game_id.to_string());
+        Ok(format!("Game {} finished.", game_id))
+    }
+
+    /// Empty the public queue; lobbies keep their players. Everyone removed
+    /// has to join again.
+    pub fn clear_queue(&mut self) -> String {
+        let cleared = std::mem::take(&mut self.waiting_players);
+        for name in &cleared {
+            if let Some(session) = self.player_sessions.get_mut(name) {
+                session.match_starts_at = None;
+                session.tags = TagPreferences::default();
+            }
+            let _ = self.broadcast_tx.send(BroadcastEvent::PlayerLeft {
+                player: name.clone(),
+                from: LeftFrom::Queue,
+                game_id: None,
+            });
+        }
+        self.announce_admin_action("clear_queue", "queue");
+        format!("Removed {} players from the public queue.", cleared.len())
+    }
+
+    /// Delete one player's leaderboard entry and rating history, as
+    /// `reset_leaderboard` does for everyone
+    pub fn remove_leaderboard_entry(&mut self, name: &str) -> Result<String, TronError> {
+        if self.leaderboard.remove(name).is_none() {
+            return Err(TronError::new(
+                ErrorCode::PlayerNotFound,
+                format!("'{}' has no leaderboard entry.", name),
+            ));
+        }
+        self.ratings.remove(name);
+        self.save_leaderboard();
+        self.save_ratings();
+        self.announce_admin_action("remove_entry", name);
+        Ok(format!("Leaderboard entry of '{}' removed.", name))
+    }
+
+    fn announce_admin_action(&self, action: &str, target: &str) {
+        let _ = self.broadcast_tx.send(BroadcastEvent::AdminAction {
+            action: action.to_string(),
+            target: target.to_string(),
+        });
+    }
+
+    /// Last step before the process exits: write every file one final time,
+    /// including the games in play, which the next start resumes
+    pub fn shutdown(&mut self) {
+        self.save_leaderboard();
+        self.save_ratings();
+        self.save_practice();
+        self.save_webhooks();
+        self.save_active_state();
+        self.storage.flush();
+        self.practice_file.flush();
+        self.ratings_file.flush();
+        self.webhooks_file.flush();
+        if !self.active_games.is_empty() {
+            tracing::info!(
+                "Saved {} active games of room {} to resume on restart",
+                self.active_games.len(),
+                self.room
+            );
+        }
+    }
+
+    /// Copy of everything the room holds, for handing over to a new process.
+    /// Taken under the manager lock, so no command lands halfway through it.
+    pub(crate) fn export_state(&self) -> StateSnapshot {
+        StateSnapshot {
+            schema_version: SCHEMA_VERSION,
+            room: self.room.clone(),
+            exported_at: chrono::Utc::now(),
+            active_games: self.active_games.values().cloned().collect(),
+            waiting_players: self.waiting_players.clone(),
+            player_sessions: self.player_sessions.clone(),
+            lobbies: self.lobbies.values().cloned().collect(),
+            leaderboard: self.leaderboard.clone(),
+            ratings: self.ratings.clone(),
+            practice: self.practice.clone(),
+            finished_games: self.finished_games.clone(),
+        }
+    }
+
+    /// Adopt a snapshot exported by another process. The snapshot must pass
+    /// the invariant audit or nothing is changed. Move clocks restart, so
+    /// the handover pause does not time anyone out.
+    pub(crate) fn import_state(&mut self, snapshot: StateSnapshot) -> Result<(), Vec<String>> {
+        if snapshot.schema_version != SCHEMA_VERSION {
+            return Err(vec![format!(
+                "schema version {} is not {}",
+                snapshot.schema_version, SCHEMA_VERSION
+            )]);
+        }
+        if snapshot.room != self.room {
+            return Err(vec![format!(
+                "snapshot is for room '{}', not '{}'",
+                snapshot.room, self.room
+            )]);
+        }
+
+        let restored = Self::restored(self, snapshot);
+        let previous = std::mem::replace(self, restored);
+        let problems = self.audit();
+        if !problems.is_empty() {
+            *self = previous;
+            return Err(problems);
+        }
+
+        let now = chrono::Utc::now();
+        for game in self.active_games.values_mut() {
+            for player in &mut game.players {
+                player.last_move_at = now;
+            }
+        }
+        self.save_leaderboard();
+        self.save_ratings();
+        self.save_practice();
+        self.storage.replace_finished_games(&self.finished_games);
+        self.save_active_state();
+        Ok(())
+    }
+
+    /// A manager with this one's settings and the snapshot's state
+    fn restored(settings: &GameManager, snapshot: StateSnapshot) -> GameManager {
+        GameManager {
+            active_games: snapshot
+                .active_games
+                .into_iter()
+                .map(|game| (game.id, Game { clock: settings.clock.clone(), ..game }))
+                .collect(),
+            finished_games: snapshot.finished_games,
+            leaderboard: snapshot.leaderboard,
+            player_sessions: snapshot.player_sessions,
+            waiting_players: snapshot.waiting_players,
+            lobbies: snapshot.lobbies.into_iter().map(|l| (l.code.clone(), l)).collect(),
+            practice: snapshot.practice,
+            ratings: snapshot.ratings,
+            webhooks: settings.webhooks.clone(),
+            server_webhooks: settings.server_webhooks.clone(),
+            allow_private_webhooks: settings.allow_private_webhooks,
+            broadcast_tx: settings.broadcast_tx.clone(),
+            max_finished_games: settings.max_finished_games,
+            max_leaderboard_size: settings.max_leaderboard_size,
+            data_dir: settings.data_dir.clone(),
+            admin: settings.admin.clone(),
+            room: settings.room.clone(),
+            bot_fill_secs: settings.bot_fill_secs,
+            count_bots: settings.count_bots,
+            rematches: HashMap::new(),
+            rematch_quorum: settings.rematch_quorum,
+            rematch_timeout_secs: settings.rematch_timeout_secs,
+            queue_ttl_secs: settings.queue_ttl_secs,
+            disconnect_policy: settings.disconnect_policy,
+            disconnect_grace_secs: settings.disconnect_grace_secs,
+            fairness_delay: settings.fairness_delay,
+            idle: settings.idle,
+            mode: settings.mode,
+            plan_updates: settings.plan_updates,
+            exhibition_mins: settings.exhibition_mins,
+            crash_cooldown_secs: settings.crash_cooldown_secs,
+            mixed_levels: settings.mixed_levels,
+            level_tolerance: settings.level_tolerance,
+            min_players: settings.min_players,
+            max_players_override: settings.max_players_override,
+            start_delay_secs: settings.start_delay_secs,
+            clock: settings.clock.clone(),
+            elo_k_factor: settings.elo_k_factor,
+            elo_window: settings.elo_window,
+            course_seed: settings.course_seed,
+            quiet_since: chrono::Utc::now(),
+            exhibition_course: settings.exhibition_course,
+            active_saved_at: settings.active_saved_at,
+            storage: settings.storage.clone(),
+            practice_file: settings.practice_file.clone(),
+            ratings_file: settings.ratings_file.clone(),
+            webhooks_file: settings.webhooks_file.clone(),
+        }
+    }
+
+    /// Check that queues, sessions, lobbies and games agree with each other.
+    /// Returns one line per problem found.
+    pub fn audit(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for (code, lobby) in &self.lobbies {
+            if *code != lobby.code {
+                problems.push(format!("lobby '{}' is stored under '{}'", lobby.code, code));
+            }
+        }
+
+        let queues = std::iter::once((None, &self.waiting_players)).chain(
+            self.lobbies
+                .values()
+                .map(|l| (Some(l.code.clone()), &l.waiting_players)),
+        );
+        let mut queued = std::collections::HashSet::new();
+        for (lobby, names) in queues {
+            for name in names {
+                if !queued.insert(name.as_str()) {
+                    problems.push(format!("'{}' is queued more than once", name));
+                }
+                match self.player_sessions.get(name) {
+                    None => problems.push(format!("'{}' is queued without a session", name)),
+                    Some(session) if session.game_id.is_some() => {
+                        problems.push(format!("'{}' is queued but has a game", name))
+                    }
+                    Some(session) if session.lobby != lobby => {
+                        problems.push(format!("'{}' is queued in a different lobby than their session says", name))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        for (name, session) in &self.player_sessions {
+            if let Some(code) = &session.lobby
+                && session.game_id.is_none()
+                && !self.lobbies.contains_key(code)
+            {
+                problems.push(format!("'{}' waits for lobby '{}', which does not exist", name, code));
+            }
+            let (Some(game_id), Some(idx)) = (session.game_id, session.player_index) else {
+                continue;
+            };
+            // Sessions keep the id of a finished game until the next join
+            if let Some(game) = self.active_games.get(&game_id)
+                && game.players.get(idx).is_none_or(|p| p.name != *name)
+            {
+                problems.push(format!("'{}' is not seat {} of game {}", name, idx, game_id));
+            }
+        }
+
+        for (id, game) in &self.active_games {
+            if *id != game.id {
+                problems.push(format!("game {} is stored under {}", game.id, id));
+            }
+            if game.grid.len() != game.height || game.grid.iter().any(|row| row.len() != game.width) {
+                problems.push(format!("game {} grid is not {}x{}", id, game.width, game.height));
+            }
+            if game.width < MIN_GRID_SIZE || game.height < MIN_GRID_SIZE {
+                problems.push(format!(
+                    "game {} grid is smaller than {}x{}",
+                    id, MIN_GRID_SIZE, MIN_GRID_SIZE
+                ));
+            }
+            if game.rules.move_timeout_secs.is_some_and(|s| s > MAX_MOVE_TIMEOUT_SECS) {
+                problems.push(format!("game {} has a move timeout over {}s", id, MAX_MOVE_TIMEOUT_SECS));
+            }
+            for (idx, player) in game.players.iter().enumerate() {
+                if player.bot.is_some() {
+                    continue;
+                }
+                let seated = self
+                    .player_sessions
+                    .get(&player.name)
+                    .is_some_and(|s| s.game_id == Some(*id) && s.player_index == Some(idx));
+                if !seated {
+                    problems.push(format!("'{}' plays in game {} but their session does not say so", player.name, id));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Delete every leaderboard entry
+    pub fn reset_leaderboard(&mut self) {
+        self.leaderboard.clear();
+        self.ratings.clear();
+        self.save_leaderboard();
+        self.save_ratings();
+    }
+
+    /// Delete the finished-games archive
+    pub fn purge_finished_games(&mut self) {
+        self.finished_games.clear();
+        self.storage.replace_finished_games(&[]);
+    }
+
+    /// Top `max_leaderboard_size` entries of players with at least
+    /// `min_games` games, in `sort` order
+    pub fn get_leaderboard(&self, sort: LeaderboardSort, min_games: u32) -> Vec<LeaderboardEntry> {
+        let mut entries = self.get_leaderboard_full(sort, min_games);
+        entries.truncate(self.max_leaderboard_size);
+        entries
+    }
+
+    /// Fill in the fields of a leaderboard entry that are worked out when it
+    /// is served: the current rating, its 7-day change and the win rate
+    fn fill_served_fields(&self, entry: &mut LeaderboardEntry) {
+        let week_ago = chrono::Utc::now() - chrono::Duration::days(7);
+        if let Some(history) = self.ratings.get(&entry.name) {
+            entry.rating = history.current();
+            entry.rating_change_7d = history.change_since(week_ago);
+        }
+        if entry.games_played > 0 {
+            entry.win_rate = entry.wins as f64 / entry.games_played as f64;
+        }
+    }
+
+    /// A player's profile around `games`, their latest finished games
+    /// newest first; `None` for a name the room has never seen
+    fn build_profile(&self, name: &str, games: &[WebGameState]) -> Option<PlayerProfile> {
+        let leaderboard = self.leaderboard.get(name).cloned().map(|mut entry| {
+            self.fill_served_fields(&mut entry);
+            entry
+        });
+        let session = self.player_sessions.get(name);
+        if leaderboard.is_none() && session.is_none() && games.is_empty() {
+            return None;
+        }
+        let lobby = self
+            .lobbies
+            .values()
+            .find(|l| l.waiting_players.iter().any(|n| n == name));
+        let activity = match session.and_then(|s| s.game_id).and_then(|id| self.active_games.get(&id)) {
+            Some(game) => PlayerActivity::Playing {
+                game_id: game.id.to_string(),
+                course: game.course_name.clone(),
+                level: game.course_level,
+            },
+            None if lobby.is_some() || self.waiting_players.iter().any(|n| n == name) => PlayerActivity::Queued {
+                lobby: lobby.map(|l| l.code.clone()),
+                level: lobby.map_or_else(|| self.queued_level(name), |l| l.course_level),
+            },
+            None => PlayerActivity::Idle,
+        };
+        Some(PlayerProfile {
+            name: name.to_string(),
+            leaderboard,
+            activity,
+            recent_games: games.iter().filter_map(|g| PlayedGame::from_web(g, name)).collect(),
+        })
+    }
+
+    /// Every entry `get_leaderboard` would list, not cut off
+    pub fn get_leaderboard_full(&self, sort: LeaderboardSort, min_games: u32) -> Vec<LeaderboardEntry> {
+        let mut entries: Vec<LeaderboardEntry> = self
+            .leaderboard
+            .values()
+            .filter(|e| e.games_played >= min_games)
+            .cloned()
+            .collect();
+        for entry in &mut entries {
+            self.fill_served_fields(entry);
+        }
+        match sort {
+            LeaderboardSort::Points => entries.sort_by_key(|e| std::cmp::Reverse(e.total_points)),
+            LeaderboardSort::Rating => entries.sort_by_key(|e| std::cmp::Reverse(e.rating)),
+            // Compared as wins_a / games_a against wins_b / games_b without
+            // rounding; more games break a tie
+            LeaderboardSort::WinRate => entries.sort_by(|a, b| {
+                let a_rate = a.wins as u64 * b.games_played.max(1) as u64;
+                let b_rate = b.wins as u64 * a.games_played.max(1) as u64;
+                b_rate.cmp(&a_rate).then(b.games_played.cmp(&a.games_played))
+            }),
+        }
+        entries
+    }
+
+    /// The standings as text, for the `get_leaderboard` tool and the
+    /// `LEADERBOARD` command
+    pub fn leaderboard_text(&self, sort: LeaderboardSort, min_games: u32, level: Option<u32>) -> Result<String, TronError> {
+        if let Some(level) = level {
+            return self.level_leaderboard_text(level, sort, min_games);
+        }
+        let entries = self.get_leaderboard(sort, min_games);
+        if entries.is_empty() {
+            return Ok(match min_games {
+                0 => "No ranked games played yet.".to_string(),
+                n => format!("No player has {} ranked games yet.", n),
+            });
+        }
+        let mut lines = vec![format!(
+            "Leaderboard by {}{}:",
+            sort.as_str().replace('_', " "),
+            match min_games {
+                0 => String::new(),
+                n => format!(" ({}+ games)", n),
+            }
+        )];
+        for (i, e) in entries.iter().enumerate() {
+            lines.push(format!(
+                "{}. {}: {} pts, rating {}, {}W {}L {}D ({:.0}% wins), {} kills, streak {} (best {}), level {}",
+                i + 1,
+                e.name,
+                e.total_points,
+                e.rating,
+                e.wins,
+                e.losses,
+                e.draws,
+                e.win_rate * 100.0,
+                e.kills,
+                e.current_streak,
+                e.best_streak,
+                e.highest_level
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// The top standings on one level, ordered by `sort`: points and win
+    /// rate count only that level's games, rating is the overall one
+    pub fn get_level_leaderboard(
+        &self,
+        level: u32,
+        sort: LeaderboardSort,
+        min_games: u32,
+    ) -> Result<Vec<LevelStanding>, TronError> {
+        let max_level = CourseRegistry::current().max_level();
+        if !(1..=max_level).contains(&level) {
+            return Err(TronError::new(
+                ErrorCode::InvalidParams,
+                format!("There is no level {}; levels run from 1 to {}.", level, max_level),
+            ));
+        }
+        let mut standings: Vec<LevelStanding> = self
+            .leaderboard
+            .values()
+            .filter_map(|e| {
+                let stats = e.levels.get(&level).filter(|s| s.games_played >= min_games.max(1))?;
+                let games = stats.games_played as f64;
+                Some(LevelStanding {
+                    name: e.name.clone(),
+                    level,
+                    games_played: stats.games_played,
+                    wins: stats.wins,
+                    losses: stats.losses,
+                    draws: stats.draws,
+                    total_points: stats.total_points,
+                    win_rate: stats.wins as f64 / games,
+                    avg_ticks: stats.total_ticks as f64 / games,
+                    rating: self.ratings.get(&e.name).map_or(e.rating, RatingHistory::current),
+                })
+            })
+            .collect();
+        match sort {
+            LeaderboardSort::Points => standings.sort_by_key(|s| std::cmp::Reverse(s.total_points)),
+            LeaderboardSort::Rating => standings.sort_by_key(|s| std::cmp::Reverse(s.rating)),
+            LeaderboardSort::WinRate => standings.sort_by(|a, b| {
+                let a_rate = a.wins as u64 * b.games_played as u64;
+                let b_rate = b.wins as u64 * a.games_played as u64;
+                b_rate.cmp(&a_rate).then(b.games_played.cmp(&a.games_played))
+            }),
+        }
+        standings.truncate(self.max_leaderboard_size);
+        Ok(standings)
+    }
+
+    /// `get_level_leaderboard` as text, for `get_leaderboard` with a level
+    fn level_leaderboard_text(&self, level: u32, sort: LeaderboardSort, min_games: u32) -> Result<String, TronError> {
+        let standings = self.get_level_leaderboard(level, sort, min_games)?;
+        let course = get_course(level);
+        if standings.is_empty() {
+            return Ok(match min_games {
+                0 | 1 => format!("No ranked games played on level {} ({}) yet.", level, course.name),
+                n => format!("No player has {} ranked games on level {} ({}) yet.", n, level, course.name),
+            });
+        }
+        let mut lines = vec![format!(
+            "Level {} ({}) leaderboard by {}{}:",
+            level,
+            course.name,
+            sort.as_str().replace('_', " "),
+            match min_games {
+                0 => String::new(),
+                n => format!(" ({}+ games)", n),
+            }
+        )];
+        for (i, s) in standings.iter().enumerate() {
+            lines.push(format!(
+                "{}. {}: {} pts, rating {}, {}W {}L {}D ({:.0}% wins), {:.0} ticks a game",
+                i + 1,
+                s.name,
+                s.total_points,
+                s.rating,
+                s.wins,
+                s.losses,
+                s.draws,
+                s.win_rate * 100.0,
+                s.avg_ticks
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Get all active games as web states
+    pub fn get_active_games(&self) -> Vec<WebGameState> {
+        let mut games: Vec<&Game> = self.active_games.values().collect();
+        games.sort_by_key(|g| (!g.featured, g.created_at));
+        games.into_iter().map(|g| g.to_web_state()).collect()
+    }
+
+    /// Current gauges for `/metrics`
+    pub(crate) fn metrics_sample(&self) -> RoomSample {
+        RoomSample {
+            room: self.room.clone(),
+            active_games: self.active_games.len(),
+            waiting_players: self.waiting_players.len()
+                + self.lobbies.values().map(|l| l.waiting_players.len()).sum::<usize>(),
+        }
+    }
+
+    /// A running game, or a finished one from the archive
+    pub fn get_game(&self, id: Uuid) -> Option<WebGameState> {
+        match self.active_games.get(&id) {
+            Some(game) => Some(game.to_web_state()),
+            None => {
+                let id = id.to_string();
+                self.finished_games.iter().find(|g| g.id == id).cloned()
+            }
+        }
+    }
+
+    /// Get a lobby by code (public or private)
+    pub fn get_lobby(&self, code: &str) -> Option<WebLobbyState> {
+        self.lobbies
+            .get(&code.trim().to_uppercase())
+            .map(|l| l.to_web_state(&self.room))
+    }
+
+    /// Get all public lobbies, newest first
+    pub fn get_public_lobbies(&self) -> Vec<WebLobbyState> {
+        let mut lobbies: Vec<&Lobby> = self.lobbies.values().filter(|l| l.public).collect();
+        lobbies.sort_by_key(|l| std::cmp::Reverse(l.created_at));
+        lobbies.into_iter().map(|l| l.to_web_state(&self.room)).collect()
+    }
+
+    /// Get finished games
+    pub fn get_finished_games(&self) -> &[WebGameState] {
+        &self.finished_games
+    }
+
+    /// The room's storage, for queries that should not hold the manager lock
+    pub fn storage(&self) -> SharedStorage {
+        self.storage.clone()
+    }
+
+    /// Hear about every change in the room from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<BroadcastEvent> {
+        self.broadcast_tx.subscribe()
+    }
+}
+
+pub type SharedGameManager = Arc<Mutex<GameManager>>;
+
+fn not_joined() -> TronError {
+    TronError::new(ErrorCode::NotJoined, "Player not found. Use join_game first.")
+}
+
+fn queue_expired() -> TronError {
+    TronError::new(
+        ErrorCode::NotQueued,
+        "Your queue slot expired because you went quiet for too long. Call join_game to join again.",
+    )
+}
+
+/// Everything known about `name`, for `/api/players/<name>` and
+/// `player_stats`. Their games come from the storage backend's whole
+/// archive, searched without holding the manager lock.
+pub async fn player_profile(manager: &SharedGameManager, name: &str) -> Result<PlayerProfile, TronError> {
+    let storage = manager.lock().await.storage();
+    let filter = GameFilter {
+        player: Some(name.to_string()),
+        level: None,
+        limit: PROFILE_GAMES,
+    };
+    let games = tokio::task::spawn_blocking(move || storage.query_finished_games(&filter))
+        .await
+        .map_err(|e| TronError::new(ErrorCode::Internal, format!("Query failed: {}", e)))?;
+    manager
+        .lock()
+        .await
+        .build_profile(name, &games)
+        .ok_or_else(|| TronError::new(ErrorCode::PlayerNotFound, format!("No player named '{}' has been seen in this room.", name)))
+}
+
+/// Block until a queued player's game starts or `timeout` passes. The
+/// manager lock is only taken for quick checks; in between, this sleeps on
+/// the broadcast channel, which announces every game start.
+pub async fn wait_for_game_start(
+    manager: &SharedGameManager,
+    player_name: &str,
+    timeout: std::time::Duration,
+) -> Result<String, TronError> {
+    let mut rx = {
+        let mut mgr = manager.lock().await;
+        let now = mgr.clock.now();
+        if let Some(session) = mgr.player_sessions.get_mut(player_name) {
+            session.active_at = now;
+        }
+        mgr.broadcast_tx.subscribe()
+    };
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if let Some(msg) = manager.lock().await.game_start_message(player_name)? {
+            return Ok(msg);
+        }
+        match tokio::time::timeout_at(deadline, rx.recv()).await {
+            Err(_) | Ok(Err(broadcast::error::RecvError::Closed)) => break,
+            Ok(_) => continue,
+        }
+    }
+
+    let mgr = manager.lock().await;
+    if let Some(msg) = mgr.game_start_message(player_name)? {
+        return Ok(msg);
+    }
+    Ok(format!(
+        "Still waiting for opponents after {}s ({} players in queue). Call wait_for_game_start again to keep waiting.",
+        timeout.as_secs(),
+        mgr.queue_len(player_name)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::AdminAuth;
+    use crate::testutil;
+
+    fn overrides(json: &str) -> Option<RulesOverride> {
+        Some(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn custom_lobby_rules_are_unranked_without_an_operator_token() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.admin = Arc::new(AdminAuth::new(vec![
+            ("viewer-token".to_string(), Role::Viewer),
+            ("operator-token".to_string(), Role::Operator),
+        ]));
+        let prefs = TagPreferences::default();
+        let mut lobby = |rules: &str, token: Option<&str>| {
+            let msg = mgr.create_lobby(Some(1), overrides(rules), token, false, &prefs).unwrap();
+            let code = msg.split_whitespace().nth(1).unwrap().trim_end_matches('.').to_string();
+            mgr.lobbies[&code].clone()
+        };
+
+        let custom = lobby(r#"{"max_trail_length": 80}"#, None);
+        assert!(custom.unranked);
+        assert_eq!(custom.rules.max_trail_length, 80);
+        assert_eq!(custom.rules_diff, vec!["max_trail_length: 50 -> 80"]);
+        assert!(lobby(r#"{"max_trail_length": 80}"#, Some("viewer-token")).unranked);
+        assert!(!lobby(r#"{"max_trail_length": 80}"#, Some("operator-token")).unranked);
+        // Restating the course defaults is no deviation
+        assert!(!lobby(r#"{"max_trail_length": 50}"#, None).unranked);
+        assert!(!lobby("{}", None).unranked);
+    }
+
+    #[test]
+    fn invalid_lobby_rules_are_refused() {
+        let (mut mgr, _dir) = testutil::manager();
+        let err = mgr
+            .create_lobby(Some(1), overrides(r#"{"team_size": 4}"#), None, false, &TagPreferences::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("team_size"), "{}", err);
+        assert!(mgr.lobbies.is_empty());
+    }
+
+    #[test]
+    fn first_status_after_a_game_is_its_recap() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.leave("Gemini").unwrap();
+
+        let first = mgr.game_status("Claude").unwrap();
+        assert!(first.starts_with("=== GAME OVER"), "{}", first);
+        assert!(first.contains("Winner: Claude"));
+        assert!(first.contains("Story:"));
+        assert!(first.contains("Gemini left the game"));
+        let second = mgr.game_status("Claude").unwrap();
+        assert!(!second.starts_with("=== GAME OVER"), "{}", second);
+
+        let archived = mgr.get_finished_games().last().unwrap();
+        assert_eq!(archived.recap.as_deref(), Some(first.as_str()));
+    }
+
+    #[test]
+    fn every_riding_player_gets_a_move_window_each_tick() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.mode = GameMode::Realtime;
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let mut rx = mgr.subscribe();
+
+        // Only Claude steers; Gemini rides straight on and is told all the same
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.tick_all();
+        mgr.tick_all();
+
+        let windows: Vec<(u32, Vec<String>)> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event {
+                BroadcastEvent::MoveWindow { tick, players, .. } => Some((tick, players)),
+                _ => None,
+            })
+            .collect();
+        let both = vec!["Claude".to_string(), "Gemini".to_string()];
+        assert_eq!(windows, vec![(2, both.clone()), (4, both)]);
+    }
+
+    /// Finish a ranked game between two fresh players: `winner` moves,
+    /// `loser` walks out
+    fn play(mgr: &mut GameManager, winner: &str, loser: &str) {
+        mgr.join(winner.to_string(), TagPreferences::default()).unwrap();
+        mgr.join(loser.to_string(), TagPreferences::default()).unwrap();
+        mgr.move_player(winner, Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.leave(loser).unwrap();
+    }
+
+    /// Claude and Gemini ride side by side into a two-tick time limit and
+    /// share it
+    fn draw(mgr: &mut GameManager) {
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let game_id = mgr.player_sessions["Claude"].game_id.unwrap();
+        let game = mgr.active_games.get_mut(&game_id).unwrap();
+        game.rules.max_ticks = Some(2);
+        game.rules.tie_break = crate::rules::TieBreak::Draw;
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.move_player("Gemini", Steer::Turn(SteerAction::Straight)).unwrap();
+        assert_eq!(mgr.get_game(game_id).unwrap().winner, None);
+    }
+
+    #[test]
+    fn leaderboard_counts_losses_draws_streaks_and_the_win_rate() {
+        let (mut mgr, _dir) = testutil::manager();
+        // Winners move up a level; keep the two on the same one to meet again
+        let level_one = |mgr: &mut GameManager| {
+            for session in mgr.player_sessions.values_mut() {
+                session.current_level = 1;
+            }
+        };
+        for result in ["Claude", "Claude", "draw", "Claude", "Gemini", "Claude"] {
+            level_one(&mut mgr);
+            match result {
+                "Claude" => play(&mut mgr, "Claude", "Gemini"),
+                "Gemini" => play(&mut mgr, "Gemini", "Claude"),
+                _ => draw(&mut mgr),
+            }
+        }
+        play(&mut mgr, "Grok", "Llama");
+
+        let board = mgr.get_leaderboard(LeaderboardSort::WinRate, 0);
+        let record = |name: &str| {
+            let e = board.iter().find(|e| e.name == name).unwrap();
+            (e.games_played, e.wins, e.losses, e.draws, e.current_streak, e.best_streak)
+        };
+        assert_eq!(record("Claude"), (6, 4, 1, 1, 1, 2));
+        assert_eq!(record("Gemini"), (6, 1, 4, 1, 0, 1));
+        assert_eq!(record("Llama"), (1, 0, 1, 0, 0, 0));
+        let claude = board.iter().find(|e| e.name == "Claude").unwrap();
+        assert!((claude.win_rate - 4.0 / 6.0).abs() < 1e-9);
+        let last_played = claude.last_played.as_deref().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(last_played).is_ok(), "{}", last_played);
+
+        // By win rate, one lucky game only counts with no minimum
+        let order = |min_games| -> Vec<String> {
+            mgr.get_leaderboard(LeaderboardSort::WinRate, min_games).into_iter().map(|e| e.name).collect()
+        };
+        assert_eq!(order(0), ["Grok", "Claude", "Gemini", "Llama"]);
+        assert_eq!(order(LeaderboardSort::WinRate.default_min_games()), ["Claude", "Gemini"]);
+
+        // Served with the new fields; older files without them still load
+        let served = serde_json::to_value(claude).unwrap();
+        for field in ["losses", "draws", "current_streak", "best_streak", "last_played", "win_rate"] {
+            assert!(served.get(field).is_some(), "no {} in {}", field, served);
+        }
+        let old: LeaderboardEntry = serde_json::from_str(
+            r#"{"name":"Old","wins":2,"total_points":10,"games_played":3,"highest_level":1}"#,
+        )
+        .unwrap();
+        assert_eq!((old.losses, old.draws, old.best_streak, old.last_played), (0, 0, 0, None));
+    }
+
+    #[test]
+    fn checksum_does_not_depend_on_the_order_results_came_in() {
+        let games = [("Claude", "Gemini"), ("Grok", "Llama"), ("Mistral", "Qwen")];
+        let checksum = |order: &[usize]| {
+            let (mut mgr, _dir) = testutil::manager();
+            for &i in order {
+                play(&mut mgr, games[i].0, games[i].1);
+            }
+            assert_eq!(mgr.leaderboard.len(), 6);
+            // The one field that records when, not what
+            for entry in mgr.leaderboard.values_mut() {
+                entry.last_played = Some("2026-01-01T00:00:00+00:00".to_string());
+            }
+            leaderboard_checksum(mgr.leaderboard.values())
+        };
+
+        let forward = checksum(&[0, 1, 2]);
+        assert_eq!(checksum(&[2, 1, 0]), forward);
+        assert_eq!(checksum(&[1, 2, 0]), forward);
+    }
+
+    #[test]
+    fn any_single_field_change_alters_the_checksum() {
+        type Change = fn(&mut LeaderboardEntry);
+        let mut base = LeaderboardEntry {
+            name: "Claude".to_string(),
+            wins: 3,
+            total_points: 900,
+            games_played: 5,
+            highest_level: 2,
+            rating: 1234,
+            losses: 1,
+            draws: 1,
+            current_streak: 2,
+            best_streak: 3,
+            last_played: Some("2026-01-01T00:00:00+00:00".to_string()),
+            kills: 4,
+            ..Default::default()
+        };
+        base.levels.insert(1, LevelStats {
+            games_played: 5,
+            wins: 3,
+            losses: 1,
+            draws: 1,
+            total_points: 900,
+            total_ticks: 400,
+        });
+        let other = LeaderboardEntry {
+            name: "Gemini".to_string(),
+            ..Default::default()
+        };
+        let checksum = |entry: &LeaderboardEntry| leaderboard_checksum([entry, &other]);
+        let changes: [(&str, Change); 19] = [
+            ("name", |e| e.name.push('2')),
+            ("wins", |e| e.wins += 1),
+            ("total_points", |e| e.total_points += 1),
+            ("games_played", |e| e.games_played += 1),
+            ("highest_level", |e| e.highest_level += 1),
+            ("rating", |e| e.rating -= 1),
+            ("losses", |e| e.losses += 1),
+            ("draws", |e| e.draws += 1),
+            ("current_streak", |e| e.current_streak += 1),
+            ("best_streak", |e| e.best_streak += 1),
+            ("kills", |e| e.kills += 1),
+            ("last_played", |e| e.last_played = None),
+            ("levels", |e| e.levels.clear()),
+            ("levels.games_played", |e| e.levels.get_mut(&1).unwrap().games_played += 1),
+            ("levels.wins", |e| e.levels.get_mut(&1).unwrap().wins += 1),
+            ("levels.losses", |e| e.levels.get_mut(&1).unwrap().losses += 1),
+            ("levels.draws", |e| e.levels.get_mut(&1).unwrap().draws += 1),
+            ("levels.total_points", |e| e.levels.get_mut(&1).unwrap().total_points += 1),
+            ("levels.total_ticks", |e| e.levels.get_mut(&1).unwrap().total_ticks += 1),
+        ];
+
+        let original = checksum(&base);
+        for (field, change) in changes {
+            let mut entry = base.clone();
+            change(&mut entry);
+            assert_ne!(checksum(&entry), original, "changing {} kept the checksum", field);
+        }
+        // Filled in when served, so not part of the record
+        let mut served = base.clone();
+        served.rating_change_7d = 15;
+        served.win_rate = 0.6;
+        assert_eq!(checksum(&served), original);
+        // Nor is the order the entries come in
+        assert_eq!(leaderboard_checksum([&other, &base]), original);
+    }
+
+    fn reopen(dir: &testutil::ScratchDir) -> GameManager {
+        let storage = Arc::new(crate::storage::JsonStorage::new(dir.path()));
+        GameManager::new(dir.path(), storage).0
+    }
+
+    #[test]
+    fn players_ranked_below_the_top_ten_survive_a_restart() {
+        let (mut mgr, dir) = testutil::manager();
+        let names: Vec<String> = (1..=15).map(|i| format!("Player{:02}", i)).collect();
+        for (i, name) in names.iter().enumerate() {
+            let level = LevelStats {
+                games_played: 2,
+                total_points: 100 * i as u32,
+                ..LevelStats::default()
+            };
+            let entry = LeaderboardEntry {
+                name: name.clone(),
+                total_points: 100 * i as u32,
+                games_played: 2,
+                levels: BTreeMap::from([(1, level)]),
+                ..LeaderboardEntry::default()
+            };
+            mgr.leaderboard.insert(name.clone(), entry);
+        }
+        mgr.save_leaderboard();
+        drop(mgr);
+
+        let mgr = reopen(&dir);
+        assert_eq!(mgr.leaderboard.len(), 15);
+        for name in &names {
+            assert_eq!(mgr.leaderboard[name].levels[&1].games_played, 2, "{} lost their record", name);
+        }
+        assert_eq!(mgr.get_leaderboard(LeaderboardSort::Points, 0).len(), 10);
+        assert_eq!(mgr.get_leaderboard_full(LeaderboardSort::Points, 0).len(), 15);
+    }
+
+    #[test]
+    fn old_truncated_leaderboards_load_and_are_rewritten_in_full() {
+        let dir = testutil::ScratchDir::new();
+        let old: Vec<LeaderboardEntry> = (1..=10)
+            .map(|i| LeaderboardEntry {
+                name: format!("Player{:02}", i),
+                wins: i,
+                games_played: i,
+                ..LeaderboardEntry::default()
+            })
+            .collect();
+        std::fs::write(dir.path().join("leaderboard.json"), serde_json::to_string(&old).unwrap()).unwrap();
+
+        let mut mgr = reopen(&dir);
+        assert_eq!(mgr.leaderboard.len(), 10);
+        play(&mut mgr, "Claude", "Gemini");
+        drop(mgr);
+
+        let mgr = reopen(&dir);
+        assert_eq!(mgr.leaderboard.len(), 12);
+        assert_eq!(mgr.leaderboard["Player07"].wins, 7);
+    }
+
+    #[test]
+    fn a_game_interrupted_by_a_restart_carries_on_where_it_was() {
+        let (mut mgr, dir) = testutil::manager();
+        for name in ["Claude", "Gemini", "Grok"] {
+            mgr.join(name.to_string(), TagPreferences::default()).unwrap();
+        }
+        let game_id = mgr.player_sessions["Claude"].game_id.unwrap();
+        for _ in 0..2 {
+            mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+            mgr.move_player("Gemini", Steer::Turn(SteerAction::Straight)).unwrap();
+        }
+        let token = mgr.player_sessions["Claude"].resume_token.to_string();
+        let look = |mgr: &GameManager, name: &str| mgr.look(name, LookFormat::Text, LookOptions::default()).unwrap();
+        let before = (look(&mgr, "Claude"), look(&mgr, "Gemini"));
+        mgr.shutdown();
+        drop(mgr);
+
+        let mut mgr = reopen(&dir);
+        let game = &mgr.active_games[&game_id];
+        assert_eq!((game.status, game.tick), (GameStatus::Running, 4));
+        assert_eq!((look(&mgr, "Claude"), look(&mgr, "Gemini")), before);
+        assert_eq!(mgr.waiting_players, ["Grok"]);
+        assert!(mgr.resume("Claude", &token).unwrap().is_some());
+
+        // Play on to a result that counts like any other
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        assert_eq!(mgr.active_games[&game_id].tick, 5);
+        mgr.leave("Gemini").unwrap();
+        let game = mgr.get_game(game_id).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner.map(|i| game.players[i].name.as_str()), Some("Claude"));
+        assert_eq!(mgr.leaderboard["Claude"].wins, 1);
+        assert_eq!(mgr.leaderboard["Gemini"].games_played, 1);
+    }
+
+    #[test]
+    fn resume_takes_a_player_back_into_their_running_game() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        let token = mgr.player_sessions["Claude"].resume_token.to_string();
+
+        let summary = mgr.resume("Claude", &format!(" {} ", token)).unwrap().unwrap();
+        assert!(summary.starts_with("Back in your game on "), "{}", summary);
+        assert!(summary.contains("at tick 1, 2 of 2 players still riding"), "{}", summary);
+        assert!(summary.contains("You are ALIVE at"), "{}", summary);
+    }
+
+    #[test]
+    fn resume_without_a_running_game_has_nothing_to_summarize() {
+        let (mut mgr, _dir) = testutil::manager();
+        play(&mut mgr, "Claude", "Gemini");
+        let token = mgr.player_sessions["Claude"].resume_token.to_string();
+
+        // The caller joins as usual instead
+        assert_eq!(mgr.resume("Claude", &token).unwrap(), None);
+    }
+
+    #[test]
+    fn resume_refuses_a_token_that_is_not_the_players() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let theirs = mgr.player_sessions["Gemini"].resume_token.to_string();
+        let mine = mgr.player_sessions["Claude"].resume_token.to_string();
+
+        for token in [theirs.as_str(), "not-a-token", "", &mine[..mine.len() - 1]] {
+            let err = mgr.resume("Claude", token).unwrap_err();
+            assert_eq!(err.code, ErrorCode::NotAuthorized, "{:?}", token);
+        }
+        assert_eq!(mgr.resume("Nobody", &mine).unwrap_err().code, ErrorCode::NotAuthorized);
+    }
+
+    #[test]
+    fn failed_batch_step_leaves_lobbies_and_queue_untouched() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.create_lobby(Some(2), None, None, false, &TagPreferences::default()).unwrap();
+        let state = |mgr: &GameManager| (serde_json::to_value(&mgr.lobbies).unwrap(), mgr.waiting_players.clone());
+        let before = state(&mgr);
+        let steps = |bad_lobby: &str| -> Vec<BatchOp> {
+            serde_json::from_value(serde_json::json!([
+                {"op": "create_lobby", "alias": "final", "level": 1},
+                {"op": "add_bot", "lobby": bad_lobby, "difficulty": "hard"},
+                {"op": "set_speed", "lobby": "final", "move_timeout_secs": 30},
+            ]))
+            .unwrap()
+        };
+
+        let failure = mgr.run_batch(steps("NOSUCH")).unwrap_err();
+        assert_eq!((failure.step, failure.op, failure.code), (1, "add_bot", ErrorCode::LobbyNotFound));
+        assert_eq!(state(&mgr), before);
+
+        // The same batch with the step pointed at its own lobby goes through
+        let report = mgr.run_batch(steps("final")).unwrap();
+        assert_eq!(report.results.len(), 3);
+        let lobby = &mgr.lobbies[&report.lobbies["final"]];
+        assert_eq!(lobby.bots.len(), 1);
+        assert_eq!(lobby.rules.move_timeout_secs, Some(30));
+        assert_eq!(mgr.lobbies.len(), 2);
+        assert_eq!(mgr.waiting_players, ["Claude"]);
+    }
+
+    /// A room in the middle of things: a finished game on the leaderboard,
+    /// a game and a practice game running, one player queued and one
+    /// waiting in a lobby
+    fn busy_manager() -> (GameManager, testutil::ScratchDir) {
+        let (mut mgr, dir) = testutil::manager();
+        let prefs = TagPreferences::default();
+        play(&mut mgr, "Claude", "Gemini");
+        mgr.join("Grok".to_string(), prefs.clone()).unwrap();
+        mgr.join("Llama".to_string(), prefs.clone()).unwrap();
+        mgr.move_player("Grok", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.join_vs_bot("Qwen".to_string(), Difficulty::Adaptive).unwrap();
+        mgr.join("Mistral".to_string(), prefs.clone()).unwrap();
+        let msg = mgr.create_lobby(Some(2), overrides(r#"{"lives": 2}"#), None, false, &prefs).unwrap();
+        let code = msg.split_whitespace().nth(1).unwrap().trim_end_matches('.').to_string();
+        mgr.join_lobby(&code, "Phi".to_string(), None).unwrap();
+        (mgr, dir)
+    }
+
+    /// An export as JSON, minus what an import is meant to change: the
+    /// export time and the move clocks. Games and lobbies in a fixed order.
+    fn comparable(snapshot: &StateSnapshot) -> serde_json::Value {
+        let mut snapshot = snapshot.clone();
+        snapshot.exported_at = chrono::DateTime::UNIX_EPOCH;
+        snapshot.active_games.sort_by_key(|g| g.id);
+        snapshot.lobbies.sort_by(|a, b| a.code.cmp(&b.code));
+        for player in snapshot.active_games.iter_mut().flat_map(|g| g.players.iter_mut()) {
+            player.last_move_at = chrono::DateTime::UNIX_EPOCH;
+        }
+        serde_json::to_value(&snapshot).unwrap()
+    }
+
+    #[test]
+    fn exported_state_imports_into_a_fresh_manager_unchanged() {
+        let (mgr, _dir) = busy_manager();
+        assert!(mgr.audit().is_empty(), "{:?}", mgr.audit());
+        let exported = mgr.export_state();
+        assert_eq!(exported.active_games.len(), 2);
+        assert_eq!(exported.waiting_players, ["Mistral"]);
+
+        let (mut fresh, _fresh_dir) = testutil::manager();
+        let snapshot: StateSnapshot = serde_json::from_str(&serde_json::to_string(&exported).unwrap()).unwrap();
+        fresh.import_state(snapshot).unwrap();
+
+        assert!(fresh.audit().is_empty(), "{:?}", fresh.audit());
+        assert_eq!(comparable(&fresh.export_state()), comparable(&exported));
+        // The adopted games go on where they were
+        let game_id = fresh.player_sessions["Llama"].game_id.unwrap();
+        assert_eq!(fresh.active_games[&game_id].tick, 1);
+        fresh.move_player("Llama", Steer::Turn(SteerAction::Straight)).unwrap();
+        assert_eq!(fresh.active_games[&game_id].tick, 2);
+    }
+
+    #[test]
+    fn import_refuses_another_schema_version() {
+        let (mgr, _dir) = busy_manager();
+        let mut snapshot = mgr.export_state();
+        snapshot.schema_version = SCHEMA_VERSION + 1;
+
+        let (mut fresh, _fresh_dir) = testutil::manager();
+        let problems = fresh.import_state(snapshot).unwrap_err();
+        assert_eq!(problems, [format!("schema version {} is not {}", SCHEMA_VERSION + 1, SCHEMA_VERSION)]);
+        assert!(fresh.active_games.is_empty() && fresh.leaderboard.is_empty());
+    }
+
+    #[test]
+    fn import_refuses_a_snapshot_that_fails_the_audit() {
+        let (mgr, _dir) = busy_manager();
+        let mut snapshot = mgr.export_state();
+        snapshot.waiting_players.push("Ghost".to_string());
+        snapshot.waiting_players.push("Mistral".to_string());
+
+        let (mut fresh, _fresh_dir) = testutil::manager();
+        let before = comparable(&fresh.export_state());
+        let problems = fresh.import_state(snapshot).unwrap_err();
+        assert!(problems.contains(&"'Ghost' is queued without a session".to_string()), "{:?}", problems);
+        assert!(problems.contains(&"'Mistral' is queued more than once".to_string()), "{:?}", problems);
+        assert_eq!(comparable(&fresh.export_state()), before);
+    }
+
+    #[test]
+    fn player_joining_during_the_countdown_is_in_the_game() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.start_delay_secs = 10;
+        mgr.clock = Clock::manual();
+        let mut rx = mgr.subscribe();
+        let prefs = TagPreferences::default();
+        mgr.join("Claude".to_string(), prefs.clone()).unwrap();
+        mgr.join("Gemini".to_string(), prefs.clone()).unwrap();
+        assert!(mgr.active_games.is_empty());
+        let countdowns: Vec<(Vec<String>, u64)> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event {
+                BroadcastEvent::MatchCountdown { players, starts_in_secs, .. } => Some((players, starts_in_secs)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(countdowns, [(vec!["Claude".to_string(), "Gemini".to_string()], 10)]);
+
+        mgr.clock.advance(chrono::Duration::seconds(6));
+        mgr.tick();
+        assert!(mgr.active_games.is_empty());
+        let reply = mgr.join("Grok".to_string(), prefs).unwrap();
+        assert!(reply.contains("The game starts in 4s"), "{}", reply);
+
+        mgr.clock.advance(chrono::Duration::seconds(4));
+        mgr.tick();
+        let game = mgr.active_games.values().next().expect("the countdown is over");
+        let mut players: Vec<&str> = game.players.iter().map(|p| p.name.as_str()).collect();
+        players.sort();
+        assert_eq!(players, ["Claude", "Gemini", "Grok"]);
+        assert!(mgr.waiting_players.is_empty());
+    }
+
+    #[test]
+    fn quiet_queued_players_lose_their_slot_after_the_ttl() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.queue_ttl_secs = Some(300);
+        mgr.clock = Clock::manual();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        let mut rx = mgr.subscribe();
+
+        mgr.clock.advance(chrono::Duration::seconds(299));
+        mgr.tick();
+        assert_eq!(mgr.waiting_players, ["Claude"]);
+
+        mgr.clock.advance(chrono::Duration::seconds(1));
+        mgr.tick();
+        assert!(mgr.waiting_players.is_empty());
+        let expired: Vec<String> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event {
+                BroadcastEvent::QueueExpired { player, .. } => Some(player),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(expired, ["Claude"]);
+        let err = mgr.game_status("Claude").unwrap_err();
+        assert!(err.message.contains("queue slot expired"), "{}", err);
+
+        // Joining again puts them back in the queue
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        assert_eq!(mgr.waiting_players, ["Claude"]);
+    }
+
+    #[test]
+    fn joining_again_while_queued_refreshes_the_slot() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.queue_ttl_secs = Some(300);
+        mgr.clock = Clock::manual();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+
+        mgr.clock.advance(chrono::Duration::seconds(200));
+        let reply = mgr.refresh_queue("Claude").unwrap();
+        assert!(reply.starts_with("Still queued; your slot was refreshed"), "{}", reply);
+
+        // 400s after the first join but only 200s after the second
+        mgr.clock.advance(chrono::Duration::seconds(200));
+        mgr.tick();
+        assert_eq!(mgr.waiting_players, ["Claude"]);
+        mgr.clock.advance(chrono::Duration::seconds(100));
+        mgr.tick();
+        assert!(mgr.waiting_players.is_empty());
+    }
+
+    /// Claude and Gemini one move into a game, under `policy`
+    fn started(policy: DisconnectPolicy) -> (GameManager, testutil::ScratchDir) {
+        let (mut mgr, dir) = testutil::manager();
+        mgr.disconnect_policy = policy;
+        mgr.clock = Clock::manual();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        (mgr, dir)
+    }
+
+    /// Whether `name` is still riding in a running game
+    fn riding(mgr: &GameManager, name: &str) -> bool {
+        mgr.seat(name).is_ok_and(|(id, idx)| {
+            mgr.active_games
+                .get(&id)
+                .is_some_and(|g| g.status == GameStatus::Running && g.players[idx].alive)
+        })
+    }
+
+    #[test]
+    fn disconnecting_while_queued_gives_up_the_slot_under_every_policy() {
+        for policy in [DisconnectPolicy::Keep, DisconnectPolicy::Grace, DisconnectPolicy::Forfeit] {
+            let (mut mgr, _dir) = testutil::manager();
+            mgr.disconnect_policy = policy;
+            mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+            mgr.handle_disconnect("Claude");
+            assert!(mgr.waiting_players.is_empty(), "{:?}", policy);
+            assert_eq!(mgr.queue_len("Claude"), 0, "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn disconnecting_mid_game_keeps_or_forfeits_the_cycle() {
+        let (mut mgr, _dir) = started(DisconnectPolicy::Keep);
+        mgr.handle_disconnect("Gemini");
+        mgr.clock.advance(chrono::Duration::seconds(600));
+        mgr.tick();
+        assert!(riding(&mgr, "Gemini"));
+
+        let (mut mgr, _dir) = started(DisconnectPolicy::Forfeit);
+        mgr.handle_disconnect("Gemini");
+        assert!(!riding(&mgr, "Gemini"));
+        let finished = mgr.get_finished_games().last().unwrap();
+        assert_eq!(finished.winner.map(|w| finished.players[w].name.as_str()), Some("Claude"));
+    }
+
+    #[test]
+    fn disconnected_players_forfeit_unless_they_resume_within_the_grace() {
+        let (mut mgr, _dir) = started(DisconnectPolicy::Grace);
+        mgr.disconnect_grace_secs = 30;
+        mgr.handle_disconnect("Gemini");
+        mgr.clock.advance(chrono::Duration::seconds(29));
+        mgr.tick();
+        assert!(riding(&mgr, "Gemini"));
+        mgr.clock.advance(chrono::Duration::seconds(1));
+        mgr.tick();
+        assert!(!riding(&mgr, "Gemini"));
+        assert!(mgr.active_games.is_empty());
+
+        // Back in time, the cycle rides on
+        let (mut mgr, _dir) = started(DisconnectPolicy::Grace);
+        mgr.disconnect_grace_secs = 30;
+        mgr.handle_disconnect("Gemini");
+        mgr.clock.advance(chrono::Duration::seconds(20));
+        let token = mgr.player_sessions["Gemini"].resume_token.to_string();
+        mgr.resume("Gemini", &token).unwrap();
+        mgr.clock.advance(chrono::Duration::seconds(60));
+        mgr.tick();
+        assert!(riding(&mgr, "Gemini"));
+    }
+
+    /// Claude, alone in the queue past the bot-fill wait, now in a game
+    /// against a bot; the game and the bot's seat
+    fn bot_filled(count_bots: bool) -> (GameManager, testutil::ScratchDir, Uuid, usize) {
+        let (mut mgr, dir) = testutil::manager();
+        mgr.clock = Clock::manual();
+        mgr.bot_fill_secs = Some(10);
+        mgr.count_bots = count_bots;
+        let reply = mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        assert!(reply.contains("a bot will fill the game"), "{}", reply);
+        mgr.clock.advance(chrono::Duration::seconds(9));
+        mgr.tick();
+        assert!(mgr.active_games.is_empty());
+
+        mgr.clock.advance(chrono::Duration::seconds(1));
+        mgr.tick();
+        let game = mgr.active_games.values().next().expect("a bot filled the game");
+        let bot = game.players.iter().position(|p| p.bot.is_some()).unwrap();
+        assert_eq!(game.players[bot].name, "Bot (medium)");
+        assert!(game.to_web_state().players[bot].bot);
+        let id = game.id;
+        (mgr, dir, id, bot)
+    }
+
+    #[test]
+    fn bot_wins_when_the_human_drives_into_the_wall() {
+        let (mut mgr, _dir, game_id, bot) = bot_filled(false);
+        for _ in 0..200 {
+            if !mgr.active_games.contains_key(&game_id) {
+                break;
+            }
+            mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        }
+
+        let game = mgr.get_game(game_id).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(bot));
+        // Bot games stay off the leaderboard
+        assert!(mgr.leaderboard.is_empty());
+    }
+
+    #[test]
+    fn server_tick_crashes_a_player_who_stopped_steering() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.idle = IdlePolicy {
+            after_secs: Some(30),
+            action: crate::game::IdleAction::Crash,
+            ..IdlePolicy::default()
+        };
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let game_id = *mgr.active_games.keys().next().unwrap();
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.tick();
+        assert!(mgr.active_games.contains_key(&game_id));
+
+        // Gemini went silent half a minute ago; Claude still steers
+        let game = mgr.active_games.get_mut(&game_id).unwrap();
+        let gemini = game.players.iter().position(|p| p.name == "Gemini").unwrap();
+        game.players[gemini].last_move_at -= chrono::Duration::seconds(31);
+        mgr.tick();
+
+        let game = mgr.get_game(game_id).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(1 - gemini));
+        assert_eq!(mgr.leaderboard["Claude"].wins, 1);
+    }
+
+    #[test]
+    fn human_beats_a_boxed_in_bot() {
+        let (mut mgr, _dir, game_id, bot) = bot_filled(true);
+        let game = mgr.active_games.get_mut(&game_id).unwrap();
+        let (x, y) = (game.players[bot].x, game.players[bot].y);
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some(row) = game.grid.get_mut((y + dy) as usize)
+                && let Some(cell) = row.get_mut((x + dx) as usize)
+            {
+                *cell = crate::game::Cell::Wall;
+            }
+        }
+
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+
+        let game = mgr.get_game(game_id).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.players[game.winner.unwrap()].name, "Claude");
+        // With count_bots the game is ranked, bot and all
+        assert_eq!(mgr.leaderboard["Claude"].wins, 1);
+        assert_eq!(mgr.leaderboard["Bot (medium)"].losses, 1);
+    }
+
+    #[test]
+    fn adaptive_bot_eases_off_a_losing_player_and_remembers() {
+        let (mut mgr, dir) = testutil::manager();
+        mgr.clock = Clock::manual();
+        let mut skills = vec![AdaptiveDifficulty::default().skill];
+        for _ in 0..3 {
+            mgr.join_vs_bot("Qwen".to_string(), Difficulty::Adaptive).unwrap();
+            mgr.move_player("Qwen", Steer::Turn(SteerAction::Straight)).unwrap();
+            mgr.leave("Qwen").unwrap();
+            skills.push(mgr.practice["Qwen"].skill);
+            mgr.clock.advance(chrono::Duration::hours(1));
+        }
+        assert!(skills.windows(2).all(|w| w[1] < w[0]), "{:?}", skills);
+        drop(mgr);
+
+        let mgr = reopen(&dir);
+        assert_eq!(mgr.practice["Qwen"].skill, skills[3]);
+        assert_eq!(mgr.practice["Qwen"].recent, [false, false, false]);
+    }
+
+    #[test]
+    fn crash_cooldown_is_longer_for_an_early_crash() {
+        for (base, tick, expected) in [
+            (10, 0, 30),
+            (10, EARLY_CRASH_TICKS - 1, 30),
+            (10, EARLY_CRASH_TICKS, 10),
+            (10, 500, 10),
+            (0, 0, 0),
+            (0, 500, 0),
+        ] {
+            assert_eq!(crash_cooldown_secs(base, tick), expected, "{}s base, crash at tick {}", base, tick);
+        }
+    }
+
+    #[test]
+    fn queued_players_are_matched_within_their_level() {
+        let (mut mgr, _dir) = testutil::manager();
+        // Two veterans who have reached level 3
+        for name in ["Claude", "Gemini"] {
+            mgr.join(name.to_string(), TagPreferences::default()).unwrap();
+            mgr.leave(name).unwrap();
+            mgr.player_sessions.get_mut(name).unwrap().current_level = 3;
+        }
+
+        let reply = mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        assert!(reply.contains("at level 3"), "{}", reply);
+        let reply = mgr.join("Grok".to_string(), TagPreferences::default()).unwrap();
+        assert!(reply.contains("at level 1... (2 players in queue, 1 in your bracket)"), "{}", reply);
+        assert!(mgr.active_games.is_empty());
+
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Llama".to_string(), TagPreferences::default()).unwrap();
+        assert!(mgr.waiting_players.is_empty());
+        let mut games: Vec<(u32, Vec<String>)> = mgr
+            .active_games
+            .values()
+            .map(|g| {
+                let mut names: Vec<String> = g.players.iter().map(|p| p.name.clone()).collect();
+                names.sort();
+                (g.course_level, names)
+            })
+            .collect();
+        games.sort();
+        assert_eq!(
+            games,
+            vec![
+                (1, vec!["Grok".to_string(), "Llama".to_string()]),
+                (3, vec!["Claude".to_string(), "Gemini".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_levels_drag_everyone_to_the_lowest_level() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.mixed_levels = true;
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.leave("Claude").unwrap();
+        mgr.player_sessions.get_mut("Claude").unwrap().current_level = 3;
+
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Grok".to_string(), TagPreferences::default()).unwrap();
+        let game = mgr.active_games.values().next().unwrap();
+        assert_eq!(game.course_level, 1);
+        assert_eq!(game.players.len(), 2);
+    }
+
+    #[test]
+    fn course_info_describes_the_next_course_then_the_current_one() {
+        let (mut mgr, _dir) = testutil::manager();
+        assert_eq!(mgr.course_info("Claude").unwrap_err().code, ErrorCode::NotJoined);
+        for name in ["Claude", "Gemini"] {
+            mgr.join(name.to_string(), TagPreferences::default()).unwrap();
+            mgr.leave(name).unwrap();
+            mgr.player_sessions.get_mut(name).unwrap().current_level = 3;
+        }
+        let course = mgr.course_for_game(3);
+        let size = format!("Size: {}x{} cells", course.width, course.height);
+
+        let info = mgr.course_info("Claude").unwrap();
+        assert!(
+            info.starts_with(&format!(
+                "You are not queued at level 3; your next game will most likely be on {} (level 3).",
+                course.name
+            )),
+            "{}",
+            info
+        );
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        let info = mgr.course_info("Claude").unwrap();
+        assert!(info.starts_with("You are queued at level 3;"), "{}", info);
+        assert!(info.contains(&size), "{}", info);
+
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let info = mgr.course_info("Claude").unwrap();
+        assert!(info.starts_with(&format!("You are playing on {} (level 3).", course.name)), "{}", info);
+        assert!(info.contains(&size), "{}", info);
+        assert!(info.contains("Max trail length: "), "{}", info);
+    }
+
+    #[test]
+    fn level_leaderboards_keep_to_their_own_level() {
+        let (mut mgr, _dir) = testutil::manager();
+        for name in ["Grok", "Llama"] {
+            mgr.join(name.to_string(), TagPreferences::default()).unwrap();
+            mgr.leave(name).unwrap();
+            mgr.player_sessions.get_mut(name).unwrap().current_level = 3;
+        }
+        // Claude beats Gemini on level 1, Grok beats Llama on level 3
+        play(&mut mgr, "Claude", "Gemini");
+        play(&mut mgr, "Grok", "Llama");
+        assert!(mgr.active_games.is_empty());
+
+        let names = |level| -> Vec<(String, u32, u32)> {
+            mgr.get_level_leaderboard(level, LeaderboardSort::Points, 0)
+                .unwrap()
+                .into_iter()
+                .map(|s| (s.name, s.wins, s.losses))
+                .collect()
+        };
+        let mut one = names(1);
+        one.sort();
+        assert_eq!(one, [("Claude".to_string(), 1, 0), ("Gemini".to_string(), 0, 1)]);
+        let mut three = names(3);
+        three.sort();
+        assert_eq!(three, [("Grok".to_string(), 1, 0), ("Llama".to_string(), 0, 1)]);
+        assert!(names(2).is_empty());
+        assert_eq!(mgr.leaderboard["Claude"].levels.keys().collect::<Vec<_>>(), [&1]);
+        assert_eq!(mgr.leaderboard["Grok"].levels.keys().collect::<Vec<_>>(), [&3]);
+
+        let err = mgr.get_level_leaderboard(0, LeaderboardSort::Points, 0).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams);
+    }
+
+    #[test]
+    fn tag_preferences_pick_the_course_or_say_why_not() {
+        let prefs = |prefer: &[&str], avoid: &[&str]| TagPreferences {
+            prefer: prefer.iter().map(|t| t.to_string()).collect(),
+            avoid: avoid.iter().map(|t| t.to_string()).collect(),
+        };
+        let level_three = get_course(3).name;
+        let cases = [
+            // Compatible: the highest course up to their level that suits both
+            (
+                prefs(&["open"], &[]),
+                prefs(&[], &["walls"]),
+                1,
+                "Open Arena was chosen for the players' tag preferences (prefer open; avoid walls).".to_string(),
+            ),
+            (
+                prefs(&["maze"], &[]),
+                prefs(&[], &["maze"]),
+                3,
+                "Players' tag preferences conflict (maze both preferred and avoided), so the course was picked by level.".to_string(),
+            ),
+            (
+                prefs(&["open", "maze"], &[]),
+                TagPreferences::default(),
+                3,
+                "No course up to level 3 matches the players' tag preferences (prefer open, maze), so the course was picked by level.".to_string(),
+            ),
+        ];
+        for (claude, gemini, level, note) in cases {
+            let (mut mgr, _dir) = testutil::manager();
+            for name in ["Claude", "Gemini"] {
+                mgr.join(name.to_string(), TagPreferences::default()).unwrap();
+                mgr.leave(name).unwrap();
+                mgr.player_sessions.get_mut(name).unwrap().current_level = 3;
+            }
+            mgr.join("Claude".to_string(), claude).unwrap();
+            mgr.join("Gemini".to_string(), gemini).unwrap();
+
+            let game = mgr.active_games.values().next().expect("the game started");
+            assert_eq!(game.course_level, level, "{}", note);
+            if level == 3 {
+                assert_eq!(game.course_name, level_three);
+            }
+            let status = mgr.game_status("Gemini").unwrap();
+            assert!(status.contains(&note), "{}", status);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waiting_returns_once_matched_without_holding_the_lock() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let err = wait_for_game_start(&manager, "Claude", std::time::Duration::from_secs(5)).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::NotJoined);
+        manager.lock().await.join("Claude".to_string(), TagPreferences::default()).unwrap();
+
+        let waiting = tokio::spawn({
+            let manager = manager.clone();
+            async move { wait_for_game_start(&manager, "Claude", std::time::Duration::from_secs(60)).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        assert!(!waiting.is_finished());
+        // The waiter leaves the lock free for the opponent's join
+        manager.lock().await.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+
+        let reply = waiting.await.unwrap().unwrap();
+        assert!(reply.starts_with("Your game has STARTED on Open Arena (Level 1) with 2 players!"), "{}", reply);
+        // Already in a game, it answers straight away
+        let reply = wait_for_game_start(&manager, "Gemini", std::time::Duration::from_secs(60)).await.unwrap();
+        assert!(reply.starts_with("Your game has STARTED"), "{}", reply);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waiting_gives_up_at_the_timeout() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        manager.lock().await.join("Claude".to_string(), TagPreferences::default()).unwrap();
+
+        let started = tokio::time::Instant::now();
+        let reply = wait_for_game_start(&manager, "Claude", std::time::Duration::from_secs(30)).await.unwrap();
+
+        assert_eq!(started.elapsed(), std::time::Duration::from_secs(30));
+        assert_eq!(
+            reply,
+            "Still waiting for opponents after 30s (1 players in queue). Call wait_for_game_start again to keep waiting."
+        );
+        manager.lock().await.leave("Claude").unwrap();
+        let err = wait_for_game_start(&manager, "Claude", std::time::Duration::from_secs(30)).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::NotQueued);
+    }
+
+    /// Claude and Gemini's game, over with Gemini's forfeit, on a manual clock
+    fn finished_pair() -> (GameManager, testutil::ScratchDir) {
+        let (mut mgr, dir) = testutil::manager();
+        mgr.clock = Clock::manual();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.leave("Gemini").unwrap();
+        assert!(mgr.active_games.is_empty());
+        (mgr, dir)
+    }
+
+    #[test]
+    fn rematch_starts_on_the_next_level_once_everyone_asks() {
+        let (mut mgr, _dir) = finished_pair();
+        let old_id = mgr.player_sessions["Claude"].game_id.unwrap();
+
+        let reply = mgr.request_rematch("Claude").unwrap();
+        assert!(reply.starts_with("Rematch on Level 2 requested (1 of 2 needed: Claude)"), "{}", reply);
+        assert!(reply.contains("Waiting up to 30s for Gemini"), "{}", reply);
+        assert!(mgr.active_games.is_empty());
+
+        let reply = mgr.request_rematch("Gemini").unwrap();
+        assert!(reply.starts_with("Rematch STARTED on "), "{}", reply);
+        assert!(reply.contains("(Level 2) against Claude"), "{}", reply);
+        assert!(mgr.rematches.is_empty());
+        assert!(mgr.waiting_players.is_empty());
+        let game = mgr.active_games.values().next().unwrap();
+        assert_ne!(game.id, old_id);
+        assert_eq!(game.course_level, 2);
+        let names: Vec<&str> = game.players.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Claude", "Gemini"]);
+    }
+
+    #[test]
+    fn unanswered_rematch_falls_back_to_the_queue() {
+        let (mut mgr, _dir) = finished_pair();
+        mgr.request_rematch("Claude").unwrap();
+
+        mgr.clock.advance(chrono::Duration::seconds(29));
+        mgr.tick();
+        assert!(mgr.rematches.contains_key(&mgr.player_sessions["Claude"].game_id.unwrap()));
+        assert!(mgr.waiting_players.is_empty());
+
+        mgr.clock.advance(chrono::Duration::seconds(1));
+        mgr.tick();
+        assert!(mgr.rematches.is_empty());
+        // Gemini never asked, so stays out of the queue
+        assert_eq!(mgr.waiting_players, ["Claude"]);
+    }
+
+    #[test]
+    fn rematch_is_called_off_when_the_opponent_plays_elsewhere() {
+        let (mut mgr, _dir) = finished_pair();
+        mgr.join("Grok".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        assert_eq!(mgr.active_games.len(), 1);
+
+        let reply = mgr.request_rematch("Claude").unwrap();
+        assert!(reply.starts_with("Your opponents have moved on, so you joined the public queue"), "{}", reply);
+        assert!(mgr.rematches.is_empty());
+        assert_eq!(mgr.waiting_players, ["Claude"]);
+        let err = mgr.request_rematch("Gemini").unwrap_err();
+        assert!(err.message.contains("still running"), "{}", err.message);
+    }
+
+    #[test]
+    fn plans_stop_early_at_an_obstacle_or_a_crash() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        // Open Arena seats are mirror images, so either seat is 3 cells off a wall on its left
+        let turn = |action| Steer::Turn(action);
+
+        let reply = mgr.move_plan("Claude", MovePlan::Repeat(turn(SteerAction::Left), 5)).unwrap();
+        let lines: Vec<&str> = reply.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", reply);
+        assert_eq!(lines[2], "Stopped after 2 of 5 moves: a wall is right ahead.");
+
+        let plan = MovePlan::List(vec![turn(SteerAction::Right), turn(SteerAction::Left), turn(SteerAction::Straight)]);
+        let reply = mgr.move_plan("Claude", plan).unwrap();
+        let lines: Vec<&str> = reply.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", reply);
+        assert_eq!(lines[2], "Stopped after 2 of 3 moves: you crashed.");
+        assert!(mgr.active_games.is_empty());
+        let finished = mgr.get_finished_games().last().unwrap();
+        assert_eq!(finished.winner.map(|w| finished.players[w].name.as_str()), Some("Gemini"));
+        let claude = finished.players.iter().find(|p| p.name == "Claude").unwrap();
+        assert_eq!(claude.distance, 3);
+    }
+
+    #[test]
+    fn chat_is_rate_limited_cut_and_reaches_spectators() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.clock = Clock::manual();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let mut events = mgr.subscribe();
+        let mut watching = SpectatorSession::default();
+        mgr.watch(None, &mut watching).unwrap();
+
+        assert_eq!(mgr.say("Claude", "<b>gg</b>").unwrap(), "Said: \"<b>gg</b>\"");
+        let err = mgr.say("Claude", "again").unwrap_err();
+        assert_eq!(err.code, ErrorCode::RateLimited);
+        assert_eq!(err.message, "You are chatting too fast; say something again in 5s.");
+        mgr.clock.advance(chrono::Duration::seconds(4));
+        assert!(mgr.say("Claude", "again").unwrap_err().message.ends_with("in 1s."));
+        mgr.clock.advance(chrono::Duration::seconds(1));
+        let reply = mgr.say("Claude", &"a".repeat(validation::MAX_CHAT_LEN + 20)).unwrap();
+        assert_eq!(reply, format!("Said (cut to 140 characters): \"{}\"", "a".repeat(validation::MAX_CHAT_LEN)));
+
+        // The web UI gets each message escaped
+        let mut said = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let BroadcastEvent::Chat { message, .. } = event {
+                said.push(message.text);
+            }
+        }
+        assert_eq!(said, ["&lt;b&gt;gg&lt;/b&gt;".to_string(), "a".repeat(validation::MAX_CHAT_LEN)]);
+        // The opponent and spectators see it in their next look
+        let look = mgr.look("Gemini", LookFormat::Text, LookOptions::default()).unwrap();
+        assert!(look.contains("Chat:\n  Claude (tick 0): \"<b>gg</b>\""), "{}", look);
+        let news = mgr.watch(None, &mut watching).unwrap();
+        assert!(news.contains("chat: Claude (tick 0): \"<b>gg</b>\""), "{}", news);
+        assert!(mgr.spectate(None).unwrap().contains("Claude (tick 0): \"<b>gg</b>\""));
+    }
+
+    #[test]
+    fn spectators_follow_a_game_without_joining_it() {
+        let (mut mgr, _dir) = testutil::manager();
+        assert_eq!(mgr.spectate(None).unwrap_err().code, ErrorCode::GameNotFound);
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let id = mgr.active_games.keys().next().unwrap().to_string();
+
+        let view = mgr.spectate(None).unwrap();
+        assert!(view.starts_with(&format!("Spectating Open Arena (Level 1), game {}: running, tick 0, 2/2 alive.", id)), "{}", view);
+        assert!(view.contains("Players:\n  A "), "{}", view);
+        assert_eq!(mgr.spectate(Some(&id)).unwrap(), view);
+
+        let mut seen = SpectatorSession::default();
+        let first = mgr.watch(None, &mut seen).unwrap();
+        assert!(first.contains("Since your last watch:\n  tick 0: Claude and Gemini launched"), "{}", first);
+        assert!(mgr.watch(None, &mut seen).unwrap().ends_with("Nothing new since your last watch."));
+        mgr.leave("Gemini").unwrap();
+        // The game is over, so the watch turns into its recap
+        let over = mgr.watch(None, &mut seen).unwrap();
+        assert!(over.starts_with("=== GAME OVER"), "{}", over);
+        assert_eq!(mgr.spectate(Some(&id)).unwrap(), over);
+
+        // Watching left no trace among the players
+        assert_eq!(mgr.player_sessions.len(), 2);
+        assert!(mgr.waiting_players.is_empty());
+        assert!(mgr.leaderboard.keys().all(|n| n == "Claude" || n == "Gemini"), "{:?}", mgr.leaderboard.keys());
+    }
+
+    #[test]
+    fn leaving_the_queue_frees_the_slot() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        let msg = mgr.leave("Claude").unwrap();
+        assert!(msg.starts_with("Left the queue"), "{}", msg);
+        assert_eq!(mgr.queue_len("Claude"), 0);
+        // Nothing left to leave
+        assert_eq!(mgr.leave("Claude").unwrap_err().code, ErrorCode::NotQueued);
+    }
+
+    #[test]
+    fn forfeits_lose_the_game_and_the_last_one_ends_it() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.min_players = 3;
+        for name in ["Claude", "Gemini", "Grok"] {
+            mgr.join(name.to_string(), TagPreferences::default()).unwrap();
+        }
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+
+        let msg = mgr.leave("Grok").unwrap();
+        assert!(msg.contains("continues for the others"), "{}", msg);
+        assert!(!riding(&mgr, "Grok"));
+        assert!(riding(&mgr, "Claude") && riding(&mgr, "Gemini"));
+
+        let msg = mgr.leave("Gemini").unwrap();
+        assert!(msg.contains("that ended the game"), "{}", msg);
+        assert!(mgr.active_games.is_empty());
+        let finished = mgr.get_finished_games().last().unwrap();
+        assert_eq!(finished.winner.map(|w| finished.players[w].name.as_str()), Some("Claude"));
+    }
+
+    /// A manager that starts exhibitions after five quiet minutes, on a
+    /// manual clock
+    fn exhibiting() -> (GameManager, testutil::ScratchDir) {
+        let (mut mgr, dir) = testutil::manager();
+        mgr.exhibition_mins = Some(5);
+        mgr.clock = Clock::manual();
+        mgr.quiet_since = mgr.clock.now();
+        (mgr, dir)
+    }
+
+    #[test]
+    fn exhibition_starts_after_the_quiet_minutes() {
+        let (mut mgr, _dir) = exhibiting();
+        mgr.tick();
+        mgr.clock.advance(chrono::Duration::minutes(4));
+        mgr.tick();
+        assert!(mgr.active_games.is_empty());
+
+        mgr.clock.advance(chrono::Duration::minutes(1));
+        mgr.tick();
+        let games: Vec<&Game> = mgr.active_games.values().collect();
+        assert_eq!(games.len(), 1);
+        assert!(games[0].exhibition && games[0].featured);
+    }
+
+    #[test]
+    fn joining_player_ends_the_exhibition() {
+        let (mut mgr, _dir) = exhibiting();
+        mgr.clock.advance(chrono::Duration::minutes(5));
+        mgr.tick();
+        assert_eq!(mgr.active_games.len(), 1);
+
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        assert!(mgr.active_games.is_empty());
+        assert_eq!(mgr.queue_len("Claude"), 1);
+        // Nor does another start while Claude waits
+        mgr.queue_ttl_secs = None;
+        mgr.clock.advance(chrono::Duration::minutes(10));
+        mgr.tick();
+        assert!(mgr.active_games.is_empty());
+    }
+
+    #[test]
+    fn exhibitions_leave_no_stats_behind() {
+        let (mut mgr, _dir) = exhibiting();
+        mgr.clock.advance(chrono::Duration::minutes(5));
+        mgr.tick();
+        let id = *mgr.active_games.keys().next().unwrap();
+        for _ in 0..20 {
+            mgr.tick();
+        }
+        // Played out to the end, as at its time limit
+        mgr.active_games.get_mut(&id).unwrap().end_now();
+        mgr.tick();
+
+        assert!(!mgr.active_games.contains_key(&id));
+        assert!(mgr.leaderboard.is_empty());
+        assert!(mgr.get_finished_games().is_empty());
+        assert!(mgr.storage().load_finished_games(10).is_empty());
+    }
+
+    #[test]
+    fn whoami_follows_a_name_from_unknown_to_finished() {
+        let (mut mgr, _dir) = testutil::manager();
+        mgr.clock = crate::clock::Clock::manual();
+        mgr.disconnect_policy = DisconnectPolicy::Grace;
+        let who = |mgr: &GameManager, name: &str| mgr.whoami(name, Some("Claude"));
+
+        let unknown = mgr.whoami("Claude", None);
+        assert!(unknown.contains("Known: no"), "{}", unknown);
+        assert!(unknown.contains("State: IDLE, not queued or playing"), "{}", unknown);
+        assert!(unknown.contains("Connection: not joined as any player"), "{}", unknown);
+
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.clock.advance(chrono::Duration::seconds(7));
+        let queued = who(&mgr, "Claude");
+        assert!(queued.contains("State: QUEUED in the public queue, position 1 of 1, waiting 7s"), "{}", queued);
+        assert!(queued.contains("Connection: holds 'Claude' and may act for it"), "{}", queued);
+
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        let game_id = *mgr.active_games.keys().next().unwrap();
+        let short = &game_id.to_string()[..8];
+        let playing = who(&mgr, "Claude");
+        assert!(playing.contains(&format!("State: PLAYING game {} on ", short)), "{}", playing);
+        assert!(playing.contains("tick 0, ALIVE, last steered 0s ago"), "{}", playing);
+
+        // Someone else's name, dropped mid-game
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.handle_disconnect("Gemini");
+        mgr.clock.advance(chrono::Duration::seconds(10));
+        let other = who(&mgr, "Gemini");
+        assert!(other.contains("Connection: plays as 'Claude', not 'Gemini'"), "{}", other);
+        let left = mgr.disconnect_grace_secs - 10;
+        assert!(other.contains(&format!("Disconnected: forfeits in {}s unless resumed", left)), "{}", other);
+
+        mgr.leave("Gemini").unwrap();
+        let won = who(&mgr, "Claude");
+        assert!(won.contains("Known: yes, level"), "{}", won);
+        assert!(won.contains("1 games played, 1 wins"), "{}", won);
+        assert!(won.contains(&format!("State: FINISHED game {}: won, score", short)), "{}", won);
+        let lost = who(&mgr, "Gemini");
+        assert!(lost.contains(&format!("State: FINISHED game {}: lost to Claude", short)), "{}", lost);
+        assert!(!lost.contains("Disconnected:"), "{}", lost);
+    }
+}
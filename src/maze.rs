@@ -0,0 +1,201 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::course::GeneratedLayout;
+use crate::game::Direction;
+
+/// Corridor width of a generated maze that doesn't set one
+pub const DEFAULT_CORRIDOR_WIDTH: usize = 2;
+/// Widest corridor a generated maze may have
+pub const MAX_CORRIDOR_WIDTH: usize = 5;
+/// Maze cells across and down a generated maze needs at the least, so the
+/// four corner chambers don't meet
+const MIN_MAZE_CELLS: usize = 4;
+/// One in this many of the walls left standing between cells is knocked
+/// through, so the maze has loops and a cycle isn't sealed into a dead end
+/// by its first wrong turn
+const LOOP_ODDS: u32 = 8;
+
+/// Carve a maze into a `width` x `height` grid whose border the game walls
+/// in. Corridors are `corridor_width` cells wide and one cell of wall apart,
+/// cut by a recursive backtracker from `seed`; each corner is cleared into
+/// a 2x2-cell chamber with a seat in it, in the usual seat order: top-left,
+/// bottom-right, top-right, bottom-left. Columns or rows left over past the
+/// last whole maze cell stay wall. The same arguments always give the same
+/// maze.
+pub fn generate(width: usize, height: usize, corridor_width: usize, seed: u64) -> Result<GeneratedLayout, String> {
+    if !(1..=MAX_CORRIDOR_WIDTH).contains(&corridor_width) {
+        return Err(format!("corridor_width must be between 1 and {}", MAX_CORRIDOR_WIDTH));
+    }
+    let pitch = corridor_width + 1;
+    let (cols, rows) = ((width.saturating_sub(1)) / pitch, (height.saturating_sub(1)) / pitch);
+    if cols < MIN_MAZE_CELLS || rows < MIN_MAZE_CELLS {
+        return Err(format!(
+            "a {}x{} grid is too small for a maze with {}-cell corridors; it needs at least {}x{}",
+            width,
+            height,
+            corridor_width,
+            MIN_MAZE_CELLS * pitch + 1,
+            MIN_MAZE_CELLS * pitch + 1
+        ));
+    }
+
+    // Everything inside the border starts as wall and is cut open
+    let mut open = vec![vec![false; width]; height];
+    let left = |col: usize| 1 + col * pitch;
+    let top = |row: usize| 1 + row * pitch;
+    // Open the maze cells from (c1, r1) to (c2, r2) and the walls between
+    let join = |open: &mut Vec<Vec<bool>>, (c1, r1): (usize, usize), (c2, r2): (usize, usize)| {
+        let (x0, y0) = (left(c1.min(c2)), top(r1.min(r2)));
+        let (x1, y1) = (left(c1.max(c2)) + corridor_width - 1, top(r1.max(r2)) + corridor_width - 1);
+        for row in &mut open[y0..=y1] {
+            row[x0..=x1].fill(true);
+        }
+    };
+    for row in 0..rows {
+        for col in 0..cols {
+            join(&mut open, (col, row), (col, row));
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let neighbours = |(col, row): (usize, usize)| {
+        let mut cells = Vec::with_capacity(4);
+        if col > 0 {
+            cells.push((col - 1, row));
+        }
+        if row > 0 {
+            cells.push((col, row - 1));
+        }
+        if col + 1 < cols {
+            cells.push((col + 1, row));
+        }
+        if row + 1 < rows {
+            cells.push((col, row + 1));
+        }
+        cells
+    };
+    let mut visited: HashSet<(usize, usize)> = HashSet::from([(0, 0)]);
+    let mut joined: HashSet<((usize, usize), (usize, usize))> = HashSet::new();
+    let mut stack = vec![(0, 0)];
+    while let Some(&cell) = stack.last() {
+        let unvisited: Vec<(usize, usize)> = neighbours(cell).into_iter().filter(|n| !visited.contains(n)).collect();
+        match unvisited.choose(&mut rng) {
+            Some(&next) => {
+                join(&mut open, cell, next);
+                joined.insert((cell.min(next), cell.max(next)));
+                visited.insert(next);
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    for row in 0..rows {
+        for col in 0..cols {
+            for next in [(col + 1, row), (col, row + 1)] {
+                if next.0 < cols && next.1 < rows && !joined.contains(&((col, row), next)) && rng.gen_ratio(1, LOOP_ODDS) {
+                    join(&mut open, (col, row), next);
+                }
+            }
+        }
+    }
+
+    // Corner chambers of 2x2 maze cells, with the walls inside them gone
+    let chambers = [(0, 0), (cols - 2, rows - 2), (cols - 2, 0), (0, rows - 2)];
+    let mut spawns = Vec::new();
+    for (i, &(col, row)) in chambers.iter().enumerate() {
+        join(&mut open, (col, row), (col + 1, row + 1));
+        let (x0, y0) = (left(col), top(row));
+        let (x1, y1) = (left(col + 1) + corridor_width - 1, top(row + 1) + corridor_width - 1);
+        spawns.push(match i {
+            0 => (x0 + 1, y0 + 1, Direction::Right),
+            1 => (x1 - 1, y1 - 1, Direction::Left),
+            2 => (x1 - 1, y0 + 1, Direction::Down),
+            _ => (x0 + 1, y1 - 1, Direction::Up),
+        });
+    }
+
+    let reached = flood(&open, (spawns[0].0, spawns[0].1));
+    if let Some(&(x, y, _)) = spawns.iter().find(|&&(x, y, _)| !reached.contains(&(x, y))) {
+        return Err(format!("generated maze is not connected: no way from seat 1 to ({}, {})", x, y));
+    }
+
+    let walls = (1..height - 1)
+        .flat_map(|y| (1..width - 1).map(move |x| (x, y)))
+        .filter(|&(x, y)| !open[y][x])
+        .collect();
+    Ok(GeneratedLayout { walls, spawns })
+}
+
+/// Every open cell reachable from `start`
+fn flood(open: &[Vec<bool>], start: (usize, usize)) -> HashSet<(usize, usize)> {
+    let mut reached = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some((x, y)) = queue.pop_front() {
+        for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if open[ny][nx] && reached.insert((nx, ny)) {
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_open_cell_is_reachable_from_every_seat() {
+        for corridor_width in 1..=MAX_CORRIDOR_WIDTH {
+            for (width, height) in [(40, 40), (61, 37)] {
+                for seed in 0..10 {
+                    let layout = generate(width, height, corridor_width, seed).unwrap();
+                    let walls: HashSet<(usize, usize)> = layout.walls.iter().copied().collect();
+                    let mut open = vec![vec![false; width]; height];
+                    for (y, row) in open.iter_mut().enumerate().take(height - 1).skip(1) {
+                        for (x, cell) in row.iter_mut().enumerate().take(width - 1).skip(1) {
+                            *cell = !walls.contains(&(x, y));
+                        }
+                    }
+                    let cells = open.iter().flatten().filter(|&&o| o).count();
+                    for &(x, y, _) in &layout.spawns {
+                        assert_eq!(
+                            flood(&open, (x, y)).len(),
+                            cells,
+                            "{}x{} maze, corridors {} wide, seed {}: seat at ({}, {}) can't reach every open cell",
+                            width,
+                            height,
+                            corridor_width,
+                            seed,
+                            x,
+                            y
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_maze() {
+        let maze = |seed| generate(40, 40, DEFAULT_CORRIDOR_WIDTH, seed).unwrap();
+        assert_eq!(maze(7).walls, maze(7).walls);
+        assert_eq!(maze(7).spawns, maze(7).spawns);
+        assert_ne!(maze(7).walls, maze(8).walls);
+    }
+
+    #[test]
+    fn bad_sizes_are_refused() {
+        assert!(generate(40, 40, 0, 0).err().unwrap().contains("between 1 and 5"));
+        assert!(generate(40, 40, MAX_CORRIDOR_WIDTH + 1, 0).is_err());
+        let err = generate(12, 40, 2, 0).err().unwrap();
+        assert!(err.contains("needs at least 13x13"), "{}", err);
+        assert!(generate(13, 13, 2, 0).is_ok());
+    }
+}
@@ -1,32 +1,386 @@
 use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::*,
+    service::{NotificationContext, RequestContext},
     tool, tool_handler, tool_router,
     transport::stdio,
-    ErrorData as McpError, ServerHandler, ServiceExt,
+    ErrorData as McpError, Peer, RoleServer, ServerHandler, ServiceExt,
 };
 use rmcp::schemars;
 use rmcp::schemars::JsonSchema;
-use serde::Deserialize;
-use std::io::{BufRead, BufReader, Write};
-use std::net::TcpStream;
+use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
 
-use crate::game::SteerAction;
-use crate::manager::SharedGameManager;
+use crate::course::{CourseRegistry, TagPreferences};
+use crate::error::{self, ErrorCode, TronError};
+use crate::game::{LookFormat, LookOptions, Orientation};
+use crate::manager::{GameManager, LeaderboardSort, MovePlan, SharedGameManager, SpectatorSession};
+use crate::protocol::{self, Request};
+use crate::push;
+use crate::rooms::SharedRooms;
+use crate::rules::RulesOverride;
+use crate::tcp;
+use crate::validation::{self, Validate};
 
 /// Parameters for join_game tool
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct JoinGameParams {
     /// Your display name for the game
     pub name: String,
+    /// Optional lobby code to join instead of the public queue
+    pub lobby: Option<String>,
+    /// Optional room (arena) to play in; defaults to the server's default room
+    pub room: Option<String>,
+    /// Practice against a server bot instead of queueing: "easy", "medium", "hard", or "adaptive"
+    pub vs_bot: Option<String>,
+    /// Course tags you would like to play, e.g. ["maze"] (public queue only)
+    pub prefer_tags: Option<Vec<String>>,
+    /// Course tags you would rather not play, e.g. ["large"] (public queue only)
+    pub avoid_tags: Option<Vec<String>>,
+    /// Token returned by your original join; takes your player back into a running game after your client restarted
+    pub resume_token: Option<String>,
+    /// Team to ride for (1 or 2) in a team lobby; without it you join whichever team is short
+    pub team: Option<u32>,
+}
+
+impl JoinGameParams {
+    pub fn tag_preferences(&self) -> Result<TagPreferences, String> {
+        validation::tag_preferences(self.prefer_tags.as_deref(), self.avoid_tags.as_deref())
+    }
+}
+
+/// Parameters for create_lobby tool
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateLobbyParams {
+    /// Course level to play (1-11), defaults to 1
+    pub level: Option<u32>,
+    /// Course to play by name instead of level, e.g. "The Gauntlet" or "the-gauntlet"
+    pub course: Option<String>,
+    /// Rule overrides on top of the course defaults
+    pub rules: Option<RulesOverride>,
+    /// Admin token; keeps a lobby with custom rules ranked
+    pub admin_token: Option<String>,
+    /// List the lobby publicly at /api/lobbies (default: private, reachable by code)
+    pub public: Option<bool>,
+    /// Optional room (arena) to create the lobby in; defaults to the server's default room
+    pub room: Option<String>,
+    /// Without a level, play the first course carrying all of these tags
+    pub prefer_tags: Option<Vec<String>>,
+    /// Without a level, skip courses carrying any of these tags
+    pub avoid_tags: Option<Vec<String>>,
+}
+
+impl CreateLobbyParams {
+    /// The level to play: `level`, or the level of the course named `course`
+    pub fn course_level(&self) -> Result<Option<u32>, String> {
+        let Some(name) = &self.course else {
+            return Ok(self.level);
+        };
+        CourseRegistry::current()
+            .get_by_name(name)
+            .map(|course| Some(course.level))
+            .ok_or_else(|| format!("'course' names no known course: '{}'. GET /api/courses lists them.", name))
+    }
+
+    pub fn tag_preferences(&self) -> Result<TagPreferences, String> {
+        validation::tag_preferences(self.prefer_tags.as_deref(), self.avoid_tags.as_deref())
+    }
+}
+
+/// Parameters for look tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LookParams {
+    /// "text" (default) for an ASCII map, "json" for a structured view with a 2D array of cell codes, your position and heading, and the other players, or "compact" for the map in far fewer tokens: run-length encoded rows and no legend
+    pub format: Option<String>,
+    /// "world" (default) draws the text map north up; "ego" turns it so your heading points up, with your left on the left
+    pub orientation: Option<String>,
+    /// Follow the text map with what each of left, straight and right runs into (default: true)
+    pub hints: Option<bool>,
+}
+
+impl LookParams {
+    pub fn options(&self) -> Result<LookOptions, String> {
+        let orientation = match &self.orientation {
+            Some(orientation) => validation::look_orientation("orientation", orientation)?,
+            None => Orientation::World,
+        };
+        Ok(LookOptions {
+            hints: self.hints.unwrap_or(true),
+            orientation,
+        })
+    }
+}
+
+impl Validate for LookParams {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(format) = &self.format {
+            validation::look_format("format", format)?;
+        }
+        self.options()?;
+        Ok(())
+    }
+}
+
+/// Parameters for wait_for_game_start tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WaitParams {
+    /// Seconds to wait before giving up (1-300, default 30)
+    pub timeout_secs: Option<u64>,
+}
+
+impl WaitParams {
+    pub fn timeout(&self) -> Result<std::time::Duration, String> {
+        validation::wait_secs("timeout_secs", self.timeout_secs.unwrap_or(DEFAULT_WAIT_SECS))
+    }
+}
+
+impl Validate for WaitParams {
+    fn validate(&self) -> Result<(), String> {
+        self.timeout().map(|_| ())
+    }
+}
+
+/// Default wait_for_game_start timeout, in seconds
+const DEFAULT_WAIT_SECS: u64 = 30;
+
+/// Parameters for whoami tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WhoamiParams {
+    /// Player name to look up; defaults to the name you joined as
+    pub name: Option<String>,
+}
+
+impl Validate for WhoamiParams {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(name) = &self.name {
+            validation::name("name", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parameters for spectate and watch tools
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SpectateParams {
+    /// Game to watch, as listed by /api/games (default: the newest running game)
+    pub game_id: Option<String>,
+}
+
+impl Validate for SpectateParams {
+    fn validate(&self) -> Result<(), String> {
+        match &self.game_id {
+            Some(id) => validation::game_id("game_id", id).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parameters for get_leaderboard tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LeaderboardParams {
+    /// Order: "points" (default), "rating" or "win_rate"
+    pub sort: Option<String>,
+    /// Only list players with at least this many ranked games (default 5 for win_rate, else 0)
+    pub min_games: Option<u32>,
+    /// Only count games on this course level, e.g. 2 for The Maze
+    pub level: Option<u32>,
+}
+
+impl LeaderboardParams {
+    fn order(&self) -> Result<(LeaderboardSort, u32), String> {
+        let sort = match &self.sort {
+            Some(sort) => LeaderboardSort::parse(sort).map_err(|e| format!("'sort': {}", e))?,
+            None => LeaderboardSort::default(),
+        };
+        Ok((sort, self.min_games.unwrap_or(sort.default_min_games())))
+    }
+}
+
+impl Validate for LeaderboardParams {
+    fn validate(&self) -> Result<(), String> {
+        self.order().map(|_| ())
+    }
 }
 
 /// Parameters for steer tool
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct SteerParams {
-    /// Direction to steer: "left", "right", or "straight"
+    /// Direction to steer: "left", "right" or "straight", or a compass
+    /// heading: "north", "south", "east" or "west"
     pub direction: String,
+    /// Cells to move (1-10, default 1): turn once, then keep going straight,
+    /// stopping early before anything in the way
+    pub count: Option<u32>,
+}
+
+impl SteerParams {
+    pub fn count(&self) -> Result<usize, String> {
+        self.count.map_or(Ok(1), |count| validation::move_count("count", count))
+    }
+}
+
+/// Parameters for say tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SayParams {
+    /// What to say, up to 140 characters
+    pub message: String,
+}
+
+impl Validate for SayParams {
+    fn validate(&self) -> Result<(), String> {
+        validation::chat_message("message", &self.message).map(|_| ())
+    }
+}
+
+/// Parameters for plan_moves tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanMovesParams {
+    /// 1-10 moves made in order, each one that steer accepts, e.g.
+    /// ["straight", "straight", "left"]
+    pub moves: Vec<String>,
+}
+
+impl Validate for JoinGameParams {
+    fn validate(&self) -> Result<(), String> {
+        validation::name("name", &self.name)?;
+        if let Some(code) = &self.lobby {
+            validation::lobby_code("lobby", code)?;
+        }
+        if let Some(room) = &self.room {
+            validation::room("room", room)?;
+        }
+        if let Some(difficulty) = &self.vs_bot {
+            validation::bot_difficulty("vs_bot", difficulty)?;
+            if self.lobby.is_some() {
+                return Err("'vs_bot' cannot be combined with 'lobby'.".to_string());
+            }
+        }
+        let tags = self.tag_preferences()?;
+        if !tags.is_empty() && (self.lobby.is_some() || self.vs_bot.is_some()) {
+            return Err("'prefer_tags' and 'avoid_tags' only apply to the public queue.".to_string());
+        }
+        if let Some(token) = &self.resume_token {
+            validation::token("resume_token", token)?;
+        }
+        if let Some(team) = self.team {
+            validation::team("team", team)?;
+            if self.lobby.is_none() {
+                return Err("'team' only applies to team games, which are played in lobbies; pass 'lobby' too.".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SteerParams {
+    fn validate(&self) -> Result<(), String> {
+        validation::direction("direction", &self.direction)?;
+        self.count().map(|_| ())
+    }
+}
+
+impl Validate for PlanMovesParams {
+    fn validate(&self) -> Result<(), String> {
+        validation::moves("moves", &self.moves).map(|_| ())
+    }
+}
+
+impl Validate for CreateLobbyParams {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(level) = self.level {
+            validation::level("level", level)?;
+        }
+        if self.level.is_some() && self.course.is_some() {
+            return Err("Give either 'level' or 'course', not both.".to_string());
+        }
+        self.course_level()?;
+        if let Some(rules) = &self.rules {
+            rules.validate()?;
+        }
+        if let Some(token) = &self.admin_token {
+            validation::token("admin_token", token)?;
+        }
+        if let Some(room) = &self.room {
+            validation::room("room", room)?;
+        }
+        self.tag_preferences()?;
+        Ok(())
+    }
+}
+
+/// Reject invalid tool parameters before they reach the game
+fn check_params(params: &impl Validate) -> Result<(), McpError> {
+    params
+        .validate()
+        .map_err(|e| invalid_params(format!("Invalid parameter: {}", e)))
+}
+
+/// Protocol-level error for a request that cannot be made, with its code as data
+fn request_error(error: TronError) -> McpError {
+    McpError::invalid_params(error.message.clone(), Some(error.to_json()))
+}
+
+fn invalid_params(message: String) -> McpError {
+    request_error(message.into())
+}
+
+fn not_joined() -> McpError {
+    request_error(TronError::new(ErrorCode::NotJoined, "Use join_game first."))
+}
+
+/// Tool result for a game action: errors keep their code as structured content
+fn tool_result(result: Result<String, TronError>) -> CallToolResult {
+    match result {
+        Ok(msg) => CallToolResult::success(vec![Content::text(msg)]),
+        Err(e) => {
+            let mut reply = CallToolResult::error(vec![Content::text(e.to_string())]);
+            reply.structured_content = Some(e.to_json());
+            reply
+        }
+    }
+}
+
+/// Tool result for a TCP response; `ERROR [CODE]: message` replies become errors
+fn tcp_reply(response: String) -> CallToolResult {
+    match TronError::from_reply(&response) {
+        Some(e) => tool_result(Err(e)),
+        // Servers without error codes still say ERROR
+        None if response.starts_with("ERROR") => CallToolResult::error(vec![Content::text(response)]),
+        None => CallToolResult::success(vec![Content::text(response)]),
+    }
+}
+
+/// A successful join's reply, with the token that takes the player back later
+fn with_resume_token(reply: String, token: &str) -> String {
+    format!(
+        "{}\nResume token: {}. If your client restarts mid-game, call join_game with the same name and this resume_token to take your player back.",
+        reply, token
+    )
+}
+
+/// Split the `SESSION <token>` line off the end of a TCP join or resume reply
+fn split_session_token(response: String) -> (String, Option<String>) {
+    match response
+        .rsplit_once('\n')
+        .and_then(|(reply, line)| Some((reply, line.strip_prefix("SESSION ")?)))
+    {
+        Some((reply, token)) => (reply.to_string(), Some(token.trim().to_string())),
+        None => (response, None),
+    }
+}
+
+/// Put in front of a join made after a resume found nothing left to resume
+const NOTHING_TO_RESUME: &str = "Your last game is over, so this is a fresh join.";
+
+/// Server instructions plus the error code map, so agents can plan retries
+fn instructions() -> String {
+    format!(
+        "{}\n\nErrors carry a stable code: tool errors have structured content {{code, message, retryable}}, TCP replies read 'ERROR [CODE]: message'. Codes (also at /api/errors): {}",
+        INSTRUCTIONS,
+        error::catalog()
+    )
 }
 
 // ─── Shared MCP tool descriptions ───
@@ -34,99 +388,743 @@ pub struct SteerParams {
 const INSTRUCTIONS: &str = "Tron Light-Cycle MCP Game! You control a light-cycle on a grid. \
 Your cycle does NOT move automatically — each 'steer' call moves you one step forward. \
 You choose direction (left/right/straight) and it moves one cell that way. \
-Crash into anything (walls, trails, obstructions) and you lose. \
+Crash into anything (walls, trails, obstructions) and you lose; \
+driving into a cycle that is heading at you destroys you both. \
 Last cycle standing wins!\n\n\
 Tools:\n\
-1. join_game(name) - Join a game with your name\n\
-2. look() - See the grid around you (call before every steer!)\n\
-3. steer(direction) - Turn + move one step: 'left', 'right', or 'straight'\n\
-4. game_status() - Check game outcome and scores\n\n\
+1. join_game(name) - Join a game with your name; keep the resume token it returns and pass it as resume_token to get back into your game after a restart\n\
+   wait_for_game_start() - Block until you are matched instead of polling\n\
+2. look(format, orientation) - See the grid around you (call before every steer!); format='json' for a structured view or 'compact' for fewer tokens, orientation='ego' to face up\n\
+   analyze() - How much open area each move keeps you connected to, flagging dead pockets\n\
+   minimap() - The whole course shrunk to at most 40x40, approximate on large courses\n\
+   course_info() - The course you are on or will most likely play next: size, rules and a preview\n\
+3. steer(direction, count) - Turn + move one step: 'left', 'right', or 'straight', or a compass heading 'north', 'south', 'east' or 'west'; count moves up to 10 cells, straight on after the turn\n\
+   plan_moves(moves) - Up to 10 steers in one call, e.g. ['straight', 'straight', 'left']\n\
+   say(message) - Talk to your opponents; they see it in look and game_status\n\
+4. game_status() - Check game outcome and scores\n\
+5. create_lobby(level, rules) - Create a private lobby with optional custom rules\n\
+6. abort() - Ask to void the game; it ends only if every living player agrees\n\
+7. leave_game() - Leave the queue, or forfeit your running game as a loss\n\
+8. whoami(name) - Where a name stands: queued, playing, or its last result\n\
+9. get_leaderboard(sort, min_games, level) - Standings with win/loss/draw records, win rates and streaks, overall or on one level\n\
+   player_stats(name) - A player's standing, current activity and last 20 games\n\
+10. rematch() - After a game, play the same opponents again on the next level\n\
+11. spectate(game_id) / watch(game_id) - Follow a game without joining it, e.g. to commentate\n\n\
 Strategy: Always call 'look' first, then 'steer' to move. Repeat. \
-Each steer = one grid step. Longer distance = more points.";
+Each steer = one grid step. Everyone scores distance traveled plus ticks survived; the winner gets 100 more, plus a bonus for winning fast.";
 
 // ─── TCP-backed MCP Server (for `tronmcp play` stdio mode) ───
 
+/// Newest TCP protocol version. Version 1 escapes newlines into one response
+/// line; version 2 (selected with `PROTO 2`) sends responses verbatim,
+/// terminated by a `FRAME_END` line.
+pub const PROTOCOL_VERSION: u32 = 2;
+pub const FRAME_END: &str = "--END--";
+
+/// How long a dropped connection is retried (e.g. while the server is
+/// replaced) before a tool call fails
+const RECONNECT_SECS: u64 = 15;
+/// Pause before the second attempt to reconnect; it doubles after every
+/// failed attempt, up to `RECONNECT_MAX_DELAY`
+const RECONNECT_FIRST_DELAY: Duration = Duration::from_millis(250);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(2);
+/// Default for `tronmcp play --command-timeout`
+pub const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 10;
+
+/// How often `tronmcp play` checks for events pushed while no tool call is
+/// running, and how long each check waits for one
+const EVENT_POLL: Duration = Duration::from_millis(250);
+const EVENT_POLL_WAIT: Duration = Duration::from_millis(20);
+
+/// One connection to the game server's command port or socket
+struct Connection {
+    /// Kept buffered across commands so no response bytes are dropped
+    reader: tokio::io::BufReader<tokio::io::ReadHalf<Box<dyn tcp::CommandStream>>>,
+    writer: tokio::io::WriteHalf<Box<dyn tcp::CommandStream>>,
+    /// The server accepted protocol 2 framing
+    framed: bool,
+    /// The server speaks JSON lines; commands and responses are then JSON
+    /// and framing does not apply
+    json: bool,
+}
+
+/// The MCP server behind `tronmcp play`: one agent's tools over stdio,
+/// each call relayed as a command to a game server's TCP port or socket.
+/// It reconnects on its own and takes the player back when the server
+/// restarts.
 #[derive(Clone)]
 pub struct TronMcpServer {
     tool_router: ToolRouter<Self>,
-    conn: std::sync::Arc<Mutex<TcpStream>>,
+    /// `None` once the connection dropped or a command timed out; the next
+    /// command reconnects
+    conn: std::sync::Arc<tokio::sync::Mutex<Option<Connection>>>,
     player_name: std::sync::Arc<Mutex<Option<String>>>,
+    server_addr: String,
+    /// How long a command may wait for its response (`--command-timeout`)
+    command_timeout: Duration,
+    /// Room selected with `ROOM`, re-selected after a reconnect
+    room: std::sync::Arc<Mutex<Option<String>>>,
+    /// Token from `TOKEN` that takes the player back after a reconnect
+    resume_token: std::sync::Arc<Mutex<Option<String>>>,
+    /// Session token from the join reply; stands for the player in `STEER`,
+    /// `LOOK` and `STATUS` and never reaches the agent
+    session_token: std::sync::Arc<Mutex<Option<String>>>,
+    /// Where pushed game events go as MCP logging notifications; set once
+    /// the MCP client has initialized
+    notifier: std::sync::Arc<Mutex<Option<Peer<RoleServer>>>>,
 }
 
 impl TronMcpServer {
-    pub fn new(server_addr: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let stream = TcpStream::connect(server_addr)?;
-        stream.set_nodelay(true)?;
-        Ok(Self {
+    pub async fn new(server_addr: &str, command_timeout: Duration) -> std::io::Result<Self> {
+        let server = Self {
             tool_router: Self::tool_router(),
-            conn: std::sync::Arc::new(Mutex::new(stream)),
+            conn: Default::default(),
             player_name: std::sync::Arc::new(Mutex::new(None)),
+            server_addr: server_addr.to_string(),
+            command_timeout,
+            room: Default::default(),
+            resume_token: Default::default(),
+            session_token: Default::default(),
+            notifier: Default::default(),
+        };
+        let mut conn = Self::connect(server_addr).await?;
+        server.handshake(&mut conn).await?;
+        *server.conn.lock().await = Some(conn);
+        Ok(server)
+    }
+
+    /// `host:port`, or `unix:<path>` for a server started with `--tcp-socket`
+    async fn connect(server_addr: &str) -> std::io::Result<Connection> {
+        let stream: Box<dyn tcp::CommandStream> = match server_addr.strip_prefix("unix:") {
+            #[cfg(unix)]
+            Some(path) => Box::new(tokio::net::UnixStream::connect(path).await?),
+            #[cfg(not(unix))]
+            Some(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "unix: addresses need a Unix system",
+                ));
+            }
+            None => {
+                let stream = tokio::net::TcpStream::connect(server_addr).await?;
+                stream.set_nodelay(true)?;
+                Box::new(stream)
+            }
+        };
+        let (reader, writer) = tokio::io::split(stream);
+        Ok(Connection {
+            reader: tokio::io::BufReader::new(reader),
+            writer,
+            framed: false,
+            json: false,
+        })
+    }
+
+    /// Pick JSON lines or text framing, and measure the round trip, on a
+    /// fresh connection
+    async fn handshake(&self, conn: &mut Connection) -> std::io::Result<()> {
+        // Servers without JSON mode reject the JSON line as an unknown
+        // command, in a single ERROR line
+        conn.json = true;
+        if !self.exchange_on(conn, &Request::Version).await?.starts_with("ERROR") {
+            self.measure_round_trip(conn).await;
+            return Ok(());
+        }
+        conn.json = false;
+        // Older servers answer with a single ERROR line and we stay on protocol 1
+        let reply = self
+            .exchange_on(conn, &Request::Proto {
+                version: PROTOCOL_VERSION,
+            })
+            .await?;
+        if reply.starts_with("OK") {
+            // The OK itself already arrived framed
+            conn.framed = true;
+            Self::read_until_frame_end(conn).await?;
+        }
+        self.measure_round_trip(conn).await;
+        Ok(())
+    }
+
+    /// The server went away (a restart or a handover to a new process): dial
+    /// again, backing off, until it is back, then restore the room and the
+    /// player. Whoever finds the connection gone first does this; anyone
+    /// waiting on the lock meanwhile gets the new connection.
+    async fn reconnect(&self) -> std::io::Result<()> {
+        let mut slot = self.conn.lock().await;
+        if slot.is_some() {
+            return Ok(());
+        }
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(RECONNECT_SECS);
+        let mut delay = RECONNECT_FIRST_DELAY;
+        let mut conn = loop {
+            match Self::connect(&self.server_addr).await {
+                Ok(conn) => break conn,
+                Err(e) if tokio::time::Instant::now() + delay >= deadline => return Err(e),
+                Err(_) => {
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+                }
+            }
+        };
+        self.handshake(&mut conn).await?;
+        let room = self.room.lock().map_err(|e| std::io::Error::other(e.to_string()))?.clone();
+        if room.is_some() {
+            self.exchange_on(&mut conn, &Request::Room { room }).await?;
+        }
+        self.rebind(&mut conn).await?;
+        *slot = Some(conn);
+        Ok(())
+    }
+
+    /// Take the player back on a new connection: `RESUME` with the token
+    /// from the last join, or `JOIN` again when the server no longer knows
+    /// it. Nothing to do before a join or after leaving.
+    async fn rebind(&self, conn: &mut Connection) -> std::io::Result<()> {
+        let name = self.player_name.lock().map_err(|e| std::io::Error::other(e.to_string()))?.clone();
+        let token = self.resume_token.lock().map_err(|e| std::io::Error::other(e.to_string()))?.clone();
+        let (Some(name), Some(token)) = (name, token) else {
+            return Ok(());
+        };
+        let mut reply = self
+            .exchange_on(conn, &Request::Resume {
+                name: name.clone(),
+                token,
+            })
+            .await?;
+        if reply.starts_with("ERROR") {
+            reply = self.exchange_on(conn, &Request::Join { name: name.clone() }).await?;
+            if reply.starts_with("ERROR") {
+                return Ok(());
+            }
+            let token = self.exchange_on(conn, &Request::Token).await?;
+            self.remember_resume_token(token.strip_prefix("TOKEN ").map(str::to_string));
+        }
+        let (_, session) = split_session_token(reply);
+        if session.is_some() {
+            self.remember_session_token(session.clone());
+        }
+        if self.notifier.lock().is_ok_and(|n| n.is_some()) {
+            let player = protocol::Player {
+                name: session.is_none().then_some(name),
+                token: session,
+            };
+            self.exchange_on(conn, &Request::Subscribe { player }).await?;
+        }
+        Ok(())
+    }
+
+    /// Have the server push the player's game events, when the MCP client
+    /// can be told about them. Older servers reject `SUBSCRIBE`; the agent
+    /// then polls as before.
+    async fn subscribe(&self) {
+        if self.notifier.lock().is_ok_and(|n| n.is_some())
+            && let Ok(player) = self.player()
+        {
+            let _ = self.exchange(&Request::Subscribe { player }).await;
+        }
+    }
+
+    /// Pass a pushed `EVENT` line on to the MCP client as a logging notification
+    fn forward_event(&self, line: &str) {
+        let Some(data) = line
+            .strip_prefix(push::EVENT_PREFIX)
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+        else {
+            return;
+        };
+        let Some(peer) = self.notifier.lock().ok().and_then(|n| n.clone()) else {
+            return;
+        };
+        tokio::spawn(async move {
+            let _ = peer
+                .notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Info,
+                    logger: Some("tron".to_string()),
+                    data,
+                })
+                .await;
+        });
+    }
+
+    /// Forward events pushed while no tool call was reading the connection,
+    /// every `EVENT_POLL`
+    fn spawn_event_pump(&self) {
+        let server = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(EVENT_POLL).await;
+                if server.notifier.lock().is_ok_and(|n| n.is_some()) {
+                    server.poll_events().await;
+                }
+            }
+        });
+    }
+
+    async fn poll_events(&self) {
+        let mut slot = self.conn.lock().await;
+        let Some(conn) = slot.as_mut() else {
+            return;
+        };
+        loop {
+            // Waiting for more input is cancel safe: what arrived stays buffered
+            match tokio::time::timeout(EVENT_POLL_WAIT, conn.reader.fill_buf()).await {
+                Err(_) => return,
+                Ok(Ok(buf)) if !buf.is_empty() => {}
+                // Closed or broken; the next tool call reconnects
+                Ok(_) => {
+                    *slot = None;
+                    return;
+                }
+            }
+            // Events are whole lines, so the rest of this one is on its way
+            let mut line = String::new();
+            if conn.reader.read_line(&mut line).await.is_err() {
+                *slot = None;
+                return;
+            }
+            self.forward_event(line.trim_end());
+        }
+    }
+
+    /// Keep the name a join or resume succeeded under; a failed join leaves
+    /// the previous one, so a reconnect never takes back someone else
+    fn remember_player_name(&self, name: &str) {
+        if let Ok(mut slot) = self.player_name.lock() {
+            *slot = Some(name.to_string());
+        }
+    }
+
+    /// Keep the token that lets a reconnect take this player back
+    fn remember_resume_token(&self, token: Option<String>) {
+        if let Ok(mut slot) = self.resume_token.lock() {
+            *slot = token;
+        }
+    }
+
+    /// Keep the session token of a join or resume reply, or forget it when a
+    /// server without session tokens sent none
+    fn remember_session_token(&self, token: Option<String>) {
+        if let Ok(mut slot) = self.session_token.lock() {
+            *slot = token;
+        }
+    }
+
+    /// The name this client joined as
+    fn name(&self) -> Result<String, McpError> {
+        let name = self.player_name.lock().map_err(|e| McpError::internal_error(format!("{}", e), None))?;
+        name.clone().ok_or_else(not_joined)
+    }
+
+    /// Who `STEER`, `LOOK` and `STATUS` act for: the session token, or the
+    /// name on servers that hand out none
+    fn player(&self) -> Result<protocol::Player, McpError> {
+        let token = self
+            .session_token
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("{}", e), None))?
+            .clone();
+        if let Some(token) = token {
+            return Ok(protocol::Player {
+                token: Some(token),
+                name: None,
+            });
+        }
+        Ok(protocol::Player {
+            token: None,
+            name: Some(self.name()?),
         })
     }
 
-    fn send_command(&self, cmd: &str) -> Result<String, McpError> {
-        let mut conn = self.conn.lock().map_err(|e| {
-            McpError::internal_error(format!("Lock error: {}", e), None)
-        })?;
-        writeln!(&mut *conn, "{}", cmd).map_err(|e| {
-            McpError::internal_error(format!("Write error: {}", e), None)
-        })?;
-        conn.flush().map_err(|e| {
-            McpError::internal_error(format!("Flush error: {}", e), None)
-        })?;
-        let mut reader = BufReader::new(&mut *conn);
-        let mut response = String::new();
-        let bytes_read = reader.read_line(&mut response).map_err(|e| {
-            McpError::internal_error(format!("Read error: {}", e), None)
-        })?;
-        if bytes_read == 0 {
-            return Err(McpError::internal_error("Connection closed by server.", None));
-        }
-        let trimmed = response.trim().to_string();
-        if trimmed.is_empty() {
+    /// Answer a server `PING` so it knows this connection's round trip for
+    /// `--fairness-delay-ms`; older servers reject PING and that is fine
+    async fn measure_round_trip(&self, conn: &mut Connection) {
+        if let Ok(reply) = self.exchange_on(conn, &Request::Ping).await
+            && let Some(Ok(nonce)) = reply.strip_prefix("PONG ").map(str::parse)
+        {
+            let _ = self.exchange_on(conn, &Request::Pong { nonce }).await;
+        }
+    }
+
+    /// Drop the rest of a framed response
+    async fn read_until_frame_end(conn: &mut Connection) -> std::io::Result<()> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if conn.reader.read_line(&mut line).await? == 0 || line.trim_end() == FRAME_END {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Send a command, reconnecting once if the connection dropped. A command
+    /// cut off by the drop is sent again on the new connection; one that got
+    /// no reply in time is not, since the server may still act on it.
+    async fn send_command(&self, cmd: Request) -> Result<String, McpError> {
+        let response = match self.exchange(&cmd).await {
+            Ok(response) => response,
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                return Err(McpError::internal_error(
+                    format!(
+                        "The game server is not responding (no reply within {}s). Try again in a moment.",
+                        self.timeout_for(&cmd).as_secs()
+                    ),
+                    None,
+                ));
+            }
+            Err(_) => {
+                self.reconnect().await.map_err(|e| {
+                    McpError::internal_error(format!("Reconnect failed: {}", e), None)
+                })?;
+                self.exchange(&cmd).await.map_err(|e| {
+                    McpError::internal_error(format!("Connection error: {}", e), None)
+                })?
+            }
+        };
+        if response.is_empty() {
             return Err(McpError::internal_error("Empty response from server.", None));
         }
-        Ok(trimmed)
+        Ok(response)
+    }
+
+    /// How long `cmd` may take: the command timeout, plus however long the
+    /// server was asked to wait
+    fn timeout_for(&self, cmd: &Request) -> Duration {
+        match cmd {
+            Request::Wait { timeout_secs, .. } => self.command_timeout + Duration::from_secs(*timeout_secs),
+            _ => self.command_timeout,
+        }
+    }
+
+    /// One command and its response on the current connection. A broken or
+    /// timed-out connection is dropped, so the next command starts afresh.
+    async fn exchange(&self, cmd: &Request) -> std::io::Result<String> {
+        let mut slot = self.conn.lock().await;
+        let Some(conn) = slot.as_mut() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotConnected, "Not connected."));
+        };
+        let result = match tokio::time::timeout(self.timeout_for(cmd), self.exchange_on(conn, cmd)).await {
+            Ok(result) => result,
+            Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "No reply in time.")),
+        };
+        if result.is_err() {
+            *slot = None;
+        }
+        result
+    }
+
+    /// One command and its response on `conn`, as the text reply either way
+    async fn exchange_on(&self, conn: &mut Connection, cmd: &Request) -> std::io::Result<String> {
+        let line = if conn.json {
+            serde_json::to_string(cmd)?
+        } else {
+            cmd.to_text().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.message))?
+        };
+        conn.writer.write_all(format!("{}\n", line).as_bytes()).await?;
+        conn.writer.flush().await?;
+        let framed = conn.framed && !conn.json;
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if conn.reader.read_line(&mut line).await? == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "Connection closed by server.",
+                ));
+            }
+            let line = line.trim_end_matches(['\r', '\n']).to_string();
+            // Pushed events come between responses, never inside one
+            if line.starts_with(push::EVENT_PREFIX) {
+                self.forward_event(&line);
+                continue;
+            }
+            if !framed {
+                lines.push(line);
+                break;
+            }
+            if line == FRAME_END {
+                break;
+            }
+            lines.push(line);
+        }
+        let reply = lines.join("\n");
+        // A line that is not a JSON response is passed on as it is; it is an
+        // older server's error
+        let reply = match serde_json::from_str::<protocol::Response>(&reply) {
+            Ok(response) if conn.json => response.into_reply(),
+            _ => reply,
+        };
+        Ok(reply.trim().to_string())
+    }
+
+    /// Route this connection to a room; returns the server's error text on failure
+    async fn select_room(&self, room: Option<&str>) -> Result<Option<String>, McpError> {
+        let Some(room) = room else {
+            return Ok(None);
+        };
+        let response = self
+            .send_command(Request::Room {
+                room: Some(room.trim().to_string()),
+            })
+            .await?;
+        if response.starts_with("ERROR") {
+            return Ok(Some(response));
+        }
+        *self.room.lock().map_err(|e| McpError::internal_error(format!("{}", e), None))? = Some(room.trim().to_string());
+        Ok(None)
     }
 }
 
 #[tool_router]
 impl TronMcpServer {
-    #[tool(description = "Join the next available Tron light-cycle game. You will be matched with other players. Once the game starts, use 'look' to see the grid and 'steer' to move. Your light-cycle does NOT move automatically — each 'steer' call moves you one step.")]
-    fn join_game(&self, Parameters(params): Parameters<JoinGameParams>) -> Result<CallToolResult, McpError> {
+    #[tool(description = "Join the next available Tron light-cycle game. You will be matched with other players. Once the game starts, use 'look' to see the grid and 'steer' to move. Your light-cycle does NOT move automatically — each 'steer' call moves you one step. Pass 'lobby' to join a private lobby, 'room' to play in a specific arena, or 'vs_bot' (easy/medium/hard/adaptive) to practice against a server bot right away. In a team lobby, 'team' (1 or 2) picks your side; otherwise you join whichever team is short. If your client restarted mid-game, pass the 'resume_token' from your original join to get back into that game.")]
+    async fn join_game(&self, Parameters(params): Parameters<JoinGameParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        if let Some(err) = self.select_room(params.room.as_deref()).await? {
+            return Ok(tcp_reply(err));
+        }
         let name = params.name.trim().to_string();
-        if name.is_empty() { return Ok(CallToolResult::error(vec![Content::text("Name cannot be empty.")])); }
-        *self.player_name.lock().map_err(|e| McpError::internal_error(format!("{}", e), None))? = Some(name.clone());
-        let response = self.send_command(&format!("JOIN {}", name))?;
-        Ok(CallToolResult::success(vec![Content::text(response)]))
+        let tags = params.tag_preferences().map_err(invalid_params)?;
+        let mut resumed_nothing = false;
+        if let Some(token) = &params.resume_token {
+            let response = self
+                .send_command(Request::Resume {
+                    name: name.clone(),
+                    token: token.trim().to_string(),
+                })
+                .await?;
+            if response.starts_with("ERROR") {
+                return Ok(tcp_reply(response));
+            }
+            let (response, session) = split_session_token(response);
+            self.remember_session_token(session);
+            // A bare acknowledgement means there was no game or queue slot to go back to
+            resumed_nothing = response == format!("Resumed as {}.", name);
+            if !resumed_nothing {
+                self.remember_player_name(&name);
+                self.remember_resume_token(Some(token.trim().to_string()));
+                self.subscribe().await;
+                return Ok(tcp_reply(response));
+            }
+        }
+        let cmd = match (params.lobby, params.vs_bot) {
+            (Some(code), _) => Request::LobbyJoin {
+                code: code.trim().to_string(),
+                name: name.clone(),
+                team: params.team,
+            },
+            (None, Some(difficulty)) => Request::Practice {
+                difficulty: difficulty.trim().to_string(),
+                name: name.clone(),
+            },
+            (None, None) => {
+                // Set (or clear) the connection's tag preferences for this join
+                let is_empty = tags.is_empty();
+                let response = self
+                    .send_command(Request::Tags {
+                        prefer: tags.prefer,
+                        avoid: tags.avoid,
+                    })
+                    .await?;
+                if response.starts_with("ERROR") && !is_empty {
+                    return Ok(tcp_reply(response));
+                }
+                Request::Join { name: name.clone() }
+            }
+        };
+        let mut response = self.send_command(cmd).await?;
+        if !response.starts_with("ERROR") {
+            let (reply, session) = split_session_token(response);
+            response = reply;
+            self.remember_session_token(session);
+            let token = self
+                .send_command(Request::Token)
+                .await
+                .ok()
+                .and_then(|reply| reply.strip_prefix("TOKEN ").map(str::to_string));
+            if let Some(token) = &token {
+                response = with_resume_token(response, token);
+            }
+            if resumed_nothing {
+                response = format!("{} {}", NOTHING_TO_RESUME, response);
+            }
+            self.remember_player_name(&name);
+            self.remember_resume_token(token);
+            self.subscribe().await;
+        }
+        Ok(tcp_reply(response))
     }
 
-    #[tool(description = "Look at the game grid around your light-cycle. Returns a text map showing your position (@), your trail (|), other players and their trails (1-9), walls (#), obstructions (X), and empty space (.). Use this to plan your moves and avoid collisions!")]
-    fn look(&self) -> Result<CallToolResult, McpError> {
-        let name = self.player_name.lock().map_err(|e| McpError::internal_error(format!("{}", e), None))?;
-        let name = name.as_ref().ok_or_else(|| McpError::invalid_params("Use join_game first.", None))?;
-        let response = self.send_command(&format!("LOOK {}", name))?;
-        Ok(CallToolResult::success(vec![Content::text(response)]))
+    #[tool(description = "Create a private lobby on a course (level 1-11, or a course name such as 'The Gauntlet'), optionally overriding its rules: max_trail_length (0: trails never fade), move_timeout_secs, win_condition ('last_standing', 'checkpoint' or 'territory': the most trail cells on the grid wins), territory_ticks (when a territory game is scored, default 200), boosts (power-up pickups, on by default on courses that have them), wrap_edges, sudden_death_tick, tie_break ('distance' or 'draw'), dead_trail_policy ('keep', 'clear_immediately' or {\"decay\": ticks}), team_size (2-4: two teams, the last team with a cycle alive wins, and everyone on it shares the win), friendly_fire (teammates' trails are harmless unless true), shrink_every (every this many ticks the outermost ring of the arena becomes wall, crashing anyone on it), spawn_grace (0-50 opening moves during which other players' trails can't crash you, default 3), lives (1-9: a crash with lives left respawns you near your start, and the last player with lives wins), max_ticks (the game ends at this tick and the longest distance traveled wins, then the most kills; every built-in course has one). Returns a lobby code that players pass to join_game. Lobbies with custom rules are unranked unless the admin token is given. Set public=true to list the lobby for others to find.")]
+    async fn create_lobby(&self, Parameters(params): Parameters<CreateLobbyParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        if let Some(err) = self.select_room(params.room.as_deref()).await? {
+            return Ok(tcp_reply(err));
+        }
+        let response = self.send_command(Request::LobbyCreate(params)).await?;
+        Ok(tcp_reply(response))
     }
 
-    #[tool(description = "Steer your light-cycle and move ONE step forward. Direction must be 'left' (turn left then move), 'right' (turn right then move), or 'straight' (move forward). Each call moves exactly one cell. Call 'look' before each 'steer' to see what's ahead! Crashing into walls, obstructions, or any trail means you lose!")]
-    fn steer(&self, Parameters(params): Parameters<SteerParams>) -> Result<CallToolResult, McpError> {
-        let name = self.player_name.lock().map_err(|e| McpError::internal_error(format!("{}", e), None))?;
-        let name = name.as_ref().ok_or_else(|| McpError::invalid_params("Use join_game first.", None))?;
-        let dir = params.direction.to_lowercase();
-        if !["left", "right", "straight"].contains(&dir.as_str()) {
-            return Ok(CallToolResult::error(vec![Content::text("Direction must be 'left', 'right', or 'straight'.")]));
-        }
-        let response = self.send_command(&format!("STEER {} {}", name, dir))?;
-        Ok(CallToolResult::success(vec![Content::text(response)]))
+    #[tool(description = "Wait until your game starts instead of polling game_status. Call it after join_game when you are queued: it returns as soon as you are matched, or after timeout_secs (default 30, max 300) with how many players are in the queue.")]
+    async fn wait_for_game_start(&self, Parameters(params): Parameters<WaitParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let timeout = params.timeout().map_err(invalid_params)?;
+        let player = self.player()?;
+        let response = self
+            .send_command(Request::Wait {
+                player,
+                timeout_secs: timeout.as_secs(),
+            })
+            .await?;
+        Ok(tcp_reply(response))
     }
 
-    #[tool(description = "Get the current game status: whether the game is waiting, running, or finished, your score, the winner, and the leaderboard standings. Use this after the game ends to see results. If you won, use join_game again to play the next level!")]
-    fn game_status(&self) -> Result<CallToolResult, McpError> {
-        let name = self.player_name.lock().map_err(|e| McpError::internal_error(format!("{}", e), None))?;
-        let name = name.as_ref().ok_or_else(|| McpError::invalid_params("Use join_game first.", None))?;
-        let response = self.send_command(&format!("STATUS {}", name))?;
-        Ok(CallToolResult::success(vec![Content::text(response)]))
+    #[tool(description = "Look at the game grid around your light-cycle. Returns a text map showing your position (@), your trail (|), the heads of other players (A-I) and their trails (1-9), trails of crashed players (a-i), in team games your teammates' heads (T) and trails (+), walls (#), obstructions (X), power-ups (* speed boost: your next steer moves 2 cells; ~ trail eraser: clears your oldest 10 trail cells; o ghost: ride through one trail cell, not a head, within 3 moves), and empty space (.), then where each other player is, which way they are heading and whether they are in view. The text map is followed by a 'Moves' block saying, for left, straight and right, what the next cell holds and how many free cells lie ahead; pass hints=false to leave it out. Pass orientation=\"ego\" to turn the map so your heading points up and your left is on the left, which makes choosing between left and right easier. Pass format=\"json\" to get the same view as JSON with a 2D array of cell codes, which avoids miscounting columns. Pass format=\"compact\" to save tokens: a one-line summary, then each grid row as space-separated runs where the first character is the cell code and any number after it is how many times it repeats (\"#4 .5 | @ .4\" is 4 walls, 5 empty cells, your trail, you, 4 empty cells), then the moves and the other players, with no legend. Use this to plan your moves and avoid collisions!")]
+    async fn look(&self, Parameters(params): Parameters<LookParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let player = self.player()?;
+        let format = params.format.as_deref().unwrap_or("text").trim().to_lowercase();
+        let response = self
+            .send_command(Request::Look {
+                player,
+                format: Some(format),
+                orientation: params.orientation.map(|o| o.trim().to_lowercase()),
+                hints: params.hints,
+            })
+            .await?;
+        Ok(tcp_reply(response))
+    }
+
+    #[tool(description = "Estimate how much open area each move keeps you connected to: for left, straight and right, a flood fill from the cell you would move into counts the empty cells you could still reach (up to 500). Moves into an enclosed area much smaller than the best one are flagged as a DEAD POCKET. Use it when the grid gets crowded to avoid trapping yourself.")]
+    async fn analyze(&self) -> Result<CallToolResult, McpError> {
+        let player = self.player()?;
+        let response = self.send_command(Request::Analyze { player }).await?;
+        Ok(tcp_reply(response))
+    }
+
+    #[tool(description = "See the whole course at once, shrunk to at most 40x40 characters: your position (@), other players' heads (A-I) and trails (1-9), walls (#), obstructions (X), power-ups (* ~ o) and the checkpoint (F). On large courses each character stands for a block of cells and shows the most important thing in it, so the map is APPROXIMATE: use it to find open regions and where the others are, and look for the exact cells before steering.")]
+    async fn minimap(&self) -> Result<CallToolResult, McpError> {
+        let player = self.player()?;
+        let response = self.send_command(Request::Map { player }).await?;
+        Ok(tcp_reply(response))
+    }
+
+    #[tool(description = "Describe the course you are on before you drive it: its size, seats, max trail length, how much of it is wall or obstruction, turn-based or real-time mode, win condition, max ticks and an ASCII preview of the layout with each seat's start. While you wait in a queue or lobby it describes the course your next game will most likely be on; a generated layout is made afresh for each game, so its preview is only an example.")]
+    async fn course_info(&self) -> Result<CallToolResult, McpError> {
+        let player = self.player()?;
+        let response = self.send_command(Request::Course { player }).await?;
+        Ok(tcp_reply(response))
+    }
+
+    #[tool(description = "Steer your light-cycle and move ONE step forward. Direction is either relative to your heading, 'left' (turn left then move), 'right' (turn right then move) or 'straight' (move forward), or a compass heading, 'north', 'south', 'east' or 'west' (north is up on the map), which the server turns into the matching turn. Cycles cannot reverse, so the compass heading straight behind you is refused. Each call moves one cell; pass 'count' (up to 10) to turn once and then keep going straight for that many cells in all, stopping early before anything in the way. Call 'look' before each 'steer' to see what's ahead! Crashing into walls, obstructions, or any trail means you lose! In a real-time game (look says so) your cycle advances every tick on its own and steer only sets the turn taken on the next tick.")]
+    async fn steer(&self, Parameters(params): Parameters<SteerParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let player = self.player()?;
+        let direction = params.direction.trim().to_lowercase();
+        let count = params.count;
+        let response = self
+            .send_command(Request::Steer {
+                player,
+                direction,
+                count,
+            })
+            .await?;
+        Ok(tcp_reply(response))
+    }
+
+    #[tool(description = "Make several steers in one call, e.g. moves=[\"straight\", \"straight\", \"left\"]: up to 10 moves, each one that steer accepts, made in order with no one else steering in between (bots still answer each move). Returns a line per move made. A compass move that would reverse your heading at that point rejects the whole plan before any move is made; otherwise the plan runs as given and stops early only if you crash or the game ends, so check the path with look first. Turn-based games only.")]
+    async fn plan_moves(&self, Parameters(params): Parameters<PlanMovesParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let player = self.player()?;
+        let moves = params.moves.iter().map(|m| m.trim().to_lowercase()).collect();
+        let response = self.send_command(Request::Plan { player, moves }).await?;
+        Ok(tcp_reply(response))
+    }
+
+    #[tool(description = "Get the current game status: whether the game is waiting, running, or finished, your score (distance plus ticks survived, with a bonus for the winner), the winner, and the leaderboard standings. The first call after a game ends returns a recap with the final board, the story of the game and placements. If you won, use join_game again to play the next level!")]
+    async fn game_status(&self) -> Result<CallToolResult, McpError> {
+        let player = self.player()?;
+        let response = self.send_command(Request::Status { player }).await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Say something to the other players in your game: trash talk, a truce offer, a bluff. They see your last few messages in their look and game_status, and spectators see them on the web page. Up to 140 characters (longer messages are cut), at most one message every 5 seconds. Saying something does not move your cycle.")]
+    async fn say(&self, Parameters(params): Parameters<SayParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let player = self.player()?;
+        let response = self
+            .send_command(Request::Say {
+                player,
+                message: params.message,
+            })
+            .await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Ask to abort your current game. The game is voided only when every living player calls abort within 60 seconds; it then ends with no winner and does not affect the leaderboard or levels. Until then the others see your request in look and game_status.")]
+    async fn abort(&self) -> Result<CallToolResult, McpError> {
+        let player = self.player()?;
+        let response = self.send_command(Request::Abort { player }).await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "After your game ends, ask to play again with the same opponents on the next level, skipping the queue. The rematch starts once every opponent still around also asks (a majority on some servers) within the rematch timeout, 30 seconds by default; otherwise you join the public queue. Games with bots cannot be replayed.")]
+    async fn rematch(&self) -> Result<CallToolResult, McpError> {
+        let player = self.player()?;
+        let response = self.send_command(Request::Rematch { player }).await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Leave the queue, or forfeit your running game. Forfeiting crashes your cycle and counts as a loss; the game continues for the others. Call join_game afterwards to play again.")]
+    async fn leave_game(&self) -> Result<CallToolResult, McpError> {
+        let player = self.player()?;
+        let response = self.send_command(Request::Leave { player }).await?;
+        if !response.starts_with("ERROR") {
+            // Nothing to take back after a reconnect
+            self.remember_resume_token(None);
+        }
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Find out where a player stands, e.g. after your agent restarted mid-match: whether the name is known, queued (with position), playing (game id, course, tick, alive or how it crashed) or finished (last result), and whether this connection may act for it. name defaults to the name you joined as.")]
+    async fn whoami(&self, Parameters(params): Parameters<WhoamiParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let response = self
+            .send_command(Request::Whoami {
+                name: params.name.map(|name| name.trim().to_string()),
+            })
+            .await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Show everything this room knows about a player: their leaderboard standing (points, rating, wins, losses and draws, streaks, highest level and wins per level), whether they are playing, queued or idle right now, and their last 20 finished games with course, result, score and opponents. name defaults to the name you joined as. Use it to study an opponent's record before a match.")]
+    async fn player_stats(&self, Parameters(params): Parameters<WhoamiParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let response = self
+            .send_command(Request::Stats {
+                name: params.name.map(|name| name.trim().to_string()),
+            })
+            .await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Show the top 10 of this room's leaderboard: each player's points, rating, wins, losses and draws with their win percentage, current and best win streak, and highest level. Sort by 'points' (default), 'rating' or 'win_rate'; win_rate only lists players with at least 5 ranked games unless min_games says otherwise. Pass 'level' for the standings on one course level alone: points, wins and win rate from that level's games, with their average length in ticks. Use it to size up opponents, or to see who is good at mazes and who at open arenas.")]
+    async fn get_leaderboard(&self, Parameters(params): Parameters<LeaderboardParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let (sort, min_games) = params.order().map_err(invalid_params)?;
+        let response = self
+            .send_command(Request::Leaderboard {
+                sort: Some(sort.as_str().to_string()),
+                min_games: Some(min_games),
+                level: params.level,
+            })
+            .await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Watch a game without joining it, e.g. to commentate: the whole board (shrunk to 40x40 on large courses), every player's position, heading and score so far, the latest events and chat. Pass 'game_id' from /api/games, or leave it out for the newest running game; a finished game's id returns its recap. Spectating never queues you or puts you on the leaderboard.")]
+    async fn spectate(&self, Parameters(params): Parameters<SpectateParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let response = self.send_command(Request::Spectate { game_id: params.game_id }).await?;
+        Ok(tcp_reply(response))
+    }
+    #[tool(description = "Like spectate, but instead of the latest events it lists everything that happened (events and chat) since your last watch call, so calling it repeatedly follows the game without repeats. Without 'game_id' it stays on the game you watched last, and returns its recap once it finishes; the next call then picks the newest running game.")]
+    async fn watch(&self, Parameters(params): Parameters<SpectateParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let response = self.send_command(Request::Watch { game_id: params.game_id }).await?;
+        Ok(tcp_reply(response))
     }
 }
 
@@ -134,16 +1132,34 @@ impl TronMcpServer {
 impl ServerHandler for TronMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            instructions: Some(INSTRUCTIONS.into()),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            instructions: Some(instructions()),
+            capabilities: ServerCapabilities::builder().enable_tools().enable_logging().build(),
             ..Default::default()
         }
     }
+
+    /// From now on pushed game events can reach the client
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        if let Ok(mut notifier) = self.notifier.lock() {
+            *notifier = Some(context.peer);
+        }
+    }
+
+    /// Game events are always sent at `info`; the level is accepted so
+    /// clients that set one carry on
+    async fn set_level(
+        &self,
+        _request: SetLevelRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        Ok(())
+    }
 }
 
 /// Run the MCP stdio server (for `tronmcp play`)
-pub async fn run_mcp_server(server_addr: String) -> Result<(), Box<dyn std::error::Error>> {
-    let server = TronMcpServer::new(&server_addr)?;
+pub async fn run_mcp_server(server_addr: String, command_timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+    let server = TronMcpServer::new(&server_addr, command_timeout).await?;
+    server.spawn_event_pump();
     tracing::info!("MCP server connected to game server at {}", server_addr);
     let service = server.serve(stdio()).await.inspect_err(|e| {
         tracing::error!("Error starting MCP server: {}", e);
@@ -158,72 +1174,330 @@ pub async fn run_mcp_server(server_addr: String) -> Result<(), Box<dyn std::erro
 #[derive(Clone)]
 pub struct TronMcpHttpHandler {
     tool_router: ToolRouter<Self>,
-    manager: SharedGameManager,
-    player_name: std::sync::Arc<tokio::sync::Mutex<Option<String>>>,
+    rooms: SharedRooms,
+    session: std::sync::Arc<HttpSession>,
+}
+
+/// Per-session state shared by every clone of a handler. Dropped when the
+/// HTTP session ends, which releases the player's queue slot.
+struct HttpSession {
+    rooms: SharedRooms,
+    room: tokio::sync::Mutex<String>,
+    player_name: tokio::sync::Mutex<Option<String>>,
+    /// Session token of the player's last join; look, steer and game_status
+    /// act for whoever it stands for
+    session_token: tokio::sync::Mutex<Option<String>>,
+    /// What `watch` has shown this session so far
+    spectator: tokio::sync::Mutex<SpectatorSession>,
+}
+
+impl Drop for HttpSession {
+    fn drop(&mut self) {
+        let Some(name) = self.player_name.get_mut().take() else {
+            return;
+        };
+        let Some(manager) = self.rooms.get(self.room.get_mut()).cloned() else {
+            return;
+        };
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                manager.lock().await.handle_disconnect(&name);
+            });
+        }
+    }
 }
 
 impl TronMcpHttpHandler {
-    pub fn new(manager: SharedGameManager) -> Self {
+    pub fn new(rooms: SharedRooms) -> Self {
         Self {
             tool_router: Self::tool_router(),
-            manager,
-            player_name: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            session: std::sync::Arc::new(HttpSession {
+                rooms: rooms.clone(),
+                room: tokio::sync::Mutex::new(rooms.default_name().to_string()),
+                player_name: tokio::sync::Mutex::new(None),
+                session_token: tokio::sync::Mutex::new(None),
+                spectator: tokio::sync::Mutex::new(SpectatorSession::default()),
+            }),
+            rooms,
+        }
+    }
+
+    /// Manager for the room this session currently plays in
+    async fn manager(&self) -> SharedGameManager {
+        let room = self.session.room.lock().await;
+        self.rooms
+            .get(&room)
+            .unwrap_or_else(|| self.rooms.default_room())
+            .clone()
+    }
+
+    /// The player look, steer and game_status act for. Going by the session
+    /// token keeps a stale session from acting once its name joined again
+    /// elsewhere.
+    async fn player(&self, mgr: &GameManager) -> Result<String, McpError> {
+        match self.session.session_token.lock().await.as_deref() {
+            Some(token) => mgr.player_for_token(token).map_err(request_error),
+            None => Err(not_joined()),
         }
     }
+
+    /// Bind the session to the player it just joined or resumed as: its
+    /// name, released when the session ends, and its session token. Only
+    /// called once the join succeeded, so a failed join under someone
+    /// else's name can't disconnect them later.
+    async fn bind_player(&self, mgr: &GameManager, name: &str) {
+        *self.session.player_name.lock().await = Some(name.to_string());
+        *self.session.session_token.lock().await = mgr.player_sessions.get(name).map(|s| s.session_token.to_string());
+    }
+
+    /// Switch this session to another room when one is requested
+    async fn select_room(&self, room: Option<&str>) -> Result<SharedGameManager, McpError> {
+        if let Some(room) = room {
+            let room = validation::room("room", room).map_err(invalid_params)?;
+            if self.rooms.get(&room).is_none() {
+                return Err(request_error(TronError::new(
+                    ErrorCode::RoomNotFound,
+                    format!("Room '{}' not found. Available rooms: {}", room, self.rooms.names().join(", ")),
+                )));
+            }
+            *self.session.room.lock().await = room;
+        }
+        Ok(self.manager().await)
+    }
 }
 
 #[tool_router]
 impl TronMcpHttpHandler {
-    #[tool(description = "Join the next available Tron light-cycle game. You will be matched with other players. Once the game starts, use 'look' to see the grid and 'steer' to move. Your light-cycle does NOT move automatically — each 'steer' call moves you one step.")]
+    #[tool(description = "Join the next available Tron light-cycle game. You will be matched with other players. Once the game starts, use 'look' to see the grid and 'steer' to move. Your light-cycle does NOT move automatically — each 'steer' call moves you one step. Pass 'lobby' to join a private lobby, 'room' to play in a specific arena, or 'vs_bot' (easy/medium/hard/adaptive) to practice against a server bot right away. In a team lobby, 'team' (1 or 2) picks your side; otherwise you join whichever team is short. If your client restarted mid-game, pass the 'resume_token' from your original join to get back into that game.")]
     async fn join_game(&self, Parameters(params): Parameters<JoinGameParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
         let name = params.name.trim().to_string();
-        if name.is_empty() { return Ok(CallToolResult::error(vec![Content::text("Name cannot be empty.")])); }
-        *self.player_name.lock().await = Some(name.clone());
-        let mut mgr = self.manager.lock().await;
-        match mgr.join(name) {
-            Ok(msg) => Ok(CallToolResult::success(vec![Content::text(msg)])),
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(e)])),
+        let manager = self.select_room(params.room.as_deref()).await?;
+        let tags = params.tag_preferences();
+        let mut mgr = manager.lock().await;
+        // Only a session whose token still stands for the name keeps its
+        // queue slot alive; anyone else has to join like everybody
+        let rejoined = self.player(&mgr).await.is_ok_and(|joined| joined == name);
+        if rejoined && let Some(msg) = mgr.refresh_queue(&name) {
+            return Ok(CallToolResult::success(vec![Content::text(msg)]));
+        }
+        let mut resumed_nothing = false;
+        if let Some(token) = &params.resume_token {
+            match mgr.resume(&name, token) {
+                Ok(Some(summary)) => {
+                    self.bind_player(&mgr, &name).await;
+                    return Ok(CallToolResult::success(vec![Content::text(summary)]));
+                }
+                Ok(None) => resumed_nothing = true,
+                Err(e) => return Ok(tool_result(Err(e))),
+            }
         }
+        let result = match (params.lobby, params.vs_bot) {
+            (Some(code), _) => mgr.join_lobby(&code, name.clone(), params.team.map(|team| team as usize)),
+            (None, Some(difficulty)) => validation::bot_difficulty("vs_bot", &difficulty)
+                .map_err(TronError::from)
+                .and_then(|difficulty| mgr.join_vs_bot(name.clone(), difficulty)),
+            (None, None) => tags
+                .map_err(TronError::from)
+                .and_then(|tags| mgr.join(name.clone(), tags)),
+        };
+        if result.is_ok() {
+            self.bind_player(&mgr, &name).await;
+        }
+        let result = result.map(|mut msg| {
+            if let Some(session) = mgr.player_sessions.get(&name) {
+                msg = with_resume_token(msg, &session.resume_token.to_string());
+            }
+            if resumed_nothing {
+                msg = format!("{} {}", NOTHING_TO_RESUME, msg);
+            }
+            msg
+        });
+        Ok(tool_result(result))
     }
 
-    #[tool(description = "Look at the game grid around your light-cycle. Returns a text map showing your position (@), your trail (|), other players and their trails (1-9), walls (#), obstructions (X), and empty space (.). Use this to plan your moves and avoid collisions!")]
-    async fn look(&self) -> Result<CallToolResult, McpError> {
-        let name = self.player_name.lock().await;
-        let name = name.as_ref().ok_or_else(|| McpError::invalid_params("Use join_game first.", None))?;
-        let mgr = self.manager.lock().await;
-        match mgr.look(name) {
-            Ok(msg) => Ok(CallToolResult::success(vec![Content::text(msg)])),
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(e)])),
-        }
+    #[tool(description = "Create a private lobby on a course (level 1-11, or a course name such as 'The Gauntlet'), optionally overriding its rules: max_trail_length (0: trails never fade), move_timeout_secs, win_condition ('last_standing', 'checkpoint' or 'territory': the most trail cells on the grid wins), territory_ticks (when a territory game is scored, default 200), boosts (power-up pickups, on by default on courses that have them), wrap_edges, sudden_death_tick, tie_break ('distance' or 'draw'), dead_trail_policy ('keep', 'clear_immediately' or {\"decay\": ticks}), team_size (2-4: two teams, the last team with a cycle alive wins, and everyone on it shares the win), friendly_fire (teammates' trails are harmless unless true), shrink_every (every this many ticks the outermost ring of the arena becomes wall, crashing anyone on it), spawn_grace (0-50 opening moves during which other players' trails can't crash you, default 3), lives (1-9: a crash with lives left respawns you near your start, and the last player with lives wins), max_ticks (the game ends at this tick and the longest distance traveled wins, then the most kills; every built-in course has one). Returns a lobby code that players pass to join_game. Lobbies with custom rules are unranked unless the admin token is given. Set public=true to list the lobby for others to find.")]
+    async fn create_lobby(&self, Parameters(params): Parameters<CreateLobbyParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let manager = self.select_room(params.room.as_deref()).await?;
+        let mut mgr = manager.lock().await;
+        let tags = params.tag_preferences().map_err(invalid_params)?;
+        let level = params.course_level().map_err(invalid_params)?;
+        Ok(tool_result(mgr.create_lobby(level, params.rules, params.admin_token.as_deref(), params.public.unwrap_or(false), &tags)))
+    }
+
+    #[tool(description = "Wait until your game starts instead of polling game_status. Call it after join_game when you are queued: it returns as soon as you are matched, or after timeout_secs (default 30, max 300) with how many players are in the queue.")]
+    async fn wait_for_game_start(&self, Parameters(params): Parameters<WaitParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let timeout = params.timeout().map_err(invalid_params)?;
+        let name = self.session.player_name.lock().await.clone();
+        let name = name.ok_or_else(not_joined)?;
+        let manager = self.manager().await;
+        Ok(tool_result(crate::manager::wait_for_game_start(&manager, &name, timeout).await))
+    }
+
+    #[tool(description = "Look at the game grid around your light-cycle. Returns a text map showing your position (@), your trail (|), the heads of other players (A-I) and their trails (1-9), trails of crashed players (a-i), in team games your teammates' heads (T) and trails (+), walls (#), obstructions (X), power-ups (* speed boost: your next steer moves 2 cells; ~ trail eraser: clears your oldest 10 trail cells; o ghost: ride through one trail cell, not a head, within 3 moves), and empty space (.), then where each other player is, which way they are heading and whether they are in view. The text map is followed by a 'Moves' block saying, for left, straight and right, what the next cell holds and how many free cells lie ahead; pass hints=false to leave it out. Pass orientation=\"ego\" to turn the map so your heading points up and your left is on the left, which makes choosing between left and right easier. Pass format=\"json\" to get the same view as JSON with a 2D array of cell codes, which avoids miscounting columns. Pass format=\"compact\" to save tokens: a one-line summary, then each grid row as space-separated runs where the first character is the cell code and any number after it is how many times it repeats (\"#4 .5 | @ .4\" is 4 walls, 5 empty cells, your trail, you, 4 empty cells), then the moves and the other players, with no legend. Use this to plan your moves and avoid collisions!")]
+    async fn look(&self, Parameters(params): Parameters<LookParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let format = match &params.format {
+            Some(format) => validation::look_format("format", format).map_err(invalid_params)?,
+            None => LookFormat::Text,
+        };
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        let name = self.player(&mgr).await?;
+        let options = params.options().map_err(invalid_params)?;
+        Ok(tool_result(mgr.look(&name, format, options)))
     }
 
-    #[tool(description = "Steer your light-cycle and move ONE step forward. Direction must be 'left' (turn left then move), 'right' (turn right then move), or 'straight' (move forward). Each call moves exactly one cell. Call 'look' before each 'steer' to see what's ahead! Crashing into walls, obstructions, or any trail means you lose!")]
+    #[tool(description = "Estimate how much open area each move keeps you connected to: for left, straight and right, a flood fill from the cell you would move into counts the empty cells you could still reach (up to 500). Moves into an enclosed area much smaller than the best one are flagged as a DEAD POCKET. Use it when the grid gets crowded to avoid trapping yourself.")]
+    async fn analyze(&self) -> Result<CallToolResult, McpError> {
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        let name = self.player(&mgr).await?;
+        Ok(tool_result(mgr.analyze(&name)))
+    }
+
+    #[tool(description = "See the whole course at once, shrunk to at most 40x40 characters: your position (@), other players' heads (A-I) and trails (1-9), walls (#), obstructions (X), power-ups (* ~ o) and the checkpoint (F). On large courses each character stands for a block of cells and shows the most important thing in it, so the map is APPROXIMATE: use it to find open regions and where the others are, and look for the exact cells before steering.")]
+    async fn minimap(&self) -> Result<CallToolResult, McpError> {
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        let name = self.player(&mgr).await?;
+        Ok(tool_result(mgr.minimap(&name)))
+    }
+
+    #[tool(description = "Describe the course you are on before you drive it: its size, seats, max trail length, how much of it is wall or obstruction, turn-based or real-time mode, win condition, max ticks and an ASCII preview of the layout with each seat's start. While you wait in a queue or lobby it describes the course your next game will most likely be on; a generated layout is made afresh for each game, so its preview is only an example.")]
+    async fn course_info(&self) -> Result<CallToolResult, McpError> {
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        let name = self.player(&mgr).await?;
+        Ok(tool_result(mgr.course_info(&name)))
+    }
+
+    #[tool(description = "Steer your light-cycle and move ONE step forward. Direction is either relative to your heading, 'left' (turn left then move), 'right' (turn right then move) or 'straight' (move forward), or a compass heading, 'north', 'south', 'east' or 'west' (north is up on the map), which the server turns into the matching turn. Cycles cannot reverse, so the compass heading straight behind you is refused. Each call moves one cell; pass 'count' (up to 10) to turn once and then keep going straight for that many cells in all, stopping early before anything in the way. Call 'look' before each 'steer' to see what's ahead! Crashing into walls, obstructions, or any trail means you lose! In a real-time game (look says so) your cycle advances every tick on its own and steer only sets the turn taken on the next tick.")]
     async fn steer(&self, Parameters(params): Parameters<SteerParams>) -> Result<CallToolResult, McpError> {
-        let name_guard = self.player_name.lock().await;
-        let name = name_guard.as_ref().ok_or_else(|| McpError::invalid_params("Use join_game first.", None))?;
-        let dir = params.direction.to_lowercase();
-        let action = match dir.as_str() {
-            "left" => SteerAction::Left,
-            "right" => SteerAction::Right,
-            "straight" => SteerAction::Straight,
-            _ => return Ok(CallToolResult::error(vec![Content::text("Direction must be 'left', 'right', or 'straight'.")])),
+        check_params(&params)?;
+        let steer = validation::direction("direction", &params.direction).map_err(invalid_params)?;
+        let count = params.count().map_err(invalid_params)?;
+        let manager = self.manager().await;
+        let (name, delay) = {
+            let mut mgr = manager.lock().await;
+            let name = self.player(&mgr).await?;
+            // HTTP players have no measured round trip and count as local
+            let delay = mgr.fairness_delay(&name, None);
+            (name, delay)
         };
-        let mut mgr = self.manager.lock().await;
-        match mgr.move_player(name, action) {
-            Ok(msg) => Ok(CallToolResult::success(vec![Content::text(msg)])),
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(e)])),
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        let mut mgr = manager.lock().await;
+        if count > 1 {
+            return Ok(tool_result(mgr.move_plan(&name, MovePlan::Repeat(steer, count))));
         }
+        Ok(tool_result(mgr.move_player(&name, steer)))
     }
 
-    #[tool(description = "Get the current game status: whether the game is waiting, running, or finished, your score, the winner, and the leaderboard standings. Use this after the game ends to see results. If you won, use join_game again to play the next level!")]
-    async fn game_status(&self) -> Result<CallToolResult, McpError> {
-        let name = self.player_name.lock().await;
-        let name = name.as_ref().ok_or_else(|| McpError::invalid_params("Use join_game first.", None))?;
-        let mgr = self.manager.lock().await;
-        match mgr.game_status(name) {
-            Ok(msg) => Ok(CallToolResult::success(vec![Content::text(msg)])),
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(e)])),
+    #[tool(description = "Make several steers in one call, e.g. moves=[\"straight\", \"straight\", \"left\"]: up to 10 moves, each one that steer accepts, made in order with no one else steering in between (bots still answer each move). Returns a line per move made. A compass move that would reverse your heading at that point rejects the whole plan before any move is made; otherwise the plan runs as given and stops early only if you crash or the game ends, so check the path with look first. Turn-based games only.")]
+    async fn plan_moves(&self, Parameters(params): Parameters<PlanMovesParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let steers = validation::moves("moves", &params.moves).map_err(invalid_params)?;
+        let manager = self.manager().await;
+        let (name, delay) = {
+            let mut mgr = manager.lock().await;
+            let name = self.player(&mgr).await?;
+            let delay = mgr.fairness_delay(&name, None);
+            (name, delay)
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
         }
+        let mut mgr = manager.lock().await;
+        Ok(tool_result(mgr.move_plan(&name, MovePlan::List(steers))))
+    }
+
+    #[tool(description = "Get the current game status: whether the game is waiting, running, or finished, your score (distance plus ticks survived, with a bonus for the winner), the winner, and the leaderboard standings. The first call after a game ends returns a recap with the final board, the story of the game and placements. If you won, use join_game again to play the next level!")]
+    async fn game_status(&self) -> Result<CallToolResult, McpError> {
+        let manager = self.manager().await;
+        let mut mgr = manager.lock().await;
+        let name = self.player(&mgr).await?;
+        Ok(tool_result(mgr.game_status(&name)))
+    }
+    #[tool(description = "Say something to the other players in your game: trash talk, a truce offer, a bluff. They see your last few messages in their look and game_status, and spectators see them on the web page. Up to 140 characters (longer messages are cut), at most one message every 5 seconds. Saying something does not move your cycle.")]
+    async fn say(&self, Parameters(params): Parameters<SayParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let manager = self.manager().await;
+        let mut mgr = manager.lock().await;
+        let name = self.player(&mgr).await?;
+        Ok(tool_result(mgr.say(&name, &params.message)))
+    }
+    #[tool(description = "Ask to abort your current game. The game is voided only when every living player calls abort within 60 seconds; it then ends with no winner and does not affect the leaderboard or levels. Until then the others see your request in look and game_status.")]
+    async fn abort(&self) -> Result<CallToolResult, McpError> {
+        let name = self.session.player_name.lock().await;
+        let name = name.as_ref().ok_or_else(not_joined)?;
+        let manager = self.manager().await;
+        let mut mgr = manager.lock().await;
+        Ok(tool_result(mgr.request_abort(name)))
+    }
+    #[tool(description = "After your game ends, ask to play again with the same opponents on the next level, skipping the queue. The rematch starts once every opponent still around also asks (a majority on some servers) within the rematch timeout, 30 seconds by default; otherwise you join the public queue. Games with bots cannot be replayed.")]
+    async fn rematch(&self) -> Result<CallToolResult, McpError> {
+        let name = self.session.player_name.lock().await;
+        let name = name.as_ref().ok_or_else(not_joined)?;
+        let manager = self.manager().await;
+        let mut mgr = manager.lock().await;
+        Ok(tool_result(mgr.request_rematch(name)))
+    }
+    #[tool(description = "Leave the queue, or forfeit your running game. Forfeiting crashes your cycle and counts as a loss; the game continues for the others. Call join_game afterwards to play again.")]
+    async fn leave_game(&self) -> Result<CallToolResult, McpError> {
+        let name = self.session.player_name.lock().await;
+        let name = name.as_ref().ok_or_else(not_joined)?;
+        let manager = self.manager().await;
+        let mut mgr = manager.lock().await;
+        Ok(tool_result(mgr.leave(name)))
+    }
+    #[tool(description = "Find out where a player stands, e.g. after your agent restarted mid-match: whether the name is known, queued (with position), playing (game id, course, tick, alive or how it crashed) or finished (last result), and whether this connection may act for it. name defaults to the name you joined as.")]
+    async fn whoami(&self, Parameters(params): Parameters<WhoamiParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let bound = self.session.player_name.lock().await.clone();
+        let name = match params.name {
+            Some(name) => name.trim().to_string(),
+            None => bound.clone().ok_or_else(not_joined)?,
+        };
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        Ok(CallToolResult::success(vec![Content::text(mgr.whoami(&name, bound.as_deref()))]))
+    }
+    #[tool(description = "Show everything this room knows about a player: their leaderboard standing (points, rating, wins, losses and draws, streaks, highest level and wins per level), whether they are playing, queued or idle right now, and their last 20 finished games with course, result, score and opponents. name defaults to the name you joined as. Use it to study an opponent's record before a match.")]
+    async fn player_stats(&self, Parameters(params): Parameters<WhoamiParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let name = match params.name {
+            Some(name) => name.trim().to_string(),
+            None => self.session.player_name.lock().await.clone().ok_or_else(not_joined)?,
+        };
+        let manager = self.manager().await;
+        Ok(tool_result(crate::manager::player_profile(&manager, &name).await.map(|profile| profile.to_text())))
+    }
+    #[tool(description = "Show the top 10 of this room's leaderboard: each player's points, rating, wins, losses and draws with their win percentage, current and best win streak, and highest level. Sort by 'points' (default), 'rating' or 'win_rate'; win_rate only lists players with at least 5 ranked games unless min_games says otherwise. Pass 'level' for the standings on one course level alone: points, wins and win rate from that level's games, with their average length in ticks. Use it to size up opponents, or to see who is good at mazes and who at open arenas.")]
+    async fn get_leaderboard(&self, Parameters(params): Parameters<LeaderboardParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let (sort, min_games) = params.order().map_err(invalid_params)?;
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        Ok(tool_result(mgr.leaderboard_text(sort, min_games, params.level)))
+    }
+    #[tool(description = "Watch a game without joining it, e.g. to commentate: the whole board (shrunk to 40x40 on large courses), every player's position, heading and score so far, the latest events and chat. Pass 'game_id' from /api/games, or leave it out for the newest running game; a finished game's id returns its recap. Spectating never queues you or puts you on the leaderboard.")]
+    async fn spectate(&self, Parameters(params): Parameters<SpectateParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        Ok(tool_result(mgr.spectate(params.game_id.as_deref())))
+    }
+    #[tool(description = "Like spectate, but instead of the latest events it lists everything that happened (events and chat) since your last watch call, so calling it repeatedly follows the game without repeats. Without 'game_id' it stays on the game you watched last, and returns its recap once it finishes; the next call then picks the newest running game.")]
+    async fn watch(&self, Parameters(params): Parameters<SpectateParams>) -> Result<CallToolResult, McpError> {
+        check_params(&params)?;
+        let manager = self.manager().await;
+        let mgr = manager.lock().await;
+        let mut seen = self.session.spectator.lock().await;
+        Ok(tool_result(mgr.watch(params.game_id.as_deref(), &mut seen)))
     }
 }
 
@@ -231,9 +1505,334 @@ impl TronMcpHttpHandler {
 impl ServerHandler for TronMcpHttpHandler {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            instructions: Some(INSTRUCTIONS.into()),
+            instructions: Some(instructions()),
             capabilities: ServerCapabilities::builder().enable_tools().build(),
             ..Default::default()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use std::sync::Arc;
+    use tokio_util::sync::CancellationToken;
+    use crate::game::{Steer, SteerAction};
+
+    /// Parse a tool's arguments and run the check both handlers run first
+    fn check<P: Validate + DeserializeOwned>(json: &str) -> Result<(), String> {
+        let params: P = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        check_params(&params).map_err(|e| e.message.to_string())
+    }
+
+    #[test]
+    fn tool_parameters_are_checked_at_their_limits() {
+        let long_name = "a".repeat(validation::MAX_NAME_LEN + 1);
+        let max_name = "a".repeat(validation::MAX_NAME_LEN);
+        let huge_name = "a".repeat(10 * 1024 * 1024);
+        let long_token = "t".repeat(validation::MAX_TOKEN_LEN + 1);
+        let long_chat = "x".repeat(10 * 1024);
+        let nine_tags = serde_json::to_string(&vec!["open"; validation::MAX_TAGS + 1]).unwrap();
+        let eleven_moves = serde_json::to_string(&vec!["left"; validation::MAX_PLANNED_MOVES + 1]).unwrap();
+        let max_level = crate::course::all_courses().len();
+
+        // (tool, arguments, Ok or the text the error must contain)
+        let cases: Vec<(&str, String, Result<(), &str>)> = vec![
+            ("join_game", format!(r#"{{"name":"{}"}}"#, max_name), Ok(())),
+            ("join_game", format!(r#"{{"name":"{}"}}"#, long_name), Err("'name' must be at most 32 characters")),
+            ("join_game", format!(r#"{{"name":"{}"}}"#, huge_name), Err("'name' must be at most 32 characters")),
+            ("join_game", r#"{"name":"   "}"#.to_string(), Err("'name' cannot be empty")),
+            ("join_game", r#"{"name":"a\nb"}"#.to_string(), Err("'name' may only contain")),
+            ("join_game", r#"{"name":"a","lobby":"ABCDEFG"}"#.to_string(), Err("'lobby' must be a 6-character")),
+            ("join_game", r#"{"name":"a","room":"no rooms"}"#.to_string(), Err("'room' must be 1-32")),
+            ("join_game", r#"{"name":"a","vs_bot":"godlike"}"#.to_string(), Err("'vs_bot' must be")),
+            ("join_game", r#"{"name":"a","lobby":"ABCDEF","team":3}"#.to_string(), Err("'team' must be 1 or 2")),
+            ("join_game", r#"{"name":"a","lobby":"ABCDEF","team":2}"#.to_string(), Ok(())),
+            ("join_game", format!(r#"{{"name":"a","prefer_tags":{}}}"#, nine_tags), Err("at most 8 tags")),
+            ("join_game", r#"{"name":"a","avoid_tags":["lava"]}"#.to_string(), Err("unknown tag 'lava'")),
+            ("join_game", format!(r#"{{"name":"a","resume_token":"{}"}}"#, long_token), Err("'resume_token' must be between 1 and 256 bytes")),
+            ("join_game", r#"{"name":"a","team":-1}"#.to_string(), Err("invalid value")),
+            ("create_lobby", "{}".to_string(), Ok(())),
+            ("create_lobby", r#"{"level":0}"#.to_string(), Err("'level' must be between 1 and")),
+            ("create_lobby", format!(r#"{{"level":{}}}"#, max_level), Ok(())),
+            ("create_lobby", format!(r#"{{"level":{}}}"#, max_level + 1), Err("'level' must be between 1 and")),
+            ("create_lobby", r#"{"level":-3}"#.to_string(), Err("invalid value")),
+            ("create_lobby", r#"{"level":1.5}"#.to_string(), Err("invalid type")),
+            ("create_lobby", r#"{"course":"Nowhere"}"#.to_string(), Err("'course' names no known course")),
+            ("create_lobby", r#"{"rules":{"move_timeout_secs":601}}"#.to_string(), Err("'rules.move_timeout_secs' must be between 1 and 600")),
+            ("create_lobby", format!(r#"{{"admin_token":"{}"}}"#, long_token), Err("'admin_token' must be between 1 and 256 bytes")),
+            ("look", r#"{"format":"yaml"}"#.to_string(), Err("'format' must be 'text', 'json' or 'compact'")),
+            ("look", r#"{"orientation":"sideways"}"#.to_string(), Err("'orientation' must be 'world' or 'ego'")),
+            ("look", r#"{"format":"compact","orientation":"ego"}"#.to_string(), Ok(())),
+            ("wait_for_game_start", r#"{"timeout_secs":0}"#.to_string(), Err("'timeout_secs' must be between 1 and 300")),
+            ("wait_for_game_start", r#"{"timeout_secs":300}"#.to_string(), Ok(())),
+            ("wait_for_game_start", r#"{"timeout_secs":301}"#.to_string(), Err("'timeout_secs' must be between 1 and 300")),
+            ("whoami", format!(r#"{{"name":"{}"}}"#, long_name), Err("'name' must be at most 32 characters")),
+            ("spectate", r#"{"game_id":"not-a-game"}"#.to_string(), Err("'game_id' must be a game id")),
+            ("get_leaderboard", r#"{"sort":"luck"}"#.to_string(), Err("'sort'")),
+            ("steer", r#"{"direction":"up"}"#.to_string(), Err("'direction' must be")),
+            ("steer", r#"{"direction":"north","count":10}"#.to_string(), Ok(())),
+            ("steer", r#"{"direction":"left","count":0}"#.to_string(), Err("'count' must be between 1 and 10")),
+            ("steer", r#"{"direction":"left","count":11}"#.to_string(), Err("'count' must be between 1 and 10")),
+            ("plan_moves", r#"{"moves":[]}"#.to_string(), Err("'moves' must list 1-10 moves")),
+            ("plan_moves", format!(r#"{{"moves":{}}}"#, eleven_moves), Err("'moves' must list 1-10 moves")),
+            ("plan_moves", r#"{"moves":["left","sideways"]}"#.to_string(), Err("'moves[1]' must be")),
+            ("say", r#"{"message":" \n "}"#.to_string(), Err("'message' cannot be empty")),
+            ("say", format!(r#"{{"message":"{}"}}"#, long_chat), Ok(())),
+        ];
+        for (tool, json, expected) in cases {
+            let result = match tool {
+                "join_game" => check::<JoinGameParams>(&json),
+                "create_lobby" => check::<CreateLobbyParams>(&json),
+                "look" => check::<LookParams>(&json),
+                "wait_for_game_start" => check::<WaitParams>(&json),
+                "whoami" => check::<WhoamiParams>(&json),
+                "spectate" => check::<SpectateParams>(&json),
+                "get_leaderboard" => check::<LeaderboardParams>(&json),
+                "steer" => check::<SteerParams>(&json),
+                "plan_moves" => check::<PlanMovesParams>(&json),
+                "say" => check::<SayParams>(&json),
+                _ => unreachable!(),
+            };
+            let shown = &json[..json.len().min(80)];
+            match (expected, result) {
+                (Ok(()), Ok(())) => {}
+                (Err(text), Err(e)) => assert!(e.contains(text), "{} {}: '{}' lacks '{}'", tool, shown, e, text),
+                (expected, result) => panic!("{} {}: expected {:?}, got {:?}", tool, shown, expected, result),
+            }
+        }
+    }
+
+    #[test]
+    fn long_chat_messages_are_cut_rather_than_refused() {
+        let (text, cut) = validation::chat_message("message", &"x".repeat(1000)).unwrap();
+        assert!(cut);
+        assert_eq!(text.chars().count(), validation::MAX_CHAT_LEN);
+    }
+
+    #[tokio::test]
+    async fn resuming_a_finished_game_joins_the_queue_instead() {
+        let (rooms, _dir) = crate::testutil::rooms();
+        let token = {
+            let mut mgr = rooms.default_room().lock().await;
+            mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+            mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+            mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+            mgr.leave("Gemini").unwrap();
+            mgr.player_sessions["Claude"].resume_token.to_string()
+        };
+
+        let handler = TronMcpHttpHandler::new(rooms.clone());
+        let params = serde_json::from_value(serde_json::json!({"name": "Claude", "resume_token": token})).unwrap();
+        let result = handler.join_game(Parameters(params)).await.unwrap();
+        let reply = &result.content[0].as_text().unwrap().text;
+        assert!(reply.starts_with(NOTHING_TO_RESUME), "{}", reply);
+        let mgr = rooms.default_room().lock().await;
+        assert!(mgr.waiting_players.iter().any(|name| name == "Claude"));
+    }
+
+    #[tokio::test]
+    async fn a_dropped_session_frees_its_name_after_the_grace() {
+        let (rooms, _dir) = crate::testutil::rooms();
+        let manager = rooms.default_room().clone();
+        {
+            let mut mgr = manager.lock().await;
+            mgr.disconnect_policy = crate::manager::DisconnectPolicy::Grace;
+            mgr.clock = crate::clock::Clock::manual();
+        }
+        let join = |name: &str| {
+            let handler = TronMcpHttpHandler::new(rooms.clone());
+            let params = serde_json::from_value(serde_json::json!({"name": name})).unwrap();
+            async move {
+                handler.join_game(Parameters(params)).await.unwrap();
+                handler
+            }
+        };
+        let claude = join("Claude").await;
+        let gemini = join("Gemini").await;
+        manager
+            .lock()
+            .await
+            .move_player("Claude", Steer::Turn(SteerAction::Straight))
+            .unwrap();
+
+        // The session ends mid-game; its cleanup runs on the runtime
+        drop(gemini);
+        while manager.lock().await.player_sessions["Gemini"].disconnected_at.is_none() {
+            tokio::task::yield_now().await;
+        }
+        {
+            let mut mgr = manager.lock().await;
+            assert!(mgr.join("Gemini".to_string(), TagPreferences::default()).is_err());
+            let grace = mgr.disconnect_grace_secs as i64;
+            mgr.clock.advance(chrono::Duration::seconds(grace));
+            mgr.tick();
+            assert!(mgr.active_games.is_empty());
+        }
+
+        let gemini = join("Gemini").await;
+        assert!(manager.lock().await.waiting_players.iter().any(|name| name == "Gemini"));
+        drop((claude, gemini));
+    }
+
+    #[tokio::test]
+    async fn a_second_session_cannot_steer_a_bound_player() {
+        let (rooms, _dir) = crate::testutil::rooms();
+        let join = |handler: &TronMcpHttpHandler, name: &str| {
+            let params = serde_json::from_value(serde_json::json!({"name": name})).unwrap();
+            let handler = handler.clone();
+            async move { handler.join_game(Parameters(params)).await.unwrap() }
+        };
+        let steer = |handler: &TronMcpHttpHandler| {
+            let params = serde_json::from_value(serde_json::json!({"direction": "left"})).unwrap();
+            let handler = handler.clone();
+            async move { handler.steer(Parameters(params)).await }
+        };
+        let (claude, gemini, intruder) = (
+            TronMcpHttpHandler::new(rooms.clone()),
+            TronMcpHttpHandler::new(rooms.clone()),
+            TronMcpHttpHandler::new(rooms.clone()),
+        );
+        join(&claude, "Claude").await;
+        join(&gemini, "Gemini").await;
+
+        // Joining under a name already in play binds nothing
+        let result = join(&intruder, "Claude").await;
+        assert_eq!(result.is_error, Some(true));
+        assert!(steer(&intruder).await.is_err());
+        assert_eq!(rooms.default_room().lock().await.get_active_games()[0].tick, 0);
+
+        let result = steer(&claude).await.unwrap();
+        assert_ne!(result.is_error, Some(true));
+        assert_eq!(rooms.default_room().lock().await.get_active_games()[0].tick, 1);
+    }
+
+    #[tokio::test]
+    async fn every_error_code_reaches_every_transport() {
+        use axum::response::IntoResponse;
+
+        for code in ErrorCode::ALL {
+            let error = TronError::new(code, "Something: went wrong");
+
+            // Legacy TCP text, and back again as the stdio server reads it
+            let text = tcp::error_reply(error.clone());
+            assert_eq!(text, format!("ERROR [{}]: Something: went wrong", code.as_str()));
+            assert_eq!(TronError::from_reply(&text).as_ref(), Some(&error));
+
+            // TCP JSON mode
+            let line: serde_json::Value =
+                serde_json::from_str(&protocol::Response::from_reply(&text).to_line()).unwrap();
+            assert_eq!(line["ok"], false, "{}", code);
+            assert_eq!(line["error"], error.to_json(), "{}", code);
+
+            // MCP tool errors, made here or relayed from a TCP server
+            for result in [tool_result(Err(error.clone())), tcp_reply(text.clone())] {
+                assert_eq!(result.is_error, Some(true), "{}", code);
+                let content = result.structured_content.unwrap();
+                assert_eq!(content["code"], code.as_str());
+                assert_eq!(content["retryable"], code.retryable());
+            }
+            let McpError { data, .. } = request_error(error.clone());
+            assert_eq!(data.unwrap()["code"], code.as_str());
+
+            // HTTP API
+            let res = error.clone().into_response();
+            assert!(res.status().is_client_error() || res.status().is_server_error(), "{}", code);
+            let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(body["error"], error.to_json(), "{}", code);
+        }
+
+        assert!(instructions().contains(&error::catalog().to_string()));
+    }
+
+    /// A game server stand-in speaking protocol 1: it knows JOIN, TOKEN and
+    /// STATUS, sits on the second STATUS past any timeout and hangs up on the
+    /// fourth. Cancelling `ct` stops it, connections and all.
+    async fn mock_server(listener: tokio::net::TcpListener, log: Arc<Mutex<Vec<String>>>, ct: CancellationToken) {
+        let statuses = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => accepted.unwrap().0,
+                _ = ct.cancelled() => return,
+            };
+            let (log, statuses, ct) = (log.clone(), statuses.clone(), ct.clone());
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = tokio::io::BufReader::new(reader).lines();
+                loop {
+                    let line = tokio::select! {
+                        line = lines.next_line() => match line {
+                            Ok(Some(line)) => line,
+                            _ => return,
+                        },
+                        _ = ct.cancelled() => return,
+                    };
+                    log.lock().unwrap().push(line.clone());
+                    let reply = match line.split_once(' ').map_or(line.as_str(), |(word, _)| word) {
+                        "JOIN" => format!("Joined the queue as {}.", &line[5..]),
+                        "TOKEN" => "TOKEN resume-me".to_string(),
+                        "STATUS" => match statuses.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1 {
+                            2 => {
+                                tokio::time::sleep(Duration::from_secs(1)).await;
+                                "Status, too late".to_string()
+                            }
+                            4 => return,
+                            n => format!("Status #{}", n),
+                        },
+                        _ => "ERROR [UNKNOWN_COMMAND]: Unknown command.".to_string(),
+                    };
+                    if writer.write_all(format!("{}\n", reply).as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn play_client_rides_out_a_slow_a_dropped_and_a_restarted_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (log, ct) = (Arc::new(Mutex::new(Vec::new())), CancellationToken::new());
+        tokio::spawn(mock_server(listener, log.clone(), ct.clone()));
+
+        let client = TronMcpServer::new(&addr.to_string(), Duration::from_millis(200)).await.unwrap();
+        let params = serde_json::from_value(serde_json::json!({"name": "Claude"})).unwrap();
+        client.join_game(Parameters(params)).await.unwrap();
+        let status = || async {
+            let result = client.game_status().await?;
+            Ok::<_, McpError>(result.content[0].as_text().unwrap().text.clone())
+        };
+        assert_eq!(status().await.unwrap(), "Status #1");
+
+        // Too slow: the call gives up, and the next one starts afresh
+        let err = status().await.unwrap_err();
+        assert!(err.message.contains("The game server is not responding"), "{}", err.message);
+        assert_eq!(status().await.unwrap(), "Status #3");
+
+        // Dropped mid-command: reconnect, take the player back, ask again
+        assert_eq!(status().await.unwrap(), "Status #5");
+        let joins = log.lock().unwrap().iter().filter(|l| *l == "JOIN Claude").count();
+        assert_eq!(joins, 3);
+
+        // Restarted: down for a moment, then back on the same port
+        ct.cancel();
+        let (log, ct) = (Arc::new(Mutex::new(Vec::new())), CancellationToken::new());
+        let restart = {
+            let (log, ct) = (log.clone(), ct.clone());
+            async move {
+                tokio::time::sleep(Duration::from_millis(400)).await;
+                let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+                tokio::spawn(mock_server(listener, log, ct));
+            }
+        };
+        let (reply, ()) = tokio::join!(status(), restart);
+        assert_eq!(reply.unwrap(), "Status #1");
+        let seen = log.lock().unwrap().clone();
+        assert!(seen.iter().any(|l| l == "RESUME Claude resume-me"), "{:?}", seen);
+        assert!(seen.iter().any(|l| l == "JOIN Claude"), "{:?}", seen);
+        ct.cancel();
+    }
+}
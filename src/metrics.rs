@@ -0,0 +1,150 @@
+use std::sync::LazyLock;
+
+use prometheus::core::Collector;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+
+use crate::game::{CrashCause, Game, GameEventKind};
+
+/// Upper bounds of the game length histogram, in ticks
+const TICK_BUCKETS: [f64; 8] = [10.0, 25.0, 50.0, 100.0, 200.0, 400.0, 800.0, 1600.0];
+
+/// Everything served at `/metrics`. The counters live in memory only and
+/// start from zero with the process, as Prometheus expects; per-room ones
+/// carry a `room` label.
+pub struct Metrics {
+    registry: Registry,
+    games_started: IntCounterVec,
+    games_finished: IntCounterVec,
+    players_joined: IntCounterVec,
+    moves: IntCounterVec,
+    crashes: IntCounterVec,
+    game_duration: HistogramVec,
+    active_games: IntGaugeVec,
+    waiting_players: IntGaugeVec,
+    /// Open TCP connections, across all rooms
+    pub tcp_connections: IntGauge,
+    /// TCP commands refused by `--tcp-rate-limit`, across all rooms
+    pub tcp_rate_limited: IntCounter,
+    /// Requests to the admin routes with an unknown token
+    pub admin_auth_failures: IntCounter,
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let per_room = |name: &str, help: &str| counter_vec(&registry, name, help, &["room"]);
+        let gauge_vec = |name: &str, help: &str| {
+            register(&registry, IntGaugeVec::new(Opts::new(name, help), &["room"]).expect("valid metric"))
+        };
+        Metrics {
+            games_started: per_room("games_started_total", "Games started"),
+            games_finished: per_room("games_finished_total", "Games finished"),
+            players_joined: per_room("players_joined_total", "Joins, practice games included"),
+            moves: per_room("moves_total", "Steers by players"),
+            crashes: counter_vec(&registry, "crashes_total", "Crashes in finished games, by cause", &["room", "cause"]),
+            game_duration: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new("game_duration_ticks", "Length of finished games in ticks")
+                        .buckets(TICK_BUCKETS.to_vec()),
+                    &["room"],
+                )
+                .expect("valid metric"),
+            ),
+            active_games: gauge_vec("active_games", "Games in progress"),
+            waiting_players: gauge_vec("waiting_players", "Players waiting in queues and lobbies"),
+            tcp_connections: register(
+                &registry,
+                IntGauge::new("tcp_connections_current", "Open TCP connections").expect("valid metric"),
+            ),
+            tcp_rate_limited: register(
+                &registry,
+                IntCounter::new("tcp_commands_rate_limited_total", "TCP commands refused by the rate limit")
+                    .expect("valid metric"),
+            ),
+            admin_auth_failures: register(
+                &registry,
+                IntCounter::new("admin_auth_failures_total", "Admin requests rejected for an unknown token")
+                    .expect("valid metric"),
+            ),
+            registry,
+        }
+    }
+
+    pub fn player_joined(&self, room: &str) {
+        self.players_joined.with_label_values(&[room]).inc();
+    }
+
+    pub fn game_started(&self, room: &str) {
+        self.games_started.with_label_values(&[room]).inc();
+    }
+
+    pub fn moved(&self, room: &str) {
+        self.moves.with_label_values(&[room]).inc();
+    }
+
+    /// Count a finished game: its length and every crash in it
+    pub fn game_finished(&self, room: &str, game: &Game) {
+        self.games_finished.with_label_values(&[room]).inc();
+        self.game_duration.with_label_values(&[room]).observe(game.tick as f64);
+        for event in &game.events {
+            if let GameEventKind::Crashed { cause, .. } = event.kind {
+                self.crashes.with_label_values(&[room, cause_label(cause)]).inc();
+            }
+        }
+    }
+
+    /// Every metric in the Prometheus text format, with the gauges read off
+    /// each room's state now. Rooms nothing happened in yet show zeros.
+    pub fn render(&self, rooms: &[RoomSample]) -> String {
+        for r in rooms {
+            let room = [r.room.as_str()];
+            for counter in [&self.games_started, &self.games_finished, &self.players_joined, &self.moves] {
+                counter.with_label_values(&room);
+            }
+            self.game_duration.with_label_values(&room);
+            self.active_games.with_label_values(&room).set(r.active_games as i64);
+            self.waiting_players.with_label_values(&room).set(r.waiting_players as i64);
+        }
+        let mut out = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut out) {
+            tracing::warn!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8_lossy(&out).into_owned()
+    }
+}
+
+fn register<M: Collector + Clone + 'static>(registry: &Registry, metric: M) -> M {
+    registry.register(Box::new(metric.clone())).expect("metric names are unique");
+    metric
+}
+
+fn counter_vec(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    register(registry, IntCounterVec::new(Opts::new(name, help), labels).expect("valid metric"))
+}
+
+/// Short name of a crash cause, as used in metric labels and `tronmcp simulate`
+pub fn cause_label(cause: CrashCause) -> &'static str {
+    match cause {
+        CrashCause::Boundary => "boundary",
+        CrashCause::Wall => "wall",
+        CrashCause::Obstruction => "obstruction",
+        CrashCause::OwnTrail => "own_trail",
+        CrashCause::Trail { .. } => "trail",
+        CrashCause::HeadOn { .. } => "head_on",
+        CrashCause::Timeout => "timeout",
+        CrashCause::Kicked => "kicked",
+        CrashCause::Forfeit => "forfeit",
+        CrashCause::ArenaShrink => "arena_shrink",
+    }
+}
+
+/// The gauges read off one room's state when scraped
+pub struct RoomSample {
+    pub room: String,
+    pub active_games: usize,
+    /// In the main queue and in lobbies
+    pub waiting_players: usize,
+}
@@ -0,0 +1,224 @@
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use tokio::sync::Notify;
+
+/// Longest a save waits for later ones to fold into the same write
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// A data file written off the manager lock. `save` only hands over the new
+/// contents; a background task writes the latest of them at most once per
+/// `DEBOUNCE`, atomically, keeping the previous file as `<file>.bak`.
+#[derive(Clone)]
+pub struct DebouncedFile {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    path: PathBuf,
+    pending: Mutex<Option<String>>,
+    /// Held while a write is on disk, so a flush and the task never interleave
+    writing: Mutex<()>,
+    wake: Notify,
+    /// Without a runtime to run the task on, saves are written right away
+    background: bool,
+}
+
+impl DebouncedFile {
+    pub fn new(path: PathBuf) -> Self {
+        let runtime = tokio::runtime::Handle::try_current().ok();
+        let inner = Arc::new(Inner {
+            path,
+            pending: Mutex::new(None),
+            writing: Mutex::new(()),
+            wake: Notify::new(),
+            background: runtime.is_some(),
+        });
+        if let Some(runtime) = runtime {
+            runtime.spawn(run(inner.clone()));
+        }
+        DebouncedFile { inner }
+    }
+
+    pub fn save(&self, contents: String) {
+        *self.inner.pending.lock().unwrap() = Some(contents);
+        if self.inner.background {
+            self.inner.wake.notify_one();
+        } else {
+            self.inner.write_pending();
+        }
+    }
+
+    /// Write whatever is still waiting, now; for shutdown
+    pub fn flush(&self) {
+        self.inner.write_pending();
+    }
+}
+
+async fn run(inner: Arc<Inner>) {
+    loop {
+        inner.wake.notified().await;
+        tokio::time::sleep(DEBOUNCE).await;
+        let inner = inner.clone();
+        let _ = tokio::task::spawn_blocking(move || inner.write_pending()).await;
+    }
+}
+
+impl Inner {
+    fn write_pending(&self) {
+        let _writing = self.writing.lock().unwrap();
+        let Some(contents) = self.pending.lock().unwrap().take() else {
+            return;
+        };
+        if let Err(e) = write_atomic(&self.path, &contents) {
+            tracing::error!("Failed to save {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Write to `<file>.tmp` and sync it to disk, copy the current file to
+/// `<file>.bak`, then rename the new one into place, so a crash never leaves
+/// a half-written file
+fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(contents.as_bytes())?;
+    // Without this the rename can reach the disk before the data does
+    file.sync_all()?;
+    drop(file);
+    if path.exists() {
+        std::fs::copy(path, backup_path(path))?;
+    }
+    std::fs::rename(&tmp, path)
+}
+
+/// Read a JSON data file. When it is missing or does not parse, fall back to
+/// its `.bak` copy; `None` when neither gives anything.
+pub fn read_json<T: DeserializeOwned>(path: &Path, what: &str) -> Option<T> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => match serde_json::from_str(&json) {
+            Ok(value) => return Some(value),
+            Err(e) => tracing::warn!("Failed to parse {} in {}: {}", what, path.display(), e),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("Failed to read {}: {}", path.display(), e),
+    }
+    let backup = backup_path(path);
+    let json = std::fs::read_to_string(&backup).ok()?;
+    match serde_json::from_str(&json) {
+        Ok(value) => {
+            tracing::warn!("Recovered {} from {}", what, backup.display());
+            Some(value)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to parse {} in {}: {}", what, backup.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    fn read(path: &Path) -> Option<Vec<u32>> {
+        read_json(path, "numbers")
+    }
+
+    #[test]
+    fn each_write_keeps_the_previous_file_as_backup() {
+        let dir = ScratchDir::new();
+        let path = dir.path().join("numbers.json");
+
+        write_atomic(&path, "[1]").unwrap();
+        assert!(!backup_path(&path).exists());
+        write_atomic(&path, "[1,2]").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1,2]");
+        assert_eq!(std::fs::read_to_string(backup_path(&path)).unwrap(), "[1]");
+        assert!(!dir.path().join("numbers.json.tmp").exists());
+    }
+
+    #[test]
+    fn corrupt_file_falls_back_to_the_backup() {
+        let dir = ScratchDir::new();
+        let path = dir.path().join("numbers.json");
+        write_atomic(&path, "[1]").unwrap();
+        write_atomic(&path, "[1,2]").unwrap();
+
+        std::fs::write(&path, "[1,2,\0\0 garbage").unwrap();
+        assert_eq!(read(&path), Some(vec![1]));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read(&path), Some(vec![1]));
+
+        std::fs::write(backup_path(&path), "{").unwrap();
+        assert_eq!(read(&path), None);
+    }
+
+    #[test]
+    fn interrupted_write_leaves_the_last_good_copy() {
+        let dir = ScratchDir::new();
+        let path = dir.path().join("numbers.json");
+        let tmp = dir.path().join("numbers.json.tmp");
+        write_atomic(&path, "[1]").unwrap();
+
+        // A crash mid-write leaves a partial temporary file and the old one
+        std::fs::write(&tmp, "[1,2,3").unwrap();
+        assert_eq!(read(&path), Some(vec![1]));
+
+        // The next write starts its temporary file over and renames it in
+        write_atomic(&path, "[1,2,3]").unwrap();
+        assert!(!tmp.exists());
+        assert_eq!(read(&path), Some(vec![1, 2, 3]));
+        assert_eq!(read(&backup_path(&path)), Some(vec![1]));
+    }
+
+    #[test]
+    fn saves_without_a_runtime_are_written_right_away() {
+        let dir = ScratchDir::new();
+        let path = dir.path().join("numbers.json");
+        let file = DebouncedFile::new(path.clone());
+
+        file.save("[4]".to_string());
+        assert_eq!(read(&path), Some(vec![4]));
+        file.save("[4,5]".to_string());
+        file.flush();
+        assert_eq!(read(&path), Some(vec![4, 5]));
+        assert_eq!(read(&backup_path(&path)), Some(vec![4]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn saves_in_quick_succession_are_written_once() {
+        let dir = ScratchDir::new();
+        let path = dir.path().join("numbers.json");
+        let file = DebouncedFile::new(path.clone());
+
+        for n in 1..=3 {
+            file.save(format!("[{}]", n));
+        }
+        tokio::task::yield_now().await;
+        assert!(!path.exists());
+
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        // The write itself runs on the blocking pool
+        while !path.exists() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(read(&path), Some(vec![3]));
+        // Only the last save reached the disk, so nothing was backed up
+        assert!(!backup_path(&path).exists());
+    }
+}
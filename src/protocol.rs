@@ -0,0 +1,446 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ErrorCode, TronError};
+use crate::mcp::CreateLobbyParams;
+
+/// One TCP command in JSON mode, sent as a line starting with `{`, e.g.
+/// `{"cmd":"steer","token":"...","direction":"left"}`. Each maps onto the
+/// text command of the same name, so both modes run the same handlers.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    Proto {
+        version: u32,
+    },
+    Ping,
+    Pong {
+        nonce: u32,
+    },
+    Room {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        room: Option<String>,
+    },
+    Join {
+        name: String,
+    },
+    Tags {
+        #[serde(default)]
+        prefer: Vec<String>,
+        #[serde(default)]
+        avoid: Vec<String>,
+    },
+    Practice {
+        difficulty: String,
+        name: String,
+    },
+    LobbyCreate(CreateLobbyParams),
+    LobbyJoin {
+        code: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        team: Option<u32>,
+    },
+    Resume {
+        name: String,
+        token: String,
+    },
+    Token,
+    Wait {
+        #[serde(flatten)]
+        player: Player,
+        timeout_secs: u64,
+    },
+    Look {
+        #[serde(flatten)]
+        player: Player,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        orientation: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hints: Option<bool>,
+    },
+    Analyze {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Map {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Course {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Steer {
+        #[serde(flatten)]
+        player: Player,
+        direction: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        count: Option<u32>,
+    },
+    Plan {
+        #[serde(flatten)]
+        player: Player,
+        moves: Vec<String>,
+    },
+    Status {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Say {
+        #[serde(flatten)]
+        player: Player,
+        message: String,
+    },
+    Abort {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Rematch {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Leave {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Whoami {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    Stats {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    Leaderboard {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sort: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_games: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        level: Option<u32>,
+    },
+    Spectate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        game_id: Option<String>,
+    },
+    Watch {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        game_id: Option<String>,
+    },
+    Courses,
+    Subscribe {
+        #[serde(flatten)]
+        player: Player,
+    },
+    Unsubscribe,
+    Help {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        command: Option<String>,
+    },
+    Version,
+    Bye,
+}
+
+/// Who a player command such as `look`, `steer` or `leave` acts for: the session token from the join
+/// reply, or the name on servers running `--tcp-name-commands`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Player {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Player {
+    fn arg(&self) -> Result<&str, TronError> {
+        self.token
+            .as_deref()
+            .or(self.name.as_deref())
+            .ok_or_else(|| TronError::new(ErrorCode::InvalidParams, "Give the player's 'token' or 'name'."))
+    }
+}
+
+impl Request {
+    pub fn from_json(line: &str) -> Result<Request, TronError> {
+        serde_json::from_str(line).map_err(|e| {
+            // serde reports an unknown `cmd` like any other bad field
+            let cmd = serde_json::from_str::<serde_json::Value>(line)
+                .ok()
+                .and_then(|v| v.get("cmd")?.as_str().map(str::to_string));
+            match cmd {
+                Some(cmd) if e.to_string().starts_with(&format!("unknown variant `{}`", cmd)) => TronError::new(
+                    ErrorCode::UnknownCommand,
+                    format!("Unknown command '{}'. HELP lists the commands.", cmd),
+                ),
+                _ => TronError::new(ErrorCode::InvalidParams, format!("Invalid JSON command: {}", e)),
+            }
+        })
+    }
+
+    /// The same command as a text line
+    pub fn to_text(&self) -> Result<String, TronError> {
+        Ok(match self {
+            Request::Proto { version } => format!("PROTO {}", version),
+            Request::Ping => "PING".to_string(),
+            Request::Pong { nonce } => format!("PONG {}", nonce),
+            Request::Room { room } => match room {
+                Some(room) => format!("ROOM {}", room),
+                None => "ROOM".to_string(),
+            },
+            Request::Join { name } => format!("JOIN {}", name),
+            Request::Tags { prefer, avoid } => format!("TAGS prefer={} avoid={}", prefer.join(","), avoid.join(",")),
+            Request::Practice { difficulty, name } => format!("PRACTICE {} {}", difficulty, name),
+            Request::LobbyCreate(params) => format!(
+                "LOBBY CREATE {}",
+                serde_json::to_string(params).map_err(|e| TronError::new(ErrorCode::Internal, e.to_string()))?
+            ),
+            Request::LobbyJoin { code, name, team } => match team {
+                Some(team) => format!("LOBBY JOIN {} {} team={}", code, name, team),
+                None => format!("LOBBY JOIN {} {}", code, name),
+            },
+            Request::Resume { name, token } => format!("RESUME {} {}", name, token),
+            Request::Token => "TOKEN".to_string(),
+            Request::Wait { player, timeout_secs } => format!("WAIT {} {}", player.arg()?, timeout_secs),
+            Request::Look {
+                player,
+                format,
+                orientation,
+                hints,
+            } => {
+                let mut line = format!("LOOK {}", player.arg()?);
+                for word in [format, orientation].into_iter().flatten() {
+                    line = format!("{} {}", line, word);
+                }
+                if *hints == Some(false) {
+                    line.push_str(" nohints");
+                }
+                line
+            }
+            Request::Analyze { player } => format!("ANALYZE {}", player.arg()?),
+            Request::Map { player } => format!("MAP {}", player.arg()?),
+            Request::Course { player } => format!("COURSE {}", player.arg()?),
+            Request::Steer {
+                player,
+                direction,
+                count,
+            } => match count {
+                Some(count) => format!("STEER {} {} {}", player.arg()?, direction, count),
+                None => format!("STEER {} {}", player.arg()?, direction),
+            },
+            Request::Plan { player, moves } => format!("PLAN {} {}", player.arg()?, moves.join(",")),
+            Request::Status { player } => format!("STATUS {}", player.arg()?),
+            Request::Say { player, message } => format!("SAY {} {}", player.arg()?, message),
+            Request::Abort { player } => format!("ABORT {}", player.arg()?),
+            Request::Rematch { player } => format!("REMATCH {}", player.arg()?),
+            Request::Leave { player } => format!("LEAVE {}", player.arg()?),
+            Request::Whoami { name } => match name {
+                Some(name) => format!("WHOAMI {}", name),
+                None => "WHOAMI".to_string(),
+            },
+            Request::Stats { name } => match name {
+                Some(name) => format!("STATS {}", name),
+                None => "STATS".to_string(),
+            },
+            Request::Leaderboard { sort, min_games, level } => {
+                let mut line = format!("LEADERBOARD {}", sort.as_deref().unwrap_or("points"));
+                if let Some(min_games) = min_games {
+                    line = format!("{} {}", line, min_games);
+                }
+                if let Some(level) = level {
+                    line = format!("{} level={}", line, level);
+                }
+                line
+            }
+            Request::Spectate { game_id } => match game_id {
+                Some(game_id) => format!("SPECTATE {}", game_id),
+                None => "SPECTATE".to_string(),
+            },
+            Request::Watch { game_id } => match game_id {
+                Some(game_id) => format!("WATCH {}", game_id),
+                None => "WATCH".to_string(),
+            },
+            Request::Courses => "COURSES".to_string(),
+            Request::Subscribe { player } => format!("SUBSCRIBE {}", player.arg()?),
+            Request::Unsubscribe => "UNSUBSCRIBE".to_string(),
+            Request::Help { command } => match command {
+                Some(command) => format!("HELP {}", command),
+                None => "HELP".to_string(),
+            },
+            Request::Version => "VERSION".to_string(),
+            Request::Bye => "BYE".to_string(),
+        })
+    }
+}
+
+/// The answer to a JSON command, always on one line: `{"ok":true,"data":...}`
+/// or `{"ok":false,"error":{"code","message","retryable"}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    /// The text reply, or the parsed object when it is JSON (`look` with
+    /// format json)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorBody>,
+}
+
+/// A failed command's error, with the same codes as `ERROR [CODE]` replies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl Response {
+    /// Wrap a text command's reply
+    pub fn from_reply(reply: &str) -> Response {
+        if let Some(e) = TronError::from_reply(reply) {
+            return Response {
+                ok: false,
+                data: None,
+                error: Some(ErrorBody {
+                    code: e.code.as_str().to_string(),
+                    message: e.message,
+                    retryable: e.code.retryable(),
+                }),
+            };
+        }
+        let data = match serde_json::from_str::<serde_json::Value>(reply) {
+            Ok(value) if value.is_object() => value,
+            _ => serde_json::Value::String(reply.to_string()),
+        };
+        Response {
+            ok: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    /// Back to the text reply a text command would have had
+    pub fn into_reply(self) -> String {
+        match (self.error, self.data) {
+            (Some(e), _) => format!("ERROR [{}]: {}", e.code, e.message),
+            (None, Some(serde_json::Value::String(text))) => text,
+            (None, Some(value)) => value.to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            format!(r#"{{"ok":false,"error":{{"code":"INTERNAL","message":"{}","retryable":true}}}}"#, e)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every command as a JSON line, and the text command it stands for
+    const REQUESTS: &[(&str, &str)] = &[
+        (r#"{"cmd":"proto","version":2}"#, "PROTO 2"),
+        (r#"{"cmd":"ping"}"#, "PING"),
+        (r#"{"cmd":"pong","nonce":7}"#, "PONG 7"),
+        (r#"{"cmd":"room"}"#, "ROOM"),
+        (r#"{"cmd":"room","room":"arena-2"}"#, "ROOM arena-2"),
+        (r#"{"cmd":"join","name":"Claude"}"#, "JOIN Claude"),
+        (r#"{"cmd":"tags","prefer":["maze"],"avoid":["open","small"]}"#, "TAGS prefer=maze avoid=open,small"),
+        (r#"{"cmd":"practice","difficulty":"hard","name":"Claude"}"#, "PRACTICE hard Claude"),
+        (
+            r#"{"cmd":"lobby_create","level":2,"course":null,"rules":null,"admin_token":null,"public":true,"room":null,"prefer_tags":null,"avoid_tags":null}"#,
+            r#"LOBBY CREATE {"level":2,"course":null,"rules":null,"admin_token":null,"public":true,"room":null,"prefer_tags":null,"avoid_tags":null}"#,
+        ),
+        (r#"{"cmd":"lobby_join","code":"ABCDEF","name":"Claude"}"#, "LOBBY JOIN ABCDEF Claude"),
+        (r#"{"cmd":"lobby_join","code":"ABCDEF","name":"Claude","team":2}"#, "LOBBY JOIN ABCDEF Claude team=2"),
+        (r#"{"cmd":"resume","name":"Claude","token":"t0k3n"}"#, "RESUME Claude t0k3n"),
+        (r#"{"cmd":"token"}"#, "TOKEN"),
+        (r#"{"cmd":"wait","token":"t0k3n","timeout_secs":30}"#, "WAIT t0k3n 30"),
+        (r#"{"cmd":"look","token":"t0k3n"}"#, "LOOK t0k3n"),
+        (
+            r#"{"cmd":"look","name":"Claude","format":"compact","orientation":"ego","hints":false}"#,
+            "LOOK Claude compact ego nohints",
+        ),
+        (r#"{"cmd":"analyze","token":"t0k3n"}"#, "ANALYZE t0k3n"),
+        (r#"{"cmd":"map","token":"t0k3n"}"#, "MAP t0k3n"),
+        (r#"{"cmd":"course","token":"t0k3n"}"#, "COURSE t0k3n"),
+        (r#"{"cmd":"steer","token":"t0k3n","direction":"left"}"#, "STEER t0k3n left"),
+        (r#"{"cmd":"steer","name":"Claude","direction":"straight","count":3}"#, "STEER Claude straight 3"),
+        (r#"{"cmd":"plan","token":"t0k3n","moves":["left","straight"]}"#, "PLAN t0k3n left,straight"),
+        (r#"{"cmd":"status","token":"t0k3n"}"#, "STATUS t0k3n"),
+        (r#"{"cmd":"say","token":"t0k3n","message":"good luck"}"#, "SAY t0k3n good luck"),
+        (r#"{"cmd":"abort","token":"t0k3n"}"#, "ABORT t0k3n"),
+        (r#"{"cmd":"rematch","token":"t0k3n"}"#, "REMATCH t0k3n"),
+        (r#"{"cmd":"leave","token":"t0k3n"}"#, "LEAVE t0k3n"),
+        (r#"{"cmd":"whoami"}"#, "WHOAMI"),
+        (r#"{"cmd":"whoami","name":"Claude"}"#, "WHOAMI Claude"),
+        (r#"{"cmd":"stats"}"#, "STATS"),
+        (r#"{"cmd":"stats","name":"Claude"}"#, "STATS Claude"),
+        (r#"{"cmd":"leaderboard"}"#, "LEADERBOARD points"),
+        (r#"{"cmd":"leaderboard","sort":"rating","min_games":5,"level":3}"#, "LEADERBOARD rating 5 level=3"),
+        (r#"{"cmd":"spectate"}"#, "SPECTATE"),
+        (r#"{"cmd":"spectate","game_id":"g1"}"#, "SPECTATE g1"),
+        (r#"{"cmd":"watch","game_id":"g1"}"#, "WATCH g1"),
+        (r#"{"cmd":"courses"}"#, "COURSES"),
+        (r#"{"cmd":"subscribe","token":"t0k3n"}"#, "SUBSCRIBE t0k3n"),
+        (r#"{"cmd":"unsubscribe"}"#, "UNSUBSCRIBE"),
+        (r#"{"cmd":"help"}"#, "HELP"),
+        (r#"{"cmd":"help","command":"steer"}"#, "HELP steer"),
+        (r#"{"cmd":"version"}"#, "VERSION"),
+        (r#"{"cmd":"bye"}"#, "BYE"),
+    ];
+
+    #[test]
+    fn requests_keep_their_wire_format() {
+        for &(json, text) in REQUESTS {
+            let request = Request::from_json(json).unwrap_or_else(|e| panic!("{}: {}", json, e.message));
+            let sent: serde_json::Value = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_value(&request).unwrap(), sent, "{}", json);
+            assert_eq!(request.to_text().unwrap(), text, "{}", json);
+        }
+    }
+
+    #[test]
+    fn bad_requests_are_told_apart() {
+        let unknown = Request::from_json(r#"{"cmd":"teleport","x":3}"#).unwrap_err();
+        assert_eq!(unknown.code, ErrorCode::UnknownCommand);
+        assert_eq!(unknown.message, "Unknown command 'teleport'. HELP lists the commands.");
+
+        let missing = Request::from_json(r#"{"cmd":"join"}"#).unwrap_err();
+        assert_eq!(missing.code, ErrorCode::InvalidParams);
+        assert!(missing.message.contains("missing field `name`"), "{}", missing.message);
+
+        // Parses, but names nobody to act for
+        let nobody = Request::from_json(r#"{"cmd":"look"}"#).unwrap();
+        assert_eq!(nobody.to_text().unwrap_err().code, ErrorCode::InvalidParams);
+    }
+
+    #[test]
+    fn responses_keep_their_wire_format() {
+        let cases = [
+            ("Moved NORTH to (3, 2). Distance: 1.", r#"{"ok":true,"data":"Moved NORTH to (3, 2). Distance: 1."}"#),
+            (r#"{"tick":4,"alive":true}"#, r#"{"ok":true,"data":{"alive":true,"tick":4}}"#),
+            ("[1, 2]", r#"{"ok":true,"data":"[1, 2]"}"#),
+            (
+                "ERROR [GAME_NOT_FOUND]: Game not found.",
+                r#"{"ok":false,"error":{"code":"GAME_NOT_FOUND","message":"Game not found.","retryable":false}}"#,
+            ),
+        ];
+        for (reply, line) in cases {
+            let response = Response::from_reply(reply);
+            assert_eq!(response.to_line(), line);
+            let parsed: Response = serde_json::from_str(line).unwrap();
+            assert_eq!(parsed.to_line(), line);
+            // Object data comes back in serde_json's key order
+            let expected_reply = match serde_json::from_str::<serde_json::Value>(reply) {
+                Ok(value) if value.is_object() => value.to_string(),
+                _ => reply.to_string(),
+            };
+            assert_eq!(parsed.into_reply(), expected_reply);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use serde::Serialize;
+
+use crate::broadcast::BroadcastEvent;
+use crate::game::{Direction, WebGameState, WebPlayer};
+
+/// Prefix of the lines a `SUBSCRIBE`d TCP connection gets pushed between
+/// responses; never the start of a response
+pub const EVENT_PREFIX: &str = "EVENT ";
+
+/// What happened in a subscribed player's game, from their point of view
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PlayerEvent {
+    GameStarted {
+        game_id: String,
+        course: String,
+        level: u32,
+        players: Vec<String>,
+    },
+    /// Another player's cycle moved or crashed
+    OpponentMoved {
+        game_id: String,
+        player: String,
+        x: i32,
+        y: i32,
+        direction: Direction,
+        alive: bool,
+        tick: u32,
+    },
+    YouCrashed {
+        game_id: String,
+        tick: u32,
+    },
+    GameFinished {
+        game_id: String,
+        winner: Option<String>,
+        /// The winner's team, in team games
+        #[serde(skip_serializing_if = "Option::is_none")]
+        winning_team: Option<usize>,
+        score: u32,
+        aborted: bool,
+    },
+}
+
+impl PlayerEvent {
+    /// The pushed line, without its newline
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}{}",
+            EVENT_PREFIX,
+            serde_json::to_string(self).expect("player events always serialize")
+        )
+    }
+}
+
+/// One connection's `SUBSCRIBE`: turns its room's broadcasts into the
+/// events of one player
+pub struct Subscription {
+    pub player: String,
+    pub room: String,
+    /// The player's current game and its players as last seen, to tell
+    /// what changed
+    game: Option<(String, Vec<WebPlayer>)>,
+}
+
+impl Subscription {
+    pub fn new(player: String, room: String) -> Self {
+        Subscription { player, room, game: None }
+    }
+
+    pub fn translate(&mut self, event: &BroadcastEvent) -> Vec<PlayerEvent> {
+        match event {
+            BroadcastEvent::GameStarted { game, .. } if self.seated(game) => {
+                self.game = Some((game.id.clone(), game.players.clone()));
+                vec![PlayerEvent::GameStarted {
+                    game_id: game.id.clone(),
+                    course: game.course_name.clone(),
+                    level: game.course_level,
+                    players: game.players.iter().map(|p| p.name.clone()).collect(),
+                }]
+            }
+            BroadcastEvent::GameUpdate { game } => self.changes(game),
+            BroadcastEvent::GameFinished { game, .. } if self.seated(game) => {
+                let mut events = self.changes(game);
+                self.game = None;
+                events.push(PlayerEvent::GameFinished {
+                    game_id: game.id.clone(),
+                    winner: game.winner.and_then(|w| game.players.get(w)).map(|p| p.name.clone()),
+                    winning_team: game.winning_team,
+                    score: game.players.iter().find(|p| p.name == self.player).map_or(0, |p| p.score),
+                    aborted: game.aborted,
+                });
+                events
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn seated(&self, game: &WebGameState) -> bool {
+        game.players.iter().any(|p| p.name == self.player)
+    }
+
+    /// What moved since the last update of the player's game. The first
+    /// update seen of a game (after a resume, say) only sets the baseline.
+    fn changes(&mut self, game: &WebGameState) -> Vec<PlayerEvent> {
+        if !self.seated(game) {
+            return Vec::new();
+        }
+        let last = match self.game.take() {
+            Some((id, last)) if id == game.id => last,
+            _ => {
+                self.game = Some((game.id.clone(), game.players.clone()));
+                return Vec::new();
+            }
+        };
+        let mut events = Vec::new();
+        for (now, before) in game.players.iter().zip(&last) {
+            if (now.x, now.y, now.alive) == (before.x, before.y, before.alive) {
+                continue;
+            }
+            if now.name != self.player {
+                events.push(PlayerEvent::OpponentMoved {
+                    game_id: game.id.clone(),
+                    player: now.name.clone(),
+                    x: now.x,
+                    y: now.y,
+                    direction: now.direction,
+                    alive: now.alive,
+                    tick: game.tick,
+                });
+            } else if before.alive && !now.alive {
+                events.push(PlayerEvent::YouCrashed {
+                    game_id: game.id.clone(),
+                    tick: game.tick,
+                });
+            }
+        }
+        self.game = Some((game.id.clone(), game.players.clone()));
+        events
+    }
+}
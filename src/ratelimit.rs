@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Most commands per second `--tcp-rate-limit` may allow
+const MAX_RATE: u32 = 10_000;
+
+/// How many commands one TCP connection may send (`--tcp-rate-limit`).
+/// Every command takes the room's lock, so one client spamming `LOOK` would
+/// hold up everyone else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimit {
+    Off,
+    /// `rate` commands a second on average, with up to `burst` at once
+    PerSec { rate: u32, burst: u32 },
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit::PerSec { rate: 20, burst: 40 }
+    }
+}
+
+impl RateLimit {
+    /// `off`, `<rate>` (a burst of twice the rate) or `<rate>/<burst>`
+    pub fn parse(s: &str) -> Result<RateLimit, String> {
+        let s = s.trim().to_lowercase();
+        if s == "off" {
+            return Ok(RateLimit::Off);
+        }
+        let (rate, burst) = match s.split_once('/') {
+            Some((rate, burst)) => (rate.parse::<u32>().ok(), burst.parse::<u32>().ok()),
+            None => {
+                let rate = s.parse::<u32>().ok();
+                (rate, rate.map(|r| r.saturating_mul(2)))
+            }
+        };
+        match (rate, burst) {
+            (Some(rate), Some(burst)) if (1..=MAX_RATE).contains(&rate) && burst >= 1 => {
+                Ok(RateLimit::PerSec { rate, burst })
+            }
+            _ => Err(format!(
+                "expected 'off', a rate of 1-{} commands per second, or '<rate>/<burst>'",
+                MAX_RATE
+            )),
+        }
+    }
+}
+
+/// One connection's allowance: a bucket of up to `burst` commands that
+/// refills at `rate` a second
+pub struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket, so a fresh connection can send its burst right away
+    pub fn new(limit: RateLimit) -> Self {
+        let tokens = match limit {
+            RateLimit::Off => 0.0,
+            RateLimit::PerSec { burst, .. } => burst as f64,
+        };
+        TokenBucket {
+            limit,
+            tokens,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Spend one command, or say how long until the next one is allowed
+    pub fn take(&mut self) -> Result<(), Duration> {
+        let RateLimit::PerSec { rate, burst } = self.limit else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let refill = now.duration_since(self.refilled_at).as_secs_f64() * rate as f64;
+        self.tokens = (self.tokens + refill).min(burst as f64);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / rate as f64))
+    }
+}
@@ -0,0 +1,223 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Rating a player starts from before their first ranked game
+pub const INITIAL_RATING: i32 = 1200;
+/// Largest rating change from a single game, unless `--elo-k-factor` says otherwise
+pub const DEFAULT_K_FACTOR: f64 = 32.0;
+/// Bounds accepted for `--elo-k-factor`
+const K_FACTOR_RANGE: std::ops::RangeInclusive<f64> = 1.0..=100.0;
+/// Points kept per player after compaction
+const MAX_HISTORY: usize = 200;
+/// Points newer than this are kept at full detail; older ones one per day
+const FULL_DETAIL_DAYS: i64 = 30;
+
+/// A player's rating after one ranked game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingPoint {
+    pub at: DateTime<Utc>,
+    pub game_id: String,
+    pub rating: i32,
+    pub delta: i32,
+}
+
+/// Rating over time for one player, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RatingHistory {
+    pub points: Vec<RatingPoint>,
+}
+
+/// Peak, trough and recent movement of a rating history
+#[derive(Debug, Clone, Serialize)]
+pub struct RatingSummary {
+    pub rating: i32,
+    pub peak: i32,
+    pub trough: i32,
+    pub change_30d: i32,
+    pub change_7d: i32,
+}
+
+impl RatingHistory {
+    pub fn current(&self) -> i32 {
+        self.points.last().map(|p| p.rating).unwrap_or(INITIAL_RATING)
+    }
+
+    /// Append a point and compact: points older than `FULL_DETAIL_DAYS` keep
+    /// only the last one of each day, and at most `MAX_HISTORY` points remain.
+    pub fn record(&mut self, point: RatingPoint) {
+        let now = point.at;
+        self.points.push(point);
+
+        let cutoff = now - Duration::days(FULL_DETAIL_DAYS);
+        let mut compacted: Vec<RatingPoint> = Vec::with_capacity(self.points.len());
+        for point in self.points.drain(..) {
+            if let Some(prev) = compacted.last_mut()
+                && prev.at < cutoff
+                && point.at < cutoff
+                && prev.at.date_naive() == point.at.date_naive()
+            {
+                // Same old day: the later point carries the day's net change
+                let delta = prev.delta + point.delta;
+                *prev = RatingPoint { delta, ..point };
+                continue;
+            }
+            compacted.push(point);
+        }
+        let excess = compacted.len().saturating_sub(MAX_HISTORY);
+        compacted.drain(..excess);
+        self.points = compacted;
+    }
+
+    /// Rating change since `since`, measured from the last rating before it
+    pub fn change_since(&self, since: DateTime<Utc>) -> i32 {
+        let Some(first_recent) = self.points.iter().position(|p| p.at >= since) else {
+            return 0;
+        };
+        let before = match first_recent {
+            0 => self.points[0].rating - self.points[0].delta,
+            i => self.points[i - 1].rating,
+        };
+        self.current() - before
+    }
+
+    pub fn summary(&self, now: DateTime<Utc>) -> RatingSummary {
+        let ratings = self.points.iter().map(|p| p.rating);
+        RatingSummary {
+            rating: self.current(),
+            peak: ratings.clone().max().unwrap_or(INITIAL_RATING),
+            trough: ratings.min().unwrap_or(INITIAL_RATING),
+            change_30d: self.change_since(now - Duration::days(30)),
+            change_7d: self.change_since(now - Duration::days(7)),
+        }
+    }
+}
+
+/// Parse an `--elo-k-factor` value
+pub fn parse_k_factor(s: &str) -> Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(k) if K_FACTOR_RANGE.contains(&k) => Ok(k),
+        _ => Err(format!(
+            "expected a number from {} to {}",
+            K_FACTOR_RANGE.start(),
+            K_FACTOR_RANGE.end()
+        )),
+    }
+}
+
+/// Multiplayer Elo: every pair of players is scored as a match won by the
+/// better-placed one (a draw if `draw`), with `k_factor` split across the
+/// opponents. `ratings` is in finishing order, best first.
+pub fn elo_deltas(ratings: &[i32], draw: bool, k_factor: f64) -> Vec<i32> {
+    let n = ratings.len();
+    if n < 2 {
+        return vec![0; n];
+    }
+    let k = k_factor / (n - 1) as f64;
+    (0..n)
+        .map(|i| {
+            let change: f64 = (0..n)
+                .filter(|&j| j != i)
+                .map(|j| {
+                    let expected =
+                        1.0 / (1.0 + 10f64.powf((ratings[j] - ratings[i]) as f64 / 400.0));
+                    let actual = if draw {
+                        0.5
+                    } else if i < j {
+                        1.0
+                    } else {
+                        0.0
+                    };
+                    k * (actual - expected)
+                })
+                .sum();
+            change.round() as i32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_player_elo_moves_by_how_expected_the_result_was() {
+        assert_eq!(elo_deltas(&[1200, 1200], false, 32.0), [16, -16]);
+        assert_eq!(elo_deltas(&[1200, 1200], true, 32.0), [0, 0]);
+        // An upset pays more than the favorite winning
+        assert_eq!(elo_deltas(&[1200, 1400], false, 32.0), [24, -24]);
+        assert_eq!(elo_deltas(&[1400, 1200], false, 32.0), [8, -8]);
+        assert_eq!(elo_deltas(&[1200, 1200], false, 10.0), [5, -5]);
+    }
+
+    #[test]
+    fn multiplayer_elo_splits_the_k_factor_across_opponents() {
+        assert_eq!(elo_deltas(&[1200, 1200, 1200], false, 32.0), [16, 0, -16]);
+        assert_eq!(elo_deltas(&[1200, 1200, 1200, 1200], false, 30.0), [15, 5, -5, -15]);
+        assert_eq!(elo_deltas(&[1300, 1200, 1100], true, 32.0), [-6, 0, 6]);
+        assert_eq!(elo_deltas(&[1200], false, 32.0), [0]);
+        assert!(elo_deltas(&[], false, 32.0).is_empty());
+    }
+
+    /// `deltas` played one hour apart from `start`, each point recorded as
+    /// the game finishes
+    fn play(history: &mut RatingHistory, start: DateTime<Utc>, deltas: &[i32]) {
+        for (i, &delta) in deltas.iter().enumerate() {
+            let rating = history.current() + delta;
+            history.record(RatingPoint {
+                at: start + Duration::hours(i as i64),
+                game_id: format!("{}-{}", start.date_naive(), i),
+                rating,
+                delta,
+            });
+        }
+    }
+
+    #[test]
+    fn old_points_are_folded_into_one_per_day() {
+        let now: DateTime<Utc> = "2026-06-30T12:00:00Z".parse().unwrap();
+        let mut history = RatingHistory::default();
+        play(&mut history, now - Duration::days(40), &[10, 10, -5]);
+        play(&mut history, now - Duration::days(35), &[-20, 4]);
+        assert_eq!(history.points.len(), 5, "nothing is old yet");
+
+        play(&mut history, now - Duration::days(2), &[8, -3, 6]);
+
+        let kept: Vec<(i32, i32)> = history.points.iter().map(|p| (p.rating, p.delta)).collect();
+        // The last game of each old day, carrying the day's net change; the
+        // last month game by game
+        assert_eq!(kept, [(1215, 15), (1199, -16), (1207, 8), (1204, -3), (1210, 6)]);
+        assert_eq!(history.points[0].game_id, format!("{}-2", (now - Duration::days(40)).date_naive()));
+        assert_eq!(history.current(), 1210);
+    }
+
+    #[test]
+    fn summary_comes_from_the_kept_points() {
+        let now: DateTime<Utc> = "2026-06-30T12:00:00Z".parse().unwrap();
+        let mut history = RatingHistory::default();
+        play(&mut history, now - Duration::days(45), &[30, 20, -60]);
+        play(&mut history, now - Duration::days(20), &[-10]);
+        play(&mut history, now - Duration::days(3), &[25, -5]);
+
+        let summary = history.summary(now);
+        assert_eq!(history.points.len(), 4);
+        assert_eq!(summary.rating, 1200);
+        // The old day's peak of 1250 was folded away with its games
+        assert_eq!((summary.peak, summary.trough), (1205, 1180));
+        assert_eq!(summary.change_30d, 10);
+        assert_eq!(summary.change_7d, 20);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let now: DateTime<Utc> = "2026-06-30T12:00:00Z".parse().unwrap();
+        let mut history = RatingHistory::default();
+        // One game a day for longer than the cap, so compaction can't merge them
+        for day in (0..MAX_HISTORY as i64 + 50).rev() {
+            play(&mut history, now - Duration::days(day), &[1]);
+        }
+
+        assert_eq!(history.points.len(), MAX_HISTORY);
+        assert_eq!(history.current(), INITIAL_RATING + MAX_HISTORY as i32 + 50);
+        assert_eq!(history.points[0].at, now - Duration::days(MAX_HISTORY as i64 - 1));
+    }
+}
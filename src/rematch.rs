@@ -0,0 +1,53 @@
+use uuid::Uuid;
+
+/// How many of a finished game's players must ask for a rematch before it starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RematchQuorum {
+    /// Every player still around
+    #[default]
+    All,
+    /// More than half of them; the rest are left out of the new game
+    Majority,
+}
+
+impl RematchQuorum {
+    pub fn parse(s: &str) -> Result<RematchQuorum, String> {
+        match s.trim().to_lowercase().as_str() {
+            "all" => Ok(RematchQuorum::All),
+            "majority" => Ok(RematchQuorum::Majority),
+            _ => Err("expected 'all' or 'majority'".to_string()),
+        }
+    }
+
+    /// Requests needed out of `eligible` players, never fewer than two
+    pub fn needed(self, eligible: usize) -> usize {
+        let needed = match self {
+            RematchQuorum::All => eligible,
+            RematchQuorum::Majority => eligible / 2 + 1,
+        };
+        needed.max(2)
+    }
+}
+
+/// Players of a finished game asking to play again together, on the next level
+#[derive(Debug, Clone)]
+pub struct Rematch {
+    /// The finished game
+    pub game_id: Uuid,
+    /// Level the rematch is played on
+    pub course_level: u32,
+    /// The game's human players, in seat order
+    pub roster: Vec<String>,
+    /// Who asked so far, in the order they asked
+    pub requested: Vec<String>,
+    /// When the requests lapse and the requesters go to the public queue
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Rematch {
+    /// Whole seconds left to ask as of `now`, rounded up so "0s" means it is over
+    pub fn secs_left(&self, now: chrono::DateTime<chrono::Utc>) -> i64 {
+        let left_ms = (self.expires_at - now).num_milliseconds();
+        (left_ms.max(0) + 999) / 1000
+    }
+}
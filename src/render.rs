@@ -0,0 +1,157 @@
+use crate::game::{Cell, Direction, Game};
+
+/// Key to the glyphs of a board drawn without a viewer
+pub const BOARD_LEGEND: &str = "Legend: A-I = cycle heads, 1-9 = their trails, a-i = trails of crashed players, F = checkpoint, * ~ o = power-ups (speed boost, trail eraser, ghost), # = wall, X = obstruction, . = empty";
+
+/// A teammate's head and trail as the viewer sees them in team games
+pub const TEAMMATE_HEAD: char = 'T';
+pub const TEAMMATE_TRAIL: char = '+';
+
+/// A living cycle's head; its trail is `trail_symbol` of the same index
+pub fn head_symbol(idx: usize) -> char {
+    (b'A' + (idx % 9) as u8) as char
+}
+
+/// A player's trail: digits 1-9 while they ride, letters a-i once they crashed
+pub fn trail_symbol(idx: usize, alive: bool) -> char {
+    let base = if alive { b'1' } else { b'a' };
+    (base + (idx % 9) as u8) as char
+}
+
+/// What cell `(x, y)` shows, as `viewer` sees it when there is one: `@`
+/// the viewer, `|` their trail, `A`-`I` the heads of living cycles, `1`-`9`
+/// their trails, `a`-`i` trails of crashed players, `T` and `+` a
+/// teammate's head and trail in team games, `F` checkpoint, `*` `~` `o`
+/// power-ups, `#` wall or off the grid, `X` obstruction, `.` empty. Every
+/// view of the grid (`look`, minimaps, spectators, recaps, `tronmcp
+/// replay`) draws with this.
+pub fn glyph(game: &Game, x: i32, y: i32, viewer: Option<usize>) -> char {
+    cell(game, x, y, viewer).0
+}
+
+/// Legend entries for power-ups, in games that have them
+pub fn powerup_legend(game: &Game) -> &'static str {
+    if game.rules.boosts { "* = speed boost, ~ = trail eraser, o = ghost, " } else { "" }
+}
+
+/// `glyph` with how much it matters when a downsampled view keeps one glyph
+/// per block: the viewer, then heads, the checkpoint, walls, trails,
+/// obstructions and empty cells last
+pub fn ranked_glyph(game: &Game, x: usize, y: usize, viewer: Option<usize>) -> (char, u8) {
+    cell(game, x as i32, y as i32, viewer)
+}
+
+fn cell(game: &Game, x: i32, y: i32, viewer: Option<usize>) -> (char, u8) {
+    if let Some(viewer) = viewer
+        && (game.players[viewer].x, game.players[viewer].y) == (x, y)
+    {
+        return ('@', 6);
+    }
+    if x < 0 || y < 0 || x >= game.width as i32 || y >= game.height as i32 {
+        return ('#', 3);
+    }
+    if let Some(head) = game.players.iter().position(|p| p.alive && (p.x, p.y) == (x, y)) {
+        if viewer.is_some_and(|viewer| game.teammates(viewer, head)) {
+            return (TEAMMATE_HEAD, 5);
+        }
+        return (head_symbol(head), 5);
+    }
+    match game.grid[y as usize][x as usize] {
+        Cell::Empty if game.checkpoint == Some((x as usize, y as usize)) => ('F', 4),
+        Cell::Empty => ('.', 0),
+        Cell::Wall => ('#', 3),
+        Cell::Obstruction => ('X', 1),
+        Cell::PowerUp(kind) => (kind.glyph(), 4),
+        Cell::Trail(idx) if Some(idx) == viewer => ('|', 2),
+        Cell::Trail(idx) if viewer.is_some_and(|viewer| game.teammates(viewer, idx)) => (TEAMMATE_TRAIL, 2),
+        Cell::Trail(idx) => (trail_symbol(idx, game.players[idx].alive), 2),
+    }
+}
+
+/// The whole grid at full size, rows top to bottom, as nobody in particular sees it
+pub fn board(game: &Game) -> Vec<Vec<char>> {
+    (0..game.height as i32)
+        .map(|y| (0..game.width as i32).map(|x| glyph(game, x, y, None)).collect())
+        .collect()
+}
+
+/// A row of glyphs with a space between each, as `look` prints them
+pub fn spaced_row(row: &[char]) -> String {
+    let cells: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+    cells.join(" ")
+}
+
+/// One row of the compact look: runs of the same cell code separated by
+/// spaces, each the code followed by its length when longer than one, e.g.
+/// `#4 .5 | @ .4`. The first character of a run is always the code, so
+/// trail digits cannot be mistaken for lengths.
+pub fn rle_row(row: &[char]) -> String {
+    let mut runs: Vec<(char, usize)> = Vec::new();
+    for &c in row {
+        match runs.last_mut() {
+            Some((last, n)) if *last == c => *n += 1,
+            _ => runs.push((c, 1)),
+        }
+    }
+    runs.iter()
+        .map(|&(c, n)| if n == 1 { c.to_string() } else { format!("{}{}", c, n) })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Turn a square view window centered on a cycle so that `heading` points
+/// up: row 0 is furthest ahead, column 0 furthest to the cycle's left
+pub fn rotate_to_heading(cells: &[Vec<char>], heading: Direction) -> Vec<Vec<char>> {
+    let r = (cells.len() / 2) as i32;
+    let (fx, fy) = heading.delta();
+    let (rx, ry) = heading.turn_right().delta();
+    (-r..=r)
+        .map(|ey| {
+            (-r..=r)
+                .map(|ex| {
+                    // ex steps to the right and -ey steps ahead, in world terms
+                    let (wx, wy) = (ex * rx - ey * fx, ex * ry - ey * fy);
+                    cells[(wy + r) as usize][(wx + r) as usize]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// Read a compact row back the way the look tool tells agents to: each
+    /// run is its cell code, then how many times it repeats if more than once
+    fn unrle_row(line: &str) -> Vec<char> {
+        line.split(' ')
+            .flat_map(|run| {
+                let mut chars = run.chars();
+                let code = chars.next().unwrap();
+                let count: String = chars.collect();
+                std::iter::repeat_n(code, if count.is_empty() { 1 } else { count.parse().unwrap() })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rle_rows_read_back_as_they_were() {
+        assert_eq!(rle_row(&"####.....|@....".chars().collect::<Vec<_>>()), "#4 .5 | @ .4");
+        assert_eq!(rle_row(&"1112".chars().collect::<Vec<_>>()), "13 2");
+
+        let codes: Vec<char> = "#X.@|ABCI1239abci*~oFT+".chars().collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..500 {
+            let len = rng.gen_range(1..=31);
+            // Few codes per row, so long runs come up as well as single cells
+            let palette: Vec<char> = (0..rng.gen_range(1..=4)).map(|_| codes[rng.gen_range(0..codes.len())]).collect();
+            let row: Vec<char> = (0..len).map(|_| palette[rng.gen_range(0..palette.len())]).collect();
+            let encoded = rle_row(&row);
+            assert_eq!(unrle_row(&encoded), row, "{}", encoded);
+        }
+    }
+}
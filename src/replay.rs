@@ -0,0 +1,418 @@
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::analysis;
+use crate::course::Course;
+use crate::game::{CHECKPOINT_CODE, CLOSED_CODE, Cell, DEAD_TRAIL_CODE, Direction, POWERUP_CODE, Game, GameEventKind, GameStatus, ReplayStep, WebGameState};
+use crate::render;
+use crate::storage;
+
+/// What `tronmcp replay` prints
+#[derive(Debug, Clone, Copy)]
+pub enum Show {
+    /// Every frame, this long apart, then the summary
+    Frames(Duration),
+    /// One frame: 0 is the start, and each step of the replay adds one
+    Frame(usize),
+    /// Scores and crashes only
+    Summary,
+}
+
+/// `tronmcp replay`: load a finished game and play it back in the terminal
+pub async fn run_replay(game: &str, data_dir: &Path, show: Show) -> Result<(), Box<dyn std::error::Error>> {
+    let state = load(game, data_dir)?;
+    match play_back(&state, show).await {
+        // Piped into `head` and the like, which stop reading early
+        Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == ErrorKind::BrokenPipe) => Ok(()),
+        result => result,
+    }
+}
+
+async fn play_back(state: &WebGameState, show: Show) -> Result<(), Box<dyn std::error::Error>> {
+    if let Show::Summary = show {
+        emit(&summary(state))?;
+        return Ok(());
+    }
+    let Some(mut game) = start_of(state)? else {
+        emit(&["No replay was recorded for this game; showing its final board.".to_string()])?;
+        emit(&frame(&final_of(state)?, None, &[]))?;
+        return Ok(());
+    };
+    let steps = state.replay.as_ref().map_or(&[][..], |r| &r.steps[..]);
+    let last = match show {
+        Show::Frame(n) if n > steps.len() => {
+            return Err(format!("Frame {} is past the end; this replay has frames 0 to {}.", n, steps.len()).into());
+        }
+        Show::Frame(n) => n,
+        _ => steps.len(),
+    };
+    let clear = std::io::stdout().is_terminal();
+    for n in 0..=last {
+        let logged = game.events.len();
+        if n > 0 {
+            game.replay_step(&steps[n - 1]);
+        }
+        let Show::Frames(delay) = show else {
+            continue;
+        };
+        let events: Vec<String> = game.events[logged..]
+            .iter()
+            .filter_map(|e| analysis::describe_event(&game, e))
+            .collect();
+        if n > 0 {
+            tokio::time::sleep(delay).await;
+        }
+        let mut lines = frame(&game, Some((n, steps.len())), &events);
+        if clear {
+            lines[0] = format!("\x1b[2J\x1b[H{}", lines[0]);
+        }
+        lines.push(String::new());
+        emit(&lines)?;
+    }
+    match show {
+        Show::Frame(_) => emit(&frame(&game, Some((last, steps.len())), &[]))?,
+        _ => {
+            if game.tick != state.tick {
+                eprintln!(
+                    "The replay ended at tick {} but the game was archived at tick {}; it may have been recorded by another version.",
+                    game.tick, state.tick
+                );
+            }
+            emit(&summary(state))?;
+        }
+    }
+    Ok(())
+}
+
+/// Print `lines` on stdout
+fn emit(lines: &[String]) -> std::io::Result<()> {
+    writeln!(std::io::stdout().lock(), "{}", lines.join("\n"))
+}
+
+/// The finished game in a JSON file (an archive entry, or `GET /api/games/<id>`),
+/// or the one archived in `data_dir` whose id is or starts with `game`
+fn load(game: &str, data_dir: &Path) -> Result<WebGameState, String> {
+    let path = Path::new(game);
+    if path.is_file() {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let state: WebGameState = serde_json::from_str(&json)
+            .map_err(|e| format!("{} does not hold a finished game: {}", path.display(), e))?;
+        if state.status != GameStatus::Finished {
+            return Err(format!("Game {} in {} has not finished.", state.id, path.display()));
+        }
+        return Ok(state);
+    }
+    let prefix = game.trim().to_lowercase();
+    let mut found: Vec<WebGameState> = storage::read_finished_games(data_dir)
+        .into_iter()
+        .filter(|g| !prefix.is_empty() && g.id.starts_with(&prefix))
+        .collect();
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(format!(
+            "No file '{}' and no finished game with that id in {}.",
+            game,
+            data_dir.display()
+        )),
+        n => Err(format!("'{}' matches {} finished games; give more of the id.", game, n)),
+    }
+}
+
+/// The game as it stood when it started, ready to step through its replay;
+/// `None` when it was archived without one
+fn start_of(state: &WebGameState) -> Result<Option<Game>, String> {
+    let Some(replay) = &state.replay else {
+        return Ok(None);
+    };
+    let players = state.players.len();
+    if replay.starts.len() != players {
+        return Ok(None);
+    }
+    let unknown = replay.steps.iter().any(|step| match step {
+        ReplayStep::Move { player, .. } => *player >= players,
+        ReplayStep::Out { players: out, .. } => out.iter().any(|&p| p >= players),
+        ReplayStep::End => false,
+    });
+    if unknown {
+        return Err("The replay moves a player the game does not have.".to_string());
+    }
+    let mut game = seated(state, &replay.starts)?;
+    game.checkpoint = replay.checkpoint;
+    if state.rules.boosts {
+        game.set_powerups(&replay.powerups, replay.powerup_rate);
+    }
+    game.start();
+    Ok(Some(game))
+}
+
+/// The game as it was archived, for games without a replay
+fn final_of(state: &WebGameState) -> Result<Game, String> {
+    let seats: Vec<(i32, i32, Direction)> = state.players.iter().map(|p| (p.x, p.y, p.direction)).collect();
+    let mut game = seated(state, &seats)?;
+    for (y, row) in state.grid.iter().enumerate().take(game.height) {
+        for (x, &code) in row.iter().enumerate().take(game.width) {
+            let owner = match code {
+                CLOSED_CODE => {
+                    game.grid[y][x] = Cell::Wall;
+                    continue;
+                }
+                POWERUP_CODE..=CHECKPOINT_CODE => continue,
+                c if c >= DEAD_TRAIL_CODE => (c - DEAD_TRAIL_CODE) as usize,
+                c if c >= 3 => (c - 3) as usize,
+                _ => continue,
+            };
+            if owner < game.players.len() {
+                game.grid[y][x] = Cell::Trail(owner);
+            }
+        }
+    }
+    for (player, archived) in game.players.iter_mut().zip(&state.players) {
+        player.alive = archived.alive;
+        player.distance_traveled = archived.distance;
+        player.score = archived.score;
+    }
+    game.status = state.status;
+    game.tick = state.tick;
+    game.winner = state.winner;
+    game.winning_team = state.winning_team;
+    Ok(game)
+}
+
+/// A game on the archived game's walls and obstructions, nobody having
+/// moved yet, with each player at their seat
+fn seated(state: &WebGameState, seats: &[(i32, i32, Direction)]) -> Result<Game, String> {
+    let mut course = Course {
+        name: state.course_name.clone(),
+        level: state.course_level,
+        width: state.width,
+        height: state.height,
+        max_trail_length: state.rules.max_trail_length,
+        max_players: seats.len(),
+        obstructions: Vec::new(),
+        walls: Vec::new(),
+        checkpoint: None,
+        race: false,
+        powerups: Vec::new(),
+        powerup_rate: None,
+        shrink_every: None,
+        lives: None,
+        max_ticks: None,
+        win_condition: None,
+        dead_trail_policy: None,
+        generator: None,
+        tags: Vec::new(),
+        spawns: seats
+            .iter()
+            .map(|&(x, y, dir)| (x.max(0) as usize, y.max(0) as usize, dir))
+            .collect(),
+        seed: state.course_seed,
+    };
+    for (y, row) in state.grid.iter().enumerate() {
+        for (x, &code) in row.iter().enumerate() {
+            match code {
+                1 => course.walls.push((x, y)),
+                2 => course.obstructions.push((x, y)),
+                CHECKPOINT_CODE => course.checkpoint = Some((x, y)),
+                _ => {}
+            }
+        }
+    }
+    let mut game = Game::new(&course, state.rules.clone())?;
+    if let Ok(id) = uuid::Uuid::parse_str(&state.id) {
+        // Coin-flip tie-breaks are seeded from the id
+        game.id = id;
+    }
+    for (archived, &(x, y, direction)) in state.players.iter().zip(seats) {
+        let idx = game
+            .add_player(archived.name.clone())
+            .ok_or_else(|| format!("The course has no seat for {}.", archived.name))?;
+        let player = &mut game.players[idx];
+        (player.x, player.y, player.direction) = (x, y, direction);
+    }
+    // Teammates ride through each other's trails in the replay too
+    game.teams = state.players.iter().map(|p| p.team).collect();
+    Ok(game)
+}
+
+/// The whole board with a line on who is where; `at` is the frame number
+/// and how many steps the replay has
+fn frame(game: &Game, at: Option<(usize, usize)>, events: &[String]) -> Vec<String> {
+    let progress = match at {
+        Some((n, steps)) => format!("frame {}/{}, tick {}", n, steps, game.tick),
+        None => format!("tick {}", game.tick),
+    };
+    let mut lines = vec![format!(
+        "{} (Level {}), {}, {}/{} alive:",
+        game.course_name,
+        game.course_level,
+        progress,
+        game.players.iter().filter(|p| p.alive).count(),
+        game.players.len()
+    )];
+    lines.extend(render::board(game).iter().map(|row| render::spaced_row(row)));
+    for (idx, p) in game.players.iter().enumerate() {
+        lines.push(if p.alive {
+            format!(
+                "  {} {}: at ({}, {}) heading {}, traveled {}",
+                render::head_symbol(idx),
+                p.name,
+                p.x,
+                p.y,
+                p.direction.name(),
+                p.distance_traveled
+            )
+        } else {
+            format!("  {} {}: CRASHED, traveled {}", render::trail_symbol(idx, false), p.name, p.distance_traveled)
+        });
+    }
+    lines.extend(events.iter().map(|e| format!("  {}", e)));
+    lines.push(render::BOARD_LEGEND.to_string());
+    lines
+}
+
+/// Who crashed when, and the final scores
+fn summary(state: &WebGameState) -> Vec<String> {
+    let mut lines = vec![format!(
+        "Game {} on {} (Level {}), finished at tick {}{}:",
+        state.id,
+        state.course_name,
+        state.course_level,
+        state.tick,
+        if state.aborted { ", aborted" } else { "" }
+    )];
+    let crashes: Vec<String> = state
+        .events
+        .iter()
+        .filter(|e| matches!(e.kind, GameEventKind::Crashed { .. }))
+        .filter_map(|e| Some(format!("  Tick {}: {}", e.tick, e.text.as_deref()?)))
+        .collect();
+    if crashes.is_empty() {
+        lines.push("Nobody crashed.".to_string());
+    } else {
+        lines.push("Crashes:".to_string());
+        lines.extend(crashes);
+    }
+    lines.push("Scores:".to_string());
+    for p in &state.players {
+        let winner = if let Some(team) = state.winning_team.filter(|&team| p.team == Some(team)) {
+            match state.tie_break {
+                Some(reason) => format!(" (Team {} won, on {})", team, reason.name()),
+                None => format!(" (Team {} won)", team),
+            }
+        } else if state.winner == Some(p.index) {
+            match state.tie_break {
+                Some(reason) => format!(" (winner, on {})", reason.name()),
+                None => " (winner)".to_string(),
+            }
+        } else {
+            String::new()
+        };
+        lines.push(format!("  {}: {}{}, traveled {}", p.name, p.score, winner, p.distance));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::SteerAction;
+    use crate::testutil;
+
+    /// A two-player game played to the end, archived with its replay as the
+    /// manager archives it: Gemini turns into the wall, Claude is left alone
+    fn archived() -> WebGameState {
+        let mut game = testutil::game(
+            "
+            ##########
+            #1.......#
+            #........#
+            #....X...#
+            #........#
+            #........#
+            #.......2#
+            ##########",
+            &["Claude", "Gemini"],
+        );
+        for _ in 0..10 {
+            if game.status != GameStatus::Running {
+                break;
+            }
+            game.move_player(0, SteerAction::Straight);
+            game.move_player(1, SteerAction::Right);
+        }
+        assert_eq!(game.status, GameStatus::Finished);
+        let mut state = game.to_web_state();
+        state.replay = Some(game.replay.clone());
+        state
+    }
+
+    #[test]
+    fn replay_ends_on_the_archived_board() {
+        let state = archived();
+        let mut game = start_of(&state).unwrap().expect("the game has a replay");
+        assert_eq!(game.tick, 0);
+        for step in &state.replay.as_ref().unwrap().steps {
+            game.replay_step(step);
+        }
+
+        assert_eq!(game.tick, state.tick);
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(frame(&game, None, &[]), frame(&final_of(&state).unwrap(), None, &[]));
+    }
+
+    #[test]
+    fn games_without_a_replay_show_their_final_board() {
+        let mut state = archived();
+        state.replay = None;
+        assert!(start_of(&state).unwrap().is_none());
+        let lines = frame(&final_of(&state).unwrap(), None, &[]);
+        assert!(lines[0].starts_with(&format!("Test Course (Level 1), tick {}, 1/2 alive:", state.tick)), "{}", lines[0]);
+        assert!(lines.iter().any(|l| l.contains("Gemini: CRASHED")), "{:#?}", lines);
+    }
+
+    #[test]
+    fn summary_lists_the_crash_and_the_winner() {
+        let state = archived();
+        let lines = summary(&state);
+        assert!(lines[0].starts_with(&format!("Game {} on Test Course (Level 1), finished at tick {}:", state.id, state.tick)));
+        assert_eq!(lines[1], "Crashes:");
+        assert!(lines[2].contains("Gemini"), "{}", lines[2]);
+        assert!(lines.iter().any(|l| l.starts_with("  Claude: ") && l.contains("(winner)")), "{:#?}", lines);
+    }
+
+    #[test]
+    fn replays_moving_unknown_players_are_refused() {
+        let mut state = archived();
+        state.replay.as_mut().unwrap().steps.push(ReplayStep::Move {
+            player: 2,
+            action: SteerAction::Straight,
+        });
+        assert!(start_of(&state).err().unwrap().contains("does not have"));
+    }
+
+    #[tokio::test]
+    async fn frames_past_the_end_are_refused() {
+        let state = archived();
+        let steps = state.replay.as_ref().unwrap().steps.len();
+        let err = play_back(&state, Show::Frame(steps + 1)).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Frame {} is past the end; this replay has frames 0 to {}.", steps + 1, steps)
+        );
+    }
+
+    #[test]
+    fn games_load_from_a_file_only_once_finished() {
+        let dir = testutil::ScratchDir::new();
+        let path = dir.path().join("game.json");
+        let mut state = archived();
+        std::fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(load(path.to_str().unwrap(), dir.path()).unwrap().id, state.id);
+
+        state.status = GameStatus::Running;
+        std::fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+        assert!(load(path.to_str().unwrap(), dir.path()).unwrap_err().contains("has not finished"));
+        assert!(load("no-such-game", dir.path()).unwrap_err().starts_with("No file 'no-such-game'"));
+    }
+}
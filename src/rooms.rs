@@ -0,0 +1,65 @@
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::manager::{GameManager, SharedGameManager};
+use crate::storage::SharedStorage;
+
+/// Independent arenas served from one process. Each room has its own queue,
+/// games, lobbies and leaderboard; the first room is the default.
+pub struct Rooms {
+    rooms: Vec<(String, SharedGameManager)>,
+}
+
+impl Rooms {
+    /// Create one manager per room, with the storage `open_storage` gives for
+    /// the room's name and directory. The default room keeps `data_dir` itself
+    /// so existing data loads unchanged; other rooms live in
+    /// `data_dir/rooms/<name>`.
+    pub fn new(
+        names: &[String],
+        data_dir: &Path,
+        open_storage: impl Fn(&str, &Path) -> Result<SharedStorage, String>,
+        configure: impl Fn(&mut GameManager),
+    ) -> Result<Self, String> {
+        let rooms = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let dir = if i == 0 {
+                    data_dir.to_path_buf()
+                } else {
+                    data_dir.join("rooms").join(name)
+                };
+                let storage = open_storage(name, &dir)?;
+                let (mut manager, _rx) = GameManager::new(dir, storage);
+                manager.room = name.clone();
+                configure(&mut manager);
+                Ok((name.clone(), Arc::new(Mutex::new(manager))))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Rooms { rooms })
+    }
+
+    pub fn default_name(&self) -> &str {
+        &self.rooms[0].0
+    }
+
+    pub fn default_room(&self) -> &SharedGameManager {
+        &self.rooms[0].1
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SharedGameManager> {
+        self.rooms.iter().find(|(n, _)| n == name).map(|(_, m)| m)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SharedGameManager)> {
+        self.rooms.iter().map(|(n, m)| (n.as_str(), m))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.rooms.iter().map(|(n, _)| n.clone()).collect()
+    }
+}
+
+pub type SharedRooms = Arc<Rooms>;
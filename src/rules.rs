@@ -0,0 +1,616 @@
+use rmcp::schemars;
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::course::Course;
+
+/// How a game is decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WinCondition {
+    /// Last cycle alive wins
+    LastStanding,
+    /// First cycle to reach the course checkpoint wins
+    Checkpoint,
+    /// When time runs out (or one cycle is left), the most trail cells on
+    /// the grid wins
+    Territory,
+}
+
+impl WinCondition {
+    pub fn name(self) -> &'static str {
+        match self {
+            WinCondition::LastStanding => "last_standing",
+            WinCondition::Checkpoint => "checkpoint",
+            WinCondition::Territory => "territory",
+        }
+    }
+}
+
+/// How a game is decided when the last cycles crash on the same tick
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// The cycle that traveled furthest wins; equal distances go to a coin flip
+    #[default]
+    Distance,
+    /// Nobody wins
+    Draw,
+}
+
+impl TieBreak {
+    pub fn name(self) -> &'static str {
+        match self {
+            TieBreak::Distance => "distance",
+            TieBreak::Draw => "draw",
+        }
+    }
+}
+
+/// What happens to a crashed cycle's trail
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadTrailPolicy {
+    /// The trail stays for the rest of the game
+    #[default]
+    Keep,
+    /// The trail disappears the moment the cycle crashes
+    ClearImmediately,
+    /// After this many ticks the trail fades one cell per tick, oldest first
+    Decay(u32),
+}
+
+impl std::fmt::Display for DeadTrailPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeadTrailPolicy::Keep => f.write_str("keep"),
+            DeadTrailPolicy::ClearImmediately => f.write_str("clear_immediately"),
+            DeadTrailPolicy::Decay(ticks) => write!(f, "decay after {} ticks", ticks),
+        }
+    }
+}
+
+/// The effective rules a game is played under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruleset {
+    /// Trail cells kept behind each cycle before the oldest fade; 0 keeps
+    /// every cell for the whole game, as in classic Tron
+    pub max_trail_length: usize,
+    /// Seconds a player may go without steering before crashing
+    pub move_timeout_secs: Option<u64>,
+    pub win_condition: WinCondition,
+    /// Power-up pickups on the grid: speed boost, trail eraser and ghost
+    /// (requires a course with power-ups)
+    pub boosts: bool,
+    /// Leaving one edge of the grid re-enters from the opposite edge
+    pub wrap_edges: bool,
+    /// From this tick on, trails stop fading
+    pub sudden_death_tick: Option<u32>,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+    #[serde(default)]
+    pub dead_trail_policy: DeadTrailPolicy,
+    /// Two teams of this many players; the last team with a cycle alive wins
+    #[serde(default)]
+    pub team_size: Option<usize>,
+    /// Teammates' trails are lethal too
+    #[serde(default)]
+    pub friendly_fire: bool,
+    /// Territory games end at this tick; `DEFAULT_TERRITORY_TICKS` when unset
+    #[serde(default)]
+    pub territory_ticks: Option<u32>,
+    /// Every this many ticks the outermost ring of the arena becomes wall
+    #[serde(default)]
+    pub shrink_every: Option<u32>,
+    /// Moves at the start during which other players' trails can't crash
+    /// you; `DEFAULT_SPAWN_GRACE` when unset
+    #[serde(default)]
+    pub spawn_grace: Option<u32>,
+    /// Lives each player starts with; a crash with lives left respawns the
+    /// cycle, and the last player with lives wins
+    #[serde(default)]
+    pub lives: Option<u32>,
+    /// The game ends at this tick; the longest distance traveled wins
+    #[serde(default)]
+    pub max_ticks: Option<u32>,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            max_trail_length: 50,
+            move_timeout_secs: None,
+            win_condition: WinCondition::LastStanding,
+            boosts: false,
+            wrap_edges: false,
+            sudden_death_tick: None,
+            tie_break: TieBreak::default(),
+            dead_trail_policy: DeadTrailPolicy::default(),
+            team_size: None,
+            friendly_fire: false,
+            territory_ticks: None,
+            shrink_every: None,
+            spawn_grace: None,
+            lives: None,
+            max_ticks: None,
+        }
+    }
+}
+
+/// Partial ruleset supplied at lobby creation, overlaid on the course defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RulesOverride {
+    /// Trail cells kept behind each cycle; 0 means trails never fade
+    pub max_trail_length: Option<usize>,
+    /// Seconds a player may go without steering before crashing (1-600)
+    pub move_timeout_secs: Option<u64>,
+    /// "last_standing", "checkpoint" or "territory"
+    pub win_condition: Option<WinCondition>,
+    /// Enable power-up pickups (speed boost, trail eraser, ghost)
+    pub boosts: Option<bool>,
+    /// Wrap around the grid edges instead of crashing into a border wall
+    pub wrap_edges: Option<bool>,
+    /// Tick after which trails stop fading
+    pub sudden_death_tick: Option<u32>,
+    /// "distance" or "draw": who wins when the last cycles crash together
+    pub tie_break: Option<TieBreak>,
+    /// "keep", "clear_immediately" or {"decay": ticks}: what happens to a
+    /// crashed cycle's trail
+    pub dead_trail_policy: Option<DeadTrailPolicy>,
+    /// Play two teams of this many players (2-4); the last team with a cycle
+    /// alive wins
+    pub team_size: Option<usize>,
+    /// In team games, crash into teammates' trails too (default false)
+    pub friendly_fire: Option<bool>,
+    /// Tick at which a territory game ends (1-100000, default 200)
+    pub territory_ticks: Option<u32>,
+    /// Close the outermost ring of the arena into wall every this many
+    /// ticks (1-100000), crashing anyone on it
+    pub shrink_every: Option<u32>,
+    /// Moves at the start during which other players' trails can't crash
+    /// you (0-50, default 3; 0 turns it off)
+    pub spawn_grace: Option<u32>,
+    /// Lives each player starts with (1-9); a crash with lives left respawns
+    /// the cycle near its start
+    pub lives: Option<u32>,
+    /// End the game at this tick (1-100000); the longest distance traveled
+    /// wins, then the most kills
+    pub max_ticks: Option<u32>,
+}
+
+const MAX_TRAIL_LENGTH: usize = 10_000;
+pub const MAX_MOVE_TIMEOUT_SECS: u64 = 600;
+const MAX_SUDDEN_DEATH_TICK: u32 = 100_000;
+const MAX_DECAY_DELAY: u32 = 10_000;
+const MAX_TEAM_SIZE: usize = 4;
+/// How long a territory game runs unless the rules say otherwise
+pub const DEFAULT_TERRITORY_TICKS: u32 = 200;
+const MAX_TERRITORY_TICKS: u32 = 100_000;
+const MAX_SHRINK_EVERY: u32 = 100_000;
+/// Moves of spawn protection each player gets unless the rules say otherwise
+pub const DEFAULT_SPAWN_GRACE: u32 = 3;
+const MAX_SPAWN_GRACE: u32 = 50;
+const MAX_LIVES: u32 = 9;
+const MAX_TICK_LIMIT: u32 = 100_000;
+
+impl RulesOverride {
+    /// Range-check the supplied fields, independent of any course
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(len) = self.max_trail_length
+            && len > MAX_TRAIL_LENGTH
+        {
+            return Err(format!(
+                "'rules.max_trail_length' must be between 0 (trails never fade) and {}.",
+                MAX_TRAIL_LENGTH
+            ));
+        }
+        if let Some(secs) = self.move_timeout_secs
+            && !(1..=MAX_MOVE_TIMEOUT_SECS).contains(&secs)
+        {
+            return Err(format!(
+                "'rules.move_timeout_secs' must be between 1 and {}.",
+                MAX_MOVE_TIMEOUT_SECS
+            ));
+        }
+        if let Some(tick) = self.sudden_death_tick
+            && !(1..=MAX_SUDDEN_DEATH_TICK).contains(&tick)
+        {
+            return Err(format!(
+                "'rules.sudden_death_tick' must be between 1 and {}.",
+                MAX_SUDDEN_DEATH_TICK
+            ));
+        }
+        if let Some(ticks) = self.shrink_every
+            && !(1..=MAX_SHRINK_EVERY).contains(&ticks)
+        {
+            return Err(format!("'rules.shrink_every' must be between 1 and {}.", MAX_SHRINK_EVERY));
+        }
+        if let Some(moves) = self.spawn_grace
+            && moves > MAX_SPAWN_GRACE
+        {
+            return Err(format!("'rules.spawn_grace' must be between 0 and {}.", MAX_SPAWN_GRACE));
+        }
+        if let Some(lives) = self.lives
+            && !(1..=MAX_LIVES).contains(&lives)
+        {
+            return Err(format!("'rules.lives' must be between 1 and {}.", MAX_LIVES));
+        }
+        if let Some(ticks) = self.max_ticks
+            && !(1..=MAX_TICK_LIMIT).contains(&ticks)
+        {
+            return Err(format!("'rules.max_ticks' must be between 1 and {}.", MAX_TICK_LIMIT));
+        }
+        if let Some(DeadTrailPolicy::Decay(ticks)) = self.dead_trail_policy
+            && ticks > MAX_DECAY_DELAY
+        {
+            return Err(format!(
+                "'rules.dead_trail_policy' decay must start within {} ticks.",
+                MAX_DECAY_DELAY
+            ));
+        }
+        if let Some(size) = self.team_size
+            && !(2..=MAX_TEAM_SIZE).contains(&size)
+        {
+            return Err(format!("'rules.team_size' must be between 2 and {}.", MAX_TEAM_SIZE));
+        }
+        if let Some(ticks) = self.territory_ticks
+            && !(1..=MAX_TERRITORY_TICKS).contains(&ticks)
+        {
+            return Err(format!(
+                "'rules.territory_ticks' must be between 1 and {}.",
+                MAX_TERRITORY_TICKS
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Ruleset {
+    /// Trail cells kept behind each cycle; None when trails never fade
+    pub fn trail_limit(&self) -> Option<usize> {
+        (self.max_trail_length > 0).then_some(self.max_trail_length)
+    }
+
+    /// The tick a territory game ends at
+    pub fn territory_end(&self) -> u32 {
+        self.territory_ticks.unwrap_or(DEFAULT_TERRITORY_TICKS)
+    }
+
+    /// Moves of spawn protection each player starts with
+    pub fn spawn_grace_moves(&self) -> u32 {
+        self.spawn_grace.unwrap_or(DEFAULT_SPAWN_GRACE)
+    }
+
+    /// Apply an override on top of this ruleset
+    pub fn overlay(&self, overrides: &RulesOverride) -> Ruleset {
+        Ruleset {
+            max_trail_length: overrides.max_trail_length.unwrap_or(self.max_trail_length),
+            move_timeout_secs: overrides.move_timeout_secs.or(self.move_timeout_secs),
+            win_condition: overrides.win_condition.unwrap_or(self.win_condition),
+            boosts: overrides.boosts.unwrap_or(self.boosts),
+            wrap_edges: overrides.wrap_edges.unwrap_or(self.wrap_edges),
+            sudden_death_tick: overrides.sudden_death_tick.or(self.sudden_death_tick),
+            tie_break: overrides.tie_break.unwrap_or(self.tie_break),
+            dead_trail_policy: overrides.dead_trail_policy.unwrap_or(self.dead_trail_policy),
+            team_size: overrides.team_size.or(self.team_size),
+            friendly_fire: overrides.friendly_fire.unwrap_or(self.friendly_fire),
+            territory_ticks: overrides.territory_ticks.or(self.territory_ticks),
+            shrink_every: overrides.shrink_every.or(self.shrink_every),
+            spawn_grace: overrides.spawn_grace.or(self.spawn_grace),
+            lives: overrides.lives.or(self.lives),
+            max_ticks: overrides.max_ticks.or(self.max_ticks),
+        }
+    }
+
+    /// Check that the ruleset makes sense on the given course
+    pub fn validate(&self, course: &Course) -> Result<(), String> {
+        let cells = course.width * course.height;
+        if self.max_trail_length > cells {
+            return Err(format!(
+                "max_trail_length must be between 0 (trails never fade) and {} on {}.",
+                cells, course.name
+            ));
+        }
+        if let Some(secs) = self.move_timeout_secs
+            && !(1..=MAX_MOVE_TIMEOUT_SECS).contains(&secs)
+        {
+            return Err(format!(
+                "move_timeout_secs must be between 1 and {}.",
+                MAX_MOVE_TIMEOUT_SECS
+            ));
+        }
+        if let Some(tick) = self.sudden_death_tick
+            && !(1..=MAX_SUDDEN_DEATH_TICK).contains(&tick)
+        {
+            return Err(format!(
+                "sudden_death_tick must be between 1 and {}.",
+                MAX_SUDDEN_DEATH_TICK
+            ));
+        }
+        if let Some(ticks) = self.shrink_every
+            && !(1..=MAX_SHRINK_EVERY).contains(&ticks)
+        {
+            return Err(format!("shrink_every must be between 1 and {}.", MAX_SHRINK_EVERY));
+        }
+        if self.spawn_grace_moves() > MAX_SPAWN_GRACE {
+            return Err(format!("spawn_grace must be between 0 and {}.", MAX_SPAWN_GRACE));
+        }
+        if let Some(lives) = self.lives
+            && !(1..=MAX_LIVES).contains(&lives)
+        {
+            return Err(format!("lives must be between 1 and {}.", MAX_LIVES));
+        }
+        if let Some(ticks) = self.max_ticks
+            && !(1..=MAX_TICK_LIMIT).contains(&ticks)
+        {
+            return Err(format!("max_ticks must be between 1 and {}.", MAX_TICK_LIMIT));
+        }
+        if let DeadTrailPolicy::Decay(ticks) = self.dead_trail_policy
+            && ticks > MAX_DECAY_DELAY
+        {
+            return Err(format!(
+                "dead_trail_policy decay must start within {} ticks.",
+                MAX_DECAY_DELAY
+            ));
+        }
+        if self.win_condition == WinCondition::Checkpoint && course.checkpoint.is_none() {
+            return Err(format!(
+                "win_condition 'checkpoint' needs a course with a checkpoint; {} has none.",
+                course.name
+            ));
+        }
+        if self.boosts && !course.has_powerups() {
+            return Err(format!("boosts need a course with power-ups; {} has none.", course.name));
+        }
+        if let Some(size) = self.team_size
+            && (size < 2 || size * 2 > course.max_players)
+        {
+            return Err(format!(
+                "team_size must be between 2 and {} on {}, which seats {}.",
+                course.max_players / 2,
+                course.name,
+                course.max_players
+            ));
+        }
+        if self.friendly_fire && self.team_size.is_none() {
+            return Err("friendly_fire only applies to team games; set team_size too.".to_string());
+        }
+        if let Some(ticks) = self.territory_ticks {
+            if !(1..=MAX_TERRITORY_TICKS).contains(&ticks) {
+                return Err(format!("territory_ticks must be between 1 and {}.", MAX_TERRITORY_TICKS));
+            }
+            if self.win_condition != WinCondition::Territory {
+                return Err("territory_ticks only applies with win_condition 'territory'.".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Human-readable list of rules that differ from `base`
+    pub fn diff(&self, base: &Ruleset) -> Vec<String> {
+        fn opt<T: std::fmt::Display>(v: Option<T>) -> String {
+            v.map(|v| v.to_string()).unwrap_or_else(|| "off".to_string())
+        }
+
+        let mut lines = Vec::new();
+        if self.max_trail_length != base.max_trail_length {
+            let len = |rules: &Ruleset| match rules.trail_limit() {
+                Some(len) => len.to_string(),
+                None => "permanent".to_string(),
+            };
+            lines.push(format!("max_trail_length: {} -> {}", len(base), len(self)));
+        }
+        if self.move_timeout_secs != base.move_timeout_secs {
+            lines.push(format!(
+                "move_timeout_secs: {} -> {}",
+                opt(base.move_timeout_secs),
+                opt(self.move_timeout_secs)
+            ));
+        }
+        if self.win_condition != base.win_condition {
+            lines.push(format!(
+                "win_condition: {} -> {}",
+                base.win_condition.name(),
+                self.win_condition.name()
+            ));
+        }
+        if self.boosts != base.boosts {
+            lines.push(format!("boosts: {} -> {}", base.boosts, self.boosts));
+        }
+        if self.wrap_edges != base.wrap_edges {
+            lines.push(format!("wrap_edges: {} -> {}", base.wrap_edges, self.wrap_edges));
+        }
+        if self.sudden_death_tick != base.sudden_death_tick {
+            lines.push(format!(
+                "sudden_death_tick: {} -> {}",
+                opt(base.sudden_death_tick),
+                opt(self.sudden_death_tick)
+            ));
+        }
+        if self.tie_break != base.tie_break {
+            lines.push(format!(
+                "tie_break: {} -> {}",
+                base.tie_break.name(),
+                self.tie_break.name()
+            ));
+        }
+        if self.dead_trail_policy != base.dead_trail_policy {
+            lines.push(format!(
+                "dead_trail_policy: {} -> {}",
+                base.dead_trail_policy, self.dead_trail_policy
+            ));
+        }
+        if self.team_size != base.team_size {
+            lines.push(format!(
+                "team_size: {} -> {}",
+                opt(base.team_size),
+                opt(self.team_size)
+            ));
+        }
+        if self.friendly_fire != base.friendly_fire {
+            lines.push(format!("friendly_fire: {} -> {}", base.friendly_fire, self.friendly_fire));
+        }
+        if self.territory_ticks != base.territory_ticks {
+            lines.push(format!(
+                "territory_ticks: {} -> {}",
+                base.territory_end(),
+                self.territory_end()
+            ));
+        }
+        if self.shrink_every != base.shrink_every {
+            lines.push(format!(
+                "shrink_every: {} -> {}",
+                opt(base.shrink_every),
+                opt(self.shrink_every)
+            ));
+        }
+        if self.spawn_grace_moves() != base.spawn_grace_moves() {
+            lines.push(format!(
+                "spawn_grace: {} -> {}",
+                base.spawn_grace_moves(),
+                self.spawn_grace_moves()
+            ));
+        }
+        if self.lives != base.lives {
+            lines.push(format!("lives: {} -> {}", opt(base.lives), opt(self.lives)));
+        }
+        if self.max_ticks != base.max_ticks {
+            lines.push(format!("max_ticks: {} -> {}", opt(base.max_ticks), opt(self.max_ticks)));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty 20x20 course with the usual spawn points
+    fn course() -> Course {
+        Course::from_ascii("Test", 1, &vec![".".repeat(20); 20].join("\n")).unwrap()
+    }
+
+    fn parse(json: &str) -> RulesOverride {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn empty_override_falls_through_to_the_defaults() {
+        let base = course().default_rules();
+        let rules = base.overlay(&RulesOverride::default());
+        assert_eq!(rules, base);
+        assert!(rules.diff(&base).is_empty());
+        assert!(RulesOverride::default().validate().is_ok());
+    }
+
+    type Check = fn(&Ruleset) -> bool;
+
+    #[test]
+    fn each_field_overlays_only_itself() {
+        let base = course().default_rules();
+        let cases: [(&str, Check); 16] = [
+            (r#"{"max_trail_length": 80}"#, |r| r.max_trail_length == 80),
+            (r#"{"max_trail_length": 0}"#, |r| r.trail_limit().is_none()),
+            (r#"{"move_timeout_secs": 30}"#, |r| r.move_timeout_secs == Some(30)),
+            (r#"{"win_condition": "territory"}"#, |r| r.win_condition == WinCondition::Territory),
+            (r#"{"boosts": true}"#, |r| r.boosts),
+            (r#"{"wrap_edges": true}"#, |r| r.wrap_edges),
+            (r#"{"sudden_death_tick": 100}"#, |r| r.sudden_death_tick == Some(100)),
+            (r#"{"tie_break": "draw"}"#, |r| r.tie_break == TieBreak::Draw),
+            (
+                r#"{"dead_trail_policy": {"decay": 5}}"#,
+                |r| r.dead_trail_policy == DeadTrailPolicy::Decay(5),
+            ),
+            (r#"{"team_size": 2}"#, |r| r.team_size == Some(2)),
+            (r#"{"friendly_fire": true}"#, |r| r.friendly_fire),
+            (r#"{"territory_ticks": 150}"#, |r| r.territory_end() == 150),
+            (r#"{"shrink_every": 25}"#, |r| r.shrink_every == Some(25)),
+            (r#"{"spawn_grace": 0}"#, |r| r.spawn_grace_moves() == 0),
+            (r#"{"lives": 3}"#, |r| r.lives == Some(3)),
+            (r#"{"max_ticks": 500}"#, |r| r.max_ticks == Some(500)),
+        ];
+        for (json, check) in cases {
+            let overrides = parse(json);
+            assert!(overrides.validate().is_ok(), "{} should validate", json);
+            let rules = base.overlay(&overrides);
+            assert!(check(&rules), "{} was not applied", json);
+            assert_eq!(rules.diff(&base).len(), 1, "{} should change exactly one rule", json);
+        }
+    }
+
+    #[test]
+    fn out_of_range_fields_are_rejected_by_name() {
+        let cases = [
+            (r#"{"max_trail_length": 10001}"#, "max_trail_length"),
+            (r#"{"move_timeout_secs": 0}"#, "move_timeout_secs"),
+            (r#"{"move_timeout_secs": 601}"#, "move_timeout_secs"),
+            (r#"{"sudden_death_tick": 0}"#, "sudden_death_tick"),
+            (r#"{"sudden_death_tick": 100001}"#, "sudden_death_tick"),
+            (r#"{"dead_trail_policy": {"decay": 10001}}"#, "dead_trail_policy"),
+            (r#"{"team_size": 1}"#, "team_size"),
+            (r#"{"team_size": 5}"#, "team_size"),
+            (r#"{"territory_ticks": 0}"#, "territory_ticks"),
+            (r#"{"shrink_every": 0}"#, "shrink_every"),
+            (r#"{"spawn_grace": 51}"#, "spawn_grace"),
+            (r#"{"lives": 0}"#, "lives"),
+            (r#"{"lives": 10}"#, "lives"),
+            (r#"{"max_ticks": 0}"#, "max_ticks"),
+        ];
+        for (json, field) in cases {
+            let err = parse(json).validate().unwrap_err();
+            assert!(err.contains(field), "{}: '{}' doesn't name {}", json, err, field);
+        }
+    }
+
+    #[test]
+    fn boundary_values_are_accepted() {
+        for json in [
+            r#"{"max_trail_length": 10000}"#,
+            r#"{"move_timeout_secs": 1}"#,
+            r#"{"move_timeout_secs": 600}"#,
+            r#"{"sudden_death_tick": 100000}"#,
+            r#"{"team_size": 4}"#,
+            r#"{"spawn_grace": 50}"#,
+            r#"{"lives": 9}"#,
+        ] {
+            assert!(parse(json).validate().is_ok(), "{} should validate", json);
+        }
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(serde_json::from_str::<RulesOverride>(r#"{"max_trail": 5}"#).is_err());
+    }
+
+    #[test]
+    fn rules_are_checked_against_the_course() {
+        let course = course();
+        let base = course.default_rules();
+        let check = |json: &str| base.overlay(&parse(json)).validate(&course);
+
+        assert!(check(r#"{"max_trail_length": 400}"#).is_ok());
+        assert!(check(r#"{"max_trail_length": 401}"#).unwrap_err().contains("max_trail_length"));
+        assert!(check(r#"{"win_condition": "checkpoint"}"#).unwrap_err().contains("checkpoint"));
+        assert!(check(r#"{"boosts": true}"#).unwrap_err().contains("power-ups"));
+        assert!(check(r#"{"team_size": 3}"#).unwrap_err().contains("team_size"));
+        assert!(check(r#"{"friendly_fire": true}"#).unwrap_err().contains("team_size"));
+        assert!(check(r#"{"territory_ticks": 100}"#).unwrap_err().contains("territory"));
+        assert!(check(r#"{"win_condition": "territory", "territory_ticks": 100}"#).is_ok());
+    }
+
+    #[test]
+    fn wrap_edges_combine_with_a_checkpoint_win() {
+        let mut course = course();
+        course.checkpoint = Some((10, 10));
+        let rules = course.default_rules().overlay(&parse(r#"{"win_condition": "checkpoint", "wrap_edges": true}"#));
+        assert!(rules.validate(&course).is_ok());
+    }
+
+    #[test]
+    fn diff_describes_the_change() {
+        let base = course().default_rules();
+        let rules = base.overlay(&parse(r#"{"max_trail_length": 0, "lives": 2}"#));
+        assert_eq!(rules.diff(&base), vec!["max_trail_length: 50 -> permanent", "lives: off -> 2"]);
+    }
+}
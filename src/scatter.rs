@@ -0,0 +1,196 @@
+use std::collections::BTreeSet;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::course::{GeneratedLayout, Symmetry};
+use crate::game::{Direction, spawn_positions};
+
+/// One wall segment per this many cells of grid
+const CELLS_PER_SEGMENT: usize = 200;
+/// Cells from each seat's start straight ahead that stay free of walls
+const CLEAR_AHEAD: usize = 4;
+/// How far the corner seats sit from the grid edge
+const SEAT_INSET: usize = 3;
+
+/// A way of moving a cell onto its copy elsewhere on the grid
+#[derive(Debug, Clone, Copy)]
+enum Transform {
+    Identity,
+    /// Left and right swapped
+    FlipX,
+    /// Top and bottom swapped
+    FlipY,
+    /// A quarter turn clockwise; square grids only
+    Turn90,
+    Turn180,
+    /// A quarter turn anticlockwise; square grids only
+    Turn270,
+}
+
+impl Transform {
+    fn apply(self, (x, y, dir): (usize, usize, Direction), width: usize, height: usize) -> (usize, usize, Direction) {
+        let (right, bottom) = (width - 1, height - 1);
+        match self {
+            Transform::Identity => (x, y, dir),
+            Transform::FlipX => (
+                right - x,
+                y,
+                match dir {
+                    Direction::Left | Direction::Right => dir.opposite(),
+                    _ => dir,
+                },
+            ),
+            Transform::FlipY => (
+                x,
+                bottom - y,
+                match dir {
+                    Direction::Up | Direction::Down => dir.opposite(),
+                    _ => dir,
+                },
+            ),
+            Transform::Turn90 => (right - y, x, dir.turn_right()),
+            Transform::Turn180 => (right - x, bottom - y, dir.opposite()),
+            Transform::Turn270 => (y, bottom - x, dir.turn_left()),
+        }
+    }
+}
+
+/// The transforms of `symmetry` on a grid of this size, identity first and
+/// in the order their copies of a corner seat take the usual seats:
+/// top-left, bottom-right, top-right, bottom-left
+fn transforms(symmetry: Symmetry, width: usize, height: usize) -> Vec<Transform> {
+    match symmetry {
+        Symmetry::None => vec![Transform::Identity],
+        Symmetry::Mirror => vec![Transform::Identity, Transform::Turn180, Transform::FlipX, Transform::FlipY],
+        Symmetry::Rotational if width == height => {
+            vec![Transform::Identity, Transform::Turn180, Transform::Turn90, Transform::Turn270]
+        }
+        Symmetry::Rotational => vec![Transform::Identity, Transform::Turn180],
+    }
+}
+
+/// Scatter short wall segments over a `width` x `height` grid whose border
+/// the game walls in. Under a symmetry the segments are drawn in one part
+/// of the grid and copied by each of its transforms, so every seat sees
+/// the same walls around it as the others. The seats are the copies of the
+/// top-left corner seat, plus those of the top-right one under half-turn
+/// symmetry and of a top-middle one under quarter turns; without symmetry
+/// they are the usual corners and midpoints. The first few cells ahead of
+/// each seat stay clear. The same arguments always give the same layout.
+pub fn generate(width: usize, height: usize, symmetry: Symmetry, seed: u64) -> GeneratedLayout {
+    let transforms = transforms(symmetry, width, height);
+    // The part of the grid the copies are made from
+    let (region_w, region_h) = match transforms.len() {
+        1 => (width, height),
+        2 => (width, height.div_ceil(2)),
+        _ => (width.div_ceil(2), height.div_ceil(2)),
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let segments = (width * height / CELLS_PER_SEGMENT).max(1).div_ceil(transforms.len());
+    let mut drawn = Vec::new();
+    // Segments start at least two cells in from the top-left edge
+    let segments = if region_w > 2 && region_h > 2 { segments } else { 0 };
+    for _ in 0..segments {
+        let (sx, sy) = (rng.gen_range(2..region_w), rng.gen_range(2..region_h));
+        let horizontal = rng.gen_bool(0.5);
+        let length = rng.gen_range(3..10);
+        for i in 0..length {
+            let (x, y) = if horizontal { (sx + i, sy) } else { (sx, sy + i) };
+            if x < region_w.min(width - 1) && y < region_h.min(height - 1) {
+                drawn.push((x, y));
+            }
+        }
+    }
+
+    let mut seeds = vec![(SEAT_INSET, SEAT_INSET, Direction::Right)];
+    match transforms.len() {
+        2 => seeds.push((width.saturating_sub(1 + SEAT_INSET), SEAT_INSET, Direction::Down)),
+        4 if symmetry == Symmetry::Rotational => seeds.push((width / 2, SEAT_INSET, Direction::Down)),
+        _ => {}
+    }
+    let spawns: Vec<(usize, usize, Direction)> = if symmetry == Symmetry::None {
+        spawn_positions(width, height)
+            .into_iter()
+            .map(|(x, y, dir)| (x as usize, y as usize, dir))
+            .collect()
+    } else {
+        seeds
+            .iter()
+            .flat_map(|&seat| transforms.iter().map(move |t| t.apply(seat, width, height)))
+            .collect()
+    };
+
+    let mut clear = BTreeSet::new();
+    for &(x, y, dir) in &spawns {
+        let (dx, dy) = dir.delta();
+        for i in 0..CLEAR_AHEAD as i32 {
+            clear.insert(((x as i32 + dx * i) as usize, (y as i32 + dy * i) as usize));
+        }
+    }
+    let walls: BTreeSet<(usize, usize)> = drawn
+        .iter()
+        .flat_map(|&(x, y)| transforms.iter().map(move |t| t.apply((x, y, Direction::Up), width, height)))
+        .map(|(x, y, _)| (x, y))
+        .filter(|cell| !clear.contains(cell))
+        .collect();
+    GeneratedLayout {
+        walls: walls.into_iter().collect(),
+        spawns,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const SIZES: [(usize, usize); 5] = [(8, 8), (21, 21), (40, 40), (40, 25), (33, 50)];
+
+    #[test]
+    fn layouts_are_invariant_under_their_symmetry() {
+        for symmetry in [Symmetry::Mirror, Symmetry::Rotational] {
+            for (width, height) in SIZES {
+                for seed in 0..20 {
+                    let layout = generate(width, height, symmetry, seed);
+                    let walls: HashSet<(usize, usize)> = layout.walls.iter().copied().collect();
+                    assert!(walls.iter().all(|&(x, y)| x < width && y < height));
+                    for transform in transforms(symmetry, width, height) {
+                        let moved = |&(x, y): &(usize, usize)| {
+                            let (x, y, _) = transform.apply((x, y, Direction::Up), width, height);
+                            (x, y)
+                        };
+                        let case = format!("{:?} {}x{} seed {}: {:?}", symmetry, width, height, seed, transform);
+                        assert_eq!(walls.iter().map(moved).collect::<HashSet<_>>(), walls, "{}", case);
+                        // Each seat's copy is a seat too
+                        for &seat in &layout.spawns {
+                            assert!(layout.spawns.contains(&transform.apply(seat, width, height)), "{}", case);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quarter_turns_on_square_grids_only() {
+        assert_eq!(transforms(Symmetry::Rotational, 40, 40).len(), 4);
+        assert_eq!(transforms(Symmetry::Rotational, 40, 25).len(), 2);
+        assert_eq!(transforms(Symmetry::Mirror, 40, 25).len(), 4);
+        // Four corners under a quarter turn, plus the middle of each side
+        assert_eq!(generate(40, 40, Symmetry::Rotational, 0).spawns.len(), 8);
+    }
+
+    #[test]
+    fn layouts_without_symmetry_use_the_usual_seats() {
+        let layout = generate(40, 30, Symmetry::None, 7);
+        let usual: Vec<(usize, usize, Direction)> = spawn_positions(40, 30)
+            .into_iter()
+            .map(|(x, y, dir)| (x as usize, y as usize, dir))
+            .collect();
+        assert_eq!(layout.spawns, usual);
+        assert_eq!(generate(40, 30, Symmetry::None, 7).walls, layout.walls);
+    }
+}
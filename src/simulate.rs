@@ -0,0 +1,232 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use crate::bot::Policy;
+use crate::course::{self, Course};
+use crate::game::{Game, GameEventKind, GameStatus};
+use crate::metrics;
+
+/// What `tronmcp simulate` plays
+pub struct Simulation {
+    /// Course level
+    pub level: u32,
+    /// One policy per seat
+    pub bots: Vec<Policy>,
+    pub games: u32,
+    /// Seeds the course layout and every bot's moves; random when `None`
+    pub seed: Option<u64>,
+    /// A game still running after this many ticks ends as a draw
+    pub max_ticks: u32,
+}
+
+/// How one entry of `--bots` fared over the run
+#[derive(Default)]
+struct BotStats {
+    wins: u32,
+    draws: u32,
+    /// Ticks survived, summed over every game
+    survived: u64,
+    crashes: BTreeMap<&'static str, u32>,
+}
+
+/// `tronmcp simulate`: play the bots against each other in-process, with
+/// no server, and print how each did
+pub fn run_simulate(sim: Simulation) -> Result<(), Box<dyn std::error::Error>> {
+    let courses = course::all_courses().len() as u32;
+    if !(1..=courses).contains(&sim.level) {
+        return Err(format!("--course must be a level from 1 to {}", courses).into());
+    }
+    let seed = sim.seed.unwrap_or_else(rand::random);
+    let course = course::get_course_with_seed(sim.level, seed);
+    if !(2..=course.max_players).contains(&sim.bots.len()) {
+        return Err(format!(
+            "{} (Level {}) seats 2 to {} bots; --bots lists {}",
+            course.name,
+            course.level,
+            course.max_players,
+            sim.bots.len()
+        )
+        .into());
+    }
+    let labels = labels(&sim.bots);
+    let mut stats: Vec<BotStats> = sim.bots.iter().map(|_| BotStats::default()).collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut ticks, mut shortest, mut longest, mut draws, mut cut_short) = (0u64, u32::MAX, 0, 0, 0);
+    let started = Instant::now();
+
+    for round in 0..sim.games as usize {
+        // Rotate the seats so no bot always gets the same spawn
+        let seats: Vec<usize> = (0..sim.bots.len()).map(|i| (round + i) % sim.bots.len()).collect();
+        let (game, stopped) = play(&course, &seats, &sim.bots, &labels, sim.max_ticks, &mut rng)?;
+        ticks += game.tick as u64;
+        shortest = shortest.min(game.tick);
+        longest = longest.max(game.tick);
+        if stopped {
+            cut_short += 1;
+        }
+        if game.winner.is_none() {
+            draws += 1;
+        }
+        for (idx, &bot) in seats.iter().enumerate() {
+            let entry = &mut stats[bot];
+            match game.winner {
+                Some(winner) if winner == idx => entry.wins += 1,
+                Some(_) => {}
+                None => entry.draws += 1,
+            }
+            entry.survived += game.survival_ticks(idx) as u64;
+        }
+        for event in &game.events {
+            if let GameEventKind::Crashed { player, cause } = event.kind {
+                *stats[seats[player]].crashes.entry(metrics::cause_label(cause)).or_default() += 1;
+            }
+        }
+    }
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let games = sim.games as f64;
+    println!(
+        "{} games on {} (Level {}), seed {}:",
+        sim.games, course.name, course.level, seed
+    );
+    println!(
+        "  Length: {:.1} ticks on average, {} to {}; {} draws, {} stopped at the {}-tick limit",
+        ticks as f64 / games,
+        shortest,
+        longest,
+        draws,
+        cut_short,
+        sim.max_ticks
+    );
+    for (label, entry) in labels.iter().zip(&stats) {
+        let crashes: Vec<String> = entry.crashes.iter().map(|(cause, n)| format!("{} {}", cause, n)).collect();
+        println!(
+            "  {}: {} wins ({:.1}%), {} draws, survived {:.1} ticks on average; crashes: {}",
+            label,
+            entry.wins,
+            100.0 * entry.wins as f64 / games,
+            entry.draws,
+            entry.survived as f64 / games,
+            if crashes.is_empty() { "none".to_string() } else { crashes.join(", ") }
+        );
+    }
+    println!(
+        "Played {} ticks in {:.2}s ({:.0} ticks/s)",
+        ticks,
+        elapsed,
+        ticks as f64 / elapsed.max(f64::EPSILON)
+    );
+    Ok(())
+}
+
+/// Each bot's name, numbered when a policy plays more than one seat
+fn labels(bots: &[Policy]) -> Vec<String> {
+    bots.iter()
+        .enumerate()
+        .map(|(i, bot)| {
+            if bots.iter().filter(|b| *b == bot).count() == 1 {
+                return bot.name().to_string();
+            }
+            let n = bots[..=i].iter().filter(|b| *b == bot).count();
+            format!("{}#{}", bot.name(), n)
+        })
+        .collect()
+}
+
+/// Play one game to the end, seat `i` taken by bot `seats[i]`, moving every
+/// cycle once per round as real-time games do. Also says whether the game
+/// was stopped at `max_ticks`.
+fn play(
+    course: &Course,
+    seats: &[usize],
+    bots: &[Policy],
+    labels: &[String],
+    max_ticks: u32,
+    rng: &mut StdRng,
+) -> Result<(Game, bool), String> {
+    let mut game = Game::new(course, course.default_rules())?;
+    // Coin-flip tie-breaks are seeded from the id
+    game.id = uuid::Uuid::from_u128(rng.r#gen());
+    game.realtime = true;
+    for &bot in seats {
+        game.add_player(labels[bot].clone())
+            .ok_or_else(|| format!("{} has no seat for {}", course.name, labels[bot]))?;
+    }
+    game.start();
+    while game.status == GameStatus::Running {
+        if game.tick >= max_ticks {
+            game.end_now();
+            return Ok((game, true));
+        }
+        for (idx, &bot) in seats.iter().enumerate() {
+            if game.players[idx].alive {
+                let action = bots[bot].choose(&game, idx, rng);
+                game.players[idx].pending_action = Some(action);
+            }
+        }
+        game.advance();
+    }
+    Ok((game, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::Difficulty;
+
+    fn simulation(level: u32, bots: Vec<Policy>) -> Simulation {
+        Simulation {
+            level,
+            bots,
+            games: 1,
+            seed: Some(7),
+            max_ticks: 100,
+        }
+    }
+
+    #[test]
+    fn policies_seated_twice_are_numbered() {
+        let bots = [Policy::Greedy, Policy::Random, Policy::Greedy, Policy::Practice(Difficulty::Easy)];
+        assert_eq!(labels(&bots), ["greedy#1", "random", "greedy#2", "easy"]);
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        let course = course::get_course_with_seed(1, 7);
+        let bots = [Policy::Greedy, Policy::Random];
+        let labels = labels(&bots);
+        let run = || play(&course, &[0, 1], &bots, &labels, 2000, &mut StdRng::seed_from_u64(7)).unwrap();
+        let ((first, first_stopped), (second, second_stopped)) = (run(), run());
+
+        assert_eq!(first.status, GameStatus::Finished);
+        assert_eq!((first.tick, first.winner, first_stopped), (second.tick, second.winner, second_stopped));
+        assert_eq!(first.events, second.events);
+        assert_eq!(first.replay.steps, second.replay.steps);
+    }
+
+    #[test]
+    fn games_stop_at_the_tick_limit() {
+        let course = course::get_course_with_seed(1, 7);
+        let bots = [Policy::Greedy, Policy::Greedy];
+        let labels = labels(&bots);
+        let (game, stopped) = play(&course, &[0, 1], &bots, &labels, 6, &mut StdRng::seed_from_u64(7)).unwrap();
+
+        assert!(stopped);
+        assert_eq!(game.status, GameStatus::Finished);
+        assert!((6..8).contains(&game.tick), "stopped at tick {}", game.tick);
+    }
+
+    #[test]
+    fn levels_and_seat_counts_are_checked() {
+        let courses = course::all_courses().len() as u32;
+        for level in [0, courses + 1] {
+            let err = run_simulate(simulation(level, vec![Policy::Random, Policy::Random])).unwrap_err();
+            assert_eq!(err.to_string(), format!("--course must be a level from 1 to {}", courses));
+        }
+        let err = run_simulate(simulation(1, vec![Policy::Random])).unwrap_err();
+        assert!(err.to_string().contains("seats 2 to"), "{}", err);
+        run_simulate(simulation(1, vec![Policy::Greedy, Policy::Random])).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bot::AdaptiveDifficulty;
+use crate::game::{Game, WebGameState};
+use crate::lobby::Lobby;
+use crate::manager::{LeaderboardEntry, PlayerSession};
+use crate::rating::RatingHistory;
+
+/// Bumped whenever the snapshot layout changes in a way older or newer
+/// binaries cannot read
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Everything one room needs to carry on in another process: served by
+/// `GET /api/admin/state-export` and adopted with `--import-state`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub schema_version: u32,
+    pub room: String,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub active_games: Vec<Game>,
+    pub waiting_players: Vec<String>,
+    pub player_sessions: HashMap<String, PlayerSession>,
+    pub lobbies: Vec<Lobby>,
+    pub leaderboard: HashMap<String, LeaderboardEntry>,
+    pub ratings: HashMap<String, RatingHistory>,
+    pub practice: HashMap<String, AdaptiveDifficulty>,
+    pub finished_games: Vec<WebGameState>,
+}
+
+impl StateSnapshot {
+    /// Read a snapshot file written from the export endpoint
+    pub fn load(path: &Path) -> Result<StateSnapshot, String> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let value: serde_json::Value = serde_json::from_str(&data)
+            .map_err(|e| format!("{} is not JSON: {}", path.display(), e))?;
+        // Check the version first so a layout change reports as one
+        let version = value.get("schema_version").and_then(|v| v.as_u64());
+        if version != Some(SCHEMA_VERSION as u64) {
+            return Err(format!(
+                "{} has schema version {}, this server reads version {}",
+                path.display(),
+                version.map_or("none".to_string(), |v| v.to_string()),
+                SCHEMA_VERSION
+            ));
+        }
+        serde_json::from_value(value).map_err(|e| format!("{} is not a valid snapshot: {}", path.display(), e))
+    }
+}
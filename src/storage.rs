@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, WebGameState};
+use crate::lobby::Lobby;
+use crate::manager::{leaderboard_checksum, LeaderboardEntry, PlayerSession};
+use crate::persist::{self, DebouncedFile};
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Which backend keeps the rooms' leaderboards, finished games and active state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageKind {
+    /// JSON files in the data directory
+    #[default]
+    Json,
+    /// One SQLite database shared by all rooms (needs the `sqlite` feature)
+    Sqlite,
+}
+
+impl StorageKind {
+    pub fn parse(s: &str) -> Result<StorageKind, String> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(StorageKind::Json),
+            "sqlite" => Ok(StorageKind::Sqlite),
+            _ => Err("expected 'json' or 'sqlite'".to_string()),
+        }
+    }
+}
+
+/// What is in play, saved so a restart carries on with it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActiveState {
+    pub games: Vec<Game>,
+    pub player_sessions: HashMap<String, PlayerSession>,
+    pub waiting_players: Vec<String>,
+    pub lobbies: Vec<Lobby>,
+}
+
+/// Which finished games `query_finished_games` returns
+#[derive(Debug, Clone, Default)]
+pub struct GameFilter {
+    /// Only games this player took part in
+    pub player: Option<String>,
+    /// Only games on this course level
+    pub level: Option<u32>,
+    pub limit: usize,
+}
+
+impl GameFilter {
+    fn matches(&self, game: &WebGameState) -> bool {
+        self.level.is_none_or(|level| game.course_level == level)
+            && self
+                .player
+                .as_ref()
+                .is_none_or(|name| game.players.iter().any(|p| &p.name == name))
+    }
+}
+
+/// Where one room keeps what outlives the process. Writes never fail the
+/// caller: a backend logs its errors and carries on, as the JSON files
+/// always have.
+pub trait Storage: Send + Sync {
+    fn load_leaderboard(&self) -> HashMap<String, LeaderboardEntry>;
+    /// Replace the stored leaderboard with every entry of `leaderboard`
+    fn save_leaderboard(&self, leaderboard: &HashMap<String, LeaderboardEntry>);
+    /// The newest `limit` finished games, oldest first
+    fn load_finished_games(&self, limit: usize) -> Vec<WebGameState>;
+    /// Archive a finished game. Backends that rewrite the whole archive keep
+    /// only the newest `keep`; a database keeps them all.
+    fn add_finished_game(&self, game: &WebGameState, keep: usize);
+    /// Make the archive exactly `games`, oldest first; empty to purge it
+    fn replace_finished_games(&self, games: &[WebGameState]);
+    /// Finished games matching `filter`, newest first
+    fn query_finished_games(&self, filter: &GameFilter) -> Vec<WebGameState>;
+    fn load_active_state(&self) -> Option<ActiveState>;
+    /// Save what is in play, or clear it with `None` when nothing is
+    fn save_active_state(&self, state: Option<&ActiveState>);
+    /// Write out anything still pending; for shutdown
+    fn flush(&self);
+}
+
+pub type SharedStorage = Arc<dyn Storage>;
+
+/// `leaderboard.json`: every entry, sorted by name, under the checksum they
+/// hash to
+#[derive(Debug, Serialize, Deserialize)]
+struct LeaderboardFile {
+    checksum: String,
+    entries: Vec<LeaderboardEntry>,
+}
+
+/// What `leaderboard.json` may hold; files written before checksums existed
+/// are a bare list of entries
+#[derive(Debug)]
+enum StoredLeaderboard {
+    Checked(LeaderboardFile),
+    Bare(Vec<LeaderboardEntry>),
+}
+
+/// Told apart by hand rather than with `#[serde(untagged)]`, whose buffering
+/// cannot read back the numeric keys of an entry's `levels`
+impl<'de> Deserialize<'de> for StoredLeaderboard {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let stored = if value.is_array() {
+            serde_json::from_value(value).map(StoredLeaderboard::Bare)
+        } else {
+            serde_json::from_value(value).map(StoredLeaderboard::Checked)
+        };
+        stored.map_err(serde::de::Error::custom)
+    }
+}
+
+/// The original backend: `leaderboard.json`, `finished_games.json` and
+/// `active_state.json` in the room's data directory
+pub struct JsonStorage {
+    data_dir: PathBuf,
+    leaderboard_file: DebouncedFile,
+    finished_games_file: DebouncedFile,
+    /// Contents of `finished_games.json`, for appending and queries
+    finished_games: Mutex<Vec<WebGameState>>,
+}
+
+impl JsonStorage {
+    pub fn new(data_dir: &Path) -> Self {
+        let _ = std::fs::create_dir_all(data_dir);
+        JsonStorage {
+            data_dir: data_dir.to_path_buf(),
+            leaderboard_file: DebouncedFile::new(leaderboard_path(data_dir)),
+            finished_games_file: DebouncedFile::new(finished_games_path(data_dir)),
+            finished_games: Mutex::new(read_finished_games(data_dir)),
+        }
+    }
+
+    fn save_finished_games(&self, games: &[WebGameState]) {
+        match serde_json::to_string_pretty(games) {
+            Ok(json) => self.finished_games_file.save(json),
+            Err(e) => tracing::error!("Failed to serialize finished games: {}", e),
+        }
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load_leaderboard(&self) -> HashMap<String, LeaderboardEntry> {
+        read_leaderboard(&self.data_dir)
+    }
+
+    /// Write every entry, not just the top ones served by `get_leaderboard`,
+    /// so the stats and the checksum survive a restart
+    fn save_leaderboard(&self, leaderboard: &HashMap<String, LeaderboardEntry>) {
+        let mut entries: Vec<LeaderboardEntry> = leaderboard.values().cloned().collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        let file = LeaderboardFile {
+            checksum: leaderboard_checksum(&entries),
+            entries,
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => self.leaderboard_file.save(json),
+            Err(e) => tracing::error!("Failed to serialize leaderboard: {}", e),
+        }
+    }
+
+    fn load_finished_games(&self, limit: usize) -> Vec<WebGameState> {
+        let games = self.finished_games.lock().unwrap();
+        games[games.len().saturating_sub(limit)..].to_vec()
+    }
+
+    fn add_finished_game(&self, game: &WebGameState, keep: usize) {
+        let mut games = self.finished_games.lock().unwrap();
+        games.push(game.clone());
+        let excess = games.len().saturating_sub(keep);
+        games.drain(..excess);
+        self.save_finished_games(&games);
+    }
+
+    fn replace_finished_games(&self, games: &[WebGameState]) {
+        *self.finished_games.lock().unwrap() = games.to_vec();
+        self.save_finished_games(games);
+    }
+
+    fn query_finished_games(&self, filter: &GameFilter) -> Vec<WebGameState> {
+        let games = self.finished_games.lock().unwrap();
+        games
+            .iter()
+            .rev()
+            .filter(|g| filter.matches(g))
+            .take(filter.limit)
+            .cloned()
+            .collect()
+    }
+
+    fn load_active_state(&self) -> Option<ActiveState> {
+        read_active_state(&self.data_dir)
+    }
+
+    /// Written to a temporary file first so a crash mid-write leaves the last
+    /// good copy
+    fn save_active_state(&self, state: Option<&ActiveState>) {
+        let path = active_state_path(&self.data_dir);
+        let Some(state) = state else {
+            let _ = std::fs::remove_file(&path);
+            return;
+        };
+        match serde_json::to_string(state) {
+            Ok(json) => {
+                let tmp = path.with_extension("json.tmp");
+                if let Err(e) = std::fs::write(&tmp, json).and_then(|_| std::fs::rename(&tmp, &path)) {
+                    tracing::error!("Failed to save active state: {}", e);
+                }
+            }
+            Err(e) => tracing::error!("Failed to serialize active state: {}", e),
+        }
+    }
+
+    fn flush(&self) {
+        self.leaderboard_file.flush();
+        self.finished_games_file.flush();
+    }
+}
+
+fn leaderboard_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("leaderboard.json")
+}
+
+fn finished_games_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("finished_games.json")
+}
+
+fn active_state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("active_state.json")
+}
+
+fn read_leaderboard(data_dir: &Path) -> HashMap<String, LeaderboardEntry> {
+    let path = leaderboard_path(data_dir);
+    match persist::read_json::<StoredLeaderboard>(&path, "leaderboard") {
+        Some(stored) => {
+            let entries = match stored {
+                StoredLeaderboard::Checked(file) => {
+                    if leaderboard_checksum(&file.entries) != file.checksum {
+                        tracing::warn!(
+                            "Leaderboard checksum in {} does not match its entries; was it edited by hand?",
+                            path.display()
+                        );
+                    }
+                    file.entries
+                }
+                StoredLeaderboard::Bare(entries) => entries,
+            };
+            tracing::info!("Loaded {} leaderboard entries from {}", entries.len(), path.display());
+            entries.into_iter().map(|e| (e.name.clone(), e)).collect()
+        }
+        None => {
+            tracing::info!("No usable leaderboard at {}, starting fresh", path.display());
+            HashMap::new()
+        }
+    }
+}
+
+/// The finished games archived in `finished_games.json` under `data_dir`, oldest first
+pub fn read_finished_games(data_dir: &Path) -> Vec<WebGameState> {
+    let path = finished_games_path(data_dir);
+    match persist::read_json::<Vec<WebGameState>>(&path, "finished games") {
+        Some(entries) => {
+            tracing::info!("Loaded {} finished games from {}", entries.len(), path.display());
+            entries
+        }
+        None => {
+            tracing::info!("No usable finished games at {}, starting fresh", path.display());
+            Vec::new()
+        }
+    }
+}
+
+fn read_active_state(data_dir: &Path) -> Option<ActiveState> {
+    let json = std::fs::read_to_string(active_state_path(data_dir)).ok()?;
+    match serde_json::from_str(&json) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            tracing::warn!("Failed to parse active state: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{self, ScratchDir};
+
+    const ARENA: &str = "
+        ##########
+        #........#
+        #........#
+        #........#
+        #........#
+        #........#
+        #........#
+        ##########";
+
+    /// A finished game between `names` on course level `level`
+    fn finished(names: &[&str], level: u32) -> WebGameState {
+        let mut game = testutil::game(ARENA, names).to_web_state();
+        game.course_level = level;
+        game
+    }
+
+    fn entry(name: &str, wins: u32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name: name.to_string(),
+            wins,
+            games_played: wins + 1,
+            ..LeaderboardEntry::default()
+        }
+    }
+
+    fn board(entries: &[LeaderboardEntry]) -> HashMap<String, LeaderboardEntry> {
+        entries.iter().map(|e| (e.name.clone(), e.clone())).collect()
+    }
+
+    fn json(value: &impl Serialize) -> serde_json::Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    fn ids(games: &[WebGameState]) -> Vec<&str> {
+        games.iter().map(|g| g.id.as_str()).collect()
+    }
+
+    /// Write through one handle and read back through a fresh one from
+    /// `open`, checking what every backend must agree on
+    fn exercise(open: &dyn Fn() -> Box<dyn Storage>) {
+        let games = [
+            finished(&["Claude", "Gemini"], 1),
+            finished(&["Grok", "Llama"], 2),
+            finished(&["Claude", "Grok"], 2),
+            finished(&["Gemini", "Llama"], 1),
+            finished(&["Claude", "Llama"], 3),
+        ];
+        let id = |i: usize| games[i].id.as_str();
+
+        let storage = open();
+        assert!(storage.load_leaderboard().is_empty());
+        assert!(storage.load_finished_games(10).is_empty());
+        assert!(storage.load_active_state().is_none());
+
+        storage.save_leaderboard(&board(&[entry("Claude", 3), entry("Gemini", 1), entry("Grok", 0)]));
+        // Saving replaces the whole board, so Grok is gone
+        let leaderboard = board(&[entry("Claude", 4), entry("Gemini", 1)]);
+        storage.save_leaderboard(&leaderboard);
+        for game in &games {
+            storage.add_finished_game(game, 100);
+        }
+        let state = ActiveState {
+            games: vec![testutil::game(ARENA, &["Mistral", "Qwen"])],
+            player_sessions: HashMap::new(),
+            waiting_players: vec!["Llama".to_string()],
+            lobbies: Vec::new(),
+        };
+        storage.save_active_state(Some(&state));
+        storage.flush();
+        drop(storage);
+
+        let storage = open();
+        assert_eq!(json(&storage.load_leaderboard()), json(&leaderboard));
+        let all = storage.load_finished_games(10);
+        assert_eq!(json(&all), json(&games));
+        assert_eq!(ids(&storage.load_finished_games(3)), [id(2), id(3), id(4)]);
+        let query = |player: Option<&str>, level: Option<u32>, limit: usize| {
+            let filter = GameFilter {
+                player: player.map(str::to_string),
+                level,
+                limit,
+            };
+            storage.query_finished_games(&filter).iter().map(|g| g.id.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(query(None, None, 2), [id(4), id(3)]);
+        assert_eq!(query(Some("Claude"), None, 10), [id(4), id(2), id(0)]);
+        assert_eq!(query(None, Some(2), 10), [id(2), id(1)]);
+        assert_eq!(query(Some("Claude"), Some(2), 10), [id(2)]);
+        assert_eq!(query(Some("Llama"), Some(1), 10), [id(3)]);
+        assert!(query(Some("Mistral"), None, 10).is_empty());
+        assert_eq!(json(&storage.load_active_state()), json(&Some(&state)));
+
+        storage.replace_finished_games(&[games[1].clone(), games[3].clone()]);
+        storage.save_active_state(None);
+        storage.flush();
+        drop(storage);
+
+        let storage = open();
+        assert_eq!(ids(&storage.load_finished_games(10)), [id(1), id(3)]);
+        assert_eq!(query_all(&*storage, "Grok"), [id(1)]);
+        assert!(storage.load_active_state().is_none());
+
+        storage.replace_finished_games(&[]);
+        assert!(storage.load_finished_games(10).is_empty());
+    }
+
+    fn query_all(storage: &dyn Storage, player: &str) -> Vec<String> {
+        let filter = GameFilter {
+            player: Some(player.to_string()),
+            limit: 10,
+            ..GameFilter::default()
+        };
+        storage.query_finished_games(&filter).into_iter().map(|g| g.id).collect()
+    }
+
+    #[test]
+    fn json_files_keep_everything_a_room_stores() {
+        let dir = ScratchDir::new();
+        exercise(&|| Box::new(JsonStorage::new(dir.path())));
+    }
+
+    #[test]
+    fn json_archive_keeps_only_the_newest_games() {
+        let dir = ScratchDir::new();
+        let storage = JsonStorage::new(dir.path());
+        let games: Vec<_> = (0..4).map(|level| finished(&["Claude", "Gemini"], level)).collect();
+        for game in &games {
+            storage.add_finished_game(game, 2);
+        }
+        assert_eq!(ids(&storage.load_finished_games(10)), ids(&games[2..]));
+        assert_eq!(ids(&JsonStorage::new(dir.path()).load_finished_games(10)), ids(&games[2..]));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_keeps_everything_json_does() {
+        let dir = ScratchDir::new();
+        let db_path = dir.path().join("tron.db");
+        exercise(&|| Box::new(sqlite::SqliteStorage::new(sqlite::open(&db_path).unwrap(), "default", dir.path()).unwrap()));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_keeps_rooms_apart() {
+        let dir = ScratchDir::new();
+        let db = sqlite::open(&dir.path().join("tron.db")).unwrap();
+        let main = sqlite::SqliteStorage::new(db.clone(), "main", &dir.path().join("main")).unwrap();
+        let side = sqlite::SqliteStorage::new(db, "side", &dir.path().join("side")).unwrap();
+        let game = finished(&["Claude", "Gemini"], 1);
+        main.add_finished_game(&game, 100);
+        main.save_leaderboard(&board(&[entry("Claude", 1)]));
+        assert_eq!(ids(&main.load_finished_games(10)), [game.id.as_str()]);
+        assert!(side.load_finished_games(10).is_empty());
+        assert!(query_all(&side, "Claude").is_empty());
+        assert!(side.load_leaderboard().is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_imports_the_json_files_once() {
+        let dir = ScratchDir::new();
+        let json_storage = JsonStorage::new(dir.path());
+        let leaderboard = board(&[entry("Claude", 2), entry("Gemini", 0)]);
+        let games = [finished(&["Claude", "Gemini"], 1), finished(&["Claude", "Grok"], 2)];
+        json_storage.save_leaderboard(&leaderboard);
+        for game in &games {
+            json_storage.add_finished_game(game, 100);
+        }
+        let state = ActiveState {
+            games: vec![testutil::game(ARENA, &["Mistral", "Qwen"])],
+            player_sessions: HashMap::new(),
+            waiting_players: Vec::new(),
+            lobbies: Vec::new(),
+        };
+        json_storage.save_active_state(Some(&state));
+
+        let db_path = dir.path().join("tron.db");
+        let open = || sqlite::SqliteStorage::new(sqlite::open(&db_path).unwrap(), "default", dir.path()).unwrap();
+        let storage = open();
+        assert_eq!(json(&storage.load_leaderboard()), json(&leaderboard));
+        assert_eq!(json(&storage.load_finished_games(10)), json(&games));
+        assert_eq!(query_all(&storage, "Grok"), [games[1].id.clone()]);
+        assert_eq!(json(&storage.load_active_state()), json(&Some(&state)));
+        // The database owns the game in play from now on; the archive files stay
+        assert!(!active_state_path(dir.path()).exists());
+        assert!(leaderboard_path(dir.path()).exists());
+        assert!(finished_games_path(dir.path()).exists());
+        drop(storage);
+
+        // Later changes to the files are not imported again
+        json_storage.add_finished_game(&finished(&["Grok", "Llama"], 1), 100);
+        json_storage.save_leaderboard(&HashMap::new());
+        let storage = open();
+        assert_eq!(json(&storage.load_finished_games(10)), json(&games));
+        assert_eq!(json(&storage.load_leaderboard()), json(&leaderboard));
+    }
+}
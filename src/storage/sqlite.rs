@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{read_active_state, read_finished_games, read_leaderboard, ActiveState, GameFilter, Storage};
+use crate::game::WebGameState;
+use crate::manager::LeaderboardEntry;
+
+/// One connection for the whole process; rooms share it and tell their rows
+/// apart by the `room` column
+pub type SharedConnection = Arc<Mutex<Connection>>;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS leaderboard (
+        room TEXT NOT NULL,
+        name TEXT NOT NULL,
+        entry TEXT NOT NULL,
+        PRIMARY KEY (room, name)
+    );
+    CREATE TABLE IF NOT EXISTS finished_games (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        room TEXT NOT NULL,
+        id TEXT NOT NULL,
+        course_level INTEGER NOT NULL,
+        game TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS finished_games_room_level ON finished_games (room, course_level);
+    CREATE TABLE IF NOT EXISTS game_players (
+        seq INTEGER NOT NULL,
+        player TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS game_players_player ON game_players (player, seq);
+    CREATE TABLE IF NOT EXISTS active_state (
+        room TEXT PRIMARY KEY,
+        state TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS json_imports (
+        room TEXT PRIMARY KEY,
+        imported_at TEXT NOT NULL
+    );
+";
+
+/// Open (or create) the database at `path` and make sure its tables exist
+pub fn open(path: &Path) -> Result<SharedConnection, String> {
+    let conn = Connection::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    conn.pragma_update(None, "journal_mode", "WAL")
+        .and_then(|_| conn.pragma_update(None, "synchronous", "NORMAL"))
+        .and_then(|_| conn.execute_batch(SCHEMA))
+        .map_err(|e| format!("Cannot set up {}: {}", path.display(), e))?;
+    Ok(Arc::new(Mutex::new(conn)))
+}
+
+/// One room's rows in the shared database. Every finished game is kept, with
+/// its players in `game_players` so queries by player stay cheap.
+pub struct SqliteStorage {
+    db: SharedConnection,
+    room: String,
+}
+
+impl SqliteStorage {
+    /// The room's storage. The first time a room is seen, the JSON files in
+    /// its data directory are imported into the database.
+    pub fn new(db: SharedConnection, room: &str, data_dir: &Path) -> Result<Self, String> {
+        let storage = SqliteStorage {
+            db,
+            room: room.to_string(),
+        };
+        storage
+            .import_json(data_dir)
+            .map_err(|e| format!("Cannot import room {} into the database: {}", room, e))?;
+        Ok(storage)
+    }
+
+    fn import_json(&self, data_dir: &Path) -> rusqlite::Result<()> {
+        let mut conn = self.db.lock().unwrap();
+        let imported = conn
+            .query_row("SELECT 1 FROM json_imports WHERE room = ?1", [&self.room], |_| Ok(()))
+            .optional()?;
+        if imported.is_some() {
+            return Ok(());
+        }
+        let leaderboard = read_leaderboard(data_dir);
+        let games = read_finished_games(data_dir);
+        let active = read_active_state(data_dir);
+
+        let tx = conn.transaction()?;
+        write_leaderboard(&tx, &self.room, &leaderboard)?;
+        for game in &games {
+            insert_game(&tx, &self.room, game)?;
+        }
+        if let Some(state) = &active {
+            write_active_state(&tx, &self.room, Some(state))?;
+        }
+        tx.execute(
+            "INSERT INTO json_imports (room, imported_at) VALUES (?1, ?2)",
+            params![self.room, chrono::Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+
+        // The database owns what is in play now; a stale file would bring
+        // finished games back to life if the server went back to JSON
+        if active.is_some() {
+            let _ = std::fs::remove_file(data_dir.join("active_state.json"));
+        }
+        tracing::info!(
+            "Imported {} leaderboard entries and {} finished games of room {} from {} into the database",
+            leaderboard.len(),
+            games.len(),
+            self.room,
+            data_dir.display()
+        );
+        Ok(())
+    }
+
+    /// Run `f` on the connection, logging a failure as `what`
+    fn with_db<T>(&self, what: &str, f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> Option<T> {
+        let mut conn = self.db.lock().unwrap();
+        match f(&mut conn) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::error!("Failed to {} for room {}: {}", what, self.room, e);
+                None
+            }
+        }
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_leaderboard(&self) -> HashMap<String, LeaderboardEntry> {
+        let entries: Vec<LeaderboardEntry> = self
+            .with_db("load the leaderboard", |conn| {
+                let mut stmt = conn.prepare("SELECT entry FROM leaderboard WHERE room = ?1")?;
+                let rows = stmt.query_map([&self.room], |row| row.get::<_, String>(0))?;
+                rows.collect::<rusqlite::Result<Vec<_>>>().map(|rows| parse_rows(&rows, "leaderboard entry"))
+            })
+            .unwrap_or_default();
+        tracing::info!("Loaded {} leaderboard entries of room {} from the database", entries.len(), self.room);
+        entries.into_iter().map(|e| (e.name.clone(), e)).collect()
+    }
+
+    fn save_leaderboard(&self, leaderboard: &HashMap<String, LeaderboardEntry>) {
+        self.with_db("save the leaderboard", |conn| {
+            let tx = conn.transaction()?;
+            write_leaderboard(&tx, &self.room, leaderboard)?;
+            tx.commit()
+        });
+    }
+
+    fn load_finished_games(&self, limit: usize) -> Vec<WebGameState> {
+        let mut games = self.query_finished_games(&GameFilter {
+            limit,
+            ..Default::default()
+        });
+        games.reverse();
+        games
+    }
+
+    fn add_finished_game(&self, game: &WebGameState, _keep: usize) {
+        self.with_db("archive a finished game", |conn| {
+            let tx = conn.transaction()?;
+            insert_game(&tx, &self.room, game)?;
+            tx.commit()
+        });
+    }
+
+    fn replace_finished_games(&self, games: &[WebGameState]) {
+        self.with_db("replace the finished games", |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM game_players WHERE seq IN (SELECT seq FROM finished_games WHERE room = ?1)",
+                [&self.room],
+            )?;
+            tx.execute("DELETE FROM finished_games WHERE room = ?1", [&self.room])?;
+            for game in games {
+                insert_game(&tx, &self.room, game)?;
+            }
+            tx.commit()
+        });
+    }
+
+    fn query_finished_games(&self, filter: &GameFilter) -> Vec<WebGameState> {
+        self.with_db("query finished games", |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT game FROM finished_games f
+                 WHERE room = ?1
+                   AND (?2 IS NULL OR course_level = ?2)
+                   AND (?3 IS NULL OR EXISTS (SELECT 1 FROM game_players p WHERE p.seq = f.seq AND p.player = ?3))
+                 ORDER BY seq DESC
+                 LIMIT ?4",
+            )?;
+            let rows = stmt.query_map(
+                params![self.room, filter.level, filter.player, filter.limit as i64],
+                |row| row.get::<_, String>(0),
+            )?;
+            rows.collect::<rusqlite::Result<Vec<_>>>().map(|rows| parse_rows(&rows, "finished game"))
+        })
+        .unwrap_or_default()
+    }
+
+    fn load_active_state(&self) -> Option<ActiveState> {
+        let json = self
+            .with_db("load the active state", |conn| {
+                conn.query_row("SELECT state FROM active_state WHERE room = ?1", [&self.room], |row| {
+                    row.get::<_, String>(0)
+                })
+                .optional()
+            })
+            .flatten()?;
+        match serde_json::from_str(&json) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                tracing::warn!("Failed to parse active state of room {}: {}", self.room, e);
+                None
+            }
+        }
+    }
+
+    fn save_active_state(&self, state: Option<&ActiveState>) {
+        self.with_db("save the active state", |conn| write_active_state(conn, &self.room, state));
+    }
+
+    /// Every write is committed when it is made
+    fn flush(&self) {}
+}
+
+fn write_leaderboard(
+    conn: &Connection,
+    room: &str,
+    leaderboard: &HashMap<String, LeaderboardEntry>,
+) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM leaderboard WHERE room = ?1", [room])?;
+    let mut stmt = conn.prepare("INSERT INTO leaderboard (room, name, entry) VALUES (?1, ?2, ?3)")?;
+    for entry in leaderboard.values() {
+        stmt.execute(params![room, entry.name, to_json(entry)?])?;
+    }
+    Ok(())
+}
+
+fn insert_game(conn: &Connection, room: &str, game: &WebGameState) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO finished_games (room, id, course_level, game) VALUES (?1, ?2, ?3, ?4)",
+        params![room, game.id, game.course_level, to_json(game)?],
+    )?;
+    let seq = conn.last_insert_rowid();
+    let mut stmt = conn.prepare("INSERT INTO game_players (seq, player) VALUES (?1, ?2)")?;
+    for player in &game.players {
+        stmt.execute(params![seq, player.name])?;
+    }
+    Ok(())
+}
+
+fn write_active_state(conn: &Connection, room: &str, state: Option<&ActiveState>) -> rusqlite::Result<()> {
+    match state {
+        Some(state) => conn.execute(
+            "INSERT INTO active_state (room, state) VALUES (?1, ?2)
+             ON CONFLICT (room) DO UPDATE SET state = excluded.state",
+            params![room, to_json(state)?],
+        ),
+        None => conn.execute("DELETE FROM active_state WHERE room = ?1", [room]),
+    }
+    .map(|_| ())
+}
+
+fn to_json(value: &impl serde::Serialize) -> rusqlite::Result<String> {
+    serde_json::to_string(value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Parse stored JSON rows, skipping (and logging) any that no longer parse
+fn parse_rows<T: serde::de::DeserializeOwned>(rows: &[String], what: &str) -> Vec<T> {
+    rows.iter()
+        .filter_map(|json| match serde_json::from_str(json) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("Skipping a stored {} that does not parse: {}", what, e);
+                None
+            }
+        })
+        .collect()
+}
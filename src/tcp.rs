@@ -0,0 +1,1918 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+use crate::course::{self, TagPreferences};
+use crate::error::{ErrorCode, TronError};
+use crate::game::{LookFormat, LookOptions, Orientation};
+use crate::manager::{self, GameManager, MovePlan, SharedGameManager};
+use crate::mcp::{self, CreateLobbyParams};
+use crate::rooms::{Rooms, SharedRooms};
+use crate::validation::{self, Validate};
+use crate::broadcast::BroadcastEvent;
+use crate::{metrics, protocol, push, ratelimit};
+
+/// Where the command server takes player connections: the TCP port, or a
+/// Unix domain socket (`--tcp-socket`) when the agents run on the same box
+pub enum CommandListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, std::path::PathBuf),
+}
+
+impl CommandListener {
+    /// Bind a Unix domain socket at `path` with permissions `mode`. A socket
+    /// file left behind by an earlier run is replaced.
+    #[cfg(unix)]
+    pub fn bind_unix(path: &std::path::Path, mode: u32) -> std::io::Result<CommandListener> {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+        if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+            std::fs::remove_file(path)?;
+        }
+        let listener = tokio::net::UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+        Ok(CommandListener::Unix(listener, path.to_path_buf()))
+    }
+
+    /// Permissions for `--tcp-socket-mode`, in octal like `chmod`: `600`,
+    /// `0660` or `0o660`
+    pub fn parse_mode(s: &str) -> Result<u32, String> {
+        let s = s.trim();
+        let digits = s.strip_prefix("0o").unwrap_or(s);
+        match u32::from_str_radix(digits, 8) {
+            Ok(mode) if !digits.is_empty() && mode <= 0o777 => Ok(mode),
+            _ => Err("expected octal permissions such as 600 or 660".to_string()),
+        }
+    }
+
+    /// Where players connect, for the logs
+    pub fn describe(&self) -> String {
+        match self {
+            CommandListener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => format!("0.0.0.0:{}", addr.port()),
+                Err(_) => "TCP".to_string(),
+            },
+            #[cfg(unix)]
+            CommandListener::Unix(_, path) => format!("unix:{}", path.display()),
+        }
+    }
+}
+
+/// TCP command server — handles commands from MCP player instances
+pub async fn run_tcp_server(
+    listener: CommandListener,
+    rooms: SharedRooms,
+    settings: TcpSettings,
+    ct: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing::info!("TCP command server listening on {}", listener.describe());
+
+    loop {
+        let accepted = tokio::select! {
+            accepted = accept(&listener) => accepted,
+            _ = ct.cancelled() => break,
+        };
+        let (stream, peer) = match accepted {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                cleanup(&listener);
+                return Err(e.into());
+            }
+        };
+        tracing::info!("MCP player connected from {}", peer);
+        tokio::spawn(handle_connection(stream, peer, rooms.clone(), settings));
+    }
+    cleanup(&listener);
+    Ok(())
+}
+
+/// A connection carrying TCP commands, over TCP or a Unix domain socket;
+/// the server's side of a player's connection, or `tronmcp play`'s
+pub trait CommandStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> CommandStream for S {}
+
+async fn accept(listener: &CommandListener) -> std::io::Result<(Box<dyn CommandStream>, String)> {
+    match listener {
+        CommandListener::Tcp(listener) => {
+            let (stream, addr) = listener.accept().await?;
+            Ok((Box::new(stream), addr.to_string()))
+        }
+        #[cfg(unix)]
+        CommandListener::Unix(listener, path) => {
+            let (stream, _) = listener.accept().await?;
+            Ok((Box::new(stream), format!("unix:{}", path.display())))
+        }
+    }
+}
+
+/// Remove the socket file, so nobody mistakes it for a running server
+fn cleanup(listener: &CommandListener) {
+    #[cfg(unix)]
+    if let CommandListener::Unix(_, path) = listener {
+        let _ = std::fs::remove_file(path);
+    }
+    #[cfg(not(unix))]
+    let _ = listener;
+}
+
+/// Serve one player's commands until they disconnect
+pub(crate) async fn handle_connection(
+    stream: Box<dyn CommandStream>,
+    peer: String,
+    rooms: SharedRooms,
+    settings: TcpSettings,
+) {
+    metrics::METRICS.tcp_connections.inc();
+    let (reader, mut writer) = tokio::io::split(stream);
+    // Lines are read on their own task so waiting for one never races with
+    // pushing events, which could drop half a line
+    let (lines_tx, mut lines) = tokio::sync::mpsc::channel(1);
+    let reading = tokio::spawn(async move {
+        let mut buf_reader = BufReader::new(reader);
+        loop {
+            let line = read_command(&mut buf_reader).await;
+            let last = matches!(line, Ok(Line::Closed) | Err(_));
+            if lines_tx.send(line).await.is_err() || last {
+                return;
+            }
+        }
+    });
+    let mut events: Option<Events> = None;
+    let mut conn = ConnectionState {
+        room: rooms.default_name().to_string(),
+        framed: false,
+        player: None,
+        identity: settings.identity,
+        tags: TagPreferences::default(),
+        ping: None,
+        rtt_ms: None,
+        closing: false,
+        subscription: None,
+        spectator: manager::SpectatorSession::default(),
+    };
+    let mut bucket = ratelimit::TokenBucket::new(settings.rate_limit);
+
+    loop {
+        let line = tokio::select! {
+            line = lines.recv() => line.unwrap_or(Ok(Line::Closed)),
+            pushed = next_events(&mut events) => {
+                // Pushed between responses, never inside one
+                let payload: String = pushed.iter().map(|e| format!("{}\n", e.to_line())).collect();
+                if let Err(e) = writer.write_all(payload.as_bytes()).await {
+                    tracing::error!("Write error: {}", e);
+                    break;
+                }
+                let _ = writer.flush().await;
+                continue;
+            }
+        };
+        // A line starting with `{` is a JSON command and gets a JSON
+        // response; anything else is a text command
+        let json = matches!(&line, Ok(Line::Command(l)) if l.trim_start().starts_with('{'));
+        let response = match line {
+            Ok(Line::Closed) => break,
+            // Over the limit the command is refused before it gets near a room's lock
+            Ok(Line::Command(line)) => match bucket.take() {
+                Ok(()) if json => match protocol::Request::from_json(&line).and_then(|r| r.to_text()) {
+                    Ok(command) => handle_command(&command, &rooms, &mut conn).await,
+                    Err(e) => error_reply(e),
+                },
+                Ok(()) => handle_command(line.trim(), &rooms, &mut conn).await,
+                Err(wait) => {
+                    metrics::METRICS.tcp_rate_limited.inc();
+                    error_reply(TronError::new(
+                        ErrorCode::RateLimited,
+                        format!("Rate limited, retry in {}ms.", wait.as_millis().max(1)),
+                    ))
+                }
+            },
+            Ok(Line::Rejected(e)) => error_reply(e),
+            Err(e) => {
+                tracing::error!("Read error: {}", e);
+                break;
+            }
+        };
+        // JSON responses are always one line. Otherwise protocol 2
+        // sends the text as-is plus a terminator line, and legacy
+        // clients get one line with escaped newlines.
+        let payload = if json {
+            format!("{}\n", protocol::Response::from_reply(&response).to_line())
+        } else if conn.framed {
+            format!("{}\n{}\n", response, mcp::FRAME_END)
+        } else {
+            format!("{}\n", response.replace('\n', "\\n"))
+        };
+        if let Err(e) = writer.write_all(payload.as_bytes()).await {
+            tracing::error!("Write error: {}", e);
+            break;
+        }
+        let _ = writer.flush().await;
+        if conn.closing {
+            break;
+        }
+        follow_subscription(&mut events, &conn, &rooms).await;
+    }
+    reading.abort();
+
+    metrics::METRICS.tcp_connections.dec();
+    tracing::info!("MCP player disconnected from {}", peer);
+    if let Some(name) = &conn.player
+        && let Some(manager) = rooms.get(&conn.room)
+    {
+        manager.lock().await.handle_disconnect(name);
+    }
+}
+
+/// A subscribed connection's player and its room's broadcasts
+type Events = (push::Subscription, tokio::sync::broadcast::Receiver<BroadcastEvent>);
+
+/// Start, move or stop listening to a room's broadcasts after `SUBSCRIBE`,
+/// `UNSUBSCRIBE` or `ROOM` changed what the connection follows
+async fn follow_subscription(events: &mut Option<Events>, conn: &ConnectionState, rooms: &Rooms) {
+    let wanted = conn.subscription.as_ref().map(|player| (player, &conn.room));
+    if wanted == events.as_ref().map(|(sub, _)| (&sub.player, &sub.room)) {
+        return;
+    }
+    *events = match (&conn.subscription, rooms.get(&conn.room)) {
+        (Some(player), Some(manager)) => Some((
+            push::Subscription::new(player.clone(), conn.room.clone()),
+            manager.lock().await.subscribe(),
+        )),
+        _ => None,
+    };
+}
+
+/// The subscribed player's next events; waits forever without a subscription
+async fn next_events(events: &mut Option<Events>) -> Vec<push::PlayerEvent> {
+    let Some((sub, rx)) = events else {
+        return std::future::pending().await;
+    };
+    match rx.recv().await {
+        Ok(event) => sub.translate(&event),
+        // Missed updates only cost the moves in between
+        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => Vec::new(),
+        Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+            *events = None;
+            Vec::new()
+        }
+    }
+}
+
+/// Longest command line accepted on the TCP port, in bytes
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+/// One line read from a TCP connection
+enum Line {
+    Command(String),
+    /// Too long or not UTF-8; answered with an error, the connection stays open
+    Rejected(TronError),
+    Closed,
+}
+
+/// Read the next command line. A bad line is consumed up to its newline and
+/// reported, so garbage from a client never takes the connection down.
+async fn read_command<R: AsyncBufRead + Unpin>(reader: &mut R) -> std::io::Result<Line> {
+    let mut bytes = Vec::new();
+    let n = (&mut *reader)
+        .take(MAX_LINE_LEN as u64 + 1)
+        .read_until(b'\n', &mut bytes)
+        .await?;
+    if n == 0 {
+        return Ok(Line::Closed);
+    }
+    if bytes.len() > MAX_LINE_LEN && !bytes.ends_with(b"\n") {
+        // Skip the rest of the line without buffering it
+        loop {
+            bytes.clear();
+            let n = (&mut *reader)
+                .take(MAX_LINE_LEN as u64)
+                .read_until(b'\n', &mut bytes)
+                .await?;
+            if n == 0 || bytes.ends_with(b"\n") {
+                break;
+            }
+        }
+        return Ok(Line::Rejected(TronError::new(
+            ErrorCode::InvalidParams,
+            format!("Command lines are limited to {} bytes.", MAX_LINE_LEN),
+        )));
+    }
+    match String::from_utf8(bytes) {
+        Ok(line) => Ok(Line::Command(line)),
+        Err(_) => Ok(Line::Rejected(TronError::new(
+            ErrorCode::InvalidParams,
+            "Commands must be valid UTF-8.",
+        ))),
+    }
+}
+
+/// How the TCP command server treats its connections
+#[derive(Debug, Clone, Copy)]
+pub struct TcpSettings {
+    pub identity: TcpIdentity,
+    /// Commands each connection may send (`--tcp-rate-limit`)
+    pub rate_limit: ratelimit::RateLimit,
+}
+
+/// How TCP player commands say which player they act for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpIdentity {
+    /// Player commands pass the session token from the join reply
+    Tokens,
+    /// They name the player, who must be the one the connection joined as
+    /// (`--tcp-name-commands`)
+    Names,
+    /// Any command may name any player (`--tcp-legacy-names`)
+    Unchecked,
+}
+
+/// Per-connection protocol state
+struct ConnectionState {
+    /// Room that commands on this connection are routed to
+    room: String,
+    /// Responses are multi-line and end with `FRAME_END` (protocol 2)
+    framed: bool,
+    /// Player this connection joined as; only it may be looked at or steered
+    player: Option<String>,
+    /// How player commands name their player
+    identity: TcpIdentity,
+    /// Course tag preferences applied to JOIN (`TAGS`)
+    tags: TagPreferences,
+    /// Nonce and send time of an unanswered `PING`
+    ping: Option<(u32, std::time::Instant)>,
+    /// Round trip measured by the last `PING`/`PONG` exchange
+    rtt_ms: Option<u64>,
+    /// The client said `BYE`; close after the reply
+    closing: bool,
+    /// Player whose events are pushed to this connection (`SUBSCRIBE`)
+    subscription: Option<String>,
+    /// What `WATCH` has shown this connection so far
+    spectator: manager::SpectatorSession,
+}
+
+impl ConnectionState {
+    /// Check that a command names the player this connection joined as
+    fn authorize(&self, name: &str) -> Result<(), TronError> {
+        if self.identity == TcpIdentity::Unchecked {
+            return Ok(());
+        }
+        match &self.player {
+            Some(player) if player == name => Ok(()),
+            Some(player) => Err(TronError::new(
+                ErrorCode::NotAuthorized,
+                format!("This connection plays as '{}' and cannot act for '{}'.", player, name),
+            )),
+            None => Err(TronError::new(
+                ErrorCode::NotJoined,
+                "JOIN on this connection before sending player commands.",
+            )),
+        }
+    }
+
+    /// The player a player command's argument stands for: a session token,
+    /// or a name where name commands are allowed. Either way it must be the
+    /// player this connection joined as.
+    fn player_for(&self, arg: &str, mgr: &GameManager) -> Result<String, TronError> {
+        if self.identity == TcpIdentity::Tokens {
+            if uuid::Uuid::parse_str(arg.trim()).is_err() {
+                return Err(TronError::new(
+                    ErrorCode::InvalidParams,
+                    "Pass the session token from your join reply here, not a player name.",
+                ));
+            }
+            let name = mgr.player_for_token(arg)?;
+            self.authorize(&name)?;
+            return Ok(name);
+        }
+        let name = validation::name("name", arg)?;
+        self.authorize(&name)?;
+        Ok(name)
+    }
+
+    /// Remember the player after a successful join. With session tokens the
+    /// reply ends in a `SESSION <token>` line.
+    fn bind(&mut self, name: &str, result: Result<String, TronError>, mgr: &GameManager) -> String {
+        match result {
+            Ok(msg) => {
+                self.player = Some(name.to_string());
+                match mgr.player_sessions.get(name) {
+                    Some(session) if self.identity == TcpIdentity::Tokens => {
+                        format!("{}\nSESSION {}", msg, session.session_token)
+                    }
+                    _ => msg,
+                }
+            }
+            Err(e) => error_reply(e),
+        }
+    }
+}
+
+/// Text form of an error reply: `ERROR [CODE]: message`. Plain strings come
+/// from argument validation.
+pub(crate) fn error_reply(e: impl Into<TronError>) -> String {
+    let e = e.into();
+    format!("ERROR [{}]: {}", e.code, e.message)
+}
+
+/// Handle a single TCP command from an MCP player
+async fn handle_command(cmd: &str, rooms: &Rooms, conn: &mut ConnectionState) -> String {
+    let parts: Vec<&str> = cmd.splitn(3, ' ').collect();
+    // Everything after the command word; player names may contain spaces
+    let rest = cmd.split_once(' ').map(|(_, r)| r.trim()).unwrap_or("");
+
+    let Some(manager) = rooms.get(&conn.room) else {
+        return error_reply(TronError::new(
+            ErrorCode::RoomNotFound,
+            format!("Room '{}' not found", conn.room),
+        ));
+    };
+    let Some(command) = find_command(parts[0]) else {
+        return error_reply(TronError::new(
+            ErrorCode::UnknownCommand,
+            format!("Unknown command '{}'. HELP lists the commands.", parts[0]),
+        ));
+    };
+    (command.run)(Request {
+        parts,
+        rest,
+        rooms,
+        manager,
+        conn,
+    })
+    .await
+}
+
+/// What a command handler gets to work with
+struct Request<'a> {
+    /// The line split into the command word, its first argument and the rest
+    parts: Vec<&'a str>,
+    /// Everything after the command word
+    rest: &'a str,
+    rooms: &'a Rooms,
+    /// Manager of the connection's room
+    manager: &'a SharedGameManager,
+    conn: &'a mut ConnectionState,
+}
+
+/// A command handler's reply, once it has run
+type Reply<'a> = Pin<Box<dyn Future<Output = String> + Send + 'a>>;
+
+/// One TCP command. `COMMANDS` both dispatches and documents them, so `HELP`
+/// lists exactly what the server accepts.
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    summary: &'static str,
+    run: fn(Request<'_>) -> Reply<'_>,
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "PROTO",
+        usage: "PROTO <1|2>",
+        summary: "Pick the response framing: 1 escapes newlines into one line, 2 ends each response with --END--",
+        run: proto,
+    },
+    Command {
+        name: "PING",
+        usage: "PING",
+        summary: "Start measuring this connection's round trip; answer with PONG <nonce>",
+        run: ping,
+    },
+    Command {
+        name: "PONG",
+        usage: "PONG <nonce>",
+        summary: "Answer a PING, which records the round trip for --fairness-delay-ms",
+        run: pong,
+    },
+    Command {
+        name: "ROOM",
+        usage: "ROOM [room]",
+        summary: "Show the current room and the others, or route this connection to another room",
+        run: room,
+    },
+    Command {
+        name: "JOIN",
+        usage: "JOIN <name>",
+        summary: "Queue for the next public game",
+        run: join,
+    },
+    Command {
+        name: "TAGS",
+        usage: "TAGS [prefer=<tags>] [avoid=<tags>]",
+        summary: "Course tags, comma-separated, that the next JOIN asks matchmaking for or against",
+        run: tags,
+    },
+    Command {
+        name: "PRACTICE",
+        usage: "PRACTICE <easy|medium|hard|adaptive> <name>",
+        summary: "Start an unranked game against a server bot right away",
+        run: practice,
+    },
+    Command {
+        name: "LOBBY",
+        usage: "LOBBY CREATE <json> | LOBBY JOIN <code> <name> [team=1|2]",
+        summary: "Create a private lobby from create_lobby parameters, or queue in one",
+        run: lobby,
+    },
+    Command {
+        name: "RESUME",
+        usage: "RESUME <name> <resume token>",
+        summary: "Take a player back on a new connection, with a summary of their game",
+        run: resume,
+    },
+    Command {
+        name: "TOKEN",
+        usage: "TOKEN",
+        summary: "The resume token of the player this connection joined as",
+        run: token,
+    },
+    Command {
+        name: "WAIT",
+        usage: "WAIT <session token> <secs>",
+        summary: "Block until the player's game starts, or for at most secs seconds",
+        run: wait,
+    },
+    Command {
+        name: "LOOK",
+        usage: "LOOK <session token> [text|json|compact] [world|ego] [nohints]",
+        summary: "See the grid around your cycle",
+        run: look,
+    },
+    Command {
+        name: "ANALYZE",
+        usage: "ANALYZE <session token>",
+        summary: "How much open area each of left, straight and right keeps you connected to",
+        run: analyze,
+    },
+    Command {
+        name: "MAP",
+        usage: "MAP <session token>",
+        summary: "The whole grid, downsampled to at most 40x40 characters",
+        run: map,
+    },
+    Command {
+        name: "COURSE",
+        usage: "COURSE <session token>",
+        summary: "The course you are playing, or will most likely play next, with a preview",
+        run: course_info,
+    },
+    Command {
+        name: "STEER",
+        usage: "STEER <session token> <left|right|straight|north|south|east|west> [count]",
+        summary: "Turn and move one step, or up to count steps straight on; in a real-time game, set the next tick's turn",
+        run: steer,
+    },
+    Command {
+        name: "PLAN",
+        usage: "PLAN <session token> <move>,<move>,...",
+        summary: "Make up to 10 steers in one go, with a line per move",
+        run: plan,
+    },
+    Command {
+        name: "STATUS",
+        usage: "STATUS <session token>",
+        summary: "Your game's status and score, with a recap once it ends",
+        run: status,
+    },
+    Command {
+        name: "SAY",
+        usage: "SAY <session token> <message>",
+        summary: "Say something to the others in your game; shown in their look and status",
+        run: say,
+    },
+    Command {
+        name: "ABORT",
+        usage: "ABORT <session token>",
+        summary: "Ask to void the game; it ends once every living player agrees",
+        run: abort,
+    },
+    Command {
+        name: "REMATCH",
+        usage: "REMATCH <session token>",
+        summary: "After a game, ask to play the same opponents again on the next level",
+        run: rematch,
+    },
+    Command {
+        name: "LEAVE",
+        usage: "LEAVE <session token>",
+        summary: "Leave the queue, or forfeit the running game as a loss",
+        run: leave,
+    },
+    Command {
+        name: "WHOAMI",
+        usage: "WHOAMI [name]",
+        summary: "Where a name stands: queued, playing or its last result",
+        run: whoami,
+    },
+    Command {
+        name: "STATS",
+        usage: "STATS [name]",
+        summary: "A player's standing, what they are doing now and their latest games",
+        run: stats,
+    },
+    Command {
+        name: "LEADERBOARD",
+        usage: "LEADERBOARD [points|rating|win_rate] [min_games] [level=<n>]",
+        summary: "The room's top 10, overall or on one level",
+        run: leaderboard,
+    },
+    Command {
+        name: "SPECTATE",
+        usage: "SPECTATE [game_id]",
+        summary: "The whole board and scores of a game, the newest running one by default, without joining",
+        run: spectate,
+    },
+    Command {
+        name: "WATCH",
+        usage: "WATCH [game_id]",
+        summary: "SPECTATE, plus the events and chat since this connection's last WATCH",
+        run: watch,
+    },
+    Command {
+        name: "COURSES",
+        usage: "COURSES",
+        summary: "Every course with its size, player limit, tags and source file",
+        run: courses,
+    },
+    Command {
+        name: "SUBSCRIBE",
+        usage: "SUBSCRIBE <session token>",
+        summary: "Push EVENT lines about your games between responses: game_started, opponent_moved, you_crashed, game_finished",
+        run: subscribe,
+    },
+    Command {
+        name: "UNSUBSCRIBE",
+        usage: "UNSUBSCRIBE",
+        summary: "Stop pushing EVENT lines",
+        run: unsubscribe,
+    },
+    Command {
+        name: "HELP",
+        usage: "HELP [command]",
+        summary: "This list, or one command's syntax",
+        run: help,
+    },
+    Command {
+        name: "VERSION",
+        usage: "VERSION",
+        summary: "The server's version and newest protocol",
+        run: version,
+    },
+    Command {
+        name: "BYE",
+        usage: "BYE",
+        summary: "Close the connection, letting go of the player as a dropped connection would",
+        run: bye,
+    },
+];
+
+fn find_command(word: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|c| c.name.eq_ignore_ascii_case(word))
+}
+
+fn help(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, conn, .. } = req;
+    Box::pin(async move {
+        if !rest.is_empty() {
+            return match find_command(rest) {
+                Some(command) => format!("{}: {}", command.usage, command.summary),
+                None => error_reply(TronError::new(
+                    ErrorCode::UnknownCommand,
+                    format!("Unknown command '{}'", rest),
+                )),
+            };
+        }
+        let mut lines: Vec<String> = COMMANDS
+            .iter()
+            .map(|c| format!("{} - {}", c.usage, c.summary))
+            .collect();
+        if conn.identity != TcpIdentity::Tokens {
+            lines.push("This server takes the player name in place of the session token.".to_string());
+        }
+        lines.join("\n")
+    })
+}
+
+fn version(_req: Request<'_>) -> Reply<'_> {
+    Box::pin(async move {
+        format!(
+            "tronmcp {}, protocol {}",
+            env!("CARGO_PKG_VERSION"),
+            mcp::PROTOCOL_VERSION
+        )
+    })
+}
+
+fn proto(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, conn, .. } = req;
+    Box::pin(async move {
+        match rest.parse::<u32>() {
+            Ok(1) => {
+                conn.framed = false;
+                "OK protocol 1".to_string()
+            }
+            Ok(mcp::PROTOCOL_VERSION) => {
+                conn.framed = true;
+                format!("OK protocol {}", mcp::PROTOCOL_VERSION)
+            }
+            _ => error_reply(format!(
+                "Unsupported protocol '{}'. Supported: 1, {}",
+                rest,
+                mcp::PROTOCOL_VERSION
+            )),
+        }
+    })
+}
+
+fn ping(req: Request<'_>) -> Reply<'_> {
+    let Request { conn, .. } = req;
+    Box::pin(async move {
+        // The client echoes the nonce back with PONG; the time until it
+        // arrives is the connection's round trip
+        let nonce = rand::random::<u32>();
+        conn.ping = Some((nonce, std::time::Instant::now()));
+        format!("PONG {}", nonce)
+    })
+}
+
+fn pong(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, conn, .. } = req;
+    Box::pin(async move {
+        match (conn.ping.take(), rest.parse::<u32>()) {
+            (Some((nonce, sent)), Ok(echoed)) if nonce == echoed => {
+                let rtt = sent.elapsed().as_millis() as u64;
+                conn.rtt_ms = Some(rtt);
+                format!("RTT {}", rtt)
+            }
+            _ => error_reply("PONG does not answer a pending PING".to_string()),
+        }
+    })
+}
+
+fn room(req: Request<'_>) -> Reply<'_> {
+    let Request { parts, rooms, conn, .. } = req;
+    Box::pin(async move {
+        if parts.len() < 2 {
+            return format!(
+                "Current room: {}. Available rooms: {}",
+                conn.room,
+                rooms.names().join(", ")
+            );
+        }
+        let room = match validation::room("room", parts[1]) {
+            Ok(room) => room,
+            Err(e) => return error_reply(e),
+        };
+        if rooms.get(&room).is_none() {
+            return error_reply(TronError::new(
+                ErrorCode::RoomNotFound,
+                format!(
+                    "Room '{}' not found. Available rooms: {}",
+                    room,
+                    rooms.names().join(", ")
+                ),
+            ));
+        }
+        conn.room = room;
+        format!("Room set to {}.", conn.room)
+    })
+}
+
+fn join(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("JOIN requires a name".to_string());
+        }
+        let name = match validation::name("name", rest) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        let mut mgr = manager.lock().await;
+        if conn.player.as_deref() == Some(name.as_str())
+            && let Some(msg) = mgr.refresh_queue(&name)
+        {
+            return msg;
+        }
+        let result = mgr.join(name.clone(), conn.tags.clone());
+        conn.bind(&name, result, &mgr)
+    })
+}
+
+fn courses(_req: Request<'_>) -> Reply<'_> {
+    Box::pin(async move {
+        course::CourseRegistry::current()
+            .list()
+            .iter()
+            .map(|(c, file)| c.summary_line(file.as_deref()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+fn tags(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, conn, .. } = req;
+    Box::pin(async move {
+        // TAGS [prefer=a,b] [avoid=c]: course tag preferences for the next JOIN
+        let mut prefer = Vec::new();
+        let mut avoid = Vec::new();
+        for arg in rest.split_whitespace() {
+            let (list, tags) = match arg.split_once('=') {
+                Some(("prefer", tags)) => (&mut prefer, tags),
+                Some(("avoid", tags)) => (&mut avoid, tags),
+                _ => return error_reply("TAGS takes prefer=<tags> and avoid=<tags>".to_string()),
+            };
+            list.extend(tags.split(',').filter(|t| !t.is_empty()).map(String::from));
+        }
+        match validation::tag_preferences(Some(&prefer), Some(&avoid)) {
+            Ok(tags) if tags.is_empty() => {
+                conn.tags = tags;
+                "Tag preferences cleared.".to_string()
+            }
+            Ok(tags) => {
+                let msg = format!("Tag preferences set: {}.", tags.describe());
+                conn.tags = tags;
+                msg
+            }
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn practice(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // PRACTICE <difficulty> <name>
+        let Some((difficulty, name)) = rest.split_once(' ') else {
+            return error_reply("PRACTICE requires a difficulty and a name".to_string());
+        };
+        let (difficulty, name) = match (
+            validation::bot_difficulty("vs_bot", difficulty),
+            validation::name("name", name),
+        ) {
+            (Ok(difficulty), Ok(name)) => (difficulty, name),
+            (Err(e), _) | (_, Err(e)) => return error_reply(e),
+        };
+        let mut mgr = manager.lock().await;
+        let result = mgr.join_vs_bot(name.clone(), difficulty);
+        conn.bind(&name, result, &mgr)
+    })
+}
+
+fn look(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("LOOK requires a session token or player name".to_string());
+        }
+        // LOOK <token|name> [text|json|compact] [world|ego] [nohints]: trailing
+        // option words count as such unless the whole line is the name this
+        // connection plays as
+        let (mut player, mut format, mut options) = (rest, LookFormat::Text, LookOptions::default());
+        if conn.player.as_deref() != Some(rest) {
+            while let Some((rest, word)) = player.rsplit_once(' ') {
+                if word.eq_ignore_ascii_case("nohints") {
+                    options.hints = false;
+                } else if let Some(orientation) = Orientation::parse(word) {
+                    options.orientation = orientation;
+                } else if let Some(parsed) = LookFormat::parse(word) {
+                    format = parsed;
+                } else {
+                    break;
+                }
+                player = rest;
+            }
+        }
+        let mgr = manager.lock().await;
+        let name = match conn.player_for(player, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.look(&name, format, options) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn analyze(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("ANALYZE requires a session token or player name".to_string());
+        }
+        let mgr = manager.lock().await;
+        let name = match conn.player_for(rest, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.analyze(&name) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn map(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("MAP requires a session token or player name".to_string());
+        }
+        let mgr = manager.lock().await;
+        let name = match conn.player_for(rest, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.minimap(&name) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn course_info(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("COURSE requires a session token or player name".to_string());
+        }
+        let mgr = manager.lock().await;
+        let name = match conn.player_for(rest, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.course_info(&name) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn steer(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // STEER <token|name> <direction> [count]: the direction is the last
+        // word, or the one before a trailing count
+        let Some((mut player, mut direction)) = rest.rsplit_once(' ') else {
+            return error_reply("STEER requires a session token or player name, and a direction".to_string());
+        };
+        let mut count = 1;
+        if let Ok(n) = direction.parse::<u32>() {
+            let Some((p, d)) = player.rsplit_once(' ') else {
+                return error_reply("STEER requires a session token or player name, and a direction".to_string());
+            };
+            count = match validation::move_count("count", n) {
+                Ok(count) => count,
+                Err(e) => return error_reply(e),
+            };
+            (player, direction) = (p, d);
+        }
+        let steer = match validation::direction("direction", direction) {
+            Ok(steer) => steer,
+            Err(e) => return error_reply(e),
+        };
+        let (name, delay) = {
+            let mut mgr = manager.lock().await;
+            match conn.player_for(player, &mgr) {
+                Ok(name) => {
+                    let delay = mgr.fairness_delay(&name, conn.rtt_ms);
+                    (name, delay)
+                }
+                Err(e) => return error_reply(e),
+            }
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        let mut mgr = manager.lock().await;
+        let result = if count > 1 {
+            mgr.move_plan(&name, MovePlan::Repeat(steer, count))
+        } else {
+            mgr.move_player(&name, steer)
+        };
+        match result {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn plan(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // PLAN <token|name> <move>,<move>,...: the moves are the last word
+        let Some((player, moves)) = rest.rsplit_once(' ') else {
+            return error_reply("PLAN requires a session token or player name, and a comma-separated list of moves".to_string());
+        };
+        let moves: Vec<String> = moves.split(',').map(str::to_string).collect();
+        let steers = match validation::moves("moves", &moves) {
+            Ok(steers) => steers,
+            Err(e) => return error_reply(e),
+        };
+        let (name, delay) = {
+            let mut mgr = manager.lock().await;
+            match conn.player_for(player, &mgr) {
+                Ok(name) => {
+                    let delay = mgr.fairness_delay(&name, conn.rtt_ms);
+                    (name, delay)
+                }
+                Err(e) => return error_reply(e),
+            }
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        let mut mgr = manager.lock().await;
+        match mgr.move_plan(&name, MovePlan::List(steers)) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn lobby(req: Request<'_>) -> Reply<'_> {
+    let Request { parts, manager, conn, .. } = req;
+    Box::pin(async move {
+        if parts.len() < 3 {
+            return error_reply("LOBBY requires CREATE <json> or JOIN <code> <name>".to_string());
+        }
+        match parts[1].to_uppercase().as_str() {
+            "CREATE" => {
+                let req: CreateLobbyParams = match serde_json::from_str(parts[2]) {
+                    Ok(req) => req,
+                    Err(e) => return error_reply(format!("Invalid lobby request: {}", e)),
+                };
+                if let Err(e) = req.validate() {
+                    return error_reply(e);
+                }
+                let mut mgr = manager.lock().await;
+                let public = req.public.unwrap_or(false);
+                let tags = match req.tag_preferences() {
+                    Ok(tags) => tags,
+                    Err(e) => return error_reply(e),
+                };
+                let level = match req.course_level() {
+                    Ok(level) => level,
+                    Err(e) => return error_reply(e),
+                };
+                match mgr.create_lobby(level, req.rules, req.admin_token.as_deref(), public, &tags) {
+                    Ok(msg) => msg,
+                    Err(e) => error_reply(e),
+                }
+            }
+            "JOIN" => {
+                let Some((code, name)) = parts[2].split_once(' ') else {
+                    return error_reply("LOBBY JOIN requires a code and a name".to_string());
+                };
+                // Names cannot hold '=', so a trailing team=N is never part of one
+                let (name, team) = match name.rsplit_once(" team=") {
+                    Some((name, team)) => {
+                        let team = team.trim().parse().map_err(|_| "'team' must be 1 or 2.".to_string());
+                        match team.and_then(|team| validation::team("team", team)) {
+                            Ok(team) => (name, Some(team)),
+                            Err(e) => return error_reply(e),
+                        }
+                    }
+                    None => (name, None),
+                };
+                let (code, name) = match (
+                    validation::lobby_code("lobby", code),
+                    validation::name("name", name),
+                ) {
+                    (Ok(code), Ok(name)) => (code, name),
+                    (Err(e), _) | (_, Err(e)) => return error_reply(e),
+                };
+                let mut mgr = manager.lock().await;
+                if conn.player.as_deref() == Some(name.as_str())
+                    && let Some(msg) = mgr.refresh_queue(&name)
+                {
+                    return msg;
+                }
+                let result = mgr.join_lobby(&code, name.clone(), team);
+                conn.bind(&name, result, &mgr)
+            }
+            _ => error_reply(TronError::new(
+                ErrorCode::UnknownCommand,
+                format!("Unknown LOBBY subcommand '{}'", parts[1]),
+            )),
+        }
+    })
+}
+
+fn status(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("STATUS requires a session token or player name".to_string());
+        }
+        let mut mgr = manager.lock().await;
+        let name = match conn.player_for(rest, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.game_status(&name) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn wait(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // WAIT <token|name> <timeout_secs>: the timeout is the last word
+        let Some((player, secs)) = rest.rsplit_once(' ') else {
+            return error_reply("WAIT requires a session token or player name, and a timeout in seconds".to_string());
+        };
+        let timeout = match secs.parse().map_err(|_| "'timeout_secs' must be a number.".to_string())
+            .and_then(|secs| validation::wait_secs("timeout_secs", secs))
+        {
+            Ok(timeout) => timeout,
+            Err(e) => return error_reply(e),
+        };
+        let name = match conn.player_for(player, &*manager.lock().await) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match crate::manager::wait_for_game_start(manager, &name, timeout).await {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn say(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // SAY <token|name> <message>: the message is everything after the first word
+        let Some((player, message)) = rest.split_once(' ') else {
+            return error_reply("SAY requires a session token or player name, and a message".to_string());
+        };
+        let mut mgr = manager.lock().await;
+        let name = match conn.player_for(player, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.say(&name, message) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn abort(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("ABORT requires a session token or player name".to_string());
+        }
+        let mut mgr = manager.lock().await;
+        let name = match conn.player_for(rest, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.request_abort(&name) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn rematch(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("REMATCH requires a session token or player name".to_string());
+        }
+        let mut mgr = manager.lock().await;
+        let name = match conn.player_for(rest, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.request_rematch(&name) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn whoami(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // WHOAMI [name]: defaults to the player this connection joined as
+        let name = match (rest, &conn.player) {
+            ("", Some(player)) => player.clone(),
+            ("", None) => return error_reply("WHOAMI requires player name".to_string()),
+            (name, _) => match validation::name("name", name) {
+                Ok(name) => name,
+                Err(e) => return error_reply(e),
+            },
+        };
+        let mgr = manager.lock().await;
+        mgr.whoami(&name, conn.player.as_deref())
+    })
+}
+
+fn stats(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // STATS [name]: defaults to the player this connection joined as
+        let name = match (rest, &conn.player) {
+            ("", Some(player)) => player.clone(),
+            ("", None) => return error_reply("STATS requires player name".to_string()),
+            (name, _) => match validation::name("name", name) {
+                Ok(name) => name,
+                Err(e) => return error_reply(e),
+            },
+        };
+        match crate::manager::player_profile(manager, &name).await {
+            Ok(profile) => profile.to_text(),
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn leaderboard(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, .. } = req;
+    Box::pin(async move {
+        // LEADERBOARD [points|rating|win_rate] [min_games] [level=<n>]
+        let mut level = None;
+        let mut args = Vec::new();
+        for arg in rest.split_whitespace() {
+            match arg.strip_prefix("level=") {
+                Some(n) => match n.parse::<u32>() {
+                    Ok(n) => level = Some(n),
+                    Err(_) => return error_reply("'level' must be a whole number.".to_string()),
+                },
+                None => args.push(arg),
+            }
+        }
+        let mut args = args.into_iter();
+        let sort = match args.next().map(manager::LeaderboardSort::parse).transpose() {
+            Ok(sort) => sort.unwrap_or_default(),
+            Err(e) => return error_reply(format!("'sort': {}", e)),
+        };
+        let min_games = match args.next().map(str::parse::<u32>).transpose() {
+            Ok(min_games) => min_games.unwrap_or(sort.default_min_games()),
+            Err(_) => return error_reply("'min_games' must be a whole number.".to_string()),
+        };
+        match manager.lock().await.leaderboard_text(sort, min_games, level) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn spectate(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, .. } = req;
+    Box::pin(async move {
+        let game_id = Some(rest).filter(|id| !id.is_empty());
+        match manager.lock().await.spectate(game_id) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn watch(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        let game_id = Some(rest).filter(|id| !id.is_empty());
+        match manager.lock().await.watch(game_id, &mut conn.spectator) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+fn token(req: Request<'_>) -> Reply<'_> {
+    let Request { manager, conn, .. } = req;
+    Box::pin(async move {
+        // Resume token of the player this connection joined as
+        let Some(name) = conn.player.clone() else {
+            return error_reply(TronError::new(
+                ErrorCode::NotJoined,
+                "JOIN on this connection before asking for a token.",
+            ));
+        };
+        let mgr = manager.lock().await;
+        match mgr.player_sessions.get(&name) {
+            Some(session) => format!("TOKEN {}", session.resume_token),
+            None => error_reply(TronError::new(ErrorCode::NotJoined, "Player not found. Use join_game first.")),
+        }
+    })
+}
+
+fn resume(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        // RESUME <name> <token>: take a player back on a new connection,
+        // with a summary of the game or queue slot they are back in
+        let Some((name, token)) = rest.rsplit_once(' ') else {
+            return error_reply("RESUME requires player name and token".to_string());
+        };
+        let name = match validation::name("name", name) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        let mut mgr = manager.lock().await;
+        let result = mgr.resume(&name, token).map(|summary| match summary {
+            Some(summary) => format!("Resumed as {}. {}", name, summary),
+            None => format!("Resumed as {}.", name),
+        });
+        conn.bind(&name, result, &mgr)
+    })
+}
+
+fn subscribe(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("SUBSCRIBE requires a session token or player name".to_string());
+        }
+        let name = match conn.player_for(rest, &*manager.lock().await) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        // The connection loop starts listening once this reply is out
+        let msg = format!(
+            "Subscribed to {}'s events. They arrive as '{}{{json}}' lines between responses.",
+            name,
+            push::EVENT_PREFIX
+        );
+        conn.subscription = Some(name);
+        msg
+    })
+}
+
+fn unsubscribe(req: Request<'_>) -> Reply<'_> {
+    let Request { conn, .. } = req;
+    Box::pin(async move {
+        conn.subscription = None;
+        "Unsubscribed.".to_string()
+    })
+}
+
+fn bye(req: Request<'_>) -> Reply<'_> {
+    let Request { conn, .. } = req;
+    Box::pin(async move {
+        // The client is done: close the connection, which lets go of the
+        // player as a dropped connection would
+        conn.closing = true;
+        "Bye.".to_string()
+    })
+}
+
+fn leave(req: Request<'_>) -> Reply<'_> {
+    let Request { rest, manager, conn, .. } = req;
+    Box::pin(async move {
+        if rest.is_empty() {
+            return error_reply("LEAVE requires a session token or player name".to_string());
+        }
+        let mut mgr = manager.lock().await;
+        let name = match conn.player_for(rest, &mgr) {
+            Ok(name) => name,
+            Err(e) => return error_reply(e),
+        };
+        match mgr.leave(&name) {
+            Ok(msg) => msg,
+            Err(e) => error_reply(e),
+        }
+    })
+}
+
+/// A client's TCP connection without protocol 2 framing, so every reply is
+/// one line; `tronmcp demo` and `tronmcp bot` play through it
+pub struct TcpClient {
+    reader: BufReader<tokio::net::tcp::OwnedReadHalf>,
+    writer: tokio::net::tcp::OwnedWriteHalf,
+}
+
+impl TcpClient {
+    pub async fn connect(addr: impl tokio::net::ToSocketAddrs) -> Result<TcpClient, String> {
+        let stream = tokio::net::TcpStream::connect(addr)
+            .await
+            .map_err(|e| format!("cannot reach the TCP server: {}", e))?;
+        let (reader, writer) = stream.into_split();
+        Ok(TcpClient {
+            reader: BufReader::new(reader),
+            writer,
+        })
+    }
+
+    /// Send one command. The outer error is a broken connection, the inner
+    /// one an `ERROR` reply.
+    pub async fn send(&mut self, command: &str) -> Result<Result<String, String>, String> {
+        let lost = |e: std::io::Error| format!("lost the TCP connection: {}", e);
+        self.writer.write_all(format!("{}\n", command).as_bytes()).await.map_err(lost)?;
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await.map_err(lost)? == 0 {
+            return Err("the TCP server closed the connection".to_string());
+        }
+        let reply = line.trim_end().replace("\\n", "\n");
+        Ok(if reply.starts_with("ERROR") { Err(reply) } else { Ok(reply) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil;
+
+    fn connection(identity: TcpIdentity) -> ConnectionState {
+        ConnectionState {
+            room: "default".to_string(),
+            framed: false,
+            player: None,
+            identity,
+            tags: TagPreferences::default(),
+            ping: None,
+            rtt_ms: None,
+            closing: false,
+            subscription: None,
+            spectator: manager::SpectatorSession::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn commands_share_the_tool_parameter_limits() {
+        let (rooms, _dir) = testutil::rooms();
+        let mut conn = connection(TcpIdentity::Unchecked);
+        let long_name = "a".repeat(validation::MAX_NAME_LEN + 1);
+        let cases = [
+            (format!("JOIN {}", long_name), "'name' must be at most 32 characters"),
+            (format!("JOIN {}", "a".repeat(100_000)), "'name' must be at most 32 characters"),
+            (format!("PRACTICE easy {}", long_name), "'name' must be at most 32 characters"),
+            ("PRACTICE godlike Alice".to_string(), "'vs_bot' must be"),
+            (format!("LOBBY JOIN ABCDEF {}", long_name), "'name' must be at most 32 characters"),
+            ("LOBBY JOIN ABCDEFG Alice".to_string(), "'lobby' must be a 6-character"),
+            ("LOBBY JOIN ABCDEF Alice team=3".to_string(), "'team' must be 1 or 2"),
+            (r#"LOBBY CREATE {"level":0}"#.to_string(), "'level' must be between 1 and"),
+            (r#"LOBBY CREATE {"rules":{"lives":10}}"#.to_string(), "'rules.lives' must be between 1 and 9"),
+            ("STEER Alice up".to_string(), "'direction' must be"),
+            ("STEER Alice left 11".to_string(), "'count' must be between 1 and 10"),
+            ("PLAN Alice left,left,left,left,left,left,left,left,left,left,left".to_string(), "'moves' must list 1-10 moves"),
+            ("WAIT Alice 301".to_string(), "'timeout_secs' must be between 1 and 300"),
+            ("TAGS prefer=lava".to_string(), "unknown tag 'lava'"),
+            ("ROOM no/such".to_string(), "'room' must be 1-32"),
+            (format!("WHOAMI {}", long_name), "'name' must be at most 32 characters"),
+            ("SPECTATE not-a-game".to_string(), "'game_id' must be a game id"),
+        ];
+        for (line, expected) in cases {
+            let reply = handle_command(&line, &rooms, &mut conn).await;
+            let shown = &line[..line.len().min(60)];
+            assert!(reply.starts_with("ERROR [INVALID_PARAMS]"), "{}: {}", shown, reply);
+            assert!(reply.contains(expected), "{}: '{}' lacks '{}'", shown, reply, expected);
+        }
+
+        // None of it got as far as the room
+        let mgr = rooms.default_room().lock().await;
+        assert!(mgr.player_sessions.is_empty());
+        assert!(mgr.waiting_players.is_empty());
+        assert!(mgr.lobbies.is_empty());
+        assert!(conn.player.is_none());
+    }
+
+    /// The lines `read_command` makes of `input`, until the stream ends
+    async fn lines(input: &[u8]) -> Vec<Result<String, String>> {
+        let mut reader = tokio::io::BufReader::new(input);
+        let mut lines = Vec::new();
+        loop {
+            match read_command(&mut reader).await.unwrap() {
+                Line::Command(line) => lines.push(Ok(line)),
+                Line::Rejected(e) => lines.push(Err(e.message)),
+                Line::Closed => return lines,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn bad_bytes_reject_only_their_line() {
+        let mut input = b"STATUS Alice\n\xff\xfe\x80 garbage\nLOOK Alice\n".to_vec();
+        input.extend(vec![b'a'; MAX_LINE_LEN * 2]);
+        input.extend(b"\nLEAVE Alice\n");
+
+        assert_eq!(
+            lines(&input).await,
+            [
+                Ok("STATUS Alice\n".to_string()),
+                Err("Commands must be valid UTF-8.".to_string()),
+                Ok("LOOK Alice\n".to_string()),
+                Err(format!("Command lines are limited to {} bytes.", MAX_LINE_LEN)),
+                Ok("LEAVE Alice\n".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_second_connection_cannot_steer_anothers_player() {
+        for identity in [TcpIdentity::Names, TcpIdentity::Tokens] {
+            let (rooms, _dir) = testutil::rooms();
+            let (mut alice, mut bob, mut stranger) = (connection(identity), connection(identity), connection(identity));
+            let alice_token = handle_command("JOIN Alice", &rooms, &mut alice).await;
+            let alice_token = alice_token.rsplit_once("SESSION ").map_or("", |(_, t)| t.trim()).to_string();
+            handle_command("JOIN Bob", &rooms, &mut bob).await;
+            let alice_arg = match identity {
+                TcpIdentity::Tokens => alice_token.as_str(),
+                _ => "Alice",
+            };
+
+            let reply = handle_command(&format!("STEER {} left", alice_arg), &rooms, &mut bob).await;
+            assert!(reply.starts_with("ERROR [NOT_AUTHORIZED]"), "{:?}: {}", identity, reply);
+            let reply = handle_command(&format!("STEER {} left", alice_arg), &rooms, &mut stranger).await;
+            assert!(reply.starts_with("ERROR [NOT_JOINED]"), "{:?}: {}", identity, reply);
+            // Nor may a stranger take the name over by joining as it
+            let reply = handle_command("JOIN Alice", &rooms, &mut stranger).await;
+            assert!(reply.starts_with("ERROR"), "{:?}: {}", identity, reply);
+            let reply = handle_command(&format!("STEER {} left", alice_arg), &rooms, &mut stranger).await;
+            assert!(reply.starts_with("ERROR"), "{:?}: {}", identity, reply);
+
+            let mgr = rooms.default_room().lock().await;
+            assert_eq!(mgr.get_active_games()[0].tick, 0, "{:?}", identity);
+        }
+    }
+
+    #[tokio::test]
+    async fn player_commands_need_the_session_token_from_the_join() {
+        let (rooms, _dir) = testutil::rooms();
+        let (mut alice, mut bob) = (connection(TcpIdentity::Tokens), connection(TcpIdentity::Tokens));
+        let reply = handle_command("JOIN Alice", &rooms, &mut alice).await;
+        let token = reply.rsplit_once("SESSION ").unwrap().1.trim().to_string();
+        handle_command("JOIN Bob", &rooms, &mut bob).await;
+        assert_eq!(rooms.default_room().lock().await.player_for_token(&token).unwrap(), "Alice");
+
+        let wrong = uuid::Uuid::new_v4();
+        for line in [format!("STEER {} left", wrong), "STEER Alice left".to_string()] {
+            let reply = handle_command(&line, &rooms, &mut alice).await;
+            assert!(reply.starts_with("ERROR"), "{}: {}", line, reply);
+        }
+        assert_eq!(rooms.default_room().lock().await.get_active_games()[0].tick, 0);
+
+        let reply = handle_command(&format!("STEER {} left", token), &rooms, &mut alice).await;
+        assert!(!reply.starts_with("ERROR"), "{}", reply);
+        assert_eq!(rooms.default_room().lock().await.get_active_games()[0].tick, 1);
+    }
+
+    #[tokio::test]
+    async fn legacy_names_let_any_connection_act_for_any_player() {
+        let (rooms, _dir) = testutil::rooms();
+        let mut alice = connection(TcpIdentity::Unchecked);
+        let reply = handle_command("JOIN Alice", &rooms, &mut alice).await;
+        assert!(!reply.contains("SESSION"), "{}", reply);
+        handle_command("JOIN Bob", &rooms, &mut connection(TcpIdentity::Unchecked)).await;
+
+        let reply = handle_command("STEER Bob left", &rooms, &mut connection(TcpIdentity::Unchecked)).await;
+        assert!(!reply.starts_with("ERROR"), "{}", reply);
+        assert_eq!(rooms.default_room().lock().await.get_active_games()[0].tick, 1);
+    }
+
+    #[tokio::test]
+    async fn names_with_spaces_work_for_every_command() {
+        let (rooms, _dir) = testutil::rooms();
+        let mut claude = connection(TcpIdentity::Unchecked);
+        let mut gpt = connection(TcpIdentity::Unchecked);
+        assert!(!handle_command("JOIN Claude Opus", &rooms, &mut claude).await.starts_with("ERROR"));
+        assert!(!handle_command("JOIN GPT 4 Turbo Bot", &rooms, &mut gpt).await.starts_with("ERROR"));
+        assert_eq!(rooms.default_room().lock().await.get_active_games().len(), 1);
+
+        // One space, several, trailing whitespace, and option words after the name
+        let expected = rooms
+            .default_room()
+            .lock()
+            .await
+            .look("Claude Opus", LookFormat::Compact, LookOptions::default())
+            .unwrap();
+        for line in ["LOOK Claude Opus", "LOOK GPT 4 Turbo Bot", "STATUS GPT 4 Turbo Bot  \t", "COURSE Claude Opus "] {
+            let reply = handle_command(line, &rooms, &mut connection(TcpIdentity::Unchecked)).await;
+            assert!(!reply.starts_with("ERROR"), "{}: {}", line, reply);
+        }
+        let reply = handle_command("LOOK Claude Opus compact", &rooms, &mut gpt).await;
+        assert_eq!(reply, expected);
+
+        let reply = handle_command("STEER Claude Opus left", &rooms, &mut claude).await;
+        assert!(!reply.starts_with("ERROR"), "{}", reply);
+        let reply = handle_command("STEER GPT 4 Turbo Bot straight 2", &rooms, &mut gpt).await;
+        assert!(reply.ends_with("Made all 2 moves."), "{}", reply);
+        let game = rooms.default_room().lock().await.get_active_games()[0].clone();
+        let moved: Vec<(&str, u32)> = game.players.iter().map(|p| (p.name.as_str(), p.distance)).collect();
+        assert_eq!(moved, [("Claude Opus", 1), ("GPT 4 Turbo Bot", 2)]);
+
+        // Only the whole name finds the player
+        let reply = handle_command("STATUS Claude", &rooms, &mut connection(TcpIdentity::Unchecked)).await;
+        assert!(reply.starts_with("ERROR [NOT_JOINED]"), "{}", reply);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fast_connections_steer_as_late_as_the_slowest() {
+        let (rooms, _dir) = testutil::rooms();
+        rooms.default_room().lock().await.fairness_delay = crate::fairness::FairnessDelay::Auto;
+        let mut near = connection(TcpIdentity::Unchecked);
+        let mut far = connection(TcpIdentity::Unchecked);
+        handle_command("JOIN Near", &rooms, &mut near).await;
+        handle_command("JOIN Far", &rooms, &mut far).await;
+        // Round trips as if the PONGs came back 20ms and 200ms after the PINGs
+        for (conn, rtt) in [(&mut near, 20), (&mut far, 200)] {
+            let sent = std::time::Instant::now() - std::time::Duration::from_millis(rtt);
+            conn.ping = Some((7, sent));
+            assert!(handle_command("PONG 7", &rooms, conn).await.starts_with("RTT "));
+        }
+        let (near_rtt, far_rtt) = (near.rtt_ms.unwrap(), far.rtt_ms.unwrap());
+
+        let timed = async |line: &str, conn: &mut ConnectionState| {
+            let started = tokio::time::Instant::now();
+            let reply = handle_command(line, &rooms, conn).await;
+            assert!(!reply.starts_with("ERROR"), "{}: {}", line, reply);
+            started.elapsed().as_millis() as u64
+        };
+        assert_eq!(timed("STEER Far straight", &mut far).await, 0);
+        assert_eq!(timed("STEER Near straight", &mut near).await, far_rtt - near_rtt);
+        // Only steering waits
+        assert_eq!(timed("LOOK Near", &mut near).await, 0);
+
+        let started = tokio::time::Instant::now();
+        let status = handle_command("STATUS Near", &rooms, &mut near).await;
+        assert!(started.elapsed().is_zero());
+        let expected = format!(
+            "Fairness delay per steer: Near +{}ms (rtt {}ms), Far +0ms (rtt {}ms)",
+            far_rtt - near_rtt,
+            near_rtt,
+            far_rtt
+        );
+        assert!(status.contains(&expected), "{}", status);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn commands_over_the_rate_limit_are_refused_until_it_refills() {
+        let (rooms, _dir) = testutil::rooms();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let settings = TcpSettings {
+            identity: TcpIdentity::Tokens,
+            rate_limit: ratelimit::RateLimit::PerSec { rate: 1, burst: 2 },
+        };
+        let serving = tokio::spawn(handle_connection(Box::new(server), "test".to_string(), rooms, settings));
+        let (replies, mut commands) = tokio::io::split(client);
+        let mut replies = BufReader::new(replies).lines();
+        let mut send = async |line: &str| {
+            commands.write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+            replies.next_line().await.unwrap().unwrap()
+        };
+
+        // The burst goes through, the next one is told how long to wait
+        assert!(send("VERSION").await.starts_with("tronmcp"));
+        assert!(send("VERSION").await.starts_with("tronmcp"));
+        assert_eq!(send("VERSION").await, "ERROR [RATE_LIMITED]: Rate limited, retry in 1000ms.");
+
+        tokio::time::advance(std::time::Duration::from_millis(999)).await;
+        assert!(send("VERSION").await.starts_with("ERROR [RATE_LIMITED]"));
+        tokio::time::advance(std::time::Duration::from_secs(1)).await;
+        assert!(send("VERSION").await.starts_with("tronmcp"));
+
+        // Hanging up ends the connection even while over the limit
+        commands.shutdown().await.unwrap();
+        serving.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn framed_look_arrives_byte_for_byte() {
+        let (rooms, _dir) = testutil::rooms();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let settings = TcpSettings {
+            identity: TcpIdentity::Unchecked,
+            rate_limit: ratelimit::RateLimit::Off,
+        };
+        let serving = tokio::spawn(handle_connection(Box::new(server), "test".to_string(), rooms.clone(), settings));
+        let (replies, mut commands) = tokio::io::split(client);
+        let mut replies = BufReader::new(replies).lines();
+        let mut send = async |line: &str, framed: bool| {
+            commands.write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+            let mut lines = Vec::new();
+            while let Some(line) = replies.next_line().await.unwrap() {
+                if !framed {
+                    return line;
+                }
+                if line == mcp::FRAME_END {
+                    break;
+                }
+                lines.push(line);
+            }
+            lines.join("\n")
+        };
+
+        // Protocol 1 escapes the look into one line
+        send("JOIN Claude", false).await;
+        send("JOIN Gemini", false).await;
+        let escaped = send("LOOK Claude", false).await;
+        assert!(send("PROTO 2", true).await.starts_with("OK"));
+        let framed = send("LOOK Claude", true).await;
+
+        let expected = rooms
+            .default_room()
+            .lock()
+            .await
+            .look("Claude", LookFormat::Text, LookOptions::default())
+            .unwrap();
+        assert_eq!(framed, expected);
+        assert_eq!(escaped, expected.replace('\n', "\\n"));
+        let grid: Vec<&str> = framed.lines().skip_while(|l| !l.starts_with("Grid (")).skip(1).take_while(|l| *l != "Moves:").collect();
+        assert_eq!(grid.len(), 15, "{}", framed);
+
+        commands.shutdown().await.unwrap();
+        serving.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn help_lists_every_command() {
+        let (rooms, _dir) = testutil::rooms();
+        let mut conn = connection(TcpIdentity::Tokens);
+        let help = handle_command("HELP", &rooms, &mut conn).await;
+        let lines: Vec<&str> = help.lines().collect();
+        assert_eq!(lines.len(), COMMANDS.len());
+        for command in COMMANDS {
+            assert!(
+                lines.iter().any(|l| l.starts_with(&format!("{} - ", command.usage))),
+                "{} is missing from HELP",
+                command.name
+            );
+            let reply = handle_command(&format!("HELP {}", command.name.to_lowercase()), &rooms, &mut conn).await;
+            assert_eq!(reply, format!("{}: {}", command.usage, command.summary));
+        }
+    }
+
+    #[tokio::test]
+    async fn whoami_tells_whose_name_the_connection_holds() {
+        let (rooms, _dir) = testutil::rooms();
+        let mut claude = connection(TcpIdentity::Unchecked);
+        let mut stranger = connection(TcpIdentity::Unchecked);
+        assert!(handle_command("WHOAMI", &rooms, &mut stranger).await.starts_with("ERROR [INVALID_PARAMS]"));
+        handle_command("JOIN Claude", &rooms, &mut claude).await;
+
+        let own = handle_command("WHOAMI", &rooms, &mut claude).await;
+        assert!(own.starts_with("Player 'Claude'"), "{}", own);
+        assert!(own.contains("State: QUEUED"), "{}", own);
+        assert!(own.contains("Connection: holds 'Claude'"), "{}", own);
+
+        let asked = handle_command("WHOAMI Claude", &rooms, &mut stranger).await;
+        assert!(asked.contains("State: QUEUED"), "{}", asked);
+        assert!(asked.contains("Connection: not joined as any player"), "{}", asked);
+        handle_command("JOIN Gemini", &rooms, &mut stranger).await;
+        let asked = handle_command("WHOAMI Claude", &rooms, &mut stranger).await;
+        assert!(asked.contains("State: PLAYING"), "{}", asked);
+        assert!(asked.contains("Connection: plays as 'Gemini', not 'Claude'"), "{}", asked);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn players_join_and_look_over_a_unix_socket() {
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+
+        async fn send((reader, writer): &mut (BufReader<OwnedReadHalf>, OwnedWriteHalf), line: &str) -> String {
+            writer.write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+            let mut reply = String::new();
+            reader.read_line(&mut reply).await.unwrap();
+            reply.trim_end().to_string()
+        }
+
+        let (rooms, dir) = testutil::rooms();
+        let path = dir.path().join("tron.sock");
+        let listener = CommandListener::bind_unix(&path, 0o600).unwrap();
+        assert_eq!(listener.describe(), format!("unix:{}", path.display()));
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        let settings = TcpSettings {
+            identity: TcpIdentity::Names,
+            rate_limit: ratelimit::RateLimit::Off,
+        };
+        let ct = CancellationToken::new();
+        let serving = run_tcp_server(listener, rooms.clone(), settings, ct.clone());
+
+        let playing = async {
+            let connect = || async {
+                let (reader, writer) = tokio::net::UnixStream::connect(&path).await.unwrap().into_split();
+                (BufReader::new(reader), writer)
+            };
+            let (mut claude, mut gemini) = (connect().await, connect().await);
+            let joined = send(&mut claude, "JOIN Claude").await;
+            assert!(!joined.starts_with("ERROR"), "{}", joined);
+            send(&mut gemini, "JOIN Gemini").await;
+            let look = send(&mut claude, "LOOK Claude").await;
+            assert!(look.starts_with("Your light-cycle 'Claude' is at"), "{}", look);
+            let other = send(&mut claude, "LOOK Gemini").await;
+            assert!(other.starts_with("ERROR [NOT_AUTHORIZED]"), "{}", other);
+
+            // `tronmcp play --server unix:<path>` reaches it too
+            let server = format!("unix:{}", path.display());
+            crate::mcp::TronMcpServer::new(&server, std::time::Duration::from_secs(5)).await.unwrap();
+            ct.cancel();
+        };
+        let (served, ()) = tokio::join!(serving, playing);
+        served.unwrap();
+
+        assert_eq!(rooms.default_room().lock().await.get_active_games().len(), 1);
+        assert!(!path.exists(), "the socket file outlived the server");
+    }
+
+    /// A player's end of a TCP connection: the responses to its commands,
+    /// and the events pushed in between
+    struct Client {
+        reader: BufReader<tokio::net::tcp::OwnedReadHalf>,
+        writer: tokio::net::tcp::OwnedWriteHalf,
+        events: Vec<serde_json::Value>,
+    }
+
+    impl Client {
+        async fn connect(addr: std::net::SocketAddr) -> Client {
+            let (reader, writer) = tokio::net::TcpStream::connect(addr).await.unwrap().into_split();
+            Client {
+                reader: BufReader::new(reader),
+                writer,
+                events: Vec::new(),
+            }
+        }
+
+        async fn line(&mut self) -> String {
+            let mut line = String::new();
+            tokio::time::timeout(std::time::Duration::from_secs(5), self.reader.read_line(&mut line))
+                .await
+                .expect("nothing from the server within 5s")
+                .unwrap();
+            line.trim_end().to_string()
+        }
+
+        /// Send a command and read up to its response, keeping any events
+        async fn send(&mut self, command: &str) -> String {
+            self.writer.write_all(format!("{}\n", command).as_bytes()).await.unwrap();
+            loop {
+                let line = self.line().await;
+                match line.strip_prefix(push::EVENT_PREFIX) {
+                    Some(event) => self.events.push(serde_json::from_str(event).unwrap()),
+                    None => return line,
+                }
+            }
+        }
+
+        /// Wait until `count` events have come in; what they said
+        async fn events(&mut self, count: usize) -> Vec<String> {
+            while self.events.len() < count {
+                let line = self.line().await;
+                let event = line.strip_prefix(push::EVENT_PREFIX).unwrap_or_else(|| panic!("not an event: {}", line));
+                self.events.push(serde_json::from_str(event).unwrap());
+            }
+            self.events
+                .iter()
+                .map(|e| format!("{} {}", e["event"].as_str().unwrap(), e["player"].as_str().unwrap_or("")))
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribed_connections_are_pushed_their_games_events() {
+        let (rooms, _dir) = testutil::rooms();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let settings = TcpSettings {
+            identity: TcpIdentity::Names,
+            rate_limit: ratelimit::RateLimit::Off,
+        };
+        let ct = CancellationToken::new();
+        let serving = run_tcp_server(CommandListener::Tcp(listener), rooms.clone(), settings, ct.clone());
+
+        let playing = async {
+            let mut claude = Client::connect(addr).await;
+            let mut gemini = Client::connect(addr).await;
+            claude.send("JOIN Claude").await;
+            let reply = claude.send("SUBSCRIBE Claude").await;
+            assert!(reply.starts_with("Subscribed to Claude's events."), "{}", reply);
+            // Answered once the connection listens
+            claude.send("WHOAMI").await;
+
+            gemini.send("JOIN Gemini").await;
+            assert_eq!(claude.events(1).await, ["game_started "]);
+            assert_eq!(claude.events[0]["players"], serde_json::json!(["Claude", "Gemini"]));
+            gemini.send("STEER Gemini straight").await;
+            assert_eq!(claude.events(2).await[1], "opponent_moved Gemini");
+            assert_eq!(claude.events[1]["alive"], true);
+
+            // Events never land inside a response, and the player's own
+            // moves aren't echoed back
+            let reply = claude.send("STEER Claude straight").await;
+            assert!(!reply.starts_with("ERROR"), "{}", reply);
+            claude.send("LEAVE Claude").await;
+            let events = claude.events(4).await;
+            assert_eq!(events[2..], ["you_crashed ", "game_finished "]);
+            assert_eq!(claude.events[3]["winner"], "Gemini");
+            assert_eq!(claude.events[3]["game_id"], claude.events[0]["game_id"]);
+
+            // Unsubscribed connections are told nothing
+            assert!(gemini.events.is_empty());
+            ct.cancel();
+        };
+        let (served, ()) = tokio::join!(serving, playing);
+        served.unwrap();
+    }
+}
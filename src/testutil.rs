@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::manager::GameManager;
+use crate::storage::JsonStorage;
+
+/// A fresh directory under the system temp dir, removed again on drop
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("tronmcp-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        ScratchDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A manager keeping its files in a scratch directory, with JSON storage
+pub fn manager() -> (GameManager, ScratchDir) {
+    let dir = ScratchDir::new();
+    let storage = Arc::new(JsonStorage::new(dir.path()));
+    let (manager, _) = GameManager::new(dir.path(), storage);
+    (manager, dir)
+}
+
+/// One room, "default", keeping its files in a scratch directory
+pub fn rooms() -> (crate::rooms::SharedRooms, ScratchDir) {
+    let dir = ScratchDir::new();
+    let rooms = crate::rooms::Rooms::new(
+        &["default".to_string()],
+        dir.path(),
+        |_, dir| Ok(Arc::new(JsonStorage::new(dir)) as crate::storage::SharedStorage),
+        |_| {},
+    )
+    .unwrap();
+    (Arc::new(rooms), dir)
+}
+
+/// A course drawn as in `Course::from_ascii`, one row per line
+pub fn course(map: &str) -> crate::course::Course {
+    crate::course::Course::from_ascii("Test Course", 1, map).unwrap()
+}
+
+/// A running game on `map` with one player per name, seated in order. Spawn
+/// protection is off so scripted moves crash where they look like they do.
+pub fn game(map: &str, names: &[&str]) -> crate::game::Game {
+    game_with(map, names, crate::rules::Ruleset {
+        spawn_grace: Some(0),
+        ..crate::rules::Ruleset::default()
+    })
+}
+
+/// Like `game`, under the given rules
+pub fn game_with(map: &str, names: &[&str], rules: crate::rules::Ruleset) -> crate::game::Game {
+    let mut game = crate::game::Game::new(&course(map), rules).unwrap();
+    for name in names {
+        game.add_player(name.to_string()).unwrap();
+    }
+    game.start();
+    game
+}
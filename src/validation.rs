@@ -0,0 +1,224 @@
+use crate::bot::Difficulty;
+use crate::course::{all_courses, TagPreferences, KNOWN_TAGS};
+use uuid::Uuid;
+
+use crate::game::{Direction, LookFormat, Orientation, Steer, SteerAction};
+
+/// Longest accepted player name, in characters
+pub const MAX_NAME_LEN: usize = 32;
+/// Longest accepted admin token, in bytes
+pub const MAX_TOKEN_LEN: usize = 256;
+/// Length of a lobby code
+pub const LOBBY_CODE_LEN: usize = 6;
+
+/// Parameter structs that can check themselves before reaching the manager
+pub trait Validate {
+    fn validate(&self) -> Result<(), String>;
+}
+
+/// Validate a player name, returning it trimmed
+pub fn name(field: &str, value: &str) -> Result<String, String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(format!("'{}' cannot be empty.", field));
+    }
+    if trimmed.chars().count() > MAX_NAME_LEN {
+        return Err(format!(
+            "'{}' must be at most {} characters.",
+            field, MAX_NAME_LEN
+        ));
+    }
+    if !trimmed
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'))
+    {
+        return Err(format!(
+            "'{}' may only contain letters, digits, spaces, '-', '_' and '.'.",
+            field
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Parse a steering direction: a relative turn or a compass heading
+pub fn direction(field: &str, value: &str) -> Result<Steer, String> {
+    match value.trim().to_lowercase().as_str() {
+        "left" => Ok(Steer::Turn(SteerAction::Left)),
+        "right" => Ok(Steer::Turn(SteerAction::Right)),
+        "straight" => Ok(Steer::Turn(SteerAction::Straight)),
+        "north" => Ok(Steer::Toward(Direction::Up)),
+        "south" => Ok(Steer::Toward(Direction::Down)),
+        "east" => Ok(Steer::Toward(Direction::Right)),
+        "west" => Ok(Steer::Toward(Direction::Left)),
+        _ => Err(format!(
+            "'{}' must be 'left', 'right', 'straight', or a compass heading: 'north', 'south', 'east' or 'west'.",
+            field
+        )),
+    }
+}
+
+/// Parse a game id as listed by `/api/games`
+pub fn game_id(field: &str, value: &str) -> Result<Uuid, String> {
+    Uuid::parse_str(value.trim()).map_err(|_| format!("'{}' must be a game id as listed by /api/games.", field))
+}
+
+/// Longest chat message, in characters; longer ones are cut
+pub const MAX_CHAT_LEN: usize = 140;
+
+/// Tidy a chat message: line breaks and other control characters become
+/// spaces, and text past `MAX_CHAT_LEN` is cut. Returns the message and
+/// whether it was cut.
+pub fn chat_message(field: &str, value: &str) -> Result<(String, bool), String> {
+    let text: String = value.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(format!("'{}' cannot be empty.", field));
+    }
+    let cut = text.chars().count() > MAX_CHAT_LEN;
+    Ok((text.chars().take(MAX_CHAT_LEN).collect::<String>().trim_end().to_string(), cut))
+}
+
+/// Most moves one `steer` with a count or one `plan_moves` makes
+pub const MAX_PLANNED_MOVES: usize = 10;
+
+/// Check the number of cells a `steer` with a count covers
+pub fn move_count(field: &str, value: u32) -> Result<usize, String> {
+    match value as usize {
+        n @ 1..=MAX_PLANNED_MOVES => Ok(n),
+        _ => Err(format!("'{}' must be between 1 and {}.", field, MAX_PLANNED_MOVES)),
+    }
+}
+
+/// Parse the steers of a `plan_moves`, each a direction `steer` accepts
+pub fn moves(field: &str, values: &[String]) -> Result<Vec<Steer>, String> {
+    if values.is_empty() || values.len() > MAX_PLANNED_MOVES {
+        return Err(format!("'{}' must list 1-{} moves.", field, MAX_PLANNED_MOVES));
+    }
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| direction(&format!("{}[{}]", field, i), value))
+        .collect()
+}
+
+/// Most tags accepted in one preference list
+pub const MAX_TAGS: usize = 8;
+
+/// Validate a list of course tags, returning them lower-cased
+pub fn tags(field: &str, values: &[String]) -> Result<Vec<String>, String> {
+    if values.len() > MAX_TAGS {
+        return Err(format!("'{}' accepts at most {} tags.", field, MAX_TAGS));
+    }
+    values
+        .iter()
+        .map(|tag| {
+            let tag = tag.trim().to_lowercase();
+            if KNOWN_TAGS.contains(&tag.as_str()) {
+                Ok(tag)
+            } else {
+                Err(format!(
+                    "'{}' contains unknown tag '{}'; known tags: {}.",
+                    field,
+                    tag,
+                    KNOWN_TAGS.join(", ")
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Validate preferred and avoided course tags
+pub fn tag_preferences(prefer: Option<&[String]>, avoid: Option<&[String]>) -> Result<TagPreferences, String> {
+    Ok(TagPreferences {
+        prefer: tags("prefer_tags", prefer.unwrap_or_default())?,
+        avoid: tags("avoid_tags", avoid.unwrap_or_default())?,
+    })
+}
+
+/// Parse a look output format
+pub fn look_format(field: &str, value: &str) -> Result<LookFormat, String> {
+    LookFormat::parse(value).ok_or_else(|| format!("'{}' must be 'text', 'json' or 'compact'.", field))
+}
+
+/// Parse a look grid orientation
+pub fn look_orientation(field: &str, value: &str) -> Result<Orientation, String> {
+    Orientation::parse(value).ok_or_else(|| format!("'{}' must be 'world' or 'ego'.", field))
+}
+
+/// Parse a practice bot difficulty
+pub fn bot_difficulty(field: &str, value: &str) -> Result<Difficulty, String> {
+    Difficulty::parse(value).ok_or_else(|| {
+        format!(
+            "'{}' must be 'easy', 'medium', 'hard', or 'adaptive'.",
+            field
+        )
+    })
+}
+
+/// Validate a team number: 1 or 2
+pub fn team(field: &str, value: u32) -> Result<usize, String> {
+    match value {
+        1 | 2 => Ok(value as usize),
+        _ => Err(format!("'{}' must be 1 or 2.", field)),
+    }
+}
+
+/// Validate a lobby code, returning it normalized to upper case
+pub fn lobby_code(field: &str, value: &str) -> Result<String, String> {
+    let code = value.trim().to_uppercase();
+    if code.len() != LOBBY_CODE_LEN || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!(
+            "'{}' must be a {}-character lobby code.",
+            field, LOBBY_CODE_LEN
+        ));
+    }
+    Ok(code)
+}
+
+/// Validate a course level
+pub fn level(field: &str, value: u32) -> Result<(), String> {
+    let max_level = all_courses().len() as u32;
+    if !(1..=max_level).contains(&value) {
+        return Err(format!("'{}' must be between 1 and {}.", field, max_level));
+    }
+    Ok(())
+}
+
+/// Longest a wait_for_game_start call may block, in seconds
+pub const MAX_WAIT_SECS: u64 = 300;
+
+/// Validate a wait timeout in seconds
+pub fn wait_secs(field: &str, value: u64) -> Result<std::time::Duration, String> {
+    if !(1..=MAX_WAIT_SECS).contains(&value) {
+        return Err(format!("'{}' must be between 1 and {}.", field, MAX_WAIT_SECS));
+    }
+    Ok(std::time::Duration::from_secs(value))
+}
+
+/// Validate an opaque secret token
+pub fn token(field: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() || value.len() > MAX_TOKEN_LEN {
+        return Err(format!(
+            "'{}' must be between 1 and {} bytes.",
+            field, MAX_TOKEN_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a room name, returning it normalized to lower case
+pub fn room(field: &str, value: &str) -> Result<String, String> {
+    let room = value.trim().to_lowercase();
+    if room.is_empty()
+        || room.len() > MAX_NAME_LEN
+        || !room
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "'{}' must be 1-{} characters of a-z, 0-9, '-' or '_'.",
+            field, MAX_NAME_LEN
+        ));
+    }
+    Ok(room)
+}
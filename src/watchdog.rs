@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::broadcast::BroadcastEvent;
+use crate::rooms::SharedRooms;
+
+/// How often the server tick loop runs
+pub const TICK_INTERVAL: Duration = Duration::from_secs(1);
+/// A tick slower than this is logged with a per-room breakdown
+pub const TICK_SOFT_BUDGET: Duration = Duration::from_millis(250);
+/// No completed tick for this long marks the server degraded
+pub const HEARTBEAT_STALE: Duration = Duration::from_secs(5);
+
+/// Liveness of the tick loop, written by the loop and read by the watchdog
+/// and `/readyz`
+#[derive(Debug)]
+pub struct Heartbeat {
+    last_tick: Mutex<Instant>,
+    degraded: AtomicBool,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Heartbeat {
+            last_tick: Mutex::new(Instant::now()),
+            degraded: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Heartbeat {
+    fn beat(&self) {
+        *self.last_tick.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// Time since the last completed tick
+    pub fn age(&self) -> Duration {
+        self.last_tick.lock().unwrap_or_else(|e| e.into_inner()).elapsed()
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+}
+
+pub type SharedHeartbeat = Arc<Heartbeat>;
+
+/// Run housekeeping for every room once per `TICK_INTERVAL`, recording a
+/// heartbeat after each pass
+pub async fn run_tick_loop(rooms: SharedRooms, heartbeat: SharedHeartbeat, ct: CancellationToken) {
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = ct.cancelled() => return,
+        }
+        if let Some(warning) = tick_rooms(&rooms).await {
+            tracing::warn!("{}", warning);
+        }
+        heartbeat.beat();
+    }
+}
+
+/// One pass of the tick loop over every room; the warning to log, with a
+/// per-room breakdown, when it ran over `TICK_SOFT_BUDGET`
+async fn tick_rooms(rooms: &SharedRooms) -> Option<String> {
+    let started = Instant::now();
+    let mut breakdown = Vec::new();
+    for (name, manager) in rooms.iter() {
+        let room_started = Instant::now();
+        manager.lock().await.tick();
+        breakdown.push(format!("{}={:?}", name, room_started.elapsed()));
+    }
+    let took = started.elapsed();
+    (took > TICK_SOFT_BUDGET).then(|| {
+        format!(
+            "Tick took {:?}, over the {:?} budget ({})",
+            took,
+            TICK_SOFT_BUDGET,
+            breakdown.join(", ")
+        )
+    })
+}
+
+/// Watch the heartbeat: when it goes stale, mark the server degraded and tell
+/// every room; clear the flag again once ticks resume. Runs on its own thread
+/// with broadcast senders taken up front, so neither a blocked runtime worker
+/// nor a stalled room lock can silence it.
+pub fn run_watchdog(heartbeat: SharedHeartbeat, senders: Vec<broadcast::Sender<BroadcastEvent>>) {
+    loop {
+        std::thread::sleep(TICK_INTERVAL);
+        check(&heartbeat, &senders);
+    }
+}
+
+/// One look at the heartbeat, announcing a change between stalled and running
+fn check(heartbeat: &Heartbeat, senders: &[broadcast::Sender<BroadcastEvent>]) {
+    let age = heartbeat.age();
+    let stale = age > HEARTBEAT_STALE;
+    if stale == heartbeat.is_degraded() {
+        return;
+    }
+    heartbeat.degraded.store(stale, Ordering::Relaxed);
+
+    let event = if stale {
+        tracing::error!("Tick loop stalled: no tick completed for {:?}", age);
+        BroadcastEvent::ServerDegraded {
+            stalled_secs: age.as_secs(),
+        }
+    } else {
+        tracing::info!("Tick loop recovered");
+        BroadcastEvent::ServerRecovered
+    };
+    for tx in senders {
+        let _ = tx.send(event.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::AdminAuth;
+    use crate::testutil;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn a_tick_stuck_on_a_room_is_over_budget() {
+        let (rooms, _dir) = testutil::rooms();
+        assert_eq!(tick_rooms(&rooms).await, None);
+
+        // Something holds the room long enough to blow the budget
+        let held = rooms.default_room().clone().lock_owned().await;
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(TICK_SOFT_BUDGET + Duration::from_millis(50)).await;
+            drop(held);
+        });
+        let warning = tick_rooms(&rooms).await.expect("the slow tick is reported");
+        release.await.unwrap();
+
+        assert!(warning.starts_with("Tick took "), "{}", warning);
+        assert!(warning.contains("over the 250ms budget (default="), "{}", warning);
+    }
+
+    #[tokio::test]
+    async fn stale_heartbeat_fails_readiness_until_ticks_resume() {
+        let (rooms, _dir) = testutil::rooms();
+        let heartbeat = SharedHeartbeat::default();
+        let admin = Arc::new(AdminAuth::default());
+        let router = crate::web::create_router(rooms, admin, heartbeat.clone(), CancellationToken::new());
+        let readyz = || async {
+            let req = Request::builder().uri("/readyz").body(Body::empty()).unwrap();
+            router.clone().oneshot(req).await.unwrap().status()
+        };
+        let (tx, mut rx) = broadcast::channel(8);
+        let senders = [tx];
+
+        check(&heartbeat, &senders);
+        assert!(!heartbeat.is_degraded());
+        assert_eq!(readyz().await, StatusCode::OK);
+
+        // The last tick finished longer ago than the stall limit
+        *heartbeat.last_tick.lock().unwrap() -= HEARTBEAT_STALE + Duration::from_secs(1);
+        check(&heartbeat, &senders);
+        assert!(heartbeat.is_degraded());
+        assert_eq!(readyz().await, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(matches!(rx.try_recv(), Ok(BroadcastEvent::ServerDegraded { stalled_secs: 6 })));
+        // Said once, not on every look
+        check(&heartbeat, &senders);
+        assert!(rx.try_recv().is_err());
+
+        heartbeat.beat();
+        check(&heartbeat, &senders);
+        assert!(!heartbeat.is_degraded());
+        assert_eq!(readyz().await, StatusCode::OK);
+        assert!(matches!(rx.try_recv(), Ok(BroadcastEvent::ServerRecovered)));
+    }
+}
@@ -1,51 +1,426 @@
 use axum::{
-    extract::State,
+    extract::{ConnectInfo, Extension, Path, Query, Request, State},
+    middleware::{self, Next},
     response::{
-        sse::{Event, Sse},
+        sse::{Event, KeepAlive, Sse},
         Html, IntoResponse, Response,
     },
-    routing::get,
+    routing::{delete, get, post},
     Json, Router,
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
 };
 use rmcp::transport::streamable_http_server::{
-    session::local::LocalSessionManager,
+    session::local::{LocalSessionManager, SessionConfig},
     StreamableHttpServerConfig, StreamableHttpService,
 };
 use std::convert::Infallible;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
 
-use crate::manager::SharedGameManager;
+use crate::admin::{AuthError, Role, SharedAdminAuth};
+use crate::batch::BatchRequest;
+use crate::broadcast::BroadcastEvent;
+use crate::course::{self, CourseRegistry};
+use crate::error::{self, ErrorCode, TronError};
+use crate::lobby::WebLobbyState;
+use crate::manager::{leaderboard_checksum, LeaderboardSort, SharedGameManager};
 use crate::mcp::TronMcpHttpHandler;
+use crate::metrics;
+use crate::rooms::SharedRooms;
+use crate::storage::GameFilter;
+use crate::validation;
+use crate::watchdog::{SharedHeartbeat, HEARTBEAT_STALE};
+use crate::webhook::{Reach, WebhookRequest};
 
-pub fn create_router(manager: SharedGameManager, ct: CancellationToken) -> Router {
+/// Idle time after which an MCP HTTP session is closed and its player released
+const MCP_SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Finished games `/finished-games` returns when no `limit` is given
+const FINISHED_GAMES_DEFAULT_LIMIT: usize = 50;
+/// Most finished games one `/finished-games` request may ask for
+const FINISHED_GAMES_MAX_LIMIT: usize = 500;
+/// Comment sent on quiet event streams so proxies don't drop them
+const SSE_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// The web UI, the JSON and admin APIs, the event streams and the MCP HTTP
+/// endpoint (`/mcp`) for `rooms`, as one axum router. `heartbeat` is what
+/// the tick loop last reported, for `/health`; `ct` ends open event streams
+/// on shutdown.
+pub fn create_router(
+    rooms: SharedRooms,
+    admin: SharedAdminAuth,
+    heartbeat: SharedHeartbeat,
+    ct: CancellationToken,
+) -> Router {
     // Create the MCP streamable HTTP service
-    let mcp_manager = manager.clone();
+    let mcp_rooms = rooms.clone();
     let mcp_service = StreamableHttpService::new(
-        move || Ok(TronMcpHttpHandler::new(mcp_manager.clone())),
-        LocalSessionManager::default().into(),
+        move || Ok(TronMcpHttpHandler::new(mcp_rooms.clone())),
+        LocalSessionManager {
+            sessions: Default::default(),
+            session_config: SessionConfig {
+                keep_alive: Some(MCP_SESSION_IDLE_TIMEOUT),
+                ..Default::default()
+            },
+        }
+        .into(),
         StreamableHttpServerConfig {
             cancellation_token: ct.child_token(),
             ..Default::default()
         },
     );
 
-    Router::new()
+    // The un-prefixed API and join links alias the default room
+    let mut router = Router::new()
         .route("/", get(index_page))
         .route("/style.css", get(style_css))
         .route("/script.js", get(script_js))
         .route("/favicon.png", get(favicon))
-        .route("/api/games", get(get_games))
-        .route("/api/leaderboard", get(get_leaderboard))
-        .route("/api/stream", get(sse_handler))
+        .route("/api/rooms", get(get_rooms))
+        .route("/api/courses", get(get_courses))
+        .route("/api/errors", get(get_errors))
+        .route("/metrics", get(get_metrics))
+        .with_state(rooms.clone())
+        .route("/readyz", get(readyz).with_state(heartbeat))
+        .nest("/api", room_api_routes(rooms.default_room().clone(), &admin))
+        .nest("/join", join_routes(rooms.default_room().clone()));
+
+    for (name, manager) in rooms.iter() {
+        router = router
+            .nest(&format!("/api/rooms/{}", name), room_api_routes(manager.clone(), &admin))
+            .nest(&format!("/rooms/{}/join", name), join_routes(manager.clone()));
+    }
+
+    router
         .nest_service("/mcp", mcp_service)
-        .with_state(manager)
         .layer(CorsLayer::permissive())
 }
 
+/// API routes served for each room
+fn room_api_routes(manager: SharedGameManager, admin: &SharedAdminAuth) -> Router {
+    let require = |min: Role| {
+        middleware::from_fn_with_state((admin.clone(), min), require_role)
+    };
+    let viewer = Router::new()
+        .route("/admin/overview", get(admin_overview))
+        .route("/admin/leaderboard", get(admin_leaderboard))
+        .route_layer(require(Role::Viewer));
+    let operator = Router::new()
+        .route("/admin/players/{name}/kick", post(admin_kick_player))
+        .route("/admin/kick", post(admin_kick))
+        .route("/admin/games/{id}/finish", post(admin_finish_game))
+        .route("/admin/abort", post(admin_abort))
+        .route("/admin/clear-queue", post(admin_clear_queue))
+        .route("/admin/batch", post(admin_batch))
+        .route_layer(require(Role::Operator));
+    let owner = Router::new()
+        .route("/admin/leaderboard", delete(admin_reset_leaderboard))
+        .route("/admin/leaderboard/{name}", delete(admin_remove_leaderboard_entry))
+        .route("/admin/finished-games", delete(admin_purge_finished_games))
+        .route("/admin/state-export", get(admin_state_export))
+        .route_layer(require(Role::Owner));
+
+    let mut router = Router::new()
+        .route("/games", get(get_games))
+        .route("/games/{id}", get(get_game))
+        .route("/finished-games", get(get_finished_games))
+        .route("/leaderboard", get(get_leaderboard))
+        .route("/leaderboard/checksum", get(get_leaderboard_checksum))
+        .route("/lobbies", get(get_lobbies))
+        .route("/lobbies/{code}", get(get_lobby))
+        .route("/players/{name}", get(get_player_profile))
+        .route("/players/{name}/rating-history", get(get_rating_history))
+        .route("/players/{name}/webhook", post(set_webhook).delete(remove_webhook))
+        .route("/stream", get(sse_handler));
+    // Without a token nobody could pass the guard; leave the routes out
+    if admin.is_enabled() {
+        router = router.merge(viewer).merge(operator).merge(owner);
+    }
+    router.with_state(manager)
+}
+
+/// API error body: `{"error": {"code", "message", "retryable"}}`
+impl IntoResponse for TronError {
+    fn into_response(self) -> Response {
+        let status = match self.code {
+            ErrorCode::InvalidParams | ErrorCode::UnknownCommand => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden | ErrorCode::NotAuthorized => StatusCode::FORBIDDEN,
+            ErrorCode::RateLimited | ErrorCode::Cooldown => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::PlayerNotFound
+            | ErrorCode::GameNotFound
+            | ErrorCode::LobbyNotFound
+            | ErrorCode::RoomNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::CONFLICT,
+        };
+        (status, Json(serde_json::json!({ "error": self.to_json() }))).into_response()
+    }
+}
+
+/// Admin route guard: resolve the bearer token to a role, reject it if it is
+/// below `min`, and hand the role to the handler as an extension.
+async fn require_role(
+    State((admin, min)): State<(SharedAdminAuth, Role)>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+
+    match admin.authorize(ip, token, min) {
+        Ok(role) => {
+            req.extensions_mut().insert(role);
+            next.run(req).await
+        }
+        Err(AuthError::Missing) => TronError::new(ErrorCode::Unauthorized, "Admin token required").into_response(),
+        Err(AuthError::Invalid) => TronError::new(ErrorCode::Unauthorized, "Invalid admin token").into_response(),
+        Err(AuthError::RateLimited) => {
+            tracing::warn!("Admin auth rate limit hit for {}", ip);
+            TronError::new(ErrorCode::RateLimited, "Too many failed attempts, try again later").into_response()
+        }
+        Err(AuthError::Forbidden) => TronError::new(ErrorCode::Forbidden, "Token role not allowed here").into_response(),
+    }
+}
+
+fn join_routes(manager: SharedGameManager) -> Router {
+    Router::new()
+        .route("/{code}", get(join_page))
+        .with_state(manager)
+}
+
+async fn admin_overview(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+) -> impl IntoResponse {
+    let mut overview = manager.lock().await.admin_overview();
+    overview["role"] = serde_json::json!(role);
+    Json(overview)
+}
+
+/// The whole leaderboard; the public one only shows the top entries
+async fn admin_leaderboard(
+    State(manager): State<SharedGameManager>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Response {
+    let (sort, min_games) = match query.order() {
+        Ok(order) => order,
+        Err(e) => return e.into_response(),
+    };
+    let mgr = manager.lock().await;
+    let checksum = leaderboard_checksum(mgr.leaderboard.values());
+    ([("x-leaderboard-checksum", checksum)], Json(mgr.get_leaderboard_full(sort, min_games))).into_response()
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct KickRequest {
+    name: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct AbortRequest {
+    game_id: String,
+}
+
+async fn admin_kick(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+    Json(req): Json<KickRequest>,
+) -> Response {
+    admin_kick_player(State(manager), Extension(role), Path(req.name)).await
+}
+
+async fn admin_abort(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+    Json(req): Json<AbortRequest>,
+) -> Response {
+    admin_finish_game(State(manager), Extension(role), Path(req.game_id)).await
+}
+
+async fn admin_clear_queue(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+) -> impl IntoResponse {
+    let mut mgr = manager.lock().await;
+    let msg = mgr.clear_queue();
+    tracing::warn!("Admin ({:?}) cleared the queue of room {}: {}", role, mgr.room, msg);
+    msg
+}
+
+async fn admin_remove_leaderboard_entry(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+    Path(name): Path<String>,
+) -> Response {
+    let mut mgr = manager.lock().await;
+    match mgr.remove_leaderboard_entry(&name) {
+        Ok(msg) => {
+            tracing::warn!("Admin ({:?}) removed the leaderboard entry of '{}' in room {}", role, name, mgr.room);
+            msg.into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn admin_kick_player(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+    Path(name): Path<String>,
+) -> Response {
+    let mut mgr = manager.lock().await;
+    match mgr.kick_player(&name) {
+        Ok(msg) => {
+            tracing::info!("Admin ({:?}) kicked '{}' in room {}", role, name, mgr.room);
+            msg.into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn admin_finish_game(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+    Path(id): Path<String>,
+) -> Response {
+    let Ok(game_id) = uuid::Uuid::parse_str(&id) else {
+        return TronError::new(ErrorCode::InvalidParams, "Invalid game id").into_response();
+    };
+    let mut mgr = manager.lock().await;
+    match mgr.force_finish_game(game_id) {
+        Ok(msg) => {
+            tracing::info!("Admin ({:?}) force-finished game {} in room {}", role, game_id, mgr.room);
+            msg.into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn admin_batch(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+    Json(req): Json<BatchRequest>,
+) -> Response {
+    let mut mgr = manager.lock().await;
+    let steps = req.steps.len();
+    match mgr.run_batch(req.steps) {
+        Ok(report) => {
+            tracing::info!("Admin ({:?}) ran a {}-step batch in room {}", role, steps, mgr.room);
+            Json(report).into_response()
+        }
+        Err(failure) => (StatusCode::UNPROCESSABLE_ENTITY, Json(failure)).into_response(),
+    }
+}
+
+async fn admin_reset_leaderboard(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+) -> impl IntoResponse {
+    let mut mgr = manager.lock().await;
+    mgr.reset_leaderboard();
+    tracing::warn!("Admin ({:?}) reset the leaderboard in room {}", role, mgr.room);
+    "Leaderboard reset."
+}
+
+async fn admin_purge_finished_games(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+) -> impl IntoResponse {
+    let mut mgr = manager.lock().await;
+    mgr.purge_finished_games();
+    tracing::warn!("Admin ({:?}) purged finished games in room {}", role, mgr.room);
+    "Finished games purged."
+}
+
+async fn admin_state_export(
+    State(manager): State<SharedGameManager>,
+    Extension(role): Extension<Role>,
+) -> impl IntoResponse {
+    // Commands wait on the lock only while the state is copied
+    let (snapshot, room) = {
+        let mgr = manager.lock().await;
+        (mgr.export_state(), mgr.room.clone())
+    };
+    tracing::warn!(
+        "Admin ({:?}) exported room {} ({} active games)",
+        role,
+        room,
+        snapshot.active_games.len()
+    );
+    Json(snapshot)
+}
+
+async fn get_rooms(State(rooms): State<SharedRooms>) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "default": rooms.default_name(),
+        "rooms": rooms.names(),
+    }))
+}
+
+/// Readiness probe: fails while the watchdog reports the tick loop stalled
+async fn readyz(State(heartbeat): State<SharedHeartbeat>) -> impl IntoResponse {
+    if heartbeat.is_degraded() {
+        let body = format!(
+            "degraded: no tick for {}s (limit {}s)",
+            heartbeat.age().as_secs(),
+            HEARTBEAT_STALE.as_secs()
+        );
+        (StatusCode::SERVICE_UNAVAILABLE, body)
+    } else {
+        (StatusCode::OK, "ready".to_string())
+    }
+}
+
+/// Prometheus scrape target covering every room
+async fn get_metrics(State(rooms): State<SharedRooms>) -> impl IntoResponse {
+    let mut samples = Vec::new();
+    for (_, manager) in rooms.iter() {
+        samples.push(manager.lock().await.metrics_sample());
+    }
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics::METRICS.render(&samples),
+    )
+}
+
+/// Every error code with its retryability, for client retry logic
+async fn get_errors() -> impl IntoResponse {
+    Json(error::catalog())
+}
+
+async fn get_courses() -> impl IntoResponse {
+    let courses: Vec<serde_json::Value> = CourseRegistry::current()
+        .list()
+        .iter()
+        .map(|(c, file)| {
+            serde_json::json!({
+                "name": c.name,
+                "slug": course::slug(&c.name),
+                "level": c.level,
+                "width": c.width,
+                "height": c.height,
+                "max_players": c.max_players,
+                "tags": c.tags_with(&c.default_rules()),
+                "source": file.as_deref().unwrap_or("built-in"),
+                "preview": c.render_ascii(),
+            })
+        })
+        .collect();
+    Json(courses)
+}
+
 async fn index_page() -> Html<&'static str> {
     Html(include_str!("../static/index.html"))
 }
@@ -83,19 +458,826 @@ async fn get_games(State(manager): State<SharedGameManager>) -> impl IntoRespons
     }))
 }
 
-async fn get_leaderboard(State(manager): State<SharedGameManager>) -> impl IntoResponse {
+#[derive(Debug, serde::Deserialize)]
+struct GameQuery {
+    /// `false` leaves out the grid, which is most of the payload
+    include_grid: Option<bool>,
+}
+
+/// One game, running or finished, so a client can refresh it alone
+async fn get_game(
+    State(manager): State<SharedGameManager>,
+    Path(id): Path<String>,
+    Query(query): Query<GameQuery>,
+) -> Response {
+    let Ok(game_id) = uuid::Uuid::parse_str(&id) else {
+        return TronError::new(ErrorCode::InvalidParams, "Invalid game id").into_response();
+    };
+    let Some(game) = manager.lock().await.get_game(game_id) else {
+        return TronError::new(ErrorCode::GameNotFound, "Game not found").into_response();
+    };
+    let mut body = serde_json::json!(game);
+    if query.include_grid == Some(false)
+        && let Some(fields) = body.as_object_mut()
+    {
+        fields.remove("grid");
+    }
+    Json(body).into_response()
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct FinishedGamesQuery {
+    player: Option<String>,
+    level: Option<u32>,
+    limit: Option<usize>,
+}
+
+/// Search the whole finished-game archive, newest first. Runs against the
+/// storage backend, not the manager's recent games, and off the manager lock.
+async fn get_finished_games(
+    State(manager): State<SharedGameManager>,
+    Query(query): Query<FinishedGamesQuery>,
+) -> Response {
+    let limit = query.limit.unwrap_or(FINISHED_GAMES_DEFAULT_LIMIT);
+    if !(1..=FINISHED_GAMES_MAX_LIMIT).contains(&limit) {
+        return TronError::new(
+            ErrorCode::InvalidParams,
+            format!("'limit' must be between 1 and {}.", FINISHED_GAMES_MAX_LIMIT),
+        )
+        .into_response();
+    }
+    let player = match query.player.as_deref().map(|name| validation::name("player", name)).transpose() {
+        Ok(player) => player,
+        Err(e) => return TronError::from(e).into_response(),
+    };
+    let filter = GameFilter {
+        player,
+        level: query.level,
+        limit,
+    };
+    let storage = manager.lock().await.storage();
+    match tokio::task::spawn_blocking(move || storage.query_finished_games(&filter)).await {
+        Ok(games) => Json(games).into_response(),
+        Err(e) => TronError::new(ErrorCode::Internal, format!("Query failed: {}", e)).into_response(),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LeaderboardQuery {
+    /// `points` (default), `rating` or `win_rate`
+    sort: Option<String>,
+    /// Leave out players with fewer games; defaults per sort order
+    min_games: Option<u32>,
+    /// Standings on this level alone
+    level: Option<u32>,
+}
+
+impl LeaderboardQuery {
+    fn order(&self) -> Result<(LeaderboardSort, u32), TronError> {
+        let sort = match &self.sort {
+            Some(sort) => LeaderboardSort::parse(sort).map_err(|e| format!("'sort': {}", e))?,
+            None => LeaderboardSort::default(),
+        };
+        Ok((sort, self.min_games.unwrap_or(sort.default_min_games())))
+    }
+}
+
+async fn get_leaderboard(
+    State(manager): State<SharedGameManager>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Response {
+    let (sort, min_games) = match query.order() {
+        Ok(order) => order,
+        Err(e) => return e.into_response(),
+    };
     let mgr = manager.lock().await;
-    let leaderboard = mgr.get_leaderboard();
-    Json(leaderboard)
+    let checksum = leaderboard_checksum(mgr.leaderboard.values());
+    match query.level {
+        Some(level) => match mgr.get_level_leaderboard(level, sort, min_games) {
+            Ok(standings) => ([("x-leaderboard-checksum", checksum)], Json(standings)).into_response(),
+            Err(e) => e.into_response(),
+        },
+        None => ([("x-leaderboard-checksum", checksum)], Json(mgr.get_leaderboard(sort, min_games))).into_response(),
+    }
+}
+
+/// Checksum of the whole leaderboard and how many entries it covers, for
+/// mirrors that poll for changes
+async fn get_leaderboard_checksum(State(manager): State<SharedGameManager>) -> impl IntoResponse {
+    let mgr = manager.lock().await;
+    Json(serde_json::json!({
+        "checksum": leaderboard_checksum(mgr.leaderboard.values()),
+        "entries": mgr.leaderboard.len(),
+    }))
+}
+
+/// A player's standing, current activity and latest games. The name is
+/// percent-decoded, so `GPT%20Bot` finds "GPT Bot".
+async fn get_player_profile(State(manager): State<SharedGameManager>, Path(name): Path<String>) -> Response {
+    let name = match validation::name("name", &name) {
+        Ok(name) => name,
+        Err(e) => return TronError::from(e).into_response(),
+    };
+    match crate::manager::player_profile(&manager, &name).await {
+        Ok(profile) => Json(profile).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_rating_history(
+    State(manager): State<SharedGameManager>,
+    Path(name): Path<String>,
+) -> Response {
+    match manager.lock().await.rating_history(&name) {
+        Some(view) => Json(view).into_response(),
+        None => TronError::new(ErrorCode::PlayerNotFound, "No rating history for this player").into_response(),
+    }
 }
 
+async fn set_webhook(
+    State(manager): State<SharedGameManager>,
+    Path(name): Path<String>,
+    Json(req): Json<WebhookRequest>,
+) -> Response {
+    // Resolved before taking the lock; delivery checks the address again
+    let reach = if manager.lock().await.allow_private_webhooks { Reach::Any } else { Reach::Public };
+    if let Err(e) = crate::webhook::resolve(&req.url, reach).await {
+        return TronError::new(ErrorCode::InvalidParams, e).into_response();
+    }
+    match manager.lock().await.set_webhook(&name, req) {
+        Ok(msg) => msg.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// The secret comes in the `X-Webhook-Secret` header
+async fn remove_webhook(
+    State(manager): State<SharedGameManager>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let secret = headers
+        .get("x-webhook-secret")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    match manager.lock().await.remove_webhook(&name, secret) {
+        Ok(msg) => msg.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_lobbies(State(manager): State<SharedGameManager>) -> impl IntoResponse {
+    let mgr = manager.lock().await;
+    Json(mgr.get_public_lobbies())
+}
+
+async fn get_lobby(
+    State(manager): State<SharedGameManager>,
+    Path(code): Path<String>,
+) -> Response {
+    let mgr = manager.lock().await;
+    match mgr.get_lobby(&code) {
+        Some(lobby) => Json(lobby).into_response(),
+        None => TronError::new(ErrorCode::LobbyNotFound, "Lobby not found").into_response(),
+    }
+}
+
+/// Shareable join link: an HTML page for people, JSON for clients that ask for it
+async fn join_page(
+    State(manager): State<SharedGameManager>,
+    Path(code): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let lobby = manager.lock().await.get_lobby(&code);
+    let wants_json = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("application/json"));
+
+    match (lobby, wants_json) {
+        (Some(lobby), true) => Json(lobby).into_response(),
+        (None, true) => TronError::new(ErrorCode::LobbyNotFound, "Lobby not found").into_response(),
+        (Some(lobby), false) => Html(render_join_page(&lobby, &headers)).into_response(),
+        (None, false) => (
+            StatusCode::NOT_FOUND,
+            Html(format!(
+                "<!DOCTYPE html><html><head><link rel=\"stylesheet\" href=\"/style.css\"></head><body><div class=\"header\"><h1>⟐ TRON MCP ⟐</h1><p>Lobby {} not found.</p></div></body></html>",
+                escape_html(&code)
+            )),
+        )
+            .into_response(),
+    }
+}
+
+fn render_join_page(lobby: &WebLobbyState, headers: &HeaderMap) -> String {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("localhost:3000");
+    let players = if lobby.waiting_players.is_empty() && lobby.bots.is_empty() {
+        "<li>Nobody yet — be the first!</li>".to_string()
+    } else {
+        lobby
+            .waiting_players
+            .iter()
+            .map(|p| format!("<li>{}</li>", escape_html(p)))
+            .chain(lobby.bots.iter().map(|b| format!("<li>🤖 {}</li>", escape_html(b))))
+            .collect()
+    };
+    let rules = if lobby.rules_diff.is_empty() {
+        "<li>Course defaults</li>".to_string()
+    } else {
+        lobby
+            .rules_diff
+            .iter()
+            .map(|r| format!("<li>{}</li>", escape_html(r)))
+            .collect()
+    };
+    let ranked = if lobby.unranked { "Unranked" } else { "Ranked" };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>TRON MCP — Lobby {code}</title>
+<link rel="stylesheet" type="text/css" href="/style.css">
+<link rel="icon" type="image/png" href="/favicon.png">
+</head>
+<body>
+<div class="header">
+  <h1>⟐ LOBBY {code} ⟐</h1>
+  <p>{course} (Lv.{level}) · {ranked}</p>
+  <p>{tags}</p>
+</div>
+<div class="container">
+  <div class="card">
+    <h2><span class="dot live"></span> WAITING PLAYERS</h2>
+    <div class="howto"><ol>{players}</ol></div>
+    <h2>⚙ RULES</h2>
+    <div class="howto"><ol>{rules}</ol></div>
+  </div>
+  <div class="card">
+    <h2>🎮 HOW TO JOIN</h2>
+    <div class="howto">
+      <p>Add the server to your LLM's MCP config:</p>
+<pre class="config">{{
+  "mcpServers": {{
+    "tron": {{
+      "url": "http://{host}/mcp"
+    }}
+  }}
+}}</pre>
+      <p>Then tell your LLM:</p>
+<pre class="config">Join the Tron game as [YourName] with join_game(name, lobby="{code}", room="{room}").
+Then play in a loop: look(), pick a safe direction, steer().</pre>
+      <p>Over the raw TCP protocol: <code>ROOM {room}</code> then <code>LOBBY JOIN {code} YourName</code></p>
+    </div>
+  </div>
+</div>
+</body>
+</html>"#,
+        code = escape_html(&lobby.code),
+        room = escape_html(&lobby.room),
+        course = escape_html(&lobby.course_name),
+        level = lobby.course_level,
+        tags = escape_html(&lobby.tags.join(" · ")),
+        host = escape_html(host),
+    )
+}
+
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct StreamQuery {
+    /// Only send messages about this game
+    game_id: Option<String>,
+}
+
+/// Web event stream: a `snapshot` of the running games, then every
+/// broadcast message as it happens
 async fn sse_handler(
     State(manager): State<SharedGameManager>,
-) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
-    let rx = manager.lock().await.broadcast_tx.subscribe();
-    let stream = BroadcastStream::new(rx).filter_map(|msg| match msg {
-        Ok(data) => Some(Ok(Event::default().data(data))),
-        Err(_) => None,
+    Query(query): Query<StreamQuery>,
+) -> Response {
+    let only = match query.game_id.as_deref().map(uuid::Uuid::parse_str) {
+        None => None,
+        Some(Ok(id)) => Some(id.to_string()),
+        Some(Err(_)) => return TronError::new(ErrorCode::InvalidParams, "Invalid game id").into_response(),
+    };
+    // Subscribe under the same lock as the snapshot so no update falls between them
+    let (rx, games) = {
+        let mgr = manager.lock().await;
+        (mgr.subscribe(), mgr.get_active_games())
+    };
+    let games: Vec<_> = games
+        .into_iter()
+        .filter(|g| only.as_ref().is_none_or(|id| &g.id == id))
+        .collect();
+    let snapshot = BroadcastEvent::Snapshot { games }.to_json();
+
+    let updates = BroadcastStream::new(rx).filter_map(move |event| {
+        let event = event.ok()?;
+        if only.is_some() && event.game_id() != only.as_deref() && !event.is_server_wide() {
+            return None;
+        }
+        Some(Ok::<_, Infallible>(Event::default().data(event.to_json())))
     });
+    let stream = tokio_stream::once(Ok(Event::default().data(snapshot))).chain(updates);
     Sse::new(stream)
-}
\ No newline at end of file
+        .keep_alive(KeepAlive::new().interval(SSE_KEEP_ALIVE))
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::AdminAuth;
+    use crate::testutil;
+    use axum::body::Body;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn router(rooms: SharedRooms, admin: AdminAuth) -> Router {
+        create_router(rooms, Arc::new(admin), Default::default(), CancellationToken::new())
+    }
+
+    /// Send one request, bearing `token` when given; the status and body text
+    async fn send(router: &Router, method: &str, path: &str, token: Option<&str>, body: &str) -> (StatusCode, String) {
+        let mut req = axum::http::Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let res = router
+            .clone()
+            .oneshot(req.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = res.status();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    #[tokio::test]
+    async fn each_role_reaches_only_its_class_of_endpoint() {
+        let (rooms, _dir) = testutil::rooms();
+        let router = router(
+            rooms,
+            AdminAuth::new(vec![
+                ("viewer-token".to_string(), Role::Viewer),
+                ("operator-token".to_string(), Role::Operator),
+                ("owner-token".to_string(), Role::Owner),
+            ]),
+        );
+        let game_id = uuid::Uuid::nil();
+        let endpoints = [
+            (Role::Viewer, "GET", "/api/admin/overview".to_string(), ""),
+            (Role::Viewer, "GET", "/api/admin/leaderboard".to_string(), ""),
+            (Role::Viewer, "GET", "/api/rooms/default/admin/overview".to_string(), ""),
+            (Role::Operator, "POST", "/api/admin/players/Claude/kick".to_string(), ""),
+            (Role::Operator, "POST", "/api/admin/kick".to_string(), r#"{"name": "Claude"}"#),
+            (Role::Operator, "POST", format!("/api/admin/games/{}/finish", game_id), ""),
+            (Role::Operator, "POST", "/api/admin/abort".to_string(), &format!(r#"{{"game_id": "{}"}}"#, game_id)),
+            (Role::Operator, "POST", "/api/admin/clear-queue".to_string(), ""),
+            (Role::Operator, "POST", "/api/admin/batch".to_string(), r#"{"steps": []}"#),
+            (Role::Owner, "DELETE", "/api/admin/leaderboard".to_string(), ""),
+            (Role::Owner, "DELETE", "/api/admin/leaderboard/Claude".to_string(), ""),
+            (Role::Owner, "DELETE", "/api/admin/finished-games".to_string(), ""),
+            (Role::Owner, "GET", "/api/admin/state-export".to_string(), ""),
+        ];
+        let callers = [
+            (Role::Viewer, "viewer-token"),
+            (Role::Operator, "operator-token"),
+            (Role::Owner, "owner-token"),
+        ];
+
+        for (min, method, path, body) in &endpoints {
+            let (status, _) = send(&router, method, path, None, body).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{} {} without a token", method, path);
+            for (role, token) in callers {
+                let (status, text) = send(&router, method, path, Some(token), body).await;
+                if role >= *min {
+                    // Past the guard the handler answers, even if only to say
+                    // there is no such player or game
+                    assert!(
+                        status != StatusCode::UNAUTHORIZED && status != StatusCode::FORBIDDEN,
+                        "{:?} on {} {}: {} {}",
+                        role,
+                        method,
+                        path,
+                        status,
+                        text
+                    );
+                } else {
+                    assert_eq!(status, StatusCode::FORBIDDEN, "{:?} on {} {}", role, method, path);
+                    assert!(text.contains("FORBIDDEN"), "{}", text);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn overview_reports_the_callers_role() {
+        let (rooms, _dir) = testutil::rooms();
+        let router = router(rooms, AdminAuth::new(vec![("viewer-token".to_string(), Role::Viewer)]));
+
+        let (status, text) = send(&router, "GET", "/api/admin/overview", Some("viewer-token"), "").await;
+
+        assert_eq!(status, StatusCode::OK);
+        let overview: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(overview["role"], "viewer");
+    }
+
+    #[tokio::test]
+    async fn wrong_tokens_are_locked_out() {
+        let (rooms, _dir) = testutil::rooms();
+        let router = router(rooms, AdminAuth::new(vec![("owner-token".to_string(), Role::Owner)]));
+
+        for _ in 0..5 {
+            let (status, _) = send(&router, "GET", "/api/admin/overview", Some("guess"), "").await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        // Even the right token waits out the lockout
+        let (status, _) = send(&router, "GET", "/api/admin/overview", Some("owner-token"), "").await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn admin_actions_change_the_room() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let router = router(rooms, AdminAuth::new(vec![("owner-token".to_string(), Role::Owner)]));
+        let (claude_game, grok_game, mut events) = {
+            let mut mgr = manager.lock().await;
+            for name in ["Claude", "Gemini", "Grok", "Llama", "Mistral"] {
+                mgr.join(name.to_string(), Default::default()).unwrap();
+            }
+            mgr.move_player("Grok", crate::game::Steer::Turn(crate::game::SteerAction::Straight))
+                .unwrap();
+            let game_of = |name: &str| mgr.player_sessions[name].game_id.unwrap();
+            (game_of("Claude"), game_of("Grok"), mgr.subscribe())
+        };
+
+        // Turned away without a token, the room untouched
+        let (status, _) = send(&router, "POST", "/api/admin/clear-queue", None, "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(manager.lock().await.waiting_players, ["Mistral"]);
+
+        let body = format!(r#"{{"game_id": "{}"}}"#, claude_game);
+        let (status, text) = send(&router, "POST", "/api/admin/abort", Some("owner-token"), &body).await;
+        assert_eq!(status, StatusCode::OK, "{}", text);
+        {
+            let mgr = manager.lock().await;
+            assert!(!mgr.active_games.contains_key(&claude_game));
+            let finished = mgr.get_finished_games();
+            let game = finished.iter().find(|g| g.id == claude_game.to_string()).unwrap();
+            assert_eq!(game.winner, None);
+        }
+
+        let (status, text) = send(&router, "POST", "/api/admin/kick", Some("owner-token"), r#"{"name": "Llama"}"#).await;
+        assert_eq!(status, StatusCode::OK, "{}", text);
+        {
+            let mgr = manager.lock().await;
+            assert!(!mgr.player_sessions.contains_key("Llama"));
+            assert!(!mgr.active_games.contains_key(&grok_game));
+            let finished = mgr.get_finished_games();
+            let game = finished.iter().find(|g| g.id == grok_game.to_string()).unwrap();
+            assert_eq!(game.winner.map(|i| game.players[i].name.as_str()), Some("Grok"));
+        }
+        let (status, _) = send(&router, "POST", "/api/admin/kick", Some("owner-token"), r#"{"name": "Llama"}"#).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, text) = send(&router, "POST", "/api/admin/clear-queue", Some("owner-token"), "").await;
+        assert_eq!((status, text.as_str()), (StatusCode::OK, "Removed 1 players from the public queue."));
+        assert!(manager.lock().await.waiting_players.is_empty());
+
+        assert!(manager.lock().await.leaderboard.contains_key("Grok"));
+        let (status, text) = send(&router, "DELETE", "/api/admin/leaderboard/Grok", Some("owner-token"), "").await;
+        assert_eq!(status, StatusCode::OK, "{}", text);
+        assert!(!manager.lock().await.leaderboard.contains_key("Grok"));
+        let (status, _) = send(&router, "DELETE", "/api/admin/leaderboard/Grok", Some("owner-token"), "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let mut actions = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let BroadcastEvent::AdminAction { action, target } = event {
+                actions.push((action, target));
+            }
+        }
+        let expected = [
+            ("abort", claude_game.to_string()),
+            ("kick", "Llama".to_string()),
+            ("clear_queue", "queue".to_string()),
+            ("remove_entry", "Grok".to_string()),
+        ];
+        assert_eq!(actions, expected.map(|(a, t)| (a.to_string(), t)));
+    }
+
+    #[tokio::test]
+    async fn admin_routes_are_absent_without_tokens() {
+        let (rooms, _dir) = testutil::rooms();
+        let router = router(rooms, AdminAuth::default());
+
+        let (status, _) = send(&router, "GET", "/api/admin/overview", Some("anything"), "").await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn metrics_are_scraped_per_room() {
+        // The metrics are process-wide; a room of its own keeps other tests'
+        // games out of the counts
+        let dir = testutil::ScratchDir::new();
+        let rooms = crate::rooms::Rooms::new(
+            &["metrics-main".to_string(), "metrics-quiet".to_string()],
+            dir.path(),
+            |_, dir| Ok(Arc::new(crate::storage::JsonStorage::new(dir)) as crate::storage::SharedStorage),
+            |_| {},
+        )
+        .unwrap();
+        let rooms = Arc::new(rooms);
+        let router = router(rooms.clone(), AdminAuth::new(vec![("owner-token".to_string(), Role::Owner)]));
+        {
+            let mut mgr = rooms.default_room().lock().await;
+            mgr.join("Claude".to_string(), Default::default()).unwrap();
+            mgr.join("Gemini".to_string(), Default::default()).unwrap();
+            mgr.move_player("Claude", crate::game::Steer::Turn(crate::game::SteerAction::Straight))
+                .unwrap();
+            mgr.leave("Gemini").unwrap();
+            mgr.join("Grok".to_string(), Default::default()).unwrap();
+        }
+        let (status, _) = send(&router, "GET", "/api/admin/overview", Some("a-wrong-token"), "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, text) = send(&router, "GET", "/metrics", None, "").await;
+
+        assert_eq!(status, StatusCode::OK);
+        let value = |series: &str| -> f64 {
+            text.lines()
+                .find_map(|l| l.strip_prefix(series)?.strip_prefix(' ')?.parse().ok())
+                .unwrap_or_else(|| panic!("no {} in\n{}", series, text))
+        };
+        assert!(value("admin_auth_failures_total") >= 1.0);
+        for (series, expected) in [
+            ("games_started_total{room=\"metrics-main\"}", 1.0),
+            ("games_finished_total{room=\"metrics-main\"}", 1.0),
+            ("players_joined_total{room=\"metrics-main\"}", 3.0),
+            ("moves_total{room=\"metrics-main\"}", 1.0),
+            ("active_games{room=\"metrics-main\"}", 0.0),
+            ("waiting_players{room=\"metrics-main\"}", 1.0),
+            ("game_duration_ticks_count{room=\"metrics-main\"}", 1.0),
+            // A room nothing happened in yet still shows up, at zero
+            ("games_started_total{room=\"metrics-quiet\"}", 0.0),
+            ("waiting_players{room=\"metrics-quiet\"}", 0.0),
+            ("game_duration_ticks_count{room=\"metrics-quiet\"}", 0.0),
+        ] {
+            assert_eq!(value(series), expected, "{}", series);
+        }
+        assert_eq!(value("crashes_total{cause=\"forfeit\",room=\"metrics-main\"}"), 1.0);
+        assert!(text.contains("# TYPE game_duration_ticks histogram"));
+    }
+
+    #[tokio::test]
+    async fn profiles_are_found_by_names_with_spaces() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let router = router(rooms, AdminAuth::default());
+        {
+            let mut mgr = manager.lock().await;
+            mgr.join("GPT Bot".to_string(), Default::default()).unwrap();
+            mgr.join("Claude".to_string(), Default::default()).unwrap();
+            mgr.move_player("GPT Bot", crate::game::Steer::Turn(crate::game::SteerAction::Straight))
+                .unwrap();
+            mgr.leave("Claude").unwrap();
+            mgr.join("GPT Bot".to_string(), Default::default()).unwrap();
+        }
+
+        let (status, text) = send(&router, "GET", "/api/players/GPT%20Bot", None, "").await;
+
+        assert_eq!(status, StatusCode::OK, "{}", text);
+        let profile: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(profile["name"], "GPT Bot");
+        assert_eq!(profile["leaderboard"]["wins"], 1);
+        assert_eq!(profile["activity"]["state"], "queued");
+        let games = profile["recent_games"].as_array().unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0]["result"], "win");
+        assert_eq!(games[0]["opponents"], serde_json::json!(["Claude"]));
+
+        let (status, text) = send(&router, "GET", "/api/players/Claude", None, "").await;
+        assert_eq!(status, StatusCode::OK);
+        let profile: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(profile["activity"]["state"], "idle");
+        assert_eq!(profile["recent_games"][0]["result"], "loss");
+
+        // Never seen, and only part of a name that was
+        for path in ["/api/players/Nobody", "/api/players/GPT"] {
+            let (status, text) = send(&router, "GET", path, None, "").await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", path);
+            assert!(text.contains("PLAYER_NOT_FOUND"), "{}", text);
+        }
+    }
+
+    #[tokio::test]
+    async fn only_public_lobbies_are_listed_but_any_code_opens_its_page() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let router = router(rooms, AdminAuth::default());
+        let (public, private) = {
+            let mut mgr = manager.lock().await;
+            let trails_forever = crate::rules::RulesOverride {
+                max_trail_length: Some(0),
+                ..Default::default()
+            };
+            mgr.create_lobby(Some(2), Some(trails_forever), None, true, &Default::default()).unwrap();
+            mgr.create_lobby(None, None, None, false, &Default::default()).unwrap();
+            let code = |public: bool| mgr.lobbies.values().find(|l| l.public == public).unwrap().code.clone();
+            let (public, private) = (code(true), code(false));
+            mgr.join_lobby(&private, "Claude".to_string(), None).unwrap();
+            (public, private)
+        };
+
+        let (status, text) = send(&router, "GET", "/api/lobbies", None, "").await;
+        assert_eq!(status, StatusCode::OK);
+        let listed: Vec<WebLobbyState> = serde_json::from_str(&text).unwrap();
+        assert_eq!(listed.iter().map(|l| l.code.as_str()).collect::<Vec<_>>(), [public.as_str()]);
+        assert_eq!(listed[0].course_level, 2);
+        assert!(listed[0].unranked);
+        assert!(!listed[0].rules_diff.is_empty());
+
+        // Codes are case-insensitive, and private lobbies answer to theirs
+        let (status, text) = send(&router, "GET", &format!("/api/lobbies/{}", private.to_lowercase()), None, "").await;
+        assert_eq!(status, StatusCode::OK);
+        let lobby: WebLobbyState = serde_json::from_str(&text).unwrap();
+        assert!(!lobby.public);
+        assert_eq!(lobby.waiting_players, ["Claude"]);
+        let (status, _) = send(&router, "GET", "/api/lobbies/NOPE99", None, "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // The join link serves the same data to clients, and a page to people
+        let req = axum::http::Request::builder()
+            .uri(format!("/join/{}", private))
+            .header(header::ACCEPT, "application/json")
+            .body(Body::empty())
+            .unwrap();
+        let res = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let served: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let expected = serde_json::to_value(manager.lock().await.get_lobby(&private).unwrap()).unwrap();
+        assert_eq!(served, expected);
+
+        let (status, page) = send(&router, "GET", &format!("/join/{}", private), None, "").await;
+        assert_eq!(status, StatusCode::OK);
+        let course = expected["course_name"].as_str().unwrap();
+        for text in [format!("LOBBY {}", private), "<li>Claude</li>".to_string(), escape_html(course)] {
+            assert!(page.contains(&text), "no {} in\n{}", text, page);
+        }
+        let (status, _) = send(&router, "GET", "/join/NOPE99", None, "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn closed_event_streams_release_their_receivers() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let router = router(rooms, AdminAuth::default());
+        let receivers = || async { manager.lock().await.broadcast_tx.receiver_count() };
+        let baseline = receivers().await;
+
+        let mut streams = Vec::new();
+        for _ in 0..5 {
+            let req = axum::http::Request::builder().uri("/api/stream").body(Body::empty()).unwrap();
+            let res = router.clone().oneshot(req).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            streams.push(res);
+        }
+        assert_eq!(receivers().await, baseline + 5);
+
+        drop(streams);
+        assert_eq!(receivers().await, baseline);
+    }
+
+    #[tokio::test]
+    async fn single_games_are_found_running_or_finished() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let router = router(rooms, AdminAuth::default());
+        let (finished, running) = {
+            let mut mgr = manager.lock().await;
+            mgr.join("Claude".to_string(), Default::default()).unwrap();
+            mgr.join("Gemini".to_string(), Default::default()).unwrap();
+            let finished = *mgr.active_games.keys().next().unwrap();
+            mgr.move_player("Claude", crate::game::Steer::Turn(crate::game::SteerAction::Straight))
+                .unwrap();
+            mgr.leave("Gemini").unwrap();
+            mgr.join("Grok".to_string(), Default::default()).unwrap();
+            mgr.join("Llama".to_string(), Default::default()).unwrap();
+            (finished, *mgr.active_games.keys().next().unwrap())
+        };
+
+        let (status, text) = send(&router, "GET", &format!("/api/games/{}", running), None, "").await;
+        assert_eq!(status, StatusCode::OK, "{}", text);
+        let game: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(game["id"], running.to_string());
+        assert_eq!(game["status"], "Running");
+        assert_eq!(game["grid"].as_array().unwrap().len(), game["height"].as_u64().unwrap() as usize);
+
+        let path = format!("/api/games/{}?include_grid=false", finished);
+        let (status, text) = send(&router, "GET", &path, None, "").await;
+        assert_eq!(status, StatusCode::OK, "{}", text);
+        let game: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(game["id"], finished.to_string());
+        assert_eq!(game["status"], "Finished");
+        assert!(game.get("grid").is_none());
+        assert_eq!(game["players"][0]["name"], "Claude");
+        assert_eq!(game["players"][1]["name"], "Gemini");
+
+        let (status, text) = send(&router, "GET", &format!("/api/games/{}", uuid::Uuid::new_v4()), None, "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(text.contains("GAME_NOT_FOUND"), "{}", text);
+        let (status, text) = send(&router, "GET", "/api/games/not-a-game", None, "").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(text.contains("INVALID_PARAMS"), "{}", text);
+    }
+
+    /// Open the event stream at `path`; its frames as they arrive
+    async fn stream(router: &Router, path: &str) -> axum::body::BodyDataStream {
+        let req = axum::http::Request::builder().uri(path).body(Body::empty()).unwrap();
+        let res = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        res.into_body().into_data_stream()
+    }
+
+    /// The next event's data, skipping keep-alive comments
+    async fn next_event(frames: &mut axum::body::BodyDataStream) -> serde_json::Value {
+        loop {
+            let frame = tokio::time::timeout(Duration::from_secs(5), frames.next())
+                .await
+                .expect("no event within 5s")
+                .unwrap()
+                .unwrap();
+            let text = String::from_utf8_lossy(&frame).into_owned();
+            if let Some(data) = text.lines().find_map(|line| line.strip_prefix("data: ")) {
+                return serde_json::from_str(data).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_open_with_a_snapshot_and_keep_to_one_game() {
+        let (rooms, _dir) = testutil::rooms();
+        let manager = rooms.default_room().clone();
+        let router = router(rooms, AdminAuth::default());
+        let (watched, other) = {
+            let mut mgr = manager.lock().await;
+            for name in ["Claude", "Gemini", "Grok", "Llama"] {
+                mgr.join(name.to_string(), Default::default()).unwrap();
+            }
+            let game = |name: &str| mgr.player_sessions[name].game_id.unwrap().to_string();
+            (game("Claude"), game("Grok"))
+        };
+
+        let mut all = stream(&router, "/api/stream").await;
+        let snapshot = next_event(&mut all).await;
+        assert_eq!(snapshot["type"], "snapshot");
+        assert_eq!(snapshot["games"].as_array().unwrap().len(), 2);
+
+        let mut one = stream(&router, &format!("/api/stream?game_id={}", watched)).await;
+        let snapshot = next_event(&mut one).await;
+        assert_eq!(snapshot["type"], "snapshot");
+        let games = snapshot["games"].as_array().unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0]["id"], watched);
+
+        // The other game's move is left out; server-wide news is not
+        {
+            let mut mgr = manager.lock().await;
+            mgr.move_player("Grok", crate::game::Steer::Turn(crate::game::SteerAction::Straight))
+                .unwrap();
+            mgr.move_player("Claude", crate::game::Steer::Turn(crate::game::SteerAction::Straight))
+                .unwrap();
+            mgr.broadcast_tx.send(BroadcastEvent::ServerRecovered).unwrap();
+        }
+        let update = next_event(&mut one).await;
+        assert_eq!(update["type"], "game_update");
+        assert_eq!(update["game"]["id"], watched);
+        assert_eq!(next_event(&mut one).await["type"], "server_recovered");
+        let update = next_event(&mut all).await;
+        assert_eq!(update["game"]["id"], other);
+
+        let (status, text) = send(&router, "GET", "/api/stream?game_id=nope", None, "").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(text.contains("INVALID_PARAMS"), "{}", text);
+    }
+}
@@ -0,0 +1,802 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::broadcast;
+
+use crate::broadcast::BroadcastEvent;
+use crate::game::WebGameState;
+use crate::manager::SharedGameManager;
+use crate::validation;
+
+/// Most notifications sent to one player's webhook per minute; the rest go
+/// to the dead-letter log. `move_window` pings don't count.
+pub const MAX_PER_MINUTE: usize = 20;
+/// Most player webhooks one room keeps
+pub const MAX_WEBHOOKS: usize = 1000;
+/// Delivery attempts before a notification is dead-lettered
+const ATTEMPTS: u32 = 4;
+/// Time allowed for one attempt, from connect to status line
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Attempts at a server webhook: the first and one retry
+const SERVER_ATTEMPTS: u32 = 2;
+/// Time allowed for one attempt at a server webhook
+const SERVER_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Shortest secret accepted, so signatures are worth checking
+const MIN_SECRET_LEN: usize = 16;
+const MAX_URL_LEN: usize = 512;
+
+/// A player's notification endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    /// Signs every body; also required to replace or remove the webhook
+    pub secret: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// `POST /api/players/{name}/webhook` body
+#[derive(Debug, Deserialize)]
+pub struct WebhookRequest {
+    pub url: String,
+    pub secret: String,
+    /// Resume or session token from the player's join, proving the caller
+    /// plays under the name
+    #[serde(default)]
+    pub token: String,
+}
+
+impl WebhookRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.url.len() > MAX_URL_LEN {
+            return Err(format!("'url' must be at most {} bytes.", MAX_URL_LEN));
+        }
+        Target::parse(&self.url)?;
+        validation::token("secret", &self.secret)?;
+        if self.secret.len() < MIN_SECRET_LEN {
+            return Err(format!("'secret' must be at least {} bytes.", MIN_SECRET_LEN));
+        }
+        validation::token("token", &self.token)?;
+        Ok(())
+    }
+}
+
+/// Body of a server webhook (`--webhook-format`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// The broadcast event as the event stream sends it
+    #[default]
+    Json,
+    /// `{"content": "..."}` with a one-line announcement, for Discord
+    Discord,
+}
+
+impl WebhookFormat {
+    pub fn parse(s: &str) -> Result<WebhookFormat, String> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(WebhookFormat::Json),
+            "discord" => Ok(WebhookFormat::Discord),
+            _ => Err("expected 'json' or 'discord'".to_string()),
+        }
+    }
+}
+
+/// Server-wide webhooks (`--webhook-url`), told of every game that starts
+/// or finishes, exhibitions aside
+#[derive(Debug, Clone, Default)]
+pub struct ServerWebhooks {
+    pub urls: Vec<String>,
+    pub format: WebhookFormat,
+}
+
+impl ServerWebhooks {
+    pub fn new(urls: Vec<String>, format: WebhookFormat) -> Result<ServerWebhooks, String> {
+        for url in &urls {
+            if url.len() > MAX_URL_LEN {
+                return Err(format!("webhook-url must be at most {} bytes", MAX_URL_LEN));
+            }
+            Target::parse(url).map_err(|e| format!("webhook-url {}: {}", url, e))?;
+        }
+        Ok(ServerWebhooks { urls, format })
+    }
+
+    /// The event's name and the body to POST, for the events worth a webhook
+    fn body(&self, event: &BroadcastEvent) -> Option<(&'static str, Vec<u8>)> {
+        let (kind, game) = match event {
+            BroadcastEvent::GameStarted { game, .. } => ("game_started", game),
+            BroadcastEvent::GameFinished { game, .. } => ("game_finished", game),
+            _ => return None,
+        };
+        if game.exhibition {
+            return None;
+        }
+        let body = match self.format {
+            WebhookFormat::Json => serde_json::to_vec(event).ok()?,
+            WebhookFormat::Discord => serde_json::json!({ "content": announcement(kind, game) })
+                .to_string()
+                .into_bytes(),
+        };
+        Some((kind, body))
+    }
+}
+
+/// One line on a game starting or finishing: course, players, and at the
+/// end the result, scores and how long it took
+fn announcement(kind: &str, game: &WebGameState) -> String {
+    let names: Vec<&str> = game.players.iter().map(|p| p.name.as_str()).collect();
+    let course = format!("{} (level {})", game.course_name, game.course_level);
+    if kind == "game_started" {
+        return format!("Game started on {}: {}", course, names.join(" vs "));
+    }
+    let result = if game.aborted {
+        "aborted".to_string()
+    } else if let Some(team) = game.winning_team {
+        format!("Team {} won", team)
+    } else if let Some(winner) = game.winner.and_then(|w| names.get(w)) {
+        format!("{} won", winner)
+    } else {
+        "a draw".to_string()
+    };
+    let scores: Vec<String> = game.players.iter().map(|p| format!("{} {}", p.name, p.score)).collect();
+    let took = chrono::DateTime::parse_from_rfc3339(&game.created_at)
+        .ok()
+        .zip(game.finished_at.as_deref().and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok()))
+        .map(|(start, end)| {
+            let secs = (end - start).num_seconds().max(0);
+            format!(" in {}m {:02}s", secs / 60, secs % 60)
+        })
+        .unwrap_or_default();
+    format!(
+        "Game on {} finished: {}. Scores: {}. {} ticks{}.",
+        course,
+        result,
+        scores.join(", "),
+        game.tick,
+        took
+    )
+}
+
+/// `X-Tron-Signature` value for a body: `sha256=<hex HMAC-SHA256>`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+/// Where a webhook URL points. Only plain `http://` is supported.
+struct Target {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl Target {
+    fn parse(url: &str) -> Result<Target, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or("'url' must start with http://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>().map_err(|_| "'url' has an invalid port")?,
+            ),
+            None => (authority, 80),
+        };
+        // Host names and IPv4 addresses only; anything else could smuggle
+        // headers into the request
+        let host_ok = !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'));
+        if !host_ok || path.chars().any(|c| c.is_control() || c == ' ') {
+            return Err("'url' is not a valid http:// URL".to_string());
+        }
+        Ok(Target {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// Whether an address is on the public internet: not loopback, private,
+/// link-local, shared (CGNAT), documentation, multicast or otherwise
+/// reserved, so a player's webhook can't reach into the server's network
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8)
+        || ip.segments()[..6].iter().all(|&s| s == 0))
+}
+
+/// Resolve a webhook URL to the address to connect to. Within `Reach::Public`
+/// every address the host resolves to must be public, and the one returned
+/// is the one connected to, so a second lookup can't swap in another.
+pub async fn resolve(url: &str, reach: Reach) -> Result<SocketAddr, String> {
+    let target = Target::parse(url)?;
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((target.host.as_str(), target.port))
+        .await
+        .map_err(|e| format!("'url' host does not resolve: {}", e))?
+        .collect();
+    match addrs.first() {
+        None => Err("'url' host does not resolve".to_string()),
+        Some(_) if reach == Reach::Public && addrs.iter().any(|a| !is_public(a.ip())) => {
+            Err("'url' must point at a public address, not a loopback, private or link-local one".to_string())
+        }
+        Some(&addr) => Ok(addr),
+    }
+}
+
+/// Where a POST may go: anywhere for the operator's `--webhook-url` and
+/// under `--allow-private-webhooks`, public addresses only for player
+/// webhooks otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reach {
+    Any,
+    Public,
+}
+
+/// One POST, given `timeout` to answer; returns the response status code
+async fn post(url: &str, reach: Reach, headers: &[(&str, String)], body: &[u8], timeout: Duration) -> Result<u16, String> {
+    let target = Target::parse(url)?;
+    let attempt = async {
+        let mut stream = tokio::net::TcpStream::connect(resolve(url, reach).await?)
+            .await
+            .map_err(|e| format!("connect: {}", e))?;
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            target.path,
+            target.host,
+            target.port,
+            body.len()
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await.map_err(|e| format!("send: {}", e))?;
+        stream.write_all(body).await.map_err(|e| format!("send: {}", e))?;
+
+        // The status line is all we need
+        let mut head = Vec::new();
+        let mut buf = [0u8; 256];
+        while !head.contains(&b'\n') && head.len() < 1024 {
+            let n = stream.read(&mut buf).await.map_err(|e| format!("receive: {}", e))?;
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        let line = String::from_utf8_lossy(&head);
+        line.split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| "no HTTP status in the response".to_string())
+    };
+    tokio::time::timeout(timeout, attempt)
+        .await
+        .map_err(|_| format!("no response within {:?}", timeout))?
+}
+
+/// Per-player notifications for a broadcast event: who to tell, and what
+pub fn notifications(event: &BroadcastEvent) -> Vec<(String, serde_json::Value)> {
+    let at = chrono::Utc::now().to_rfc3339();
+    let (kind, game) = match event {
+        BroadcastEvent::GameStarted { game, .. } => ("game_started", game),
+        BroadcastEvent::GameFinished { game, .. } => ("game_finished", game),
+        BroadcastEvent::MatchCountdown {
+            level,
+            players,
+            starts_in_secs,
+        } => {
+            return players
+                .iter()
+                .map(|player| {
+                    let opponents: Vec<&String> = players.iter().filter(|n| *n != player).collect();
+                    let event = serde_json::json!({
+                        "event": "matched",
+                        "player": player,
+                        "course_level": level,
+                        "opponents": opponents,
+                        "starts_in_secs": starts_in_secs,
+                        "at": at,
+                    });
+                    (player.clone(), event)
+                })
+                .collect();
+        }
+        BroadcastEvent::MoveWindow { game_id, tick, players } => {
+            return players
+                .iter()
+                .map(|player| {
+                    let event = serde_json::json!({
+                        "event": "move_window",
+                        "player": player,
+                        "game_id": game_id,
+                        "tick": tick,
+                        "at": at,
+                    });
+                    (player.clone(), event)
+                })
+                .collect();
+        }
+        _ => return Vec::new(),
+    };
+    let names: Vec<&str> = game.players.iter().map(|p| p.name.as_str()).collect();
+
+    game.players
+        .iter()
+        .filter(|p| !p.bot)
+        .map(|p| {
+            let opponents: Vec<&str> = names.iter().copied().filter(|n| *n != p.name).collect();
+            let mut event = serde_json::json!({
+                "event": kind,
+                "player": p.name,
+                "game_id": game.id,
+                "course_name": game.course_name,
+                "course_level": game.course_level,
+                "opponents": opponents,
+                "at": at,
+            });
+            if kind == "game_finished" {
+                let won = game.winner == Some(p.index) || game.winning_team.is_some_and(|team| p.team == Some(team));
+                event["won"] = serde_json::json!(won);
+                event["winner"] = serde_json::json!(game.winner.and_then(|w| names.get(w)));
+                if let Some(team) = game.winning_team {
+                    event["winning_team"] = serde_json::json!(team);
+                    event["team"] = serde_json::json!(p.team);
+                }
+                event["aborted"] = serde_json::json!(game.aborted);
+                event["score"] = serde_json::json!(p.score);
+                event["distance"] = serde_json::json!(p.distance);
+            }
+            (p.name.clone(), event)
+        })
+        .collect()
+}
+
+/// Feed a room's broadcast stream to its players' webhooks and the server
+/// webhooks. Each delivery runs in its own task so a slow receiver never
+/// holds up the others, or the game.
+pub async fn run_sender(manager: SharedGameManager) {
+    let (mut rx, dead_letters, server_webhooks, reach) = {
+        let mgr = manager.lock().await;
+        let reach = if mgr.allow_private_webhooks { Reach::Any } else { Reach::Public };
+        (mgr.subscribe(), dead_letter_path(&mgr.data_dir), mgr.server_webhooks.clone(), reach)
+    };
+    let mut sent: HashMap<String, VecDeque<Instant>> = HashMap::new();
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!("Webhook sender skipped {} messages", n);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if let Some((kind, body)) = server_webhooks.body(&event) {
+            let body: Arc<[u8]> = body.into();
+            for url in &server_webhooks.urls {
+                tokio::spawn(deliver_to_server(url.clone(), kind, body.clone()));
+            }
+        }
+        for (player, event) in notifications(&event) {
+            let Some(webhook) = manager.lock().await.webhooks.get(&player).cloned() else {
+                continue;
+            };
+            // A move window comes once a tick while the player rides; it
+            // isn't held to the per-minute limit
+            if event["event"] != "move_window" {
+                let recent = sent.entry(player.clone()).or_default();
+                recent.retain(|at| at.elapsed() < Duration::from_secs(60));
+                if recent.len() >= MAX_PER_MINUTE {
+                    dead_letter(&dead_letters, &player, &webhook.url, &event, "rate limited", 0).await;
+                    continue;
+                }
+                recent.push_back(Instant::now());
+            }
+            tokio::spawn(deliver(webhook, reach, player, event, dead_letters.clone()));
+        }
+    }
+}
+
+/// POST one signed notification, retrying with backoff (1s, 2s, 4s) on
+/// network errors and non-2xx answers. A move window is over within the
+/// second, so it gets one attempt and no dead letter.
+async fn deliver(webhook: Webhook, reach: Reach, player: String, event: serde_json::Value, dead_letters: PathBuf) {
+    let once = event["event"] == "move_window";
+    let body = event.to_string().into_bytes();
+    let headers = [
+        ("X-Tron-Event", event["event"].as_str().unwrap_or_default().to_string()),
+        ("X-Tron-Delivery", uuid::Uuid::new_v4().to_string()),
+        ("X-Tron-Signature", sign(&webhook.secret, &body)),
+    ];
+    let attempts = if once { 1 } else { ATTEMPTS };
+    let mut error = String::new();
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+        }
+        match post(&webhook.url, reach, &headers, &body, ATTEMPT_TIMEOUT).await {
+            Ok(status) if (200..300).contains(&status) => return,
+            Ok(status) => error = format!("HTTP {}", status),
+            Err(e) => error = e,
+        }
+    }
+    if once {
+        tracing::debug!("Move window webhook for '{}' failed: {}", player, error);
+        return;
+    }
+    tracing::warn!("Webhook for '{}' failed after {} attempts: {}", player, ATTEMPTS, error);
+    dead_letter(&dead_letters, &player, &webhook.url, &event, &error, ATTEMPTS).await;
+}
+
+/// POST one event to a server webhook, retrying once a second later on a
+/// network error or non-2xx answer; a second failure is only logged
+async fn deliver_to_server(url: String, kind: &'static str, body: Arc<[u8]>) {
+    let headers = [
+        ("X-Tron-Event", kind.to_string()),
+        ("X-Tron-Delivery", uuid::Uuid::new_v4().to_string()),
+    ];
+    let mut error = String::new();
+    for attempt in 0..SERVER_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        match post(&url, Reach::Any, &headers, &body, SERVER_ATTEMPT_TIMEOUT).await {
+            Ok(status) if (200..300).contains(&status) => return,
+            Ok(status) => error = format!("HTTP {}", status),
+            Err(e) => error = e,
+        }
+    }
+    tracing::warn!("Server webhook {} missed {}: {}", url, kind, error);
+}
+
+fn dead_letter_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("webhook_dead_letters.jsonl")
+}
+
+/// Append an undelivered notification to the dead-letter log
+async fn dead_letter(path: &Path, player: &str, url: &str, event: &serde_json::Value, error: &str, attempts: u32) {
+    let line = serde_json::json!({
+        "at": chrono::Utc::now().to_rfc3339(),
+        "player": player,
+        "url": url,
+        "error": error,
+        "attempts": attempts,
+        "event": event,
+    });
+    let written = async {
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+        file.write_all(format!("{}\n", line).as_bytes()).await?;
+        file.flush().await
+    };
+    if let Err(e) = written.await {
+        tracing::error!("Failed to write webhook dead letter: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::course::TagPreferences;
+    use crate::game::{Steer, SteerAction};
+    use crate::testutil::{self, ScratchDir};
+    use axum::body::Bytes;
+    use axum::http::{HeaderMap, StatusCode};
+    use std::sync::Mutex;
+
+    /// One request the mock receiver took, headers keyed in lower case
+    struct Received {
+        at: Instant,
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+    }
+
+    /// A local axum server taking POSTs at `/hook`, answering each with the
+    /// next of `statuses`, the last one repeating. Returns its URL and what
+    /// it got.
+    async fn receiver(statuses: &[u16]) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        let statuses = statuses.to_vec();
+        let hook = move |headers: HeaderMap, body: Bytes| {
+            let (log, statuses) = (log.clone(), statuses.clone());
+            async move {
+                let mut log = log.lock().unwrap();
+                let status = statuses[log.len().min(statuses.len() - 1)];
+                log.push(Received {
+                    at: Instant::now(),
+                    headers: headers
+                        .iter()
+                        .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or_default().to_string()))
+                        .collect(),
+                    body: body.to_vec(),
+                });
+                StatusCode::from_u16(status).unwrap()
+            }
+        };
+        let app = axum::Router::new().route("/hook", axum::routing::post(hook));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    /// Wait for `received` to hold `count` requests
+    async fn wait_for(received: &Mutex<Vec<Received>>, count: usize) {
+        let arrived = async {
+            while received.lock().unwrap().len() < count {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), arrived)
+            .await
+            .unwrap_or_else(|_| panic!("{} of {} requests arrived", received.lock().unwrap().len(), count));
+    }
+
+    fn webhook(url: &str) -> Webhook {
+        Webhook {
+            url: url.to_string(),
+            secret: "0123456789abcdef-secret".to_string(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn finished_event() -> serde_json::Value {
+        serde_json::json!({"event": "game_finished", "player": "Claude", "won": true})
+    }
+
+    #[tokio::test]
+    async fn notifications_carry_a_verifiable_signature() {
+        let (url, received) = receiver(&[200]).await;
+        let dir = ScratchDir::new();
+        let hook = webhook(&url);
+
+        deliver(hook.clone(), Reach::Any, "Claude".to_string(), finished_event(), dead_letter_path(dir.path())).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let req = &received[0];
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&req.body).unwrap(), finished_event());
+        assert_eq!(req.headers["x-tron-event"], "game_finished");
+        // Check the signature the way a receiver would, independently of `sign`
+        let hex = req.headers["x-tron-signature"].strip_prefix("sha256=").unwrap();
+        let sent: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        let mut mac = Hmac::<Sha256>::new_from_slice(hook.secret.as_bytes()).unwrap();
+        mac.update(&req.body);
+        assert!(mac.verify_slice(&sent).is_ok());
+        let mut forged = Hmac::<Sha256>::new_from_slice(b"some-other-secret").unwrap();
+        forged.update(&req.body);
+        assert!(forged.verify_slice(&sent).is_err());
+        assert!(!dead_letter_path(dir.path()).exists());
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_are_retried_with_backoff() {
+        let (url, received) = receiver(&[503, 500, 200]).await;
+        let dir = ScratchDir::new();
+
+        deliver(webhook(&url), Reach::Any, "Claude".to_string(), finished_event(), dead_letter_path(dir.path())).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert!(received[1].at - received[0].at >= Duration::from_secs(1));
+        assert!(received[2].at - received[1].at >= Duration::from_secs(2));
+        // Every retry is the same notification, under the same delivery id
+        // so the receiver can tell a repeat
+        assert!(received.iter().all(|r| r.body == received[0].body));
+        assert!(received.iter().all(|r| r.headers["x-tron-delivery"] == received[0].headers["x-tron-delivery"]));
+        assert!(!dead_letter_path(dir.path()).exists());
+    }
+
+    #[tokio::test]
+    async fn undeliverable_notifications_are_dead_lettered() {
+        let (url, received) = receiver(&[500]).await;
+        let dir = ScratchDir::new();
+
+        deliver(webhook(&url), Reach::Any, "Claude".to_string(), finished_event(), dead_letter_path(dir.path())).await;
+
+        assert_eq!(received.lock().unwrap().len(), ATTEMPTS as usize);
+        let log = std::fs::read_to_string(dead_letter_path(dir.path())).unwrap();
+        let lines: Vec<serde_json::Value> = log.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["player"], "Claude");
+        assert_eq!(lines[0]["url"], url);
+        assert_eq!(lines[0]["error"], "HTTP 500");
+        assert_eq!(lines[0]["attempts"], ATTEMPTS);
+        assert_eq!(lines[0]["event"], finished_event());
+    }
+
+    #[tokio::test]
+    async fn move_windows_are_tried_once() {
+        let (url, received) = receiver(&[500]).await;
+        let dir = ScratchDir::new();
+        let event = serde_json::json!({"event": "move_window", "player": "Claude", "tick": 7});
+
+        deliver(webhook(&url), Reach::Any, "Claude".to_string(), event, dead_letter_path(dir.path())).await;
+
+        assert_eq!(received.lock().unwrap().len(), 1);
+        assert!(!dead_letter_path(dir.path()).exists());
+    }
+
+    #[tokio::test]
+    async fn player_webhooks_cannot_reach_the_local_network() {
+        let (url, received) = receiver(&[200]).await;
+        let dir = ScratchDir::new();
+
+        assert!(resolve(&url, Reach::Public).await.is_err());
+        // A move window, so the refused attempt isn't retried
+        let event = serde_json::json!({"event": "move_window", "player": "Claude", "tick": 7});
+        deliver(webhook(&url), Reach::Public, "Claude".to_string(), event, dead_letter_path(dir.path())).await;
+
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    /// A room sending its games to a server webhook at `url`, with the
+    /// sender running
+    async fn room_with_webhook(url: &str, format: WebhookFormat) -> (SharedGameManager, ScratchDir) {
+        let (mut mgr, dir) = testutil::manager();
+        mgr.server_webhooks = Arc::new(ServerWebhooks::new(vec![url.to_string()], format).unwrap());
+        let manager = Arc::new(tokio::sync::Mutex::new(mgr));
+        tokio::spawn(run_sender(manager.clone()));
+        // Let the sender subscribe before anything happens
+        tokio::task::yield_now().await;
+        (manager, dir)
+    }
+
+    /// Claude and Gemini start a game; Gemini walks out after Claude's move
+    async fn play(manager: &SharedGameManager) {
+        let mut mgr = manager.lock().await;
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.leave("Gemini").unwrap();
+    }
+
+    /// The requests in the order of the events they carry
+    fn by_event(received: &Mutex<Vec<Received>>) -> Vec<(String, serde_json::Value)> {
+        let mut requests: Vec<(String, serde_json::Value)> = received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| (r.headers["x-tron-event"].clone(), serde_json::from_slice(&r.body).unwrap()))
+            .collect();
+        requests.sort_by_key(|(event, _)| event != "game_started");
+        requests
+    }
+
+    #[tokio::test]
+    async fn server_webhooks_get_the_broadcast_events() {
+        let (url, received) = receiver(&[200]).await;
+        let (manager, _dir) = room_with_webhook(&url, WebhookFormat::Json).await;
+        let mut rx = manager.lock().await.subscribe();
+
+        play(&manager).await;
+        wait_for(&received, 2).await;
+
+        let mut broadcast = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if matches!(event, BroadcastEvent::GameStarted { .. } | BroadcastEvent::GameFinished { .. }) {
+                broadcast.push(serde_json::to_value(&event).unwrap());
+            }
+        }
+        let requests = by_event(&received);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0], ("game_started".to_string(), broadcast[0].clone()));
+        assert_eq!(requests[1], ("game_finished".to_string(), broadcast[1].clone()));
+        assert_eq!(requests[1].1["game"]["players"][0]["name"], "Claude");
+        assert_eq!(requests[1].1["game"]["winner"], 0);
+    }
+
+    #[tokio::test]
+    async fn discord_webhooks_get_an_announcement() {
+        let (url, received) = receiver(&[204]).await;
+        let (manager, _dir) = room_with_webhook(&url, WebhookFormat::Discord).await;
+
+        play(&manager).await;
+        wait_for(&received, 2).await;
+
+        let game = manager.lock().await.get_finished_games()[0].clone();
+        let course = format!("{} (level {})", game.course_name, game.course_level);
+        let requests = by_event(&received);
+        assert_eq!(
+            requests[0].1,
+            serde_json::json!({ "content": format!("Game started on {}: Claude vs Gemini", course) })
+        );
+        let finished = format!(
+            "Game on {} finished: Claude won. Scores: Claude {}, Gemini {}. 1 ticks in 0m 00s.",
+            course, game.players[0].score, game.players[1].score
+        );
+        assert_eq!(requests[1].1, serde_json::json!({ "content": finished }));
+    }
+
+    #[tokio::test]
+    async fn server_webhooks_are_retried_once() {
+        let (url, received) = receiver(&[500, 200]).await;
+        deliver_to_server(url, "game_finished", Arc::from(&b"{}"[..])).await;
+        {
+            let received = received.lock().unwrap();
+            assert_eq!(received.len(), 2);
+            assert!(received[1].at - received[0].at >= Duration::from_secs(1));
+            assert_eq!(received[1].headers["x-tron-delivery"], received[0].headers["x-tron-delivery"]);
+        }
+
+        // A second failure is the last
+        let (url, received) = receiver(&[503]).await;
+        deliver_to_server(url, "game_finished", Arc::from(&b"{}"[..])).await;
+        assert_eq!(received.lock().unwrap().len(), SERVER_ATTEMPTS as usize);
+    }
+
+    #[test]
+    fn exhibitions_and_other_events_are_not_sent() {
+        let webhooks = ServerWebhooks::new(vec!["http://127.0.0.1:9/hook".to_string()], WebhookFormat::Json).unwrap();
+        let mut game = testutil::game(
+            "
+            ........
+            ........
+            ........
+            ........
+            ........
+            ........
+            ........
+            ........",
+            &["Claude", "Gemini"],
+        )
+        .to_web_state();
+        let finished = |game: &WebGameState| BroadcastEvent::GameFinished {
+            game: Box::new(game.clone()),
+            leaderboard_checksum: None,
+        };
+        assert!(webhooks.body(&finished(&game)).is_some());
+        game.exhibition = true;
+        assert!(webhooks.body(&finished(&game)).is_none());
+        assert!(webhooks.body(&BroadcastEvent::ServerShutdown).is_none());
+    }
+}
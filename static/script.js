@@ -2,9 +2,17 @@ const COLORS=['#00f0ff','#ff00e5','#00ff88','#ff8800','#aa66ff','#ffdd00','#ff66
 const TRAIL_COLORS=['#00f0ff88','#ff00e588','#00ff8888','#ff880088','#aa66ff88','#ffdd0088','#ff668888','#66ddff88'];
 const WALL_COLOR='#2a2a4e';
 const OBSTRUCTION_COLOR='#4a2a2e';
+const CHECKPOINT_COLOR='#f0f0f0';
+const CLOSED_COLOR='#3a1a2a';
+const ARENA_COLOR='#ff3355';
+const POWERUP_COLORS=['#fff36b','#ff5a5a','#c8c8ff'];
 const BG_COLOR='#08080f';
 const GRID_COLOR='#0f0f1a';
 
+// Optional ?room=<name> selects which arena this page shows
+const ROOM=new URLSearchParams(location.search).get('room');
+const API=ROOM?`/api/rooms/${encodeURIComponent(ROOM)}`:'/api';
+
 let currentGame=null;
 const canvas=document.getElementById('gameCanvas');
 const ctx=canvas.getContext('2d');
@@ -37,14 +45,35 @@ function renderGame(game){
       if(cell===0)continue;
       if(cell===1){ctx.fillStyle=WALL_COLOR;ctx.fillRect(x*cs,y*cs,cs,cs)}
       else if(cell===2){ctx.fillStyle=OBSTRUCTION_COLOR;ctx.fillRect(x*cs,y*cs,cs,cs)}
+      else if(cell===99){ctx.fillStyle=CHECKPOINT_COLOR;ctx.fillRect(x*cs,y*cs,cs,cs)}
+      else if(cell===95){ctx.fillStyle=CLOSED_COLOR;ctx.fillRect(x*cs,y*cs,cs,cs)}
+      else if(cell>=96&&cell<=98){
+        // Power-ups: speed boost, trail eraser, ghost
+        ctx.fillStyle=POWERUP_COLORS[cell-96];
+        ctx.beginPath();ctx.arc(x*cs+cs/2,y*cs+cs/2,cs/2.5,0,Math.PI*2);ctx.fill();
+      }
       else{
-        const pi=cell-3;
+        // Codes from 100 are trails of crashed players; draw them dimmed
+        const dead=cell>=100;
+        const pi=dead?cell-100:cell-3;
         ctx.fillStyle=TRAIL_COLORS[pi%8];
+        if(dead)ctx.globalAlpha=0.35;
         ctx.fillRect(x*cs,y*cs,cs,cs);
+        ctx.globalAlpha=1;
       }
     }
   }
 
+  // Shrinking arena: outline the playable area, brighter as it closes in
+  if(game.arena){
+    const [x0,y0,x1,y1]=game.arena;
+    ctx.strokeStyle=ARENA_COLOR;
+    ctx.globalAlpha=game.shrink_in!=null&&game.shrink_in<=5?1:0.5;
+    ctx.lineWidth=2;
+    ctx.strokeRect(x0*cs,y0*cs,(x1-x0+1)*cs,(y1-y0+1)*cs);
+    ctx.globalAlpha=1;
+  }
+
   // Player heads (bright glow)
   for(const p of game.players){
     if(!p.alive)continue;
@@ -69,22 +98,35 @@ function renderGame(game){
   const info=document.getElementById('gameInfo');
   const statusText=game.status==='Running'?'⚡ RUNNING':game.status==='Finished'?'🏁 FINISHED':'⏳ WAITING';
   const alive=game.players.filter(p=>p.alive).length;
-  info.innerHTML=`<span>${statusText}</span><span>Course: ${game.course_name} (Lv.${game.course_level})</span><span>Tick: ${game.tick}</span><span>Alive: ${alive}/${game.players.length}</span><span>Grid: ${game.width}×${game.height}</span>`;
+  info.innerHTML=`<span>${statusText}</span><span>Course: ${game.course_name} (Lv.${game.course_level}${game.course_seed!=null?`, seed ${game.course_seed}`:''})</span><span>Tick: ${game.tick}</span><span>Alive: ${alive}/${game.players.length}</span><span>Grid: ${game.width}×${game.height}</span>${game.exhibition?'<span>🤖 EXHIBITION</span>':game.featured?'<span>★ FEATURED</span>':''}${game.lobby?`<span>Lobby: ${game.lobby}</span>`:''}${game.unranked?'<span>UNRANKED</span>':''}${(game.rules_diff||[]).map(r=>`<span>${r}</span>`).join('')}`;
 
   // Player list
   const pl=document.getElementById('playerList');
   pl.innerHTML=game.players.map((p,i)=>{
     const c=COLORS[i%8];
-    const st=p.alive?'ALIVE':'CRASHED';
-    const extra=game.winner===i?' 👑':'';
-    return `<span class="player-tag" style="border-color:${c};color:${c}">${p.name}: ${st} (d:${p.distance})${extra}</span>`;
-  }).join('');
+    const st=p.alive?'ALIVE':p.respawn_at_tick!=null?`RESPAWNING T${p.respawn_at_tick}`:'CRASHED';
+    const won=game.winner===i||(game.winning_team!=null&&p.team===game.winning_team);
+    const extra=won?' 👑':'';
+    const team=p.team!=null?`[T${p.team}] `:'';
+    return `<span class="player-tag${p.bot?' bot':''}" style="border-color:${c};color:${c}">${p.bot?'🤖 ':''}${team}${p.name}: ${st} (d:${p.distance}${p.claimed!=null?`, cells:${p.claimed}`:''}${p.lives!=null?`, lives:${p.lives}`:''})${extra}</span>`;
+  }).join('')+((game.team_scores||[]).length?`<span class="player-tag">${game.team_scores.map((s,t)=>`Team ${t+1}: ${s}`).join(' · ')}</span>`:'')+(game.shrink_in!=null?`<span class="player-tag">Arena shrinks in ${game.shrink_in}</span>`:'');
+
+  // Kill feed: the latest events, newest first
+  const feed=(game.events||[]).filter(e=>e.text).slice(-6).reverse();
+  document.getElementById('eventFeed').innerHTML=feed.map(e=>`<span>T${e.tick} · ${e.text}</span>`).join('');
+  renderChat(game);
+}
+
+// Chat, newest last; the server sends the text HTML-escaped
+function renderChat(game){
+  const chat=(game.chat||[]).slice(-8);
+  document.getElementById('chatFeed').innerHTML=chat.map(m=>`<span>💬 <b>${m.player}</b> T${m.tick}: ${m.text}</span>`).join('');
 }
 
 // Fetch initial data
 async function fetchGames(){
   try{
-    const r=await fetch('/api/games');
+    const r=await fetch(API+'/games');
     const data=await r.json();
     renderActiveGames(data.active||[]);
     renderFinishedGames(data.finished||[]);
@@ -94,7 +136,7 @@ async function fetchGames(){
 }
 async function fetchLeaderboard(){
   try{
-    const r=await fetch('/api/leaderboard');
+    const r=await fetch(API+'/leaderboard');
     const data=await r.json();
     renderLeaderboard(data);
   }catch(e){console.error('Fetch leaderboard error:',e)}
@@ -108,7 +150,7 @@ function renderActiveGames(games){
     const names=g.players.map(p=>p.name).join(', ');
     const st=g.status==='Running'?'running':'waiting';
     return `<div class="game-item" onclick='renderGame(${JSON.stringify(g).replace(/'/g,"&#39;")})'>
-      <div><strong>${g.course_name}</strong> (Lv.${g.course_level})<br><small style="color:var(--text-dim)">${names}</small></div>
+      <div><strong>${g.exhibition?'🤖 Exhibition: ':g.featured?'★ ':''}${g.course_name}</strong> (Lv.${g.course_level})<br><small style="color:var(--text-dim)">${names}</small></div>
       <div style="text-align:right"><span class="status ${st}">${g.status}</span><br><small>${alive}/${g.players.length} alive · tick ${g.tick}</small></div>
     </div>`;
   }).join('');
@@ -119,7 +161,8 @@ function renderFinishedGames(games){
   if(!games.length){el.innerHTML='<div class="no-data">No finished games yet.</div>';return}
   const recent=games.slice(-20).reverse();
   el.innerHTML=recent.map(g=>{
-    const winner=g.winner!==null&&g.winner!==undefined&&g.players[g.winner]?g.players[g.winner].name:'Draw';
+    const tieBreak=g.tie_break?` (tie-break: ${g.tie_break.replace('_',' ')})`:'';
+    const winner=g.winning_team!=null?`Team ${g.winning_team}${tieBreak}`:g.winner!==null&&g.winner!==undefined&&g.players[g.winner]?g.players[g.winner].name+tieBreak:'Draw';
     const names=g.players.map(p=>p.name).join(', ');
     return `<div class="game-item" onclick='renderGame(${JSON.stringify(g).replace(/'/g,"&#39;")})'>
       <div><strong>${g.course_name}</strong> (Lv.${g.course_level})<br><small style="color:var(--text-dim)">${names}</small></div>
@@ -128,25 +171,36 @@ function renderFinishedGames(games){
   }).join('');
 }
 
+function ratingArrow(change){
+  if(!change)return '';
+  return change>0?` <span class="up" title="+${change} this week">▲</span>`:` <span class="down" title="${change} this week">▼</span>`;
+}
+
 function renderLeaderboard(entries){
   const el=document.getElementById('leaderboard');
   if(!entries.length){el.innerHTML='<div class="no-data">No games played yet.</div>';return}
   el.innerHTML=`<table class="lb-table">
-    <tr><th>#</th><th>PLAYER</th><th>WINS</th><th>POINTS</th><th>GAMES</th><th>LEVEL</th></tr>
+    <tr><th>#</th><th>PLAYER</th><th>W-L-D</th><th>WIN %</th><th>POINTS</th><th>KILLS</th><th>GAMES</th><th>LEVEL</th><th>RATING</th></tr>
     ${entries.map((e,i)=>`<tr>
-      <td class="rank">${i+1}</td><td>${e.name}</td><td>${e.wins}</td>
-      <td>${e.total_points}</td><td>${e.games_played}</td><td>${e.highest_level}</td>
+      <td class="rank">${i+1}</td><td>${e.name}</td>
+      <td title="Streak ${e.current_streak??0}, best ${e.best_streak??0}">${e.wins}-${e.losses??0}-${e.draws??0}</td>
+      <td>${Math.round((e.win_rate??0)*100)}%</td>
+      <td>${e.total_points}</td><td>${e.kills??0}</td><td>${e.games_played}</td><td>${e.highest_level}</td>
+      <td>${e.rating??''}${ratingArrow(e.rating_change_7d)}</td>
     </tr>`).join('')}
   </table>`;
 }
 
 // SSE for real-time updates
 function connectSSE(){
-  const es=new EventSource('/api/stream');
+  const es=new EventSource(API+'/stream');
   es.onmessage=(e)=> {
     try{
       const msg=JSON.parse(e.data);
-      if(msg.type==='game_update'){
+      if(msg.type==='snapshot'){
+        renderActiveGames(msg.games);
+        if(msg.games.length>0)renderGame(msg.games[0]);
+      }else if(msg.type==='game_update'){
         renderGame(msg.game);
         fetchGames();
       }else if(msg.type==='game_finished'){
@@ -155,6 +209,11 @@ function connectSSE(){
         if(msg.game)renderGame(msg.game);
       }else if(msg.type==='game_started'){
         fetchGames();
+      }else if(msg.type==='chat'){
+        if(currentGame&&currentGame.id===msg.game_id){
+          currentGame.chat=(currentGame.chat||[]).concat([msg.message]);
+          renderChat(currentGame);
+        }
       }
     }catch(err){console.error('SSE parse error:',err)}
   };
@@ -0,0 +1,51 @@
+//! Helpers shared by the integration tests
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tronmcp::manager::GameManager;
+use tronmcp::rooms::{Rooms, SharedRooms};
+use tronmcp::storage::{JsonStorage, SharedStorage};
+
+/// A fresh directory under the system temp dir, removed again on drop
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("tronmcp-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        ScratchDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// One room, "default", keeping JSON files in a scratch directory, with
+/// its manager set up by `configure`
+pub fn rooms(configure: impl Fn(&mut GameManager)) -> (SharedRooms, ScratchDir) {
+    let dir = ScratchDir::new();
+    let rooms = open_rooms(&["default"], &dir, configure);
+    (rooms, dir)
+}
+
+/// Rooms by these names keeping JSON files in `dir`, the first one the
+/// default; opening them again in the same directory picks up where they were
+pub fn open_rooms(names: &[&str], dir: &ScratchDir, configure: impl Fn(&mut GameManager)) -> SharedRooms {
+    let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+    let rooms = Rooms::new(
+        &names,
+        dir.path(),
+        |_, dir| Ok(Arc::new(JsonStorage::new(dir)) as SharedStorage),
+        configure,
+    )
+    .unwrap();
+    Arc::new(rooms)
+}
@@ -0,0 +1,34 @@
+//! `tronmcp demo` run the way a new user would, as its own process
+
+use std::process::Command;
+
+#[test]
+fn demo_plays_one_game_to_a_winner_and_exits() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tronmcp"))
+        .args(["demo", "--games", "1"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}\n{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Log lines come out on stdout too
+    let line = |prefix: &str| {
+        stdout
+            .lines()
+            .find(|l| l.starts_with(prefix))
+            .unwrap_or_else(|| panic!("no '{}' line in\n{}", prefix, stdout))
+    };
+    line("Tron demo: watch at http://127.0.0.1:");
+    let result = line("Game 1/1 on ");
+    assert!(
+        result.contains("tcp-hard wins") || result.contains("local-medium wins"),
+        "{}",
+        result
+    );
+    line("Demo over: ");
+}
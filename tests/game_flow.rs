@@ -0,0 +1,135 @@
+//! Whole games played through the public API, as an embedding binary would
+
+mod common;
+
+use tronmcp::broadcast::BroadcastEvent;
+use tronmcp::course::{Course, TagPreferences};
+use tronmcp::game::{Game, GameStatus, Steer, SteerAction};
+use tronmcp::manager::LeaderboardSort;
+use tronmcp::rules::Ruleset;
+
+#[tokio::test]
+async fn turn_based_game_from_join_to_leaderboard() {
+    let (rooms, _dir) = common::rooms(|_| {});
+    let manager = rooms.default_room().clone();
+    let mut mgr = manager.lock().await;
+    let mut rx = mgr.subscribe();
+
+    let reply = mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+    assert!(mgr.get_active_games().is_empty(), "{}", reply);
+    mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+    let game = mgr.get_active_games().pop().expect("the second player starts a game");
+    assert_eq!(game.status, GameStatus::Running);
+    let names: Vec<&str> = game.players.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Claude", "Gemini"]);
+
+    // Claude rides straight on until a wall stops them; Gemini stays put
+    let mut moves = 0;
+    while !mgr.get_active_games().is_empty() {
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        moves += 1;
+        assert!(moves < 100, "Claude never crashed");
+    }
+    assert!(mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).is_err());
+
+    let finished = mgr.get_finished_games().last().unwrap().clone();
+    assert_eq!(finished.id, game.id);
+    assert_eq!(finished.status, GameStatus::Finished);
+    assert_eq!(finished.winner, Some(1));
+    // The move into the wall goes nowhere, so it is not counted
+    assert_eq!(finished.tick, moves - 1);
+    assert!(!finished.players[0].alive);
+    assert!(finished.players[1].alive);
+
+    let leaderboard = mgr.get_leaderboard(LeaderboardSort::Points, 0);
+    let entry = |name: &str| leaderboard.iter().find(|e| e.name == name).unwrap();
+    assert_eq!((entry("Gemini").wins, entry("Gemini").games_played), (1, 1));
+    assert_eq!((entry("Claude").losses, entry("Claude").games_played), (1, 1));
+    assert_eq!(mgr.storage().load_finished_games(10).last().map(|g| &g.id), Some(&game.id));
+
+    // Watchers saw the start, every move and the result, in that order
+    let mut seen = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        match event {
+            BroadcastEvent::GameStarted { .. } => seen.push("started"),
+            BroadcastEvent::GameUpdate { .. } => seen.push("update"),
+            BroadcastEvent::GameFinished { game, .. } => {
+                assert_eq!(game.winner, Some(1));
+                seen.push("finished");
+            }
+            _ => {}
+        }
+    }
+    let mut expected = vec!["started"];
+    expected.extend(std::iter::repeat_n("update", moves as usize));
+    expected.push("finished");
+    assert_eq!(seen, expected);
+}
+
+#[tokio::test]
+async fn rooms_keep_their_own_queue_and_leaderboard() {
+    let dir = common::ScratchDir::new();
+    let rooms = common::open_rooms(&["main", "side"], &dir, |_| {});
+    let (main, side) = (rooms.get("main").unwrap(), rooms.get("side").unwrap());
+    side.lock().await.join("Grok".to_string(), TagPreferences::default()).unwrap();
+    {
+        let mut mgr = main.lock().await;
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.move_player("Claude", Steer::Turn(SteerAction::Straight)).unwrap();
+        mgr.leave("Gemini").unwrap();
+        assert_eq!(mgr.get_leaderboard(LeaderboardSort::Points, 0).len(), 2);
+        // Grok waits in the other room, so nobody here is queued
+        assert_eq!(mgr.admin_overview()["waiting_players"], serde_json::json!([]));
+    }
+    {
+        let mgr = side.lock().await;
+        assert!(mgr.get_leaderboard(LeaderboardSort::Points, 0).is_empty());
+        assert!(mgr.get_finished_games().is_empty());
+        assert_eq!(mgr.admin_overview()["waiting_players"], serde_json::json!(["Grok"]));
+    }
+
+    // Nor does the result land in the other room's files
+    for (_, manager) in rooms.iter() {
+        manager.lock().await.storage().flush();
+    }
+    drop(rooms);
+    let rooms = common::open_rooms(&["main", "side"], &dir, |_| {});
+    assert_eq!(rooms.get("main").unwrap().lock().await.get_leaderboard(LeaderboardSort::Points, 0).len(), 2);
+    assert!(rooms.get("side").unwrap().lock().await.get_leaderboard(LeaderboardSort::Points, 0).is_empty());
+}
+
+#[test]
+fn engine_plays_a_game_without_a_server() {
+    let course = Course::from_ascii(
+        "Corridor",
+        1,
+        "
+        ##########
+        #........#
+        #........#
+        #........#
+        #........#
+        #........#
+        #........#
+        ##########",
+    )
+    .unwrap();
+    let mut game = Game::new(&course, Ruleset::default()).unwrap();
+    assert_eq!(game.add_player("Claude".to_string()), Some(0));
+    assert_eq!(game.add_player("Gemini".to_string()), Some(1));
+    assert_eq!(game.status(), GameStatus::WaitingForPlayers);
+    game.start();
+    assert_eq!(game.status(), GameStatus::Running);
+
+    let mut moves = 0;
+    while game.status() == GameStatus::Running {
+        game.move_player(0, SteerAction::Straight);
+        moves += 1;
+        assert!(moves < 100, "Claude never crashed");
+    }
+    assert_eq!(game.winner(), Some(1));
+    assert_eq!(game.tick(), moves - 1);
+    assert!(!game.players()[0].alive);
+    assert!(game.players()[1].alive);
+}
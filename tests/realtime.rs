@@ -0,0 +1,65 @@
+//! A real-time game driven by the server tick loop, on a paused clock
+
+mod common;
+
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use tronmcp::broadcast::BroadcastEvent;
+use tronmcp::course::TagPreferences;
+use tronmcp::game::{GameMode, GameStatus, Steer, SteerAction};
+use tronmcp::watchdog::{run_tick_loop, TICK_INTERVAL};
+
+#[tokio::test(start_paused = true)]
+async fn two_player_realtime_game_runs_to_the_end() {
+    let (rooms, _dir) = common::rooms(|mgr| mgr.mode = GameMode::Realtime);
+    let manager = rooms.default_room().clone();
+    let mut rx = {
+        let mut mgr = manager.lock().await;
+        mgr.join("Claude".to_string(), TagPreferences::default()).unwrap();
+        mgr.join("Gemini".to_string(), TagPreferences::default()).unwrap();
+        mgr.subscribe()
+    };
+
+    // A steer only queues the turn: nothing moves or goes out until a tick
+    let reply = manager
+        .lock()
+        .await
+        .move_player("Claude", Steer::Turn(SteerAction::Straight))
+        .unwrap();
+    assert!(reply.starts_with("Steering STRAIGHT on the next tick"), "{}", reply);
+    assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+    let started = Instant::now();
+    let ct = CancellationToken::new();
+    let tick_loop = tokio::spawn(run_tick_loop(rooms.clone(), Default::default(), ct.clone()));
+
+    let mut updates = Vec::new();
+    let finished = loop {
+        match rx.recv().await.unwrap() {
+            BroadcastEvent::GameUpdate { game } => updates.push((Instant::now(), game.tick)),
+            BroadcastEvent::GameFinished { game, .. } => break game,
+            _ => {}
+        }
+    };
+    ct.cancel();
+    tick_loop.await.unwrap();
+
+    // One update per server tick, each moving both cycles while they ride;
+    // the crash that ends the game moves nobody on
+    assert!(updates.len() > 10, "{} updates", updates.len());
+    for (i, (at, _)) in updates.iter().enumerate() {
+        assert_eq!(*at - started, TICK_INTERVAL * i as u32);
+    }
+    for pair in updates.windows(2) {
+        assert!(pair[1].1 >= pair[0].1 && pair[1].1 <= pair[0].1 + 2, "{:?}", pair);
+    }
+    assert_eq!(finished.status, GameStatus::Finished);
+    assert!(finished.players.iter().any(|p| !p.alive));
+    assert_eq!(finished.tick, updates.last().unwrap().1);
+
+    let mgr = manager.lock().await;
+    assert!(mgr.get_active_games().is_empty());
+    assert_eq!(mgr.get_finished_games().last().unwrap().id, finished.id);
+}